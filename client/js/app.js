@@ -11,11 +11,20 @@ let mapCanvas = null;
 let characterCreator = null;
 let allCharacters = []; // Store all characters for canvas repopulation
 let allAdversaries = []; // Store all adversaries for canvas repopulation
+let preferences = null; // Stored server-side under the session token
+let currentMap = null; // Battle map on the table, if any
+let myCharacterIds = []; // Every character this device plays (parents and GMs may run several)
+let addingCharacter = false; // Picking another character to play alongside these
+const rollsWaiting = new Set(); // Characters of ours not on screen with a roll request
+// Sequence number of the sheet we hold for each character (see character_changed);
+// null right after selecting, when the sheet is current but its number unknown
+const sheetSequences = {};
 
 // LocalStorage keys
 const STORAGE_KEYS = {
     CHARACTER_ID: 'dh_vtt_character_id',
-    SESSION_ACTIVE: 'dh_vtt_session_active'
+    SESSION_ACTIVE: 'dh_vtt_session_active',
+    PLAYER_NAME: 'dh_vtt_player_name'
 };
 
 document.addEventListener('DOMContentLoaded', () => {
@@ -40,8 +49,8 @@ function initDesktopView() {
     // Load QR code
     loadQRCode();
     
-    // Connect to WebSocket
-    ws = new WebSocketClient(handleServerMessage);
+    // Connect to WebSocket; the TV only watches
+    ws = new WebSocketClient(handleServerMessage, { role: 'spectator' });
     window.ws = ws; // Update global reference
     ws.connect();
 }
@@ -118,13 +127,121 @@ function initMobileView() {
         });
     }
     
+    // Preferences
+    ['pref-lite-mode', 'pref-theme', 'pref-notify-rolls', 'pref-vibration'].forEach(id => {
+        document.getElementById(id)?.addEventListener('change', savePreferences);
+    });
+    
+    // Emotes
+    document.querySelectorAll('.emote-btn').forEach(btn => {
+        btn.addEventListener('click', () => sendEmote(btn.dataset.emote));
+    });
+    const emoteInput = document.getElementById('emote-input');
+    const emoteSendBtn = document.getElementById('emote-send-btn');
+    if (emoteInput && emoteSendBtn) {
+        const sendTyped = () => {
+            const text = emoteInput.value.trim();
+            const roll = text.match(/^\/r(?:oll)?\s+(.+)$/i);
+            if (roll) {
+                rollDice(roll[1]);
+                emoteInput.value = '';
+            } else if (text) {
+                sendEmote(text, true);
+                emoteInput.value = '';
+            }
+        };
+        emoteSendBtn.addEventListener('click', sendTyped);
+        emoteInput.addEventListener('keydown', (e) => {
+            if (e.key === 'Enter') sendTyped();
+        });
+    }
+    
+    // Chat
+    setupChat((type, payload) => ws?.send(type, payload));
+
+    // Token color
+    const colorInput = document.getElementById('char-color');
+    if (colorInput) {
+        colorInput.addEventListener('change', () => {
+            if (currentCharacterId && ws) {
+                ws.send('set_character_color', {
+                    character_id: currentCharacterId,
+                    color: colorInput.value
+                });
+            }
+        });
+    }
+    
+    document.getElementById('edit-sheet-btn')?.addEventListener('click', sendSheetEdit);
+
+    // Token facing, elevation and note
+    document.getElementById('turn-left-btn')?.addEventListener('click', () => turnToken(-45));
+    document.getElementById('turn-right-btn')?.addEventListener('click', () => turnToken(45));
+    document.getElementById('token-apply-btn')?.addEventListener('click', () => {
+        const elevation = parseInt(document.getElementById('token-elevation').value, 10);
+        sendTokenDetails({
+            elevation: Number.isNaN(elevation) || elevation === 0 ? null : elevation,
+            note: document.getElementById('token-note').value.trim() || null,
+        });
+    });
+    
     // Mobile tap-to-move
     setupMobileCanvas('mini-canvas');
 }
 
+// Change some of our token's details, keeping the rest
+function sendTokenDetails(changes) {
+    const player = mapCanvas?.players.get(currentCharacterId);
+    if (!player || !ws) return;
+    ws.send('set_token_details', {
+        token_id: currentCharacterId,
+        token: { ...player.token, ...changes },
+    });
+}
+
+function turnToken(degrees) {
+    const rotation = mapCanvas?.players.get(currentCharacterId)?.token?.rotation || 0;
+    sendTokenDetails({ rotation: rotation + degrees });
+}
+
+function sendEmote(text, log = false) {
+    if (currentCharacterId && ws) {
+        ws.send('emote', { text, log });
+    }
+}
+
+// "/roll 2d6 + 3 Loot": dice words first, anything after is the label
+function rollDice(command) {
+    if (!ws) return;
+    const words = command.trim().split(/\s+/);
+    let split = words.findIndex(word => !/^[\d+\-d!khl]+$/i.test(word));
+    if (split === -1) split = words.length;
+    const expression = words.slice(0, split).join('');
+    const label = words.slice(split).join(' ') || null;
+    ws.send('roll_dice', { expression, label, character_id: currentCharacterId });
+}
+
+// Holding a touch this long pings instead of moving
+const LONG_PRESS_MS = 500;
+
 function setupMobileCanvas(canvasId) {
     const canvas = document.getElementById(canvasId);
     if (!canvas) return;
+    let touchStartedAt = 0;
+
+    // Right-click (or long-press) pings the spot for everyone
+    canvas.addEventListener('contextmenu', (e) => {
+        e.preventDefault();
+        if (!ws) return;
+        const rect = canvas.getBoundingClientRect();
+        const x = (e.clientX - rect.left) * (canvas.width / rect.width);
+        const y = (e.clientY - rect.top) * (canvas.height / rect.height);
+        ws.send('ping', { position: { x, y } });
+    });
+
+    canvas.addEventListener('touchstart', () => {
+        touchStartedAt = Date.now();
+    });
     
     canvas.addEventListener('click', (e) => {
         if (currentCharacterId && ws) {
@@ -132,19 +249,24 @@ function setupMobileCanvas(canvasId) {
             const x = (e.clientX - rect.left) * (canvas.width / rect.width);
             const y = (e.clientY - rect.top) * (canvas.height / rect.height);
             console.log('Tap to move:', {x, y});
-            ws.send('move_character', { x, y });
+            ws.send('move_character', { x, y, character_id: currentCharacterId });
         }
     });
     
     canvas.addEventListener('touchend', (e) => {
         e.preventDefault();
-        if (currentCharacterId && ws && e.changedTouches.length > 0) {
+        if (ws && e.changedTouches.length > 0) {
             const touch = e.changedTouches[0];
             const rect = canvas.getBoundingClientRect();
             const x = (touch.clientX - rect.left) * (canvas.width / rect.width);
             const y = (touch.clientY - rect.top) * (canvas.height / rect.height);
+            if (Date.now() - touchStartedAt >= LONG_PRESS_MS) {
+                ws.send('ping', { position: { x, y } });
+                return;
+            }
+            if (!currentCharacterId) return;
             console.log('Touch to move:', {x, y});
-            ws.send('move_character', { x, y });
+            ws.send('move_character', { x, y, character_id: currentCharacterId });
         }
     });
 }
@@ -163,6 +285,10 @@ function autoReconnect(characterId) {
     // After connection, try to select the character
     setTimeout(() => {
         if (ws) {
+            const playerName = localStorage.getItem(STORAGE_KEYS.PLAYER_NAME);
+            if (playerName) {
+                ws.send('set_display_name', { name: playerName });
+            }
             ws.send('select_character', { character_id: characterId });
             
             // Clear auto-reconnect flag after a delay
@@ -194,6 +320,7 @@ function leaveGame() {
     
     // Clear localStorage
     localStorage.removeItem(STORAGE_KEYS.CHARACTER_ID);
+    localStorage.removeItem(STORAGE_KEYS.PLAYER_NAME);
     localStorage.setItem(STORAGE_KEYS.SESSION_ACTIVE, 'false');
     
     // Disconnect WebSocket
@@ -213,6 +340,7 @@ function leaveGame() {
     currentConnectionId = null;
     currentCharacterId = null;
     currentCharacter = null;
+    myCharacterIds = [];
 }
 
 function showPanel(panelId) {
@@ -252,53 +380,97 @@ function showCharacterSelection(characters) {
     
     list.innerHTML = '';
     
-    // Filter for player characters that aren't controlled by others
-    const availableChars = characters.filter(c => !c.is_npc && !c.controlled_by_other);
+    // Player characters; ones played elsewhere can be asked for from the GM
+    const playerChars = characters.filter(c => !c.is_npc && !myCharacterIds.includes(c.id));
+
+    if (addingCharacter) {
+        const back = document.createElement('button');
+        back.className = 'btn-secondary';
+        back.textContent = '← Back to my characters';
+        back.onclick = () => {
+            addingCharacter = false;
+            selectCharacter(currentCharacterId);
+        };
+        list.appendChild(back);
+    }
     
-    if (availableChars.length === 0) {
-        list.innerHTML = '<p>No available characters. Create a new one!</p>';
+    if (playerChars.length === 0) {
+        list.insertAdjacentHTML('beforeend', '<p>No available characters. Create a new one!</p>');
         return;
     }
     
-    availableChars.forEach(char => {
+    playerChars.forEach(char => {
         const card = document.createElement('div');
         card.className = 'character-card';
         card.innerHTML = `
             <h3>${char.name}</h3>
             <p>${char.class} • ${char.ancestry}</p>
-            <button onclick="selectCharacter('${char.id}')">Select</button>
+            ${char.controlled_by_other
+                ? `<button onclick="requestControl('${char.id}', this)" class="btn-secondary">Ask the GM</button>`
+                : `<button onclick="selectCharacter('${char.id}')">Select</button>`}
         `;
         list.appendChild(card);
     });
 }
 
+// Ask the GM for a character that's being played on another device
+function requestControl(characterId, button) {
+    if (ws) {
+        ws.send('request_control', { character_id: characterId });
+        button.disabled = true;
+        button.textContent = 'Asked ✓';
+    }
+}
+
 function selectCharacter(characterId) {
     console.log('Selecting character:', characterId);
     if (ws) {
-        ws.send('select_character', { character_id: characterId });
+        // Switching between our own characters, or adding one, keeps the rest
+        const keepOthers = addingCharacter || myCharacterIds.includes(characterId);
+        ws.send('select_character', { character_id: characterId, keep_others: keepOthers });
     }
 }
 
+// Pick another character to play from this device too
+function addCharacter() {
+    addingCharacter = true;
+    showCharacterSelection(allCharacters);
+}
+
+// Tabs for switching between the characters this device plays
+function renderMyCharacters() {
+    const tabs = document.getElementById('my-characters');
+    if (!tabs) return;
+    tabs.innerHTML = myCharacterIds.map(id => {
+        const char = allCharacters.find(c => c.id === id);
+        const active = id === currentCharacterId ? ' active' : '';
+        const waiting = rollsWaiting.has(id) ? ' 🎲' : '';
+        return `<button class="my-character${active}" onclick="selectCharacter('${id}')">${char ? char.name : '…'}${waiting}</button>`;
+    }).join('') + '<button class="my-character" onclick="addCharacter()" title="Play another character">＋</button>';
+}
+
 function showCharacterSheet(character) {
     showPanel('char-sheet-panel');
     updateCharacterSheet(character);
+    renderMyCharacters();
     
     // Initialize/reinitialize mini canvas for character sheet
     console.log('📊 Initializing character sheet canvas...');
     mapCanvas = new MapCanvas('mini-canvas');
+    mapCanvas.setBackground(currentMap?.url);
     
     // Repopulate canvas with all characters
     console.log(`🎮 Repopulating canvas with ${allCharacters.length} characters:`);
     allCharacters.forEach(char => {
         console.log(`   - ${char.id.substring(0, 8)}: "${char.name}"`);
-        mapCanvas.addPlayer(char.id, char.name, char.position, char.color);
+        mapCanvas.addPlayer(char.id, char.name, char.position, char.color, char.token);
     });
     
     // Repopulate canvas with all adversaries
     console.log(`👹 Repopulating canvas with ${allAdversaries.length} adversaries:`);
     allAdversaries.forEach(adv => {
         console.log(`   - ${adv.id.substring(0, 8)}: "${adv.name}"`);
-        mapCanvas.drawAdversary(adv.id, adv.name, adv.position.x, adv.position.y);
+        mapCanvas.drawAdversary(adv.id, adv.name, adv.position.x, adv.position.y, adv.token);
         if (adv.hp !== undefined && adv.max_hp !== undefined) {
             mapCanvas.updateAdversaryHP(adv.id, adv.hp, adv.max_hp);
         }
@@ -327,6 +499,11 @@ function updateCharacterSheet(character) {
     
     document.getElementById('evasion-value').textContent = character.evasion;
     
+    const listed = allCharacters.find(c => c.id === currentCharacterId);
+    if (listed) {
+        document.getElementById('char-color').value = listed.color;
+    }
+    
     // Update attributes
     const attrs = character.attributes;
     document.getElementById('attr-agility').textContent = formatModifier(attrs.agility);
@@ -335,6 +512,35 @@ function updateCharacterSheet(character) {
     document.getElementById('attr-instinct').textContent = formatModifier(attrs.instinct);
     document.getElementById('attr-presence').textContent = formatModifier(attrs.presence);
     document.getElementById('attr-knowledge').textContent = formatModifier(attrs.knowledge);
+
+    // Leave the correction form alone while it's being filled in
+    const sheetEdit = document.getElementById('sheet-edit');
+    if (sheetEdit && !sheetEdit.open) {
+        document.getElementById('edit-name').value = character.name;
+        document.getElementById('edit-level').value = character.level;
+        document.getElementById('edit-evasion').value = character.evasion;
+        document.querySelectorAll('[data-edit-attr]').forEach(input => {
+            input.value = attrs[input.dataset.editAttr];
+        });
+    }
+}
+
+// Fix typos in the sheet: name, level, Evasion and attributes
+function sendSheetEdit() {
+    if (!ws || !currentCharacterId) return;
+    const number = (id) => parseInt(document.getElementById(id).value, 10);
+    const attributes = ['agility', 'strength', 'finesse', 'instinct', 'presence', 'knowledge']
+        .map(attr => parseInt(document.querySelector(`[data-edit-attr="${attr}"]`).value, 10));
+    ws.send('update_character', {
+        character_id: currentCharacterId,
+        changes: {
+            name: document.getElementById('edit-name').value.trim() || null,
+            level: Number.isNaN(number('edit-level')) ? null : number('edit-level'),
+            evasion: Number.isNaN(number('edit-evasion')) ? null : number('edit-evasion'),
+            attributes: attributes.some(Number.isNaN) ? null : attributes,
+        },
+    });
+    document.getElementById('sheet-edit').open = false;
 }
 
 function formatModifier(value) {
@@ -351,6 +557,7 @@ function rollDuality() {
     ws.send('roll_duality', {
         modifier,
         with_advantage: withAdvantage,
+        character_id: currentCharacterId,
     });
 }
 
@@ -379,15 +586,83 @@ function handleServerMessage(message) {
         case 'character_moved':
             handleCharacterMoved(payload);
             break;
+        case 'character_emoted':
+            if (mapCanvas) {
+                mapCanvas.showEmote(payload.character_id, payload.text, payload.duration_ms);
+            }
+            break;
         case 'character_created':
             handleCharacterCreated(payload);
             break;
         case 'character_updated':
             handleCharacterUpdated(payload);
             break;
+        case 'character_changed':
+            handleCharacterChanged(payload);
+            break;
         case 'roll_result':
             handleRollResult(payload);
             break;
+        case 'dice_rolled':
+            handleDiceRolled(payload);
+            break;
+        case 'chat_message':
+            appendChatMessage(payload.message);
+            break;
+        case 'handout_shared':
+            handleHandoutShared(payload.handout);
+            break;
+        case 'handouts':
+            showHandouts(payload.handouts);
+            break;
+        case 'note_saved':
+            showNote(payload.note);
+            break;
+        case 'note_deleted':
+            removeNote(payload.note_id);
+            break;
+        case 'notes':
+            showNotes(payload.notes);
+            break;
+        case 'quest_updated':
+            showQuest(payload.quest);
+            break;
+        case 'quest_removed':
+            removeQuest(payload.quest_id);
+            break;
+        case 'map_changed':
+            showMap(payload.map);
+            break;
+        case 'scene_layout_changed':
+            mapCanvas?.setLayout(payload.layout);
+            break;
+        case 'fog_changed':
+            showFog(payload.fog);
+            break;
+        case 'token_hidden':
+            hideToken(payload.token_id);
+            break;
+        case 'map_lock_changed':
+            showMapLocked(payload.locked);
+            break;
+        case 'token_details_changed':
+            mapCanvas?.setTokenDetails(payload.token_id, payload.token);
+            break;
+        case 'token_renamed':
+            handleTokenRenamed(payload);
+            break;
+        case 'control_transferred':
+            handleControlTransferred(payload);
+            break;
+        case 'adversary_moved':
+            mapCanvas?.moveAdversary(payload.adversary_id, payload.position);
+            break;
+        case 'pinged':
+            mapCanvas?.showPing(payload.position, payload.name, payload.color);
+            break;
+        case 'drawings_changed':
+            mapCanvas?.setDrawings(payload.drawings);
+            break;
         case 'roll_requested':
             handleRollRequested(payload);
             break;
@@ -397,9 +672,37 @@ function handleServerMessage(message) {
         case 'roll_request_status':
             handleRollRequestStatus(payload);
             break;
+        case 'tag_team_proposed':
+            handleTagTeamProposed(payload);
+            break;
+        case 'tag_team_response':
+            console.log(`🤝 ${payload.partner_name} ${payload.accepted ? 'joined' : 'turned down'} the tag team`);
+            break;
+        case 'tag_team_rolled':
+            handleTagTeamRolled(payload);
+            break;
+        case 'tag_team_resolved':
+            if (!window.location.pathname.includes('mobile')) {
+                addEventToLog({
+                    timestamp: new Date().toLocaleTimeString(),
+                    event_type: 'RollExecuted',
+                    character_name: payload.character_name,
+                    message: `Tag team: ${payload.total} vs ${payload.difficulty} (${payload.success_type.replace(/_/g, ' ')})`,
+                });
+            }
+            break;
         case 'game_event':
             handleGameEvent(payload);
             break;
+        case 'catch_up':
+            handleCatchUp(payload.snapshot);
+            break;
+        case 'cutaway_started':
+            showCutaway(payload.cutaway);
+            break;
+        case 'cutaway_ended':
+            handleCutawayEnded(payload);
+            break;
         case 'adversary_spawned':
             handleAdversarySpawned(payload);
             break;
@@ -409,6 +712,28 @@ function handleServerMessage(message) {
         case 'adversary_updated':
             handleAdversaryUpdated(payload);
             break;
+        case 'character_color_changed':
+            handleCharacterColorChanged(payload);
+            break;
+        case 'display_name_changed':
+            handleDisplayNameChanged(payload);
+            break;
+        case 'preferences_updated':
+            if (payload.connection_id === currentConnectionId) {
+                applyPreferences(payload.preferences);
+            }
+            break;
+        case 'kicked':
+            if (payload.connection_id === currentConnectionId) {
+                alert(payload.banned
+                    ? 'The GM removed you from the game and blocked this device.'
+                    : 'The GM removed you from the game. Reload the page to rejoin.');
+            }
+            break;
+        case 'checkpoint_restored':
+            // The GM rewound the game; start over from the server's state
+            location.reload();
+            break;
         case 'error':
             handleError(payload);
             break;
@@ -419,6 +744,9 @@ function handleServerMessage(message) {
 
 function handleConnected(payload) {
     const { connection_id } = payload;
+    if (payload.preferences) {
+        applyPreferences(payload.preferences);
+    }
     console.log('✅ Connected with ID:', connection_id);
     console.log('📍 Current pathname:', window.location.pathname);
     console.log('📝 pendingPlayerName:', window.pendingPlayerName);
@@ -432,6 +760,9 @@ function handleConnected(payload) {
         const playerName = window.pendingPlayerName;
         delete window.pendingPlayerName;
         
+        ws.send('set_display_name', { name: playerName });
+        localStorage.setItem(STORAGE_KEYS.PLAYER_NAME, playerName);
+        
         console.log('🎨 Showing character creation for:', playerName);
         
         // Show character creation immediately (no separate join panel needed)
@@ -461,12 +792,14 @@ function handleCharactersList(payload) {
     
     // Store characters for later use
     allCharacters = characters;
+    updateChatTargets(characters);
+    setQuestCharacters(characters);
     
     // Clear and re-add all characters to canvas
     if (mapCanvas) {
         mapCanvas.clearPlayers();
         characters.forEach(char => {
-            mapCanvas.addPlayer(char.id, char.name, char.position, char.color);
+            mapCanvas.addPlayer(char.id, char.name, char.position, char.color, char.token);
         });
     }
     
@@ -488,7 +821,7 @@ function handleAdversariesList(payload) {
     // Add all adversaries to canvas (if it exists)
     if (mapCanvas) {
         adversaries.forEach(adv => {
-            mapCanvas.drawAdversary(adv.id, adv.name, adv.position.x, adv.position.y);
+            mapCanvas.drawAdversary(adv.id, adv.name, adv.position.x, adv.position.y, adv.token);
             if (adv.hp !== undefined && adv.max_hp !== undefined) {
                 mapCanvas.updateAdversaryHP(adv.id, adv.hp, adv.max_hp);
             }
@@ -499,11 +832,58 @@ function handleAdversariesList(payload) {
     }
 }
 
+function applyPreferences(prefs) {
+    preferences = prefs;
+    
+    document.body.classList.toggle('lite-mode', prefs.lite_mode);
+    if (prefs.theme) {
+        document.documentElement.dataset.theme = prefs.theme;
+    } else {
+        delete document.documentElement.dataset.theme;
+    }
+    if (prefs.locale) {
+        document.documentElement.lang = prefs.locale;
+    }
+    
+    const liteMode = document.getElementById('pref-lite-mode');
+    if (!liteMode) return;
+    liteMode.checked = prefs.lite_mode;
+    document.getElementById('pref-theme').value = prefs.theme || '';
+    document.getElementById('pref-notify-rolls').checked = prefs.notifications.roll_requests;
+    document.getElementById('pref-vibration').checked = prefs.notifications.vibration;
+}
+
+function savePreferences() {
+    if (!ws || !preferences) return;
+    
+    ws.send('set_preferences', {
+        preferences: {
+            ...preferences,
+            lite_mode: document.getElementById('pref-lite-mode').checked,
+            theme: document.getElementById('pref-theme').value || null,
+            notifications: {
+                ...preferences.notifications,
+                roll_requests: document.getElementById('pref-notify-rolls').checked,
+                vibration: document.getElementById('pref-vibration').checked,
+            },
+        },
+    });
+}
+
 function handleCharacterSelected(payload) {
     const { character_id, character } = payload;
     console.log('✅ Character selected:', character);
     
     currentCharacterId = character_id;
+    if (!addingCharacter && !myCharacterIds.includes(character_id)) {
+        myCharacterIds = [];
+    }
+    if (!myCharacterIds.includes(character_id)) {
+        myCharacterIds.push(character_id);
+    }
+    addingCharacter = false;
+    rollsWaiting.delete(character_id);
+    sheetSequences[character_id] = null;
     
     // Save to localStorage
     localStorage.setItem(STORAGE_KEYS.CHARACTER_ID, character_id);
@@ -511,11 +891,16 @@ function handleCharacterSelected(payload) {
     
     // Show character sheet
     showCharacterSheet(character);
+    requestHandouts();
+    requestNotes();
 }
 
 function handleCharacterSpawned(payload) {
-    const { character_id, name, position, color, is_npc } = payload;
+    const { character_id, name, position, color, is_npc, token } = payload;
     console.log(`Character spawned: ${name} (${character_id}) at (${position.x}, ${position.y})`);
+
+    // Sent again when the fog changes; keep the token we already have
+    if (allCharacters.some(c => c.id === character_id)) return;
     
     // Add to all characters list
     allCharacters.push({
@@ -524,11 +909,12 @@ function handleCharacterSpawned(payload) {
         position,
         color,
         is_npc,
+        token,
     });
     
     // Add to canvas
     if (mapCanvas) {
-        mapCanvas.addPlayer(character_id, name, position, color);
+        mapCanvas.addPlayer(character_id, name, position, color, token);
     }
     
     // Add to characters list if we're on desktop
@@ -543,6 +929,11 @@ function handleCharacterRemoved(payload) {
     
     // Remove from all characters list
     allCharacters = allCharacters.filter(c => c.id !== character_id);
+
+    // The GM deleted or retired one of our characters
+    if (myCharacterIds.includes(character_id)) {
+        letGoOf(character_id);
+    }
     
     // Remove from canvas
     if (mapCanvas) {
@@ -559,7 +950,7 @@ function handleCharacterRemoved(payload) {
 }
 
 function handleCharacterMoved(payload) {
-    const { character_id, position } = payload;
+    const { character_id, position, path, teleported, rotation } = payload;
     console.log(`Character ${character_id} moved to (${position.x}, ${position.y})`);
     
     // Update in allCharacters
@@ -570,7 +961,7 @@ function handleCharacterMoved(payload) {
     
     // Update canvas
     if (mapCanvas) {
-        mapCanvas.updatePlayerPosition(character_id, position);
+        mapCanvas.updatePlayerPosition(character_id, position, path, teleported, rotation);
     }
 }
 
@@ -580,6 +971,7 @@ function handleCharacterCreated(payload) {
     
     // This character is automatically selected for us
     currentCharacterId = character_id;
+    myCharacterIds = [character_id];
     
     // Save to localStorage
     localStorage.setItem(STORAGE_KEYS.CHARACTER_ID, character_id);
@@ -587,18 +979,73 @@ function handleCharacterCreated(payload) {
     
     // Show character sheet
     showCharacterSheet(character);
+    requestHandouts();
+    requestNotes();
 }
 
 function handleCharacterUpdated(payload) {
-    const { character_id, character } = payload;
+    const { character_id, character, sequence } = payload;
     console.log('Character updated:', character);
     
     // If it's our character, update sheet
     if (character_id === currentCharacterId) {
+        sheetSequences[character_id] = sequence;
         updateCharacterSheet(character);
     }
 }
 
+// Only the fields that changed; merge them into the sheet we hold
+function handleCharacterChanged(payload) {
+    const { character_id, sequence, since, changes } = payload;
+    if (character_id !== currentCharacterId || !currentCharacter) return;
+
+    const held = sheetSequences[character_id];
+    if (held !== null && held !== since) {
+        // We missed a change; get the whole sheet again
+        ws.send('request_sheet', { character_id });
+        return;
+    }
+    sheetSequences[character_id] = sequence;
+    updateCharacterSheet({ ...currentCharacter, ...changes });
+}
+
+function handleCharacterColorChanged(payload) {
+    const { character_id, color } = payload;
+    
+    const character = allCharacters.find(c => c.id === character_id);
+    if (character) {
+        character.color = color;
+    }
+    if (mapCanvas) {
+        mapCanvas.updatePlayerColor(character_id, color);
+    }
+    
+    const card = document.getElementById(`character-${character_id}`);
+    if (card) {
+        card.style.borderLeftColor = color;
+        const swatch = card.querySelector('h3 span');
+        if (swatch) swatch.style.background = color;
+    }
+    
+    if (character_id === currentCharacterId) {
+        document.getElementById('char-color').value = color;
+    }
+}
+
+function handleDisplayNameChanged(payload) {
+    const { display_name, character_id } = payload;
+    
+    const character = allCharacters.find(c => c.id === character_id);
+    if (character) {
+        character.player_name = display_name;
+    }
+    
+    const status = document.querySelector(`#character-${character_id} .status`);
+    if (status) {
+        status.textContent = display_name;
+    }
+}
+
 function handleRollResult(payload) {
     const { character_id, character_name, roll } = payload;
     console.log(`${character_name} rolled:`, roll);
@@ -615,19 +1062,48 @@ function handleRollResult(payload) {
     }
 }
 
+function handleDiceRolled(payload) {
+    const { character_id, roller_name, label, roll } = payload;
+    console.log(`${roller_name} rolled ${roll.expression}:`, roll);
+
+    if (!window.location.pathname.includes('mobile')) {
+        showDiceRollOnTV(roller_name, label, roll);
+    } else if (character_id === currentCharacterId && navigator.vibrate && preferences?.notifications?.vibration) {
+        navigator.vibrate(50);
+    }
+}
+
 // Handle GM roll request
 let currentRollRequest = null;
 
 function handleRollRequested(payload) {
     console.log('Roll requested:', payload);
     
-    // Store the current request
-    currentRollRequest = payload;
-    
     // Only show on mobile if this character is targeted
     if (!window.location.pathname.includes('mobile')) {
         return;
     }
+
+    // For another of our characters: flag its tab until it's picked
+    if (payload.character_id !== currentCharacterId && myCharacterIds.includes(payload.character_id)) {
+        rollsWaiting.add(payload.character_id);
+        renderMyCharacters();
+        return;
+    }
+
+    // Someone else's roll: offer to help instead
+    if (payload.character_id !== currentCharacterId) {
+        offerHelp(payload);
+        return;
+    }
+
+    // Store the current request
+    currentRollRequest = payload;
+    
+    const notifications = preferences?.notifications;
+    if (notifications?.roll_requests && notifications.vibration && navigator.vibrate) {
+        navigator.vibrate(200);
+    }
     
     const rollPanel = document.getElementById('roll-request-panel');
     const rollBtn = document.getElementById('roll-btn');
@@ -635,28 +1111,58 @@ function handleRollRequested(payload) {
     if (!rollPanel) return;
     
     // Populate the panel
-    document.getElementById('roll-context').textContent = payload.context || 'Roll requested';
+    const isReaction = payload.roll_type === 'reaction' || payload.roll_type === 'save';
+    const context = payload.context || 'Roll requested';
+    document.getElementById('roll-context').textContent = isReaction ? `${context} (reaction)` : context;
     
     const attrName = payload.attribute ? payload.attribute.charAt(0).toUpperCase() + payload.attribute.slice(1) : 'None';
     document.getElementById('roll-attribute').textContent = attrName;
     document.getElementById('roll-difficulty').textContent = payload.difficulty;
     document.getElementById('roll-modifier').textContent = `+${payload.total_modifier}`;
     
-    // Show/hide advantage
+    // Show/hide advantage and disadvantage (having both cancels out)
     const advantageInfo = document.getElementById('advantage-info');
-    if (payload.has_advantage) {
-        advantageInfo.style.display = 'flex';
-    } else {
-        advantageInfo.style.display = 'none';
-    }
+    const disadvantageInfo = document.getElementById('disadvantage-info');
+    const hasDisadvantage = payload.has_disadvantage || false;
+    advantageInfo.style.display = payload.has_advantage && !hasDisadvantage ? 'flex' : 'none';
+    disadvantageInfo.style.display = hasDisadvantage && !payload.has_advantage ? 'flex' : 'none';
+    document.getElementById('hidden-roll-info').style.display = payload.hidden ? 'flex' : 'none';
     
     // Show/hide Hope spending option
     const hopeLabel = document.getElementById('spend-hope-label');
     if (payload.can_spend_hope) {
         hopeLabel.style.display = 'flex';
+        const experienceSelect = document.getElementById('experience-select');
+        experienceSelect.innerHTML = '';
+        payload.experiences.forEach(exp => {
+            const option = document.createElement('option');
+            option.value = exp.name;
+            option.textContent = `${exp.name} (${exp.modifier >= 0 ? '+' : ''}${exp.modifier})`;
+            experienceSelect.appendChild(option);
+        });
     } else {
         hopeLabel.style.display = 'none';
     }
+
+    // Offer a tag team on action rolls when there's Hope to pay for it
+    const tagTeamOption = document.getElementById('tag-team-option');
+    const partners = allCharacters.filter(c => !c.is_npc && c.id !== currentCharacterId);
+    const hope = currentCharacter?.hope?.current || 0;
+    if (tagTeamOption && !isReaction && hope >= 3 && partners.length > 0) {
+        tagTeamOption.style.display = 'flex';
+        document.getElementById('tag-team-partner').innerHTML = partners
+            .map(c => `<option value="${c.id}">${c.name}</option>`)
+            .join('');
+        document.getElementById('tag-team-btn').onclick = () => {
+            ws.send('propose_tag_team', {
+                request_id: payload.request_id,
+                partner_id: document.getElementById('tag-team-partner').value,
+            });
+            tagTeamOption.style.display = 'none';
+        };
+    } else if (tagTeamOption) {
+        tagTeamOption.style.display = 'none';
+    }
     
     // Show panel, hide normal roll button
     rollPanel.style.display = 'block';
@@ -677,13 +1183,16 @@ function executeRollRequest() {
     
     const spendHopeCheckbox = document.getElementById('spend-hope-checkbox');
     const spendHope = spendHopeCheckbox ? spendHopeCheckbox.checked : false;
+    const experienceSelect = document.getElementById('experience-select');
+    const chosenExperience = spendHope && experienceSelect ? experienceSelect.value : null;
     
     console.log('Executing roll request:', currentRollRequest.request_id, 'spend hope:', spendHope);
     
     ws.send('execute_roll', {
         request_id: currentRollRequest.request_id,
         spend_hope_for_bonus: spendHope,
-        chosen_experience: null, // TODO: Add experience selection UI
+        chosen_experience: chosenExperience,
+        character_id: currentRollRequest.character_id,
     });
     
     // Hide the panel
@@ -706,13 +1215,63 @@ function handleDetailedRollResult(payload) {
         showDetailedRollResultOnTV(payload);
     }
     
+    // Too late to help once the roll is made
+    const helpBar = document.getElementById('help-ally-bar');
+    if (helpBar) helpBar.style.display = 'none';
+
     // If this is our roll, show feedback on mobile
     if (payload.character_id === currentCharacterId) {
         // Could show toast notification or result panel
-        console.log(`Your roll: ${payload.outcome_description}`);
+        console.log(`Your roll: ${outcomeLabel(payload.roll_details.success_type, isReactionRoll(payload.roll_type))}`);
     }
 }
 
+// Help an Ally: spend a Hope to add a d6 to someone else's roll
+function offerHelp(request) {
+    const bar = document.getElementById('help-ally-bar');
+    const target = allCharacters.find(c => c.id === request.character_id);
+    if (!bar || !target || !currentCharacterId || (currentCharacter?.hope?.current || 0) < 1) return;
+
+    document.getElementById('help-ally-text').textContent = `${target.name}: ${request.context}`;
+    document.getElementById('help-ally-btn').onclick = () => {
+        ws.send('help_ally', {
+            request_id: request.request_id,
+            character_id: request.character_id,
+        });
+        bar.style.display = 'none';
+    };
+    bar.style.display = 'flex';
+}
+
+// Tag team rolls: the partner accepts, both roll, the initiator picks
+let currentTagTeam = null;
+
+function handleTagTeamProposed(payload) {
+    currentTagTeam = payload;
+    if (payload.partner_id !== currentCharacterId) return;
+
+    const accept = confirm(
+        `${payload.initiator_name} wants to tag team on "${payload.context}". Spend 3 Hope to join?`
+    );
+    ws.send('respond_tag_team', { request_id: payload.request_id, accept });
+}
+
+function handleTagTeamRolled(payload) {
+    if (!currentTagTeam || currentTagTeam.request_id !== payload.request_id) return;
+    if (currentTagTeam.initiator_id !== currentCharacterId) return;
+
+    const [first, second] = payload.results;
+    const describe = r => `${r.character_name}: ${r.total} (${r.success_type.replace(/_/g, ' ')})`;
+    const useFirst = confirm(
+        `Which roll applies?\nOK: ${describe(first)}\nCancel: ${describe(second)}`
+    );
+    ws.send('choose_tag_team_result', {
+        request_id: payload.request_id,
+        character_id: (useFirst ? first : second).character_id,
+    });
+    currentTagTeam = null;
+}
+
 function handleRollRequestStatus(payload) {
     console.log('Roll request status:', payload);
     
@@ -723,15 +1282,16 @@ function handleRollRequestStatus(payload) {
 }
 
 function handleError(payload) {
-    const { message } = payload;
-    console.error('Server error:', message);
+    const { code, message } = payload;
+    console.error('Server error:', code, message);
+
+    if (code === 'game_replaced') {
+        alert(message);
+        return;
+    }
     
     // If this is an auto-reconnect failure, handle it gracefully
-    if (window.isAutoReconnecting && (
-        message.includes('Character not found') || 
-        message.includes('Connection not found') ||
-        message.includes('select character')
-    )) {
+    if (window.isAutoReconnecting && payload.request_type === 'select_character') {
         console.warn('Auto-reconnect failed:', message, '- Clearing saved session.');
         
         // Clear saved character
@@ -769,24 +1329,16 @@ function showRollResultOnTV(characterName, roll) {
     // Update controlling die badge
     const controllingBadge = document.getElementById('controlling-die');
     controllingBadge.className = 'controlling-badge';
-    if (roll.controlling_die === 'Hope') {
-        controllingBadge.classList.add('hope');
-        controllingBadge.textContent = 'With Hope';
-    } else if (roll.controlling_die === 'Fear') {
-        controllingBadge.classList.add('fear');
-        controllingBadge.textContent = 'With Fear';
-    } else {
-        controllingBadge.classList.add('tied');
-        controllingBadge.textContent = 'Tied';
-    }
+    controllingBadge.classList.add(roll.controlling_die);
+    controllingBadge.textContent = t(`controlling_die.${roll.controlling_die}`);
     
     // Update success badge
     const successBadge = document.getElementById('success-badge');
     if (roll.is_success) {
-        successBadge.textContent = 'SUCCESS';
+        successBadge.textContent = t('success_type.success');
         successBadge.className = 'success-badge success';
     } else {
-        successBadge.textContent = 'FAILURE';
+        successBadge.textContent = t('success_type.failure');
         successBadge.className = 'success-badge failure';
     }
     
@@ -807,7 +1359,35 @@ function showRollResultOnTV(characterName, roll) {
     }, 5000);
 }
 
-function addCharacterToList(characterId, name, color, isNpc) {
+function showDiceRollOnTV(rollerName, label, roll) {
+    const overlay = document.getElementById('dice-roll-overlay');
+    if (!overlay) return;
+
+    document.getElementById('dice-roll-player').textContent = rollerName;
+    document.getElementById('dice-roll-expression').textContent =
+        label ? `${label}: ${roll.expression}` : roll.expression;
+    document.getElementById('dice-roll-total').textContent = roll.total;
+
+    // One chip per die; dropped dice (kh/kl) are struck through
+    const diceEl = document.getElementById('dice-roll-dice');
+    diceEl.innerHTML = '';
+    roll.dice.forEach((die, i) => {
+        const chip = document.createElement('span');
+        chip.className = `damage-die${die.kept ? '' : ' dropped'}`;
+        chip.title = `d${die.sides}`;
+        chip.textContent = `${die.negative ? '−' : ''}${die.value}${die.explosions ? '💥' : ''}`;
+        chip.style.animationDelay = `${i * 80}ms`;
+        diceEl.appendChild(chip);
+    });
+
+    overlay.style.display = 'flex';
+    clearTimeout(showDiceRollOnTV.timer);
+    showDiceRollOnTV.timer = setTimeout(() => {
+        overlay.style.display = 'none';
+    }, 5000);
+}
+
+function addCharacterToList(characterId, name, color, isNpc, playerName = null) {
     const playersList = document.getElementById('players-list');
     if (!playersList) return;
     
@@ -824,7 +1404,7 @@ function addCharacterToList(characterId, name, color, isNpc) {
     card.style.borderLeftColor = color;
     card.innerHTML = `
         <h3><span style="display:inline-block;width:12px;height:12px;border-radius:50%;background:${color};margin-right:8px;"></span>${name}</h3>
-        <p class="status">${isNpc ? 'NPC' : 'Player'}</p>
+        <p class="status">${isNpc ? 'NPC' : (playerName || 'Player')}</p>
     `;
     
     playersList.appendChild(card);
@@ -844,7 +1424,7 @@ function updateCharactersList(characters) {
     
     // Add each character
     characters.forEach(char => {
-        addCharacterToList(char.id, char.name, char.color, char.is_npc);
+        addCharacterToList(char.id, char.name, char.color, char.is_npc, char.player_name);
     });
 }
 
@@ -885,35 +1465,34 @@ function showDetailedRollResultOnTV(result) {
     document.getElementById('roll-player').textContent = result.character_name;
     document.getElementById('hope-value').textContent = roll.hope_die;
     document.getElementById('fear-value').textContent = roll.fear_die;
-    document.getElementById('total-value').textContent = roll.total;
+    const extras = [
+        roll.advantage_die ? `adv +${roll.advantage_die}` : null,
+        roll.disadvantage_die ? `disadv −${roll.disadvantage_die}` : null,
+        roll.help_bonus ? `help +${roll.help_bonus}` : null,
+    ].filter(Boolean);
+    document.getElementById('total-value').textContent = extras.length
+        ? `${roll.total} (${extras.join(', ')})`
+        : roll.total;
     
     // Update controlling die badge
     const controllingBadge = document.getElementById('controlling-die');
     controllingBadge.className = 'controlling-badge';
     
-    if (roll.controlling_die === 'hope') {
-        controllingBadge.classList.add('hope');
-        controllingBadge.textContent = 'With Hope';
-    } else if (roll.controlling_die === 'fear') {
-        controllingBadge.classList.add('fear');
-        controllingBadge.textContent = 'With Fear';
-    } else {
-        controllingBadge.classList.add('tied');
-        controllingBadge.textContent = 'Tied';
-    }
+    controllingBadge.classList.add(roll.controlling_die);
+    controllingBadge.textContent = t(`controlling_die.${roll.controlling_die}`);
     
     // Update success badge
     const successBadge = document.getElementById('success-badge');
     const successType = roll.success_type;
     
     if (successType === 'critical_success') {
-        successBadge.textContent = 'CRITICAL SUCCESS!';
+        successBadge.textContent = `${t('success_type.critical_success')}!`;
         successBadge.className = 'success-badge success critical';
     } else if (successType === 'success_with_hope' || successType === 'success_with_fear') {
-        successBadge.textContent = 'SUCCESS';
+        successBadge.textContent = t('success_type.success');
         successBadge.className = 'success-badge success';
     } else {
-        successBadge.textContent = 'FAILURE';
+        successBadge.textContent = t('success_type.failure');
         successBadge.className = 'success-badge failure';
     }
     
@@ -948,6 +1527,95 @@ function handleGameEvent(payload) {
     addEventToLog(payload);
 }
 
+// Fill in what happened before we joined
+function handleCatchUp(snapshot) {
+    showMap(snapshot.map);
+    if (snapshot.layout) mapCanvas?.setLayout(snapshot.layout);
+    if (snapshot.fog) showFog(snapshot.fog);
+    mapCanvas?.setDrawings(snapshot.drawings);
+    showMapLocked(snapshot.map_locked);
+    showQuests(snapshot.quests);
+
+    const eventLog = document.getElementById('event-log');
+    if (!eventLog) return;
+
+    eventLog.querySelectorAll('.event-item').forEach(item => item.remove());
+    snapshot.events.forEach(event => addEventToLog(event));
+    showCutaway(snapshot.cutaway);
+}
+
+// The GM can lock the map; moves are refused until it's unlocked
+function showMapLocked(locked) {
+    const instructions = document.querySelector('.mini-map-container .instructions');
+    if (!instructions) return;
+    instructions.textContent = locked
+        ? '🔒 The GM has locked the map'
+        : 'Tap map to move your token, hold to ping';
+}
+
+function showMap(map) {
+    currentMap = map;
+    mapCanvas?.setBackground(map?.url);
+}
+
+// Drop NPCs and adversaries the fog now covers; the server sends back
+// whatever a reveal uncovered
+function showFog(fog) {
+    if (!mapCanvas) return;
+    mapCanvas.setFog(fog);
+    allCharacters
+        .filter(c => c.is_npc && !mapCanvas.isRevealed(c.position.x, c.position.y))
+        .forEach(c => hideToken(c.id));
+    allAdversaries
+        .filter(a => !mapCanvas.isRevealed(a.position.x, a.position.y))
+        .forEach(a => hideToken(a.id));
+}
+
+function hideToken(tokenId) {
+    allCharacters = allCharacters.filter(c => c.id !== tokenId);
+    allAdversaries = allAdversaries.filter(a => a.id !== tokenId);
+    mapCanvas?.removePlayer(tokenId);
+    mapCanvas?.removeAdversary(tokenId);
+}
+
+function inCutaway(cutaway) {
+    return cutaway.character_ids.includes(currentCharacterId);
+}
+
+// Those left behind by a cutaway see a "meanwhile…" placeholder
+function showCutaway(cutaway) {
+    const banner = document.getElementById('cutaway-banner');
+    if (!banner) return;
+    if (!cutaway) {
+        banner.style.display = 'none';
+        return;
+    }
+
+    const scene = cutaway.label ? ` (${cutaway.label})` : '';
+    banner.textContent = inCutaway(cutaway)
+        ? `🎬 Cutaway${scene}: only you and the GM see this scene`
+        : `🎬 Meanwhile… ${cutaway.character_names.join(', ')} are elsewhere${scene}`;
+    banner.style.display = 'block';
+}
+
+// Catch up on the scene we missed
+function handleCutawayEnded(payload) {
+    showCutaway(null);
+    if (!inCutaway(payload.cutaway)) {
+        payload.events.forEach(event => addEventToLog(event));
+    }
+}
+
+// Open a new handout where the player will see it
+function handleHandoutShared(handout) {
+    appendHandout(handout, true);
+    const panel = document.querySelector('.handouts-panel');
+    if (panel) panel.open = true;
+    if (navigator.vibrate && preferences?.notifications?.vibration) {
+        navigator.vibrate(50);
+    }
+}
+
 // Adversary Handlers
 function handleAdversarySpawned(payload) {
     const { adversary_id, name, position, template, hp, max_hp, evasion, armor, attack_modifier, damage_dice } = payload;
@@ -970,7 +1638,7 @@ function handleAdversarySpawned(payload) {
     });
     
     if (mapCanvas) {
-        mapCanvas.drawAdversary(adversary_id, name, position.x, position.y);
+        mapCanvas.drawAdversary(adversary_id, name, position.x, position.y, payload.token);
         if (hp !== undefined && max_hp !== undefined) {
             mapCanvas.updateAdversaryHP(adversary_id, hp, max_hp);
         }
@@ -989,6 +1657,54 @@ function handleAdversaryRemoved(payload) {
     }
 }
 
+function handleTokenRenamed(payload) {
+    const { token_id, name } = payload;
+    mapCanvas?.renameToken(token_id, name);
+    const adv = allAdversaries.find(a => a.id === token_id);
+    if (adv) adv.name = name;
+    if (token_id === currentCharacterId && currentCharacter) {
+        currentCharacter.name = name;
+        document.getElementById('char-name').textContent = name;
+    }
+}
+
+function handleControlTransferred(payload) {
+    const { character_id, from_connection_id, to_connection_id } = payload;
+    const char = allCharacters.find(c => c.id === character_id);
+    if (char) {
+        char.controlled_by_me = to_connection_id === currentConnectionId;
+        char.controlled_by_other = !!to_connection_id && !char.controlled_by_me;
+    }
+
+    if (to_connection_id === currentConnectionId && currentCharacterId) {
+        // Played alongside what we have; its sheet follows
+        addingCharacter = true;
+    } else if (from_connection_id === currentConnectionId) {
+        // The GM handed one of our characters to someone else (or freed it)
+        letGoOf(character_id);
+    } else if (!currentCharacterId) {
+        showCharacterSelection(allCharacters);
+    }
+}
+
+// We no longer play a character: show another of ours, or pick again
+function letGoOf(characterId) {
+    myCharacterIds = myCharacterIds.filter(id => id !== characterId);
+    rollsWaiting.delete(characterId);
+    if (characterId !== currentCharacterId) {
+        renderMyCharacters();
+        return;
+    }
+    currentCharacterId = null;
+    currentCharacter = null;
+    const next = myCharacterIds[myCharacterIds.length - 1];
+    if (next) {
+        selectCharacter(next);
+    } else {
+        showCharacterSelection(allCharacters);
+    }
+}
+
 function handleAdversaryUpdated(payload) {
     const { adversary_id, hp, stress } = payload;
     console.log(`🩸 Adversary ${adversary_id} updated: HP ${hp}, Stress ${stress}`);
@@ -1024,7 +1740,7 @@ function addEventToLog(event) {
     let html = `
         <div>
             <span class="event-timestamp">${event.timestamp}</span>
-            ${event.character_name ? `<span class="event-character">${event.character_name}:</span>` : ''}
+            ${event.character_name ? `<span class="event-character">${event.character_name}${event.player_name ? ` (${event.player_name})` : ''}:</span>` : ''}
             <span class="event-message">${event.message}</span>
         </div>
     `;
@@ -1050,7 +1766,7 @@ function addEventToLog(event) {
 // Load event history on connection
 async function loadEventHistory() {
     try {
-        const response = await fetch('/api/events');
+        const response = await fetch('/api/events?limit=30');
         const data = await response.json();
         
         const eventLog = document.getElementById('event-log');
@@ -1060,9 +1776,7 @@ async function loadEventHistory() {
         eventLog.innerHTML = '';
         
         if (data.events && data.events.length > 0) {
-            // Show last 30 events
-            const recentEvents = data.events.slice(-30);
-            recentEvents.forEach(event => addEventToLog(event));
+            data.events.forEach(event => addEventToLog(event));
         } else {
             eventLog.innerHTML = '<p class="empty-state">No events yet...</p>';
         }
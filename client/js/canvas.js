@@ -4,6 +4,13 @@
 const MAP_WIDTH = 800;
 const MAP_HEIGHT = 600;
 const PLAYER_RADIUS = 20;
+const PING_DURATION_MS = 1500;
+const TOKEN_CELLS = { normal: 1, large: 2, huge: 3 };
+
+// Large and huge tokens are drawn two and three times as wide
+function tokenRadius(token) {
+    return PLAYER_RADIUS * (TOKEN_CELLS[token?.size] || 1);
+}
 
 class MapCanvas {
     constructor(canvasId) {
@@ -19,26 +26,43 @@ class MapCanvas {
         this.ctx = this.canvas.getContext('2d');
         this.players = new Map(); // character_id -> character data (keeping "players" var name for compatibility)
         this.animating = new Map(); // character_id -> animation state
+        this.emotes = new Map(); // character_id -> { text, expiresAt }
         this.adversaryPositions = new Map(); // Initialize adversaries map in constructor
         this.selectedAttackerId = null; // Track selected attacker for combat
+        this.background = null; // Loaded battle map image, if any
+        this.grid = null; // Grid tokens snap to, if the GM set one
+        this.fog = null; // Fog of war, when the GM has it on
+        this.fogOpacity = 0.95; // The GM sees through it
+        this.drawings = []; // The GM's drawings on this scene
+        this.draft = null; // A drawing the GM is in the middle of
+        this.pings = []; // { x, y, name, color, startTime }
         
         // Set canvas size
-        this.canvas.width = MAP_WIDTH;
-        this.canvas.height = MAP_HEIGHT;
+        this.setLayout({ width: MAP_WIDTH, height: MAP_HEIGHT, grid: null });
         
         // Start render loop
         this.startRenderLoop();
         
-        console.log('MapCanvas initialized:', MAP_WIDTH, 'x', MAP_HEIGHT);
+        console.log('MapCanvas initialized:', this.width, 'x', this.height);
+    }
+
+    // Resize to the scene and draw its grid (from the server's SceneLayout)
+    setLayout(layout) {
+        this.width = layout.width;
+        this.height = layout.height;
+        this.grid = layout.grid || null;
+        this.canvas.width = this.width;
+        this.canvas.height = this.height;
     }
     
-    addPlayer(playerId, name, position, color) {
+    addPlayer(playerId, name, position, color, token = {}) {
         this.players.set(playerId, {
             id: playerId,
             name,
             position: { ...position },
             targetPosition: { ...position },
-            color
+            color,
+            token
         });
         console.log('Added player to canvas:', name, position);
     }
@@ -58,23 +82,103 @@ class MapCanvas {
         }
     }
     
-    updatePlayerPosition(playerId, newPosition) {
+    updatePlayerColor(playerId, color) {
+        const player = this.players.get(playerId);
+        if (player) {
+            player.color = color;
+        }
+    }
+    
+    // The GM renamed a character's or adversary's token
+    renameToken(tokenId, name) {
+        const owner = this.players.get(tokenId) || this.adversaryPositions.get(tokenId);
+        if (owner) owner.name = name;
+    }
+
+    // Turn, resize or re-note a character's or adversary's token (from the
+    // server's TokenDetails)
+    setTokenDetails(tokenId, token) {
+        const owner = this.players.get(tokenId) || this.adversaryPositions.get(tokenId);
+        if (owner) owner.token = token;
+    }
+
+    updatePlayerPosition(playerId, newPosition, path = [], teleported = false, rotation = null) {
         const player = this.players.get(playerId);
         if (player) {
             player.targetPosition = { ...newPosition };
+            if (rotation !== null) player.token = { ...player.token, rotation };
+
+            if (teleported) {
+                player.position = { ...newPosition };
+                this.animating.delete(playerId);
+                return;
+            }
+
+            // Walk the server's route (start, waypoints, destination) when given
+            const route = path.length >= 2
+                ? path.map(p => ({ ...p }))
+                : [{ ...player.position }, { ...newPosition }];
             
             // Start animation
             this.animating.set(playerId, {
                 startTime: Date.now(),
-                duration: 500, // ms
-                startPos: { ...player.position }
+                duration: 500 * Math.max(route.length - 1, 1), // ms
+                startPos: { ...player.position },
+                route
             });
         }
     }
+
+    // Point a fraction `t` (0-1) of the way along a polyline
+    pointAlong(route, t) {
+        const lengths = [];
+        let total = 0;
+        for (let i = 1; i < route.length; i++) {
+            const length = Math.hypot(route[i].x - route[i - 1].x, route[i].y - route[i - 1].y);
+            lengths.push(length);
+            total += length;
+        }
+        if (total === 0) return { ...route[route.length - 1] };
+
+        let remaining = t * total;
+        for (let i = 0; i < lengths.length; i++) {
+            if (remaining <= lengths[i] || i === lengths.length - 1) {
+                const f = lengths[i] === 0 ? 1 : Math.min(remaining / lengths[i], 1);
+                return {
+                    x: route[i].x + (route[i + 1].x - route[i].x) * f,
+                    y: route[i].y + (route[i + 1].y - route[i].y) * f
+                };
+            }
+            remaining -= lengths[i];
+        }
+        return { ...route[route.length - 1] };
+    }
     
+    // Show an uploaded battle map behind the grid (null clears it)
+    setBackground(url) {
+        if (!url) {
+            this.background = null;
+            return;
+        }
+        const image = new Image();
+        image.onload = () => { this.background = image; };
+        image.onerror = () => console.error('Failed to load map:', url);
+        image.src = url;
+    }
+
+    // Scale the map to fit, centred
+    drawBackground() {
+        const image = this.background;
+        const scale = Math.min(this.width / image.naturalWidth, this.height / image.naturalHeight);
+        const width = image.naturalWidth * scale;
+        const height = image.naturalHeight * scale;
+        this.ctx.drawImage(image, (this.width - width) / 2, (this.height - height) / 2, width, height);
+    }
+
     clearPlayers() {
         this.players.clear();
         this.animating.clear();
+        this.emotes.clear();
     }
     
     startRenderLoop() {
@@ -90,10 +194,18 @@ class MapCanvas {
         
         // Clear canvas
         this.ctx.fillStyle = '#1a1a1a';
-        this.ctx.fillRect(0, 0, MAP_WIDTH, MAP_HEIGHT);
+        this.ctx.fillRect(0, 0, this.width, this.height);
+        if (this.background) this.drawBackground();
         
         // Draw grid (subtle)
         this.drawGrid();
+
+        // Drawings sit on the map, so the fog hides those in unrevealed areas
+        this.drawings.forEach(drawing => this.drawDrawing(drawing));
+        if (this.draft) this.drawDrawing(this.draft);
+
+        // Fog covers the map but not the tokens players are allowed to see
+        if (this.fog?.enabled) this.drawFog();
         
         // Update animations and render players
         const now = Date.now();
@@ -107,9 +219,10 @@ class MapCanvas {
                 // Ease out cubic
                 const eased = 1 - Math.pow(1 - progress, 3);
                 
-                // Interpolate position
-                player.position.x = anim.startPos.x + (player.targetPosition.x - anim.startPos.x) * eased;
-                player.position.y = anim.startPos.y + (player.targetPosition.y - anim.startPos.y) * eased;
+                // Interpolate position along the route
+                const point = this.pointAlong(anim.route, eased);
+                player.position.x = point.x;
+                player.position.y = point.y;
                 
                 // Remove animation when done
                 if (progress >= 1.0) {
@@ -122,32 +235,236 @@ class MapCanvas {
         
         // Draw adversaries
         this.renderAdversaries();
+
+        // Speech bubbles and pings go on top of everything
+        this.renderEmotes(now);
+        this.renderPings(now);
+    }
+
+    // Show the scene's drawings (from the server's Drawing list)
+    setDrawings(drawings) {
+        this.drawings = drawings || [];
+    }
+
+    // Preview a drawing before it's sent; null clears it
+    setDraft(drawing) {
+        this.draft = drawing;
+    }
+
+    drawDrawing(drawing) {
+        const shape = drawing.shape;
+        const ctx = this.ctx;
+        ctx.strokeStyle = drawing.color;
+        ctx.fillStyle = drawing.color;
+        ctx.lineWidth = 3;
+        ctx.beginPath();
+
+        let labelAt;
+        if (shape.kind === 'circle') {
+            ctx.arc(shape.center.x, shape.center.y, shape.radius, 0, Math.PI * 2);
+            labelAt = shape.center;
+        } else {
+            shape.points.forEach((p, i) => i === 0 ? ctx.moveTo(p.x, p.y) : ctx.lineTo(p.x, p.y));
+            if (shape.kind === 'zone') ctx.closePath();
+            labelAt = shape.points[0];
+        }
+
+        // Circles and zones are shaded areas; lines are just lines
+        if (shape.kind !== 'line') {
+            ctx.globalAlpha = 0.25;
+            ctx.fill();
+            ctx.globalAlpha = 1;
+        }
+        ctx.stroke();
+
+        if (drawing.label && labelAt) {
+            ctx.font = 'bold 14px sans-serif';
+            ctx.textAlign = 'center';
+            ctx.textBaseline = 'middle';
+            ctx.lineWidth = 3;
+            ctx.strokeStyle = '#1a1a1a';
+            ctx.strokeText(drawing.label, labelAt.x, labelAt.y);
+            ctx.fillText(drawing.label, labelAt.x, labelAt.y);
+        }
+    }
+
+    showPing(position, name, color) {
+        this.pings.push({ x: position.x, y: position.y, name, color, startTime: Date.now() });
+    }
+
+    // Two expanding rings and the pinger's name, fading out
+    renderPings(now) {
+        this.pings = this.pings.filter(ping => now - ping.startTime < PING_DURATION_MS);
+        this.pings.forEach(ping => {
+            const progress = (now - ping.startTime) / PING_DURATION_MS;
+            this.ctx.strokeStyle = ping.color;
+            this.ctx.lineWidth = 3;
+            [0, 0.3].forEach(delay => {
+                const t = progress - delay;
+                if (t < 0) return;
+                this.ctx.globalAlpha = 1 - t;
+                this.ctx.beginPath();
+                this.ctx.arc(ping.x, ping.y, 10 + t * 50, 0, Math.PI * 2);
+                this.ctx.stroke();
+            });
+
+            this.ctx.globalAlpha = 1 - progress;
+            this.ctx.fillStyle = ping.color;
+            this.ctx.font = 'bold 13px sans-serif';
+            this.ctx.textAlign = 'center';
+            this.ctx.textBaseline = 'bottom';
+            this.ctx.fillText(ping.name, ping.x, ping.y - 14);
+            this.ctx.globalAlpha = 1;
+        });
+    }
+
+    showEmote(playerId, text, durationMs) {
+        this.emotes.set(playerId, { text, expiresAt: Date.now() + durationMs });
+    }
+
+    renderEmotes(now) {
+        for (const [playerId, emote] of this.emotes) {
+            const player = this.players.get(playerId);
+            if (!player || now >= emote.expiresAt) {
+                this.emotes.delete(playerId);
+                continue;
+            }
+
+            this.ctx.font = '16px sans-serif';
+            const width = this.ctx.measureText(emote.text).width + 16;
+            const height = 26;
+            const x = player.position.x - width / 2;
+            const y = player.position.y - PLAYER_RADIUS - 30 - height;
+
+            // Fade out over the last half second
+            this.ctx.globalAlpha = Math.min((emote.expiresAt - now) / 500, 1);
+
+            this.ctx.fillStyle = '#ffffff';
+            this.ctx.beginPath();
+            this.ctx.roundRect(x, y, width, height, 8);
+            this.ctx.moveTo(player.position.x - 6, y + height);
+            this.ctx.lineTo(player.position.x, y + height + 8);
+            this.ctx.lineTo(player.position.x + 6, y + height);
+            this.ctx.fill();
+
+            this.ctx.fillStyle = '#1a1a1a';
+            this.ctx.textAlign = 'center';
+            this.ctx.textBaseline = 'middle';
+            this.ctx.fillText(emote.text, player.position.x, y + height / 2);
+
+            this.ctx.globalAlpha = 1;
+        }
     }
     
     drawGrid() {
-        const gridSize = 50;
-        this.ctx.strokeStyle = '#2a2a2a';
+        if (this.grid?.kind === 'hex') {
+            this.drawHexGrid(this.grid);
+            return;
+        }
+
+        // Without a grid the lines are just a guide, 50 units apart
+        const gridSize = this.grid ? this.grid.cell_size : 50;
+        const offsetX = this.grid ? this.grid.offset_x % gridSize : 0;
+        const offsetY = this.grid ? this.grid.offset_y % gridSize : 0;
+        this.ctx.strokeStyle = this.grid ? '#444' : '#2a2a2a';
         this.ctx.lineWidth = 1;
         
         // Vertical lines
-        for (let x = 0; x <= MAP_WIDTH; x += gridSize) {
+        for (let x = offsetX; x <= this.width; x += gridSize) {
             this.ctx.beginPath();
             this.ctx.moveTo(x, 0);
-            this.ctx.lineTo(x, MAP_HEIGHT);
+            this.ctx.lineTo(x, this.height);
             this.ctx.stroke();
         }
         
         // Horizontal lines
-        for (let y = 0; y <= MAP_HEIGHT; y += gridSize) {
+        for (let y = offsetY; y <= this.height; y += gridSize) {
             this.ctx.beginPath();
             this.ctx.moveTo(0, y);
-            this.ctx.lineTo(MAP_WIDTH, y);
+            this.ctx.lineTo(this.width, y);
             this.ctx.stroke();
         }
     }
+
+    // Show fog of war (from the server's FogState); `opacity` is lower for the GM
+    setFog(fog, opacity = 0.95) {
+        this.fog = fog;
+        this.fogOpacity = opacity;
+    }
+
+    // Whether players can see a point
+    isRevealed(x, y) {
+        if (!this.fog?.enabled) return true;
+        return this.fog.revealed.some(area => this.areaContains(area, x, y));
+    }
+
+    // Whether a point is inside a revealed area (even-odd rule, as the server checks)
+    areaContains(area, x, y) {
+        let inside = false;
+        const points = area.points;
+        for (let i = 0, j = points.length - 1; i < points.length; j = i++) {
+            const a = points[i];
+            const b = points[j];
+            if ((a.y > y) !== (b.y > y) && x < a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y)) {
+                inside = !inside;
+            }
+        }
+        return inside;
+    }
+
+    // Darken everything, then cut the revealed areas back out
+    drawFog() {
+        if (!this.fogLayer) this.fogLayer = document.createElement('canvas');
+        const layer = this.fogLayer;
+        layer.width = this.width;
+        layer.height = this.height;
+        const ctx = layer.getContext('2d');
+        ctx.fillStyle = `rgba(0, 0, 0, ${this.fogOpacity})`;
+        ctx.fillRect(0, 0, this.width, this.height);
+        ctx.globalCompositeOperation = 'destination-out';
+        this.fog.revealed.forEach(area => {
+            ctx.beginPath();
+            area.points.forEach((p, i) => i === 0 ? ctx.moveTo(p.x, p.y) : ctx.lineTo(p.x, p.y));
+            ctx.closePath();
+            ctx.fill();
+        });
+        this.ctx.drawImage(layer, 0, 0);
+    }
+
+    // Pointy-topped hexes, odd rows shifted half a cell right (as the server snaps)
+    drawHexGrid(grid) {
+        const size = grid.cell_size;
+        const radius = size / Math.sqrt(3);
+        const rowHeight = 1.5 * radius;
+        this.ctx.strokeStyle = '#444';
+        this.ctx.lineWidth = 1;
+
+        const firstRow = Math.floor(-grid.offset_y / rowHeight) - 1;
+        const lastRow = Math.ceil((this.height - grid.offset_y) / rowHeight) + 1;
+        for (let row = firstRow; row <= lastRow; row++) {
+            const shift = Math.abs(row % 2) === 1 ? size / 2 : 0;
+            const cy = grid.offset_y + row * rowHeight + radius;
+            const firstColumn = Math.floor((-grid.offset_x - shift) / size) - 1;
+            const lastColumn = Math.ceil((this.width - grid.offset_x - shift) / size) + 1;
+            for (let column = firstColumn; column <= lastColumn; column++) {
+                const cx = grid.offset_x + column * size + size / 2 + shift;
+                this.ctx.beginPath();
+                for (let corner = 0; corner < 6; corner++) {
+                    const angle = Math.PI / 180 * (60 * corner - 30);
+                    const x = cx + radius * Math.cos(angle);
+                    const y = cy + radius * Math.sin(angle);
+                    if (corner === 0) this.ctx.moveTo(x, y);
+                    else this.ctx.lineTo(x, y);
+                }
+                this.ctx.closePath();
+                this.ctx.stroke();
+            }
+        }
+    }
     
     drawPlayer(player) {
-        const { position, color, name, id } = player;
+        const { position, color, name, id, token } = player;
+        const radius = tokenRadius(token);
         
         // Draw selection ring if this is the selected attacker
         if (this.selectedAttackerId === id) {
@@ -155,7 +472,7 @@ class MapCanvas {
             this.ctx.lineWidth = 4;
             this.ctx.setLineDash([5, 5]);
             this.ctx.beginPath();
-            this.ctx.arc(position.x, position.y, PLAYER_RADIUS + 8, 0, Math.PI * 2);
+            this.ctx.arc(position.x, position.y, radius + 8, 0, Math.PI * 2);
             this.ctx.stroke();
             this.ctx.setLineDash([]); // Reset dash
         }
@@ -167,7 +484,7 @@ class MapCanvas {
         // Draw circle
         this.ctx.fillStyle = color;
         this.ctx.beginPath();
-        this.ctx.arc(position.x, position.y, PLAYER_RADIUS, 0, Math.PI * 2);
+        this.ctx.arc(position.x, position.y, radius, 0, Math.PI * 2);
         this.ctx.fill();
         
         // Draw border
@@ -177,6 +494,7 @@ class MapCanvas {
         
         // Reset shadow
         this.ctx.shadowBlur = 0;
+        this.drawTokenDetails(position.x, position.y, radius, token);
         
         // Draw name label
         this.ctx.fillStyle = '#ffffff';
@@ -188,11 +506,48 @@ class MapCanvas {
         this.ctx.shadowColor = '#000000';
         this.ctx.shadowBlur = 4;
         
-        this.ctx.fillText(name, position.x, position.y - PLAYER_RADIUS - 5);
+        this.ctx.fillText(name, position.x, position.y - radius - 5);
         
         // Reset shadow
         this.ctx.shadowBlur = 0;
     }
+
+    // A notch on the rim the way the token faces, its elevation and its note
+    drawTokenDetails(x, y, radius, token) {
+        if (!token) return;
+        const angle = (token.rotation || 0) * Math.PI / 180;
+        const rim = (r, offset) => ({
+            x: x + Math.sin(angle + offset) * r,
+            y: y - Math.cos(angle + offset) * r,
+        });
+        const tip = rim(radius + 9, 0);
+        const left = rim(radius, -0.35);
+        const right = rim(radius, 0.35);
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.beginPath();
+        this.ctx.moveTo(tip.x, tip.y);
+        this.ctx.lineTo(left.x, left.y);
+        this.ctx.lineTo(right.x, right.y);
+        this.ctx.closePath();
+        this.ctx.fill();
+
+        this.ctx.font = 'bold 11px sans-serif';
+        this.ctx.textAlign = 'center';
+        this.ctx.shadowColor = '#000000';
+        this.ctx.shadowBlur = 4;
+        if (token.elevation) {
+            this.ctx.fillStyle = '#5dade2';
+            this.ctx.textBaseline = 'middle';
+            const arrow = token.elevation > 0 ? '↑' : '↓';
+            this.ctx.fillText(`${arrow}${Math.abs(token.elevation)} ft`, x + radius + 18, y - radius + 4);
+        }
+        if (token.note) {
+            this.ctx.fillStyle = '#f5f5f5';
+            this.ctx.textBaseline = 'top';
+            this.ctx.fillText(token.note, x, y + radius + 12);
+        }
+        this.ctx.shadowBlur = 0;
+    }
     
     // Get canvas click position (for mobile tap)
     getClickPosition(event) {
@@ -208,16 +563,26 @@ class MapCanvas {
 
     // ===== Adversary Rendering =====
     
-    drawAdversary(id, name, x, y) {
+    drawAdversary(id, name, x, y, token = {}) {
         // Store adversary position for later rendering
         if (!this.adversaryPositions) {
             this.adversaryPositions = new Map();
         }
         
-        this.adversaryPositions.set(id, { name, x, y, hp: null, maxHp: null });
+        this.adversaryPositions.set(id, { name, x, y, hp: null, maxHp: null, token });
         this.render();
     }
     
+    // The GM moved an adversary
+    moveAdversary(id, position) {
+        const adv = this.adversaryPositions?.get(id);
+        if (adv) {
+            adv.x = position.x;
+            adv.y = position.y;
+            this.render();
+        }
+    }
+
     removeAdversary(id) {
         if (this.adversaryPositions) {
             this.adversaryPositions.delete(id);
@@ -234,8 +599,8 @@ class MapCanvas {
         }
     }
     
-    drawAdversaryToken(name, x, y, hp, maxHp) {
-        const ADVERSARY_RADIUS = 20;
+    drawAdversaryToken(name, x, y, hp, maxHp, token) {
+        const ADVERSARY_RADIUS = tokenRadius(token);
         
         // Draw red glow
         this.ctx.shadowBlur = 15;
@@ -261,6 +626,7 @@ class MapCanvas {
         
         // Reset shadow
         this.ctx.shadowBlur = 0;
+        this.drawTokenDetails(x, y, ADVERSARY_RADIUS, token);
         
         // Draw name label
         this.ctx.fillStyle = '#ffffff';
@@ -299,7 +665,7 @@ class MapCanvas {
     renderAdversaries() {
         if (this.adversaryPositions) {
             this.adversaryPositions.forEach((adv, id) => {
-                this.drawAdversaryToken(adv.name, adv.x, adv.y, adv.hp, adv.maxHp);
+                this.drawAdversaryToken(adv.name, adv.x, adv.y, adv.hp, adv.maxHp, adv.token);
             });
         }
     }
@@ -308,14 +674,13 @@ class MapCanvas {
     
     // Check if click hit a character
     getCharacterAtPosition(x, y) {
-        const CLICK_RADIUS = PLAYER_RADIUS + 5; // Slightly bigger hitbox
-        
         for (const [id, player] of this.players) {
             const dx = x - player.position.x;
             const dy = y - player.position.y;
             const distance = Math.sqrt(dx * dx + dy * dy);
             
-            if (distance <= CLICK_RADIUS) {
+            // Slightly bigger hitbox than the token
+            if (distance <= tokenRadius(player.token) + 5) {
                 return { id, name: player.name };
             }
         }
@@ -324,8 +689,6 @@ class MapCanvas {
     
     // Check if click hit an adversary
     getAdversaryAtPosition(x, y) {
-        const CLICK_RADIUS = 25; // Adversary click radius
-        
         if (!this.adversaryPositions) return null;
         
         for (const [id, adv] of this.adversaryPositions) {
@@ -333,7 +696,7 @@ class MapCanvas {
             const dy = y - adv.y;
             const distance = Math.sqrt(dx * dx + dy * dy);
             
-            if (distance <= CLICK_RADIUS) {
+            if (distance <= tokenRadius(adv.token) + 5) {
                 return { id, name: adv.name };
             }
         }
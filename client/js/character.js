@@ -2,15 +2,15 @@
 // Phase 3: Daggerheart Integration
 
 const CLASSES = [
-    { id: "Bard", name: "Bard", description: "Support and utility specialist" },
-    { id: "Druid", name: "Druid", description: "Nature magic and shapeshifting" },
-    { id: "Guardian", name: "Guardian", description: "Defensive tank and protector" },
-    { id: "Ranger", name: "Ranger", description: "Wilderness expert and tracker" },
-    { id: "Rogue", name: "Rogue", description: "Stealth and precision striker" },
-    { id: "Seraph", name: "Seraph", description: "Divine magic and healing" },
-    { id: "Sorcerer", name: "Sorcerer", description: "Raw magical power" },
-    { id: "Warrior", name: "Warrior", description: "Martial combat expert" },
-    { id: "Wizard", name: "Wizard", description: "Learned arcane magic" },
+    { id: "Bard", name: "Bard", description: "Support and utility specialist", subclasses: ["Troubadour", "Wordsmith"] },
+    { id: "Druid", name: "Druid", description: "Nature magic and shapeshifting", subclasses: ["Warden of the Elements", "Warden of Renewal"] },
+    { id: "Guardian", name: "Guardian", description: "Defensive tank and protector", subclasses: ["Stalwart", "Vengeance"] },
+    { id: "Ranger", name: "Ranger", description: "Wilderness expert and tracker", subclasses: ["Beastbound", "Wayfinder"] },
+    { id: "Rogue", name: "Rogue", description: "Stealth and precision striker", subclasses: ["Nightwalker", "Syndicate"] },
+    { id: "Seraph", name: "Seraph", description: "Divine magic and healing", subclasses: ["Divine Wielder", "Winged Sentinel"] },
+    { id: "Sorcerer", name: "Sorcerer", description: "Raw magical power", subclasses: ["Elemental Origin", "Primal Origin"] },
+    { id: "Warrior", name: "Warrior", description: "Martial combat expert", subclasses: ["Call of the Brave", "Call of the Slayer"] },
+    { id: "Wizard", name: "Wizard", description: "Learned arcane magic", subclasses: ["School of Knowledge", "School of War"] },
 ];
 
 const ANCESTRIES = [
@@ -37,6 +37,7 @@ class CharacterCreator {
         this.data = {
             name: "",
             class: null,
+            subclass: null,
             ancestry: null,
             attributes: [0, 0, 0, 0, 0, 0], // agility, strength, finesse, instinct, presence, knowledge
         };
@@ -111,6 +112,11 @@ class CharacterCreator {
                         <div class="class-card" data-class="${cls.id}">
                             <h3>${cls.name}</h3>
                             <p>${cls.description}</p>
+                            <div class="subclass-options">
+                                ${cls.subclasses.map(sub => `
+                                    <button class="btn-secondary subclass-btn" data-subclass="${sub}">${sub}</button>
+                                `).join('')}
+                            </div>
                         </div>
                     `).join('')}
                 </div>
@@ -122,8 +128,10 @@ class CharacterCreator {
         `;
         
         document.querySelectorAll('.class-card').forEach(card => {
-            card.addEventListener('click', () => {
+            card.addEventListener('click', (event) => {
+                const subclassBtn = event.target.closest('.subclass-btn');
                 this.data.class = card.dataset.class;
+                this.data.subclass = subclassBtn ? subclassBtn.dataset.subclass : null;
                 this.step = 3;
                 this.render();
             });
@@ -237,7 +245,7 @@ class CharacterCreator {
                 
                 <div class="character-summary">
                     <p><strong>Name:</strong> ${this.data.name}</p>
-                    <p><strong>Class:</strong> ${this.data.class}</p>
+                    <p><strong>Class:</strong> ${this.data.class}${this.data.subclass ? ` (${this.data.subclass})` : ''}</p>
                     <p><strong>Ancestry:</strong> ${this.data.ancestry}</p>
                     <h3>Attributes:</h3>
                     <ul>
@@ -273,6 +281,7 @@ class CharacterCreator {
             window.ws.send('create_character', {
                 name: this.data.name,
                 class: this.data.class,
+                subclass: this.data.subclass,
                 ancestry: this.data.ancestry,
                 attributes: this.data.attributes,
             });
@@ -0,0 +1,85 @@
+// Table chat panel, shared by the mobile and GM views
+//
+// Expects #chat-log, #chat-channel, #chat-input and #chat-send-btn on the
+// page. History comes from /api/chat (whispers aren't listed there, so
+// they're only seen live).
+
+let chatSend = null;
+
+function setupChat(send) {
+    const input = document.getElementById('chat-input');
+    if (!input) return;
+    chatSend = send;
+
+    const sendTyped = () => {
+        const text = input.value.trim();
+        if (!text) return;
+        chatSend('chat_message', { channel: selectedChatChannel(), text });
+        input.value = '';
+    };
+    document.getElementById('chat-send-btn').addEventListener('click', sendTyped);
+    input.addEventListener('keydown', (e) => {
+        if (e.key === 'Enter') sendTyped();
+    });
+
+    fetch('/api/chat')
+        .then(response => response.json())
+        .then(data => data.messages.forEach(appendChatMessage))
+        .catch(error => console.error('Failed to load chat:', error));
+}
+
+// Option values: "in_character", "out_of_character", "whisper:gm", "whisper:<character id>"
+function selectedChatChannel() {
+    const value = document.getElementById('chat-channel').value;
+    if (!value.startsWith('whisper:')) return { kind: value };
+    const target = value.slice('whisper:'.length);
+    return { kind: 'whisper', to_character_id: target === 'gm' ? null : target };
+}
+
+// Offer a whisper to each player character
+function updateChatTargets(characters) {
+    const select = document.getElementById('chat-channel');
+    if (!select) return;
+    const selected = select.value;
+
+    select.querySelectorAll('option[data-whisper-character]').forEach(o => o.remove());
+    characters.filter(c => !c.is_npc).forEach(c => {
+        const option = document.createElement('option');
+        option.value = `whisper:${c.id}`;
+        option.textContent = `🤫 Whisper to ${c.name}`;
+        option.dataset.whisperCharacter = c.id;
+        select.appendChild(option);
+    });
+    if ([...select.options].some(o => o.value === selected)) {
+        select.value = selected;
+    }
+}
+
+function appendChatMessage(message) {
+    const log = document.getElementById('chat-log');
+    if (!log) return;
+
+    const item = document.createElement('div');
+    item.className = `chat-message chat-${message.channel.kind.replace(/_/g, '-')}`;
+
+    const header = document.createElement('span');
+    header.className = 'chat-sender';
+    if (message.channel.kind === 'whisper') {
+        header.textContent = `${message.sender_name} → ${message.to_name}: `;
+    } else if (message.channel.kind === 'out_of_character') {
+        header.textContent = `(OOC) ${message.sender_name}: `;
+    } else {
+        header.textContent = `${message.sender_name}: `;
+    }
+
+    const text = document.createElement('span');
+    text.textContent = message.text;
+
+    const time = document.createElement('span');
+    time.className = 'chat-time';
+    time.textContent = message.timestamp;
+
+    item.append(time, header, text);
+    log.appendChild(item);
+    log.scrollTop = log.scrollHeight;
+}
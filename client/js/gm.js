@@ -9,6 +9,17 @@ let characters = [];
 let saves = [];
 let connectionCount = 0;
 
+// Optional API token for save/load (open the GM view with ?token=...)
+const apiToken = new URLSearchParams(window.location.search).get('token');
+
+function apiHeaders() {
+    const headers = { 'Content-Type': 'application/json' };
+    if (apiToken) {
+        headers['X-Api-Token'] = apiToken;
+    }
+    return headers;
+}
+
 document.addEventListener('DOMContentLoaded', () => {
     console.log('GM DOM loaded');
     
@@ -26,14 +37,18 @@ document.addEventListener('DOMContentLoaded', () => {
             const y = (e.clientY - rect.top) * scaleY;
             
             if (window.handleCanvasClick) {
-                window.handleCanvasClick(x, y);
+                window.handleCanvasClick(x, y, e);
             }
         });
+        canvas.addEventListener('contextmenu', (e) => {
+            e.preventDefault();
+            ws.send('ping', { position: mapCanvas.getClickPosition(e) });
+        });
         console.log('Canvas click handler installed');
     }
     
     // Connect to WebSocket
-    ws = new WebSocketClient(handleServerMessage);
+    ws = new WebSocketClient(handleServerMessage, { role: 'gm', token: apiToken });
     ws.connect();
     
     // Setup event listeners
@@ -58,13 +73,88 @@ function setupEventListeners() {
     
     // Request roll button
     document.getElementById('request-roll-btn').addEventListener('click', requestRoll);
+    document.getElementById('preview-roll-btn').addEventListener('click', previewRoll);
+    document.getElementById('secret-roll-btn').addEventListener('click', secretRoll);
+
+    // Next session
+    document.getElementById('schedule-session-btn').addEventListener('click', scheduleNextSession);
+    document.getElementById('clear-session-btn').addEventListener('click', () => ws.send('clear_next_session'));
+    loadNextSession();
+
+    // Chat
+    setupChat((type, payload) => ws.send(type, payload));
+
+    // Cutaway
+    document.getElementById('start-cutaway-btn').addEventListener('click', startCutaway);
+    document.getElementById('end-cutaway-btn').addEventListener('click', () => ws.send('end_cutaway'));
+
+    // Handouts
+    document.getElementById('handout-kind').addEventListener('change', (e) => {
+        const isImage = e.target.value === 'image';
+        document.getElementById('handout-text').style.display = isImage ? 'none' : '';
+        document.getElementById('handout-file').style.display = isImage ? '' : 'none';
+    });
+    document.getElementById('share-handout-btn').addEventListener('click', shareHandout);
+
+    // Notes
+    document.getElementById('save-note-btn').addEventListener('click', saveNote);
+    document.getElementById('new-note-btn').addEventListener('click', clearNoteForm);
+    document.getElementById('delete-note-btn').addEventListener('click', deleteNote);
+    document.getElementById('note-search').addEventListener('change', (e) => requestNotes(e.target.value));
+
+    // Quests
+    document.getElementById('save-quest-btn').addEventListener('click', saveQuest);
+    document.getElementById('new-quest-btn').addEventListener('click', clearQuestForm);
+    document.getElementById('remove-quest-btn').addEventListener('click', deleteQuest);
+
+    // Map
+    document.getElementById('upload-map-btn').addEventListener('click', uploadMap);
+    document.getElementById('show-map-btn').addEventListener('click', () => {
+        ws.send('set_map', { map_id: document.getElementById('map-select').value || null });
+    });
+    document.getElementById('apply-layout-btn').addEventListener('click', applySceneLayout);
+    document.getElementById('measure-range-btn').addEventListener('click', toggleMeasureMode);
+    document.getElementById('map-locked').addEventListener('change', (e) => {
+        ws.send('set_map_locked', { locked: e.target.checked });
+    });
+    document.getElementById('fog-enabled').addEventListener('change', (e) => {
+        ws.send('set_fog_enabled', { enabled: e.target.checked });
+    });
+    document.getElementById('reveal-area-btn').addEventListener('click', toggleRevealMode);
+    document.getElementById('hide-area-btn').addEventListener('click', () => {
+        fogMode = fogMode === 'hide' ? null : 'hide';
+        updateFogButtons();
+    });
+    document.getElementById('pick-token-btn').addEventListener('click', () => {
+        pickTokenMode = !pickTokenMode;
+        document.getElementById('pick-token-btn').textContent =
+            pickTokenMode ? '❌ Cancel' : '🎯 Edit Token';
+        if (pickTokenMode) showCombatFeedback('🎯 Click a token to edit it');
+        else hideCombatFeedback();
+    });
+    document.getElementById('apply-token-btn').addEventListener('click', applyTokenDetails);
+    document.getElementById('move-token-btn').addEventListener('click', toggleMoveTokenMode);
+    document.getElementById('draw-btn').addEventListener('click', toggleDrawMode);
+    document.getElementById('erase-drawing-btn').addEventListener('click', () => {
+        drawMode = drawMode === 'erase' ? null : 'erase';
+        drawPoints = [];
+        updateDrawButtons();
+    });
+    document.getElementById('clear-drawings-btn').addEventListener('click', () => {
+        if (confirm('Erase every drawing on this scene?')) ws.send('clear_drawings', {});
+    });
+    loadMaps();
 }
 
 async function saveGame() {
+    const name = prompt('Name this save:', 'Manual Save');
+    if (name === null) return;
+
     try {
         const response = await fetch('/api/save', {
             method: 'POST',
-            headers: { 'Content-Type': 'application/json' }
+            headers: apiHeaders(),
+            body: JSON.stringify({ name })
         });
         
         const result = await response.json();
@@ -83,6 +173,47 @@ async function saveGame() {
     }
 }
 
+// Everything on disk as one zip, for moving the table to another machine
+async function downloadBackup() {
+    try {
+        const response = await fetch('/api/backup', { headers: apiHeaders() });
+        if (!response.ok) {
+            const result = await response.json();
+            alert(`❌ Backup failed: ${result.error}`);
+            return;
+        }
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(await response.blob());
+        link.download = `daggerheart-backup-${new Date().toISOString().slice(0, 10)}.zip`;
+        link.click();
+        URL.revokeObjectURL(link.href);
+    } catch (error) {
+        alert(`❌ Error: ${error.message}`);
+    }
+}
+
+async function restoreBackup(input) {
+    const file = input.files[0];
+    input.value = '';
+    if (!file || !confirm(`Restore ${file.name}? Saves, maps and handouts with the same names are replaced.`)) return;
+
+    const headers = apiToken ? { 'X-Api-Token': apiToken } : {};
+    headers['Content-Type'] = 'application/zip';
+    try {
+        const response = await fetch('/api/restore', { method: 'POST', headers, body: file });
+        const result = await response.json();
+        if (result.success) {
+            alert(`✅ Restored ${result.files} files`);
+            loadSaves();
+            loadMaps();
+        } else {
+            alert(`❌ Restore failed: ${result.error}`);
+        }
+    } catch (error) {
+        alert(`❌ Error: ${error.message}`);
+    }
+}
+
 async function loadSaves() {
     try {
         const response = await fetch('/api/saves');
@@ -107,28 +238,84 @@ function renderSavesList() {
         return;
     }
     
-    container.innerHTML = saves.map(save => {
-        const date = new Date(save.timestamp);
-        const timeStr = date.toLocaleString();
-        
-        return `
-            <div class="save-item" data-path="${save.path}">
-                <strong>${save.name}</strong>
-                <br>
-                <small>${timeStr}</small>
-            </div>
-        `;
-    }).join('');
-    
-    // Add click handlers
-    document.querySelectorAll('.save-item').forEach(item => {
-        item.addEventListener('click', () => {
-            const path = item.dataset.path;
-            loadGameSession(path);
+    // Built with DOM calls since save names are free text
+    container.innerHTML = '';
+    saves.forEach(save => {
+        const item = document.createElement('div');
+        item.className = 'save-item';
+
+        const name = document.createElement('strong');
+        name.textContent = save.name;
+        const time = document.createElement('small');
+        time.textContent = new Date(save.timestamp).toLocaleString();
+
+        const actions = document.createElement('span');
+        actions.className = 'save-actions';
+        const rename = document.createElement('button');
+        rename.className = 'btn-secondary btn-small';
+        rename.title = 'Rename';
+        rename.textContent = '✏️';
+        rename.addEventListener('click', (e) => {
+            e.stopPropagation();
+            renameSave(save);
+        });
+        const remove = document.createElement('button');
+        remove.className = 'btn-secondary btn-small';
+        remove.title = 'Delete';
+        remove.textContent = '🗑️';
+        remove.addEventListener('click', (e) => {
+            e.stopPropagation();
+            deleteSave(save);
         });
+        actions.append(rename, remove);
+
+        item.append(actions, name, document.createElement('br'), time);
+        item.addEventListener('click', () => loadGameSession(save.path));
+        container.appendChild(item);
     });
 }
 
+async function renameSave(save) {
+    const name = prompt('Rename save:', save.name);
+    if (name === null || name === save.name) return;
+
+    try {
+        const response = await fetch('/api/saves/rename', {
+            method: 'POST',
+            headers: apiHeaders(),
+            body: JSON.stringify({ path: save.path, name })
+        });
+        const result = await response.json();
+        if (result.success) {
+            loadSaves();
+        } else {
+            alert(`❌ Rename failed: ${result.error}`);
+        }
+    } catch (error) {
+        alert(`❌ Error: ${error.message}`);
+    }
+}
+
+async function deleteSave(save) {
+    if (!confirm(`Delete the save "${save.name}"? This can't be undone.`)) return;
+
+    try {
+        const response = await fetch('/api/saves', {
+            method: 'DELETE',
+            headers: apiHeaders(),
+            body: JSON.stringify({ path: save.path })
+        });
+        const result = await response.json();
+        if (result.success) {
+            loadSaves();
+        } else {
+            alert(`❌ Delete failed: ${result.error}`);
+        }
+    } catch (error) {
+        alert(`❌ Error: ${error.message}`);
+    }
+}
+
 async function loadGameSession(path) {
     if (!confirm(`Load this session? Current game will be replaced.\n\n${path}`)) {
         return;
@@ -137,7 +324,7 @@ async function loadGameSession(path) {
     try {
         const response = await fetch('/api/load', {
             method: 'POST',
-            headers: { 'Content-Type': 'application/json' },
+            headers: apiHeaders(),
             body: JSON.stringify({ path })
         });
         
@@ -203,20 +390,67 @@ function handleServerMessage(message) {
         case 'character_moved':
             handleCharacterMoved(payload);
             break;
+        case 'character_emoted':
+            if (mapCanvas) {
+                mapCanvas.showEmote(payload.character_id, payload.text, payload.duration_ms);
+            }
+            break;
         case 'character_created':
+            // Character was created, will get new list
+            break;
         case 'character_updated':
-            // Character was updated, will get new list
+            characterSheets[payload.character_id] = payload.character;
+            sheetSequences[payload.character_id] = payload.sequence;
+            renderCharactersList();
+            break;
+        case 'character_changed': {
+            const sheet = characterSheets[payload.character_id];
+            if (!sheet || sheetSequences[payload.character_id] !== payload.since) {
+                // New to us, or we missed a change: get the whole sheet
+                ws.send('request_sheet', { character_id: payload.character_id });
+                break;
+            }
+            characterSheets[payload.character_id] = { ...sheet, ...payload.changes };
+            sheetSequences[payload.character_id] = payload.sequence;
+            renderCharactersList();
             break;
+        }
         case 'roll_request_status':
             updateRollStatus(payload);
             break;
+        case 'group_roll_result': {
+            const helpers = payload.helpers
+                .map(h => `${h.character_name} ${h.success ? '✓' : '✗'}`)
+                .join(', ');
+            showCombatFeedback(`👥 ${payload.leader_name}: ${outcomeLabel(payload.success_type, isReactionRoll(payload.roll_type))} (${payload.total} vs ${payload.difficulty}; helpers ${helpers})`);
+            break;
+        }
+        case 'roll_preview':
+            handleRollPreview(payload);
+            break;
+        case 'tag_team_resolved':
+            showCombatFeedback(`🤝 Tag team went with ${payload.character_name}: ${payload.total} vs ${payload.difficulty} (Fear: ${payload.new_fear})`);
+            break;
         case 'detailed_roll_result':
             console.log('Roll result:', payload);
-            // Results are shown on TV view
+            // Results are shown on TV view, except hidden ones
+            if (payload.hidden) {
+                const roll = payload.roll_details;
+                showCombatFeedback(`🔒 ${payload.character_name}: ${outcomeLabel(roll.success_type, isReactionRoll(payload.roll_type))} (${roll.total} vs ${roll.difficulty})`);
+            }
+            break;
+        case 'dice_rolled':
+            if (payload.hidden) {
+                showCombatFeedback(`🔒 ${payload.roll.expression}: ${payload.roll.total} (${payload.roll.dice.map(d => d.kept ? d.value : `(${d.value})`).join(', ')})`);
+            }
             break;
         case 'game_event':
             handleGameEvent(payload);
             break;
+        case 'error':
+            console.error('Server error:', payload.code, payload.message);
+            alert(payload.code === 'game_replaced' ? payload.message : `Error: ${payload.message}`);
+            break;
         case 'adversary_spawned':
             handleAdversarySpawned(payload);
             break;
@@ -228,12 +462,17 @@ function handleServerMessage(message) {
             break;
         case 'combat_started':
             handleCombatStarted(payload);
+            loadCheckpoints();
             break;
         case 'combat_ended':
             handleCombatEnded(payload);
             break;
         case 'tracker_updated':
             handleTrackerUpdated(payload);
+            if (payload.next_token !== 'adversary') showTacticsReminder([]);
+            break;
+        case 'tactics_reminder':
+            showTacticsReminder(payload.adversaries);
             break;
         case 'attack_result':
             handleAttackResult(payload);
@@ -241,17 +480,220 @@ function handleServerMessage(message) {
         case 'damage_result':
             handleDamageResult(payload);
             break;
+        case 'area_damage_result':
+            handleAreaDamageResult(payload);
+            break;
+        case 'adversary_attack_result':
+            handleAdversaryAttackResult(payload);
+            break;
+        case 'environment_spawned':
+            environments[payload.environment.id] = payload.environment;
+            renderEnvironmentsList();
+            break;
+        case 'environment_removed':
+            delete environments[payload.environment_id];
+            renderEnvironmentsList();
+            break;
+        case 'environment_feature_activated':
+            showCombatFeedback(`🏞️ ${payload.environment_name}: ${payload.feature.name} (Fear: ${payload.fear_pool})`);
+            break;
+        case 'fear_spent':
+            showCombatFeedback(`${payload.message} (Fear: ${payload.fear_pool})`);
+            break;
+        case 'character_healed': {
+            const { healing } = payload;
+            const cleared = [
+                healing.hp_cleared && `${healing.hp_cleared} HP`,
+                healing.stress_cleared && `${healing.stress_cleared} Stress`,
+            ].filter(Boolean).join(', ');
+            showCombatFeedback(`🩹 ${healing.character_name}: ${healing.source}${cleared ? ` cleared ${cleared}` : ''}`);
+            break;
+        }
+        case 'adversary_stress_marked':
+            showCombatFeedback(`${payload.message} (Stress: ${payload.stress}/${payload.max_stress})`);
+            break;
+        case 'character_color_changed': {
+            const character = characters.find(c => c.id === payload.character_id);
+            if (character) character.color = payload.color;
+            mapCanvas.updatePlayerColor(payload.character_id, payload.color);
+            break;
+        }
+        case 'display_name_changed':
+            console.log(`👤 ${payload.display_name} (${payload.connection_id.substring(0, 8)})`);
+            break;
+        case 'combat_mode_changed':
+            setCombatMode(payload.mode);
+            break;
+        case 'settings_updated':
+            showTableSettings(payload.settings);
+            break;
+        case 'next_session_updated':
+            showNextSession(payload.next_session);
+            break;
+        case 'chat_message':
+            appendChatMessage(payload.message);
+            break;
+        case 'handout_shared':
+            appendHandout(payload.handout);
+            break;
+        case 'handouts':
+            showHandouts(payload.handouts);
+            break;
+        case 'note_saved':
+            showNote(payload.note);
+            break;
+        case 'note_deleted':
+            removeNote(payload.note_id);
+            break;
+        case 'notes':
+            showNotes(payload.notes);
+            break;
+        case 'quest_updated':
+            showQuest(payload.quest);
+            break;
+        case 'quest_removed':
+            removeQuest(payload.quest_id);
+            break;
+        case 'map_changed':
+            showMap(payload.map);
+            break;
+        case 'scene_layout_changed':
+            showSceneLayout(payload.layout);
+            break;
+        case 'range_measured':
+            showRangeMeasured(payload);
+            break;
+        case 'fog_changed':
+            showFog(payload.fog);
+            break;
+        case 'map_lock_changed':
+            document.getElementById('map-locked').checked = payload.locked;
+            break;
+        case 'token_details_changed':
+            mapCanvas.setTokenDetails(payload.token_id, payload.token);
+            if (payload.token_id === tokenEditId) fillTokenForm(payload.token);
+            break;
+        case 'token_renamed':
+            handleTokenRenamed(payload);
+            break;
+        case 'control_requested': {
+            const who = payload.display_name || 'Another device';
+            if (confirm(`${who} wants to play ${payload.name}. Hand it over?`)) {
+                ws.send('transfer_control', {
+                    character_id: payload.character_id,
+                    to_connection_id: payload.connection_id,
+                });
+            }
+            break;
+        }
+        case 'control_transferred': {
+            const character = characters.find(c => c.id === payload.character_id);
+            if (character) {
+                character.controlled_by_me = false;
+                character.controlled_by_other = !!payload.to_connection_id;
+                character.controller_id = payload.to_connection_id;
+                renderCharactersList();
+            }
+            break;
+        }
+        case 'adversary_moved': {
+            const adversary = adversaries.find(adv => adv.id === payload.adversary_id);
+            if (adversary) adversary.position = payload.position;
+            mapCanvas.moveAdversary(payload.adversary_id, payload.position);
+            break;
+        }
+        case 'pinged':
+            mapCanvas.showPing(payload.position, payload.name, payload.color);
+            break;
+        case 'drawings_changed':
+            mapCanvas.setDrawings(payload.drawings);
+            break;
+        case 'checkpoint_restored':
+            alert(`⏪ Rolled back to "${payload.checkpoint.label}" (${payload.checkpoint.created_at})`);
+            location.reload();
+            break;
+        case 'spotlight_changed':
+            document.getElementById('spotlight-holder').textContent = payload.holder_name;
+            showCombatFeedback(`🔦 Spotlight on ${payload.holder_name}`);
+            break;
+        case 'catch_up':
+            handleCatchUp(payload.snapshot);
+            break;
+        case 'cutaway_started':
+            showCutaway(payload.cutaway);
+            break;
+        case 'cutaway_ended':
+            showCutaway(null);
+            break;
+        case 'round_advanced':
+            document.getElementById('combat-round').textContent = payload.round;
+            break;
+        case 'round_started':
+            document.getElementById('combat-round').textContent = payload.round;
+            console.log(`Round ${payload.previous.round}:`, payload.previous.actions);
+            break;
+        case 'round_history':
+            console.log('Rounds so far:', payload.rounds);
+            break;
+        case 'scene_log':
+            console.log('Log by scene:', payload.scenes);
+            break;
+        case 'hazard_added':
+        case 'hazard_updated':
+            handleHazardUpdated(payload);
+            break;
+        case 'hazard_removed':
+            delete hazards[payload.hazard_id];
+            renderHazardsList();
+            break;
+        case 'hazard_triggered':
+            showCombatFeedback(`🌊 ${payload.name}: ${payload.affected.join(', ') || 'no one in the area'}`);
+            break;
+        case 'aura_attached':
+        case 'aura_detached':
+        case 'aura_entered':
+        case 'aura_left':
+            // Aura changes also arrive as game events for the log
+            console.log('Aura:', type, payload);
+            break;
+        case 'encounter_priced': {
+            const { budget } = payload;
+            showCombatFeedback(`⚔️ ${budget.spent} of ${budget.budget} Battle Points (${budget.remaining} left)`);
+            break;
+        }
+        case 'encounter_presets':
+            console.log('Encounter presets:', payload.presets.map(p => p.name));
+            if (payload.skipped && payload.skipped.length > 0) {
+                console.log('Not kept (no template):', payload.skipped);
+            }
+            break;
+        case 'adversary_groups_updated':
+            console.log('Adversary groups:', payload.groups.map(g =>
+                `${g.name}: ${g.active_members}/${g.members.length} standing, ${g.hp}/${g.max_hp} HP`));
+            break;
+        case 'modifiers_updated':
+            // So do modifiers being added, removed and running out
+            console.log('Modifiers:', payload.modifiers);
+            break;
         default:
             console.log('GM received:', type, payload);
     }
 }
 
 function handleConnected(payload) {
-    const { connection_id } = payload;
+    const { connection_id, role } = payload;
     console.log('✅ GM Connected with ID:', connection_id);
+    if (role !== 'gm') {
+        // The server wants the API token: open the GM view with ?token=...
+        showCombatFeedback('⚠️ Not recognized as GM; GM-only updates are hidden');
+    }
     
     // Load event history
     loadEventHistory();
+    requestNotes();
+    requestHandouts();
+    loadAdversaryTemplates();
+    loadEnvironmentTemplates();
 }
 
 function handleCharactersList(payload) {
@@ -262,7 +704,7 @@ function handleCharactersList(payload) {
     if (mapCanvas) {
         mapCanvas.clearPlayers();
         characters.forEach(char => {
-            mapCanvas.addPlayer(char.id, char.name, char.position, char.color);
+            mapCanvas.addPlayer(char.id, char.name, char.position, char.color, char.token);
         });
     }
     
@@ -272,6 +714,11 @@ function handleCharactersList(payload) {
     
     // Update roll target dropdown
     updateTargetDropdown(characters);
+    updateChatTargets(characters);
+    renderCheckboxes('cutaway-characters');
+    renderCheckboxes('handout-characters');
+    renderCheckboxes('quest-characters');
+    setQuestCharacters(characters);
 }
 
 function handleAdversariesList(payload) {
@@ -281,7 +728,7 @@ function handleAdversariesList(payload) {
     // Add all adversaries to canvas
     if (mapCanvas) {
         adversaries.forEach(adv => {
-            mapCanvas.drawAdversary(adv.id, adv.name, adv.position.x, adv.position.y);
+            mapCanvas.drawAdversary(adv.id, adv.name, adv.position.x, adv.position.y, adv.token);
             if (adv.hp !== undefined && adv.max_hp !== undefined) {
                 mapCanvas.updateAdversaryHP(adv.id, adv.hp, adv.max_hp);
             }
@@ -295,8 +742,11 @@ function handleAdversariesList(payload) {
 }
 
 function handleCharacterSpawned(payload) {
-    const { character_id, name, position, color, is_npc } = payload;
+    const { character_id, name, position, color, is_npc, token } = payload;
     console.log(`Character spawned: ${name} (${is_npc ? 'NPC' : 'PC'})`);
+
+    // Sent again when the fog changes; the GM already has every token
+    if (characters.some(c => c.id === character_id)) return;
     
     // Add to characters list
     characters.push({
@@ -305,11 +755,12 @@ function handleCharacterSpawned(payload) {
         position,
         color,
         is_npc,
+        token,
     });
     
     // Add to canvas
     if (mapCanvas) {
-        mapCanvas.addPlayer(character_id, name, position, color);
+        mapCanvas.addPlayer(character_id, name, position, color, token);
     }
     
     // Update sidebar
@@ -335,7 +786,7 @@ function handleCharacterRemoved(payload) {
 }
 
 function handleCharacterMoved(payload) {
-    const { character_id, position } = payload;
+    const { character_id, position, path, teleported, rotation } = payload;
     
     // Update in characters list
     const char = characters.find(c => c.id === character_id);
@@ -345,7 +796,7 @@ function handleCharacterMoved(payload) {
     
     // Update canvas
     if (mapCanvas) {
-        mapCanvas.updatePlayerPosition(character_id, position);
+        mapCanvas.updatePlayerPosition(character_id, position, path, teleported, rotation);
     }
 }
 
@@ -376,12 +827,149 @@ function renderCharactersList() {
                 <div class="player-stats">
                     <div class="stat">${char.class} • ${char.ancestry}</div>
                     <div class="stat">${controlInfo}</div>
+                    ${characterSheets[char.id] ? `<div class="stat">HP ${characterSheets[char.id].hp.current}/${characterSheets[char.id].hp.maximum} • Stress ${characterSheets[char.id].stress} • Hope ${characterSheets[char.id].hope.current}</div>` : ''}
                 </div>
+                ${resourceButtons(char.id, ['hp', 'stress', 'hope'])}
+                ${char.is_npc ? `<button onclick="convertNpc('${char.id}')" class="btn-secondary" style="width: 100%; margin-top: 0.25rem;">⚔️ Turn Hostile</button>` : ''}
+                <div style="display: flex; gap: 0.25rem; margin-top: 0.25rem;">
+                    <button onclick="editCharacter('${char.id}')" class="btn-secondary btn-small" style="flex: 1;">✏️ Edit</button>
+                    <a href="/api/characters/${char.id}/export" download class="btn-secondary btn-small" style="flex: 1; text-align: center; text-decoration: none;">📤 Export</a>
+                    <button onclick="deleteCharacter('${char.id}', true)" class="btn-secondary btn-small" style="flex: 1;">📦 Retire</button>
+                    <button onclick="deleteCharacter('${char.id}', false)" class="btn-secondary btn-small" style="flex: 1;">🗑️ Delete</button>
+                </div>
+                ${char.controlled_by_other ? `<div style="display: flex; gap: 0.25rem; margin-top: 0.25rem;">
+                    <button onclick="releaseControl('${char.id}')" class="btn-secondary btn-small" style="flex: 1;">🔓 Release</button>
+                    ${char.controller_id ? `<button onclick="kickController('${char.id}')" class="btn-secondary btn-small" style="flex: 1;">👢 Kick</button>` : ''}
+                </div>` : ''}
             </div>
         `;
     }).join('');
 }
 
+function createNpc() {
+    const name = document.getElementById('npc-name').value.trim();
+    if (!name) {
+        alert('Please enter a name for the NPC');
+        return;
+    }
+
+    const hp = parseInt(document.getElementById('npc-hp').value);
+    const evasion = parseInt(document.getElementById('npc-evasion').value);
+    const useTemplate = document.getElementById('npc-use-template').checked;
+    const template = document.getElementById('adversary-template').value;
+
+    ws.send('create_npc', {
+        name,
+        hp: isNaN(hp) ? null : hp,
+        evasion: isNaN(evasion) ? null : evasion,
+        template: useTemplate && template !== 'custom' ? template : null,
+    });
+    document.getElementById('npc-name').value = '';
+}
+
+// Latest sheet seen for each character, from character_updated and
+// character_changed, and the sequence number it's at
+const characterSheets = {};
+const sheetSequences = {};
+
+const RESOURCE_LABELS = { hp: 'HP', stress: 'Stress', hope: 'Hope' };
+
+// −/+ buttons nudging HP, Stress or Hope on any character or adversary
+function resourceButtons(tokenId, resources) {
+    return `<div style="display: flex; gap: 0.25rem; margin-top: 0.25rem; font-size: 0.8rem;">${resources.map(resource => `
+        <span style="flex: 1; text-align: center;">${RESOURCE_LABELS[resource]}
+            <button onclick="adjustResource('${tokenId}', '${resource}', -1)" class="btn-secondary btn-small">−</button>
+            <button onclick="adjustResource('${tokenId}', '${resource}', 1)" class="btn-secondary btn-small">+</button>
+        </span>`).join('')}
+    </div>`;
+}
+
+function adjustResource(tokenId, resource, delta) {
+    ws.send('set_resource', { token_id: tokenId, resource, value: delta, relative: true });
+}
+
+// Correct a sheet; blank answers leave that part alone
+function editCharacter(characterId) {
+    const character = characters.find(c => c.id === characterId);
+    if (!character) return;
+    const name = prompt(`Name for ${character.name}:`, character.name);
+    if (name === null) return;
+    const level = prompt('Level (blank to keep):', '');
+    if (level === null) return;
+    const evasion = prompt('Evasion (blank to keep):', '');
+    if (evasion === null) return;
+    const attributes = prompt('Agility, Strength, Finesse, Instinct, Presence, Knowledge (e.g. 2,1,1,0,0,-1; blank to keep):', '');
+    if (attributes === null) return;
+
+    const number = (text) => (text.trim() === '' ? null : parseInt(text, 10));
+    ws.send('update_character', {
+        character_id: characterId,
+        changes: {
+            name: name.trim() || null,
+            level: number(level),
+            evasion: number(evasion),
+            attributes: attributes.trim() === '' ? null : attributes.split(',').map(v => parseInt(v, 10)),
+        },
+    });
+}
+
+// Retired characters are kept in the vault; deleted ones are gone
+function deleteCharacter(characterId, retire) {
+    const character = characters.find(c => c.id === characterId);
+    if (!character) return;
+    const question = retire
+        ? `Retire ${character.name}? They leave the table but their sheet is kept in the vault.`
+        : `Delete ${character.name} for good? This can't be undone.`;
+    if (confirm(question)) {
+        ws.send('delete_character', { character_id: characterId, retire });
+    }
+}
+
+// Bring in a character file exported from this or another table
+async function importCharacter(input) {
+    const file = input.files[0];
+    input.value = '';
+    if (!file) return;
+    try {
+        const response = await fetch('/api/characters/import', {
+            method: 'POST',
+            headers: apiHeaders(),
+            body: await file.text(),
+        });
+        const result = await response.json();
+        if (!result.success) {
+            alert(`Failed to import character: ${result.error}`);
+        }
+    } catch (error) {
+        alert(`Failed to import character: ${error.message}`);
+    }
+}
+
+// Free a character from whoever is playing it, e.g. a dropped phone
+function releaseControl(characterId) {
+    const character = characters.find(c => c.id === characterId);
+    if (character && confirm(`Release ${character.name} so another device can pick them?`)) {
+        ws.send('release_control', { character_id: characterId });
+    }
+}
+
+// Throw whoever is playing a character off the table, e.g. an uninvited guest
+function kickController(characterId) {
+    const character = characters.find(c => c.id === characterId);
+    if (!character?.controller_id) return;
+    const who = character.player_name || `${character.name}'s player`;
+    if (!confirm(`Kick ${who}?`)) return;
+    const ban = confirm('Also block their device until the server restarts?');
+    ws.send('kick_connection', { connection_id: character.controller_id, ban });
+}
+
+function convertNpc(characterId) {
+    const npc = characters.find(c => c.id === characterId);
+    if (npc && confirm(`Turn ${npc.name} into an adversary?`)) {
+        ws.send('convert_npc', { character_id: characterId });
+    }
+}
+
 function updateSessionInfo() {
     const pcCount = characters.filter(c => !c.is_npc).length;
     const npcCount = characters.filter(c => c.is_npc).length;
@@ -407,15 +995,65 @@ setInterval(fetchGameState, 5000);
 fetchGameState(); // Initial fetch
 
 // Roll Request Functions
+function previewRoll() {
+    const target = document.getElementById('roll-target').value;
+    const isReaction = document.getElementById('roll-reaction').checked;
+
+    ws.send('preview_roll_request', {
+        target_type: target === 'all' ? 'all' : 'specific',
+        target_character_ids: target === 'all' ? [] : [target],
+        roll_type: isReaction ? 'reaction' : 'action',
+        attribute: document.getElementById('roll-attribute').value || null,
+        difficulty: parseInt(document.getElementById('roll-difficulty').value),
+        situational_modifier: 0,
+        has_advantage: document.getElementById('roll-advantage').checked,
+        has_disadvantage: document.getElementById('roll-disadvantage').checked,
+    });
+}
+
+function handleRollPreview(payload) {
+    const panel = document.getElementById('roll-preview');
+    panel.innerHTML = payload.previews.map(p => `
+        <div style="margin-bottom: 0.25rem;">
+            <strong>${p.character_name}</strong>: ${p.total_modifier >= 0 ? '+' : ''}${p.total_modifier},
+            ${Math.round(p.success_chance * 100)}% to succeed, Hope ${p.hope}
+            ${p.conditions.length ? `<div style="color: var(--fear-color);">${p.conditions.join(' • ')}</div>` : ''}
+        </div>
+    `).join('');
+    panel.style.display = 'block';
+}
+
 function requestRoll() {
+    document.getElementById('roll-preview').style.display = 'none';
     const target = document.getElementById('roll-target').value;
     const attribute = document.getElementById('roll-attribute').value || null;
     const difficulty = parseInt(document.getElementById('roll-difficulty').value);
     const context = document.getElementById('roll-context').value || 'GM requested roll';
     const hasAdvantage = document.getElementById('roll-advantage').checked;
+    const hasDisadvantage = document.getElementById('roll-disadvantage').checked;
+    const isReaction = document.getElementById('roll-reaction').checked;
+    const isGroup = document.getElementById('roll-group').checked;
+    const hidden = document.getElementById('roll-hidden').checked;
     
     console.log('Requesting roll:', { target, attribute, difficulty, context, hasAdvantage });
     
+    if (isGroup) {
+        if (target === 'all') {
+            alert('Choose the character leading the group roll');
+            return;
+        }
+        ws.send('request_group_roll', {
+            leader_id: target,
+            helper_ids: characters.filter(c => !c.is_npc && c.id !== target).map(c => c.id),
+            attribute: attribute,
+            difficulty: difficulty,
+            context: context,
+            has_advantage: hasAdvantage,
+            has_disadvantage: hasDisadvantage,
+        });
+        return;
+    }
+    
     // Determine target type and IDs
     let targetType = 'all';
     let targetIds = [];
@@ -429,14 +1067,16 @@ function requestRoll() {
     ws.send('request_roll', {
         target_type: targetType,
         target_character_ids: targetIds,
-        roll_type: 'action',
+        roll_type: isReaction ? 'reaction' : 'action',
         attribute: attribute,
         difficulty: difficulty,
         context: context,
         narrative_stakes: null,
         situational_modifier: 0,
         has_advantage: hasAdvantage,
+        has_disadvantage: hasDisadvantage,
         is_combat: false,
+        hidden: hidden,
     });
     
     // Show status panel
@@ -449,6 +1089,14 @@ function requestRoll() {
     document.getElementById('roll-context').value = '';
 }
 
+// Roll dice only the GM sees, e.g. for a hidden ambush
+function secretRoll() {
+    const input = document.getElementById('secret-roll-expression');
+    const expression = input.value.trim();
+    if (!expression) return;
+    ws.send('roll_dice', { expression, label: null, hidden: true });
+}
+
 function updateRollStatus(status) {
     const completedList = document.getElementById('completed-list');
     const pendingList = document.getElementById('pending-list');
@@ -518,11 +1166,12 @@ function addEventToLog(event) {
     // Create event item
     const item = document.createElement('div');
     item.className = `event-item event-type-${event.event_type.toLowerCase().replace(/_/g, '-')}`;
+    if (event.cutaway) item.classList.add('event-cutaway');
     
     let html = `
         <div>
             <span class="event-timestamp">${event.timestamp}</span>
-            ${event.character_name ? `<span class="event-character">${event.character_name}:</span>` : ''}
+            ${event.character_name ? `<span class="event-character">${event.character_name}${event.player_name ? ` (${event.player_name})` : ''}:</span>` : ''}
             <span class="event-message">${event.message}</span>
         </div>
     `;
@@ -548,7 +1197,7 @@ function addEventToLog(event) {
 // Load event history on connection
 async function loadEventHistory() {
     try {
-        const response = await fetch('/api/events');
+        const response = await fetch('/api/events?limit=30');
         const data = await response.json();
         
         const eventLog = document.getElementById('event-log-gm');
@@ -558,9 +1207,7 @@ async function loadEventHistory() {
         eventLog.innerHTML = '';
         
         if (data.events && data.events.length > 0) {
-            // Show last 30 events
-            const recentEvents = data.events.slice(-30);
-            recentEvents.forEach(event => addEventToLog(event));
+            data.events.forEach(event => addEventToLog(event));
         } else {
             eventLog.innerHTML = '<p class="empty-state">No events yet...</p>';
         }
@@ -574,6 +1221,72 @@ async function loadEventHistory() {
 let adversaries = [];
 let spawnMode = false;
 let selectedAttackerId = null; // For click-to-attack combat
+let selectedAdversaryId = null; // Adversary about to attack a character
+
+// Populate the template selector (compendium plus homebrew) grouped by tier,
+// applying the search filters and keeping "Custom..." last
+async function loadAdversaryTemplates() {
+    try {
+        const params = new URLSearchParams();
+        const search = document.getElementById('adversary-search').value.trim();
+        const tier = document.getElementById('adversary-tier-filter').value;
+        const role = document.getElementById('adversary-role-filter').value;
+        if (search) params.set('q', search);
+        if (tier) params.set('tier', tier);
+        if (role) params.set('role', role);
+
+        const response = await fetch(`/api/adversaries?${params}`);
+        const data = await response.json();
+
+        const select = document.getElementById('adversary-template');
+        const selected = select.value;
+        select.innerHTML = '';
+
+        const groups = {};
+        data.templates.forEach(template => {
+            if (!groups[template.tier]) {
+                groups[template.tier] = document.createElement('optgroup');
+                groups[template.tier].label = `Tier ${template.tier}`;
+            }
+            const option = document.createElement('option');
+            option.value = template.id;
+            const roleName = template.role.charAt(0).toUpperCase() + template.role.slice(1);
+            option.textContent = `${template.name} - ${roleName} (HP: ${template.hp}, Evasion: ${template.evasion})`;
+            groups[template.tier].appendChild(option);
+        });
+        Object.keys(groups).sort().forEach(tier => select.appendChild(groups[tier]));
+
+        const custom = document.createElement('option');
+        custom.value = 'custom';
+        custom.textContent = 'Custom...';
+        select.appendChild(custom);
+
+        if ([...select.options].some(o => o.value === selected)) {
+            select.value = selected;
+        }
+    } catch (error) {
+        console.error('Failed to load adversary templates:', error);
+    }
+}
+
+// Re-read homebrew template files on the server
+async function reloadAdversaryTemplates() {
+    try {
+        const response = await fetch('/api/adversaries/reload', {
+            method: 'POST',
+            headers: apiHeaders()
+        });
+        const result = await response.json();
+
+        if (result.success) {
+            loadAdversaryTemplates();
+        } else {
+            alert(`❌ Reload failed: ${result.error}`);
+        }
+    } catch (error) {
+        console.error('Reload error:', error);
+    }
+}
 
 // Add adversary event listeners to setupEventListeners
 (function() {
@@ -581,6 +1294,15 @@ let selectedAttackerId = null; // For click-to-attack combat
     setupEventListeners = function() {
         originalSetup();
         
+        document.getElementById('reload-adversaries-btn').addEventListener('click', reloadAdversaryTemplates);
+        document.getElementById('renumber-adversaries-btn').addEventListener('click', () => {
+            ws.send('renumber_adversaries', {});
+        });
+        document.getElementById('create-npc-btn').addEventListener('click', createNpc);
+        document.getElementById('adversary-search').addEventListener('input', loadAdversaryTemplates);
+        document.getElementById('adversary-tier-filter').addEventListener('change', loadAdversaryTemplates);
+        document.getElementById('adversary-role-filter').addEventListener('change', loadAdversaryTemplates);
+
         // Adversary template selector
         document.getElementById('adversary-template').addEventListener('change', (e) => {
             const customPanel = document.getElementById('custom-adversary-panel');
@@ -612,14 +1334,75 @@ let selectedAttackerId = null; // For click-to-attack combat
         document.getElementById('add-pc-token').addEventListener('click', () => addTrackerToken('pc'));
         document.getElementById('add-adv-token').addEventListener('click', () => addTrackerToken('adversary'));
         document.getElementById('reset-tracker').addEventListener('click', resetTracker);
+        document.getElementById('spend-token-btn').addEventListener('click', () => ws.send('spend_tracker_token'));
+        document.getElementById('advance-hope-btn').addEventListener('click', () => {
+            ws.send('advance_tracker', { success_with_hope: true });
+        });
+        document.getElementById('advance-fear-btn').addEventListener('click', () => {
+            ws.send('advance_tracker', { success_with_hope: false });
+        });
+        document.getElementById('spend-fear-token-btn').addEventListener('click', () => {
+            ws.send('spend_fear', { spend: { kind: 'adversary_token' } });
+        });
+        document.getElementById('advance-round-btn').addEventListener('click', () => ws.send('advance_round'));
+        document.getElementById('end-round-btn').addEventListener('click', () => ws.send('end_round'));
+        document.getElementById('mark-scene-btn').addEventListener('click', () => {
+            const title = prompt('Scene title:', '');
+            if (title && title.trim()) {
+                ws.send('mark_scene', { title: title.trim() });
+            }
+        });
+        document.getElementById('combat-mode').addEventListener('change', (e) => {
+            ws.send('set_combat_mode', { mode: e.target.value });
+        });
+        document.querySelectorAll('[data-setting]').forEach(input => {
+            input.addEventListener('change', () => updateTableSetting(input));
+        });
+        fetch('/api/settings')
+            .then(response => response.json())
+            .then(data => showTableSettings(data.settings))
+            .catch(error => console.error('Failed to load table settings:', error));
+        document.getElementById('checkpoint-select').addEventListener('focus', loadCheckpoints);
+        document.getElementById('rollback-btn').addEventListener('click', rollbackToCheckpoint);
+        loadCheckpoints();
+        document.getElementById('spotlight-target').addEventListener('focus', renderSpotlightTargets);
+        document.getElementById('pass-spotlight-btn').addEventListener('click', passSpotlight);
+
+        document.getElementById('spawn-environment-btn').addEventListener('click', () => {
+            const template = document.getElementById('environment-template').value;
+            if (template) {
+                ws.send('spawn_environment', { template });
+            }
+        });
+
+        // Hazard placement
+        document.getElementById('place-hazard-btn').addEventListener('click', () => {
+            hazardMode = !hazardMode;
+            document.getElementById('place-hazard-btn').textContent =
+                hazardMode ? '❌ Cancel Placement' : '📍 Click Map to Place';
+        });
     };
 })();
 
 // Handle canvas click for spawning
 (function() {
     const originalHandleCanvasClick = window.handleCanvasClick || function() {};
-    window.handleCanvasClick = function(x, y) {
-        if (spawnMode) {
+    window.handleCanvasClick = function(x, y, e) {
+        if (measureMode) {
+            measureAtPosition(x, y);
+        } else if (pickTokenMode) {
+            pickToken(x, y);
+        } else if (moveTokenMode) {
+            moveTokenClick(x, y, e?.shiftKey);
+        } else if (drawMode) {
+            drawClick(x, y);
+        } else if (fogMode) {
+            fogClick(x, y);
+        } else if (hazardMode) {
+            placeHazardAtPosition(x, y);
+            hazardMode = false;
+            document.getElementById('place-hazard-btn').textContent = '📍 Click Map to Place';
+        } else if (spawnMode) {
             // Spawning adversaries
             spawnAdversaryAtPosition(x, y);
             spawnMode = false;
@@ -640,6 +1423,12 @@ let selectedAttackerId = null; // For click-to-attack combat
 function handleCombatClick(x, y) {
     // Check if clicked on a character
     const character = mapCanvas.getCharacterAtPosition(x, y);
+    if (character && selectedAdversaryId) {
+        // The selected adversary attacks
+        adversaryAttack(selectedAdversaryId, character.id);
+        selectedAdversaryId = null;
+        return;
+    }
     if (character) {
         // Select as attacker
         selectedAttackerId = character.id;
@@ -657,10 +1446,17 @@ function handleCombatClick(x, y) {
         rollAttack(selectedAttackerId, adversary.id);
         return;
     }
+    if (adversary) {
+        // Select the adversary to attack a character
+        selectedAdversaryId = adversary.id;
+        showCombatFeedback(`Selected: ${adversary.name}. Click a character to attack!`);
+        return;
+    }
     
     // Clicked empty space - clear selection
     if (!character && !adversary) {
         selectedAttackerId = null;
+        selectedAdversaryId = null;
         mapCanvas.clearSelectedAttacker();
         hideCombatFeedback();
     }
@@ -687,11 +1483,32 @@ function rollAttack(attackerId, targetId) {
     });
 }
 
-function showCombatFeedback(message) {
-    const feedback = document.getElementById('combat-feedback');
-    if (feedback) {
-        feedback.textContent = message;
-        feedback.style.display = 'block';
+function adversaryAttack(adversaryId, targetId) {
+    const adversary = adversaries.find(a => a.id === adversaryId);
+    const target = characters.find(c => c.id === targetId);
+    if (!adversary || !target) return;
+    
+    showCombatFeedback(`${adversary.name} attacks ${target.name}...`);
+    ws.send('adversary_attack', {
+        adversary_id: adversaryId,
+        target_id: targetId,
+    });
+}
+
+function handleAdversaryAttackResult(payload) {
+    const { adversary_name, target_name, die, modifier, total, target_evasion, hit, is_critical, narration } = payload;
+    const outcome = is_critical ? '🌟 CRITICAL!' : hit ? '✅ HIT!' : '❌ MISS!';
+    showCombatFeedback(
+        `${adversary_name} → ${target_name}: d20 ${die} ${modifier >= 0 ? '+' : ''}${modifier} = ${total} vs Evasion ${target_evasion} ${outcome}` +
+        (narration ? ` ${narration}` : '')
+    );
+}
+
+function showCombatFeedback(message) {
+    const feedback = document.getElementById('combat-feedback');
+    if (feedback) {
+        feedback.textContent = message;
+        feedback.style.display = 'block';
     }
 }
 
@@ -743,13 +1560,16 @@ function handleAdversarySpawned(payload) {
         adversary_id,
         name,
         template,
+        tier,
+        role,
         position,
         hp,
         max_hp,
         evasion,
         armor,
         attack_modifier,
-        damage_dice
+        damage_dice,
+        motives_and_tactics
     } = payload;
     
     // Add to local list
@@ -757,6 +1577,8 @@ function handleAdversarySpawned(payload) {
         id: adversary_id,
         name,
         template,
+        tier,
+        role,
         position,
         hp,
         max_hp,
@@ -764,12 +1586,13 @@ function handleAdversarySpawned(payload) {
         armor,
         attack_modifier,
         damage_dice,
+        motives_and_tactics,
         is_active: true
     });
     
     // Render on canvas
     if (mapCanvas) {
-        mapCanvas.drawAdversary(adversary_id, name, position.x, position.y);
+        mapCanvas.drawAdversary(adversary_id, name, position.x, position.y, payload.token);
     }
     
     // Update adversary list
@@ -839,7 +1662,15 @@ function renderAdversariesList() {
                     <div class="adversary-stat">Stress: <strong>${adv.stress || 0}/${adv.max_hp}</strong></div>
                     <div class="adversary-stat">Evasion: <strong>${adv.evasion}</strong></div>
                     <div class="adversary-stat">Armor: <strong>${adv.armor}</strong></div>
+                    ${adv.role ? `<div class="adversary-stat">Tier ${adv.tier} <strong>${adv.role}</strong></div>` : ''}
                 </div>
+                ${resourceButtons(adv.id, ['hp', 'stress'])}
+                ${adv.is_active ? `<div style="display: flex; gap: 0.25rem; margin-top: 0.25rem;">
+                    <button class="btn-small" onclick="markAdversaryStress('${adv.id}', 'extra_damage_die')" title="Mark a Stress: next damage roll adds a die">💢 +Die</button>
+                    <button class="btn-small" onclick="markAdversaryStress('${adv.id}', 'act_again')" title="Mark a Stress to act again">💢 Again</button>
+                    <button class="btn-small" onclick="spendFearToInterrupt('${adv.id}')" title="Spend a Fear to act now">😈 Interrupt</button>
+                </div>` : ''}
+                ${adv.motives_and_tactics ? `<div class="adversary-stat" style="margin-top: 0.25rem;">🎯 <em>${adv.motives_and_tactics}</em></div>` : ''}
                 <div style="background: var(--bg-dark); height: 4px; border-radius: 2px; overflow: hidden; margin-top: 0.5rem;">
                     <div style="height: 100%; background: var(--hope-color); width: ${hpPercent}%;"></div>
                 </div>
@@ -850,6 +1681,31 @@ function renderAdversariesList() {
     listEl.innerHTML = html;
 }
 
+// Motives & tactics of the adversaries that may act now
+function showTacticsReminder(tactics) {
+    const reminder = document.getElementById('tactics-reminder');
+    if (tactics.length === 0) {
+        reminder.style.display = 'none';
+        return;
+    }
+    reminder.innerHTML = '<strong>🎯 Motives & Tactics</strong>' + tactics
+        .map(t => `<div><strong>${t.name}:</strong> ${t.motives_and_tactics}</div>`)
+        .join('');
+    reminder.style.display = 'block';
+}
+
+function markAdversaryStress(adversaryId, kind) {
+    ws.send('mark_adversary_stress', {
+        adversary_id: adversaryId,
+        stress: 1,
+        stress_move: { kind }
+    });
+}
+
+function spendFearToInterrupt(adversaryId) {
+    ws.send('spend_fear', { spend: { kind: 'interrupt', adversary_id: adversaryId } });
+}
+
 function removeAdversary(adversaryId) {
     if (confirm('Remove this adversary?')) {
         ws.send('remove_adversary', {
@@ -858,6 +1714,661 @@ function removeAdversary(adversaryId) {
     }
 }
 
+// ===== Checkpoints =====
+
+async function loadCheckpoints() {
+    try {
+        const response = await fetch('/api/checkpoints');
+        const data = await response.json();
+        const select = document.getElementById('checkpoint-select');
+        const selected = select.value;
+
+        // Newest first
+        select.innerHTML = data.checkpoints.length
+            ? data.checkpoints.slice().reverse()
+                .map(c => `<option value="${c.id}">${c.created_at} · ${c.label}</option>`)
+                .join('')
+            : '<option value="">No checkpoints</option>';
+        if (data.checkpoints.some(c => c.id === selected)) {
+            select.value = selected;
+        }
+    } catch (error) {
+        console.error('Failed to load checkpoints:', error);
+    }
+}
+
+function rollbackToCheckpoint() {
+    const select = document.getElementById('checkpoint-select');
+    if (!select.value) {
+        alert('No checkpoint to roll back to');
+        return;
+    }
+    const label = select.options[select.selectedIndex].textContent;
+    if (confirm(`Roll the whole game back to ${label}?`)) {
+        ws.send('rollback_to_checkpoint', { checkpoint_id: select.value });
+    }
+}
+
+// ===== Next Session =====
+
+async function loadNextSession() {
+    try {
+        const response = await fetch('/api/next-session');
+        const data = await response.json();
+        showNextSession(data.next_session);
+    } catch (error) {
+        console.error('Failed to load next session:', error);
+    }
+}
+
+function showNextSession(session) {
+    const summary = document.getElementById('next-session-summary');
+    if (!session) {
+        summary.textContent = 'Not scheduled';
+        return;
+    }
+    const when = new Date(session.scheduled_at).toLocaleString([], { dateStyle: 'full', timeStyle: 'short' });
+    summary.textContent = session.notes ? `${when} · ${session.notes}` : when;
+}
+
+// A checkbox per player character, keeping what was ticked
+function renderCheckboxes(containerId) {
+    const container = document.getElementById(containerId);
+    const checked = new Set([...container.querySelectorAll('input:checked')].map(box => box.value));
+
+    container.innerHTML = '';
+    characters.filter(c => !c.is_npc).forEach(c => {
+        const label = document.createElement('label');
+        label.style.display = 'block';
+        const box = document.createElement('input');
+        box.type = 'checkbox';
+        box.value = c.id;
+        box.checked = checked.has(c.id);
+        label.append(box, ` ${c.name}`);
+        container.appendChild(label);
+    });
+}
+
+function startCutaway() {
+    const characterIds = [...document.querySelectorAll('#cutaway-characters input:checked')].map(box => box.value);
+    if (characterIds.length === 0) {
+        alert('Pick the characters in the scene');
+        return;
+    }
+    ws.send('start_cutaway', {
+        character_ids: characterIds,
+        label: document.getElementById('cutaway-label').value,
+    });
+}
+
+async function uploadMap() {
+    const file = document.getElementById('map-file').files[0];
+    if (!file) {
+        alert('Pick a map image');
+        return;
+    }
+    const form = new FormData();
+    form.append('scene', document.getElementById('map-scene').value);
+    form.append('file', file);
+
+    // No Content-Type: the browser sets the multipart boundary
+    const headers = apiToken ? { 'X-Api-Token': apiToken } : {};
+    try {
+        const response = await fetch('/api/maps', { method: 'POST', headers, body: form });
+        const result = await response.json();
+        if (!result.success) {
+            alert(`Failed to upload map: ${result.error}`);
+            return;
+        }
+        document.getElementById('map-scene').value = '';
+        document.getElementById('map-file').value = '';
+        loadMaps();
+    } catch (error) {
+        alert(`Failed to upload map: ${error.message}`);
+    }
+}
+
+// Fill the map picker with every uploaded map
+async function loadMaps() {
+    try {
+        const response = await fetch('/api/maps');
+        const data = await response.json();
+        const select = document.getElementById('map-select');
+        const selected = select.value;
+        select.querySelectorAll('option[value]:not([value=""])').forEach(o => o.remove());
+        data.maps.forEach(map => {
+            const option = document.createElement('option');
+            option.value = map.id;
+            option.textContent = map.scene;
+            select.appendChild(option);
+        });
+        select.value = selected;
+    } catch (error) {
+        console.error('Failed to load maps:', error);
+    }
+}
+
+function showMap(map) {
+    mapCanvas.setBackground(map?.url);
+    const select = document.getElementById('map-select');
+    if (map && ![...select.options].some(o => o.value === map.id)) {
+        loadMaps().then(() => { select.value = map.id; });
+    } else {
+        select.value = map ? map.id : '';
+    }
+}
+
+// Send the scene size and grid from the map panel
+function applySceneLayout() {
+    const number = id => parseFloat(document.getElementById(id).value) || 0;
+    const kind = document.getElementById('layout-grid-kind').value;
+    ws.send('set_scene_layout', {
+        layout: {
+            width: number('layout-width'),
+            height: number('layout-height'),
+            units_per_foot: number('layout-units-per-foot'),
+            grid: kind ? {
+                kind,
+                cell_size: number('layout-cell-size'),
+                offset_x: number('layout-offset-x'),
+                offset_y: number('layout-offset-y'),
+            } : null,
+        },
+    });
+}
+
+function showSceneLayout(layout) {
+    mapCanvas.setLayout(layout);
+    document.getElementById('layout-width').value = layout.width;
+    document.getElementById('layout-height').value = layout.height;
+    document.getElementById('layout-grid-kind').value = layout.grid ? layout.grid.kind : '';
+    document.getElementById('layout-units-per-foot').value = layout.units_per_foot;
+    if (layout.grid) {
+        document.getElementById('layout-cell-size').value = layout.grid.cell_size;
+        document.getElementById('layout-offset-x').value = layout.grid.offset_x;
+        document.getElementById('layout-offset-y').value = layout.grid.offset_y;
+    }
+}
+
+// ===== Range =====
+
+let measureMode = false;
+let measureFromId = null; // First token clicked while measuring
+
+const RANGE_BANDS = {
+    melee: 'Melee',
+    very_close: 'Very Close',
+    close: 'Close',
+    far: 'Far',
+    very_far: 'Very Far',
+};
+
+function toggleMeasureMode() {
+    measureMode = !measureMode;
+    measureFromId = null;
+    document.getElementById('measure-range-btn').textContent =
+        measureMode ? '❌ Cancel Measuring' : '📏 Measure Range';
+    if (measureMode) showCombatFeedback('📏 Click the token to measure from');
+    else hideCombatFeedback();
+}
+
+// Click two tokens; the server measures at the scene's scale
+function measureAtPosition(x, y) {
+    const token = mapCanvas.getCharacterAtPosition(x, y) || mapCanvas.getAdversaryAtPosition(x, y);
+    if (!token) return;
+    if (!measureFromId) {
+        measureFromId = token.id;
+        showCombatFeedback(`📏 From ${token.name}: click the token to measure to`);
+        return;
+    }
+    ws.send('measure_range', { from_id: measureFromId, to_id: token.id });
+    toggleMeasureMode();
+}
+
+function showRangeMeasured({ from_id, to_id, feet, band }) {
+    const name = id => characters.find(c => c.id === id)?.name
+        || adversaries.find(a => a.id === id)?.name
+        || 'Unknown';
+    const range = band ? RANGE_BANDS[band] : 'out of range';
+    showCombatFeedback(`📏 ${name(from_id)} → ${name(to_id)}: ${Math.round(feet)} ft (${range})`);
+}
+
+// ===== Fog of War =====
+
+let fogMode = null; // 'reveal' while clicking out an area, 'hide' to pick one
+let revealPoints = [];
+
+// First press starts an area; clicks add corners; second press reveals it
+function toggleRevealMode() {
+    if (fogMode === 'reveal' && revealPoints.length >= 3) {
+        ws.send('reveal_area', { points: revealPoints });
+    }
+    fogMode = fogMode === 'reveal' ? null : 'reveal';
+    revealPoints = [];
+    updateFogButtons();
+}
+
+function updateFogButtons() {
+    const revealing = fogMode === 'reveal';
+    document.getElementById('reveal-area-btn').textContent = revealing
+        ? `✅ Reveal (${revealPoints.length} corners)`
+        : 'Reveal Area';
+    document.getElementById('hide-area-btn').textContent =
+        fogMode === 'hide' ? '❌ Cancel' : 'Hide Area';
+    if (revealing) showCombatFeedback('🌫️ Click the corners of the area to reveal');
+    else if (fogMode === 'hide') showCombatFeedback('🌫️ Click inside a revealed area to hide it');
+    else hideCombatFeedback();
+}
+
+function fogClick(x, y) {
+    if (fogMode === 'reveal') {
+        revealPoints.push({ x, y });
+        updateFogButtons();
+        return;
+    }
+    const area = (mapCanvas.fog?.revealed || []).find(area => mapCanvas.areaContains(area, x, y));
+    if (area) ws.send('hide_area', { area_id: area.id });
+    fogMode = null;
+    updateFogButtons();
+}
+
+// The GM sees through the fog, dimly
+function showFog(fog) {
+    mapCanvas.setFog(fog, 0.45);
+    document.getElementById('fog-enabled').checked = fog.enabled;
+}
+
+// ===== Token Details =====
+
+let pickTokenMode = false;
+let tokenEditId = null; // Token the form is editing
+
+function pickToken(x, y) {
+    const token = mapCanvas.getCharacterAtPosition(x, y) || mapCanvas.getAdversaryAtPosition(x, y);
+    if (!token) return;
+    tokenEditId = token.id;
+    pickTokenMode = false;
+    document.getElementById('pick-token-btn').textContent = '🎯 Edit Token';
+    document.getElementById('token-edit-name').textContent = token.name;
+    document.getElementById('token-name').value = token.name;
+    hideCombatFeedback();
+    const owner = mapCanvas.players.get(token.id) || mapCanvas.adversaryPositions.get(token.id);
+    fillTokenForm(owner?.token || {});
+}
+
+function fillTokenForm(token) {
+    document.getElementById('token-size').value = token.size || 'normal';
+    document.getElementById('token-rotation').value = Math.round(token.rotation || 0);
+    document.getElementById('token-elevation').value = token.elevation || 0;
+    document.getElementById('token-note').value = token.note || '';
+}
+
+function applyTokenDetails() {
+    if (!tokenEditId) return;
+    const elevation = parseInt(document.getElementById('token-elevation').value, 10) || 0;
+    const name = document.getElementById('token-name').value.trim();
+    ws.send('edit_token', {
+        token_id: tokenEditId,
+        name: name || null,
+        token: {
+            size: document.getElementById('token-size').value,
+            rotation: parseFloat(document.getElementById('token-rotation').value) || 0,
+            elevation: elevation === 0 ? null : elevation,
+            note: document.getElementById('token-note').value.trim() || null,
+        },
+    });
+}
+
+function handleTokenRenamed(payload) {
+    const { token_id, name } = payload;
+    mapCanvas.renameToken(token_id, name);
+    const character = characters.find(c => c.id === token_id);
+    if (character) {
+        character.name = name;
+        renderCharactersList();
+    }
+    const adversary = adversaries.find(adv => adv.id === token_id);
+    if (adversary) {
+        adversary.name = name;
+        renderAdversariesList();
+    }
+    if (token_id === tokenEditId) {
+        document.getElementById('token-edit-name').textContent = name;
+    }
+}
+
+// ===== Moving Tokens =====
+
+// null, 'pick' while choosing a token, or the id of the token to move
+let moveTokenMode = null;
+
+function toggleMoveTokenMode() {
+    moveTokenMode = moveTokenMode ? null : 'pick';
+    document.getElementById('move-token-btn').textContent =
+        moveTokenMode ? '❌ Cancel' : '✋ Move Token';
+    if (moveTokenMode) showCombatFeedback('✋ Click a token to move');
+    else hideCombatFeedback();
+}
+
+// First click picks any token, the second puts it down (shift to teleport)
+function moveTokenClick(x, y, teleport) {
+    if (moveTokenMode === 'pick') {
+        const token = mapCanvas.getCharacterAtPosition(x, y) || mapCanvas.getAdversaryAtPosition(x, y);
+        if (!token) return;
+        moveTokenMode = token.id;
+        showCombatFeedback(`✋ Click where ${token.name} goes (hold Shift to teleport)`);
+        return;
+    }
+    ws.send('move_token', {
+        token_id: moveTokenMode,
+        position: { x, y },
+        teleport: !!teleport,
+    });
+    toggleMoveTokenMode();
+}
+
+// ===== Drawings =====
+
+let drawMode = null; // 'draw' while placing points, 'erase' to pick a drawing
+let drawPoints = [];
+
+// First press starts a drawing; clicks add points; second press finishes it.
+// Circles finish themselves: centre, then a point on the edge.
+function toggleDrawMode() {
+    if (drawMode === 'draw') {
+        sendDrawing(draftShape());
+    }
+    drawMode = drawMode === 'draw' ? null : 'draw';
+    drawPoints = [];
+    updateDrawButtons();
+}
+
+function draftShape() {
+    const kind = document.getElementById('drawing-shape').value;
+    if (kind === 'circle') {
+        if (drawPoints.length < 2) return null;
+        const [center, edge] = drawPoints;
+        return { kind, center, radius: Math.hypot(edge.x - center.x, edge.y - center.y) };
+    }
+    const needed = kind === 'zone' ? 3 : 2;
+    return drawPoints.length >= needed ? { kind, points: drawPoints } : null;
+}
+
+function sendDrawing(shape) {
+    if (!shape) return;
+    ws.send('add_drawing', {
+        shape,
+        color: document.getElementById('drawing-color').value,
+        label: document.getElementById('drawing-label').value.trim(),
+    });
+}
+
+function updateDrawButtons() {
+    const drawing = drawMode === 'draw';
+    document.getElementById('draw-btn').textContent = drawing
+        ? `✅ Finish (${drawPoints.length} points)`
+        : '✏️ Draw';
+    document.getElementById('erase-drawing-btn').textContent =
+        drawMode === 'erase' ? '❌ Cancel' : 'Erase';
+    mapCanvas.setDraft(drawing && drawPoints.length > 1
+        ? { shape: { kind: 'line', points: drawPoints }, color: document.getElementById('drawing-color').value }
+        : null);
+    if (drawing) showCombatFeedback('✏️ Click the map to draw; a circle is its centre, then its edge');
+    else if (drawMode === 'erase') showCombatFeedback('✏️ Click a drawing to erase it');
+    else hideCombatFeedback();
+}
+
+function drawClick(x, y) {
+    if (drawMode === 'draw') {
+        drawPoints.push({ x, y });
+        if (document.getElementById('drawing-shape').value === 'circle' && drawPoints.length === 2) {
+            toggleDrawMode();
+            return;
+        }
+        updateDrawButtons();
+        return;
+    }
+    const drawing = [...mapCanvas.drawings].reverse().find(d => drawingContains(d.shape, x, y));
+    if (drawing) ws.send('remove_drawing', { drawing_id: drawing.id });
+    drawMode = null;
+    updateDrawButtons();
+}
+
+// Inside a circle or zone, or within a few units of a line
+function drawingContains(shape, x, y) {
+    if (shape.kind === 'circle') {
+        return Math.hypot(x - shape.center.x, y - shape.center.y) <= shape.radius;
+    }
+    if (shape.kind === 'zone') return mapCanvas.areaContains(shape, x, y);
+    return shape.points.slice(1).some((b, i) => {
+        const a = shape.points[i];
+        const length = Math.hypot(b.x - a.x, b.y - a.y) || 1;
+        const t = Math.max(0, Math.min(1, ((x - a.x) * (b.x - a.x) + (y - a.y) * (b.y - a.y)) / length ** 2));
+        return Math.hypot(x - (a.x + t * (b.x - a.x)), y - (a.y + t * (b.y - a.y))) <= 8;
+    });
+}
+
+// Send the one setting that changed
+function updateTableSetting(input) {
+    let value = input.value;
+    if (input.type === 'checkbox') value = input.checked;
+    if (input.type === 'number') value = parseInt(input.value, 10);
+    ws.send('update_table_settings', { settings: { [input.dataset.setting]: value } });
+}
+
+function showTableSettings(settings) {
+    document.querySelectorAll('[data-setting]').forEach(input => {
+        const value = settings[input.dataset.setting];
+        if (input.type === 'checkbox') {
+            input.checked = value;
+        } else {
+            input.value = value;
+        }
+    });
+}
+
+function shareHandout() {
+    const title = document.getElementById('handout-title').value.trim();
+    const kind = document.getElementById('handout-kind').value;
+    if (!title) {
+        alert('Give the handout a title');
+        return;
+    }
+
+    const send = (content) => {
+        ws.send('share_handout', {
+            title,
+            kind,
+            content,
+            character_ids: [...document.querySelectorAll('#handout-characters input:checked')].map(box => box.value),
+        });
+        document.getElementById('handout-title').value = '';
+        document.getElementById('handout-text').value = '';
+        document.getElementById('handout-file').value = '';
+    };
+
+    if (kind !== 'image') {
+        send(document.getElementById('handout-text').value);
+        return;
+    }
+    const file = document.getElementById('handout-file').files[0];
+    if (!file) {
+        alert('Pick an image');
+        return;
+    }
+    const reader = new FileReader();
+    reader.onload = () => send(reader.result);
+    reader.readAsDataURL(file);
+}
+
+function showCutaway(cutaway) {
+    const status = document.getElementById('cutaway-status');
+    if (!cutaway) {
+        status.textContent = 'With the whole table';
+        return;
+    }
+    const scene = cutaway.label ? ` · ${cutaway.label}` : '';
+    status.textContent = `🎬 With ${cutaway.character_names.join(', ')}${scene}`;
+}
+
+function scheduleNextSession() {
+    const value = document.getElementById('next-session-at').value;
+    if (!value) {
+        alert('Pick a date and time');
+        return;
+    }
+    ws.send('schedule_next_session', {
+        scheduled_at: toRfc3339(new Date(value)),
+        notes: document.getElementById('next-session-notes').value,
+    });
+}
+
+// Local time with this browser's UTC offset, e.g. 2026-11-07T19:00:00-05:00
+function toRfc3339(date) {
+    const pad = n => String(Math.floor(Math.abs(n))).padStart(2, '0');
+    const offset = -date.getTimezoneOffset();
+    const sign = offset >= 0 ? '+' : '-';
+    return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}` +
+        `T${pad(date.getHours())}:${pad(date.getMinutes())}:00` +
+        `${sign}${pad(offset / 60)}:${pad(offset % 60)}`;
+}
+
+// ===== Environments =====
+
+let environments = {}; // environment_id -> environment info
+
+async function loadEnvironmentTemplates() {
+    try {
+        const response = await fetch('/api/environments');
+        const data = await response.json();
+
+        const select = document.getElementById('environment-template');
+        select.innerHTML = '';
+        data.templates.forEach(template => {
+            const option = document.createElement('option');
+            option.value = template.id;
+            option.textContent = `Tier ${template.tier} ${template.name} (${template.kind}, Difficulty ${template.difficulty})`;
+            select.appendChild(option);
+        });
+    } catch (error) {
+        console.error('Failed to load environment templates:', error);
+    }
+}
+
+function activateEnvironmentFeature(environmentId, feature) {
+    ws.send('activate_environment_feature', {
+        environment_id: environmentId,
+        feature
+    });
+}
+
+function renderEnvironmentsList() {
+    const listEl = document.getElementById('environments-list');
+    const list = Object.values(environments);
+
+    if (list.length === 0) {
+        listEl.innerHTML = '<p style="text-align: center; color: var(--text-secondary); font-size: 0.85rem;">No environment</p>';
+        return;
+    }
+
+    listEl.innerHTML = list.map(env => {
+        const features = env.features.map(feature => {
+            const cost = feature.fear_cost > 0 ? ` (${feature.fear_cost} Fear)` : '';
+            const button = feature.kind === 'passive'
+                ? ''
+                : `<button class="btn-small" onclick='activateEnvironmentFeature("${env.id}", ${JSON.stringify(feature.name)})'>Activate${cost}</button>`;
+            return `<div class="adversary-stat" title="${feature.description.replace(/"/g, '&quot;')}"><strong>${feature.name}</strong> <em>${feature.kind}</em> ${button}</div>`;
+        }).join('');
+
+        return `
+            <div class="adversary-item" data-id="${env.id}">
+                <h5>
+                    ${env.name}
+                    <button onclick="ws.send('remove_environment', { environment_id: '${env.id}' })" class="btn-small">🗑️</button>
+                </h5>
+                <div class="adversary-stats">
+                    <div class="adversary-stat">Tier ${env.tier} <strong>${env.kind}</strong></div>
+                    <div class="adversary-stat">Difficulty: <strong>${env.difficulty}</strong></div>
+                </div>
+                <p style="font-size: 0.8rem; margin: 0.25rem 0;">${env.description}</p>
+                ${features}
+                <p style="font-size: 0.75rem; color: var(--text-secondary);">Adversaries: ${env.potential_adversaries.join(', ')}</p>
+            </div>
+        `;
+    }).join('');
+}
+
+// ===== Hazards =====
+
+let hazards = {}; // hazard_id -> { hazard, active, ticks_remaining }
+let hazardMode = false;
+
+function placeHazardAtPosition(x, y) {
+    const value = id => document.getElementById(id).value;
+    const name = value('hazard-name').trim() || 'Hazard';
+
+    const cadence = value('hazard-cadence') === 'countdown'
+        ? { type: 'countdown', ticks: parseInt(value('hazard-ticks')) || 1 }
+        : { type: 'each_round' };
+    const outcome = value('hazard-outcome') === 'damage'
+        ? { type: 'damage', dice: value('hazard-dice') }
+        : {
+            type: 'roll',
+            attribute: value('hazard-attribute'),
+            difficulty: parseInt(value('hazard-difficulty')) || 10
+        };
+
+    ws.send('add_hazard', {
+        hazard: {
+            name,
+            area: { center: { x, y }, radius: parseFloat(value('hazard-radius')) || 100 },
+            cadence,
+            outcome
+        }
+    });
+}
+
+function handleHazardUpdated(payload) {
+    const { hazard_id, hazard, active, ticks_remaining } = payload;
+    const existing = hazards[hazard_id] || {};
+    hazards[hazard_id] = {
+        hazard: hazard || existing.hazard,
+        active,
+        ticks_remaining
+    };
+    renderHazardsList();
+}
+
+function renderHazardsList() {
+    const listEl = document.getElementById('hazards-list');
+    const entries = Object.entries(hazards);
+
+    if (entries.length === 0) {
+        listEl.innerHTML = '<p style="text-align: center; color: var(--text-secondary); font-size: 0.85rem;">No hazards</p>';
+        return;
+    }
+
+    listEl.innerHTML = entries.map(([id, { hazard, active, ticks_remaining }]) => {
+        const cadence = hazard.cadence.type === 'countdown'
+            ? `${ticks_remaining}/${hazard.cadence.ticks} ticks`
+            : 'each round';
+        return `
+            <div class="adversary-item" data-id="${id}">
+                <h5>
+                    ${active ? '⚠️' : '💤'} ${hazard.name}
+                    <button onclick="ws.send('set_hazard_active', { hazard_id: '${id}', active: ${!active} })" class="btn-small">${active ? 'Off' : 'On'}</button>
+                    <button onclick="ws.send('tick_hazard', { hazard_id: '${id}' })" class="btn-small">Tick</button>
+                    <button onclick="ws.send('remove_hazard', { hazard_id: '${id}' })" class="btn-small">🗑️</button>
+                </h5>
+                <div class="adversary-stats">
+                    <div class="adversary-stat">${cadence}</div>
+                    <div class="adversary-stat">${hazard.outcome.type === 'damage' ? hazard.outcome.dice : `${hazard.outcome.attribute} ${hazard.outcome.difficulty}`}</div>
+                </div>
+            </div>
+        `;
+    }).join('');
+}
+
 // ===== Combat Management =====
 
 let combatActive = false;
@@ -883,6 +2394,8 @@ function handleCombatStarted(payload) {
     document.getElementById('start-combat-btn').style.display = 'none';
     document.getElementById('end-combat-btn').style.display = 'block';
     document.getElementById('combat-controls').style.display = 'block';
+    document.getElementById('combat-round').textContent = 1;
+    setCombatMode('action_tracker');
     
     // Update tracker display
     document.getElementById('pc-tokens').textContent = pc_tokens;
@@ -894,6 +2407,69 @@ function handleCombatStarted(payload) {
     console.log(`⚔️ Combat started! Encounter: ${encounter_id}`);
 }
 
+// Restore the combat panel, scene and hazards after a reconnect
+function handleCatchUp(snapshot) {
+    showMap(snapshot.map);
+    showSceneLayout(snapshot.layout);
+    showFog(snapshot.fog);
+    mapCanvas.setDrawings(snapshot.drawings);
+    document.getElementById('map-locked').checked = snapshot.map_locked;
+
+    environments = {};
+    snapshot.environments.forEach(env => environments[env.id] = env);
+    renderEnvironmentsList();
+
+    hazards = {};
+    snapshot.hazards.forEach(h => handleHazardUpdated(h));
+    renderHazardsList();
+
+    showCutaway(snapshot.cutaway);
+    showQuests(snapshot.quests);
+
+    const combat = snapshot.combat;
+    if (!combat) return;
+
+    handleCombatStarted(combat);
+    setCombatMode(combat.mode);
+    handleTrackerUpdated({ ...combat, next_token: combat.queue[0] });
+    if (combat.spotlight) {
+        const holder = characters.find(c => c.id === combat.spotlight)
+            || adversaries.find(a => a.id === combat.spotlight);
+        if (holder) document.getElementById('spotlight-holder').textContent = holder.name;
+    }
+}
+
+function setCombatMode(mode) {
+    const spotlight = mode === 'spotlight';
+    document.getElementById('combat-mode').value = mode;
+    document.getElementById('spotlight-controls').style.display = spotlight ? 'block' : 'none';
+    document.getElementById('tracker-controls').style.display = spotlight ? 'none' : 'block';
+    document.getElementById('spotlight-holder').textContent = 'GM';
+    renderSpotlightTargets();
+}
+
+function renderSpotlightTargets() {
+    const select = document.getElementById('spotlight-target');
+    const selected = select.value;
+    const options = [{ id: '', name: 'GM' }]
+        .concat(characters.map(c => ({ id: c.id, name: c.name })))
+        .concat(adversaries.filter(a => a.is_active).map(a => ({ id: a.id, name: `${a.name} (adversary)` })));
+
+    select.innerHTML = '';
+    options.forEach(o => {
+        const option = document.createElement('option');
+        option.value = o.id;
+        option.textContent = o.name;
+        select.appendChild(option);
+    });
+    select.value = options.some(o => o.id === selected) ? selected : '';
+}
+
+function passSpotlight() {
+    const to = document.getElementById('spotlight-target').value;
+    ws.send('pass_spotlight', { to: to || null });
+}
+
 function handleCombatEnded(payload) {
     const { reason } = payload;
     
@@ -913,10 +2489,14 @@ function handleCombatEnded(payload) {
 }
 
 function handleTrackerUpdated(payload) {
-    const { pc_tokens, adversary_tokens, next_token } = payload;
+    const { pc_tokens, adversary_tokens, next_token, queue, round } = payload;
     
     document.getElementById('pc-tokens').textContent = pc_tokens;
     document.getElementById('adv-tokens').textContent = adversary_tokens;
+    document.getElementById('combat-round').textContent = round;
+    document.getElementById('tracker-queue').textContent = queue
+        .map(token => token === 'pc' ? '🧝' : '👹')
+        .join(' ');
     
     console.log(`🎲 Tracker updated: PC ${pc_tokens}, Adversary ${adversary_tokens}, Next: ${next_token}`);
 }
@@ -937,7 +2517,7 @@ function resetTracker() {
 let lastAttackResult = null;
 
 function handleAttackResult(payload) {
-    const { attacker_name, target_name, hope, fear, total, target_evasion, hit, controlling_die, is_critical } = payload;
+    const { attacker_name, target_name, hope, fear, total, target_evasion, hit, controlling_die, is_critical, narration } = payload;
     
     console.log('⚔️ Attack result:', payload);
     lastAttackResult = payload;
@@ -956,11 +2536,14 @@ function handleAttackResult(payload) {
             </div>
         </div>
         <p style="font-size: 1.2rem;">Total: <strong>${total}</strong> vs Evasion <strong>${target_evasion}</strong></p>
+        ${payload.advantage_die ? `<p>✨ Advantage die: +${payload.advantage_die}</p>` : ''}
+        ${payload.disadvantage_die ? `<p>⚠️ Disadvantage die: −${payload.disadvantage_die}</p>` : ''}
         <p style="font-size: 1.3rem; font-weight: bold; color: ${hit ? '#2ecc71' : '#e74c3c'};">
             ${hit ? '✅ HIT!' : '❌ MISS!'}
             ${is_critical ? ' 🌟 CRITICAL!' : ''}
         </p>
-        <p style="font-style: italic;">Controlling Die: ${controlling_die === 'hope' ? '🔵 Hope' : '🔴 Fear'}</p>
+        <p style="font-style: italic;">Controlling Die: ${{ hope: '🔵', fear: '🔴', tied: '⚪' }[controlling_die]} ${t(`controlling_die.${controlling_die}`)}</p>
+        ${narration ? `<p style="font-style: italic; color: var(--accent);">${narration}</p>` : ''}
     `;
     
     // Show overlay
@@ -986,20 +2569,45 @@ function handleAttackResult(payload) {
 }
 
 function handleDamageResult(payload) {
-    const { target_name, raw_damage, after_armor, hp_lost, stress_gained, new_hp, new_stress, taken_out } = payload;
+    const { target_name, raw_damage, is_critical, critical_bonus, dice = [], damage_type, adjustment, adjusted_damage, after_armor, hp_lost, stress_gained, new_hp, new_stress, taken_out, narration } = payload;
     
     console.log('💥 Damage result:', payload);
     
+    // e.g. "magical (fire)"
+    const typeLabel = damage_type
+        ? `${damage_type.kind}${damage_type.elements.length ? ` (${damage_type.elements.join(', ')})` : ''}`
+        : 'physical';
+    const adjustmentLabels = {
+        halved: '🛡️ Resisted: halved',
+        doubled: '🎯 Vulnerable: doubled',
+        immune: '🚫 Immune'
+    };
+    const adjustmentHTML = adjustmentLabels[adjustment]
+        ? `<p style="font-size: 1.1rem;">${adjustmentLabels[adjustment]} → <strong>${adjusted_damage}</strong></p>`
+        : '';
+    
+    // One chip per die; dropped dice (kh/kl) are struck through
+    const diceHTML = dice.length
+        ? `<p class="damage-dice">${dice.map(d => `
+            <span class="damage-die${d.kept ? '' : ' dropped'}" title="d${d.sides}${d.explosions ? ` exploded ${d.explosions}x` : ''}">
+                ${d.negative ? '−' : ''}${d.value}${d.explosions ? '💥' : ''}
+            </span>`).join('')}</p>`
+        : '';
+    
     // Build result HTML
     let resultHTML = `
         <h3>💥 Damage to ${target_name}</h3>
-        <p style="font-size: 1.1rem;">Raw Damage: <strong>${raw_damage}</strong></p>
+        ${diceHTML}
+        <p style="font-size: 1.1rem;">Raw Damage: <strong>${raw_damage}</strong> ${typeLabel}</p>
+        ${is_critical ? `<p style="font-size: 1.1rem;">🌟 Critical: ${raw_damage - critical_bonus} rolled + ${critical_bonus} max dice</p>` : ''}
+        ${adjustmentHTML}
         <p style="font-size: 1.1rem;">After Armor: <strong>${after_armor}</strong></p>
         <hr style="border-color: var(--accent); margin: 0.75rem 0;">
         <p>HP Lost: <strong style="color: #e74c3c;">${hp_lost}</strong></p>
         <p>Stress Gained: <strong style="color: #f39c12;">${stress_gained}</strong></p>
         <p>New HP: <strong>${new_hp}</strong> | New Stress: <strong>${new_stress}</strong></p>
         ${taken_out ? '<p style="font-size: 1.3rem; font-weight: bold; color: #e74c3c;">💀 TAKEN OUT!</p>' : ''}
+        ${narration ? `<p style="font-style: italic; color: var(--accent);">${narration}</p>` : ''}
     `;
     
     // Show overlay
@@ -1014,6 +2622,33 @@ function handleDamageResult(payload) {
     overlay.style.display = 'block';
 }
 
+// One roll dealt to everyone in an area
+function handleAreaDamageResult(payload) {
+    const { source, raw_damage, dice = [], targets } = payload;
+
+    targets.forEach(t => {
+        const adversary = adversaries.find(a => a.id === t.target_id);
+        if (adversary) {
+            adversary.hp = t.new_hp;
+            adversary.stress = t.new_stress;
+            if (t.taken_out) adversary.is_active = false;
+        }
+    });
+    renderAdversariesList();
+
+    const rows = targets.map(t => `
+        <p>${t.taken_out ? '💀' : '🎯'} <strong>${t.target_name}</strong>: ${t.after_armor} after armor
+            → ${t.hp_lost} HP, ${t.stress_gained} Stress (HP ${t.new_hp})</p>`).join('');
+    document.getElementById('combat-result-title').textContent = `💥 ${source}`;
+    document.getElementById('combat-result-content').innerHTML = `
+        <p style="font-size: 1.1rem;">Rolled <strong>${raw_damage}</strong> (${dice.map(d => d.value).join(' + ')})</p>
+        <hr style="border-color: var(--accent); margin: 0.75rem 0;">
+        ${rows}
+    `;
+    document.getElementById('roll-damage-btn').style.display = 'none';
+    document.getElementById('combat-result-overlay').style.display = 'block';
+}
+
 function rollDamageForLastAttack() {
     if (!lastAttackResult) {
         console.error('No attack result to roll damage for');
@@ -1026,29 +2661,25 @@ function rollDamageForLastAttack() {
     const attacker = adversaries.find(a => a.id === attacker_id);
     const character = characters.find(c => c.id === attacker_id);
     
-    let damageDice = '1d6'; // Default
-    let armor = 0;
+    // Characters use their equipped weapon and armor (filled in by the server)
+    let damageDice = undefined;
+    let armor = undefined;
     
     // Get damage dice from attacker
     if (attacker) {
         damageDice = attacker.damage_dice || '1d6';
-    } else if (character) {
-        // TODO: Get character weapon damage
-        damageDice = '1d8'; // Placeholder
+    } else if (!character) {
+        damageDice = '1d6'; // Default
     }
     
     // Get armor from target
     const targetAdv = adversaries.find(a => a.id === target_id);
-    const targetChar = characters.find(c => c.id === target_id);
     
     if (targetAdv) {
         armor = targetAdv.armor || 0;
-    } else if (targetChar) {
-        // TODO: Get character armor
-        armor = 1; // Placeholder
     }
     
-    console.log(`🎲 Rolling damage: ${damageDice} against armor ${armor}`);
+    console.log(`🎲 Rolling damage: ${damageDice || 'weapon'} against armor ${armor ?? 'equipped'}`);
     
     ws.send('roll_damage', {
         attacker_id,
@@ -0,0 +1,60 @@
+// Handouts panel, shared by the mobile and GM views
+//
+// Expects #handout-list on the page. The list comes over the WebSocket, so
+// each player gets only their own private handouts. Content is fetched when
+// a handout is opened; text and markdown are shown as written, images inline.
+
+function requestHandouts() {
+    ws.send('request_handouts');
+}
+
+// Replaces the list; they come oldest first, so the newest ends up on top
+function showHandouts(handouts) {
+    const list = document.getElementById('handout-list');
+    if (!list) return;
+    list.innerHTML = '';
+    handouts.forEach(handout => appendHandout(handout));
+}
+
+// Newest first; `open` shows it straight away
+function appendHandout(handout, open = false) {
+    const list = document.getElementById('handout-list');
+    if (!list || list.querySelector(`[data-handout-id="${handout.id}"]`)) return;
+
+    const item = document.createElement('details');
+    item.className = 'handout';
+    item.dataset.handoutId = handout.id;
+
+    const summary = document.createElement('summary');
+    summary.textContent = handout.character_ids.length > 0 ? `🔒 ${handout.title}` : handout.title;
+    item.appendChild(summary);
+
+    item.addEventListener('toggle', () => {
+        if (item.open && !item.dataset.loaded) {
+            item.dataset.loaded = 'true';
+            showHandoutContent(item, handout);
+        }
+    });
+
+    list.prepend(item);
+    item.open = open;
+}
+
+function showHandoutContent(item, handout) {
+    if (handout.kind === 'image') {
+        const image = document.createElement('img');
+        image.className = 'handout-image';
+        image.src = handout.url;
+        image.alt = handout.title;
+        item.appendChild(image);
+        return;
+    }
+
+    const text = document.createElement('div');
+    text.className = 'handout-text';
+    item.appendChild(text);
+    fetch(handout.url)
+        .then(response => response.text())
+        .then(content => { text.textContent = content; })
+        .catch(error => console.error('Failed to load handout:', error));
+}
@@ -0,0 +1,38 @@
+// Display strings for the enums the server sends
+//
+// The server sends outcomes as enum values (e.g. `success_with_fear`,
+// `hope`) and leaves the wording to the client. Strings are looked up in
+// the table for the page's language (set from the player's locale
+// preference), falling back to English.
+
+const STRINGS = {
+    en: {
+        'success_type.critical_success': 'CRITICAL SUCCESS',
+        'success_type.success_with_hope': 'SUCCESS WITH HOPE',
+        'success_type.success_with_fear': 'SUCCESS WITH FEAR',
+        'success_type.success': 'SUCCESS',
+        'success_type.failure': 'FAILURE',
+        'controlling_die.hope': 'With Hope',
+        'controlling_die.fear': 'With Fear',
+        'controlling_die.tied': 'Tied',
+    },
+};
+
+// Look up a display string, e.g. t('controlling_die.hope')
+function t(key) {
+    const lang = (document.documentElement.lang || 'en').toLowerCase();
+    const table = STRINGS[lang] || STRINGS[lang.split('-')[0]] || STRINGS.en;
+    return table[key] || STRINGS.en[key] || key;
+}
+
+// Reactions never generate Hope or Fear, so they're worded as plain successes
+function outcomeLabel(successType, isReaction = false) {
+    if (isReaction && (successType === 'success_with_hope' || successType === 'success_with_fear')) {
+        return t('success_type.success');
+    }
+    return t(`success_type.${successType}`);
+}
+
+function isReactionRoll(rollType) {
+    return rollType === 'reaction' || rollType === 'save';
+}
@@ -0,0 +1,93 @@
+// Notes panel, shared by the mobile and GM views
+//
+// Expects #note-list on the page. The GM view also has the note form
+// (#note-id, #note-title, #note-body, #note-visibility): opening a note
+// there loads it into the form for editing.
+
+function requestNotes(search = '') {
+    ws.send('request_notes', { search });
+}
+
+// Replaces the list, e.g. with search results
+function showNotes(notes) {
+    const list = document.getElementById('note-list');
+    if (!list) return;
+    list.innerHTML = '';
+    if (notes.length === 0) {
+        list.innerHTML = '<p class="empty-state">No notes</p>';
+    }
+    notes.forEach(note => list.appendChild(noteItem(note)));
+}
+
+// A new or changed note goes to the top
+function showNote(note) {
+    const list = document.getElementById('note-list');
+    if (!list) return;
+    removeNote(note.id);
+    list.querySelector('.empty-state')?.remove();
+    list.prepend(noteItem(note));
+}
+
+function removeNote(noteId) {
+    document.querySelector(`#note-list [data-note-id="${noteId}"]`)?.remove();
+}
+
+function noteItem(note) {
+    const item = document.createElement('details');
+    item.className = 'handout';
+    item.dataset.noteId = note.id;
+
+    const summary = document.createElement('summary');
+    summary.textContent = note.visibility === 'gm' ? `🔒 ${note.title}` : note.title;
+    item.appendChild(summary);
+
+    const body = document.createElement('div');
+    body.className = 'handout-text';
+    body.textContent = note.body;
+    item.appendChild(body);
+
+    if (document.getElementById('note-title')) {
+        item.addEventListener('toggle', () => {
+            if (item.open) editNote(note);
+        });
+    }
+    return item;
+}
+
+function editNote(note) {
+    document.getElementById('note-id').value = note.id;
+    document.getElementById('note-title').value = note.title;
+    document.getElementById('note-body').value = note.body;
+    document.getElementById('note-visibility').value = note.visibility;
+}
+
+function clearNoteForm() {
+    ['note-id', 'note-title', 'note-body'].forEach(id => {
+        document.getElementById(id).value = '';
+    });
+    document.getElementById('note-visibility').value = 'gm';
+}
+
+function saveNote() {
+    const title = document.getElementById('note-title').value.trim();
+    if (!title) {
+        alert('Give the note a title');
+        return;
+    }
+    ws.send('save_note', {
+        note_id: document.getElementById('note-id').value || null,
+        note: {
+            title,
+            body: document.getElementById('note-body').value,
+            visibility: document.getElementById('note-visibility').value,
+        },
+    });
+    clearNoteForm();
+}
+
+function deleteNote() {
+    const noteId = document.getElementById('note-id').value;
+    if (!noteId || !confirm('Delete this note?')) return;
+    ws.send('delete_note', { note_id: noteId });
+    clearNoteForm();
+}
@@ -0,0 +1,179 @@
+// Quest board, shared by the mobile and GM views
+//
+// Expects #quest-board on the page. The GM view also has the quest form
+// (#quest-id, #quest-title, ...), and there objectives can be ticked off
+// and countdowns moved right on the board.
+
+let quests = [];
+let questCharacterNames = {};
+
+// Keeps the names shown against quests in step with the character list
+function setQuestCharacters(characters) {
+    questCharacterNames = {};
+    characters.forEach(c => { questCharacterNames[c.id] = c.name; });
+    renderQuestBoard();
+}
+
+function showQuests(list) {
+    quests = list || [];
+    renderQuestBoard();
+}
+
+function showQuest(quest) {
+    const index = quests.findIndex(q => q.id === quest.id);
+    if (index >= 0) {
+        quests[index] = quest;
+    } else {
+        quests.push(quest);
+    }
+    renderQuestBoard();
+}
+
+function removeQuest(questId) {
+    quests = quests.filter(q => q.id !== questId);
+    renderQuestBoard();
+}
+
+function renderQuestBoard() {
+    const board = document.getElementById('quest-board');
+    if (!board) return;
+    const editable = !!document.getElementById('quest-title');
+    board.innerHTML = '';
+    if (quests.length === 0) {
+        board.innerHTML = '<p class="empty-state">No quests yet</p>';
+        return;
+    }
+    quests.forEach(quest => board.appendChild(questItem(quest, editable)));
+}
+
+const QUEST_STATUS_ICONS = { active: '📜', completed: '🏆', failed: '💀' };
+
+function questItem(quest, editable) {
+    const item = document.createElement('details');
+    item.className = 'handout';
+    item.open = quest.status === 'active';
+
+    const summary = document.createElement('summary');
+    const done = quest.objectives.filter(o => o.completed).length;
+    const progress = quest.objectives.length > 0 ? ` (${done}/${quest.objectives.length})` : '';
+    summary.textContent = `${QUEST_STATUS_ICONS[quest.status] || '📜'} ${quest.title}${progress}`;
+    item.appendChild(summary);
+
+    const who = quest.character_ids.map(id => questCharacterNames[id]).filter(Boolean);
+    if (quest.description || who.length > 0) {
+        const text = document.createElement('div');
+        text.className = 'handout-text';
+        text.textContent = [quest.description, who.length > 0 ? `For ${who.join(', ')}` : '']
+            .filter(Boolean).join('\n');
+        item.appendChild(text);
+    }
+
+    quest.objectives.forEach(objective => {
+        const label = document.createElement('label');
+        label.style.display = 'block';
+        const box = document.createElement('input');
+        box.type = 'checkbox';
+        box.checked = objective.completed;
+        box.disabled = !editable;
+        box.addEventListener('change', () => ws.send('set_objective_completed', {
+            quest_id: quest.id,
+            objective_id: objective.id,
+            completed: box.checked,
+        }));
+        label.append(box, ` ${objective.text}`);
+        item.appendChild(label);
+    });
+
+    quest.countdowns.forEach(countdown => {
+        const row = document.createElement('div');
+        row.style.fontSize = '0.9rem';
+        const icon = countdown.kind === 'consequence' ? '⚠️' : '⏳';
+        row.textContent = `${icon} ${countdown.label}: ${countdown.remaining}/${countdown.length} `;
+        if (editable) {
+            [['−', 1], ['+', -1]].forEach(([text, ticks]) => {
+                const button = document.createElement('button');
+                button.className = 'btn-small';
+                button.textContent = text;
+                button.title = ticks > 0 ? 'Tick down' : 'Wind back';
+                button.addEventListener('click', () => ws.send('tick_quest_countdown', {
+                    quest_id: quest.id,
+                    countdown_id: countdown.id,
+                    ticks,
+                }));
+                row.appendChild(button);
+            });
+        }
+        item.appendChild(row);
+    });
+
+    if (editable) {
+        const edit = document.createElement('button');
+        edit.className = 'btn-secondary btn-small';
+        edit.textContent = '✏️ Edit';
+        edit.addEventListener('click', () => editQuest(quest));
+        item.appendChild(edit);
+    }
+    return item;
+}
+
+// In the form, countdowns are written one a line as "Label / length",
+// with a leading "!" for a consequence countdown
+function editQuest(quest) {
+    document.getElementById('quest-id').value = quest.id;
+    document.getElementById('quest-title').value = quest.title;
+    document.getElementById('quest-description').value = quest.description;
+    document.getElementById('quest-status').value = quest.status;
+    document.getElementById('quest-objectives').value = quest.objectives.map(o => o.text).join('\n');
+    document.getElementById('quest-countdowns').value = quest.countdowns
+        .map(c => `${c.kind === 'consequence' ? '!' : ''}${c.label} / ${c.length}`)
+        .join('\n');
+    document.querySelectorAll('#quest-characters input').forEach(box => {
+        box.checked = quest.character_ids.includes(box.value);
+    });
+}
+
+function clearQuestForm() {
+    ['quest-id', 'quest-title', 'quest-description', 'quest-objectives', 'quest-countdowns'].forEach(id => {
+        document.getElementById(id).value = '';
+    });
+    document.getElementById('quest-status').value = 'active';
+    document.querySelectorAll('#quest-characters input').forEach(box => { box.checked = false; });
+}
+
+function saveQuest() {
+    const title = document.getElementById('quest-title').value.trim();
+    if (!title) {
+        alert('Give the quest a title');
+        return;
+    }
+    const lines = id => document.getElementById(id).value.split('\n').map(l => l.trim()).filter(Boolean);
+    const countdowns = lines('quest-countdowns').map(line => {
+        const consequence = line.startsWith('!');
+        const [label, length] = line.replace(/^!/, '').split('/').map(part => part.trim());
+        return {
+            label,
+            kind: consequence ? 'consequence' : 'progress',
+            length: parseInt(length, 10) || 4,
+        };
+    });
+
+    ws.send('save_quest', {
+        quest_id: document.getElementById('quest-id').value || null,
+        quest: {
+            title,
+            description: document.getElementById('quest-description').value,
+            status: document.getElementById('quest-status').value,
+            character_ids: [...document.querySelectorAll('#quest-characters input:checked')].map(box => box.value),
+            objectives: lines('quest-objectives'),
+            countdowns,
+        },
+    });
+    clearQuestForm();
+}
+
+function deleteQuest() {
+    const questId = document.getElementById('quest-id').value;
+    if (!questId || !confirm('Take this quest off the board?')) return;
+    ws.send('remove_quest', { quest_id: questId });
+    clearQuestForm();
+}
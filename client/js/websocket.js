@@ -1,16 +1,30 @@
 // WebSocket Client Module
 
 class WebSocketClient {
-    constructor(onMessage) {
+    // options.role: 'gm' for the GM view, 'spectator' for the TV; options.token: the API token, if one is set
+    constructor(onMessage, options = {}) {
         this.ws = null;
         this.onMessage = onMessage;
+        this.role = options.role || null;
+        this.token = options.token || null;
         this.reconnectAttempts = 0;
         this.maxReconnectAttempts = 5;
+        this.connectionId = null;
+        // Set when the GM kicks this connection; it stays closed
+        this.kicked = false;
+        // Preferences are stored on the server under this token; it lives in
+        // the page URL so refreshes and shared links keep them
+        this.sessionToken = new URLSearchParams(window.location.search).get('session');
     }
 
     connect() {
         const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-        const wsUrl = `${protocol}//${window.location.host}/ws`;
+        const params = new URLSearchParams();
+        if (this.sessionToken) params.set('session', this.sessionToken);
+        if (this.role) params.set('role', this.role);
+        if (this.token) params.set('token', this.token);
+        const query = params.toString() ? `?${params}` : '';
+        const wsUrl = `${protocol}//${window.location.host}/ws${query}`;
         
         console.log('Connecting to WebSocket:', wsUrl);
         
@@ -28,6 +42,15 @@ class WebSocketClient {
             try {
                 const message = JSON.parse(event.data);
                 console.log('📨 Received:', message);
+                if (message.type === 'connected') {
+                    this.connectionId = message.payload.connection_id;
+                    if (message.payload.session_token) {
+                        this.rememberSession(message.payload.session_token);
+                    }
+                }
+                if (message.type === 'kicked' && message.payload.connection_id === this.connectionId) {
+                    this.kicked = true;
+                }
                 if (this.onMessage) {
                     this.onMessage(message);
                 }
@@ -42,10 +65,19 @@ class WebSocketClient {
         
         this.ws.onclose = () => {
             console.log('WebSocket disconnected');
-            this.attemptReconnect();
+            if (!this.kicked) {
+                this.attemptReconnect();
+            }
         };
     }
 
+    rememberSession(token) {
+        this.sessionToken = token;
+        const url = new URL(window.location.href);
+        url.searchParams.set('session', token);
+        window.history.replaceState(null, '', url);
+    }
+
     attemptReconnect() {
         if (this.reconnectAttempts < this.maxReconnectAttempts) {
             this.reconnectAttempts++;
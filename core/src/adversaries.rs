@@ -0,0 +1,310 @@
+//! Adversary template system
+//!
+//! Built-in templates are the SRD compendium compiled in from
+//! `data/srd_adversaries.json`: stat blocks by tier (1-4) and role, with
+//! Difficulty used as Evasion and damage thresholds folded into Armor.
+//! Homebrew templates are loaded from a directory of JSON or TOML files and
+//! can be reloaded while the server runs.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::damage::{DamageType, Defenses};
+use crate::protocol::AdversaryRole;
+
+/// The built-in compendium
+const SRD_ADVERSARIES: &str = include_str!("../data/srd_adversaries.json");
+
+/// Highest adversary tier
+pub const MAX_TIER: u8 = 4;
+
+/// Directory holding homebrew adversary template files
+pub const ADVERSARY_DIR_ENV: &str = "DAGGERHEART_ADVERSARY_DIR";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_ADVERSARY_DIR: &str = "adversaries";
+
+/// Adversary template for spawning enemies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdversaryTemplate {
+    pub id: String,
+    pub name: String,
+    pub tier: u8, // 1-4, matching character tiers
+    #[serde(default)]
+    pub role: AdversaryRole,
+    pub hp: u8,
+    pub evasion: u8,
+    pub armor: u8,
+    pub attack_modifier: i8,
+    pub damage: String, // e.g., "1d6", "2d8+2"
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub defenses: Defenses,
+    #[serde(default)]
+    pub description: String,
+    /// What drives it and how it fights, e.g. "Ambush, drag away, feed"
+    #[serde(default)]
+    pub motives_and_tactics: String,
+}
+
+/// Compendium search: every given field must match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TemplateQuery {
+    pub tier: Option<u8>,
+    pub role: Option<AdversaryRole>,
+    /// Case-insensitive text matched against name, id and description
+    pub q: Option<String>,
+}
+
+impl TemplateQuery {
+    pub fn matches(&self, template: &AdversaryTemplate) -> bool {
+        if self.tier.is_some_and(|tier| template.tier != tier) {
+            return false;
+        }
+        if self.role.is_some_and(|role| template.role != role) {
+            return false;
+        }
+        match self.q.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => {
+                let text = text.to_lowercase();
+                template.name.to_lowercase().contains(&text)
+                    || template.id.contains(&text)
+                    || template.description.to_lowercase().contains(&text)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// A template file: a single template, a list, or a `templates` table
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TemplateFile {
+    Table { templates: Vec<AdversaryTemplate> },
+    List(Vec<AdversaryTemplate>),
+    One(Box<AdversaryTemplate>),
+}
+
+impl TemplateFile {
+    fn into_templates(self) -> Vec<AdversaryTemplate> {
+        match self {
+            TemplateFile::Table { templates } | TemplateFile::List(templates) => templates,
+            TemplateFile::One(template) => vec![*template],
+        }
+    }
+}
+
+impl AdversaryTemplate {
+    /// Get all built-in templates (the SRD compendium)
+    pub fn get_all_templates() -> Vec<AdversaryTemplate> {
+        static COMPENDIUM: OnceLock<Vec<AdversaryTemplate>> = OnceLock::new();
+        COMPENDIUM
+            .get_or_init(|| {
+                serde_json::from_str(SRD_ADVERSARIES)
+                    .expect("built-in adversary compendium is valid")
+            })
+            .clone()
+    }
+
+    /// Get a specific template by ID
+    pub fn get_template(id: &str) -> Option<AdversaryTemplate> {
+        Self::get_all_templates()
+            .into_iter()
+            .find(|t| t.id == id)
+    }
+
+    /// Check a homebrew template is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() || self.id == "custom" {
+            return Err(format!("Invalid template id: \"{}\"", self.id));
+        }
+        if self.hp == 0 {
+            return Err(format!("{}: HP must be at least 1", self.id));
+        }
+        if !(1..=MAX_TIER).contains(&self.tier) {
+            return Err(format!("{}: tier must be 1-{}", self.id, MAX_TIER));
+        }
+        if self.damage.trim().is_empty() {
+            return Err(format!("{}: damage cannot be empty", self.id));
+        }
+        Ok(())
+    }
+
+    /// Load every `.json` and `.toml` template file in a directory
+    ///
+    /// A missing directory just means there is no homebrew.
+    pub fn load_dir(dir: &Path) -> Result<Vec<AdversaryTemplate>, String> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("json") | Some("toml")
+                )
+            })
+            .collect();
+        paths.sort();
+
+        let mut templates: Vec<AdversaryTemplate> = Vec::new();
+        for path in paths {
+            for template in Self::load_file(&path)? {
+                template
+                    .validate()
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                if templates.iter().any(|t| t.id == template.id) {
+                    return Err(format!(
+                        "{}: duplicate template id \"{}\"",
+                        path.display(),
+                        template.id
+                    ));
+                }
+                templates.push(template);
+            }
+        }
+
+        Ok(templates)
+    }
+
+    fn load_file(path: &Path) -> Result<Vec<AdversaryTemplate>, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let file: TemplateFile = if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?
+        };
+
+        Ok(file.into_templates())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_dir_json_and_toml() {
+        let dir = temp_dir("adversaries");
+        fs::write(
+            dir.join("cultists.json"),
+            r#"[{"id":"cultist","name":"Cultist","tier":1,"role":"minion","hp":3,"evasion":10,"armor":1,"attack_modifier":1,"damage":"1d6"}]"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("beasts.toml"),
+            r#"
+[[templates]]
+id = "dire_bear"
+name = "Dire Bear"
+tier = 1
+role = "bruiser"
+hp = 6
+evasion = 11
+armor = 2
+attack_modifier = 3
+damage = "1d10+2"
+description = "Hulking, territorial predator"
+"#,
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let templates = AdversaryTemplate::load_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let ids: Vec<&str> = templates.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["dire_bear", "cultist"]);
+        assert_eq!(templates[0].role, AdversaryRole::Bruiser);
+        assert_eq!(templates[1].description, "");
+    }
+
+    #[test]
+    fn test_load_dir_rejects_invalid_templates() {
+        let dir = temp_dir("adversaries-invalid");
+        fs::write(
+            dir.join("broken.json"),
+            r#"{"id":"ghost","name":"Ghost","tier":1,"role":"minion","hp":0,"evasion":10,"armor":0,"attack_modifier":1,"damage":"1d4"}"#,
+        )
+        .unwrap();
+
+        let result = AdversaryTemplate::load_dir(&dir);
+        fs::remove_dir_all(&dir).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_compendium_is_valid() {
+        let templates = AdversaryTemplate::get_all_templates();
+        assert!(templates.len() >= 100);
+
+        for template in &templates {
+            assert!(
+                template.validate().is_ok(),
+                "Invalid template: {}",
+                template.id
+            );
+            let duplicates = templates.iter().filter(|t| t.id == template.id).count();
+            assert_eq!(duplicates, 1, "Duplicate template: {}", template.id);
+        }
+
+        // Every tier has fodder and a centerpiece
+        for tier in 1..=MAX_TIER {
+            for role in [AdversaryRole::Minion, AdversaryRole::Solo] {
+                let query = TemplateQuery {
+                    tier: Some(tier),
+                    role: Some(role),
+                    q: None,
+                };
+                assert!(
+                    templates.iter().any(|t| query.matches(t)),
+                    "No tier {} {:?}",
+                    tier,
+                    role
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_template_query() {
+        let templates = AdversaryTemplate::get_all_templates();
+        let query = TemplateQuery {
+            q: Some("  Jagged KNIFE ".to_string()),
+            ..TemplateQuery::default()
+        };
+        let found: Vec<_> = templates.iter().filter(|t| query.matches(t)).collect();
+        assert!(found.len() > 3);
+        assert!(found.iter().all(|t| t.tier == 1));
+
+        let query = TemplateQuery {
+            tier: Some(4),
+            role: Some(AdversaryRole::Leader),
+            q: None,
+        };
+        assert!(templates
+            .iter()
+            .filter(|t| query.matches(t))
+            .all(|t| t.tier == 4 && t.role == AdversaryRole::Leader));
+    }
+
+    #[test]
+    fn test_load_missing_dir() {
+        let dir = std::env::temp_dir().join("daggerheart-no-such-adversary-dir");
+        assert!(AdversaryTemplate::load_dir(&dir).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+//! Cutaways: a scene with part of the party while the rest wait
+//!
+//! While a cutaway runs, what its characters say, roll and do reaches only
+//! their players and the GM (see [`crate::visibility`]); everyone else gets
+//! a "meanwhile…" placeholder. Events logged in the scene are marked and
+//! kept out of public views until the cutaway ends, when they join the
+//! public log where they happened.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::protocol::cutaway::CutawayInfo;
+
+/// The scene the GM cut away to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cutaway {
+    pub id: Uuid,
+    pub label: String,
+    pub character_ids: Vec<Uuid>,
+    /// Names at the start, for matching logged events
+    pub character_names: Vec<String>,
+}
+
+impl Cutaway {
+    pub fn includes(&self, character_id: &Uuid) -> bool {
+        self.character_ids.contains(character_id)
+    }
+
+    pub fn to_info(&self) -> CutawayInfo {
+        CutawayInfo {
+            id: self.id.to_string(),
+            label: self.label.clone(),
+            character_ids: self.character_ids.iter().map(|id| id.to_string()).collect(),
+            character_names: self.character_names.clone(),
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Demo campaign generator
+//!
+//! Populates a fresh game with a sample party, a goblin camp with a background
+//! map and adversaries, a quest with a countdown running and a pending roll
+//! request so new users can see every subsystem working right after
+//! `cargo run`.
+
+use uuid::Uuid;
+
+use daggerheart_engine::character::{Ancestry, Attributes, Class};
+
+use crate::game::{GameEventType, GameState, PendingRollRequest};
+use crate::protocol::maps::{MapInfo, SceneLayout};
+use crate::protocol::quests::{CountdownDraft, CountdownKind, QuestDraft, QuestStatus};
+use crate::protocol::{Position, RollType};
+
+/// The demo's scene, and the name its background map is stored under
+pub const DEMO_SCENE: &str = "Goblin Camp at Dusk";
+
+/// Sample party: (name, class, ancestry, attributes)
+fn demo_party() -> Vec<(&'static str, Class, Ancestry, [i8; 6])> {
+    vec![
+        (
+            "Theron",
+            Class::Warrior,
+            Ancestry::Human,
+            [2, 1, 1, 0, 0, -1],
+        ),
+        (
+            "Elara",
+            Class::Wizard,
+            Ancestry::Faerie,
+            [0, -1, 1, 1, 0, 2],
+        ),
+        (
+            "Brindle",
+            Class::Rogue,
+            Ancestry::Halfling,
+            [1, 0, 2, 1, -1, 0],
+        ),
+    ]
+}
+
+/// Sample adversaries: (template id, x, y)
+const DEMO_ADVERSARIES: &[(&str, f32, f32)] = &[
+    ("goblin", 560.0, 180.0),
+    ("goblin", 620.0, 260.0),
+    ("orc_warrior", 660.0, 400.0),
+];
+
+/// The demo's quest, with a countdown for the camp raising the alarm
+fn demo_quest(party: &[Uuid]) -> QuestDraft {
+    QuestDraft {
+        title: "Raid the goblin camp".to_string(),
+        description: "The goblins took a merchant on the north road".to_string(),
+        status: QuestStatus::Active,
+        character_ids: party.iter().map(Uuid::to_string).collect(),
+        objectives: vec![
+            "Get past the sentries".to_string(),
+            "Free the merchant".to_string(),
+        ],
+        countdowns: vec![CountdownDraft {
+            label: "Reinforcements arrive".to_string(),
+            kind: CountdownKind::Consequence,
+            length: 4,
+        }],
+    }
+}
+
+/// Replace the current game with the demo campaign, on `background` if
+/// there's a map for it
+///
+/// Connections and table settings are kept; everything else left from the
+/// last game (characters, adversaries, roll requests, combat, the Fear
+/// pool, rollback points, the next session, players' preferences, notes,
+/// quests, chat, the map with its fog and drawings, roll tallies and any
+/// cutaway) is cleared first.
+pub fn populate_demo(game: &mut GameState, background: Option<MapInfo>) -> Result<(), String> {
+    game.characters.clear();
+    game.control_mapping.clear();
+    game.adversaries.clear();
+    game.adversary_groups.clear();
+    game.auras.clear();
+    game.modifiers.clear();
+    game.hazards.clear();
+    game.environments.clear();
+    game.pending_roll_requests.clear();
+    game.combat_encounter = None;
+    game.fear_pool = game.settings.starting_fear;
+    game.checkpoints.clear();
+    game.next_session = None;
+    game.preferences.clear();
+    game.ghosted_characters.clear();
+    game.adversary_spawn_counts.clear();
+    game.critical_hits.clear();
+    game.combat_recordings.clear();
+    game.notes.clear();
+    game.quests.clear();
+    game.chat_log.clear();
+    game.roll_stats.clear();
+    game.cutaway = None;
+    game.map = None;
+    game.layout = SceneLayout::default();
+    game.fog.clear();
+    game.drawings.clear();
+    game.map_locked = false;
+    game.clear_events();
+
+    game.add_event(
+        GameEventType::SystemMessage,
+        "Demo campaign loaded".to_string(),
+        None,
+        Some("The party approaches a goblin camp at dusk".to_string()),
+    );
+    if background.is_some() {
+        game.set_map(background);
+    }
+
+    // Sample party
+    let mut party = Vec::new();
+    for (i, (name, class, ancestry, attributes)) in demo_party().into_iter().enumerate() {
+        let attrs = Attributes::from_array(attributes)
+            .map_err(|e| format!("Invalid demo attributes: {}", e))?;
+        let character = game.create_character(name.to_string(), class, ancestry, attrs);
+
+        // Line the party up on the left side of the map
+        let position = Position::new(150.0, 180.0 + i as f32 * 110.0);
+        game.update_character_position(&character.id, position)?;
+
+        game.add_event(
+            GameEventType::CharacterCreated,
+            format!("{} joined the game", character.name),
+            Some(character.name.clone()),
+            Some(format!(
+                "Class: {}, Ancestry: {}",
+                character.class, character.ancestry
+            )),
+        );
+        party.push(character.id);
+    }
+
+    // Adversaries guarding the camp
+    for (template, x, y) in DEMO_ADVERSARIES {
+        game.spawn_adversary(template, Position::new(*x, *y))?;
+    }
+
+    // The camp raises the alarm if the party takes too long
+    game.add_quest(&demo_quest(&party))?;
+
+    // Pending roll request for the whole party
+    let request_id = Uuid::new_v4().to_string();
+    let request = PendingRollRequest {
+        id: request_id.clone(),
+        target_character_ids: party,
+        roll_type: RollType::Action,
+        attribute: Some("finesse".to_string()),
+        difficulty: 12,
+        context: "Sneak up on the goblin camp".to_string(),
+        narrative_stakes: Some("On a failure, the goblins raise the alarm".to_string()),
+        situational_modifier: 0,
+        has_advantage: false,
+        has_disadvantage: false,
+        is_combat: false,
+        completed_by: Vec::new(),
+        timestamp: std::time::SystemTime::now(),
+        group: None,
+        tag_team: None,
+        help: Vec::new(),
+        hidden: false,
+    };
+    game.pending_roll_requests.insert(request_id, request);
+
+    game.add_event(
+        GameEventType::RollRequested,
+        "GM requested finesse roll: \"Sneak up on the goblin camp\"".to_string(),
+        None,
+        Some("Target: all players, DC 12".to_string()),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::chat::ChatChannel;
+    use crate::protocol::drawings::DrawingShape;
+    use crate::protocol::notes::{NoteDraft, NoteVisibility};
+    use crate::protocol::ConnectionRole;
+
+    fn demo_map() -> MapInfo {
+        MapInfo {
+            id: Uuid::new_v4().to_string(),
+            scene: DEMO_SCENE.to_string(),
+            url: "/maps/camp.png".to_string(),
+            uploaded_at: "2026-01-01T00:00:00Z".to_string(),
+            layout: SceneLayout::default(),
+        }
+    }
+
+    #[test]
+    fn test_populate_demo() {
+        let mut game = GameState::new();
+        let map = demo_map();
+        populate_demo(&mut game, Some(map.clone())).unwrap();
+
+        assert_eq!(game.get_player_characters().len(), demo_party().len());
+        assert_eq!(game.adversaries.len(), DEMO_ADVERSARIES.len());
+        assert_eq!(game.pending_roll_requests.len(), 1);
+        assert_eq!(game.map, Some(map));
+
+        assert_eq!(game.quests.len(), 1);
+        let countdown = &game.quests[0].countdowns[0];
+        assert_eq!(countdown.kind, CountdownKind::Consequence);
+        assert_eq!(countdown.remaining, 4);
+        assert_eq!(game.quests[0].character_ids.len(), demo_party().len());
+
+        let request = game.pending_roll_requests.values().next().unwrap();
+        assert_eq!(request.target_character_ids.len(), demo_party().len());
+    }
+
+    #[test]
+    fn test_populate_demo_replaces_existing_game() {
+        let mut game = GameState::new();
+        let conn = game.add_connection();
+        game.connections.get_mut(&conn.id).unwrap().role = ConnectionRole::Gm;
+        populate_demo(&mut game, Some(demo_map())).unwrap();
+
+        // Leave a game's worth of state behind
+        let theron = *game.characters.keys().next().unwrap();
+        game.record_duality(&theron, 7, 3);
+        game.add_note(&NoteDraft {
+            title: "The merchant is a spy".to_string(),
+            body: String::new(),
+            visibility: NoteVisibility::Gm,
+        })
+        .unwrap();
+        game.chat(&conn.id, ChatChannel::OutOfCharacter, "Back in five")
+            .unwrap();
+        game.set_fog_enabled(true);
+        game.add_drawing(
+            DrawingShape::Circle {
+                center: Position::new(300.0, 300.0),
+                radius: 50.0,
+            },
+            "#e74c3c",
+            "Trap",
+        )
+        .unwrap();
+        game.start_cutaway(&[theron.to_string()], "Scouting")
+            .unwrap();
+        game.fear_pool = 9;
+        game.create_checkpoint("Before the ambush".to_string());
+        let next_week = chrono::Utc::now() + chrono::Duration::days(7);
+        game.schedule_next_session(&next_week.to_rfc3339(), "")
+            .unwrap();
+        game.preferences
+            .insert("session".to_string(), Default::default());
+
+        populate_demo(&mut game, None).unwrap();
+
+        assert_eq!(game.character_count(), demo_party().len());
+        assert_eq!(game.adversaries.len(), DEMO_ADVERSARIES.len());
+        assert_eq!(game.adversary_spawn_counts.get("goblin"), Some(&2));
+        assert!(game.connections.contains_key(&conn.id));
+        assert!(game.notes.is_empty());
+        assert!(game.chat_log.is_empty());
+        assert!(game.roll_stats.is_empty());
+        assert!(game.fog.is_empty());
+        assert!(game.drawings.is_empty());
+        assert!(game.cutaway.is_none());
+        assert!(game.map.is_none());
+        assert_eq!(game.fear_pool, game.settings.starting_fear);
+        assert!(game.checkpoints.is_empty());
+        assert!(game.next_session.is_none());
+        assert!(game.preferences.is_empty());
+        assert_eq!(game.quests.len(), 1);
+        assert_eq!(game.pending_roll_requests.len(), 1);
+    }
+}
@@ -0,0 +1,127 @@
+//! Environment template system
+//!
+//! Built-in templates are the SRD environments compiled in from
+//! `data/srd_environments.json`, by tier (1-4) and type. Each lists the
+//! adversaries likely to appear by compendium template id.
+
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::adversaries::{AdversaryTemplate, MAX_TIER};
+
+pub use crate::protocol::environments::{
+    EnvironmentFeature, EnvironmentInfo, EnvironmentType, FeatureKind,
+};
+
+/// The built-in environment compendium
+const SRD_ENVIRONMENTS: &str = include_str!("../data/srd_environments.json");
+
+/// Environment template for setting a scene
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentTemplate {
+    pub id: String,
+    pub name: String,
+    pub tier: u8,
+    pub kind: EnvironmentType,
+    pub difficulty: u16,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub impulses: Vec<String>,
+    #[serde(default)]
+    pub features: Vec<EnvironmentFeature>,
+    /// Adversary template ids
+    #[serde(default)]
+    pub potential_adversaries: Vec<String>,
+}
+
+impl EnvironmentTemplate {
+    /// Get all built-in templates
+    pub fn get_all_templates() -> Vec<EnvironmentTemplate> {
+        static COMPENDIUM: OnceLock<Vec<EnvironmentTemplate>> = OnceLock::new();
+        COMPENDIUM
+            .get_or_init(|| {
+                serde_json::from_str(SRD_ENVIRONMENTS)
+                    .expect("built-in environment compendium is valid")
+            })
+            .clone()
+    }
+
+    /// Get a specific template by ID
+    pub fn get_template(id: &str) -> Option<EnvironmentTemplate> {
+        Self::get_all_templates().into_iter().find(|t| t.id == id)
+    }
+
+    /// Check a template is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Environment id cannot be empty".to_string());
+        }
+        if !(1..=MAX_TIER).contains(&self.tier) {
+            return Err(format!("{}: tier must be 1-{}", self.id, MAX_TIER));
+        }
+        if self.difficulty == 0 {
+            return Err(format!("{}: difficulty must be at least 1", self.id));
+        }
+        for feature in &self.features {
+            if self
+                .features
+                .iter()
+                .filter(|f| f.name == feature.name)
+                .count()
+                > 1
+            {
+                return Err(format!(
+                    "{}: duplicate feature \"{}\"",
+                    self.id, feature.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The potential adversaries that exist in the compendium
+    pub fn adversary_templates(&self) -> Vec<AdversaryTemplate> {
+        self.potential_adversaries
+            .iter()
+            .filter_map(|id| AdversaryTemplate::get_template(id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compendium_is_valid() {
+        let templates = EnvironmentTemplate::get_all_templates();
+        assert!(!templates.is_empty());
+
+        for template in &templates {
+            assert!(
+                template.validate().is_ok(),
+                "Invalid environment: {}",
+                template.id
+            );
+            assert_eq!(
+                template.adversary_templates().len(),
+                template.potential_adversaries.len(),
+                "Unknown adversary in {}",
+                template.id
+            );
+        }
+
+        for tier in 1..=MAX_TIER {
+            assert!(templates.iter().any(|t| t.tier == tier), "No tier {}", tier);
+        }
+        for kind in [
+            EnvironmentType::Exploration,
+            EnvironmentType::Social,
+            EnvironmentType::Traversal,
+            EnvironmentType::Event,
+        ] {
+            assert!(templates.iter().any(|t| t.kind == kind), "No {:?}", kind);
+        }
+    }
+}
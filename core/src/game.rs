@@ -0,0 +1,11191 @@
+//! Game state management - Phase 5A: Refactored architecture
+//!
+//! NEW ARCHITECTURE:
+//! - Connection: Ephemeral WebSocket session (disappears on disconnect/refresh)
+//! - Character: Persistent game entity (survives restarts, can be controlled by any connection)
+//! - Control mapping: Connection → Character relationship
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use daggerheart_engine::{
+    character::{Ancestry, Attributes, Class},
+    combat::{damage::DamageResult, HitPoints, Hope, Stress},
+    core::dice::duality,
+};
+
+use crate::adversaries::{AdversaryTemplate, DEFAULT_ADVERSARY_DIR, MAX_TIER};
+use crate::auras::{Aura, AuraEffect};
+use crate::cutaway::Cutaway;
+use crate::damage::{AreaDamageTarget, DamageAdjustment, DamageType, Defenses};
+use crate::dice::{self, DiceRoll, DieResult};
+use crate::environments::{EnvironmentFeature, EnvironmentInfo, EnvironmentTemplate, FeatureKind};
+use crate::equipment::{Armor, Equipment, Weapon, WeaponRange};
+use crate::hazards::{Hazard, HazardArea, HazardOutcome};
+use crate::inventory::{Gold, Inventory, Item};
+use crate::leveling::{self, Advancement, AdvancementOption, Progression, TakenAdvancement};
+use crate::limits::ResourceLimits;
+use crate::narrative::{self, CombatBeat, NarrativeMode, NarrativeTemplates};
+use crate::preferences::UserPreferences;
+use crate::protocol::chat::{ChatChannel, ChatEntry, MAX_CHAT_LENGTH};
+use crate::protocol::cutaway::{CutawayInfo, MAX_CUTAWAY_LABEL_LENGTH};
+use crate::protocol::drawings::{Drawing, DrawingShape, MAX_DRAWINGS, MAX_DRAWING_LABEL_LENGTH};
+use crate::protocol::encounters::{
+    EncounterBudget, EncounterEntry, EncounterLine, EncounterPlan, MAX_PARTY_SIZE,
+};
+use crate::protocol::events::{self, EventLogQuery, LogMarker, SceneLog, SCENE_PREFIX};
+use crate::protocol::fog::{self, FogArea, FogState, MAX_FOG_AREAS};
+use crate::protocol::groups::{AdversaryGroupInfo, GroupMember};
+use crate::protocol::healing::{DowntimeMove, Healing, HealingSource, RestKind, SHORT_REST_DIE};
+use crate::protocol::journal::{PositionKeyframe, TokenPosition};
+use crate::protocol::maps::{GridKind, MapInfo, SceneLayout};
+use crate::protocol::modifiers::{Modifier, ModifierDuration, ModifierStat, TimedModifier};
+use crate::protocol::notes::{Note, NoteDraft, MAX_NOTES};
+use crate::protocol::quests::{Objective, Quest, QuestCountdown, QuestDraft, MAX_QUESTS};
+use crate::protocol::rounds::{RoundAction, RoundHistory};
+use crate::protocol::schedule::NextSession;
+use crate::protocol::settings::{TableSettings, TableSettingsPatch};
+use crate::protocol::stats::{RollStats, RollStatsReport};
+use crate::protocol::tokens::{self, TokenDetails, MAX_TOKEN_NAME_LENGTH};
+use crate::protocol::{
+    AdversaryRole, AdversaryTactics, AttributesData, CharacterData, CharacterEdit, CheckpointInfo,
+    CombatMode, ConnectionRole, ControllingDie, FearSpend, GameEventData, Position, ResourceData,
+    ResourceKind, RollPreview, RollResult, RollTargetType, RollType, StressMove, MAX_EVASION,
+};
+use crate::rng::DiceRng;
+use crate::schedule::ScheduledSession;
+use crate::subclasses::Subclass;
+
+pub use crate::protocol::{Experience, DEFAULT_EXPERIENCE_MODIFIER};
+
+/// Game event for the event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameEvent {
+    pub timestamp: std::time::SystemTime,
+    pub event_type: GameEventType,
+    pub message: String,
+    pub character_name: Option<String>,
+    pub details: Option<String>,
+    /// Display name of the player controlling the character, if set
+    #[serde(default)]
+    pub player_name: Option<String>,
+    /// Part of a running cutaway, kept out of public views until it ends
+    #[serde(default)]
+    pub cutaway: bool,
+    /// Set on the events that start a scene or start or end a fight
+    #[serde(default)]
+    pub marker: Option<LogMarker>,
+}
+
+impl GameEvent {
+    /// Wire form, with the time as "HH:MM:SS"
+    pub fn to_data(&self) -> GameEventData {
+        let seconds = self
+            .timestamp
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let timestamp = chrono::DateTime::from_timestamp(seconds as i64, 0)
+            .map(|dt| dt.format("%H:%M:%S").to_string())
+            .unwrap_or_else(|| "??:??:??".to_string());
+
+        GameEventData {
+            timestamp,
+            event_type: format!("{:?}", self.event_type),
+            message: self.message.clone(),
+            character_name: self.character_name.clone(),
+            details: self.details.clone(),
+            player_name: self.player_name.clone(),
+            cutaway: self.cutaway,
+            marker: self.marker.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameEventType {
+    CharacterCreated,
+    CharacterMoved,
+    RollRequested,
+    RollExecuted,
+    ResourceUpdate,
+    InventoryUpdate,
+    CombatAction,
+    SystemMessage,
+    Chat,
+}
+
+/// Most waypoints accepted in a single move
+pub const MAX_PATH_WAYPOINTS: usize = 64;
+
+/// Longest emote a player can send, in characters
+pub const MAX_EMOTE_LENGTH: usize = 80;
+
+/// Longest label on a free-form dice roll, in characters
+pub const MAX_ROLL_LABEL_LENGTH: usize = 40;
+
+/// Minimum time between two emotes from the same character
+pub const EMOTE_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Minimum time between two pings from the same connection
+pub const PING_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Color of the GM's pings
+pub const GM_PING_COLOR: &str = "#f1c40f";
+
+/// How long clients show an emote bubble
+pub const EMOTE_DURATION_MS: u32 = 4000;
+
+/// Chat messages kept for `GET /api/chat`, oldest dropped first
+pub const MAX_CHAT_HISTORY: usize = 200;
+
+/// Longest display name a connection can choose, in characters
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 32;
+
+/// Background of the map canvas; token colors must stand out against it
+const MAP_BACKGROUND: [u8; 3] = [0x1a, 0x1a, 0x1a];
+
+/// Minimum contrast ratio between a token color and the map background
+const MIN_COLOR_CONTRAST: f32 = 3.0;
+
+/// Closest two characters' colors may be (RGB distance)
+const MIN_COLOR_DISTANCE: f32 = 48.0;
+
+/// How often the server snapshots token positions during combat
+pub const KEYFRAME_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Most keyframes kept per encounter (an hour at the default interval)
+pub const MAX_KEYFRAMES_PER_COMBAT: usize = 1800;
+
+/// Most recent encounters whose keyframes are kept
+pub const MAX_COMBAT_RECORDINGS: usize = 10;
+
+/// Attributes given to GM-created NPCs (the standard starting array)
+const NPC_ATTRIBUTES: [i8; 6] = [2, 1, 1, 0, 0, -1];
+
+/// Damage an NPC without a linked template deals once it turns hostile
+const NPC_DAMAGE_DICE: &str = "1d6";
+
+/// Character color palette
+const CHARACTER_COLORS: &[&str] = &[
+    "#3b82f6", // Blue
+    "#10b981", // Green
+    "#f59e0b", // Orange
+    "#ef4444", // Red
+    "#8b5cf6", // Purple
+    "#ec4899", // Pink
+    "#14b8a6", // Teal
+    "#f97316", // Dark Orange
+];
+
+/// Pending roll request from GM (Phase 1)
+#[derive(Debug, Clone)]
+pub struct PendingRollRequest {
+    pub id: String,
+    pub target_character_ids: Vec<Uuid>,
+    pub roll_type: RollType,
+    pub attribute: Option<String>,
+    pub difficulty: u16,
+    pub context: String,
+    pub narrative_stakes: Option<String>,
+    pub situational_modifier: i8,
+    pub has_advantage: bool,
+    pub has_disadvantage: bool,
+    pub is_combat: bool,
+    pub completed_by: Vec<Uuid>, // Characters who have rolled
+    pub timestamp: std::time::SystemTime,
+    pub group: Option<GroupRoll>,  // Set for group action rolls
+    pub tag_team: Option<TagTeam>, // Set once a player proposes a tag team
+    pub help: Vec<HelpDie>,        // Help dice banked by allies
+    pub hidden: bool,              // Results go to the GM only
+}
+
+impl PendingRollRequest {
+    /// The kind of roll a target makes (helpers in a group roll react)
+    pub fn roll_type_for(&self, character_id: &Uuid) -> RollType {
+        match &self.group {
+            Some(group) if group.leader_id != *character_id => RollType::Reaction,
+            _ => self.roll_type.clone(),
+        }
+    }
+}
+
+/// A group action roll
+///
+/// Helpers make reaction rolls first; the leader then makes the action roll
+/// with +1 for each helper who succeeded and -1 for each who failed.
+#[derive(Debug, Clone)]
+pub struct GroupRoll {
+    pub leader_id: Uuid,
+    /// Helper id and whether they succeeded, in the order they rolled
+    pub helper_results: Vec<(Uuid, bool)>,
+}
+
+impl GroupRoll {
+    /// Modifier the helpers' results give the leader
+    pub fn modifier(&self) -> i8 {
+        self.helper_results
+            .iter()
+            .map(|(_, success)| if *success { 1i8 } else { -1 })
+            .fold(0i8, |total, m| total.saturating_add(m))
+    }
+}
+
+/// The roll a GM is weighing up, before it's sent to anyone
+#[derive(Debug, Clone)]
+pub struct RollSpec {
+    pub roll_type: RollType,
+    pub attribute: Option<String>,
+    pub difficulty: u16,
+    pub situational_modifier: i8,
+    pub has_advantage: bool,
+    pub has_disadvantage: bool,
+}
+
+/// A roll request as the GM sends it, before its targets are resolved
+#[derive(Debug, Clone)]
+pub struct RollRequest {
+    pub target_type: RollTargetType,
+    pub target_character_ids: Vec<String>,
+    pub roll_type: RollType,
+    pub attribute: Option<String>,
+    pub difficulty: u16,
+    pub context: String,
+    pub narrative_stakes: Option<String>,
+    pub situational_modifier: i8,
+    pub has_advantage: bool,
+    pub has_disadvantage: bool,
+    pub is_combat: bool,
+    pub hidden: bool,
+}
+
+/// A group action roll as the GM asks for it
+#[derive(Debug, Clone)]
+pub struct GroupRollRequest {
+    pub leader_id: String,
+    pub helper_ids: Vec<String>,
+    pub attribute: Option<String>,
+    pub difficulty: u16,
+    pub context: String,
+    pub has_advantage: bool,
+    pub has_disadvantage: bool,
+}
+
+/// A help die an ally banked on someone else's roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelpDie {
+    pub helper_id: Uuid,
+    pub target_id: Uuid,
+}
+
+/// A tag team roll
+///
+/// Two characters each spend 3 Hope, both roll, and the group picks which
+/// result applies. Hope and Fear wait until the pick, then follow the chosen
+/// result for both characters.
+#[derive(Debug, Clone)]
+pub struct TagTeam {
+    pub initiator_id: Uuid,
+    pub partner_id: Uuid,
+    /// Set once the partner agrees and both have paid
+    pub accepted: bool,
+    /// Each character's roll, in the order they rolled
+    pub results: Vec<(Uuid, crate::protocol::DetailedRollResult)>,
+    /// Character whose result the group picked
+    pub chosen: Option<Uuid>,
+}
+
+impl TagTeam {
+    pub fn includes(&self, character_id: &Uuid) -> bool {
+        self.initiator_id == *character_id || self.partner_id == *character_id
+    }
+
+    /// The other half of the team
+    pub fn partner_of(&self, character_id: &Uuid) -> Uuid {
+        if self.initiator_id == *character_id {
+            self.partner_id
+        } else {
+            self.initiator_id
+        }
+    }
+}
+
+/// The tag team result the group picked, and what it did to Hope and Fear
+#[derive(Debug, Clone)]
+pub struct TagTeamOutcome {
+    pub chosen_id: Uuid,
+    pub team: [Uuid; 2],
+    pub result: crate::protocol::DetailedRollResult,
+    /// Hope each character gained
+    pub hope_change: i8,
+    /// Fear the GM gained
+    pub fear_change: u8,
+}
+
+/// Token type in the Action Tracker
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    PC,
+    Adversary,
+}
+
+impl TokenType {
+    /// Wire name, as used by `add_tracker_token` and `tracker_updated`
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenType::PC => "pc",
+            TokenType::Adversary => "adversary",
+        }
+    }
+}
+
+/// Action Tracker for combat turn order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionTracker {
+    pub pc_tokens: u8,
+    pub adversary_tokens: u8,
+    pub queue: Vec<TokenType>, // Order of tokens (leftmost = next)
+}
+
+impl ActionTracker {
+    pub fn new() -> Self {
+        Self {
+            pc_tokens: 3,
+            adversary_tokens: 3,
+            queue: vec![
+                TokenType::PC,
+                TokenType::PC,
+                TokenType::PC,
+                TokenType::Adversary,
+                TokenType::Adversary,
+                TokenType::Adversary,
+            ],
+        }
+    }
+
+    /// Get the next token to act (leftmost in queue)
+    pub fn get_next(&self) -> Option<TokenType> {
+        self.queue.first().copied()
+    }
+
+    /// Remove the next token from the queue
+    pub fn pop_next(&mut self) -> Option<TokenType> {
+        if !self.queue.is_empty() {
+            Some(self.queue.remove(0))
+        } else {
+            None
+        }
+    }
+
+    /// Advance a token (add to end of queue)
+    pub fn advance_token(&mut self, token_type: TokenType) {
+        match token_type {
+            TokenType::PC if self.pc_tokens > 0 => {
+                self.pc_tokens -= 1;
+                self.queue.push(TokenType::PC);
+            }
+            TokenType::Adversary if self.adversary_tokens > 0 => {
+                self.adversary_tokens -= 1;
+                self.queue.push(TokenType::Adversary);
+            }
+            _ => {}
+        }
+    }
+
+    /// Refill tokens when pool is depleted
+    pub fn refill_if_needed(&mut self) {
+        if self.queue.is_empty() {
+            self.pc_tokens = 3;
+            self.adversary_tokens = 3;
+            self.queue = vec![
+                TokenType::PC,
+                TokenType::PC,
+                TokenType::PC,
+                TokenType::Adversary,
+                TokenType::Adversary,
+                TokenType::Adversary,
+            ];
+        }
+    }
+
+    /// Add a PC token
+    pub fn add_pc_token(&mut self) {
+        self.pc_tokens += 1;
+        self.queue.push(TokenType::PC);
+    }
+
+    /// Add an Adversary token
+    pub fn add_adversary_token(&mut self) {
+        self.adversary_tokens += 1;
+        self.queue.push(TokenType::Adversary);
+    }
+}
+
+impl Default for ActionTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Combat encounter state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombatEncounter {
+    pub id: String,
+    pub is_active: bool,
+    pub round: u32,
+    pub action_tracker: ActionTracker,
+    #[serde(default)]
+    pub mode: CombatMode,
+    /// Token holding the spotlight in spotlight mode (`None` = the GM)
+    #[serde(default)]
+    pub spotlight: Option<String>,
+    /// Feet each character has moved since the turn last moved on
+    #[serde(default)]
+    pub moved_feet: HashMap<Uuid, f32>,
+    /// Rolls and combat actions, one entry per round that saw any
+    #[serde(default)]
+    pub history: Vec<RoundHistory>,
+}
+
+impl CombatEncounter {
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            is_active: true,
+            round: 1,
+            action_tracker: ActionTracker::new(),
+            mode: CombatMode::default(),
+            spotlight: None,
+            moved_feet: HashMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Note an event against the current round
+    fn record_action(&mut self, event: &GameEvent) {
+        if self.history.last().map(|r| r.round) != Some(self.round) {
+            self.history.push(RoundHistory::new(self.round));
+        }
+        let data = event.to_data();
+        if let Some(current) = self.history.last_mut() {
+            current.record(RoundAction {
+                timestamp: data.timestamp,
+                who: data.character_name,
+                action: data.message,
+                details: data.details,
+            });
+        }
+    }
+
+    /// What's happened so far in the current round
+    pub fn current_round(&self) -> RoundHistory {
+        self.history
+            .last()
+            .filter(|r| r.round == self.round)
+            .cloned()
+            .unwrap_or_else(|| RoundHistory::new(self.round))
+    }
+}
+
+/// Position keyframes recorded over one combat encounter
+#[derive(Debug, Clone)]
+pub struct CombatRecording {
+    pub encounter_id: String,
+    pub started: std::time::SystemTime,
+    pub keyframes: Vec<PositionKeyframe>,
+}
+
+/// A free-form dice roll and who made it
+#[derive(Debug, Clone)]
+pub struct FreeRoll {
+    /// `None` when the GM rolled
+    pub character_id: Option<Uuid>,
+    pub roller_name: String,
+    pub label: Option<String>,
+    pub roll: DiceRoll,
+    /// Only the GM sees the result
+    pub hidden: bool,
+}
+
+/// Checkpoints kept before the oldest is dropped
+pub const MAX_CHECKPOINTS: usize = 5;
+
+/// An in-memory copy of the game to roll back to
+///
+/// Cheaper than a save file and covers everything, not just characters.
+/// Connections, preferences and the event log aren't captured: rolling back
+/// keeps whoever is connected and the history of what happened.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub id: String,
+    pub label: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    state: Box<GameState>,
+}
+
+impl Checkpoint {
+    pub fn to_info(&self) -> CheckpointInfo {
+        CheckpointInfo {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            created_at: self.created_at.format("%H:%M:%S").to_string(),
+        }
+    }
+}
+
+/// Result of spending the next token on the Action Tracker
+#[derive(Debug, Clone)]
+pub struct TrackerSpend {
+    pub token: TokenType,
+    /// Set when the spend emptied the tracker, so it refilled and a new round began
+    pub round_advanced: Option<(u32, Vec<HazardTrigger>)>,
+}
+
+impl Default for CombatEncounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adversary (enemy) in the game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adversary {
+    pub id: String,
+    pub name: String,
+    pub template: String,
+    pub tier: u8,
+    pub role: AdversaryRole,
+    pub position: crate::protocol::Position,
+    pub hp: u8,
+    pub max_hp: u8,
+    pub stress: u8,
+    pub max_stress: u8,
+    pub evasion: u8,
+    pub armor: u8,
+    pub attack_modifier: i8,
+    pub damage_dice: String,
+    pub damage_type: DamageType,
+    pub defenses: Defenses,
+    pub is_active: bool,
+    /// Number within its template ("Goblin #3"), 0 if unnumbered
+    #[serde(default)]
+    pub instance: usize,
+    /// From the stat block; for the GM's eyes
+    #[serde(default)]
+    pub motives_and_tactics: String,
+    /// Facing, size and elevation on the map
+    #[serde(default)]
+    pub token: TokenDetails,
+    /// Damage dice it marked Stress for, added to its next damage roll
+    #[serde(default)]
+    pub extra_damage_dice: u8,
+}
+
+/// A GM-made adversary's stats, as the GM sends them
+#[derive(Debug, Clone)]
+pub struct CustomAdversary {
+    pub name: String,
+    pub position: Position,
+    pub hp: u8,
+    pub evasion: u8,
+    pub armor: u8,
+    pub attack_modifier: i8,
+    pub damage_dice: String,
+}
+
+impl Adversary {
+    /// Create from template
+    pub fn from_template(
+        template: &crate::adversaries::AdversaryTemplate,
+        position: crate::protocol::Position,
+        instance_number: usize,
+    ) -> Self {
+        let name = if instance_number > 0 {
+            format!("{} #{}", template.name, instance_number)
+        } else {
+            template.name.clone()
+        };
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            template: template.id.clone(),
+            tier: template.tier,
+            role: template.role,
+            position,
+            hp: template.hp,
+            max_hp: template.hp,
+            stress: 0,
+            max_stress: template.hp, // Stress max = HP max in Daggerheart
+            evasion: template.evasion,
+            armor: template.armor,
+            attack_modifier: template.attack_modifier,
+            damage_dice: template.damage.clone(),
+            damage_type: template.damage_type.clone(),
+            defenses: template.defenses.clone(),
+            is_active: true,
+            instance: instance_number,
+            motives_and_tactics: template.motives_and_tactics.clone(),
+            token: TokenDetails::default(),
+            extra_damage_dice: 0,
+        }
+    }
+
+    /// Create custom adversary
+    pub fn custom(
+        name: String,
+        position: crate::protocol::Position,
+        hp: u8,
+        evasion: u8,
+        armor: u8,
+        attack_modifier: i8,
+        damage_dice: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            template: "custom".to_string(),
+            tier: 1,
+            role: AdversaryRole::Standard,
+            position,
+            hp,
+            max_hp: hp,
+            stress: 0,
+            max_stress: hp,
+            evasion,
+            armor,
+            attack_modifier,
+            damage_dice,
+            damage_type: DamageType::physical(),
+            defenses: Defenses::default(),
+            is_active: true,
+            instance: 0,
+            motives_and_tactics: String::new(),
+            token: TokenDetails::default(),
+            extra_damage_dice: 0,
+        }
+    }
+
+    /// A reminder of how it fights, `None` if its stat block gives none
+    pub fn tactics(&self) -> Option<AdversaryTactics> {
+        if self.motives_and_tactics.is_empty() {
+            return None;
+        }
+        Some(AdversaryTactics {
+            adversary_id: self.id.clone(),
+            name: self.name.clone(),
+            motives_and_tactics: self.motives_and_tactics.clone(),
+        })
+    }
+
+    /// Take damage (returns true if taken out)
+    pub fn take_damage(&mut self, hp_loss: u8, stress_gain: u8) -> bool {
+        if hp_loss > 0 {
+            self.hp = self.hp.saturating_sub(hp_loss);
+        }
+
+        if stress_gain > 0 {
+            self.stress = (self.stress + stress_gain).min(self.max_stress);
+        }
+
+        // Taken out if HP = 0 and Stress = max
+        if self.hp == 0 && self.stress >= self.max_stress {
+            self.is_active = false;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Adversaries the GM runs as one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdversaryGroup {
+    pub id: String,
+    pub name: String,
+    pub member_ids: Vec<String>,
+    /// Cleared once every member is taken out
+    pub is_active: bool,
+}
+
+/// How a group took its activation
+#[derive(Debug, Clone)]
+pub enum GroupActivation {
+    /// Spotlight flow: the spotlight went to the group's first standing member
+    Spotlight {
+        previous_id: Option<String>,
+        holder_id: String,
+    },
+    /// The group spent the adversary token at the front of the Action Tracker
+    Tracker(TrackerSpend),
+}
+
+/// An environment setting the current scene
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Environment {
+    pub id: String,
+    pub template: EnvironmentTemplate,
+}
+
+impl Environment {
+    pub fn from_template(template: EnvironmentTemplate) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            template,
+        }
+    }
+
+    /// Find a feature by name (case-insensitive)
+    pub fn feature(&self, name: &str) -> Option<&EnvironmentFeature> {
+        self.template
+            .features
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn to_info(&self) -> EnvironmentInfo {
+        let t = &self.template;
+        EnvironmentInfo {
+            id: self.id.clone(),
+            template: t.id.clone(),
+            name: t.name.clone(),
+            tier: t.tier,
+            kind: t.kind,
+            difficulty: t.difficulty,
+            description: t.description.clone(),
+            impulses: t.impulses.clone(),
+            features: t.features.clone(),
+            potential_adversaries: t.potential_adversaries.clone(),
+        }
+    }
+}
+
+/// An aura attached to a token, with the tokens currently inside it
+#[derive(Debug, Clone)]
+pub struct AttachedAura {
+    pub id: String,
+    pub owner_id: String,
+    pub aura: Aura,
+    pub members: HashSet<String>,
+}
+
+/// A token entering or leaving an aura
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuraChange {
+    Entered {
+        aura_id: String,
+        aura_name: String,
+        token_id: String,
+        token_name: String,
+    },
+    Left {
+        aura_id: String,
+        aura_name: String,
+        token_id: String,
+        token_name: String,
+    },
+    /// The owner is gone, so the aura went with it
+    Removed { aura_id: String, aura_name: String },
+}
+
+/// An ambient hazard placed on the scene
+#[derive(Debug, Clone)]
+pub struct SceneHazard {
+    pub id: String,
+    pub hazard: Hazard,
+    pub active: bool,
+    /// Ticks left before the hazard next triggers
+    pub ticks_remaining: u8,
+}
+
+/// A hazard going off, with the tokens caught in its area
+#[derive(Debug, Clone, PartialEq)]
+pub struct HazardTrigger {
+    pub hazard_id: String,
+    pub name: String,
+    pub outcome: HazardOutcome,
+    /// (token id, token name) of each character and active adversary inside
+    pub targets: Vec<(String, String)>,
+}
+
+/// A character's walk across the map
+#[derive(Debug, Clone, PartialEq)]
+pub struct Movement {
+    /// Start, waypoints and destination
+    pub path: Vec<Position>,
+    /// (hazard id, hazard name) of each active hazard the route passed through
+    pub crossed_hazards: Vec<(String, String)>,
+    /// Degrees the token faces on arrival
+    pub rotation: f32,
+}
+
+/// A character's or adversary's state after taking damage
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedDamage {
+    pub target_name: String,
+    pub new_hp: u8,
+    pub new_stress: u8,
+    pub taken_out: bool,
+}
+
+/// One roll of area damage and what it did to each target
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaDamage {
+    pub roll: DiceRoll,
+    pub damage_type: DamageType,
+    pub targets: Vec<AreaDamageTarget>,
+}
+
+/// An adversary's d20 attack roll against a character's Evasion
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdversaryAttackRoll {
+    pub adversary_name: String,
+    pub target_name: String,
+    /// Every d20 rolled; two with advantage or disadvantage
+    pub rolls: Vec<u8>,
+    /// The d20 that counts
+    pub die: u8,
+    pub modifier: i8,
+    pub total: i16,
+    pub target_evasion: i32,
+    pub hit: bool,
+    /// A natural 20 always hits
+    pub is_critical: bool,
+    pub damage_dice: String,
+    pub damage_type: DamageType,
+}
+
+/// A token on the map for aura and hazard checks
+struct MapToken {
+    id: String,
+    name: String,
+    position: Position,
+    is_adversary: bool,
+}
+
+/// Damage as rolled, before the target's defenses and armor
+#[derive(Debug, Clone)]
+pub struct DamageRoll {
+    pub roll: DiceRoll,
+    /// What a critical hit added: the most the dice could have rolled
+    pub critical_bonus: u16,
+    /// The roll plus any critical bonus
+    pub raw_damage: u16,
+}
+
+/// Itemized damage: the roll, the type adjustment, then armor and thresholds
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageBreakdown {
+    pub raw_damage: u16,
+    pub damage_type: DamageType,
+    pub adjustment: DamageAdjustment,
+    pub adjusted_damage: u16,
+    pub after_armor: u16,
+    pub hp_lost: u8,
+    pub stress_gained: u8,
+}
+
+/// Apply the target's defenses to rolled damage, then armor and thresholds
+///
+/// Resistance and vulnerability act on the rolled damage before armor; an
+/// immune target takes nothing at all.
+pub fn resolve_damage(
+    raw_damage: u16,
+    armor: u8,
+    damage_type: DamageType,
+    defenses: &Defenses,
+) -> DamageBreakdown {
+    let adjustment = defenses.adjustment(&damage_type);
+    let adjusted_damage = adjustment.apply(raw_damage);
+
+    let (after_armor, hp_lost, stress_gained) = if adjusted_damage == 0 {
+        (0, 0, 0)
+    } else {
+        let result = DamageResult::calculate(adjusted_damage, armor);
+        (result.after_armor, result.hp_lost, result.stress_gained)
+    };
+
+    DamageBreakdown {
+        raw_damage,
+        damage_type,
+        adjustment,
+        adjusted_damage,
+        after_armor,
+        hp_lost,
+        stress_gained,
+    }
+}
+
+/// A character in the game (persistent entity)
+#[derive(Debug, Clone, Serialize)]
+pub struct Character {
+    pub id: Uuid,
+    pub name: String,
+    pub class: Class,
+    pub subclass: Option<Subclass>,
+    pub ancestry: Ancestry,
+    pub attributes: Attributes,
+    #[serde(skip)]
+    pub hp: HitPoints,
+    #[serde(skip)]
+    pub stress: Stress,
+    #[serde(skip)]
+    pub hope: Hope,
+    pub evasion: i32,
+    pub position: Position,
+    pub color: String,
+    pub is_npc: bool,
+
+    /// Facing, size and elevation on the map
+    pub token: TokenDetails,
+
+    /// Adversary template an NPC takes its stats from
+    pub adversary_template: Option<String>,
+
+    // Phase 1: Experience system
+    pub level: u8,
+    pub experiences: Vec<Experience>,
+
+    // Items and gold
+    pub inventory: Inventory,
+
+    // Equipped weapon and armor
+    pub equipment: Equipment,
+
+    // Advancements from leveling up
+    pub progression: Progression,
+
+    // Resistances, immunities and vulnerabilities
+    pub defenses: Defenses,
+
+    // Serializable resource values (for save/load)
+    pub hp_current: u8,
+    pub hp_max: u8,
+    pub stress_current: u8,
+    pub hope_current: u8,
+    pub hope_max: u8,
+}
+
+impl Character {
+    /// Create new player character
+    pub fn new(
+        name: String,
+        class: Class,
+        ancestry: Ancestry,
+        attributes: Attributes,
+        position: Position,
+        color: String,
+    ) -> Self {
+        // Calculate HP
+        let base_hp = class.starting_hp() as i32;
+        let hp_modifier = ancestry.hp_modifier();
+        let max_hp = (base_hp + hp_modifier as i32).max(1) as u8;
+
+        // Calculate Evasion
+        let base_evasion = class.starting_evasion() as i32;
+        let evasion_modifier = ancestry.evasion_modifier();
+        let evasion = base_evasion + evasion_modifier as i32;
+
+        let hp = HitPoints::new(max_hp);
+        let stress = Stress::new();
+        let hope = Hope::new(5); // Standard starting Hope
+
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            class,
+            subclass: None,
+            ancestry,
+            attributes,
+            hp,
+            stress,
+            hope,
+            evasion,
+            position,
+            color,
+            is_npc: false,
+            token: TokenDetails::default(),
+            adversary_template: None,
+            level: 1,                // Start at level 1
+            experiences: Vec::new(), // Start with no Experiences
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
+            progression: Progression::default(),
+            defenses: Defenses::default(),
+            hp_current: max_hp,
+            hp_max: max_hp,
+            stress_current: 0,
+            hope_current: 5,
+            hope_max: 5,
+        }
+    }
+
+    /// Create NPC character
+    pub fn new_npc(
+        name: String,
+        class: Class,
+        ancestry: Ancestry,
+        attributes: Attributes,
+        position: Position,
+        color: String,
+        hp_max: u8,
+    ) -> Self {
+        let hp = HitPoints::new(hp_max);
+        let stress = Stress::new();
+        let hope = Hope::new(0); // NPCs typically don't have Hope
+
+        // Calculate Evasion
+        let base_evasion = class.starting_evasion() as i32;
+        let evasion_modifier = ancestry.evasion_modifier();
+        let evasion = base_evasion + evasion_modifier as i32;
+
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            class,
+            subclass: None,
+            ancestry,
+            attributes,
+            hp,
+            stress,
+            hope,
+            evasion,
+            position,
+            color,
+            is_npc: true,
+            token: TokenDetails::default(),
+            adversary_template: None,
+            level: 1,
+            experiences: Vec::new(),
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
+            progression: Progression::default(),
+            defenses: Defenses::default(),
+            hp_current: hp_max,
+            hp_max,
+            stress_current: 0,
+            hope_current: 0,
+            hope_max: 0,
+        }
+    }
+
+    /// Sync serializable fields with runtime resources
+    pub fn sync_resources(&mut self) {
+        self.hp_current = self.hp.current;
+        self.hp_max = self.hp.maximum;
+        self.stress_current = self.stress.current;
+        self.hope_current = self.hope.current;
+        self.hope_max = self.hope.maximum;
+    }
+
+    /// Restore runtime resources from serializable fields
+    pub fn restore_resources(&mut self) {
+        self.hp = HitPoints::new(self.hp_max);
+        if self.hp_current < self.hp_max {
+            let damage = self.hp_max - self.hp_current;
+            self.hp.take_damage(damage);
+        }
+
+        self.stress = Stress::new();
+        self.stress.gain(self.stress_current);
+
+        self.hope = Hope::new(self.hope_max);
+        if self.hope_current < self.hope_max {
+            let spent = self.hope_max - self.hope_current;
+            let _ = self.hope.spend(spent);
+        }
+    }
+
+    /// Convert to protocol CharacterData
+    pub fn to_data(&self) -> CharacterData {
+        CharacterData {
+            name: self.name.clone(),
+            class: self.class.to_string(),
+            subclass: self.subclass.clone(),
+            ancestry: self.ancestry.to_string(),
+            attributes: AttributesData {
+                agility: self.get_attribute("agility").unwrap_or(0),
+                strength: self.get_attribute("strength").unwrap_or(0),
+                finesse: self.get_attribute("finesse").unwrap_or(0),
+                instinct: self.get_attribute("instinct").unwrap_or(0),
+                presence: self.get_attribute("presence").unwrap_or(0),
+                knowledge: self.get_attribute("knowledge").unwrap_or(0),
+            },
+            hp: ResourceData {
+                current: self.hp.current as i32,
+                maximum: self.hp.maximum as i32,
+            },
+            stress: self.stress.current as i32,
+            stress_max: self.progression.stress_slots() as i32,
+            hope: ResourceData {
+                current: self.hope.current as i32,
+                maximum: self.hope.maximum as i32,
+            },
+            evasion: self.evasion,
+            level: self.level,
+            experiences: self.experiences.clone(),
+            inventory: self.inventory.clone(),
+            equipment: self.equipment.clone(),
+            progression: self.progression.clone(),
+            defenses: self.defenses.clone(),
+        }
+    }
+
+    /// Get proficiency bonus based on level (Phase 1)
+    pub fn proficiency_bonus(&self) -> i8 {
+        let base = match self.level {
+            1..=3 => 1,
+            4..=6 => 2,
+            7..=9 => 3,
+            _ => 4,
+        };
+        base + self.progression.proficiency_bonus()
+    }
+
+    /// Get attribute modifier by name (Phase 1)
+    pub fn get_attribute(&self, attr_name: &str) -> Option<i8> {
+        let base = match attr_name.to_lowercase().as_str() {
+            "agility" => self.attributes.agility,
+            "strength" => self.attributes.strength,
+            "finesse" => self.attributes.finesse,
+            "instinct" => self.attributes.instinct,
+            "presence" => self.attributes.presence,
+            "knowledge" => self.attributes.knowledge,
+            _ => return None,
+        };
+        Some(base + self.progression.trait_bonus(attr_name))
+    }
+
+    /// Attribute and proficiency modifiers for a roll of this type
+    pub fn roll_modifiers(&self, roll_type: &RollType, attribute: Option<&str>) -> (i8, i8) {
+        let attr_mod = attribute
+            .and_then(|attr| self.get_attribute(attr))
+            .unwrap_or(0);
+        let prof_mod = match roll_type {
+            RollType::Attack | RollType::Spellcast => self.proficiency_bonus(),
+            _ => 0,
+        };
+        (attr_mod, prof_mod)
+    }
+
+    /// Level up with the chosen advancements (and tier achievement Experience)
+    ///
+    /// Nothing changes unless every choice is legal.
+    pub fn apply_level_up(
+        &mut self,
+        advancements: Vec<Advancement>,
+        new_experience: Option<String>,
+    ) -> Result<u8, String> {
+        if !self.progression.pending_level_up {
+            return Err(format!("{} has no pending level up", self.name));
+        }
+        let new_level = self.level + 1;
+
+        // Tier achievements grant a new Experience
+        let mut experiences = self.experiences.clone();
+        match (leveling::is_tier_achievement(new_level), new_experience) {
+            (true, Some(name)) => {
+                let name = name.trim().to_string();
+                if name.is_empty() || self.get_experience(&name).is_some() {
+                    return Err(
+                        "Choose a new, unique Experience for your tier achievement".to_string()
+                    );
+                }
+                experiences.push(Experience::new(name, DEFAULT_EXPERIENCE_MODIFIER));
+            }
+            (true, None) => {
+                return Err(format!("Level {} grants a new Experience", new_level));
+            }
+            (false, Some(_)) => {
+                return Err(format!(
+                    "Level {} does not grant a new Experience",
+                    new_level
+                ));
+            }
+            (false, None) => {}
+        }
+
+        self.progression
+            .validate_choices(new_level, &advancements, &experiences)?;
+
+        self.level = new_level;
+        self.experiences = experiences;
+        self.progression.pending_level_up = false;
+        if let Some(subclass) = self.subclass.as_mut() {
+            subclass.unlock_for_level(new_level);
+        }
+        if leveling::clears_marked_traits(new_level) {
+            self.progression.marked_traits.clear();
+        }
+
+        for advancement in advancements {
+            match &advancement {
+                Advancement::TraitBoost { traits } => {
+                    self.progression
+                        .marked_traits
+                        .extend(traits.iter().map(|t| t.to_lowercase()));
+                }
+                Advancement::HitPointSlot => {
+                    self.sync_resources();
+                    self.hp_max += 1;
+                    self.hp_current += 1;
+                    self.restore_resources();
+                }
+                Advancement::ExperienceBoost { experiences } => {
+                    for experience in self
+                        .experiences
+                        .iter_mut()
+                        .filter(|e| experiences.iter().any(|n| n.eq_ignore_ascii_case(&e.name)))
+                    {
+                        experience.modifier += 1;
+                    }
+                }
+                Advancement::Evasion => self.evasion += 1,
+                // Counted from the advancement record
+                Advancement::StressSlot
+                | Advancement::DomainCard { .. }
+                | Advancement::Proficiency => {}
+            }
+            self.progression.advancements.push(TakenAdvancement {
+                level: new_level,
+                advancement,
+            });
+        }
+
+        Ok(new_level)
+    }
+
+    /// Find an Experience by name (case insensitive)
+    pub fn get_experience(&self, name: &str) -> Option<&Experience> {
+        self.experiences
+            .iter()
+            .find(|e| e.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Trait modifier for attacks with the equipped weapon (0 if unarmed)
+    pub fn weapon_trait_modifier(&self) -> i8 {
+        self.equipment
+            .weapon
+            .as_ref()
+            .and_then(|w| self.get_attribute(&w.trait_name))
+            .unwrap_or(0)
+    }
+
+    /// Damage dice of the equipped weapon
+    pub fn weapon_damage_dice(&self) -> Option<String> {
+        self.equipment
+            .weapon
+            .as_ref()
+            .map(|w| w.damage_dice.clone())
+    }
+
+    /// Damage type of the equipped weapon (physical when unarmed)
+    pub fn weapon_damage_type(&self) -> DamageType {
+        self.equipment
+            .weapon
+            .as_ref()
+            .map(|w| w.damage_type.clone())
+            .unwrap_or_default()
+    }
+
+    /// Armor score from equipped armor (0 if unarmored)
+    pub fn armor_score(&self) -> u8 {
+        self.equipment
+            .armor
+            .as_ref()
+            .map(|a| a.base_score)
+            .unwrap_or(0)
+    }
+}
+
+/// A WebSocket connection (ephemeral)
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub id: Uuid,
+    /// Name the player chose for themselves
+    pub display_name: Option<String>,
+    /// Key the player's preferences are stored under
+    pub session_token: Option<String>,
+    /// Decides which broadcasts reach this connection
+    pub role: ConnectionRole,
+    /// Where the connection came from, for bans
+    pub address: Option<IpAddr>,
+}
+
+impl Connection {
+    pub fn new() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            display_name: None,
+            session_token: None,
+            role: ConnectionRole::default(),
+            address: None,
+        }
+    }
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The global game state
+#[derive(Debug, Clone, Default)]
+pub struct GameState {
+    /// All characters in the game (persistent)
+    pub characters: HashMap<Uuid, Character>,
+
+    /// Active WebSocket connections (ephemeral)
+    pub connections: HashMap<Uuid, Connection>,
+
+    /// Which connection controls which characters, most recently selected
+    /// last (a GM or a parent can drive more than one)
+    pub control_mapping: HashMap<Uuid, Vec<Uuid>>, // connection_id -> character_ids
+
+    /// Color assignment index
+    pub(crate) color_index: usize,
+
+    /// Phase 1: Pending roll requests
+    pub pending_roll_requests: HashMap<String, PendingRollRequest>,
+
+    /// Phase 1: GM Fear pool
+    pub fear_pool: u8,
+    
+    /// Game event log
+    pub event_log: Vec<GameEvent>,
+
+    /// Events logged since the game was created, including those trimmed
+    /// from the log since
+    pub events_logged: u64,
+
+    /// Combat encounter (if active)
+    pub combat_encounter: Option<CombatEncounter>,
+    
+    /// Adversaries in the game
+    pub adversaries: HashMap<String, Adversary>,
+
+    /// Adversaries run as one (group_id -> group)
+    pub adversary_groups: HashMap<String, AdversaryGroup>,
+
+    /// Characters whose player dropped mid-combat (character_id -> stand-in connection_id)
+    pub ghosted_characters: HashMap<Uuid, Option<Uuid>>,
+
+    /// Quotas enforced on characters, adversaries and the event log
+    pub limits: ResourceLimits,
+
+    /// Directory homebrew adversary templates are loaded from
+    pub adversary_dir: PathBuf,
+
+    /// Homebrew templates loaded from `adversary_dir`
+    pub homebrew_templates: Vec<AdversaryTemplate>,
+
+    /// Environments setting the scene (environment_id -> environment)
+    pub environments: HashMap<String, Environment>,
+
+    /// Auras attached to characters and adversaries (aura_id -> aura)
+    pub auras: HashMap<String, AttachedAura>,
+
+    /// Buffs and debuffs on characters and adversaries (modifier_id -> modifier)
+    pub modifiers: HashMap<String, TimedModifier>,
+
+    /// Ambient hazards on the scene (hazard_id -> hazard)
+    pub hazards: HashMap<String, SceneHazard>,
+
+    /// When each character last emoted, for rate limiting
+    pub(crate) last_emotes: HashMap<Uuid, std::time::Instant>,
+
+    /// When each connection last pinged the map, for rate limiting
+    pub(crate) last_pings: HashMap<Uuid, std::time::Instant>,
+
+    /// Position keyframes of recent combat encounters, oldest first
+    pub combat_recordings: Vec<CombatRecording>,
+
+    /// House rules and options the GM sets for the table
+    pub settings: TableSettings,
+
+    /// Phrasings used for flavor text
+    pub narrative_templates: NarrativeTemplates,
+
+    /// Player preferences by session token
+    pub preferences: HashMap<String, UserPreferences>,
+
+    /// Adversaries spawned so far per template, for unique instance numbers
+    pub adversary_spawn_counts: HashMap<String, usize>,
+
+    /// Rollback points taken at combat starts and before loads, oldest first
+    pub checkpoints: Vec<Checkpoint>,
+
+    /// When the table next meets, if the GM has set it
+    pub next_session: Option<ScheduledSession>,
+
+    /// The GM's notes and those shared with the table, oldest first
+    pub notes: Vec<Note>,
+
+    /// The quest board, in the order quests went up
+    pub quests: Vec<Quest>,
+
+    /// Recent chat, oldest first, at most [`MAX_CHAT_HISTORY`]
+    pub chat_log: Vec<ChatEntry>,
+
+    /// The scene the GM cut away to, if any
+    pub cutaway: Option<Cutaway>,
+
+    /// The battle map on the table, if the GM has put one down
+    pub map: Option<MapInfo>,
+
+    /// The scene's size and grid; tokens are kept inside it and snap to the grid
+    pub layout: SceneLayout,
+
+    /// Fog of war for each scene, keyed by map id ("" with no map on the table)
+    pub fog: HashMap<String, FogState>,
+
+    /// The GM's drawings for each scene, keyed like [`GameState::fog`]
+    pub drawings: HashMap<String, Vec<Drawing>>,
+
+    /// Whether the GM has locked token movement for players
+    pub map_locked: bool,
+
+    /// Addresses the GM banned; they can't reconnect until the server restarts
+    pub banned_addresses: HashSet<IpAddr>,
+
+    /// Every roll draws from these; seeded for tests and replays
+    pub dice: DiceRng,
+
+    /// How each character's duality dice have come up this session
+    pub roll_stats: HashMap<Uuid, RollStats>,
+
+    /// Attacks that were critical hits and haven't rolled damage yet
+    /// (attacker_id, target_id)
+    pub(crate) critical_hits: HashSet<(String, String)>,
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        let settings = TableSettings::default();
+        Self {
+            characters: HashMap::new(),
+            connections: HashMap::new(),
+            control_mapping: HashMap::new(),
+            color_index: 0,
+            pending_roll_requests: HashMap::new(),
+            fear_pool: settings.starting_fear,
+            event_log: Vec::new(),
+            events_logged: 0,
+            combat_encounter: None,
+            adversaries: HashMap::new(),
+            adversary_groups: HashMap::new(),
+            ghosted_characters: HashMap::new(),
+            limits: ResourceLimits::default(),
+            adversary_dir: PathBuf::from(DEFAULT_ADVERSARY_DIR),
+            homebrew_templates: Vec::new(),
+            environments: HashMap::new(),
+            auras: HashMap::new(),
+            modifiers: HashMap::new(),
+            hazards: HashMap::new(),
+            last_emotes: HashMap::new(),
+            last_pings: HashMap::new(),
+            combat_recordings: Vec::new(),
+            settings,
+            narrative_templates: narrative::builtin_templates(),
+            preferences: HashMap::new(),
+            adversary_spawn_counts: HashMap::new(),
+            checkpoints: Vec::new(),
+            next_session: None,
+            notes: Vec::new(),
+            quests: Vec::new(),
+            chat_log: Vec::new(),
+            cutaway: None,
+            map: None,
+            layout: SceneLayout::default(),
+            fog: HashMap::new(),
+            drawings: HashMap::new(),
+            map_locked: false,
+            banned_addresses: HashSet::new(),
+            dice: DiceRng::default(),
+            roll_stats: HashMap::new(),
+            critical_hits: HashSet::new(),
+        }
+    }
+
+    /// Add a new connection
+    pub fn add_connection(&mut self) -> Connection {
+        let conn = Connection::new();
+        self.connections.insert(conn.id, conn.clone());
+        conn
+    }
+
+    /// Attach a session token to a connection, returning it with its preferences
+    ///
+    /// A token the server has stored preferences for is kept; anything else
+    /// gets a fresh token. Preferences are only stored once a player saves
+    /// some, so tokens cost nothing until then.
+    pub fn resume_session(
+        &mut self,
+        conn_id: &Uuid,
+        token: Option<&str>,
+    ) -> Result<(String, UserPreferences), String> {
+        let conn = self
+            .connections
+            .get_mut(conn_id)
+            .ok_or_else(|| "Connection not found".to_string())?;
+
+        let (token, preferences) = match token.and_then(|t| self.preferences.get_key_value(t)) {
+            Some((token, preferences)) => (token.clone(), preferences.clone()),
+            None => (Uuid::new_v4().to_string(), UserPreferences::default()),
+        };
+        conn.session_token = Some(token.clone());
+        Ok((token, preferences))
+    }
+
+    /// Save a connection's preferences under its session token
+    pub fn set_preferences(
+        &mut self,
+        conn_id: &Uuid,
+        preferences: UserPreferences,
+    ) -> Result<UserPreferences, String> {
+        preferences.validate()?;
+        let token = self
+            .connections
+            .get(conn_id)
+            .and_then(|c| c.session_token.clone())
+            .ok_or_else(|| "Connection has no session".to_string())?;
+
+        self.preferences.insert(token, preferences.clone());
+        Ok(preferences)
+    }
+
+    /// Remove a connection and its control mapping
+    ///
+    /// If the connection controlled a character during active combat, the
+    /// character is ghosted so the GM can stand in until the player returns.
+    pub fn remove_connection(&mut self, conn_id: &Uuid) -> Option<Connection> {
+        let in_combat = self.combat_encounter.as_ref().is_some_and(|e| e.is_active);
+        for char_id in self.control_mapping.remove(conn_id).unwrap_or_default() {
+            if let Some(stand_in) = self.ghosted_characters.get_mut(&char_id) {
+                // The stand-in left; the character goes back to waiting for its player
+                *stand_in = None;
+            } else if in_combat {
+                self.ghost_character(&char_id);
+            }
+        }
+        self.connections.remove(conn_id)
+    }
+
+    /// Throw a connection off the table (the GM's call), optionally banning
+    /// its address for the rest of the session, returning its name
+    ///
+    /// Only the event is logged here; the caller removes the connection once
+    /// it has noted what it controlled.
+    pub fn kick_connection(
+        &mut self,
+        by: &Uuid,
+        conn_id: &Uuid,
+        ban: bool,
+    ) -> Result<String, String> {
+        if by == conn_id {
+            return Err("You can't kick yourself".to_string());
+        }
+        let conn = self
+            .connections
+            .get(conn_id)
+            .ok_or("Connection not found")?;
+        let name = conn
+            .display_name
+            .clone()
+            .unwrap_or_else(|| "A connection".to_string());
+
+        let address = conn.address;
+        if ban {
+            let address = address.ok_or("That connection's address is unknown")?;
+            self.banned_addresses.insert(address);
+        }
+
+        let message = if ban {
+            format!("{} was kicked and banned", name)
+        } else {
+            format!("{} was kicked", name)
+        };
+        self.add_event(GameEventType::SystemMessage, message, None, None);
+        Ok(name)
+    }
+
+    /// Whether an address was banned this session
+    pub fn is_banned(&self, address: &IpAddr) -> bool {
+        self.banned_addresses.contains(address)
+    }
+
+    /// Mark a character as uncontrolled mid-combat
+    fn ghost_character(&mut self, char_id: &Uuid) {
+        let Some(name) = self.characters.get(char_id).map(|c| c.name.clone()) else {
+            return;
+        };
+
+        self.ghosted_characters.insert(*char_id, None);
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} lost their player mid-combat", name),
+            Some(name),
+            Some("The GM can take control until they return".to_string()),
+        );
+    }
+
+    /// Check whether a character is ghosted (player dropped mid-combat)
+    pub fn is_ghosted(&self, char_id: &Uuid) -> bool {
+        self.ghosted_characters.contains_key(char_id)
+    }
+
+    /// Let a GM connection temporarily control a ghosted character
+    pub fn assume_control(&mut self, conn_id: &Uuid, char_id: &Uuid) -> Result<(), String> {
+        if !self.connections.contains_key(conn_id) {
+            return Err("Connection not found".to_string());
+        }
+
+        match self.ghosted_characters.get(char_id) {
+            None => return Err("Character is not awaiting a player".to_string()),
+            Some(Some(stand_in)) if stand_in != conn_id => {
+                return Err("Character already controlled by another connection".to_string());
+            }
+            _ => {}
+        }
+
+        // The GM can stand in for several players at once
+        self.claim_character(conn_id, char_id, true);
+        self.ghosted_characters.insert(*char_id, Some(*conn_id));
+
+        let name = self
+            .characters
+            .get(char_id)
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("GM took control of {}", name),
+            Some(name),
+            None,
+        );
+
+        Ok(())
+    }
+
+    /// The connection controlling a character, if any
+    pub fn controller_of(&self, char_id: &Uuid) -> Option<Uuid> {
+        self.control_mapping
+            .iter()
+            .find(|(_, ids)| ids.contains(char_id))
+            .map(|(conn_id, _)| *conn_id)
+    }
+
+    /// Characters a connection controls, most recently selected last
+    pub fn controlled_characters(&self, conn_id: &Uuid) -> &[Uuid] {
+        self.control_mapping
+            .get(conn_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Whether a connection controls a character
+    pub fn controls(&self, conn_id: &Uuid, char_id: &Uuid) -> bool {
+        self.controlled_characters(conn_id).contains(char_id)
+    }
+
+    /// The character a connection is acting as
+    ///
+    /// Clients driving several characters name the one they mean; without
+    /// a name it's the one selected last, so single-character clients never
+    /// need to.
+    pub fn acting_character(
+        &self,
+        conn_id: &Uuid,
+        character_id: Option<&str>,
+    ) -> Result<Uuid, String> {
+        match character_id {
+            Some(id) => {
+                let char_id = Uuid::parse_str(id).map_err(|_| "Invalid character ID")?;
+                if !self.controls(conn_id, &char_id) {
+                    return Err("You don't control that character".to_string());
+                }
+                Ok(char_id)
+            }
+            None => self
+                .controlled_characters(conn_id)
+                .last()
+                .copied()
+                .ok_or_else(|| "No character selected".to_string()),
+        }
+    }
+
+    /// Put a character under a connection's control, taking it from anyone
+    /// else; `keep_others` adds it to what the connection already controls
+    /// instead of switching
+    fn claim_character(&mut self, conn_id: &Uuid, char_id: &Uuid, keep_others: bool) {
+        self.release_character(char_id);
+        let controlled = self.control_mapping.entry(*conn_id).or_default();
+        if !keep_others {
+            controlled.clear();
+        }
+        controlled.push(*char_id);
+    }
+
+    /// Take a character away from whoever controls it
+    fn release_character(&mut self, char_id: &Uuid) {
+        self.control_mapping.retain(|_, ids| {
+            ids.retain(|id| id != char_id);
+            !ids.is_empty()
+        });
+    }
+
+    /// Hand a character to a connection (the GM's call, e.g. when a player
+    /// switches devices), returning the connection that had it
+    ///
+    /// The new connection keeps whatever else it controls, and a ghosted
+    /// character counts as reclaimed.
+    pub fn transfer_control(
+        &mut self,
+        char_id: &Uuid,
+        to_conn_id: &Uuid,
+    ) -> Result<Option<Uuid>, String> {
+        let name = self
+            .characters
+            .get(char_id)
+            .ok_or("Character not found")?
+            .name
+            .clone();
+        let to = self
+            .connections
+            .get(to_conn_id)
+            .ok_or("Connection not found")?;
+        let to_name = to
+            .display_name
+            .clone()
+            .unwrap_or_else(|| "another device".to_string());
+
+        let from = self.controller_of(char_id);
+        if from == Some(*to_conn_id) {
+            return Err(format!("{} is already controlled there", name));
+        }
+        self.ghosted_characters.remove(char_id);
+        self.claim_character(to_conn_id, char_id, true);
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} is now played by {}", name, to_name),
+            Some(name),
+            None,
+        );
+        Ok(from)
+    }
+
+    /// Free a character from whoever controls it (the GM's force release),
+    /// returning that connection
+    pub fn release_control(&mut self, char_id: &Uuid) -> Result<Option<Uuid>, String> {
+        let name = self
+            .characters
+            .get(char_id)
+            .ok_or("Character not found")?
+            .name
+            .clone();
+        let from = self.controller_of(char_id);
+        self.release_character(char_id);
+        // A ghosted character keeps waiting for its player, just without a stand-in
+        if let Some(stand_in) = self.ghosted_characters.get_mut(char_id) {
+            *stand_in = None;
+        }
+
+        if from.is_some() {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("GM released {}", name),
+                Some(name),
+                None,
+            );
+        }
+        Ok(from)
+    }
+
+    /// Create a new character
+    pub fn create_character(
+        &mut self,
+        name: String,
+        class: Class,
+        ancestry: Ancestry,
+        attributes: Attributes,
+    ) -> Character {
+        let color = self.assign_color();
+        let position = self.random_position();
+
+        let character = Character::new(name, class, ancestry, attributes, position, color);
+        self.characters.insert(character.id, character.clone());
+        character
+    }
+
+    /// Create a GM-controlled NPC
+    ///
+    /// HP and Evasion come from `hp`/`evasion` when given, otherwise from
+    /// the linked adversary template.
+    pub fn create_npc(
+        &mut self,
+        name: String,
+        hp: Option<u8>,
+        evasion: Option<i32>,
+        template_id: Option<&str>,
+        position: Option<Position>,
+    ) -> Result<Character, String> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("NPC name cannot be empty".to_string());
+        }
+        self.limits.check_characters(self.characters.len())?;
+
+        let template = match template_id {
+            Some(id) => Some(
+                self.find_adversary_template(id)
+                    .ok_or_else(|| format!("Template not found: {}", id))?,
+            ),
+            None => None,
+        };
+
+        let hp = hp
+            .or(template.as_ref().map(|t| t.hp))
+            .ok_or("NPC needs HP or an adversary template")?;
+        if hp == 0 {
+            return Err("NPC HP must be at least 1".to_string());
+        }
+
+        let color = self.assign_color();
+        let position = position.unwrap_or_else(|| self.random_position());
+        let attributes = Attributes::from_array(NPC_ATTRIBUTES)
+            .map_err(|e| format!("Invalid attributes: {}", e))?;
+
+        let mut npc = Character::new_npc(
+            name,
+            Class::Warrior,
+            Ancestry::Human,
+            attributes,
+            position,
+            color,
+            hp,
+        );
+        if let Some(evasion) = evasion.or(template.as_ref().map(|t| t.evasion as i32)) {
+            npc.evasion = evasion;
+        }
+        npc.adversary_template = template.map(|t| t.id);
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} entered the scene", npc.name),
+            Some(npc.name.clone()),
+            Some(format!("NPC, HP: {}, Evasion: {}", hp, npc.evasion)),
+        );
+
+        self.characters.insert(npc.id, npc.clone());
+        Ok(npc)
+    }
+
+    /// Select a character for a connection to control, in place of any it
+    /// controlled before
+    pub fn select_character(&mut self, conn_id: &Uuid, char_id: &Uuid) -> Result<(), String> {
+        self.take_character(conn_id, char_id, false)
+    }
+
+    /// Control another character alongside the ones a connection already has
+    pub fn add_controlled_character(
+        &mut self,
+        conn_id: &Uuid,
+        char_id: &Uuid,
+    ) -> Result<(), String> {
+        self.take_character(conn_id, char_id, true)
+    }
+
+    fn take_character(
+        &mut self,
+        conn_id: &Uuid,
+        char_id: &Uuid,
+        keep_others: bool,
+    ) -> Result<(), String> {
+        if !self.connections.contains_key(conn_id) {
+            return Err("Connection not found".to_string());
+        }
+
+        if !self.characters.contains_key(char_id) {
+            return Err("Character not found".to_string());
+        }
+
+        if self.ghosted_characters.remove(char_id).is_some() {
+            // A returning player reclaims a ghosted character from its
+            // stand-in, which claim_character takes it from
+            let name = self.characters[char_id].name.clone();
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{} was reclaimed by their player", name),
+                Some(name),
+                None,
+            );
+        } else if self
+            .controller_of(char_id)
+            .is_some_and(|controller| controller != *conn_id)
+        {
+            return Err("Character already controlled by another connection".to_string());
+        }
+
+        self.claim_character(conn_id, char_id, keep_others);
+        Ok(())
+    }
+
+    /// Get the character a connection selected last
+    pub fn get_controlled_character(&self, conn_id: &Uuid) -> Option<&Character> {
+        let char_id = self.controlled_characters(conn_id).last()?;
+        self.characters.get(char_id)
+    }
+
+    /// Get mutable reference to the character a connection selected last
+    pub fn get_controlled_character_mut(&mut self, conn_id: &Uuid) -> Option<&mut Character> {
+        let char_id = *self.controlled_characters(conn_id).last()?;
+        self.characters.get_mut(&char_id)
+    }
+
+    /// Get character by ID
+    pub fn get_character(&self, char_id: &Uuid) -> Option<&Character> {
+        self.characters.get(char_id)
+    }
+
+    /// Get mutable character by ID
+    pub fn get_character_mut(&mut self, char_id: &Uuid) -> Option<&mut Character> {
+        self.characters.get_mut(char_id)
+    }
+
+    /// The character, if it can be taken off the table right now
+    ///
+    /// Characters stay put during combat, where the tracker and spotlight
+    /// may still point at them.
+    pub fn removable_character(&self, char_id: &Uuid) -> Result<&Character, String> {
+        let character = self
+            .characters
+            .get(char_id)
+            .ok_or_else(|| format!("Character not found: {}", char_id))?;
+        if self.combat_encounter.is_some() {
+            return Err(format!("End combat before removing {}", character.name));
+        }
+        Ok(character)
+    }
+
+    /// Take a character off the table for good (the GM's delete or retire)
+    ///
+    /// Whoever controlled it goes back to picking a character, and its auras
+    /// and modifiers go with it.
+    pub fn delete_character(&mut self, char_id: &Uuid, retired: bool) -> Result<Character, String> {
+        self.removable_character(char_id)?;
+        let character = self
+            .characters
+            .remove(char_id)
+            .expect("character checked above");
+        self.release_character(char_id);
+        self.ghosted_characters.remove(char_id);
+        let owner_id = char_id.to_string();
+        self.auras.retain(|_, aura| aura.owner_id != owner_id);
+        self.modifiers.retain(|_, m| m.target_id != owner_id);
+        for quest in &mut self.quests {
+            quest.character_ids.retain(|id| *id != owner_id);
+        }
+
+        let message = if retired {
+            format!("{} retired to the vault", character.name)
+        } else {
+            format!("{} was deleted", character.name)
+        };
+        self.add_event(
+            GameEventType::SystemMessage,
+            message,
+            Some(character.name.clone()),
+            None,
+        );
+        Ok(character)
+    }
+
+    /// A random spot in the scene, snapped to the grid
+    fn random_position(&self) -> Position {
+        let layout = &self.layout;
+        let position = layout.snap(Position::random(layout.width, layout.height));
+        if layout.contains(&position) {
+            position
+        } else {
+            // A partial cell at the edge; its centre is off the scene
+            Position::random(layout.width, layout.height)
+        }
+    }
+
+    /// Update character position
+    ///
+    /// The position snaps to the scene's grid, if it has one, and must fall
+    /// inside the scene. Returns where the character ended up.
+    pub fn update_character_position(
+        &mut self,
+        char_id: &Uuid,
+        position: Position,
+    ) -> Result<Position, String> {
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| format!("Character not found: {}", char_id))?;
+        let position = self.layout.place_token(position, character.token.size)?;
+        character.position = position;
+        character.sync_resources(); // Sync resources whenever we modify character
+        Ok(position)
+    }
+
+    /// Walk a character along waypoints to a destination
+    ///
+    /// Every point must be in the scene, and the destination snaps to the
+    /// grid. The token ends up facing `facing` degrees, or the way it last
+    /// moved without one. Teleports skip this and use
+    /// `update_character_position`.
+    pub fn move_character(
+        &mut self,
+        char_id: &Uuid,
+        waypoints: &[Position],
+        destination: Position,
+        facing: Option<f32>,
+    ) -> Result<Movement, String> {
+        if facing.is_some_and(|degrees| !degrees.is_finite()) {
+            return Err("Facing must be a number".to_string());
+        }
+        if waypoints.len() > MAX_PATH_WAYPOINTS {
+            return Err(format!(
+                "Path has too many waypoints (max {})",
+                MAX_PATH_WAYPOINTS
+            ));
+        }
+        if !waypoints.iter().all(|p| self.layout.contains(p)) {
+            return Err("Path leaves the map".to_string());
+        }
+        let (start, size, rotation) = self
+            .characters
+            .get(char_id)
+            .map(|c| (c.position, c.token.size, c.token.rotation))
+            .ok_or_else(|| format!("Character not found: {}", char_id))?;
+        let destination = self
+            .layout
+            .place_token(destination, size)
+            .map_err(|_| "Path leaves the map".to_string())?;
+        let remaining = self.remaining_movement(char_id);
+
+        let mut path = Vec::with_capacity(waypoints.len() + 2);
+        path.push(start);
+        path.extend_from_slice(waypoints);
+        path.push(destination);
+
+        let mut crossed_hazards: Vec<(String, String)> = self
+            .hazards
+            .values()
+            .filter(|h| h.active)
+            .filter(|h| {
+                path.windows(2)
+                    .any(|leg| h.hazard.area.crosses(&leg[0], &leg[1]))
+            })
+            .map(|h| (h.id.clone(), h.hazard.name.clone()))
+            .collect();
+        crossed_hazards.sort();
+
+        let feet: f32 = path
+            .windows(2)
+            .map(|leg| self.layout.feet_between(&leg[0], &leg[1]))
+            .sum();
+        if let Some(remaining) = remaining {
+            // A little slack for grid snapping and rounding
+            if feet > remaining + 0.5 {
+                return Err(format!(
+                    "That's {:.0} ft; {} can only move {:.0} ft more this turn",
+                    feet,
+                    self.characters[char_id].name,
+                    remaining.max(0.0)
+                ));
+            }
+        }
+
+        self.update_character_position(char_id, destination)?;
+        if remaining.is_some() {
+            if let Some(encounter) = self.combat_encounter.as_mut() {
+                *encounter.moved_feet.entry(*char_id).or_default() += feet;
+            }
+        }
+
+        // Face the way the last leg went (a move in place keeps the facing)
+        let rotation = match facing {
+            Some(degrees) => tokens::normalize_rotation(degrees),
+            None => path
+                .windows(2)
+                .rev()
+                .find(|leg| leg[0] != leg[1])
+                .map(|leg| {
+                    let (dx, dy) = (leg[1].x - leg[0].x, leg[1].y - leg[0].y);
+                    tokens::normalize_rotation(dx.atan2(-dy).to_degrees())
+                })
+                .unwrap_or(rotation),
+        };
+        if let Some(character) = self.characters.get_mut(char_id) {
+            character.token.rotation = rotation;
+        }
+
+        if !crossed_hazards.is_empty() {
+            let name = self.characters[char_id].name.clone();
+            let hazards: Vec<&str> = crossed_hazards.iter().map(|(_, n)| n.as_str()).collect();
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{} moved through {}", name, hazards.join(", ")),
+                Some(name),
+                None,
+            );
+        }
+
+        Ok(Movement {
+            path,
+            crossed_hazards,
+            rotation,
+        })
+    }
+
+    /// Feet a character may still move this turn, `None` without a limit
+    ///
+    /// The limit is the `combat_move_feet` table setting and only applies
+    /// during combat. It resets whenever the turn moves on: a tracker token
+    /// is spent, the spotlight passes or a new round starts.
+    pub fn remaining_movement(&self, char_id: &Uuid) -> Option<f32> {
+        let budget = self.settings.combat_move_feet;
+        let encounter = self.combat_encounter.as_ref().filter(|e| e.is_active)?;
+        if budget == 0 {
+            return None;
+        }
+        let moved = encounter.moved_feet.get(char_id).copied().unwrap_or(0.0);
+        Some(budget as f32 - moved)
+    }
+
+    /// Check a connection may move a token, returning the character it moves
+    ///
+    /// Players need the map unlocked, and may only move characters they
+    /// control (`character_id` names one; without it, the one selected last).
+    /// Teleporting is off for players while combat movement is limited.
+    pub fn check_can_move(
+        &self,
+        conn_id: &Uuid,
+        character_id: Option<&str>,
+        teleport: bool,
+    ) -> Result<Uuid, String> {
+        let connection = self
+            .connections
+            .get(conn_id)
+            .ok_or_else(|| format!("Connection not found: {}", conn_id))?;
+        let is_gm = connection.role == ConnectionRole::Gm;
+        if self.map_locked && !is_gm {
+            return Err("The GM has locked the map".to_string());
+        }
+        let char_id = match self.acting_character(conn_id, character_id) {
+            Err(_) if character_id.is_some() => {
+                return Err("You can only move characters you control".to_string());
+            }
+            result => result?,
+        };
+        if !self.characters.contains_key(&char_id) {
+            return Err(format!("Character not found: {}", char_id));
+        }
+        if teleport && !is_gm && self.remaining_movement(&char_id).is_some() {
+            return Err("Teleporting is off while combat movement is limited".to_string());
+        }
+        Ok(char_id)
+    }
+
+    /// Lock or unlock token movement for players
+    pub fn set_map_locked(&mut self, locked: bool) {
+        self.map_locked = locked;
+        self.add_event(
+            GameEventType::SystemMessage,
+            if locked {
+                "🔒 The GM locked the map".to_string()
+            } else {
+                "🔓 The GM unlocked the map".to_string()
+            },
+            None,
+            None,
+        );
+    }
+
+    /// Validate and rate-limit a speech bubble from a character
+    ///
+    /// Returns the trimmed text. With `log` set it also goes in the event feed.
+    pub fn emote(&mut self, char_id: &Uuid, text: &str, log: bool) -> Result<String, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Emote cannot be empty".to_string());
+        }
+        if text.chars().count() > MAX_EMOTE_LENGTH {
+            return Err(format!(
+                "Emote is too long (max {} characters)",
+                MAX_EMOTE_LENGTH
+            ));
+        }
+
+        let name = self
+            .characters
+            .get(char_id)
+            .map(|c| c.name.clone())
+            .ok_or_else(|| format!("Character not found: {}", char_id))?;
+
+        let now = std::time::Instant::now();
+        if let Some(last) = self.last_emotes.get(char_id) {
+            if now.duration_since(*last) < EMOTE_COOLDOWN {
+                return Err("Slow down! Wait a moment before emoting again".to_string());
+            }
+        }
+        self.last_emotes.insert(*char_id, now);
+
+        if log {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{}: \"{}\"", name, text),
+                Some(name),
+                None,
+            );
+        }
+
+        Ok(text.to_string())
+    }
+
+    /// Post a chat message from a connection
+    ///
+    /// Speaking in character needs a character (the GM speaks as "GM").
+    /// Table talk and whispers go under the player's display name.
+    pub fn chat(
+        &mut self,
+        conn_id: &Uuid,
+        channel: ChatChannel,
+        text: &str,
+    ) -> Result<ChatEntry, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Message cannot be empty".to_string());
+        }
+        if text.chars().count() > MAX_CHAT_LENGTH {
+            return Err(format!(
+                "Message is too long (max {} characters)",
+                MAX_CHAT_LENGTH
+            ));
+        }
+
+        let connection = self
+            .connections
+            .get(conn_id)
+            .ok_or_else(|| format!("Connection not found: {}", conn_id))?;
+        let is_gm = connection.role == ConnectionRole::Gm;
+        let character = self.get_controlled_character(conn_id);
+
+        // The GM speaking in character during a cutaway is narrating it
+        let cutaway = channel == ChatChannel::InCharacter
+            && self
+                .cutaway
+                .as_ref()
+                .is_some_and(|c| is_gm || character.is_some_and(|ch| c.includes(&ch.id)));
+
+        let sender_name = match (&channel, character) {
+            (ChatChannel::InCharacter, Some(c)) => c.name.clone(),
+            (ChatChannel::InCharacter, None) if is_gm => "GM".to_string(),
+            (ChatChannel::InCharacter, None) => {
+                return Err("Select a character to speak in character".to_string())
+            }
+            _ if is_gm => "GM".to_string(),
+            _ => connection
+                .display_name
+                .clone()
+                .or_else(|| character.map(|c| c.name.clone()))
+                .unwrap_or_else(|| "Player".to_string()),
+        };
+
+        if !is_gm
+            && !self.settings.player_whispers
+            && matches!(
+                channel,
+                ChatChannel::Whisper {
+                    to_character_id: Some(_)
+                }
+            )
+        {
+            return Err("The GM has turned off whispers between players".to_string());
+        }
+
+        let to_name = match &channel {
+            ChatChannel::Whisper {
+                to_character_id: Some(id),
+            } => Some(
+                Uuid::parse_str(id)
+                    .ok()
+                    .and_then(|id| self.characters.get(&id))
+                    .map(|c| c.name.clone())
+                    .ok_or_else(|| format!("Character not found: {}", id))?,
+            ),
+            ChatChannel::Whisper {
+                to_character_id: None,
+            } => Some("GM".to_string()),
+            _ => None,
+        };
+
+        let entry = ChatEntry {
+            id: Uuid::new_v4().to_string(),
+            channel,
+            sender_connection_id: conn_id.to_string(),
+            character_id: character.map(|c| c.id.to_string()),
+            sender_name,
+            to_name,
+            text: text.to_string(),
+            timestamp: chrono::Utc::now().format("%H:%M:%S").to_string(),
+            cutaway,
+        };
+
+        // Whispers stay out of the shared log
+        match entry.channel {
+            ChatChannel::InCharacter => self.add_event(
+                GameEventType::Chat,
+                format!("{}: \"{}\"", entry.sender_name, entry.text),
+                Some(entry.sender_name.clone()),
+                None,
+            ),
+            ChatChannel::OutOfCharacter => self.add_event(
+                GameEventType::Chat,
+                format!("(OOC) {}: {}", entry.sender_name, entry.text),
+                None,
+                None,
+            ),
+            ChatChannel::Whisper { .. } => {}
+        }
+        if cutaway {
+            if let Some(event) = self.event_log.last_mut() {
+                event.cutaway = true;
+            }
+        }
+
+        self.chat_log.push(entry.clone());
+        if self.chat_log.len() > MAX_CHAT_HISTORY {
+            let excess = self.chat_log.len() - MAX_CHAT_HISTORY;
+            self.chat_log.drain(..excess);
+        }
+
+        Ok(entry)
+    }
+
+    /// Roll a free-form dice expression for a connection
+    ///
+    /// Players roll as their character (`character_id` picks one of several,
+    /// otherwise the one selected last); the GM may roll without one, and
+    /// may roll in secret.
+    pub fn roll_dice(
+        &mut self,
+        conn_id: &Uuid,
+        character_id: Option<&str>,
+        expression: &str,
+        label: Option<&str>,
+        hidden: bool,
+    ) -> Result<FreeRoll, String> {
+        let label = label.map(str::trim).filter(|l| !l.is_empty());
+        if label.is_some_and(|l| l.chars().count() > MAX_ROLL_LABEL_LENGTH) {
+            return Err(format!(
+                "Roll label is too long (max {} characters)",
+                MAX_ROLL_LABEL_LENGTH
+            ));
+        }
+
+        let is_gm = self
+            .connections
+            .get(conn_id)
+            .is_some_and(|c| c.role == ConnectionRole::Gm);
+        if hidden && !is_gm {
+            return Err("Only the GM can roll in secret".to_string());
+        }
+
+        let char_id = match character_id {
+            Some(_) => Some(self.acting_character(conn_id, character_id)?),
+            None => self.controlled_characters(conn_id).last().copied(),
+        };
+        let character = char_id.and_then(|id| self.characters.get(&id));
+        let (character_id, name) = match character {
+            Some(c) => (Some(c.id), c.name.clone()),
+            None if is_gm => (None, "GM".to_string()),
+            None => return Err("No character selected".to_string()),
+        };
+
+        let roll = self.dice.with(|rng| dice::roll(expression, rng))?;
+
+        if hidden {
+            // The table only learns that a secret roll happened
+            self.add_event(
+                GameEventType::RollExecuted,
+                format!("{} rolled in secret", name),
+                None,
+                None,
+            );
+        } else {
+            self.add_event(
+                GameEventType::RollExecuted,
+                format!(
+                    "{} rolled {}{}: {}",
+                    name,
+                    roll.expression,
+                    label.map(|l| format!(" ({})", l)).unwrap_or_default(),
+                    roll.total
+                ),
+                character_id.map(|_| name.clone()),
+                Some(
+                    roll.dice
+                        .iter()
+                        .map(|d| {
+                            let value = if d.negative {
+                                format!("-{}", d.value)
+                            } else {
+                                d.value.to_string()
+                            };
+                            if d.kept {
+                                value
+                            } else {
+                                format!("({})", value)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            );
+        }
+
+        Ok(FreeRoll {
+            character_id,
+            roller_name: name,
+            label: label.map(str::to_string),
+            roll,
+            hidden,
+        })
+    }
+
+    /// Roll duality dice for a character
+    pub fn roll_duality(
+        &self,
+        modifier: i32,
+        with_advantage: bool,
+        with_disadvantage: bool,
+    ) -> RollResult {
+        let (hope, fear) = self.dice.duality();
+        let (advantage_die, disadvantage_die) =
+            roll_advantage_dice(&self.dice, with_advantage, with_disadvantage);
+        let total = (hope as i32 + fear as i32 + modifier + advantage_die.unwrap_or(0) as i32
+            - disadvantage_die.unwrap_or(0) as i32)
+            .max(0);
+        let is_critical = hope == fear;
+
+        // Standard difficulty is 12 in Daggerheart
+        const STANDARD_DIFFICULTY: i32 = 12;
+
+        RollResult {
+            hope: hope as i32,
+            fear: fear as i32,
+            modifier,
+            advantage_die,
+            disadvantage_die,
+            total,
+            controlling_die: duality_controlling_die(hope, fear),
+            is_critical,
+            is_success: is_critical || total >= STANDARD_DIFFICULTY,
+        }
+    }
+
+    /// Get all characters
+    pub fn get_characters(&self) -> Vec<&Character> {
+        self.characters.values().collect()
+    }
+
+    /// Get all player characters (non-NPCs)
+    pub fn get_player_characters(&self) -> Vec<&Character> {
+        self.characters.values().filter(|c| !c.is_npc).collect()
+    }
+
+    /// Get all NPCs
+    pub fn get_npcs(&self) -> Vec<&Character> {
+        self.characters.values().filter(|c| c.is_npc).collect()
+    }
+
+    /// Get connection count
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Get the number of read-only connections (TV views, streams)
+    pub fn spectator_count(&self) -> usize {
+        self.connections
+            .values()
+            .filter(|c| c.role == ConnectionRole::Spectator)
+            .count()
+    }
+
+    /// Get character count
+    pub fn character_count(&self) -> usize {
+        self.characters.len()
+    }
+
+    /// Display name of the player controlling the named character
+    fn player_name_for(&self, character_name: &str) -> Option<String> {
+        let character = self
+            .characters
+            .values()
+            .find(|c| c.name == character_name)?;
+        self.display_name_of_controller(&character.id)
+    }
+
+    /// Display name of the connection controlling a character
+    pub fn display_name_of_controller(&self, char_id: &Uuid) -> Option<String> {
+        self.controller_of(char_id)
+            .and_then(|conn_id| self.connections.get(&conn_id))
+            .and_then(|conn| conn.display_name.clone())
+    }
+
+    /// Set the display name of a connection
+    ///
+    /// Names are unique across connected players, ignoring case.
+    pub fn set_display_name(&mut self, conn_id: &Uuid, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Display name cannot be empty".to_string());
+        }
+        if name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(format!(
+                "Display name is too long (max {} characters)",
+                MAX_DISPLAY_NAME_LENGTH
+            ));
+        }
+        if name.chars().any(char::is_control) {
+            return Err("Display name contains invalid characters".to_string());
+        }
+        let taken = self.connections.values().any(|c| {
+            c.id != *conn_id
+                && c.display_name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        });
+        if taken {
+            return Err(format!("{} is already taken", name));
+        }
+
+        let conn = self
+            .connections
+            .get_mut(conn_id)
+            .ok_or("Connection not found")?;
+        let previous = conn.display_name.replace(name.to_string());
+
+        let message = match previous {
+            Some(previous) if previous != name => format!("{} is now {}", previous, name),
+            Some(_) => return Ok(name.to_string()),
+            None => format!("{} joined", name),
+        };
+        self.add_event(GameEventType::SystemMessage, message, None, None);
+        Ok(name.to_string())
+    }
+
+    /// Change a character's token color
+    ///
+    /// The color must be `#rrggbb`, readable on the map, and distinct from
+    /// every other character's color. Returns the normalized color.
+    pub fn set_character_color(&mut self, char_id: &Uuid, color: &str) -> Result<String, String> {
+        let color = self.check_character_color(char_id, color)?;
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or("Character not found")?;
+        character.color = color.clone();
+        let name = character.name.clone();
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Changed color to {}", color),
+            Some(name),
+            None,
+        );
+        Ok(color)
+    }
+
+    /// Check a color a character could take, returning it normalized
+    fn check_character_color(&self, char_id: &Uuid, color: &str) -> Result<String, String> {
+        let color = color.trim().to_lowercase();
+        let rgb = parse_hex_color(&color).ok_or_else(|| format!("Invalid color: {}", color))?;
+        if contrast_ratio(rgb, MAP_BACKGROUND) < MIN_COLOR_CONTRAST {
+            return Err(format!("{} is too dark to see on the map", color));
+        }
+        if let Some(other) = self.characters.values().find(|c| {
+            c.id != *char_id
+                && parse_hex_color(&c.color)
+                    .is_some_and(|other| color_distance(rgb, other) < MIN_COLOR_DISTANCE)
+        }) {
+            return Err(format!("{} is too close to {}'s color", color, other.name));
+        }
+        Ok(color)
+    }
+
+    /// Fix a character's name, attributes, level, Evasion or color
+    ///
+    /// Everything is checked before anything changes. Returns what changed
+    /// (e.g. `["name", "level"]`), logging it when anything did.
+    pub fn update_character(
+        &mut self,
+        char_id: &Uuid,
+        edit: CharacterEdit,
+    ) -> Result<Vec<&'static str>, String> {
+        if !self.characters.contains_key(char_id) {
+            return Err("Character not found".to_string());
+        }
+        let name = edit.name.as_deref().map(check_name).transpose()?;
+        let attributes = edit
+            .attributes
+            .map(|values| {
+                Attributes::from_array(values)
+                    .map(|attributes| (values, attributes))
+                    .map_err(|e| format!("Invalid attributes: {}", e))
+            })
+            .transpose()?;
+        if edit
+            .level
+            .is_some_and(|level| !(1..=leveling::MAX_LEVEL).contains(&level))
+        {
+            return Err(format!("Level must be 1 to {}", leveling::MAX_LEVEL));
+        }
+        if edit
+            .evasion
+            .is_some_and(|evasion| !(0..=MAX_EVASION).contains(&evasion))
+        {
+            return Err(format!("Evasion must be 0 to {}", MAX_EVASION));
+        }
+        let color = edit
+            .color
+            .as_deref()
+            .map(|color| self.check_character_color(char_id, color))
+            .transpose()?;
+
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .expect("character checked above");
+        let mut changed = Vec::new();
+        if let Some(name) = name.filter(|name| *name != character.name) {
+            character.name = name.to_string();
+            changed.push("name");
+        }
+        if let Some((values, attributes)) = attributes {
+            let current = &character.attributes;
+            let current = [
+                current.agility,
+                current.strength,
+                current.finesse,
+                current.instinct,
+                current.presence,
+                current.knowledge,
+            ];
+            if values != current {
+                character.attributes = attributes;
+                changed.push("attributes");
+            }
+        }
+        if let Some(level) = edit.level.filter(|level| *level != character.level) {
+            character.level = level;
+            changed.push("level");
+        }
+        if let Some(evasion) = edit.evasion.filter(|evasion| *evasion != character.evasion) {
+            character.evasion = evasion;
+            changed.push("Evasion");
+        }
+        if let Some(color) = color.filter(|color| *color != character.color) {
+            character.color = color;
+            changed.push("color");
+        }
+
+        if !changed.is_empty() {
+            let name = character.name.clone();
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("Sheet corrected: {}", changed.join(", ")),
+                Some(name),
+                None,
+            );
+        }
+        Ok(changed)
+    }
+
+    /// Assign a color from the palette (cycles through)
+    fn assign_color(&mut self) -> String {
+        let color = CHARACTER_COLORS[self.color_index % CHARACTER_COLORS.len()].to_string();
+        self.color_index += 1;
+        color
+    }
+
+    /// Sync all character resources (call before saving)
+    pub fn sync_all_resources(&mut self) {
+        for character in self.characters.values_mut() {
+            character.sync_resources();
+        }
+    }
+
+    /// Restore all character resources (call after loading)
+    pub fn restore_all_resources(&mut self) {
+        for character in self.characters.values_mut() {
+            character.restore_resources();
+        }
+    }
+
+    // ===== Narrative =====
+
+    /// Name of a character or adversary by ID
+    pub fn combatant_name(&self, id: &str) -> Option<String> {
+        self.characters
+            .values()
+            .find(|c| c.id.to_string() == id)
+            .map(|c| c.name.clone())
+            .or_else(|| self.adversaries.get(id).map(|a| a.name.clone()))
+    }
+
+    /// Replace the table settings, bringing Fear under a lowered cap
+    pub fn set_settings(&mut self, settings: TableSettings) -> Result<(), String> {
+        settings.validate()?;
+        self.fear_pool = self.fear_pool.min(settings.max_fear);
+        self.settings = settings;
+        Ok(())
+    }
+
+    /// Change some of the table settings (GM)
+    pub fn update_settings(&mut self, patch: &TableSettingsPatch) -> Result<TableSettings, String> {
+        let settings = self.settings.patched(patch)?;
+        self.set_settings(settings.clone())?;
+        self.add_event(
+            GameEventType::SystemMessage,
+            "⚙️ GM updated the table settings".to_string(),
+            None,
+            None,
+        );
+        Ok(settings)
+    }
+
+    /// Add Fear up to the table's cap, returning how much was added
+    fn gain_fear(&mut self, amount: u8) -> u8 {
+        let before = self.fear_pool;
+        self.fear_pool = self
+            .fear_pool
+            .saturating_add(amount)
+            .min(self.settings.max_fear);
+        self.fear_pool - before
+    }
+
+    /// Replace the combat phrasings
+    pub fn set_narrative_templates(&mut self, templates: NarrativeTemplates) -> Result<(), String> {
+        templates.validate()?;
+        self.narrative_templates = templates;
+        Ok(())
+    }
+
+    /// Flavor text for a combat beat, or `None` in plain mode
+    pub fn narrate(
+        &self,
+        beat: CombatBeat,
+        attacker: &str,
+        target: &str,
+        damage: Option<u16>,
+    ) -> Option<String> {
+        if self.settings.narrative_mode == NarrativeMode::Plain {
+            return None;
+        }
+        self.dice.with(|rng| {
+            self.narrative_templates
+                .render(beat, attacker, target, damage, rng)
+        })
+    }
+
+    // ===== Event Log System =====
+    
+    /// Add an event to the game log
+    pub fn add_event(
+        &mut self,
+        event_type: GameEventType,
+        message: String,
+        character_name: Option<String>,
+        details: Option<String>,
+    ) {
+        self.push_event(event_type, message, character_name, details, None);
+    }
+
+    /// Log the start of a scene, or the start or end of a fight
+    pub fn add_marker_event(
+        &mut self,
+        marker: LogMarker,
+        message: String,
+        details: Option<String>,
+    ) {
+        self.push_event(
+            GameEventType::SystemMessage,
+            message,
+            None,
+            details,
+            Some(marker),
+        );
+    }
+
+    /// Log a scene starting, titled "Scene: {title}"
+    fn add_scene_event(&mut self, title: String, details: Option<String>) {
+        let message = format!("{}{}", SCENE_PREFIX, title);
+        self.add_marker_event(LogMarker::Scene { title }, message, details);
+    }
+
+    fn push_event(
+        &mut self,
+        event_type: GameEventType,
+        message: String,
+        character_name: Option<String>,
+        details: Option<String>,
+        marker: Option<LogMarker>,
+    ) {
+        let player_name = character_name
+            .as_deref()
+            .and_then(|name| self.player_name_for(name));
+        let cutaway = self.cutaway.as_ref().is_some_and(|c| {
+            character_name
+                .as_ref()
+                .is_some_and(|name| c.character_names.contains(name))
+        });
+        let event = GameEvent {
+            timestamp: std::time::SystemTime::now(),
+            event_type,
+            message,
+            character_name,
+            details,
+            player_name,
+            cutaway,
+            marker,
+        };
+
+        // Rolls and blows in a fight go in its round history; cutaway
+        // scenes stay out of it
+        let is_action = matches!(
+            event.event_type,
+            GameEventType::RollExecuted | GameEventType::CombatAction
+        );
+        if let Some(encounter) = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active && is_action && !event.cutaway)
+        {
+            encounter.record_action(&event);
+        }
+
+        self.event_log.push(event);
+        self.events_logged += 1;
+
+        // Keep log size within the configured limit (trim the oldest fifth)
+        let max = self.limits.max_event_log;
+        if self.event_log.len() > max {
+            let excess = (self.event_log.len() - max).max(max / 5);
+            self.event_log.drain(0..excess);
+        }
+    }
+    
+    /// Get recent events (last N)
+    pub fn get_recent_events(&self, count: usize) -> Vec<GameEvent> {
+        let total = self.event_log.len();
+        if total <= count {
+            self.event_log.clone()
+        } else {
+            self.event_log[total - count..].to_vec()
+        }
+    }
+    
+    /// Get all events
+    pub fn get_all_events(&self) -> &[GameEvent] {
+        &self.event_log
+    }
+
+    /// Tally a character's duality roll
+    pub fn record_duality(&mut self, character_id: &Uuid, hope: u8, fear: u8) {
+        let Some(character) = self.characters.get(character_id) else {
+            return;
+        };
+        self.roll_stats
+            .entry(*character_id)
+            .or_insert_with(|| RollStats::new(character_id.to_string(), &character.name))
+            .record(hope, fear);
+    }
+
+    /// Every character's duality rolls so far, and the table's
+    pub fn roll_stats_report(&self) -> RollStatsReport {
+        RollStatsReport::new(self.roll_stats.values())
+    }
+
+    /// A page of the event log and how many events matched in all; a
+    /// running cutaway's events are left out unless `include_cutaway`
+    pub fn event_page(
+        &self,
+        query: &EventLogQuery,
+        include_cutaway: bool,
+    ) -> (Vec<GameEventData>, usize) {
+        let events: Vec<_> = self
+            .event_log
+            .iter()
+            .filter(|event| include_cutaway || !event.cutaway)
+            .map(GameEvent::to_data)
+            .collect();
+        query.page(&events)
+    }
+
+    /// The event log split into scenes and fights, filtered by `query`'s
+    /// kind and character; a running cutaway's events are left out unless
+    /// `include_cutaway`
+    pub fn scene_log(&self, query: &EventLogQuery, include_cutaway: bool) -> Vec<SceneLog> {
+        let log: Vec<_> = self
+            .event_log
+            .iter()
+            .filter(|event| include_cutaway || !event.cutaway)
+            .map(GameEvent::to_data)
+            .collect();
+        events::group_by_scene(&log, query)
+    }
+
+    /// Start a new scene in the log; returns its trimmed title
+    pub fn mark_scene(&mut self, title: &str) -> Result<String, String> {
+        let title = check_name(title)?.to_string();
+        self.add_scene_event(title.clone(), Some("Marked by the GM".to_string()));
+        Ok(title)
+    }
+
+    /// Clear event log
+    pub fn clear_events(&mut self) {
+        self.event_log.clear();
+    }
+
+    // ===== Phase 1: GM-Initiated Dice Rolls =====
+
+    /// Execute a dice roll for a character
+    pub fn execute_roll(
+        &mut self,
+        character_id: &Uuid,
+        request_id: &str,
+        spend_hope: bool,
+        chosen_experience: Option<&str>,
+    ) -> Result<crate::protocol::DetailedRollResult, String> {
+        // Get the request
+        let request = self
+            .pending_roll_requests
+            .get(request_id)
+            .ok_or_else(|| "Roll request not found".to_string())?
+            .clone();
+
+        // Get the character (immutable first to calculate modifiers)
+        let character = self
+            .characters
+            .get(character_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        // Check if already rolled
+        if request.completed_by.contains(character_id) {
+            return Err("Character has already rolled for this request".to_string());
+        }
+        let roll_type = request.roll_type_for(character_id);
+
+        // The leader of a group roll goes last, with the helpers' modifier
+        let group_modifier = match &request.group {
+            Some(group) if group.leader_id == *character_id => {
+                let helpers = request.target_character_ids.len() - 1;
+                if group.helper_results.len() < helpers {
+                    return Err("Waiting for helpers to roll".to_string());
+                }
+                group.modifier()
+            }
+            _ => 0,
+        };
+
+        // Tag team rolls wait for the partner, and defer Hope and Fear to the pick
+        let in_tag_team = match request
+            .tag_team
+            .as_ref()
+            .filter(|t| t.includes(character_id))
+        {
+            Some(tag_team) if !tag_team.accepted => {
+                return Err("Waiting for the tag team partner to accept".to_string());
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        // Calculate modifiers (while character is borrowed immutably)
+        let (attr_mod, prof_mod) =
+            character.roll_modifiers(&roll_type, request.attribute.as_deref());
+        let timed_modifier = self.modifier_total(&character_id.to_string(), ModifierStat::Roll);
+        let mut total_mod =
+            attr_mod + prof_mod + request.situational_modifier + group_modifier + timed_modifier;
+
+        // Roll the help dice; only the highest counts
+        let help_dice: Vec<crate::protocol::HelpDieRoll> = request
+            .help
+            .iter()
+            .filter(|h| h.target_id == *character_id)
+            .map(|h| crate::protocol::HelpDieRoll {
+                helper_id: h.helper_id.to_string(),
+                helper_name: self
+                    .characters
+                    .get(&h.helper_id)
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default(),
+                value: self.dice.die(6),
+            })
+            .collect();
+        let help_bonus = help_dice.iter().map(|d| d.value).max().unwrap_or(0);
+
+        // Now get mutable reference to handle Hope spending
+        let character = self
+            .characters
+            .get_mut(character_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        // Handle Hope spending (the bonus comes from the chosen Experience)
+        let hope_bonus = if spend_hope {
+            if character.hope.current < 1 {
+                return Err("Not enough Hope to spend".to_string());
+            }
+            let experience_name = chosen_experience
+                .ok_or_else(|| "Choose an Experience to spend Hope on".to_string())?;
+            let modifier = character
+                .get_experience(experience_name)
+                .map(|e| e.modifier)
+                .ok_or_else(|| format!("Unknown Experience: {}", experience_name))?;
+
+            let _ = character.hope.spend(1);
+            character.sync_resources();
+            modifier
+        } else {
+            0
+        };
+
+        total_mod += hope_bonus;
+
+        // Roll the dice
+        let (hope_die, fear_die) = self.dice.duality();
+        self.roll_stats
+            .entry(*character_id)
+            .or_insert_with(|| RollStats::new(character_id.to_string(), &character.name))
+            .record(hope_die, fear_die);
+
+        // Handle advantage and disadvantage
+        let (advantage_die, disadvantage_die) =
+            roll_advantage_dice(&self.dice, request.has_advantage, request.has_disadvantage);
+        // Negative modifiers can't take the total below zero
+        let total = (hope_die as i32 + fear_die as i32 + advantage_die.unwrap_or(0) as i32
+            - disadvantage_die.unwrap_or(0) as i32
+            + help_bonus as i32
+            + total_mod as i32)
+            .max(0) as u16;
+
+        // Determine outcome
+        let is_critical = hope_die == fear_die;
+        let controlling_die = duality_controlling_die(hope_die, fear_die);
+
+        let success_type = if is_critical {
+            crate::protocol::SuccessType::CriticalSuccess
+        } else if total < request.difficulty {
+            crate::protocol::SuccessType::Failure
+        } else if controlling_die == crate::protocol::ControllingDie::Hope {
+            crate::protocol::SuccessType::SuccessWithHope
+        } else {
+            crate::protocol::SuccessType::SuccessWithFear
+        };
+
+        // Update Hope/Fear (reaction rolls never change either)
+        let (hope_change, fear_change) = match success_type {
+            _ if roll_type.is_reaction() || in_tag_team => (0, 0),
+            crate::protocol::SuccessType::SuccessWithHope => {
+                character.hope.gain(1);
+                character.sync_resources();
+                (1, 0)
+            }
+            crate::protocol::SuccessType::SuccessWithFear => (0, self.gain_fear(1) as i8),
+            _ => (0, 0), // Critical or Failure = no resource change
+        };
+
+        // Subtract Hope bonus if it was spent
+        let final_hope_change = hope_change - (if spend_hope { 1 } else { 0 });
+
+        let result = crate::protocol::DetailedRollResult {
+            hope_die,
+            fear_die,
+            advantage_die,
+            disadvantage_die,
+            attribute_modifier: attr_mod,
+            proficiency_modifier: prof_mod,
+            situational_modifier: request.situational_modifier,
+            hope_bonus,
+            group_modifier,
+            total_modifier: total_mod,
+            help_dice,
+            help_bonus,
+            timed_modifier,
+            total,
+            difficulty: request.difficulty,
+            success_type,
+            controlling_die,
+            is_critical,
+            hope_change: final_hope_change,
+            fear_change,
+        };
+
+        // Mark as completed, recording helper results for the leader and
+        // tag team results for the pick
+        if let Some(req) = self.pending_roll_requests.get_mut(request_id) {
+            req.completed_by.push(*character_id);
+            if let Some(group) = req.group.as_mut().filter(|g| g.leader_id != *character_id) {
+                let success = success_type != crate::protocol::SuccessType::Failure;
+                group.helper_results.push((*character_id, success));
+            }
+            if let Some(tag_team) = req.tag_team.as_mut().filter(|_| in_tag_team) {
+                tag_team.results.push((*character_id, result.clone()));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// What a prospective roll request would look like for each target
+    ///
+    /// Lets the GM check modifiers, Hope, conditions and odds before
+    /// committing the request.
+    pub fn preview_roll(
+        &self,
+        target_ids: &[Uuid],
+        spec: &RollSpec,
+    ) -> Result<Vec<RollPreview>, String> {
+        if target_ids.is_empty() {
+            return Err("No valid characters targeted".to_string());
+        }
+        let roll_type = &spec.roll_type;
+        let situational_modifier = spec.situational_modifier;
+
+        target_ids
+            .iter()
+            .map(|id| {
+                let character = self.characters.get(id).ok_or("Character not found")?;
+                let (attribute_modifier, proficiency_modifier) =
+                    character.roll_modifiers(roll_type, spec.attribute.as_deref());
+                let total_modifier = attribute_modifier
+                    .saturating_add(proficiency_modifier)
+                    .saturating_add(situational_modifier)
+                    .saturating_add(self.modifier_total(&id.to_string(), ModifierStat::Roll));
+
+                Ok(RollPreview {
+                    character_id: id.to_string(),
+                    character_name: character.name.clone(),
+                    attribute_modifier,
+                    proficiency_modifier,
+                    situational_modifier,
+                    total_modifier,
+                    hope: character.hope.current,
+                    can_spend_hope: character.hope.current >= 1
+                        && !character.experiences.is_empty(),
+                    conditions: self.roll_conditions(character, roll_type),
+                    success_chance: success_probability(
+                        total_modifier,
+                        spec.difficulty,
+                        spec.has_advantage,
+                        spec.has_disadvantage,
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Anything about a character's state the GM should know before a roll
+    fn roll_conditions(&self, character: &Character, roll_type: &RollType) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if character.hp.current == 0 {
+            conditions.push("Taken out (no HP left)".to_string());
+        }
+        if character.stress.current as u32 >= character.progression.stress_slots() as u32 {
+            conditions.push("Vulnerable (Stress full)".to_string());
+        }
+        if self.is_ghosted(&character.id) {
+            conditions.push("Player disconnected".to_string());
+        }
+        if roll_type.is_reaction() {
+            conditions.push("Reaction: no Hope or Fear".to_string());
+        }
+
+        let token_id = character.id.to_string();
+        for attached in self
+            .auras
+            .values()
+            .filter(|a| a.members.contains(&token_id))
+        {
+            conditions.push(format!(
+                "In {} ({})",
+                attached.aura.name,
+                attached.aura.effect.describe()
+            ));
+        }
+        for timed in self
+            .modifiers
+            .values()
+            .filter(|m| m.target_id == token_id && m.modifier.stat == ModifierStat::Roll)
+        {
+            conditions.push(format!(
+                "{} ({})",
+                timed.modifier.name,
+                timed.modifier.describe()
+            ));
+        }
+        conditions
+    }
+
+    /// Spend a Hope to help another character with their pending roll
+    ///
+    /// Each helper banks a d6 advantage die on the roll; when it is made,
+    /// the highest help die is added to the total.
+    pub fn help_ally(
+        &mut self,
+        request_id: &str,
+        helper_id: &Uuid,
+        target_id: &Uuid,
+    ) -> Result<(), String> {
+        let request = self
+            .pending_roll_requests
+            .get(request_id)
+            .ok_or("Roll request not found")?;
+        if !request.target_character_ids.contains(target_id)
+            || request.completed_by.contains(target_id)
+        {
+            return Err("That character has no roll to help with".to_string());
+        }
+        if helper_id == target_id {
+            return Err("You can't help your own roll".to_string());
+        }
+        let help = HelpDie {
+            helper_id: *helper_id,
+            target_id: *target_id,
+        };
+        if request.help.contains(&help) {
+            return Err("You are already helping with this roll".to_string());
+        }
+
+        let target_name = self
+            .characters
+            .get(target_id)
+            .map(|c| c.name.clone())
+            .ok_or("Character not found")?;
+        let helper = self
+            .characters
+            .get_mut(helper_id)
+            .ok_or("Character not found")?;
+        if helper.hope.current < 1 {
+            return Err("Not enough Hope to help".to_string());
+        }
+        let _ = helper.hope.spend(1);
+        helper.sync_resources();
+        let helper_name = helper.name.clone();
+
+        if let Some(request) = self.pending_roll_requests.get_mut(request_id) {
+            request.help.push(help);
+        }
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} helps {}", helper_name, target_name),
+            Some(helper_name),
+            Some("Spent 1 Hope for an advantage die".to_string()),
+        );
+        Ok(())
+    }
+
+    /// Check both halves of a tag team can pay for it
+    fn check_tag_team_hope(&self, ids: [&Uuid; 2]) -> Result<(), String> {
+        let cost = self.settings.tag_team_hope_cost;
+        for id in ids {
+            let character = self.characters.get(id).ok_or("Character not found")?;
+            if character.hope.current < cost {
+                return Err(format!(
+                    "{} needs {} Hope for a tag team",
+                    character.name, cost
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Propose a tag team on a roll request the initiator has yet to roll
+    pub fn propose_tag_team(
+        &mut self,
+        request_id: &str,
+        initiator_id: &Uuid,
+        partner_id: &Uuid,
+    ) -> Result<(), String> {
+        let request = self
+            .pending_roll_requests
+            .get(request_id)
+            .ok_or("Roll request not found")?;
+        if request.group.is_some() {
+            return Err("Group rolls can't be tag teamed".to_string());
+        }
+        if request.tag_team.is_some() {
+            return Err("This roll already has a tag team".to_string());
+        }
+        if !request.target_character_ids.contains(initiator_id)
+            || request.completed_by.contains(initiator_id)
+        {
+            return Err("You have no roll to make for this request".to_string());
+        }
+        if request.roll_type_for(initiator_id).is_reaction() {
+            return Err("Reaction rolls can't be tag teamed".to_string());
+        }
+        if initiator_id == partner_id {
+            return Err("Choose another character to tag team with".to_string());
+        }
+        if request.completed_by.contains(partner_id) {
+            return Err("Your partner has already rolled".to_string());
+        }
+        let partner = self.characters.get(partner_id).ok_or("Partner not found")?;
+        if partner.is_npc {
+            return Err("NPCs can't join a tag team".to_string());
+        }
+        self.check_tag_team_hope([initiator_id, partner_id])?;
+
+        let initiator_name = self.characters[initiator_id].name.clone();
+        let partner_name = partner.name.clone();
+        if let Some(request) = self.pending_roll_requests.get_mut(request_id) {
+            request.tag_team = Some(TagTeam {
+                initiator_id: *initiator_id,
+                partner_id: *partner_id,
+                accepted: false,
+                results: Vec::new(),
+                chosen: None,
+            });
+        }
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!(
+                "{} proposed a tag team with {}",
+                initiator_name, partner_name
+            ),
+            Some(initiator_name),
+            None,
+        );
+        Ok(())
+    }
+
+    /// The partner accepts a proposed tag team (both pay 3 Hope) or turns it down
+    pub fn respond_tag_team(
+        &mut self,
+        request_id: &str,
+        partner_id: &Uuid,
+        accept: bool,
+    ) -> Result<PendingRollRequest, String> {
+        let request = self
+            .pending_roll_requests
+            .get(request_id)
+            .ok_or("Roll request not found")?;
+        let tag_team = request
+            .tag_team
+            .clone()
+            .filter(|t| t.partner_id == *partner_id && !t.accepted)
+            .ok_or("No tag team waiting on you")?;
+        let partner_name = self
+            .characters
+            .get(partner_id)
+            .map(|c| c.name.clone())
+            .ok_or("Character not found")?;
+
+        if !accept {
+            if let Some(request) = self.pending_roll_requests.get_mut(request_id) {
+                request.tag_team = None;
+            }
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{} turned down the tag team", partner_name),
+                Some(partner_name),
+                None,
+            );
+            return Ok(self.pending_roll_requests[request_id].clone());
+        }
+
+        self.check_tag_team_hope([&tag_team.initiator_id, partner_id])?;
+        let cost = self.settings.tag_team_hope_cost;
+        for id in [&tag_team.initiator_id, partner_id] {
+            if let Some(character) = self.characters.get_mut(id) {
+                let _ = character.hope.spend(cost);
+                character.sync_resources();
+            }
+        }
+
+        let request = self
+            .pending_roll_requests
+            .get_mut(request_id)
+            .expect("request checked above");
+        if !request.target_character_ids.contains(partner_id) {
+            request.target_character_ids.push(*partner_id);
+        }
+        if let Some(tag_team) = request.tag_team.as_mut() {
+            tag_team.accepted = true;
+        }
+        let request = request.clone();
+
+        let initiator_name = self.characters[&tag_team.initiator_id].name.clone();
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} and {} team up", initiator_name, partner_name),
+            Some(partner_name),
+            Some(format!("Each spent {} Hope", cost)),
+        );
+        Ok(request)
+    }
+
+    /// Apply the tag team result the group picked
+    ///
+    /// With Hope, both characters gain a Hope; with Fear, the GM gains a Fear
+    /// for each of them.
+    pub fn choose_tag_team_result(
+        &mut self,
+        request_id: &str,
+        chooser_id: &Uuid,
+        chosen_id: &Uuid,
+    ) -> Result<TagTeamOutcome, String> {
+        let request = self
+            .pending_roll_requests
+            .get(request_id)
+            .ok_or("Roll request not found")?;
+        let tag_team = request
+            .tag_team
+            .as_ref()
+            .filter(|t| t.includes(chooser_id))
+            .ok_or("You are not part of a tag team on this roll")?;
+        if tag_team.chosen.is_some() {
+            return Err("The tag team result has already been picked".to_string());
+        }
+        if tag_team.results.len() < 2 {
+            return Err("Both characters must roll first".to_string());
+        }
+        let result = tag_team
+            .results
+            .iter()
+            .find(|(id, _)| id == chosen_id)
+            .map(|(_, result)| result.clone())
+            .ok_or("Pick one of the tag team's rolls")?;
+        let team = [tag_team.initiator_id, tag_team.partner_id];
+
+        let (hope_change, fear_change) = match result.success_type {
+            crate::protocol::SuccessType::SuccessWithHope => {
+                for id in &team {
+                    if let Some(character) = self.characters.get_mut(id) {
+                        character.hope.gain(1);
+                        character.sync_resources();
+                    }
+                }
+                (1, 0)
+            }
+            crate::protocol::SuccessType::SuccessWithFear => (0, self.gain_fear(team.len() as u8)),
+            _ => (0, 0),
+        };
+
+        if let Some(tag_team) = self
+            .pending_roll_requests
+            .get_mut(request_id)
+            .and_then(|r| r.tag_team.as_mut())
+        {
+            tag_team.chosen = Some(*chosen_id);
+        }
+
+        let chosen_name = self.characters[chosen_id].name.clone();
+        self.add_event(
+            GameEventType::RollExecuted,
+            format!("The tag team went with {}'s roll", chosen_name),
+            Some(chosen_name),
+            Some(format!(
+                "Total: {} vs {}, Hope {:+} each, Fear {:+}",
+                result.total, result.difficulty, hope_change, fear_change
+            )),
+        );
+
+        Ok(TagTeamOutcome {
+            chosen_id: *chosen_id,
+            team,
+            result,
+            hope_change,
+            fear_change,
+        })
+    }
+
+    /// Open a group action roll led by `leader_id`
+    ///
+    /// Every helper is sent a reaction roll; the leader's action roll waits
+    /// until they have all rolled.
+    pub fn request_group_roll(
+        &mut self,
+        request: GroupRollRequest,
+    ) -> Result<PendingRollRequest, String> {
+        let GroupRollRequest {
+            leader_id,
+            helper_ids,
+            attribute,
+            difficulty,
+            context,
+            has_advantage,
+            has_disadvantage,
+        } = request;
+        let parse =
+            |id: &str| Uuid::parse_str(id).map_err(|_| format!("Invalid character ID: {}", id));
+        let leader_id = parse(&leader_id)?;
+        let helper_ids = helper_ids
+            .iter()
+            .map(|id| parse(id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let leader_name = self
+            .characters
+            .get(&leader_id)
+            .map(|c| c.name.clone())
+            .ok_or("Leader not found")?;
+        if helper_ids.is_empty() {
+            return Err("A group roll needs at least one helper".to_string());
+        }
+        let mut helper_names = Vec::new();
+        for (i, id) in helper_ids.iter().enumerate() {
+            if *id == leader_id || helper_ids[..i].contains(id) {
+                return Err("Each character can only join a group roll once".to_string());
+            }
+            let character = self.characters.get(id).ok_or("Helper not found")?;
+            helper_names.push(character.name.clone());
+        }
+
+        let mut target_character_ids = helper_ids;
+        target_character_ids.push(leader_id);
+        let request = PendingRollRequest {
+            id: Uuid::new_v4().to_string(),
+            target_character_ids,
+            roll_type: RollType::Action,
+            attribute,
+            difficulty,
+            context: context.clone(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage,
+            has_disadvantage,
+            is_combat: self.combat_encounter.as_ref().is_some_and(|e| e.is_active),
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: Some(GroupRoll {
+                leader_id,
+                helper_results: Vec::new(),
+            }),
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+        self.pending_roll_requests
+            .insert(request.id.clone(), request.clone());
+
+        self.add_event(
+            GameEventType::RollRequested,
+            format!("GM requested a group roll: \"{}\"", context),
+            Some(leader_name),
+            Some(format!(
+                "Helpers: {}, DC {}",
+                helper_names.join(", "),
+                difficulty
+            )),
+        );
+        Ok(request)
+    }
+
+    // ===== Combat Management =====
+
+    /// Start a new combat encounter
+    pub fn start_combat(&mut self) -> String {
+        let encounter = CombatEncounter::new();
+        let encounter_id = encounter.id.clone();
+        
+        self.combat_encounter = Some(encounter);
+        
+        // Log event
+        self.add_marker_event(
+            LogMarker::CombatStarted,
+            "Combat started".to_string(),
+            Some(format!("Round {}", 1)),
+        );
+
+        // In case the fight goes sideways and the table wants a redo
+        self.create_checkpoint("Combat started".to_string());
+
+        encounter_id
+    }
+
+    /// Set when the table next meets, replacing any earlier date
+    pub fn schedule_next_session(
+        &mut self,
+        scheduled_at: &str,
+        notes: &str,
+    ) -> Result<NextSession, String> {
+        let session = ScheduledSession::new(scheduled_at, notes, chrono::Utc::now())?;
+        let info = session.to_info();
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!(
+                "Next session scheduled for {}",
+                session.at.format("%a %-d %b %Y, %H:%M")
+            ),
+            None,
+            (!session.notes.is_empty()).then(|| session.notes.clone()),
+        );
+        self.next_session = Some(session);
+
+        Ok(info)
+    }
+
+    /// Cancel the next scheduled session
+    pub fn clear_next_session(&mut self) -> Result<(), String> {
+        if self.next_session.take().is_none() {
+            return Err("No session is scheduled".to_string());
+        }
+        self.add_event(
+            GameEventType::SystemMessage,
+            "Next session cancelled".to_string(),
+            None,
+            None,
+        );
+        Ok(())
+    }
+
+    // ===== Notes =====
+
+    /// Write a new note
+    pub fn add_note(&mut self, draft: &NoteDraft) -> Result<Note, String> {
+        let draft = draft.validate()?;
+        if self.notes.len() >= MAX_NOTES {
+            return Err(format!("A campaign holds up to {} notes", MAX_NOTES));
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        let note = Note {
+            id: Uuid::new_v4().to_string(),
+            title: draft.title,
+            body: draft.body,
+            visibility: draft.visibility,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.notes.push(note.clone());
+        Ok(note)
+    }
+
+    /// Rewrite a note, keeping when it was first written
+    pub fn update_note(&mut self, note_id: &str, draft: &NoteDraft) -> Result<Note, String> {
+        let draft = draft.validate()?;
+        let note = self
+            .notes
+            .iter_mut()
+            .find(|n| n.id == note_id)
+            .ok_or_else(|| "Note not found".to_string())?;
+        note.title = draft.title;
+        note.body = draft.body;
+        note.visibility = draft.visibility;
+        note.updated_at = chrono::Utc::now().to_rfc3339();
+        Ok(note.clone())
+    }
+
+    pub fn delete_note(&mut self, note_id: &str) -> Result<Note, String> {
+        let index = self
+            .notes
+            .iter()
+            .position(|n| n.id == note_id)
+            .ok_or_else(|| "Note not found".to_string())?;
+        Ok(self.notes.remove(index))
+    }
+
+    /// Notes with every word of `search` (all of them for a blank search),
+    /// most recently changed first; the GM's own are left out unless
+    /// `include_private`
+    pub fn search_notes(&self, search: &str, include_private: bool) -> Vec<Note> {
+        let mut notes: Vec<_> = self
+            .notes
+            .iter()
+            .filter(|n| include_private || n.is_shared())
+            .filter(|n| n.matches(search))
+            .cloned()
+            .collect();
+        notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        notes
+    }
+
+    // ===== Quests =====
+
+    /// Put a quest on the board
+    pub fn add_quest(&mut self, draft: &QuestDraft) -> Result<Quest, String> {
+        if self.quests.len() >= MAX_QUESTS {
+            return Err(format!("The quest board holds up to {} quests", MAX_QUESTS));
+        }
+        let quest = self.quest_from_draft(Uuid::new_v4().to_string(), draft, None)?;
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("📜 New quest: {}", quest.title),
+            None,
+            (!quest.description.is_empty()).then(|| quest.description.clone()),
+        );
+        self.quests.push(quest.clone());
+        Ok(quest)
+    }
+
+    /// Rewrite a quest; objectives and countdowns whose text is unchanged
+    /// keep where they stand
+    pub fn update_quest(&mut self, quest_id: &str, draft: &QuestDraft) -> Result<Quest, String> {
+        let index = self.quest_index(quest_id)?;
+        let previous = self.quests[index].clone();
+        let quest = self.quest_from_draft(previous.id.clone(), draft, Some(&previous))?;
+        if quest.status != previous.status {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("📜 {}: {}", quest.status.name(), quest.title),
+                None,
+                None,
+            );
+        }
+        self.quests[index] = quest.clone();
+        Ok(quest)
+    }
+
+    pub fn remove_quest(&mut self, quest_id: &str) -> Result<Quest, String> {
+        let index = self.quest_index(quest_id)?;
+        Ok(self.quests.remove(index))
+    }
+
+    /// Tick an objective off, or back on
+    pub fn set_objective_completed(
+        &mut self,
+        quest_id: &str,
+        objective_id: &str,
+        completed: bool,
+    ) -> Result<Quest, String> {
+        let index = self.quest_index(quest_id)?;
+        let quest = &mut self.quests[index];
+        let objective = quest
+            .objectives
+            .iter_mut()
+            .find(|o| o.id == objective_id)
+            .ok_or_else(|| "Objective not found".to_string())?;
+        let newly_done = completed && !objective.completed;
+        objective.completed = completed;
+        let text = objective.text.clone();
+
+        let quest = quest.clone();
+        if newly_done {
+            let (done, total) = quest.progress();
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("✅ {}", text),
+                None,
+                Some(format!("{}: {}/{} objectives", quest.title, done, total)),
+            );
+        }
+        Ok(quest)
+    }
+
+    /// Tick a quest's countdown down, or back up for a negative count
+    pub fn tick_quest_countdown(
+        &mut self,
+        quest_id: &str,
+        countdown_id: &str,
+        ticks: i8,
+    ) -> Result<Quest, String> {
+        let index = self.quest_index(quest_id)?;
+        let quest = &mut self.quests[index];
+        let countdown = quest
+            .countdowns
+            .iter_mut()
+            .find(|c| c.id == countdown_id)
+            .ok_or_else(|| "Countdown not found".to_string())?;
+        let was_done = countdown.is_done();
+        countdown.tick(ticks);
+        let ran_out = !was_done && countdown.is_done();
+        let label = countdown.label.clone();
+
+        let quest = quest.clone();
+        if ran_out {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("⏳ Countdown done: {}", label),
+                None,
+                Some(quest.title.clone()),
+            );
+        }
+        Ok(quest)
+    }
+
+    fn quest_index(&self, quest_id: &str) -> Result<usize, String> {
+        self.quests
+            .iter()
+            .position(|q| q.id == quest_id)
+            .ok_or_else(|| "Quest not found".to_string())
+    }
+
+    /// A quest from a checked draft, keeping `previous`'s progress on the
+    /// objectives and countdowns it still has
+    fn quest_from_draft(
+        &self,
+        id: String,
+        draft: &QuestDraft,
+        previous: Option<&Quest>,
+    ) -> Result<Quest, String> {
+        let draft = draft.validate()?;
+        for character_id in &draft.character_ids {
+            Uuid::parse_str(character_id)
+                .ok()
+                .filter(|id| self.characters.contains_key(id))
+                .ok_or_else(|| "Character not found".to_string())?;
+        }
+
+        let objectives = draft
+            .objectives
+            .into_iter()
+            .map(|text| {
+                let kept = previous.and_then(|q| q.objectives.iter().find(|o| o.text == text));
+                match kept {
+                    Some(objective) => objective.clone(),
+                    None => Objective {
+                        id: Uuid::new_v4().to_string(),
+                        text,
+                        completed: false,
+                    },
+                }
+            })
+            .collect();
+        let countdowns = draft
+            .countdowns
+            .into_iter()
+            .map(|countdown| {
+                let kept = previous.and_then(|q| {
+                    q.countdowns
+                        .iter()
+                        .find(|c| c.label == countdown.label && c.kind == countdown.kind)
+                });
+                QuestCountdown {
+                    id: kept.map_or_else(|| Uuid::new_v4().to_string(), |c| c.id.clone()),
+                    remaining: kept.map_or(countdown.length, |c| c.remaining.min(countdown.length)),
+                    label: countdown.label,
+                    kind: countdown.kind,
+                    length: countdown.length,
+                }
+            })
+            .collect();
+
+        Ok(Quest {
+            id,
+            title: draft.title,
+            description: draft.description,
+            status: draft.status,
+            character_ids: draft.character_ids,
+            objectives,
+            countdowns,
+        })
+    }
+
+    /// Cut away to a scene with some of the characters
+    pub fn start_cutaway(
+        &mut self,
+        character_ids: &[String],
+        label: &str,
+    ) -> Result<CutawayInfo, String> {
+        if self.cutaway.is_some() {
+            return Err("A cutaway is already running".to_string());
+        }
+        let label = label.trim();
+        if label.chars().count() > MAX_CUTAWAY_LABEL_LENGTH {
+            return Err(format!(
+                "Scene label is too long (max {} characters)",
+                MAX_CUTAWAY_LABEL_LENGTH
+            ));
+        }
+
+        let mut ids = Vec::new();
+        let mut names = Vec::new();
+        for id in character_ids {
+            let character = Uuid::parse_str(id)
+                .ok()
+                .and_then(|id| self.characters.get(&id))
+                .ok_or_else(|| format!("Character not found: {}", id))?;
+            if !ids.contains(&character.id) {
+                ids.push(character.id);
+                names.push(character.name.clone());
+            }
+        }
+        if ids.is_empty() {
+            return Err("Pick at least one character for the cutaway".to_string());
+        }
+
+        let cutaway = Cutaway {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            character_ids: ids,
+            character_names: names,
+        };
+        let info = cutaway.to_info();
+
+        let mut message = format!(
+            "Meanwhile… the GM cuts away to {}",
+            info.character_names.join(", ")
+        );
+        if !info.label.is_empty() {
+            message.push_str(&format!(" ({})", info.label));
+        }
+        self.add_event(GameEventType::SystemMessage, message, None, None);
+        self.cutaway = Some(cutaway);
+
+        Ok(info)
+    }
+
+    /// Return from the cutaway, merging what happened in it into the public
+    /// log; returns the scene and its events
+    pub fn end_cutaway(&mut self) -> Result<(CutawayInfo, Vec<GameEventData>), String> {
+        let cutaway = self
+            .cutaway
+            .take()
+            .ok_or_else(|| "No cutaway is running".to_string())?;
+
+        let mut events = Vec::new();
+        for event in self.event_log.iter_mut().filter(|e| e.cutaway) {
+            event.cutaway = false;
+            events.push(event.to_data());
+        }
+        for entry in self.chat_log.iter_mut() {
+            entry.cutaway = false;
+        }
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!(
+                "Back from the cutaway with {}",
+                cutaway.character_names.join(", ")
+            ),
+            None,
+            None,
+        );
+
+        Ok((cutaway.to_info(), events))
+    }
+
+    /// Put a map on the table, or clear it
+    ///
+    /// The scene takes the map's layout; clearing the map goes back to the
+    /// default scene.
+    pub fn set_map(&mut self, map: Option<MapInfo>) {
+        let message = match &map {
+            Some(map) => format!("🗺️ The scene is now {}", map.scene),
+            None => "🗺️ The map was cleared".to_string(),
+        };
+        self.layout = map.as_ref().map(|m| m.layout).unwrap_or_default();
+        self.map = map;
+        self.add_event(GameEventType::SystemMessage, message, None, None);
+        self.expire_modifiers(ModifierDuration::Scene, None);
+    }
+
+    /// Change the scene's size and grid
+    ///
+    /// Tokens already on the table stay where they are until they next move.
+    pub fn set_layout(&mut self, layout: SceneLayout) -> Result<(), String> {
+        layout.validate()?;
+        self.layout = layout;
+        if let Some(map) = &mut self.map {
+            map.layout = layout;
+        }
+        let message = match &layout.grid {
+            Some(grid) => format!(
+                "📐 The scene is {}×{} with a {}-unit {} grid",
+                layout.width,
+                layout.height,
+                grid.cell_size,
+                match grid.kind {
+                    GridKind::Square => "square",
+                    GridKind::Hex => "hex",
+                }
+            ),
+            None => format!(
+                "📐 The scene is {}×{} with no grid",
+                layout.width, layout.height
+            ),
+        };
+        self.add_event(GameEventType::SystemMessage, message, None, None);
+        Ok(())
+    }
+
+    // ===== Fog of War =====
+
+    /// Which scene's fog applies: the map on the table, or "" without one
+    fn scene_key(&self) -> String {
+        self.map.as_ref().map(|m| m.id.clone()).unwrap_or_default()
+    }
+
+    /// The current scene's fog
+    pub fn current_fog(&self) -> FogState {
+        self.fog.get(&self.scene_key()).cloned().unwrap_or_default()
+    }
+
+    /// Turn fog of war on or off for the current scene
+    ///
+    /// Revealed areas are kept while it's off.
+    pub fn set_fog_enabled(&mut self, enabled: bool) -> FogState {
+        let key = self.scene_key();
+        let fog = self.fog.entry(key).or_default();
+        fog.enabled = enabled;
+        let fog = fog.clone();
+        self.add_event(
+            GameEventType::SystemMessage,
+            if enabled {
+                "🌫️ Fog of war rolls in".to_string()
+            } else {
+                "🌫️ The fog lifts".to_string()
+            },
+            None,
+            None,
+        );
+        fog
+    }
+
+    /// Reveal a polygon of the current scene to the players
+    pub fn reveal_area(&mut self, points: Vec<Position>) -> Result<FogState, String> {
+        fog::validate_area(&points)?;
+        let key = self.scene_key();
+        let fog = self.fog.entry(key).or_default();
+        if fog.revealed.len() >= MAX_FOG_AREAS {
+            return Err(format!(
+                "Too many revealed areas (max {}); hide some first",
+                MAX_FOG_AREAS
+            ));
+        }
+        fog.revealed.push(FogArea {
+            id: Uuid::new_v4().to_string(),
+            points,
+        });
+        Ok(fog.clone())
+    }
+
+    /// Cover a revealed area of the current scene again
+    pub fn hide_area(&mut self, area_id: &str) -> Result<FogState, String> {
+        let key = self.scene_key();
+        let fog = self.fog.entry(key).or_default();
+        let before = fog.revealed.len();
+        fog.revealed.retain(|area| area.id != area_id);
+        if fog.revealed.len() == before {
+            return Err(format!("Revealed area not found: {}", area_id));
+        }
+        Ok(fog.clone())
+    }
+
+    /// NPCs and adversaries players can't see: under the fog of the current
+    /// scene. Player characters are never hidden.
+    pub fn fogged_tokens(&self) -> HashSet<String> {
+        let Some(fog) = self.fog.get(&self.scene_key()).filter(|f| f.enabled) else {
+            return HashSet::new();
+        };
+        let npcs = self
+            .characters
+            .values()
+            .filter(|c| c.is_npc)
+            .map(|c| (c.id.to_string(), c.position));
+        let adversaries = self
+            .adversaries
+            .values()
+            .map(|a| (a.id.clone(), a.position));
+        npcs.chain(adversaries)
+            .filter(|(_, position)| !fog.is_revealed(position))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    // ===== Pings and Drawings =====
+
+    /// Rate-limit a ping on the map, returning who pinged and in what color
+    ///
+    /// Players ping as their character, falling back to their display name;
+    /// the GM pings in gold.
+    pub fn ping(
+        &mut self,
+        conn_id: &Uuid,
+        position: &Position,
+    ) -> Result<(String, String), String> {
+        if !position.x.is_finite() || !position.y.is_finite() || !self.layout.contains(position) {
+            return Err("Ping is outside the scene".to_string());
+        }
+        let connection = self
+            .connections
+            .get(conn_id)
+            .ok_or_else(|| format!("Connection not found: {}", conn_id))?;
+        let character = self.get_controlled_character(conn_id);
+        let (name, color) = match character {
+            _ if connection.role == ConnectionRole::Gm => {
+                ("GM".to_string(), GM_PING_COLOR.to_string())
+            }
+            Some(c) => (c.name.clone(), c.color.clone()),
+            None => (
+                connection
+                    .display_name
+                    .clone()
+                    .unwrap_or_else(|| "Player".to_string()),
+                GM_PING_COLOR.to_string(),
+            ),
+        };
+
+        let now = std::time::Instant::now();
+        if let Some(last) = self.last_pings.get(conn_id) {
+            if now.duration_since(*last) < PING_COOLDOWN {
+                return Err("Slow down! Wait a moment before pinging again".to_string());
+            }
+        }
+        self.last_pings.insert(*conn_id, now);
+        Ok((name, color))
+    }
+
+    /// The current scene's drawings
+    pub fn current_drawings(&self) -> &[Drawing] {
+        self.drawings
+            .get(&self.scene_key())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Draw on the current scene, returning its drawings
+    pub fn add_drawing(
+        &mut self,
+        shape: DrawingShape,
+        color: &str,
+        label: &str,
+    ) -> Result<Vec<Drawing>, String> {
+        shape.validate()?;
+        let color = color.trim().to_lowercase();
+        parse_hex_color(&color).ok_or_else(|| format!("Invalid color: {}", color))?;
+        let label = label.trim();
+        if label.chars().count() > MAX_DRAWING_LABEL_LENGTH {
+            return Err(format!(
+                "Label is too long (max {} characters)",
+                MAX_DRAWING_LABEL_LENGTH
+            ));
+        }
+
+        let key = self.scene_key();
+        let drawings = self.drawings.entry(key).or_default();
+        if drawings.len() >= MAX_DRAWINGS {
+            return Err(format!(
+                "Too many drawings (max {}); erase some first",
+                MAX_DRAWINGS
+            ));
+        }
+        drawings.push(Drawing {
+            id: Uuid::new_v4().to_string(),
+            shape,
+            color,
+            label: label.to_string(),
+        });
+        Ok(drawings.clone())
+    }
+
+    /// Erase a drawing from the current scene
+    pub fn remove_drawing(&mut self, drawing_id: &str) -> Result<Vec<Drawing>, String> {
+        let key = self.scene_key();
+        let drawings = self.drawings.entry(key).or_default();
+        let before = drawings.len();
+        drawings.retain(|drawing| drawing.id != drawing_id);
+        if drawings.len() == before {
+            return Err(format!("Drawing not found: {}", drawing_id));
+        }
+        Ok(drawings.clone())
+    }
+
+    /// Erase everything drawn on the current scene
+    pub fn clear_drawings(&mut self) -> Vec<Drawing> {
+        let key = self.scene_key();
+        self.drawings.remove(&key);
+        Vec::new()
+    }
+
+    /// The scheduled session, if its reminder is due, marking it sent
+    pub fn take_due_reminder(
+        &mut self,
+        now: chrono::DateTime<chrono::Utc>,
+        lead: chrono::Duration,
+    ) -> Option<ScheduledSession> {
+        let session = self
+            .next_session
+            .as_mut()
+            .filter(|s| s.reminder_due(now, lead))?;
+        session.reminded = true;
+        Some(session.clone())
+    }
+
+    /// Take an in-memory checkpoint of the game, dropping the oldest past
+    /// [`MAX_CHECKPOINTS`]
+    pub fn create_checkpoint(&mut self, label: String) -> CheckpointInfo {
+        let checkpoints = std::mem::take(&mut self.checkpoints);
+        let mut state = self.clone();
+        self.checkpoints = checkpoints;
+
+        state.connections.clear();
+        state.preferences.clear();
+        state.event_log.clear();
+        state.combat_recordings.clear();
+
+        let checkpoint = Checkpoint {
+            id: Uuid::new_v4().to_string(),
+            label,
+            created_at: chrono::Utc::now(),
+            state: Box::new(state),
+        };
+        let info = checkpoint.to_info();
+
+        self.checkpoints.push(checkpoint);
+        if self.checkpoints.len() > MAX_CHECKPOINTS {
+            self.checkpoints.remove(0);
+        }
+        info
+    }
+
+    /// Restore a checkpoint, the most recent when `checkpoint_id` is None
+    ///
+    /// The checkpoint is kept so the same moment can be replayed again.
+    pub fn rollback_to_checkpoint(
+        &mut self,
+        checkpoint_id: Option<&str>,
+    ) -> Result<CheckpointInfo, String> {
+        let checkpoint = match checkpoint_id {
+            Some(id) => self
+                .checkpoints
+                .iter()
+                .find(|c| c.id == id)
+                .ok_or_else(|| format!("Checkpoint not found: {}", id))?,
+            None => self
+                .checkpoints
+                .last()
+                .ok_or_else(|| "No checkpoints to roll back to".to_string())?,
+        };
+        let info = checkpoint.to_info();
+        let mut restored = (*checkpoint.state).clone();
+
+        // Who's connected, session settings and history carry over
+        restored.connections = std::mem::take(&mut self.connections);
+        restored.preferences = std::mem::take(&mut self.preferences);
+        restored.event_log = std::mem::take(&mut self.event_log);
+        restored.events_logged = self.events_logged;
+        restored.combat_recordings = std::mem::take(&mut self.combat_recordings);
+        restored.checkpoints = std::mem::take(&mut self.checkpoints);
+        restored.limits = self.limits;
+        restored.adversary_dir = std::mem::take(&mut self.adversary_dir);
+        restored.homebrew_templates = std::mem::take(&mut self.homebrew_templates);
+        restored.settings = self.settings.clone();
+        restored.narrative_templates = std::mem::take(&mut self.narrative_templates);
+        restored.last_emotes = std::mem::take(&mut self.last_emotes);
+        restored.last_pings = std::mem::take(&mut self.last_pings);
+        restored.next_session = self.next_session.take();
+        restored.chat_log = std::mem::take(&mut self.chat_log);
+        restored.cutaway = self.cutaway.take();
+        restored.map = self.map.take();
+        restored.layout = self.layout;
+        restored.fog = std::mem::take(&mut self.fog);
+        restored.drawings = std::mem::take(&mut self.drawings);
+        restored.map_locked = self.map_locked;
+        restored.banned_addresses = std::mem::take(&mut self.banned_addresses);
+        // The dice roll on rather than repeat what came up after the checkpoint
+        restored.dice = std::mem::take(&mut self.dice);
+        restored.roll_stats = std::mem::take(&mut self.roll_stats);
+
+        // Players keep their characters if those existed back then
+        restored.control_mapping = std::mem::take(&mut self.control_mapping);
+        let characters = &restored.characters;
+        restored.control_mapping.retain(|_, char_ids| {
+            char_ids.retain(|id| characters.contains_key(id));
+            !char_ids.is_empty()
+        });
+        let connections = &restored.connections;
+        for stand_in in restored.ghosted_characters.values_mut() {
+            if stand_in.is_some_and(|conn_id| !connections.contains_key(&conn_id)) {
+                *stand_in = None;
+            }
+        }
+
+        *self = restored;
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Rolled back to checkpoint: {}", info.label),
+            None,
+            Some(format!("Taken at {}", info.created_at)),
+        );
+        Ok(info)
+    }
+
+    /// End the current combat encounter
+    pub fn end_combat(&mut self, reason: &str) {
+        if let Some(_encounter) = self.combat_encounter.take() {
+            // Ghosting only lasts for the fight; release any GM stand-ins
+            let ghosted: Vec<_> = self.ghosted_characters.drain().collect();
+            for (char_id, stand_in) in ghosted {
+                if stand_in.is_some() {
+                    self.release_character(&char_id);
+                }
+            }
+
+            self.add_marker_event(
+                LogMarker::CombatEnded,
+                format!("Combat ended: {}", reason),
+                None,
+            );
+            self.expire_modifiers(ModifierDuration::Scene, None);
+        }
+    }
+
+    /// Snapshot token positions for the active encounter
+    ///
+    /// Returns the encounter id and the new keyframe, or `None` when there is
+    /// no active combat, nothing moved since the last keyframe, or the
+    /// encounter already has `MAX_KEYFRAMES_PER_COMBAT` keyframes.
+    pub fn capture_keyframe(&mut self) -> Option<(String, PositionKeyframe)> {
+        let encounter = self.combat_encounter.as_ref().filter(|e| e.is_active)?;
+        let (encounter_id, round) = (encounter.id.clone(), encounter.round);
+
+        let mut tokens: Vec<TokenPosition> = self
+            .map_tokens()
+            .into_iter()
+            .map(|t| TokenPosition {
+                id: t.id,
+                name: t.name,
+                is_adversary: t.is_adversary,
+                position: t.position,
+            })
+            .collect();
+        tokens.sort_by(|a, b| a.id.cmp(&b.id));
+
+        if self
+            .combat_recordings
+            .last()
+            .is_none_or(|r| r.encounter_id != encounter_id)
+        {
+            self.combat_recordings.push(CombatRecording {
+                encounter_id: encounter_id.clone(),
+                started: std::time::SystemTime::now(),
+                keyframes: Vec::new(),
+            });
+            if self.combat_recordings.len() > MAX_COMBAT_RECORDINGS {
+                self.combat_recordings.remove(0);
+            }
+        }
+        let recording = self.combat_recordings.last_mut()?;
+
+        let unchanged = recording
+            .keyframes
+            .last()
+            .is_some_and(|k| k.round == round && k.tokens == tokens);
+        if unchanged || recording.keyframes.len() >= MAX_KEYFRAMES_PER_COMBAT {
+            return None;
+        }
+
+        let keyframe = PositionKeyframe {
+            at_ms: recording.started.elapsed().unwrap_or_default().as_millis() as u64,
+            round,
+            tokens,
+        };
+        recording.keyframes.push(keyframe.clone());
+        Some((encounter_id, keyframe))
+    }
+
+    /// Keyframes recorded for an encounter
+    pub fn combat_recording(&self, encounter_id: &str) -> Option<&CombatRecording> {
+        self.combat_recordings
+            .iter()
+            .find(|r| r.encounter_id == encounter_id)
+    }
+
+    /// Switch the active encounter between the Action Tracker and spotlight flow
+    pub fn set_combat_mode(&mut self, mode: CombatMode) -> Result<(), String> {
+        let encounter = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        if encounter.mode == mode {
+            return Ok(());
+        }
+        encounter.mode = mode;
+        encounter.spotlight = None;
+
+        let description = match mode {
+            CombatMode::ActionTracker => "Using the Action Tracker",
+            CombatMode::Spotlight => "Using spotlight flow",
+        };
+        self.add_event(
+            GameEventType::SystemMessage,
+            description.to_string(),
+            None,
+            None,
+        );
+        Ok(())
+    }
+
+    /// Pass the spotlight to a character or active adversary, or back to the GM
+    ///
+    /// Returns the previous holder and the new holder's name.
+    pub fn pass_spotlight(&mut self, to: Option<&str>) -> Result<(Option<String>, String), String> {
+        let holder_name = match to {
+            None => "GM".to_string(),
+            Some(id) => self
+                .map_tokens()
+                .into_iter()
+                .find(|t| t.id == id)
+                .map(|t| t.name)
+                .ok_or_else(|| format!("Token not found: {}", id))?,
+        };
+
+        let encounter = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        if encounter.mode != CombatMode::Spotlight {
+            return Err("Combat is using the Action Tracker".to_string());
+        }
+        let previous = std::mem::replace(&mut encounter.spotlight, to.map(str::to_string));
+        encounter.moved_feet.clear();
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Spotlight on {}", holder_name),
+            to.map(|_| holder_name.clone()),
+            None,
+        );
+        Ok((previous, holder_name))
+    }
+
+    /// Get the current combat encounter
+    pub fn get_combat(&self) -> Option<&CombatEncounter> {
+        self.combat_encounter.as_ref()
+    }
+
+    /// Get mutable reference to combat
+    pub fn get_combat_mut(&mut self) -> Option<&mut CombatEncounter> {
+        self.combat_encounter.as_mut()
+    }
+
+    /// The active encounter, if it is using the Action Tracker
+    fn tracker_encounter_mut(&mut self) -> Result<&mut CombatEncounter, String> {
+        let encounter = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        if encounter.mode != CombatMode::ActionTracker {
+            return Err("Combat is using spotlight flow".to_string());
+        }
+        Ok(encounter)
+    }
+
+    /// Advance the action tracker based on roll result
+    pub fn advance_tracker(&mut self, success_with_hope: bool) -> Result<TokenType, String> {
+        let token_type = if success_with_hope {
+            TokenType::PC
+        } else {
+            TokenType::Adversary
+        };
+
+        let tracker = &mut self.tracker_encounter_mut()?.action_tracker;
+        let pool = match token_type {
+            TokenType::PC => tracker.pc_tokens,
+            TokenType::Adversary => tracker.adversary_tokens,
+        };
+        if pool == 0 {
+            return Err(format!("No {} tokens left to advance", token_type.name()));
+        }
+        tracker.advance_token(token_type);
+        tracker.refill_if_needed();
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} token added to the tracker", token_type.name()),
+            None,
+            None,
+        );
+        Ok(token_type)
+    }
+
+    /// Spend the next token on the Action Tracker
+    ///
+    /// Spending the last token refills the tracker and advances the round,
+    /// which also ticks any active hazards.
+    pub fn spend_tracker_token(&mut self) -> Result<TrackerSpend, String> {
+        let tracker = &mut self.tracker_encounter_mut()?.action_tracker;
+        let token = tracker.pop_next().ok_or("The action tracker is empty")?;
+        let exhausted = tracker.queue.is_empty();
+        if exhausted {
+            tracker.refill_if_needed();
+        }
+        if let Some(encounter) = self.combat_encounter.as_mut() {
+            encounter.moved_feet.clear();
+        }
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} token spent", token.name()),
+            None,
+            None,
+        );
+
+        let round_advanced = if exhausted {
+            Some(self.advance_round()?)
+        } else {
+            None
+        };
+        Ok(TrackerSpend {
+            token,
+            round_advanced,
+        })
+    }
+
+    /// Spend a Fear from the pool on an extra adversary token or an
+    /// adversary interrupting, returning the announcement
+    ///
+    /// An extra token needs the Action Tracker. An interrupting adversary's
+    /// token goes to the front of the tracker, or in spotlight flow the
+    /// adversary takes the spotlight.
+    pub fn spend_fear(&mut self, spend: &FearSpend) -> Result<String, String> {
+        if self.fear_pool == 0 {
+            return Err("No Fear to spend".to_string());
+        }
+        let message = match spend {
+            FearSpend::AdversaryToken => {
+                self.tracker_encounter_mut()?
+                    .action_tracker
+                    .queue
+                    .push(TokenType::Adversary);
+                "😈 The GM spends a Fear: the adversaries gain another action".to_string()
+            }
+            FearSpend::Interrupt { adversary_id } => format!(
+                "😈 The GM spends a Fear: {} seizes the spotlight!",
+                self.adversary_acts_now(adversary_id)?
+            ),
+        };
+        self.fear_pool -= 1;
+
+        self.add_event(
+            GameEventType::CombatAction,
+            message.clone(),
+            None,
+            Some(format!("{} Fear left", self.fear_pool)),
+        );
+        Ok(message)
+    }
+
+    /// Put an active adversary next in line: its token goes to the front of
+    /// the tracker, or in spotlight flow it takes the spotlight. Returns its
+    /// name.
+    fn adversary_acts_now(&mut self, adversary_id: &str) -> Result<String, String> {
+        let name = self
+            .adversaries
+            .get(adversary_id)
+            .filter(|a| a.is_active)
+            .ok_or_else(|| format!("No active adversary {}", adversary_id))?
+            .name
+            .clone();
+        let encounter = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        match encounter.mode {
+            CombatMode::Spotlight => encounter.spotlight = Some(adversary_id.to_string()),
+            CombatMode::ActionTracker => encounter
+                .action_tracker
+                .queue
+                .insert(0, TokenType::Adversary),
+        }
+        encounter.moved_feet.clear();
+        Ok(name)
+    }
+
+    /// Mark an adversary's Stress, spending it on a move if one is given;
+    /// returns the announcement
+    ///
+    /// An adversary can only mark Stress it has slots left for. An extra
+    /// damage die waits for its next damage roll, acting again needs combat,
+    /// and a named feature is just announced for the table to resolve.
+    pub fn mark_adversary_stress(
+        &mut self,
+        adversary_id: &str,
+        stress: u8,
+        stress_move: Option<&StressMove>,
+    ) -> Result<String, String> {
+        if stress == 0 {
+            return Err("Mark at least one Stress".to_string());
+        }
+        let adversary = self
+            .adversaries
+            .get(adversary_id)
+            .ok_or_else(|| format!("Adversary not found: {}", adversary_id))?;
+        if !adversary.is_active {
+            return Err(format!("{} has been taken out", adversary.name));
+        }
+        let slots_left = adversary.max_stress.saturating_sub(adversary.stress);
+        if stress > slots_left {
+            return Err(format!(
+                "{} has only {} Stress left to mark",
+                adversary.name, slots_left
+            ));
+        }
+        let name = adversary.name.clone();
+
+        let message = match stress_move {
+            None => format!("{} marks {} Stress", name, stress),
+            Some(StressMove::ExtraDamageDie) => {
+                format!("💢 {} marks {} Stress to hit harder", name, stress)
+            }
+            Some(StressMove::ActAgain) => {
+                self.adversary_acts_now(adversary_id)?;
+                format!("💢 {} marks {} Stress to act again!", name, stress)
+            }
+            Some(StressMove::Feature { name: feature }) => {
+                format!("💢 {} marks {} Stress: {}", name, stress, feature)
+            }
+        };
+
+        if let Some(adversary) = self.adversaries.get_mut(adversary_id) {
+            adversary.stress += stress;
+            if stress_move == Some(&StressMove::ExtraDamageDie) {
+                adversary.extra_damage_dice += 1;
+            }
+        }
+        self.add_event(GameEventType::CombatAction, message.clone(), None, None);
+        Ok(message)
+    }
+
+    /// An attacker's damage dice with the extra dice it marked Stress for,
+    /// which are used up; each is another of its first die
+    pub fn damage_dice_with_extras(&mut self, attacker_id: &str, damage_dice: &str) -> String {
+        let Some(adversary) = self
+            .adversaries
+            .get_mut(attacker_id)
+            .filter(|a| a.extra_damage_dice > 0)
+        else {
+            return damage_dice.to_string();
+        };
+        let Some(sides) = dice::DiceExpression::parse(damage_dice)
+            .ok()
+            .and_then(|expression| expression.groups.first().map(|group| group.sides))
+        else {
+            return damage_dice.to_string();
+        };
+        let extra = std::mem::take(&mut adversary.extra_damage_dice);
+        format!("{}+{}d{}", damage_dice, extra, sides)
+    }
+
+    /// Note whether an attack roll was a critical hit, so the damage rolled
+    /// for it next is one too
+    pub fn record_attack(&mut self, attacker_id: &str, target_id: &str, critical_hit: bool) {
+        let attack = (attacker_id.to_string(), target_id.to_string());
+        if critical_hit {
+            self.critical_hits.insert(attack);
+        } else {
+            self.critical_hits.remove(&attack);
+        }
+    }
+
+    /// Whether the attacker's last attack on the target was a critical hit
+    /// still waiting on its damage; asking uses it up
+    pub fn take_critical_hit(&mut self, attacker_id: &str, target_id: &str) -> bool {
+        self.critical_hits
+            .remove(&(attacker_id.to_string(), target_id.to_string()))
+    }
+
+    /// Roll an attacker's damage, with any extra dice it marked Stress for;
+    /// a critical hit adds the most the dice could roll
+    pub fn roll_damage(
+        &mut self,
+        attacker_id: &str,
+        damage_dice: &str,
+        critical: bool,
+    ) -> Result<DamageRoll, String> {
+        let damage_dice = self.damage_dice_with_extras(attacker_id, damage_dice);
+        let expression = dice::DiceExpression::parse(&damage_dice)?;
+        let roll = self.dice.with(|rng| expression.roll(rng));
+        let critical_bonus = if critical { expression.max_dice() } else { 0 };
+        Ok(DamageRoll {
+            raw_damage: roll.total.saturating_add(critical_bonus),
+            roll,
+            critical_bonus,
+        })
+    }
+
+    /// Get next actor in combat
+    pub fn get_next_actor(&self) -> Option<TokenType> {
+        self.combat_encounter
+            .as_ref()
+            .and_then(|e| e.action_tracker.get_next())
+    }
+
+    /// Motives & tactics of every active adversary, when an adversary token
+    /// is at the front of the tracker (empty otherwise)
+    pub fn tactics_for_next_token(&self) -> Vec<AdversaryTactics> {
+        if self.get_next_actor() != Some(TokenType::Adversary) {
+            return Vec::new();
+        }
+        let mut tactics: Vec<_> = self
+            .adversaries
+            .values()
+            .filter(|a| a.is_active)
+            .filter_map(|a| a.tactics())
+            .collect();
+        tactics.sort_by(|a, b| a.name.cmp(&b.name));
+        tactics
+    }
+
+    // ===== Adversary Management =====
+
+    /// All spawnable templates; homebrew templates replace built-ins with the same id
+    pub fn adversary_templates(&self) -> Vec<AdversaryTemplate> {
+        let mut templates: Vec<AdversaryTemplate> = AdversaryTemplate::get_all_templates()
+            .into_iter()
+            .filter(|t| !self.homebrew_templates.iter().any(|h| h.id == t.id))
+            .collect();
+        templates.extend(self.homebrew_templates.iter().cloned());
+        templates
+    }
+
+    /// Look up a template by id, preferring homebrew
+    pub fn find_adversary_template(&self, id: &str) -> Option<AdversaryTemplate> {
+        self.homebrew_templates
+            .iter()
+            .find(|t| t.id == id)
+            .cloned()
+            .or_else(|| AdversaryTemplate::get_template(id))
+    }
+
+    /// Re-read homebrew templates from `adversary_dir`
+    ///
+    /// On error the previously loaded templates are kept.
+    pub fn reload_adversary_templates(&mut self) -> Result<usize, String> {
+        let templates = AdversaryTemplate::load_dir(&self.adversary_dir)?;
+        let count = templates.len();
+        self.homebrew_templates = templates;
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Loaded {} homebrew adversary templates", count),
+            None,
+            Some(self.adversary_dir.display().to_string()),
+        );
+
+        Ok(count)
+    }
+
+    /// Spawn an adversary from template
+    pub fn spawn_adversary(
+        &mut self,
+        template_id: &str,
+        position: crate::protocol::Position,
+    ) -> Result<Adversary, String> {
+        self.limits.check_adversaries(self.adversaries.len())?;
+
+        let template = self
+            .find_adversary_template(template_id)
+            .ok_or_else(|| format!("Template not found: {}", template_id))?;
+
+        // Numbers are never reused, so removals can't cause duplicate names
+        let spawned = self
+            .adversary_spawn_counts
+            .entry(template.id.clone())
+            .or_insert(0);
+        *spawned += 1;
+
+        let adversary = Adversary::from_template(&template, position, *spawned);
+        let adversary_id = adversary.id.clone();
+
+        // Log event
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} spawned", adversary.name),
+            None,
+            Some(format!(
+                "HP: {}/{}, Evasion: {}, Armor: {}",
+                adversary.hp, adversary.max_hp, adversary.evasion, adversary.armor
+            )),
+        );
+
+        self.adversaries.insert(adversary_id.clone(), adversary.clone());
+        Ok(adversary)
+    }
+
+    // ===== Encounter building =====
+
+    /// Price a plan against the party's Battle Points budget
+    pub fn price_encounter(&self, plan: &EncounterPlan) -> Result<EncounterBudget, String> {
+        if !(1..=MAX_PARTY_SIZE).contains(&plan.party_size) {
+            return Err(format!("Party size must be 1-{}", MAX_PARTY_SIZE));
+        }
+        if !(1..=MAX_TIER).contains(&plan.tier) {
+            return Err(format!("Tier must be 1-{}", MAX_TIER));
+        }
+
+        let lines = plan
+            .entries
+            .iter()
+            .map(|entry| {
+                let template = self
+                    .find_adversary_template(&entry.template_id)
+                    .ok_or_else(|| format!("Template not found: {}", entry.template_id))?;
+                if entry.count == 0 {
+                    return Err(format!("How many {}?", template.name));
+                }
+                Ok(EncounterLine::new(
+                    template.id,
+                    template.name,
+                    template.role,
+                    template.tier,
+                    entry.count,
+                    plan.party_size,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(EncounterBudget::new(plan, lines))
+    }
+
+    /// Put every adversary of a plan on the map at once, each at its
+    /// entry's position or else somewhere in the scene
+    ///
+    /// All or nothing: the templates and the adversary limit are checked
+    /// before anything spawns.
+    pub fn spawn_encounter(
+        &mut self,
+        entries: &[EncounterEntry],
+    ) -> Result<Vec<Adversary>, String> {
+        let total: usize = entries.iter().map(|e| e.count as usize).sum();
+        if total == 0 {
+            return Err("The encounter has no adversaries".to_string());
+        }
+        if let Some(entry) = entries
+            .iter()
+            .find(|e| self.find_adversary_template(&e.template_id).is_none())
+        {
+            return Err(format!("Template not found: {}", entry.template_id));
+        }
+        self.limits
+            .check_adversaries(self.adversaries.len() + total - 1)?;
+
+        let mut spawned = Vec::with_capacity(total);
+        for entry in entries {
+            for n in 0..entry.count as usize {
+                let position = entry
+                    .positions
+                    .get(n)
+                    .copied()
+                    .filter(|p| self.layout.contains(p))
+                    .unwrap_or_else(|| self.random_position());
+                spawned.push(self.spawn_adversary(&entry.template_id, position)?);
+            }
+        }
+        Ok(spawned)
+    }
+
+    /// The adversaries still in the fight as a plan for the party as it
+    /// stands, each entry keeping where its adversaries are
+    ///
+    /// Custom adversaries have no template to spawn again from, so they're
+    /// left out; their names come back with the plan.
+    pub fn encounter_from_map(&self) -> Result<(EncounterPlan, Vec<String>), String> {
+        let mut adversaries: Vec<&Adversary> =
+            self.adversaries.values().filter(|a| a.is_active).collect();
+        if adversaries.is_empty() {
+            return Err("No adversaries on the map".to_string());
+        }
+        adversaries.sort_by(|a, b| (&a.template, a.instance).cmp(&(&b.template, b.instance)));
+
+        let mut entries: Vec<EncounterEntry> = Vec::new();
+        let mut skipped = Vec::new();
+        for adversary in adversaries {
+            if self.find_adversary_template(&adversary.template).is_none() {
+                skipped.push(adversary.name.clone());
+                continue;
+            }
+            match entries.last_mut() {
+                Some(entry) if entry.template_id == adversary.template && entry.count < u8::MAX => {
+                    entry.count += 1;
+                    entry.positions.push(adversary.position);
+                }
+                _ => entries.push(EncounterEntry {
+                    template_id: adversary.template.clone(),
+                    count: 1,
+                    positions: vec![adversary.position],
+                }),
+            }
+        }
+        if entries.is_empty() {
+            return Err("Only custom adversaries are on the map".to_string());
+        }
+
+        let party = self.get_player_characters();
+        let plan = EncounterPlan {
+            party_size: (party.len() as u8).clamp(1, MAX_PARTY_SIZE),
+            tier: party
+                .iter()
+                .map(|c| leveling::tier_for_level(c.level))
+                .max()
+                .unwrap_or(1),
+            entries,
+            difficulty: Default::default(),
+            extra_damage: false,
+        };
+        Ok((plan, skipped))
+    }
+
+    /// Create a custom adversary
+    pub fn create_custom_adversary(
+        &mut self,
+        custom: CustomAdversary,
+    ) -> Result<Adversary, String> {
+        self.limits.check_adversaries(self.adversaries.len())?;
+
+        let adversary = Adversary::custom(
+            custom.name,
+            custom.position,
+            custom.hp,
+            custom.evasion,
+            custom.armor,
+            custom.attack_modifier,
+            custom.damage_dice,
+        );
+
+        // Log event
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} spawned (custom)", adversary.name),
+            None,
+            Some(format!(
+                "HP: {}/{}, Evasion: {}, Armor: {}",
+                adversary.hp, adversary.max_hp, adversary.evasion, adversary.armor
+            )),
+        );
+
+        let adversary_id = adversary.id.clone();
+        self.adversaries.insert(adversary_id, adversary.clone());
+        Ok(adversary)
+    }
+
+    /// Turn an NPC into an adversary when talk breaks down
+    ///
+    /// The adversary keeps the NPC's name, position, HP and Evasion, takes
+    /// the rest of its stats from the linked template (if any), and inherits
+    /// its auras and modifiers. The NPC is removed.
+    pub fn convert_npc_to_adversary(&mut self, character_id: &Uuid) -> Result<Adversary, String> {
+        let npc = self
+            .characters
+            .get(character_id)
+            .ok_or("Character not found")?;
+        if !npc.is_npc {
+            return Err(format!("{} is not an NPC", npc.name));
+        }
+        self.limits.check_adversaries(self.adversaries.len())?;
+
+        let template = npc
+            .adversary_template
+            .as_deref()
+            .and_then(|id| self.find_adversary_template(id));
+        let evasion = npc.evasion.clamp(0, u8::MAX as i32) as u8;
+
+        let mut adversary = match template {
+            Some(template) => Adversary::from_template(&template, npc.position, 0),
+            None => Adversary::custom(
+                npc.name.clone(),
+                npc.position,
+                npc.hp.maximum,
+                evasion,
+                0,
+                0,
+                NPC_DAMAGE_DICE.to_string(),
+            ),
+        };
+        adversary.name = npc.name.clone();
+        adversary.hp = npc.hp.current;
+        adversary.max_hp = npc.hp.maximum;
+        adversary.evasion = evasion;
+
+        let npc = self
+            .characters
+            .remove(character_id)
+            .expect("NPC checked above");
+        self.release_character(character_id);
+        self.ghosted_characters.remove(character_id);
+        let owner_id = character_id.to_string();
+        for aura in self.auras.values_mut().filter(|a| a.owner_id == owner_id) {
+            aura.owner_id = adversary.id.clone();
+        }
+        for modifier in self
+            .modifiers
+            .values_mut()
+            .filter(|m| m.target_id == owner_id)
+        {
+            modifier.target_id = adversary.id.clone();
+        }
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} turns hostile", npc.name),
+            Some(npc.name.clone()),
+            Some(format!(
+                "HP: {}/{}, Evasion: {}, Armor: {}",
+                adversary.hp, adversary.max_hp, adversary.evasion, adversary.armor
+            )),
+        );
+
+        self.adversaries
+            .insert(adversary.id.clone(), adversary.clone());
+        Ok(adversary)
+    }
+
+    /// Damage type an attacker deals: a character's weapon or an adversary's attack
+    pub fn attack_damage_type(&self, attacker_id: &str) -> DamageType {
+        self.characters
+            .values()
+            .find(|c| c.id.to_string() == attacker_id)
+            .map(|c| c.weapon_damage_type())
+            .or_else(|| {
+                self.adversaries
+                    .get(attacker_id)
+                    .map(|a| a.damage_type.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Resistances, immunities and vulnerabilities of a character or adversary
+    pub fn defenses_of(&self, target_id: &str) -> Defenses {
+        self.characters
+            .values()
+            .find(|c| c.id.to_string() == target_id)
+            .map(|c| c.defenses.clone())
+            .or_else(|| self.adversaries.get(target_id).map(|a| a.defenses.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Armor score of a character or adversary (0 if unknown)
+    pub fn armor_of(&self, target_id: &str) -> u8 {
+        self.characters
+            .values()
+            .find(|c| c.id.to_string() == target_id)
+            .map(|c| c.armor_score())
+            .or_else(|| self.adversaries.get(target_id).map(|a| a.armor))
+            .unwrap_or(0)
+    }
+
+    /// Mark resolved damage on a character or adversary
+    pub fn apply_damage(
+        &mut self,
+        target_id: &str,
+        damage: &DamageBreakdown,
+    ) -> Option<AppliedDamage> {
+        if let Some(character) = self
+            .characters
+            .values_mut()
+            .find(|c| c.id.to_string() == target_id)
+        {
+            // Stress that doesn't fit in the character's slots marks HP instead
+            let room = character
+                .progression
+                .stress_slots()
+                .saturating_sub(character.stress.current);
+            let stress = damage.stress_gained.min(room);
+            let hp_lost = damage.hp_lost.saturating_add(damage.stress_gained - stress);
+
+            if hp_lost > 0 {
+                character.hp.take_damage(hp_lost.min(character.hp.current));
+            }
+            if stress > 0 {
+                character.stress.gain(stress);
+            }
+            character.sync_resources();
+
+            // Marking the last HP takes a character out until their death move
+            Some(AppliedDamage {
+                target_name: character.name.clone(),
+                new_hp: character.hp.current,
+                new_stress: character.stress.current,
+                taken_out: character.hp.current == 0,
+            })
+        } else if let Some(adversary) = self.adversaries.get_mut(target_id) {
+            let taken_out = adversary.take_damage(damage.hp_lost, damage.stress_gained);
+            Some(AppliedDamage {
+                target_name: adversary.name.clone(),
+                new_hp: adversary.hp,
+                new_stress: adversary.stress,
+                taken_out,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Roll damage once and deal it to every character and active adversary
+    /// in `area` and to each of `target_ids`, each through their own
+    /// defenses and armor
+    ///
+    /// A group's id in `target_ids` stands for its members still standing.
+    ///
+    /// Everything is checked before anyone is hurt, so an unknown target or
+    /// a bad expression leaves the game as it was.
+    pub fn apply_area_damage(
+        &mut self,
+        source: &str,
+        area: Option<&HazardArea>,
+        target_ids: &[String],
+        damage_dice: &str,
+        damage_type: DamageType,
+    ) -> Result<AreaDamage, String> {
+        if area.is_some_and(|area| !area.radius.is_finite() || area.radius <= 0.0) {
+            return Err("Area radius must be positive".to_string());
+        }
+        let tokens = self.map_tokens();
+        let mut targets: Vec<String> = area
+            .map(|area| {
+                tokens
+                    .iter()
+                    .filter(|t| area.contains(&t.position))
+                    .map(|t| t.id.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for id in target_ids {
+            // A group stands for its members still in the fight
+            if let Some(group) = self.adversary_groups.get(id) {
+                let members: Vec<String> = group
+                    .member_ids
+                    .iter()
+                    .filter(|m| !targets.contains(m))
+                    .filter(|m| self.adversaries.get(*m).is_some_and(|a| a.is_active))
+                    .cloned()
+                    .collect();
+                targets.extend(members);
+                continue;
+            }
+            if !tokens.iter().any(|t| &t.id == id) {
+                return Err(format!("Token not found: {}", id));
+            }
+            if !targets.contains(id) {
+                targets.push(id.clone());
+            }
+        }
+        if targets.is_empty() {
+            return Err("No one is in the area".to_string());
+        }
+        let expression = dice::DiceExpression::parse(damage_dice)?;
+        let roll = self.dice.with(|rng| expression.roll(rng));
+
+        let mut results = Vec::new();
+        for target_id in targets {
+            let armor = self.armor_of(&target_id);
+            let defenses = self.defenses_of(&target_id);
+            let damage = resolve_damage(roll.total, armor, damage_type.clone(), &defenses);
+            let Some(applied) = self.apply_damage(&target_id, &damage) else {
+                continue;
+            };
+            results.push(AreaDamageTarget {
+                target_id,
+                target_name: applied.target_name,
+                adjustment: damage.adjustment,
+                adjusted_damage: damage.adjusted_damage,
+                after_armor: damage.after_armor,
+                hp_lost: damage.hp_lost,
+                stress_gained: damage.stress_gained,
+                new_hp: applied.new_hp,
+                new_stress: applied.new_stress,
+                taken_out: applied.taken_out,
+            });
+        }
+
+        let hit = results
+            .iter()
+            .map(|t| format!("{} ({})", t.target_name, t.after_armor))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.add_event(
+            GameEventType::CombatAction,
+            format!("💥 {} deals {} damage", source, roll.total),
+            None,
+            Some(hit),
+        );
+        Ok(AreaDamage {
+            roll,
+            damage_type,
+            targets: results,
+        })
+    }
+
+    /// Replace a character's or adversary's defenses
+    pub fn set_defenses(&mut self, target_id: &str, defenses: Defenses) -> Result<(), String> {
+        let name = if let Some(character) = self
+            .characters
+            .values_mut()
+            .find(|c| c.id.to_string() == target_id)
+        {
+            character.defenses = defenses.clone();
+            character.name.clone()
+        } else if let Some(adversary) = self.adversaries.get_mut(target_id) {
+            adversary.defenses = defenses.clone();
+            adversary.name.clone()
+        } else {
+            return Err(format!("Target not found: {}", target_id));
+        };
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{}'s defenses changed", name),
+            Some(name),
+            Some(defenses.describe()),
+        );
+
+        Ok(())
+    }
+
+    /// Remove an adversary
+    pub fn remove_adversary(&mut self, adversary_id: &str) -> Option<Adversary> {
+        if let Some(adversary) = self.adversaries.remove(adversary_id) {
+            self.modifiers.retain(|_, m| m.target_id != adversary_id);
+            for group in self.adversary_groups.values_mut() {
+                group.member_ids.retain(|id| id != adversary_id);
+            }
+            self.adversary_groups
+                .retain(|_, g| !g.member_ids.is_empty());
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{} removed", adversary.name),
+                None,
+                None,
+            );
+            Some(adversary)
+        } else {
+            None
+        }
+    }
+
+    // ===== Adversary groups =====
+
+    /// Run adversaries as one group, returning it
+    ///
+    /// Adversaries already in a group leave it; a group left with no one in
+    /// it is gone.
+    pub fn create_adversary_group(
+        &mut self,
+        name: &str,
+        adversary_ids: &[String],
+    ) -> Result<AdversaryGroupInfo, String> {
+        let name = check_name(name)?.to_string();
+        let mut member_ids: Vec<String> = Vec::new();
+        for id in adversary_ids {
+            if !self.adversaries.contains_key(id) {
+                return Err(format!("Adversary not found: {}", id));
+            }
+            if !member_ids.contains(id) {
+                member_ids.push(id.clone());
+            }
+        }
+        if member_ids.len() < 2 {
+            return Err("A group needs at least two adversaries".to_string());
+        }
+
+        for group in self.adversary_groups.values_mut() {
+            group.member_ids.retain(|id| !member_ids.contains(id));
+        }
+        self.adversary_groups
+            .retain(|_, g| !g.member_ids.is_empty());
+
+        let group_id = Uuid::new_v4().to_string();
+        self.adversary_groups.insert(
+            group_id.clone(),
+            AdversaryGroup {
+                id: group_id.clone(),
+                name,
+                member_ids,
+                is_active: true,
+            },
+        );
+        let info = self
+            .adversary_group_info(&group_id)
+            .expect("group added above");
+        if let Some(group) = self.adversary_groups.get_mut(&group_id) {
+            group.is_active = info.is_active;
+        }
+
+        let members = info
+            .members
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("👥 {} gathers", info.name),
+            None,
+            Some(members),
+        );
+        Ok(info)
+    }
+
+    /// Split a group up, leaving its adversaries where they are; returns its
+    /// name
+    pub fn disband_adversary_group(&mut self, group_id: &str) -> Result<String, String> {
+        let group = self
+            .adversary_groups
+            .remove(group_id)
+            .ok_or_else(|| format!("Group not found: {}", group_id))?;
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("👥 {} splits up", group.name),
+            None,
+            None,
+        );
+        Ok(group.name)
+    }
+
+    /// A group with its members' HP added up
+    pub fn adversary_group_info(&self, group_id: &str) -> Option<AdversaryGroupInfo> {
+        let group = self.adversary_groups.get(group_id)?;
+        let members = group
+            .member_ids
+            .iter()
+            .filter_map(|id| self.adversaries.get(id))
+            .map(|a| GroupMember {
+                id: a.id.clone(),
+                name: a.name.clone(),
+                hp: a.hp,
+                max_hp: a.max_hp,
+                is_active: a.is_active,
+            })
+            .collect();
+        Some(AdversaryGroupInfo::new(
+            group.id.clone(),
+            group.name.clone(),
+            members,
+        ))
+    }
+
+    /// Every group, by name
+    pub fn adversary_group_list(&self) -> Vec<AdversaryGroupInfo> {
+        let mut groups: Vec<_> = self
+            .adversary_groups
+            .keys()
+            .filter_map(|id| self.adversary_group_info(id))
+            .collect();
+        groups.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        groups
+    }
+
+    /// Move a group in formation: its centre goes to `position` and each
+    /// member keeps its place around it
+    ///
+    /// Every member has to land in the scene, or none of them move. Returns
+    /// where each member ended up.
+    pub fn move_adversary_group(
+        &mut self,
+        group_id: &str,
+        position: Position,
+    ) -> Result<Vec<(String, Position)>, String> {
+        let group = self
+            .adversary_groups
+            .get(group_id)
+            .ok_or_else(|| format!("Group not found: {}", group_id))?;
+        let members: Vec<&Adversary> = group
+            .member_ids
+            .iter()
+            .filter_map(|id| self.adversaries.get(id))
+            .collect();
+        if members.is_empty() {
+            return Err(format!("{} has no one in it", group.name));
+        }
+        let count = members.len() as f32;
+        let centre_x = members.iter().map(|a| a.position.x).sum::<f32>() / count;
+        let centre_y = members.iter().map(|a| a.position.y).sum::<f32>() / count;
+
+        let moves = members
+            .iter()
+            .map(|a| {
+                let target = Position::new(
+                    position.x + a.position.x - centre_x,
+                    position.y + a.position.y - centre_y,
+                );
+                self.layout
+                    .place_token(target, a.token.size)
+                    .map(|placed| (a.id.clone(), placed))
+                    .map_err(|e| format!("{}: {}", a.name, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        for (id, placed) in &moves {
+            if let Some(adversary) = self.adversaries.get_mut(id) {
+                adversary.position = *placed;
+            }
+        }
+        Ok(moves)
+    }
+
+    /// Give a group its activation, on which every standing member acts
+    ///
+    /// In spotlight flow the spotlight goes to the group's first standing
+    /// member. On the Action Tracker the group spends the next token, which
+    /// has to be an adversary token.
+    pub fn activate_adversary_group(
+        &mut self,
+        group_id: &str,
+    ) -> Result<(AdversaryGroupInfo, GroupActivation), String> {
+        let group = self
+            .adversary_group_info(group_id)
+            .ok_or_else(|| format!("Group not found: {}", group_id))?;
+        let standing: Vec<&GroupMember> = group.members.iter().filter(|m| m.is_active).collect();
+        let leader_id = standing
+            .first()
+            .ok_or_else(|| format!("{} has been taken out", group.name))?
+            .id
+            .clone();
+        let mode = self
+            .combat_encounter
+            .as_ref()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?
+            .mode;
+
+        let activation = match mode {
+            CombatMode::Spotlight => {
+                let (previous_id, _) = self.pass_spotlight(Some(&leader_id))?;
+                GroupActivation::Spotlight {
+                    previous_id,
+                    holder_id: leader_id,
+                }
+            }
+            CombatMode::ActionTracker => {
+                if self.get_next_actor() != Some(TokenType::Adversary) {
+                    return Err(
+                        "The next token on the tracker isn't an adversary token".to_string()
+                    );
+                }
+                GroupActivation::Tracker(self.spend_tracker_token()?)
+            }
+        };
+
+        let members = standing
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.add_event(
+            GameEventType::CombatAction,
+            format!("👥 {} acts together", group.name),
+            None,
+            Some(members),
+        );
+        Ok((group, activation))
+    }
+
+    /// Take a group and every adversary in it off the map; returns the
+    /// group's name and the adversaries removed
+    pub fn remove_adversary_group(
+        &mut self,
+        group_id: &str,
+    ) -> Result<(String, Vec<Adversary>), String> {
+        let group = self
+            .adversary_groups
+            .remove(group_id)
+            .ok_or_else(|| format!("Group not found: {}", group_id))?;
+        let removed = group
+            .member_ids
+            .iter()
+            .filter_map(|id| self.remove_adversary(id))
+            .collect();
+        Ok((group.name, removed))
+    }
+
+    /// Catch groups up with their members: a group whose last member was
+    /// taken out goes down, and one with a member back up returns
+    ///
+    /// Returns the groups that changed.
+    pub fn refresh_adversary_groups(&mut self) -> Vec<AdversaryGroupInfo> {
+        let changed: Vec<AdversaryGroupInfo> = self
+            .adversary_group_list()
+            .into_iter()
+            .filter(|info| {
+                self.adversary_groups
+                    .get(&info.id)
+                    .is_some_and(|g| g.is_active != info.is_active)
+            })
+            .collect();
+        for info in &changed {
+            if let Some(group) = self.adversary_groups.get_mut(&info.id) {
+                group.is_active = info.is_active;
+            }
+            let message = if info.is_active {
+                format!("👥 {} is back in the fight", info.name)
+            } else {
+                format!("👥 {} is taken out!", info.name)
+            };
+            self.add_event(
+                GameEventType::CombatAction,
+                message,
+                None,
+                Some(info.summary()),
+            );
+        }
+        changed
+    }
+
+    /// Close the gaps in instance numbering left by removals
+    ///
+    /// Renames the numbered adversaries of one template (or every template)
+    /// to #1..n in spawn order and resets the spawn counter to match.
+    /// Returns the ids of the renamed adversaries.
+    pub fn renumber_adversaries(&mut self, template_id: Option<&str>) -> Vec<String> {
+        let mut by_template: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+        for adversary in self.adversaries.values() {
+            if adversary.instance == 0 || template_id.is_some_and(|t| t != adversary.template) {
+                continue;
+            }
+            by_template
+                .entry(adversary.template.clone())
+                .or_default()
+                .push((adversary.instance, adversary.id.clone()));
+        }
+
+        // Templates with no numbered adversaries left start over from #1
+        match template_id {
+            Some(template) => {
+                self.adversary_spawn_counts.remove(template);
+            }
+            None => self.adversary_spawn_counts.clear(),
+        }
+
+        let mut renamed = Vec::new();
+        for (template, mut instances) in by_template {
+            instances.sort();
+            let base_name = self
+                .find_adversary_template(&template)
+                .map(|t| t.name)
+                .unwrap_or_else(|| template.clone());
+            for (number, (instance, id)) in instances.iter().enumerate() {
+                let number = number + 1;
+                if *instance == number {
+                    continue;
+                }
+                if let Some(adversary) = self.adversaries.get_mut(id) {
+                    adversary.instance = number;
+                    adversary.name = format!("{} #{}", base_name, number);
+                    renamed.push(id.clone());
+                }
+            }
+            self.adversary_spawn_counts
+                .insert(template, instances.len());
+        }
+
+        if !renamed.is_empty() {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("Renumbered {} adversaries", renamed.len()),
+                None,
+                None,
+            );
+        }
+        renamed
+    }
+
+    /// Get all adversaries
+    pub fn get_adversaries(&self) -> Vec<&Adversary> {
+        self.adversaries.values().collect()
+    }
+
+    /// Get active adversaries only
+    pub fn get_active_adversaries(&self) -> Vec<&Adversary> {
+        self.adversaries
+            .values()
+            .filter(|adv| adv.is_active)
+            .collect()
+    }
+
+    /// Roll an adversary's attack against a character
+    ///
+    /// Adversaries roll a d20 plus their attack modifier (and any aura
+    /// bonus) against the target's Evasion. Advantage rolls two d20s and
+    /// keeps the higher, disadvantage the lower; having both cancels out.
+    pub fn adversary_attack(
+        &self,
+        adversary_id: &str,
+        target_id: &Uuid,
+        has_advantage: bool,
+        has_disadvantage: bool,
+    ) -> Result<AdversaryAttackRoll, String> {
+        let adversary = self
+            .adversaries
+            .get(adversary_id)
+            .ok_or_else(|| format!("Adversary not found: {}", adversary_id))?;
+        if !adversary.is_active {
+            return Err(format!("{} has been taken out", adversary.name));
+        }
+        let target = self
+            .characters
+            .get(target_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+        let target_evasion = self
+            .effective_evasion(&target_id.to_string())
+            .unwrap_or(target.evasion);
+
+        let dice = if has_advantage != has_disadvantage {
+            2
+        } else {
+            1
+        };
+        let rolls: Vec<u8> = (0..dice).map(|_| self.dice.die(20)).collect();
+        let die = if has_disadvantage && !has_advantage {
+            rolls.iter().copied().min()
+        } else {
+            rolls.iter().copied().max()
+        }
+        .unwrap_or(1);
+
+        let modifier = adversary
+            .attack_modifier
+            .saturating_add(self.attack_bonus(adversary_id));
+        let total = die as i16 + modifier as i16;
+        let is_critical = die == 20;
+
+        Ok(AdversaryAttackRoll {
+            adversary_name: adversary.name.clone(),
+            target_name: target.name.clone(),
+            rolls,
+            die,
+            modifier,
+            total,
+            target_evasion,
+            hit: is_critical || total >= target_evasion as i16,
+            is_critical,
+            damage_dice: adversary.damage_dice.clone(),
+            damage_type: adversary.damage_type.clone(),
+        })
+    }
+
+    /// Set a character's or adversary's HP, Stress or Hope (the GM's override)
+    ///
+    /// `relative` adds `value` to the current amount instead of replacing it;
+    /// either way the result is kept between 0 and the maximum. An adversary
+    /// at 0 HP and full Stress is taken out, and comes back if either is
+    /// raised. Returns the new amount.
+    pub fn set_resource(
+        &mut self,
+        token_id: &str,
+        resource: ResourceKind,
+        value: i32,
+        relative: bool,
+    ) -> Result<u8, String> {
+        let resolve = |current: u8, maximum: u8| {
+            let target = if relative {
+                current as i32 + value
+            } else {
+                value
+            };
+            target.clamp(0, maximum as i32) as u8
+        };
+
+        let (name, label, amount) = if let Some(character) = Uuid::parse_str(token_id)
+            .ok()
+            .and_then(|id| self.characters.get_mut(&id))
+        {
+            character.sync_resources();
+            let (label, amount) = match resource {
+                ResourceKind::Hp => {
+                    character.hp_current = resolve(character.hp_current, character.hp_max);
+                    ("HP", character.hp_current)
+                }
+                ResourceKind::Stress => {
+                    let max_stress = character.progression.stress_slots();
+                    character.stress_current = resolve(character.stress_current, max_stress);
+                    ("Stress", character.stress_current)
+                }
+                ResourceKind::Hope => {
+                    character.hope_current = resolve(character.hope_current, character.hope_max);
+                    ("Hope", character.hope_current)
+                }
+            };
+            character.restore_resources();
+            (character.name.clone(), label, amount)
+        } else {
+            let adversary = self
+                .adversaries
+                .get_mut(token_id)
+                .ok_or_else(|| format!("Token not found: {}", token_id))?;
+            let (label, amount) = match resource {
+                ResourceKind::Hp => {
+                    adversary.hp = resolve(adversary.hp, adversary.max_hp);
+                    ("HP", adversary.hp)
+                }
+                ResourceKind::Stress => {
+                    adversary.stress = resolve(adversary.stress, adversary.max_stress);
+                    ("Stress", adversary.stress)
+                }
+                ResourceKind::Hope => return Err("Adversaries don't have Hope".to_string()),
+            };
+            adversary.is_active = adversary.hp > 0 || adversary.stress < adversary.max_stress;
+            (adversary.name.clone(), label, amount)
+        };
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("GM set {}'s {} to {}", name, label, amount),
+            Some(name),
+            None,
+        );
+        Ok(amount)
+    }
+
+    /// Clear a character's HP or Stress (or gain Hope preparing) by a rest,
+    /// a Hope feature or an ally's ability, as `protocol::healing` describes
+    ///
+    /// Clearing stops at what's marked, and nothing is rolled or spent if the
+    /// healing isn't allowed.
+    pub fn heal(&mut self, character_id: &Uuid, source: &HealingSource) -> Result<Healing, String> {
+        let in_combat = self.combat_encounter.as_ref().is_some_and(|e| e.is_active);
+        let ally_name = match source {
+            HealingSource::Ally {
+                ally_id,
+                ability,
+                hp,
+                stress,
+            } => {
+                let ally = Uuid::parse_str(ally_id)
+                    .ok()
+                    .and_then(|id| self.characters.get(&id))
+                    .ok_or_else(|| format!("Character not found: {}", ally_id))?;
+                if ally.id == *character_id {
+                    return Err("An ally's ability has to heal someone else".to_string());
+                }
+                if ally.hp_current == 0 {
+                    return Err(format!("{} has been taken out", ally.name));
+                }
+                if ability.trim().is_empty() {
+                    return Err("Name the ability that heals".to_string());
+                }
+                if *hp == 0 && *stress == 0 {
+                    return Err("The ability has to clear some HP or Stress".to_string());
+                }
+                ally.name.clone()
+            }
+            _ => String::new(),
+        };
+
+        let character = self
+            .characters
+            .get_mut(character_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+        character.sync_resources();
+        let name = character.name.clone();
+        let hp_marked = character.hp_max - character.hp_current;
+        let stress_marked = character.stress_current;
+        let tier = leveling::tier_for_level(character.level);
+
+        let (label, hp, stress, hope_change, roll) = match source {
+            HealingSource::Downtime {
+                rest,
+                downtime_move,
+            } => {
+                if in_combat {
+                    return Err("Can't rest during combat".to_string());
+                }
+                let rest_name = match rest {
+                    RestKind::Short => "short rest",
+                    RestKind::Long => "long rest",
+                };
+                let label = format!("{} ({})", downtime_move.name(*rest), rest_name);
+                match downtime_move {
+                    DowntimeMove::TendToWounds if hp_marked == 0 => {
+                        return Err(format!("{} has no HP marked", name));
+                    }
+                    DowntimeMove::ClearStress if stress_marked == 0 => {
+                        return Err(format!("{} has no Stress marked", name));
+                    }
+                    DowntimeMove::Prepare if character.hope_current >= character.hope_max => {
+                        return Err(format!("{} is already full of Hope", name));
+                    }
+                    _ => {}
+                }
+
+                let roll = (*rest == RestKind::Short && *downtime_move != DowntimeMove::Prepare)
+                    .then(|| self.dice.die(SHORT_REST_DIE));
+                let amount = roll.map_or(u8::MAX, |die| die + tier);
+                match downtime_move {
+                    DowntimeMove::TendToWounds => (label, amount, 0, 0, roll),
+                    DowntimeMove::ClearStress => (label, 0, amount, 0, roll),
+                    DowntimeMove::Prepare => (label, 0, 0, 1, None),
+                }
+            }
+            HealingSource::Hope { hope, hp } => {
+                if *hope == 0 || *hp == 0 {
+                    return Err("Spend at least one Hope to clear at least one HP".to_string());
+                }
+                if character.hope_current < *hope {
+                    return Err(format!("{} has only {} Hope", name, character.hope_current));
+                }
+                if hp_marked == 0 {
+                    return Err(format!("{} has no HP marked", name));
+                }
+                ("Hope feature".to_string(), *hp, 0, -(*hope as i8), None)
+            }
+            HealingSource::Ally {
+                ability,
+                hp,
+                stress,
+                ..
+            } => {
+                if hp_marked == 0 && stress_marked == 0 {
+                    return Err(format!("{} has no HP or Stress marked", name));
+                }
+                let label = format!("{}'s {}", ally_name, ability.trim());
+                (label, *hp, *stress, 0, None)
+            }
+        };
+
+        let hp = hp.min(hp_marked);
+        let stress = stress.min(stress_marked);
+        character.hp_current += hp;
+        character.stress_current -= stress;
+        character.hope_current = (character.hope_current as i16 + hope_change as i16)
+            .clamp(0, character.hope_max as i16) as u8;
+        character.restore_resources();
+
+        // One entry for each resource, naming the source
+        let details = match roll {
+            Some(die) => format!("{}: rolled {} + tier {}", label, die, tier),
+            None => label.clone(),
+        };
+        let mut changes = Vec::new();
+        if hp > 0 {
+            changes.push(format!("cleared {} HP", hp));
+        }
+        if stress > 0 {
+            changes.push(format!("cleared {} Stress", stress));
+        }
+        match hope_change {
+            1.. => changes.push(format!("gained {} Hope", hope_change)),
+            ..=-1 => changes.push(format!("spent {} Hope", -hope_change)),
+            0 => {}
+        }
+        for change in changes {
+            self.add_event(
+                GameEventType::ResourceUpdate,
+                format!("🩹 {} {}", name, change),
+                Some(name.clone()),
+                Some(details.clone()),
+            );
+        }
+        // Resting ends what lasts until the next rest
+        if matches!(source, HealingSource::Downtime { .. }) {
+            self.expire_modifiers(ModifierDuration::UntilRest, Some(&character_id.to_string()));
+        }
+
+        Ok(Healing {
+            character_id: character_id.to_string(),
+            character_name: name,
+            source: label,
+            hp_cleared: hp,
+            stress_cleared: stress,
+            hope_change,
+            roll: roll.map(|value| DieResult {
+                sides: SHORT_REST_DIE as u16,
+                value: value as u16,
+                kept: true,
+                negative: false,
+                explosions: 0,
+            }),
+        })
+    }
+
+    /// Update adversary HP after damage
+    pub fn update_adversary_hp(&mut self, adversary_id: &str, hp_loss: u8, stress_gain: u8) -> Result<bool, String> {
+        let adversary = self
+            .adversaries
+            .get_mut(adversary_id)
+            .ok_or_else(|| format!("Adversary not found: {}", adversary_id))?;
+
+        let taken_out = adversary.take_damage(hp_loss, stress_gain);
+        let adversary_name = adversary.name.clone(); // Clone before borrowing self again
+
+        if taken_out {
+            self.add_event(
+                GameEventType::CombatAction,
+                format!("{} taken out!", adversary_name),
+                None,
+                None,
+            );
+        }
+
+        Ok(taken_out)
+    }
+
+    // ===== Environments =====
+
+    /// Set the scene with an environment from the compendium
+    pub fn spawn_environment(&mut self, template_id: &str) -> Result<Environment, String> {
+        let template = EnvironmentTemplate::get_template(template_id)
+            .ok_or_else(|| format!("Environment not found: {}", template_id))?;
+
+        let environment = Environment::from_template(template);
+        self.add_scene_event(
+            environment.template.name.clone(),
+            Some(format!(
+                "Tier {} {}, Difficulty {}",
+                environment.template.tier,
+                environment.template.kind.name(),
+                environment.template.difficulty
+            )),
+        );
+
+        self.environments
+            .insert(environment.id.clone(), environment.clone());
+        Ok(environment)
+    }
+
+    /// Remove an environment
+    pub fn remove_environment(&mut self, environment_id: &str) -> Option<Environment> {
+        let environment = self.environments.remove(environment_id)?;
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} removed", environment.template.name),
+            None,
+            None,
+        );
+        Some(environment)
+    }
+
+    /// Activate an environment's action or reaction feature, spending its Fear
+    pub fn activate_environment_feature(
+        &mut self,
+        environment_id: &str,
+        feature_name: &str,
+    ) -> Result<EnvironmentFeature, String> {
+        let environment = self
+            .environments
+            .get(environment_id)
+            .ok_or_else(|| format!("Environment not found: {}", environment_id))?;
+        let feature = environment.feature(feature_name).cloned().ok_or_else(|| {
+            format!(
+                "{} has no feature \"{}\"",
+                environment.template.name, feature_name
+            )
+        })?;
+
+        if feature.kind == FeatureKind::Passive {
+            return Err(format!("{} is passive and always in effect", feature.name));
+        }
+        if self.fear_pool < feature.fear_cost {
+            return Err(format!(
+                "{} costs {} Fear (have {})",
+                feature.name, feature.fear_cost, self.fear_pool
+            ));
+        }
+        self.fear_pool -= feature.fear_cost;
+
+        let message = format!("{}: {}", environment.template.name, feature.name);
+        let details = if feature.fear_cost > 0 {
+            format!("{} (spent {} Fear)", feature.description, feature.fear_cost)
+        } else {
+            feature.description.clone()
+        };
+        self.add_event(GameEventType::SystemMessage, message, None, Some(details));
+
+        Ok(feature)
+    }
+
+    // ===== Auras =====
+
+    /// Characters and active adversaries, with their side
+    fn map_tokens(&self) -> Vec<MapToken> {
+        let characters = self.characters.values().map(|c| MapToken {
+            id: c.id.to_string(),
+            name: c.name.clone(),
+            position: c.position,
+            is_adversary: false,
+        });
+        let adversaries = self
+            .adversaries
+            .values()
+            .filter(|a| a.is_active)
+            .map(|a| MapToken {
+                id: a.id.clone(),
+                name: a.name.clone(),
+                position: a.position,
+                is_adversary: true,
+            });
+        characters.chain(adversaries).collect()
+    }
+
+    /// Attach an aura to a character or adversary
+    ///
+    /// Returns the new aura's id; call `refresh_auras` to fill its members.
+    pub fn attach_aura(&mut self, owner_id: &str, aura: Aura) -> Result<String, String> {
+        aura.validate()?;
+
+        let owner_name = self
+            .map_tokens()
+            .into_iter()
+            .find(|t| t.id == owner_id)
+            .map(|t| t.name)
+            .ok_or_else(|| format!("Token not found: {}", owner_id))?;
+
+        let aura_id = Uuid::new_v4().to_string();
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} projects {}", owner_name, aura.name),
+            Some(owner_name),
+            Some(format!("{} within {}", aura.effect.describe(), aura.radius)),
+        );
+        self.auras.insert(
+            aura_id.clone(),
+            AttachedAura {
+                id: aura_id.clone(),
+                owner_id: owner_id.to_string(),
+                aura,
+                members: HashSet::new(),
+            },
+        );
+
+        Ok(aura_id)
+    }
+
+    /// Remove an aura; its members lose the effect immediately
+    pub fn detach_aura(&mut self, aura_id: &str) -> Result<AttachedAura, String> {
+        let attached = self
+            .auras
+            .remove(aura_id)
+            .ok_or_else(|| format!("Aura not found: {}", aura_id))?;
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} fades", attached.aura.name),
+            None,
+            None,
+        );
+
+        Ok(attached)
+    }
+
+    /// Re-evaluate which tokens are inside each aura after positions change
+    ///
+    /// Auras whose owner has left the map are removed.
+    pub fn refresh_auras(&mut self) -> Vec<AuraChange> {
+        let tokens = self.map_tokens();
+        let mut changes = Vec::new();
+
+        let orphaned: Vec<String> = self
+            .auras
+            .values()
+            .filter(|a| !tokens.iter().any(|t| t.id == a.owner_id))
+            .map(|a| a.id.clone())
+            .collect();
+        for aura_id in orphaned {
+            if let Some(attached) = self.auras.remove(&aura_id) {
+                changes.push(AuraChange::Removed {
+                    aura_id,
+                    aura_name: attached.aura.name,
+                });
+            }
+        }
+
+        for attached in self.auras.values_mut() {
+            let Some(owner) = tokens.iter().find(|t| t.id == attached.owner_id) else {
+                continue;
+            };
+
+            let inside: HashSet<String> = tokens
+                .iter()
+                .filter(|t| t.id != owner.id || attached.aura.include_self)
+                .filter(|t| {
+                    attached
+                        .aura
+                        .affects_side(t.is_adversary == owner.is_adversary)
+                })
+                .filter(|t| owner.position.distance_to(&t.position) <= attached.aura.radius)
+                .map(|t| t.id.clone())
+                .collect();
+
+            for token in &tokens {
+                let was_inside = attached.members.contains(&token.id);
+                let is_inside = inside.contains(&token.id);
+                let (aura_id, aura_name, token_id, token_name) = (
+                    attached.id.clone(),
+                    attached.aura.name.clone(),
+                    token.id.clone(),
+                    token.name.clone(),
+                );
+                if is_inside && !was_inside {
+                    changes.push(AuraChange::Entered {
+                        aura_id,
+                        aura_name,
+                        token_id,
+                        token_name,
+                    });
+                } else if was_inside && !is_inside {
+                    changes.push(AuraChange::Left {
+                        aura_id,
+                        aura_name,
+                        token_id,
+                        token_name,
+                    });
+                }
+            }
+
+            attached.members = inside;
+        }
+
+        for change in &changes {
+            match change {
+                AuraChange::Entered {
+                    aura_name,
+                    token_name,
+                    ..
+                } => self.add_event(
+                    GameEventType::SystemMessage,
+                    format!("{} entered {}", token_name, aura_name),
+                    Some(token_name.clone()),
+                    None,
+                ),
+                AuraChange::Left {
+                    aura_name,
+                    token_name,
+                    ..
+                } => self.add_event(
+                    GameEventType::SystemMessage,
+                    format!("{} left {}", token_name, aura_name),
+                    Some(token_name.clone()),
+                    None,
+                ),
+                AuraChange::Removed { aura_name, .. } => self.add_event(
+                    GameEventType::SystemMessage,
+                    format!("{} fades", aura_name),
+                    None,
+                    None,
+                ),
+            }
+        }
+
+        changes
+    }
+
+    /// Combined effect of every aura a token is inside
+    pub fn aura_effect_on(&self, token_id: &str) -> AuraEffect {
+        self.auras
+            .values()
+            .filter(|a| a.members.contains(token_id))
+            .fold(AuraEffect::default(), |total, a| {
+                total.combine(a.aura.effect)
+            })
+    }
+
+    /// Evasion of a character or adversary including aura bonuses and
+    /// modifiers
+    pub fn effective_evasion(&self, token_id: &str) -> Option<i32> {
+        let base = self
+            .characters
+            .values()
+            .find(|c| c.id.to_string() == token_id)
+            .map(|c| c.evasion)
+            .or_else(|| self.adversaries.get(token_id).map(|a| a.evasion as i32))?;
+        Some(
+            base + self.aura_effect_on(token_id).evasion as i32
+                + self.modifier_total(token_id, ModifierStat::Evasion) as i32,
+        )
+    }
+
+    /// Everything a token adds to its attack rolls: auras and modifiers
+    pub fn attack_bonus(&self, token_id: &str) -> i8 {
+        self.aura_effect_on(token_id)
+            .attack
+            .saturating_add(self.modifier_total(token_id, ModifierStat::Roll))
+            .saturating_add(self.modifier_total(token_id, ModifierStat::Attack))
+    }
+
+    // ===== Modifiers =====
+
+    /// Put a buff or debuff on a character or adversary
+    pub fn add_modifier(
+        &mut self,
+        target_id: &str,
+        modifier: Modifier,
+    ) -> Result<TimedModifier, String> {
+        modifier.validate()?;
+        let target_name = match Uuid::parse_str(target_id)
+            .ok()
+            .and_then(|id| self.characters.get(&id))
+        {
+            Some(character) => character.name.clone(),
+            None => {
+                let adversary = self
+                    .adversaries
+                    .get(target_id)
+                    .ok_or_else(|| format!("Token not found: {}", target_id))?;
+                if modifier.duration == ModifierDuration::UntilRest {
+                    return Err("Adversaries don't rest; make it last the scene".to_string());
+                }
+                adversary.name.clone()
+            }
+        };
+
+        let timed = TimedModifier {
+            id: Uuid::new_v4().to_string(),
+            target_id: target_id.to_string(),
+            target_name: target_name.clone(),
+            modifier: Modifier {
+                name: modifier.name.trim().to_string(),
+                ..modifier
+            },
+        };
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("✨ {} gains {}", target_name, timed.modifier.name),
+            Some(target_name),
+            Some(timed.modifier.describe()),
+        );
+        self.modifiers.insert(timed.id.clone(), timed.clone());
+        Ok(timed)
+    }
+
+    /// Take a buff or debuff off before it runs out
+    pub fn remove_modifier(&mut self, modifier_id: &str) -> Result<TimedModifier, String> {
+        let timed = self
+            .modifiers
+            .remove(modifier_id)
+            .ok_or_else(|| format!("Modifier not found: {}", modifier_id))?;
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} loses {}", timed.target_name, timed.modifier.name),
+            Some(timed.target_name.clone()),
+            None,
+        );
+        Ok(timed)
+    }
+
+    /// Every modifier on the table, by token then name
+    pub fn modifier_list(&self) -> Vec<TimedModifier> {
+        let mut modifiers: Vec<_> = self.modifiers.values().cloned().collect();
+        modifiers.sort_by(|a, b| {
+            (&a.target_name, &a.modifier.name).cmp(&(&b.target_name, &b.modifier.name))
+        });
+        modifiers
+    }
+
+    /// What a token's modifiers add to one stat
+    pub fn modifier_total(&self, token_id: &str, stat: ModifierStat) -> i8 {
+        self.modifiers
+            .values()
+            .filter(|m| m.target_id == token_id && m.modifier.stat == stat)
+            .fold(0i8, |total, m| total.saturating_add(m.modifier.value))
+    }
+
+    /// End the modifiers of one duration, on one token or everyone
+    fn expire_modifiers(&mut self, duration: ModifierDuration, target_id: Option<&str>) {
+        let expired: Vec<TimedModifier> = self
+            .modifiers
+            .values()
+            .filter(|m| m.modifier.duration == duration)
+            .filter(|m| target_id.is_none_or(|id| m.target_id == id))
+            .cloned()
+            .collect();
+        for timed in expired {
+            self.modifiers.remove(&timed.id);
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{}'s {} wears off", timed.target_name, timed.modifier.name),
+                Some(timed.target_name),
+                None,
+            );
+        }
+    }
+
+    // ===== Range =====
+
+    /// Where a character or active adversary is on the map
+    fn token_position(&self, token_id: &str) -> Option<Position> {
+        self.map_tokens()
+            .into_iter()
+            .find(|t| t.id == token_id)
+            .map(|t| t.position)
+    }
+
+    /// Put a character's or adversary's token somewhere, ignoring movement
+    /// rules (the GM's move)
+    ///
+    /// The position snaps to the grid for the token's size and must be in
+    /// the scene. Returns where the token ended up.
+    pub fn move_token(&mut self, token_id: &str, position: Position) -> Result<Position, String> {
+        if let Some(char_id) = Uuid::parse_str(token_id)
+            .ok()
+            .filter(|id| self.characters.contains_key(id))
+        {
+            return self.update_character_position(&char_id, position);
+        }
+        let adversary = self
+            .adversaries
+            .get_mut(token_id)
+            .ok_or_else(|| format!("Token not found: {}", token_id))?;
+        let position = self.layout.place_token(position, adversary.token.size)?;
+        adversary.position = position;
+        Ok(position)
+    }
+
+    /// Rename a character's or adversary's token, returning the new name
+    pub fn rename_token(&mut self, token_id: &str, name: &str) -> Result<String, String> {
+        let name = check_name(name)?;
+        let current = match Uuid::parse_str(token_id)
+            .ok()
+            .and_then(|id| self.characters.get_mut(&id))
+        {
+            Some(character) => &mut character.name,
+            None => {
+                &mut self
+                    .adversaries
+                    .get_mut(token_id)
+                    .ok_or_else(|| format!("Token not found: {}", token_id))?
+                    .name
+            }
+        };
+        let previous = std::mem::replace(current, name.to_string());
+        if previous != name {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{} is now {}", previous, name),
+                None,
+                None,
+            );
+        }
+        Ok(name.to_string())
+    }
+
+    /// Turn, resize or re-note a character's or adversary's token
+    ///
+    /// Returns the details as stored, with the rotation wrapped into 0..360.
+    /// A resized token settles on the grid the next time it moves.
+    pub fn set_token_details(
+        &mut self,
+        token_id: &str,
+        details: TokenDetails,
+    ) -> Result<TokenDetails, String> {
+        let details = details.normalized()?;
+        let token = match Uuid::parse_str(token_id)
+            .ok()
+            .and_then(|id| self.characters.get_mut(&id))
+        {
+            Some(character) => &mut character.token,
+            None => {
+                &mut self
+                    .adversaries
+                    .get_mut(token_id)
+                    .ok_or_else(|| format!("Token not found: {}", token_id))?
+                    .token
+            }
+        };
+        *token = details.clone();
+        Ok(details)
+    }
+
+    /// Distance in feet between two tokens, and the range band it falls in
+    /// (`None` past Very Far)
+    pub fn measure_range(
+        &self,
+        from_id: &str,
+        to_id: &str,
+    ) -> Result<(f32, Option<WeaponRange>), String> {
+        let from = self
+            .token_position(from_id)
+            .ok_or_else(|| format!("Token not found: {}", from_id))?;
+        let to = self
+            .token_position(to_id)
+            .ok_or_else(|| format!("Token not found: {}", to_id))?;
+        Ok((
+            self.layout.feet_between(&from, &to),
+            self.layout.range_between(&from, &to),
+        ))
+    }
+
+    /// Check a character's equipped weapon reaches their target
+    ///
+    /// Adversaries and unarmed characters aren't checked.
+    pub fn check_weapon_range(&self, attacker_id: &str, target_id: &str) -> Result<(), String> {
+        let Some((character, weapon)) = self
+            .characters
+            .values()
+            .find(|c| c.id.to_string() == attacker_id)
+            .and_then(|c| c.equipment.weapon.as_ref().map(|w| (c, w)))
+        else {
+            return Ok(());
+        };
+        let (_, band) = self.measure_range(attacker_id, target_id)?;
+        match band {
+            Some(band) if weapon.range.reaches(band) => Ok(()),
+            _ => Err(format!(
+                "{} is out of range of {}'s {} ({} range, target is {})",
+                self.combatant_name(target_id)
+                    .unwrap_or_else(|| "The target".to_string()),
+                character.name,
+                weapon.name,
+                weapon.range,
+                band.map_or("beyond Very Far".to_string(), |band| band.to_string())
+            )),
+        }
+    }
+
+    // ===== Hazards =====
+
+    /// Place a hazard on the scene; it starts active with a full countdown
+    pub fn add_hazard(&mut self, hazard: Hazard) -> Result<String, String> {
+        hazard.validate()?;
+
+        let hazard_id = Uuid::new_v4().to_string();
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Hazard: {}", hazard.name),
+            None,
+            Some(hazard.description.clone()).filter(|d| !d.is_empty()),
+        );
+        self.hazards.insert(
+            hazard_id.clone(),
+            SceneHazard {
+                id: hazard_id.clone(),
+                ticks_remaining: hazard.countdown_length(),
+                hazard,
+                active: true,
+            },
+        );
+
+        Ok(hazard_id)
+    }
+
+    /// Remove a hazard from the scene
+    pub fn remove_hazard(&mut self, hazard_id: &str) -> Result<SceneHazard, String> {
+        let removed = self
+            .hazards
+            .remove(hazard_id)
+            .ok_or_else(|| format!("Hazard not found: {}", hazard_id))?;
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} cleared", removed.hazard.name),
+            None,
+            None,
+        );
+
+        Ok(removed)
+    }
+
+    /// Switch a hazard on or off; switching on restarts its countdown
+    pub fn set_hazard_active(&mut self, hazard_id: &str, active: bool) -> Result<(), String> {
+        let scene = self
+            .hazards
+            .get_mut(hazard_id)
+            .ok_or_else(|| format!("Hazard not found: {}", hazard_id))?;
+
+        if scene.active == active {
+            return Ok(());
+        }
+        scene.active = active;
+        if active {
+            scene.ticks_remaining = scene.hazard.countdown_length();
+        }
+
+        let message = format!(
+            "{} {}",
+            scene.hazard.name,
+            if active { "activated" } else { "deactivated" }
+        );
+        self.add_event(GameEventType::SystemMessage, message, None, None);
+
+        Ok(())
+    }
+
+    /// Advance the combat round and tick every active hazard
+    ///
+    /// Returns the new round and the hazards that triggered.
+    pub fn advance_round(&mut self) -> Result<(u32, Vec<HazardTrigger>), String> {
+        let encounter = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        encounter.round += 1;
+        encounter.moved_feet.clear();
+        let round = encounter.round;
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Round {}", round),
+            None,
+            None,
+        );
+
+        let mut active: Vec<String> = self
+            .hazards
+            .values()
+            .filter(|h| h.active)
+            .map(|h| h.id.clone())
+            .collect();
+        active.sort();
+
+        let triggers = active
+            .iter()
+            .filter_map(|hazard_id| self.tick_countdown(hazard_id))
+            .collect();
+
+        Ok((round, triggers))
+    }
+
+    /// Close out the current round: the Action Tracker refills with fresh
+    /// tokens, and the next round starts as `advance_round` starts one
+    ///
+    /// Returns what happened in the round that ended, the new round and the
+    /// hazards that triggered.
+    pub fn end_round(&mut self) -> Result<(RoundHistory, u32, Vec<HazardTrigger>), String> {
+        let encounter = self
+            .combat_encounter
+            .as_mut()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        let ended = encounter.current_round();
+        encounter.action_tracker = ActionTracker::new();
+
+        let acted = ended.who_acted();
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("Round {} ends", ended.round),
+            None,
+            (!acted.is_empty()).then(|| format!("Acted: {}", acted.join(", "))),
+        );
+        let (round, triggers) = self.advance_round()?;
+        Ok((ended, round, triggers))
+    }
+
+    /// Every round of the running fight so far
+    pub fn round_history(&self) -> Result<Vec<RoundHistory>, String> {
+        let encounter = self
+            .combat_encounter
+            .as_ref()
+            .filter(|e| e.is_active)
+            .ok_or("No active combat")?;
+        Ok(encounter.history.clone())
+    }
+
+    /// Tick one hazard's countdown by hand (e.g. when the GM spends a move)
+    pub fn tick_hazard(&mut self, hazard_id: &str) -> Result<Option<HazardTrigger>, String> {
+        let scene = self
+            .hazards
+            .get(hazard_id)
+            .ok_or_else(|| format!("Hazard not found: {}", hazard_id))?;
+        if !scene.active {
+            return Err(format!("{} is not active", scene.hazard.name));
+        }
+
+        Ok(self.tick_countdown(hazard_id))
+    }
+
+    /// Count a hazard down, triggering it and restarting the countdown at zero
+    fn tick_countdown(&mut self, hazard_id: &str) -> Option<HazardTrigger> {
+        let scene = self.hazards.get_mut(hazard_id)?;
+        scene.ticks_remaining = scene.ticks_remaining.saturating_sub(1);
+        if scene.ticks_remaining > 0 {
+            return None;
+        }
+        scene.ticks_remaining = scene.hazard.countdown_length();
+
+        let hazard = scene.hazard.clone();
+        let targets: Vec<(String, String)> = self
+            .map_tokens()
+            .into_iter()
+            .filter(|t| hazard.area.contains(&t.position))
+            .map(|t| (t.id, t.name))
+            .collect();
+
+        let caught = if targets.is_empty() {
+            "No one in the area".to_string()
+        } else {
+            targets
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} triggered", hazard.name),
+            None,
+            Some(caught),
+        );
+
+        Some(HazardTrigger {
+            hazard_id: hazard_id.to_string(),
+            name: hazard.name,
+            outcome: hazard.outcome,
+            targets,
+        })
+    }
+
+    /// Open a reaction roll request for the characters caught by a hazard
+    ///
+    /// Returns `None` for damage hazards or when no character is in the area.
+    pub fn hazard_roll_request(&mut self, trigger: &HazardTrigger) -> Option<PendingRollRequest> {
+        let HazardOutcome::Roll {
+            attribute,
+            difficulty,
+        } = &trigger.outcome
+        else {
+            return None;
+        };
+
+        let target_character_ids: Vec<Uuid> = trigger
+            .targets
+            .iter()
+            .filter_map(|(id, _)| Uuid::parse_str(id).ok())
+            .filter(|id| self.characters.contains_key(id))
+            .collect();
+        if target_character_ids.is_empty() {
+            return None;
+        }
+
+        let request = PendingRollRequest {
+            id: Uuid::new_v4().to_string(),
+            target_character_ids,
+            roll_type: RollType::Reaction,
+            attribute: Some(attribute.clone()),
+            difficulty: *difficulty,
+            context: trigger.name.clone(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: self.combat_encounter.as_ref().is_some_and(|e| e.is_active),
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+        self.pending_roll_requests
+            .insert(request.id.clone(), request.clone());
+
+        Some(request)
+    }
+
+    // ===== Inventory Management =====
+
+    /// Add an item to a character's inventory
+    pub fn add_item(
+        &mut self,
+        char_id: &Uuid,
+        name: String,
+        description: String,
+        quantity: u32,
+    ) -> Result<Item, String> {
+        if quantity == 0 {
+            return Err("Quantity must be at least 1".to_string());
+        }
+
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let character_name = character.name.clone();
+        let item = character
+            .inventory
+            .add_item(name.clone(), description, quantity);
+
+        self.add_event(
+            GameEventType::InventoryUpdate,
+            format!("{} gained {} x{}", character_name, name, quantity),
+            Some(character_name),
+            None,
+        );
+
+        Ok(item)
+    }
+
+    /// Remove an item from a character's inventory
+    pub fn remove_item(
+        &mut self,
+        char_id: &Uuid,
+        item_id: &str,
+        quantity: u32,
+    ) -> Result<Item, String> {
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let character_name = character.name.clone();
+        let removed = character.inventory.remove_item(item_id, quantity)?;
+
+        self.add_event(
+            GameEventType::InventoryUpdate,
+            format!(
+                "{} lost {} x{}",
+                character_name, removed.name, removed.quantity
+            ),
+            Some(character_name),
+            None,
+        );
+
+        Ok(removed)
+    }
+
+    /// Move an item from one character's inventory to another's
+    pub fn transfer_item(
+        &mut self,
+        from_id: &Uuid,
+        to_id: &Uuid,
+        item_id: &str,
+        quantity: u32,
+    ) -> Result<Item, String> {
+        if from_id == to_id {
+            return Err("Cannot transfer an item to the same character".to_string());
+        }
+
+        let to_name = self
+            .characters
+            .get(to_id)
+            .map(|c| c.name.clone())
+            .ok_or_else(|| "Target character not found".to_string())?;
+
+        let from = self
+            .characters
+            .get_mut(from_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let from_name = from.name.clone();
+        let item = from.inventory.remove_item(item_id, quantity)?;
+
+        if let Some(to) = self.characters.get_mut(to_id) {
+            to.inventory
+                .add_item(item.name.clone(), item.description.clone(), item.quantity);
+        }
+
+        self.add_event(
+            GameEventType::InventoryUpdate,
+            format!(
+                "{} gave {} x{} to {}",
+                from_name, item.name, item.quantity, to_name
+            ),
+            Some(from_name),
+            None,
+        );
+
+        Ok(item)
+    }
+
+    /// Equip a weapon on a character (None unequips)
+    pub fn equip_weapon(&mut self, char_id: &Uuid, weapon: Option<Weapon>) -> Result<(), String> {
+        if let Some(ref w) = weapon {
+            w.validate()?;
+        }
+
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let character_name = character.name.clone();
+        let message = match &weapon {
+            Some(w) => format!("{} equipped {}", character_name, w.name),
+            None => format!("{} put away their weapon", character_name),
+        };
+        let details = weapon
+            .as_ref()
+            .map(|w| format!("Trait: {}, Damage: {}", w.trait_name, w.damage_dice));
+        character.equipment.weapon = weapon;
+
+        self.add_event(
+            GameEventType::InventoryUpdate,
+            message,
+            Some(character_name),
+            details,
+        );
+
+        Ok(())
+    }
+
+    /// Equip armor on a character (None unequips)
+    pub fn equip_armor(&mut self, char_id: &Uuid, armor: Option<Armor>) -> Result<(), String> {
+        if let Some(ref a) = armor {
+            a.validate()?;
+        }
+
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let character_name = character.name.clone();
+        let message = match &armor {
+            Some(a) => format!("{} donned {}", character_name, a.name),
+            None => format!("{} removed their armor", character_name),
+        };
+        let details = armor.as_ref().map(|a| {
+            format!(
+                "Armor: {}, Thresholds: {}/{}",
+                a.base_score, a.major_threshold, a.severe_threshold
+            )
+        });
+        character.equipment.armor = armor;
+
+        self.add_event(
+            GameEventType::InventoryUpdate,
+            message,
+            Some(character_name),
+            details,
+        );
+
+        Ok(())
+    }
+
+    /// Award gold (in handfuls) to a character
+    pub fn award_gold(&mut self, char_id: &Uuid, handfuls: u32) -> Result<Gold, String> {
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        character.inventory.gold.add_handfuls(handfuls);
+        let gold = character.inventory.gold;
+        let character_name = character.name.clone();
+
+        self.add_event(
+            GameEventType::InventoryUpdate,
+            format!("{} received {} handfuls of gold", character_name, handfuls),
+            Some(character_name),
+            Some(format!(
+                "Gold: {} handfuls, {} bags, {} chests",
+                gold.handfuls, gold.bags, gold.chests
+            )),
+        );
+
+        Ok(gold)
+    }
+
+    /// GM marks a level up, returning the new level and its advancement options
+    pub fn mark_level_up(
+        &mut self,
+        char_id: &Uuid,
+    ) -> Result<(u8, Vec<AdvancementOption>), String> {
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        if character.level >= leveling::MAX_LEVEL {
+            return Err(format!(
+                "{} is already at the maximum level",
+                character.name
+            ));
+        }
+        if character.progression.pending_level_up {
+            return Err(format!("{} already has a pending level up", character.name));
+        }
+
+        character.progression.pending_level_up = true;
+        let new_level = character.level + 1;
+        let options = character.progression.options_for(new_level);
+        let character_name = character.name.clone();
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} can level up to level {}", character_name, new_level),
+            Some(character_name),
+            Some(format!("Tier {}", leveling::tier_for_level(new_level))),
+        );
+
+        Ok((new_level, options))
+    }
+
+    /// Apply a player's level-up choices
+    pub fn apply_level_up(
+        &mut self,
+        char_id: &Uuid,
+        advancements: Vec<Advancement>,
+        new_experience: Option<String>,
+    ) -> Result<u8, String> {
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let summary = advancements
+            .iter()
+            .map(|a| a.kind().description())
+            .collect::<Vec<_>>()
+            .join("; ");
+        let level = character.apply_level_up(advancements, new_experience)?;
+        let character_name = character.name.clone();
+
+        // Subclass cards unlocked by reaching this level
+        let unlocked: Vec<String> = character
+            .subclass
+            .iter()
+            .flat_map(|s| {
+                s.cards
+                    .iter()
+                    .filter(|card| card.unlock_level() == level)
+                    .map(move |card| format!("{} {}", s.name, card.name()))
+            })
+            .collect();
+
+        self.add_event(
+            GameEventType::SystemMessage,
+            format!("{} reached level {}", character_name, level),
+            Some(character_name.clone()),
+            Some(summary),
+        );
+
+        for card in unlocked {
+            self.add_event(
+                GameEventType::SystemMessage,
+                format!("{} unlocked the {} card", character_name, card),
+                Some(character_name.clone()),
+                None,
+            );
+        }
+
+        Ok(level)
+    }
+
+    /// Add a new Experience to a character
+    pub fn add_experience(
+        &mut self,
+        char_id: &Uuid,
+        name: String,
+        modifier: Option<i8>,
+    ) -> Result<Experience, String> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Experience name cannot be empty".to_string());
+        }
+
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        if character.get_experience(&name).is_some() {
+            return Err(format!(
+                "{} already has the Experience {}",
+                character.name, name
+            ));
+        }
+
+        let experience = Experience::new(name, modifier.unwrap_or(DEFAULT_EXPERIENCE_MODIFIER));
+        character.experiences.push(experience.clone());
+        let character_name = character.name.clone();
+
+        self.add_event(
+            GameEventType::ResourceUpdate,
+            format!(
+                "{} gained the Experience {}",
+                character_name, experience.name
+            ),
+            Some(character_name),
+            Some(format!("Modifier: {:+}", experience.modifier)),
+        );
+
+        Ok(experience)
+    }
+
+    /// Rename an Experience and/or change its modifier
+    pub fn edit_experience(
+        &mut self,
+        char_id: &Uuid,
+        name: &str,
+        new_name: Option<String>,
+        modifier: Option<i8>,
+    ) -> Result<Experience, String> {
+        let character = self
+            .characters
+            .get_mut(char_id)
+            .ok_or_else(|| "Character not found".to_string())?;
+
+        let new_name = new_name.map(|n| n.trim().to_string());
+        if let Some(ref n) = new_name {
+            if n.is_empty() {
+                return Err("Experience name cannot be empty".to_string());
+            }
+            if !n.eq_ignore_ascii_case(name.trim()) && character.get_experience(n).is_some() {
+                return Err(format!(
+                    "{} already has the Experience {}",
+                    character.name, n
+                ));
+            }
+        }
+
+        let experience = character
+            .experiences
+            .iter_mut()
+            .find(|e| e.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown Experience: {}", name))?;
+
+        if let Some(n) = new_name {
+            experience.name = n;
+        }
+        if let Some(m) = modifier {
+            experience.modifier = m;
+        }
+        let experience = experience.clone();
+        let character_name = character.name.clone();
+
+        self.add_event(
+            GameEventType::ResourceUpdate,
+            format!(
+                "{} updated the Experience {}",
+                character_name, experience.name
+            ),
+            Some(character_name),
+            Some(format!("Modifier: {:+}", experience.modifier)),
+        );
+
+        Ok(experience)
+    }
+}
+
+/// Parse a `#rrggbb` color
+/// A character's or token's name, trimmed, if it's one we accept
+fn check_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_TOKEN_NAME_LENGTH {
+        return Err(format!(
+            "Name is too long (max {} characters)",
+            MAX_TOKEN_NAME_LENGTH
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Name contains invalid characters".to_string());
+    }
+    Ok(name)
+}
+
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// WCAG relative luminance
+fn relative_luminance(rgb: [u8; 3]) -> f32 {
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.039_28 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(rgb[0]) + 0.7152 * linear(rgb[1]) + 0.0722 * linear(rgb[2])
+}
+
+/// WCAG contrast ratio between two colors (1.0 to 21.0)
+fn contrast_ratio(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Straight-line distance between two colors in RGB space
+fn color_distance(a: [u8; 3], b: [u8; 3]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| (x as f32 - y as f32).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// The protocol's name for the engine's controlling die
+pub fn controlling_die(die: duality::ControllingDie) -> ControllingDie {
+    match die {
+        duality::ControllingDie::Hope => ControllingDie::Hope,
+        duality::ControllingDie::Fear => ControllingDie::Fear,
+        duality::ControllingDie::Tied => ControllingDie::Tied,
+    }
+}
+
+/// Which die of a duality roll is higher
+pub fn duality_controlling_die(hope: u8, fear: u8) -> ControllingDie {
+    match hope.cmp(&fear) {
+        std::cmp::Ordering::Greater => ControllingDie::Hope,
+        std::cmp::Ordering::Less => ControllingDie::Fear,
+        std::cmp::Ordering::Equal => ControllingDie::Tied,
+    }
+}
+
+/// Roll the d6 for advantage or disadvantage
+///
+/// Returns `(advantage_die, disadvantage_die)`. Advantage and disadvantage
+/// cancel out, so with both neither die is rolled.
+pub fn roll_advantage_dice(
+    dice: &DiceRng,
+    has_advantage: bool,
+    has_disadvantage: bool,
+) -> (Option<u8>, Option<u8>) {
+    if has_advantage == has_disadvantage {
+        return (None, None);
+    }
+    let die = dice.die(6);
+    if has_advantage {
+        (Some(die), None)
+    } else {
+        (None, Some(die))
+    }
+}
+
+/// Chance a duality roll with this modifier meets the difficulty
+///
+/// Matching dice are a critical success whatever the total.
+pub fn success_probability(
+    modifier: i8,
+    difficulty: u16,
+    has_advantage: bool,
+    has_disadvantage: bool,
+) -> f32 {
+    let advantage: &[i32] = match (has_advantage, has_disadvantage) {
+        (true, false) => &[1, 2, 3, 4, 5, 6],
+        (false, true) => &[-1, -2, -3, -4, -5, -6],
+        _ => &[0],
+    };
+
+    let mut successes = 0;
+    let mut outcomes = 0;
+    for hope in 1..=12 {
+        for fear in 1..=12 {
+            for bonus in advantage {
+                outcomes += 1;
+                let total = (hope + fear + bonus + modifier as i32).max(0);
+                if hope == fear || total >= difficulty as i32 {
+                    successes += 1;
+                }
+            }
+        }
+    }
+    successes as f32 / outcomes as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::maps::GridSettings;
+
+    #[test]
+    fn test_add_connection() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+
+        assert_eq!(state.connection_count(), 1);
+        assert!(state.connections.contains_key(&conn.id));
+
+        let tv = state.add_connection();
+        state.connections.get_mut(&tv.id).unwrap().role = ConnectionRole::Spectator;
+        assert_eq!(state.spectator_count(), 1);
+    }
+
+    #[test]
+    fn test_remove_connection() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+
+        let removed = state.remove_connection(&conn.id);
+        assert!(removed.is_some());
+        assert_eq!(state.connection_count(), 0);
+    }
+
+    #[test]
+    fn test_kick_connection() {
+        let mut state = GameState::new();
+        let gm = state.add_connection();
+        let troll = state.add_connection();
+        let address: IpAddr = "192.168.1.23".parse().unwrap();
+        state.connections.get_mut(&troll.id).unwrap().address = Some(address);
+
+        assert!(state.kick_connection(&gm.id, &gm.id, false).is_err());
+        assert!(state
+            .kick_connection(&gm.id, &Uuid::new_v4(), false)
+            .is_err());
+
+        assert_eq!(
+            state.kick_connection(&gm.id, &troll.id, true),
+            Ok("A connection".to_string())
+        );
+        assert!(state.is_banned(&address));
+        assert_eq!(
+            state.event_log.last().unwrap().message,
+            "A connection was kicked and banned"
+        );
+
+        // Banning needs an address to ban
+        assert!(state.kick_connection(&troll.id, &gm.id, true).is_err());
+        assert!(!state.is_banned(&"10.0.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_create_character() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        assert_eq!(character.name, "Theron");
+        assert_eq!(character.class, Class::Warrior);
+        assert!(!character.is_npc);
+        assert_eq!(state.character_count(), 1);
+    }
+
+    #[test]
+    fn test_select_character() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let result = state.select_character(&conn.id, &character.id);
+        assert!(result.is_ok());
+
+        let controlled = state.get_controlled_character(&conn.id);
+        assert!(controlled.is_some());
+        assert_eq!(controlled.unwrap().name, "Theron");
+    }
+
+    #[test]
+    fn test_select_character_already_controlled() {
+        let mut state = GameState::new();
+        let conn1 = state.add_connection();
+        let conn2 = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // First connection controls character
+        state.select_character(&conn1.id, &character.id).unwrap();
+
+        // Second connection tries to control same character - should fail
+        let result = state.select_character(&conn2.id, &character.id);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_update_character_position() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let new_pos = Position::new(100.0, 200.0);
+        let updated = state.update_character_position(&character.id, new_pos);
+
+        assert_eq!(updated, Ok(new_pos));
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.position.x, 100.0);
+        assert_eq!(char.position.y, 200.0);
+
+        assert!(state
+            .update_character_position(&character.id, Position::new(900.0, 200.0))
+            .is_err());
+        assert!(state
+            .update_character_position(&Uuid::new_v4(), new_pos)
+            .is_err());
+    }
+
+    #[test]
+    fn test_moves_snap_to_grid() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let layout = SceneLayout {
+            width: 1000.0,
+            height: 500.0,
+            grid: Some(GridSettings {
+                kind: GridKind::Square,
+                cell_size: 50.0,
+                offset_x: 0.0,
+                offset_y: 0.0,
+            }),
+            ..SceneLayout::default()
+        };
+        state.set_layout(layout).unwrap();
+
+        let movement = state
+            .move_character(&character.id, &[], Position::new(910.0, 130.0), None)
+            .unwrap();
+        assert_eq!(movement.path.last(), Some(&Position::new(925.0, 125.0)));
+        assert_eq!(
+            state.get_character(&character.id).unwrap().position,
+            Position::new(925.0, 125.0)
+        );
+        assert!(state
+            .move_character(&character.id, &[], Position::new(400.0, 550.0), None)
+            .is_err());
+
+        // Clearing the map goes back to the default scene
+        state.set_map(None);
+        assert_eq!(state.layout, SceneLayout::default());
+        assert!(state
+            .move_character(&character.id, &[], Position::new(910.0, 130.0), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_fog_hides_npcs_and_adversaries_per_scene() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let guard = state
+            .create_npc(
+                "Guard".to_string(),
+                Some(5),
+                None,
+                None,
+                Some(Position::new(60.0, 30.0)),
+            )
+            .unwrap();
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(500.0, 500.0))
+            .unwrap();
+        assert!(state.fogged_tokens().is_empty(), "no fog yet");
+
+        state.set_fog_enabled(true);
+        let fogged = state.fogged_tokens();
+        assert_eq!(fogged.len(), 2, "player characters are never hidden");
+        assert!(fogged.contains(&guard.id.to_string()));
+
+        let fog = state
+            .reveal_area(vec![
+                Position::new(0.0, 0.0),
+                Position::new(100.0, 0.0),
+                Position::new(100.0, 100.0),
+            ])
+            .unwrap();
+        assert_eq!(
+            state.fogged_tokens(),
+            HashSet::from([goblin.id.clone()]),
+            "the guard is in the revealed corner"
+        );
+        assert!(state.reveal_area(vec![Position::new(0.0, 0.0)]).is_err());
+
+        // Another map has its own fog
+        state.map = Some(MapInfo {
+            id: "other".to_string(),
+            scene: "Elsewhere".to_string(),
+            url: "/maps/other.png".to_string(),
+            uploaded_at: String::new(),
+            layout: SceneLayout::default(),
+        });
+        assert!(state.fogged_tokens().is_empty());
+        state.map = None;
+
+        state.hide_area(&fog.revealed[0].id).unwrap();
+        assert_eq!(state.fogged_tokens().len(), 2);
+        assert!(state.hide_area("missing").is_err());
+    }
+
+    #[test]
+    fn test_drawings_per_scene() {
+        let mut state = GameState::new();
+        let circle = DrawingShape::Circle {
+            center: Position::new(200.0, 200.0),
+            radius: 50.0,
+        };
+
+        let drawings = state
+            .add_drawing(circle.clone(), " #E74C3C ", "Fire wall")
+            .unwrap();
+        assert_eq!(drawings.len(), 1);
+        assert_eq!(drawings[0].color, "#e74c3c");
+        assert!(state.add_drawing(circle.clone(), "red", "").is_err());
+        assert!(state
+            .add_drawing(circle.clone(), "#e74c3c", &"x".repeat(41))
+            .is_err());
+        let line = DrawingShape::Line {
+            points: vec![Position::new(0.0, 0.0)],
+        };
+        assert!(state.add_drawing(line, "#e74c3c", "").is_err());
+
+        // Another map has its own drawings
+        state.map = Some(MapInfo {
+            id: "other".to_string(),
+            scene: "Elsewhere".to_string(),
+            url: "/maps/other.png".to_string(),
+            uploaded_at: String::new(),
+            layout: SceneLayout::default(),
+        });
+        assert!(state.current_drawings().is_empty());
+        state.add_drawing(circle, "#3498db", "").unwrap();
+        assert!(state.clear_drawings().is_empty());
+        state.map = None;
+
+        let id = drawings[0].id.clone();
+        assert_eq!(state.current_drawings().len(), 1);
+        assert!(state.remove_drawing(&id).unwrap().is_empty());
+        assert!(state.remove_drawing(&id).is_err());
+    }
+
+    #[test]
+    fn test_ping_limits() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state.select_character(&conn.id, &character.id).unwrap();
+
+        assert!(state.ping(&conn.id, &Position::new(900.0, 10.0)).is_err());
+        let (name, color) = state.ping(&conn.id, &Position::new(100.0, 100.0)).unwrap();
+        assert_eq!(name, "Theron");
+        assert_eq!(color, character.color);
+        assert!(
+            state.ping(&conn.id, &Position::new(100.0, 100.0)).is_err(),
+            "too soon"
+        );
+    }
+
+    #[test]
+    fn test_connection_removal_clears_control() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        state.select_character(&conn.id, &character.id).unwrap();
+        assert!(state.control_mapping.contains_key(&conn.id));
+
+        state.remove_connection(&conn.id);
+        assert!(!state.control_mapping.contains_key(&conn.id));
+        // Character should still exist
+        assert!(state.characters.contains_key(&character.id));
+    }
+
+    #[test]
+    fn test_get_player_characters_and_npcs() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+
+        // Create PC
+        state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Create NPC
+        let npc = Character::new_npc(
+            "Goblin".to_string(),
+            Class::Rogue,
+            Ancestry::Goblin,
+            attrs,
+            Position::new(100.0, 100.0),
+            "#ff0000".to_string(),
+            10,
+        );
+        state.characters.insert(npc.id, npc);
+
+        assert_eq!(state.get_player_characters().len(), 1);
+        assert_eq!(state.get_npcs().len(), 1);
+        assert_eq!(state.character_count(), 2);
+    }
+
+    #[test]
+    fn test_create_and_convert_npc() {
+        let mut state = GameState::new();
+
+        assert!(state
+            .create_npc("Innkeeper".to_string(), None, None, None, None)
+            .is_err());
+
+        let innkeeper = state
+            .create_npc("Innkeeper".to_string(), Some(5), Some(9), None, None)
+            .unwrap();
+        assert!(innkeeper.is_npc);
+        assert_eq!(innkeeper.hp.maximum, 5);
+        assert_eq!(innkeeper.evasion, 9);
+
+        let guard = state
+            .create_npc(
+                "Gate Guard".to_string(),
+                None,
+                None,
+                Some("goblin"),
+                Some(Position::new(40.0, 40.0)),
+            )
+            .unwrap();
+        let template = state.find_adversary_template("goblin").unwrap();
+        assert_eq!(guard.hp.maximum, template.hp);
+        assert_eq!(guard.evasion, template.evasion as i32);
+        assert_eq!(state.get_npcs().len(), 2);
+
+        state
+            .get_character_mut(&guard.id)
+            .unwrap()
+            .hp
+            .take_damage(1);
+        let adversary = state.convert_npc_to_adversary(&guard.id).unwrap();
+        assert_eq!(adversary.name, "Gate Guard");
+        assert_eq!(adversary.template, "goblin");
+        assert_eq!(adversary.hp, template.hp - 1);
+        assert_eq!(adversary.armor, template.armor);
+        assert_eq!(adversary.position, Position::new(40.0, 40.0));
+        assert!(state.get_character(&guard.id).is_none());
+        assert!(state.adversaries.contains_key(&adversary.id));
+
+        let adversary = state.convert_npc_to_adversary(&innkeeper.id).unwrap();
+        assert_eq!(adversary.template, "custom");
+        assert_eq!(adversary.evasion, 9);
+
+        // Player characters can't be converted
+        let theron = state.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        assert!(state.convert_npc_to_adversary(&theron.id).is_err());
+    }
+
+    #[test]
+    fn test_color_assignment() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+
+        let c1 = state.create_character("C1".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let c2 = state.create_character("C2".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let c3 = state.create_character("C3".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Should assign different colors
+        assert_ne!(c1.color, c2.color);
+        assert_ne!(c2.color, c3.color);
+    }
+
+    #[test]
+    fn test_palette_is_readable() {
+        for color in CHARACTER_COLORS {
+            let rgb = parse_hex_color(color).unwrap();
+            assert!(
+                contrast_ratio(rgb, MAP_BACKGROUND) >= MIN_COLOR_CONTRAST,
+                "{}",
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_character_color() {
+        let mut state = GameState::new();
+        let c1 = state.create_character(
+            "C1".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        let c2 = state.create_character(
+            "C2".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+
+        assert_eq!(
+            state.set_character_color(&c1.id, " #FACC15 "),
+            Ok("#facc15".to_string())
+        );
+        assert_eq!(state.characters[&c1.id].color, "#facc15");
+
+        assert!(state.set_character_color(&c1.id, "yellow").is_err());
+        assert!(
+            state.set_character_color(&c1.id, "#202020").is_err(),
+            "too dark"
+        );
+        assert!(
+            state.set_character_color(&c2.id, "#fbcd18").is_err(),
+            "too close to C1"
+        );
+        assert!(
+            state.set_character_color(&c1.id, "#fbcd18").is_ok(),
+            "own color is fine"
+        );
+    }
+
+    #[test]
+    fn test_set_display_name() {
+        let mut state = GameState::new();
+        let alice = state.add_connection();
+        let bob = state.add_connection();
+
+        assert_eq!(
+            state.set_display_name(&alice.id, "  Alice "),
+            Ok("Alice".to_string())
+        );
+        assert!(
+            state.set_display_name(&bob.id, "alice").is_err(),
+            "names are unique"
+        );
+        assert!(state.set_display_name(&bob.id, "   ").is_err());
+        assert!(state
+            .set_display_name(&bob.id, &"x".repeat(MAX_DISPLAY_NAME_LENGTH + 1))
+            .is_err());
+
+        // Events about Alice's character are attributed to her
+        let character = state.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        state.control_mapping.insert(alice.id, vec![character.id]);
+        state.set_character_color(&character.id, "#facc15").unwrap();
+        assert_eq!(
+            state.event_log.last().unwrap().player_name.as_deref(),
+            Some("Alice")
+        );
+    }
+
+    #[test]
+    fn test_preferences_follow_session_token() {
+        let mut state = GameState::new();
+        let phone = state.add_connection();
+        let (token, prefs) = state.resume_session(&phone.id, None).unwrap();
+        assert_eq!(prefs, UserPreferences::default());
+
+        let lite = UserPreferences {
+            lite_mode: true,
+            theme: Some("high_contrast".to_string()),
+            ..Default::default()
+        };
+        state.set_preferences(&phone.id, lite.clone()).unwrap();
+
+        // Switching devices with the same token brings the preferences along
+        let tablet = state.add_connection();
+        assert_eq!(
+            state.resume_session(&tablet.id, Some(&token)).unwrap(),
+            (token.clone(), lite)
+        );
+
+        // Unknown tokens get a fresh session
+        let stranger = state.add_connection();
+        let (other, prefs) = state.resume_session(&stranger.id, Some("made-up")).unwrap();
+        assert_ne!(other, "made-up");
+        assert_eq!(prefs, UserPreferences::default());
+    }
+
+    #[test]
+    fn test_roll_duality() {
+        let state = GameState::new();
+        let result = state.roll_duality(2, false, false);
+
+        // Should have valid values
+        assert!(result.hope >= 1 && result.hope <= 12);
+        assert!(result.fear >= 1 && result.fear <= 12);
+        assert_eq!(result.modifier, 2);
+        assert_eq!(
+            (result.advantage_die, result.disadvantage_die),
+            (None, None)
+        );
+        assert_eq!(
+            result.controlling_die == ControllingDie::Tied,
+            result.hope == result.fear
+        );
+    }
+
+    #[test]
+    fn test_seeded_dice_repeat_rolls() {
+        let rolls = || {
+            let mut state = GameState::new();
+            state.dice = DiceRng::seeded(42);
+            (0..10)
+                .map(|_| {
+                    let result = state.roll_duality(1, true, false);
+                    (result.hope, result.fear, result.advantage_die, result.total)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rolls(), rolls());
+    }
+
+    #[test]
+    fn test_resource_sync_and_restore() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Modify resources
+        let char_mut = state.get_character_mut(&character.id).unwrap();
+        char_mut.hp.take_damage(3);
+        char_mut.stress.gain(2);
+        let _ = char_mut.hope.spend(1);
+
+        // Sync to serializable fields
+        let char_mut = state.get_character_mut(&character.id).unwrap();
+        char_mut.sync_resources();
+
+        let hp_current = char_mut.hp_current;
+        let stress_current = char_mut.stress_current;
+        let hope_current = char_mut.hope_current;
+
+        // Restore from serializable fields
+        let char_mut = state.get_character_mut(&character.id).unwrap();
+        char_mut.restore_resources();
+
+        assert_eq!(char_mut.hp.current, hp_current);
+        assert_eq!(char_mut.stress.current, stress_current);
+        assert_eq!(char_mut.hope.current, hope_current);
+    }
+
+    // ===== Phase 1: Dice Roll Tests =====
+
+    #[test]
+    fn test_proficiency_bonus_progression() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let mut character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Level 1-3: +1
+        character.level = 1;
+        assert_eq!(character.proficiency_bonus(), 1);
+        character.level = 3;
+        assert_eq!(character.proficiency_bonus(), 1);
+
+        // Level 4-6: +2
+        character.level = 4;
+        assert_eq!(character.proficiency_bonus(), 2);
+        character.level = 6;
+        assert_eq!(character.proficiency_bonus(), 2);
+
+        // Level 7-9: +3
+        character.level = 7;
+        assert_eq!(character.proficiency_bonus(), 3);
+        character.level = 9;
+        assert_eq!(character.proficiency_bonus(), 3);
+
+        // Level 10+: +4
+        character.level = 10;
+        assert_eq!(character.proficiency_bonus(), 4);
+        character.level = 15;
+        assert_eq!(character.proficiency_bonus(), 4);
+    }
+
+    #[test]
+    fn test_get_attribute() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        assert_eq!(character.get_attribute("agility"), Some(2));
+        assert_eq!(character.get_attribute("strength"), Some(1));
+        assert_eq!(character.get_attribute("knowledge"), Some(-1));
+        assert_eq!(character.get_attribute("invalid"), None);
+        assert_eq!(character.get_attribute("AGILITY"), Some(2)); // case insensitive
+    }
+
+    #[test]
+    fn test_experience_initialization() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        assert_eq!(character.level, 1);
+        assert!(character.experiences.is_empty());
+    }
+
+    #[test]
+    fn test_fear_pool_initialization() {
+        let state = GameState::new();
+        assert_eq!(state.fear_pool, 5); // Starting Fear pool
+    }
+
+    #[test]
+    fn test_pending_roll_requests() {
+        let state = GameState::new();
+        assert!(state.pending_roll_requests.is_empty());
+    }
+
+    #[test]
+    fn test_execute_roll_without_request() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Try to execute a roll for a non-existent request
+        let result = state.execute_roll(&character.id, "fake-request-id", false, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Roll request not found");
+    }
+
+    #[test]
+    fn test_execute_roll_with_insufficient_hope() {
+        use crate::protocol::RollType;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Spend all Hope
+        let char_mut = state.get_character_mut(&character.id).unwrap();
+        let _ = char_mut.hope.spend(5);
+        char_mut.sync_resources();
+
+        // Create a roll request
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Action,
+            attribute: Some("agility".to_string()),
+            difficulty: 14,
+            context: "Test roll".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: false,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+
+        state
+            .pending_roll_requests
+            .insert("test-request".to_string(), request);
+
+        // Try to execute with spend_hope=true but no Hope
+        let result = state.execute_roll(&character.id, "test-request", true, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Not enough Hope to spend");
+    }
+
+    #[test]
+    fn test_execute_roll_success() {
+        use crate::protocol::{RollType, SuccessType};
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Create a roll request
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Action,
+            attribute: Some("agility".to_string()),
+            difficulty: 14,
+            context: "Test roll".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: false,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+
+        state
+            .pending_roll_requests
+            .insert("test-request".to_string(), request);
+
+        // Execute the roll
+        let result = state.execute_roll(&character.id, "test-request", false, None);
+        assert!(result.is_ok());
+
+        let roll_result = result.unwrap();
+
+        // Verify dice are in valid range
+        assert!(roll_result.hope_die >= 1 && roll_result.hope_die <= 12);
+        assert!(roll_result.fear_die >= 1 && roll_result.fear_die <= 12);
+
+        // Verify modifiers
+        assert_eq!(roll_result.attribute_modifier, 2); // Agility
+        assert_eq!(roll_result.proficiency_modifier, 0); // Not an attack
+        assert_eq!(roll_result.situational_modifier, 0);
+        assert_eq!(roll_result.hope_bonus, 0); // Didn't spend Hope
+
+        // Verify success type is one of the valid types
+        match roll_result.success_type {
+            SuccessType::Failure
+            | SuccessType::SuccessWithHope
+            | SuccessType::SuccessWithFear
+            | SuccessType::CriticalSuccess => {}
+        }
+
+        // Verify critical detection
+        if roll_result.hope_die == roll_result.fear_die {
+            assert!(roll_result.is_critical);
+            assert_eq!(roll_result.success_type, SuccessType::CriticalSuccess);
+        }
+
+        // Verify the request is marked as completed
+        let req = state.pending_roll_requests.get("test-request").unwrap();
+        assert!(req.completed_by.contains(&character.id));
+    }
+
+    #[test]
+    fn test_roll_stats_tally_executed_rolls() {
+        use crate::protocol::RollType;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        assert_eq!(state.roll_stats_report().table.rolls, 0);
+
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Action,
+            attribute: Some("agility".to_string()),
+            difficulty: 14,
+            context: "Test roll".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: false,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+        state
+            .pending_roll_requests
+            .insert("test-request".to_string(), request);
+        let roll_result = state
+            .execute_roll(&character.id, "test-request", false, None)
+            .unwrap();
+
+        let report = state.roll_stats_report();
+        assert_eq!(report.characters[0].name, "Theron");
+        assert_eq!(report.table.rolls, 1);
+        assert_eq!(
+            report.table.hope_faces[roll_result.hope_die as usize - 1],
+            1
+        );
+        assert_eq!(
+            report.table.fear_faces[roll_result.fear_die as usize - 1],
+            1
+        );
+    }
+
+    #[test]
+    fn test_hope_fear_changes_on_success() {
+        use crate::protocol::RollType;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Reduce Hope below max so we can test the gain
+        let char_mut = state.get_character_mut(&character.id).unwrap();
+        let _ = char_mut.hope.spend(2); // Spend 2 Hope (5 → 3)
+        char_mut.sync_resources();
+
+        let initial_hope = state.characters.get(&character.id).unwrap().hope.current;
+        let initial_fear = state.fear_pool;
+
+        assert_eq!(initial_hope, 3); // Verify starting Hope is 3
+
+        // Create a roll request with very low DC to ensure success
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Action,
+            attribute: Some("agility".to_string()),
+            difficulty: 1, // Very low DC, almost guaranteed success
+            context: "Easy test roll".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: false,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+
+        state
+            .pending_roll_requests
+            .insert("test-request".to_string(), request);
+
+        // Execute the roll
+        let result = state.execute_roll(&character.id, "test-request", false, None);
+        assert!(result.is_ok());
+
+        let roll_result = result.unwrap();
+
+        // Check resource changes based on success type
+        let character = state.characters.get(&character.id).unwrap();
+        match roll_result.success_type {
+            crate::protocol::SuccessType::SuccessWithHope => {
+                // Hope should increase by 1 (3 → 4)
+                assert_eq!(character.hope.current, initial_hope + 1);
+                assert_eq!(state.fear_pool, initial_fear);
+                assert_eq!(roll_result.hope_change, 1);
+                assert_eq!(roll_result.fear_change, 0);
+            }
+            crate::protocol::SuccessType::SuccessWithFear => {
+                // Fear should increase by 1
+                assert_eq!(character.hope.current, initial_hope);
+                assert_eq!(state.fear_pool, initial_fear + 1);
+                assert_eq!(roll_result.hope_change, 0);
+                assert_eq!(roll_result.fear_change, 1);
+            }
+            crate::protocol::SuccessType::CriticalSuccess => {
+                // No resource changes on critical
+                assert_eq!(character.hope.current, initial_hope);
+                assert_eq!(state.fear_pool, initial_fear);
+                assert_eq!(roll_result.hope_change, 0);
+                assert_eq!(roll_result.fear_change, 0);
+            }
+            crate::protocol::SuccessType::Failure => {
+                // No resource changes on failure
+                assert_eq!(character.hope.current, initial_hope);
+                assert_eq!(state.fear_pool, initial_fear);
+                assert_eq!(roll_result.hope_change, 0);
+                assert_eq!(roll_result.fear_change, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_group_roll() {
+        let mut state = GameState::new();
+        let mut ids = Vec::new();
+        for name in ["Leader", "Helper A", "Helper B"] {
+            let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+            let character =
+                state.create_character(name.to_string(), Class::Warrior, Ancestry::Human, attrs);
+            ids.push(character.id);
+        }
+        let (leader, helpers) = (ids[0], ids[1..].to_vec());
+
+        let group = |helpers: &[Uuid]| GroupRollRequest {
+            leader_id: leader.to_string(),
+            helper_ids: helpers.iter().map(Uuid::to_string).collect(),
+            attribute: Some("agility".to_string()),
+            difficulty: 12,
+            context: "Climb the cliff".to_string(),
+            has_advantage: false,
+            has_disadvantage: false,
+        };
+        assert!(state.request_group_roll(group(&[])).is_err());
+        assert!(state.request_group_roll(group(&[leader])).is_err());
+        assert!(state
+            .request_group_roll(GroupRollRequest {
+                helper_ids: vec!["not-an-id".to_string()],
+                ..group(&[])
+            })
+            .is_err());
+
+        let request = state.request_group_roll(group(&helpers)).unwrap();
+        assert!(matches!(
+            request.roll_type_for(&helpers[0]),
+            RollType::Reaction
+        ));
+        assert!(matches!(request.roll_type_for(&leader), RollType::Action));
+
+        // The leader waits for every helper
+        assert!(state
+            .execute_roll(&leader, &request.id, false, None)
+            .is_err());
+        let mut expected = 0;
+        for helper in &helpers {
+            let result = state
+                .execute_roll(helper, &request.id, false, None)
+                .unwrap();
+            assert_eq!((result.hope_change, result.fear_change), (0, 0));
+            expected += if result.success_type == crate::protocol::SuccessType::Failure {
+                -1
+            } else {
+                1
+            };
+        }
+
+        let result = state
+            .execute_roll(&leader, &request.id, false, None)
+            .unwrap();
+        assert_eq!(result.group_modifier, expected);
+        assert_eq!(result.total_modifier, 2 + expected);
+    }
+
+    #[test]
+    fn test_success_probability() {
+        // Only criticals succeed against an impossible difficulty
+        assert_eq!(success_probability(0, 100, false, false), 12.0 / 144.0);
+        assert_eq!(success_probability(0, 2, false, false), 1.0);
+        assert!(success_probability(2, 12, true, false) > success_probability(2, 12, false, false));
+        assert!(success_probability(2, 12, false, true) < success_probability(2, 12, false, false));
+        assert_eq!(
+            success_probability(2, 12, true, true),
+            success_probability(2, 12, false, false)
+        );
+        assert!(
+            success_probability(3, 12, false, false) > success_probability(0, 12, false, false)
+        );
+    }
+
+    #[test]
+    fn test_roll_dice() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        assert_eq!(
+            state
+                .roll_dice(&conn.id, None, "2d6", None, false)
+                .unwrap_err(),
+            "No character selected"
+        );
+
+        let theron = state.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        state.control_mapping.insert(conn.id, vec![theron.id]);
+        let result = state
+            .roll_dice(&conn.id, None, "2d6+1d4+3", Some(" Loot "), false)
+            .unwrap();
+        assert_eq!(result.character_id, Some(theron.id));
+        assert_eq!(result.label.as_deref(), Some("Loot"));
+        assert_eq!(result.roll.dice.len(), 3);
+        assert!(matches!(
+            state.event_log.last().unwrap().event_type,
+            GameEventType::RollExecuted
+        ));
+        assert!(state
+            .roll_dice(&conn.id, None, "2d6+", None, false)
+            .is_err());
+        assert!(
+            state.roll_dice(&conn.id, None, "2d6", None, true).is_err(),
+            "players can't roll in secret"
+        );
+
+        let gm = state.add_connection();
+        state.connections.get_mut(&gm.id).unwrap().role = ConnectionRole::Gm;
+        let result = state.roll_dice(&gm.id, None, "1d20", None, false).unwrap();
+        assert_eq!(result.character_id, None);
+        assert_eq!(result.roller_name, "GM");
+
+        let result = state
+            .roll_dice(&gm.id, None, "1d20", Some("Ambush"), true)
+            .unwrap();
+        assert!(result.hidden);
+        let event = state.event_log.last().unwrap();
+        assert_eq!(event.message, "GM rolled in secret");
+        assert!(event.details.is_none(), "the dice stay with the GM");
+    }
+
+    #[test]
+    fn test_roll_with_disadvantage() {
+        use crate::protocol::RollType;
+
+        // Advantage and disadvantage cancel out
+        let dice = DiceRng::default();
+        assert_eq!(roll_advantage_dice(&dice, true, true), (None, None));
+        assert!(matches!(
+            roll_advantage_dice(&dice, true, false),
+            (Some(1..=6), None)
+        ));
+        assert!(matches!(
+            roll_advantage_dice(&dice, false, true),
+            (None, Some(1..=6))
+        ));
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Action,
+            attribute: Some("agility".to_string()),
+            difficulty: 12,
+            context: "Cross the ice".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: true,
+            is_combat: false,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+        state
+            .pending_roll_requests
+            .insert(request.id.clone(), request);
+
+        let result = state
+            .execute_roll(&character.id, "test-request", false, None)
+            .unwrap();
+        let die = result.disadvantage_die.expect("Disadvantage rolls a d6");
+        assert_eq!(result.advantage_die, None);
+        assert_eq!(
+            result.total as i32,
+            (result.hope_die as i32 + result.fear_die as i32 - die as i32
+                + result.total_modifier as i32)
+                .max(0)
+        );
+    }
+
+    #[test]
+    fn test_preview_roll() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let spec = RollSpec {
+            roll_type: RollType::Attack,
+            attribute: Some("agility".to_string()),
+            difficulty: 12,
+            situational_modifier: -1,
+            has_advantage: false,
+            has_disadvantage: false,
+        };
+        assert!(state.preview_roll(&[], &spec).is_err());
+
+        let previews = state.preview_roll(&[theron.id], &spec).unwrap();
+        let preview = &previews[0];
+        assert_eq!(preview.attribute_modifier, 2);
+        assert_eq!(preview.proficiency_modifier, theron.proficiency_bonus());
+        assert_eq!(preview.total_modifier, 1 + theron.proficiency_bonus());
+        assert_eq!(preview.hope, 5);
+        assert_eq!(
+            preview.success_chance,
+            success_probability(preview.total_modifier, 12, false, false)
+        );
+        assert!(preview.conditions.is_empty());
+
+        // Previewing leaves no request behind
+        assert!(state.pending_roll_requests.is_empty());
+    }
+
+    #[test]
+    fn test_help_ally() {
+        let mut state = GameState::new();
+        let mut ids = Vec::new();
+        for name in ["Theron", "Elara"] {
+            let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+            let character =
+                state.create_character(name.to_string(), Class::Warrior, Ancestry::Human, attrs);
+            ids.push(character.id);
+        }
+        let (theron, elara) = (ids[0], ids[1]);
+
+        let request_id = "help".to_string();
+        state.pending_roll_requests.insert(
+            request_id.clone(),
+            PendingRollRequest {
+                id: request_id.clone(),
+                target_character_ids: vec![theron],
+                roll_type: RollType::Action,
+                attribute: Some("agility".to_string()),
+                difficulty: 12,
+                context: "Pick the lock".to_string(),
+                narrative_stakes: None,
+                situational_modifier: 0,
+                has_advantage: false,
+                has_disadvantage: false,
+                is_combat: false,
+                completed_by: Vec::new(),
+                timestamp: std::time::SystemTime::now(),
+                group: None,
+                tag_team: None,
+                help: Vec::new(),
+                hidden: false,
+            },
+        );
+
+        assert!(state.help_ally(&request_id, &theron, &theron).is_err());
+        assert!(state.help_ally(&request_id, &theron, &elara).is_err());
+        state.help_ally(&request_id, &elara, &theron).unwrap();
+        assert!(state.help_ally(&request_id, &elara, &theron).is_err());
+        assert_eq!(state.characters[&elara].hope.current, 4);
+
+        let result = state
+            .execute_roll(&theron, &request_id, false, None)
+            .unwrap();
+        assert_eq!(result.help_dice.len(), 1);
+        assert!((1..=6).contains(&result.help_bonus));
+        assert_eq!(
+            result.total,
+            result.hope_die as u16
+                + result.fear_die as u16
+                + result.help_bonus as u16
+                + result.total_modifier as u16
+        );
+    }
+
+    #[test]
+    fn test_tag_team_roll() {
+        let mut state = GameState::new();
+        let mut ids = Vec::new();
+        for name in ["Theron", "Elara", "Poor Pip"] {
+            let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+            let character =
+                state.create_character(name.to_string(), Class::Warrior, Ancestry::Human, attrs);
+            ids.push(character.id);
+        }
+        let (theron, elara, pip) = (ids[0], ids[1], ids[2]);
+        let char_mut = state.get_character_mut(&pip).unwrap();
+        let _ = char_mut.hope.spend(3);
+        char_mut.sync_resources();
+
+        let request_id = "tag-team".to_string();
+        state.pending_roll_requests.insert(
+            request_id.clone(),
+            PendingRollRequest {
+                id: request_id.clone(),
+                target_character_ids: vec![theron],
+                roll_type: RollType::Action,
+                attribute: Some("agility".to_string()),
+                difficulty: 12,
+                context: "Vault the barricade".to_string(),
+                narrative_stakes: None,
+                situational_modifier: 0,
+                has_advantage: false,
+                has_disadvantage: false,
+                is_combat: false,
+                completed_by: Vec::new(),
+                timestamp: std::time::SystemTime::now(),
+                group: None,
+                tag_team: None,
+                help: Vec::new(),
+                hidden: false,
+            },
+        );
+
+        // Both halves need 3 Hope, and only the partner answers
+        assert!(state.propose_tag_team(&request_id, &theron, &pip).is_err());
+        assert!(state
+            .propose_tag_team(&request_id, &theron, &theron)
+            .is_err());
+        state
+            .propose_tag_team(&request_id, &theron, &elara)
+            .unwrap();
+        assert!(state
+            .execute_roll(&theron, &request_id, false, None)
+            .is_err());
+        assert!(state.respond_tag_team(&request_id, &theron, true).is_err());
+
+        let request = state.respond_tag_team(&request_id, &elara, true).unwrap();
+        assert!(request.target_character_ids.contains(&elara));
+        assert_eq!(state.characters[&theron].hope.current, 2);
+        assert_eq!(state.characters[&elara].hope.current, 2);
+
+        // Hope and Fear wait for the pick
+        let fear_before = state.fear_pool;
+        for id in [theron, elara] {
+            let result = state.execute_roll(&id, &request_id, false, None).unwrap();
+            assert_eq!((result.hope_change, result.fear_change), (0, 0));
+        }
+        assert_eq!(state.fear_pool, fear_before);
+        assert!(state
+            .choose_tag_team_result(&request_id, &pip, &elara)
+            .is_err());
+
+        let outcome = state
+            .choose_tag_team_result(&request_id, &theron, &elara)
+            .unwrap();
+        let hope = 2 + outcome.hope_change as u8;
+        assert_eq!(state.characters[&theron].hope.current, hope);
+        assert_eq!(state.characters[&elara].hope.current, hope);
+        assert_eq!(state.fear_pool, fear_before + outcome.fear_change);
+        if outcome.result.success_type == crate::protocol::SuccessType::SuccessWithFear {
+            assert_eq!(outcome.fear_change, 2);
+        }
+        assert!(state
+            .choose_tag_team_result(&request_id, &elara, &theron)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reaction_roll_keeps_hope_and_fear() {
+        use crate::protocol::RollType;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let char_mut = state.get_character_mut(&character.id).unwrap();
+        let _ = char_mut.hope.spend(2);
+        char_mut.sync_resources();
+
+        // Roll enough times that both Hope and Fear outcomes come up
+        for (i, roll_type) in [RollType::Reaction, RollType::Save]
+            .into_iter()
+            .cycle()
+            .take(40)
+            .enumerate()
+        {
+            let request_id = format!("reaction-{}", i);
+            state.pending_roll_requests.insert(
+                request_id.clone(),
+                PendingRollRequest {
+                    id: request_id.clone(),
+                    target_character_ids: vec![character.id],
+                    roll_type,
+                    attribute: Some("agility".to_string()),
+                    difficulty: 1,
+                    context: "Dodge the falling rocks".to_string(),
+                    narrative_stakes: None,
+                    situational_modifier: 0,
+                    has_advantage: false,
+                    has_disadvantage: false,
+                    is_combat: false,
+                    completed_by: Vec::new(),
+                    timestamp: std::time::SystemTime::now(),
+                    group: None,
+                    tag_team: None,
+                    help: Vec::new(),
+                    hidden: false,
+                },
+            );
+
+            let result = state
+                .execute_roll(&character.id, &request_id, false, None)
+                .unwrap();
+            assert_eq!((result.hope_change, result.fear_change), (0, 0));
+        }
+
+        assert_eq!(state.characters[&character.id].hope.current, 3);
+        assert_eq!(state.fear_pool, 5);
+    }
+
+    #[test]
+    fn test_attack_roll_uses_proficiency() {
+        use crate::protocol::RollType;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Create an attack roll request
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Attack, // Attack should use proficiency
+            attribute: Some("strength".to_string()),
+            difficulty: 14,
+            context: "Attack roll".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: true,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+
+        state
+            .pending_roll_requests
+            .insert("test-request".to_string(), request);
+
+        // Execute the roll
+        let result = state.execute_roll(&character.id, "test-request", false, None);
+        assert!(result.is_ok());
+
+        let roll_result = result.unwrap();
+
+        // Attack rolls should include proficiency
+        assert_eq!(roll_result.proficiency_modifier, 1); // Level 1 = +1 proficiency
+        assert_eq!(roll_result.attribute_modifier, 1); // Strength
+        assert_eq!(roll_result.total_modifier, 2); // 1 + 1
+    }
+
+    // ===== Combat & Adversary Tests =====
+
+    #[test]
+    fn test_spawn_adversary_from_template() {
+        let mut state = GameState::new();
+        let position = crate::protocol::Position::new(100.0, 100.0);
+
+        let result = state.spawn_adversary("goblin", position);
+        assert!(result.is_ok());
+
+        let adversary = result.unwrap();
+        assert_eq!(adversary.template, "goblin");
+        assert!(adversary.name.contains("Goblin"));
+        assert_eq!(adversary.hp, 3);
+        assert_eq!(adversary.max_hp, 3);
+        assert_eq!(adversary.evasion, 10);
+        assert_eq!(adversary.armor, 1);
+        assert_eq!(adversary.attack_modifier, 1);
+        assert_eq!(adversary.damage_dice, "1d6");
+        assert!(adversary.is_active);
+
+        // Check it was added to game state
+        assert_eq!(state.adversaries.len(), 1);
+        assert!(state.adversaries.contains_key(&adversary.id));
+
+        // Check event log
+        assert_eq!(state.event_log.len(), 1);
+    }
+
+    #[test]
+    fn test_spawn_multiple_adversaries_instance_numbers() {
+        let mut state = GameState::new();
+        let pos1 = crate::protocol::Position::new(100.0, 100.0);
+        let pos2 = crate::protocol::Position::new(200.0, 100.0);
+
+        let goblin1 = state.spawn_adversary("goblin", pos1).unwrap();
+        let goblin2 = state.spawn_adversary("goblin", pos2).unwrap();
+
+        assert_eq!(goblin1.name, "Goblin #1");
+        assert_eq!(goblin2.name, "Goblin #2");
+        assert_eq!(state.adversaries.len(), 2);
+    }
+
+    #[test]
+    fn test_checkpoint_rollback() {
+        let mut state = GameState::new();
+        assert!(state.rollback_to_checkpoint(None).is_err());
+
+        let conn = state.add_connection();
+        let theron = state.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        state.control_mapping.insert(conn.id, vec![theron.id]);
+        let pos = crate::protocol::Position::new(100.0, 100.0);
+        let goblin = state.spawn_adversary("goblin", pos).unwrap();
+        state.start_combat();
+        assert_eq!(state.checkpoints.len(), 1);
+
+        // The fight goes badly
+        state.fear_pool = 0;
+        state.remove_adversary(&goblin.id);
+        state.characters.get_mut(&theron.id).unwrap().hp_current = 0;
+        let events_before = state.event_log.len();
+
+        let info = state.rollback_to_checkpoint(None).unwrap();
+        assert_eq!(info.label, "Combat started");
+        assert_eq!(state.fear_pool, 5);
+        assert!(state.adversaries.contains_key(&goblin.id));
+        assert!(state.characters[&theron.id].hp_current > 0);
+        assert!(state.combat_encounter.is_some());
+        assert!(
+            state.connections.contains_key(&conn.id),
+            "players stay connected"
+        );
+        assert_eq!(state.controlled_characters(&conn.id), [theron.id]);
+        assert_eq!(state.event_log.len(), events_before + 1, "history is kept");
+        assert_eq!(state.checkpoints.len(), 1, "checkpoints survive a rollback");
+
+        for i in 0..MAX_CHECKPOINTS + 2 {
+            state.create_checkpoint(format!("Checkpoint {}", i));
+        }
+        assert_eq!(state.checkpoints.len(), MAX_CHECKPOINTS);
+        assert!(state.rollback_to_checkpoint(Some(&info.id)).is_err());
+    }
+
+    #[test]
+    fn test_chat_channels() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        assert!(state
+            .chat(&conn.id, ChatChannel::InCharacter, "Hello")
+            .is_err());
+
+        let theron = state.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        state.control_mapping.insert(conn.id, vec![theron.id]);
+        state.connections.get_mut(&conn.id).unwrap().display_name = Some("Sam".to_string());
+
+        let said = state
+            .chat(&conn.id, ChatChannel::InCharacter, " Stand back! ")
+            .unwrap();
+        assert_eq!(said.sender_name, "Theron");
+        assert_eq!(said.text, "Stand back!");
+        let ooc = state
+            .chat(&conn.id, ChatChannel::OutOfCharacter, "brb")
+            .unwrap();
+        assert_eq!(ooc.sender_name, "Sam");
+        assert_eq!(state.event_log.last().unwrap().message, "(OOC) Sam: brb");
+
+        let events = state.event_log.len();
+        let whisper = state
+            .chat(
+                &conn.id,
+                ChatChannel::Whisper {
+                    to_character_id: None,
+                },
+                "I pocket the key",
+            )
+            .unwrap();
+        assert_eq!(whisper.to_name.as_deref(), Some("GM"));
+        assert_eq!(state.event_log.len(), events, "whispers aren't logged");
+        assert!(state
+            .chat(
+                &conn.id,
+                ChatChannel::Whisper {
+                    to_character_id: Some(Uuid::new_v4().to_string()),
+                },
+                "psst",
+            )
+            .is_err());
+        assert!(state
+            .chat(
+                &conn.id,
+                ChatChannel::OutOfCharacter,
+                &"x".repeat(MAX_CHAT_LENGTH + 1)
+            )
+            .is_err());
+
+        for i in 0..MAX_CHAT_HISTORY {
+            state
+                .chat(&conn.id, ChatChannel::OutOfCharacter, &i.to_string())
+                .unwrap();
+        }
+        assert_eq!(state.chat_log.len(), MAX_CHAT_HISTORY);
+        assert_eq!(state.chat_log[0].text, "0");
+    }
+
+    #[test]
+    fn test_table_settings() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let theron = state.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        state.control_mapping.insert(conn.id, vec![theron.id]);
+
+        let settings = state
+            .update_settings(&TableSettingsPatch {
+                max_fear: Some(6),
+                player_whispers: Some(false),
+                ..TableSettingsPatch::default()
+            })
+            .unwrap();
+        assert_eq!(state.settings, settings);
+        assert_eq!(state.fear_pool, 5);
+
+        assert_eq!(state.gain_fear(3), 1, "capped at 6");
+        assert_eq!(state.fear_pool, 6);
+        state
+            .update_settings(&TableSettingsPatch {
+                starting_fear: Some(2),
+                max_fear: Some(4),
+                ..TableSettingsPatch::default()
+            })
+            .unwrap();
+        assert_eq!(state.fear_pool, 4, "lowering the cap drops Fear to it");
+        assert!(state
+            .update_settings(&TableSettingsPatch {
+                max_fear: Some(0),
+                ..TableSettingsPatch::default()
+            })
+            .is_err());
+        assert_eq!(
+            state.settings.max_fear, 4,
+            "a rejected patch changes nothing"
+        );
+
+        let whisper_to = |id: Option<Uuid>| ChatChannel::Whisper {
+            to_character_id: id.map(|id| id.to_string()),
+        };
+        assert_eq!(
+            state
+                .chat(&conn.id, whisper_to(Some(theron.id)), "psst")
+                .unwrap_err(),
+            "The GM has turned off whispers between players"
+        );
+        assert!(state.chat(&conn.id, whisper_to(None), "psst").is_ok());
+    }
+
+    #[test]
+    fn test_cutaway_events_merge_when_it_ends() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let mira =
+            state.create_character("Mira".to_string(), Class::Rogue, Ancestry::Faerie, attrs);
+        let conn = state.add_connection();
+        state.control_mapping.insert(conn.id, vec![mira.id]);
+
+        assert!(state.end_cutaway().is_err());
+        assert!(state.start_cutaway(&[], "").is_err());
+        assert!(state
+            .start_cutaway(&[Uuid::new_v4().to_string()], "")
+            .is_err());
+
+        let info = state
+            .start_cutaway(&[mira.id.to_string()], "The vault")
+            .unwrap();
+        assert_eq!(info.character_names, vec!["Mira".to_string()]);
+        assert!(
+            !state.event_log.last().unwrap().cutaway,
+            "the placeholder is public"
+        );
+        assert!(state.start_cutaway(&[theron.id.to_string()], "").is_err());
+
+        state.add_event(
+            GameEventType::RollExecuted,
+            "Mira picks the lock".to_string(),
+            Some("Mira".to_string()),
+            None,
+        );
+        state.add_event(
+            GameEventType::RollExecuted,
+            "Theron keeps watch".to_string(),
+            Some("Theron".to_string()),
+            None,
+        );
+        let said = state
+            .chat(&conn.id, ChatChannel::InCharacter, "Almost...")
+            .unwrap();
+        assert!(said.cutaway);
+        assert_eq!(state.event_log.iter().filter(|e| e.cutaway).count(), 2);
+        let rolls = EventLogQuery {
+            event_type: Some("roll_executed".to_string()),
+            ..Default::default()
+        };
+        // Theron's roll stays public; Mira's only shows with the cutaway
+        assert_eq!(state.event_page(&rolls, false).1, 1);
+        assert_eq!(state.event_page(&rolls, true).1, 2);
+
+        let (ended, events) = state.end_cutaway().unwrap();
+        assert_eq!(ended.id, info.id);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message, "Mira picks the lock");
+        assert!(state.event_log.iter().all(|e| !e.cutaway));
+        assert!(state.chat_log.iter().all(|m| !m.cutaway));
+        assert!(state.cutaway.is_none());
+    }
+
+    #[test]
+    fn test_next_session_reminder() {
+        let mut state = GameState::new();
+        assert!(state.clear_next_session().is_err());
+
+        let at = chrono::Utc::now() + chrono::Duration::hours(30);
+        let info = state
+            .schedule_next_session(&at.to_rfc3339(), "Mira hosts")
+            .unwrap();
+        assert_eq!(info.notes, "Mira hosts");
+        assert_eq!(
+            state.event_log.last().unwrap().details.as_deref(),
+            Some("Mira hosts")
+        );
+
+        let lead = chrono::Duration::hours(24);
+        assert!(state.take_due_reminder(chrono::Utc::now(), lead).is_none());
+        let later = chrono::Utc::now() + chrono::Duration::hours(8);
+        assert!(state.take_due_reminder(later, lead).is_some());
+        assert!(
+            state.take_due_reminder(later, lead).is_none(),
+            "the reminder goes out once"
+        );
+
+        // A new date gets its own reminder
+        state
+            .schedule_next_session(&at.to_rfc3339(), "Mira hosts")
+            .unwrap();
+        assert!(state.take_due_reminder(later, lead).is_some());
+
+        state.clear_next_session().unwrap();
+        assert!(state.next_session.is_none());
+    }
+
+    #[test]
+    fn test_notes_private_and_shared() {
+        use crate::protocol::notes::NoteVisibility;
+
+        let mut state = GameState::new();
+        let draft = |title: &str, body: &str, visibility| NoteDraft {
+            title: title.to_string(),
+            body: body.to_string(),
+            visibility,
+        };
+        let secret = state
+            .add_note(&draft(
+                "Vess",
+                "Secretly the baron's sister",
+                NoteVisibility::Gm,
+            ))
+            .unwrap();
+        let shared = state
+            .add_note(&draft(
+                "Thornwood",
+                "Met Vess at the bridge",
+                NoteVisibility::Table,
+            ))
+            .unwrap();
+        assert!(state.add_note(&draft(" ", "", NoteVisibility::Gm)).is_err());
+
+        assert_eq!(state.search_notes("vess", true).len(), 2);
+        let public = state.search_notes("vess", false);
+        assert_eq!(public, vec![shared.clone()]);
+        assert!(state.search_notes("baron", false).is_empty());
+
+        let edited = state
+            .update_note(
+                &shared.id,
+                &draft("Thornwood", "Vess fled", NoteVisibility::Gm),
+            )
+            .unwrap();
+        assert_eq!(edited.created_at, shared.created_at);
+        assert!(state.search_notes("", false).is_empty());
+        assert!(state
+            .update_note("nope", &draft("x", "", NoteVisibility::Gm))
+            .is_err());
+
+        state.delete_note(&secret.id).unwrap();
+        assert!(state.delete_note(&secret.id).is_err());
+        assert_eq!(state.notes.len(), 1);
+    }
+
+    #[test]
+    fn test_quest_board() {
+        use crate::protocol::quests::{CountdownDraft, CountdownKind, QuestStatus};
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let mira =
+            state.create_character("Mira".to_string(), Class::Rogue, Ancestry::Faerie, attrs);
+        let mut draft = QuestDraft {
+            title: "The Missing Caravan".to_string(),
+            description: String::new(),
+            status: QuestStatus::Active,
+            character_ids: vec![mira.id.to_string()],
+            objectives: vec![
+                "Question the innkeeper".to_string(),
+                "Track the wagons".to_string(),
+            ],
+            countdowns: vec![CountdownDraft {
+                label: "The bandits move on".to_string(),
+                kind: CountdownKind::Consequence,
+                length: 2,
+            }],
+        };
+        let quest = state.add_quest(&draft).unwrap();
+        assert_eq!(
+            state.event_log.last().unwrap().message,
+            "📜 New quest: The Missing Caravan"
+        );
+
+        let innkeeper = quest.objectives[0].id.clone();
+        let quest = state
+            .set_objective_completed(&quest.id, &innkeeper, true)
+            .unwrap();
+        assert_eq!(quest.progress(), (1, 2));
+        assert_eq!(
+            state.event_log.last().unwrap().message,
+            "✅ Question the innkeeper"
+        );
+
+        let bandits = quest.countdowns[0].id.clone();
+        state.tick_quest_countdown(&quest.id, &bandits, 1).unwrap();
+        let quest = state.tick_quest_countdown(&quest.id, &bandits, 1).unwrap();
+        assert!(quest.countdowns[0].is_done());
+        assert_eq!(
+            state.event_log.last().unwrap().message,
+            "⏳ Countdown done: The bandits move on"
+        );
+
+        // Rewriting keeps the progress on what's still there
+        draft.objectives.push("Face the bandit chief".to_string());
+        draft.status = QuestStatus::Completed;
+        let quest = state.update_quest(&quest.id, &draft).unwrap();
+        assert_eq!(quest.progress(), (1, 3));
+        assert_eq!(quest.countdowns[0].id, bandits);
+        assert_eq!(quest.countdowns[0].remaining, 0);
+        assert!(state
+            .event_log
+            .last()
+            .unwrap()
+            .message
+            .contains("Completed"));
+
+        draft.character_ids = vec![Uuid::new_v4().to_string()];
+        assert!(state.update_quest(&quest.id, &draft).is_err());
+
+        state.delete_character(&mira.id, false).unwrap();
+        assert!(state.quests[0].character_ids.is_empty());
+        state.remove_quest(&quest.id).unwrap();
+        assert!(state.remove_quest(&quest.id).is_err());
+    }
+
+    #[test]
+    fn test_adversary_numbers_stay_unique_and_renumber() {
+        let mut state = GameState::new();
+        let pos = crate::protocol::Position::new(100.0, 100.0);
+
+        let goblin1 = state.spawn_adversary("goblin", pos).unwrap();
+        let goblin2 = state.spawn_adversary("goblin", pos).unwrap();
+        state.remove_adversary(&goblin1.id);
+        let goblin3 = state.spawn_adversary("goblin", pos).unwrap();
+        assert_eq!(goblin3.name, "Goblin #3", "numbers are not reused");
+
+        let renamed = state.renumber_adversaries(Some("goblin"));
+        assert_eq!(renamed.len(), 2);
+        assert_eq!(state.adversaries[&goblin2.id].name, "Goblin #1");
+        assert_eq!(state.adversaries[&goblin3.id].name, "Goblin #2");
+
+        let goblin4 = state.spawn_adversary("goblin", pos).unwrap();
+        assert_eq!(goblin4.name, "Goblin #3");
+        assert!(state.renumber_adversaries(None).is_empty());
+    }
+
+    #[test]
+    fn test_spawn_invalid_template() {
+        let mut state = GameState::new();
+        let position = crate::protocol::Position::new(100.0, 100.0);
+
+        let result = state.spawn_adversary("invalid_template", position);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Template not found: invalid_template");
+    }
+
+    #[test]
+    fn test_create_custom_adversary() {
+        let mut state = GameState::new();
+        let position = crate::protocol::Position::new(100.0, 100.0);
+
+        let adversary = state
+            .create_custom_adversary(CustomAdversary {
+                name: "Custom Boss".to_string(),
+                position,
+                hp: 10,
+                evasion: 15,
+                armor: 5,
+                attack_modifier: 3,
+                damage_dice: "2d8+3".to_string(),
+            })
+            .unwrap();
+
+        assert_eq!(adversary.name, "Custom Boss");
+        assert_eq!(adversary.template, "custom");
+        assert_eq!(adversary.hp, 10);
+        assert_eq!(adversary.evasion, 15);
+        assert_eq!(adversary.armor, 5);
+        assert_eq!(adversary.attack_modifier, 3);
+        assert_eq!(adversary.damage_dice, "2d8+3");
+
+        assert_eq!(state.adversaries.len(), 1);
+    }
+
+    #[test]
+    fn test_adversary_limit() {
+        let mut state = GameState::new();
+        state.limits.max_adversaries = 1;
+        let position = crate::protocol::Position::new(100.0, 100.0);
+
+        state.spawn_adversary("goblin", position).unwrap();
+        assert!(state.spawn_adversary("goblin", position).is_err());
+        assert!(state
+            .create_custom_adversary(CustomAdversary {
+                name: "Boss".to_string(),
+                position,
+                hp: 10,
+                evasion: 15,
+                armor: 5,
+                attack_modifier: 3,
+                damage_dice: "2d8".to_string(),
+            })
+            .is_err());
+        assert_eq!(state.adversaries.len(), 1);
+    }
+
+    #[test]
+    fn test_event_log_limit() {
+        let mut state = GameState::new();
+        state.limits.max_event_log = 10;
+
+        for i in 0..25 {
+            state.add_event(
+                GameEventType::SystemMessage,
+                format!("Event {}", i),
+                None,
+                None,
+            );
+        }
+
+        assert!(state.event_log.len() <= 10);
+        assert_eq!(state.event_log.last().unwrap().message, "Event 24");
+    }
+
+    #[test]
+    fn test_remove_adversary() {
+        let mut state = GameState::new();
+        let position = crate::protocol::Position::new(100.0, 100.0);
+
+        let adversary = state.spawn_adversary("goblin", position).unwrap();
+        let adversary_id = adversary.id.clone();
+
+        assert_eq!(state.adversaries.len(), 1);
+
+        let removed = state.remove_adversary(&adversary_id);
+        assert!(removed.is_some());
+        assert_eq!(removed.unwrap().id, adversary_id);
+        assert_eq!(state.adversaries.len(), 0);
+
+        // Check event log (spawn + remove)
+        assert_eq!(state.event_log.len(), 2);
+    }
+
+    #[test]
+    fn test_adversary_take_damage_hp_loss() {
+        let position = crate::protocol::Position::new(100.0, 100.0);
+        let mut adversary = Adversary::custom(
+            "Test Enemy".to_string(),
+            position,
+            5, // hp
+            10, // evasion
+            2, // armor
+            1, // attack_modifier
+            "1d6".to_string(),
+        );
+
+        // Deal 1 HP damage
+        let taken_out = adversary.take_damage(1, 0);
+        assert_eq!(adversary.hp, 4);
+        assert_eq!(adversary.stress, 0);
+        assert!(!taken_out);
+        assert!(adversary.is_active);
+    }
+
+    #[test]
+    fn test_adversary_take_damage_stress_gain() {
+        let position = crate::protocol::Position::new(100.0, 100.0);
+        let mut adversary = Adversary::custom(
+            "Test Enemy".to_string(),
+            position,
+            5, // hp
+            10, // evasion
+            2, // armor
+            1, // attack_modifier
+            "1d6".to_string(),
+        );
+
+        // Deal stress damage (scratch)
+        let taken_out = adversary.take_damage(0, 1);
+        assert_eq!(adversary.hp, 5);
+        assert_eq!(adversary.stress, 1);
+        assert!(!taken_out);
+    }
+
+    #[test]
+    fn test_adversary_taken_out() {
+        let position = crate::protocol::Position::new(100.0, 100.0);
+        let mut adversary = Adversary::custom(
+            "Test Enemy".to_string(),
+            position,
+            3, // hp
+            10, // evasion
+            2, // armor
+            1, // attack_modifier
+            "1d6".to_string(),
+        );
+
+        // Reduce HP to 0
+        adversary.take_damage(3, 0);
+        assert_eq!(adversary.hp, 0);
+        assert!(adversary.is_active); // Still active until stress fills
+
+        // Fill stress to max
+        let taken_out = adversary.take_damage(0, 3);
+        assert_eq!(adversary.stress, 3);
+        assert!(taken_out);
+        assert!(!adversary.is_active);
+    }
+
+    #[test]
+    fn test_apply_damage_to_character() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let id = theron.id.to_string();
+        let damage = |hp_lost, stress_gained| DamageBreakdown {
+            raw_damage: 0,
+            damage_type: DamageType::physical(),
+            adjustment: DamageAdjustment::None,
+            adjusted_damage: 0,
+            after_armor: 0,
+            hp_lost,
+            stress_gained,
+        };
+
+        let applied = state.apply_damage(&id, &damage(1, 2)).unwrap();
+        assert_eq!(applied.new_hp, theron.hp_max - 1);
+        assert_eq!(applied.new_stress, 2);
+        let character = &state.characters[&theron.id];
+        assert_eq!(
+            character.hp.current, character.hp_current,
+            "HitPoints stay in sync"
+        );
+
+        // Stress past the last slot marks HP instead
+        state.apply_damage(&id, &damage(0, 4)).unwrap();
+        let applied = state.apply_damage(&id, &damage(0, 1)).unwrap();
+        assert_eq!(applied.new_stress, leveling::BASE_STRESS_SLOTS);
+        assert_eq!(applied.new_hp, theron.hp_max - 2);
+        assert!(!applied.taken_out);
+
+        // Marking the last HP takes the character out
+        let applied = state.apply_damage(&id, &damage(u8::MAX, 0)).unwrap();
+        assert_eq!(applied.new_hp, 0);
+        assert!(applied.taken_out);
+    }
+
+    #[test]
+    fn test_adversary_attack() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+
+        for _ in 0..20 {
+            let attack = state
+                .adversary_attack(&goblin.id, &theron.id, true, false)
+                .unwrap();
+            assert_eq!(attack.rolls.len(), 2);
+            assert_eq!(attack.die, *attack.rolls.iter().max().unwrap());
+            assert_eq!(
+                attack.total,
+                attack.die as i16 + goblin.attack_modifier as i16
+            );
+            assert_eq!(attack.target_evasion, theron.evasion);
+            assert_eq!(
+                attack.hit,
+                attack.is_critical || attack.total >= attack.target_evasion as i16
+            );
+        }
+
+        let attack = state
+            .adversary_attack(&goblin.id, &theron.id, true, true)
+            .unwrap();
+        assert_eq!(attack.rolls.len(), 1, "advantage and disadvantage cancel");
+
+        assert!(state
+            .adversary_attack("missing", &theron.id, false, false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_critical_hits_add_the_dice_maximum() {
+        let mut state = GameState::new();
+
+        let plain = state.roll_damage("theron", "2d6+1", false).unwrap();
+        assert_eq!(plain.critical_bonus, 0);
+        assert_eq!(plain.raw_damage, plain.roll.total);
+
+        let critical = state.roll_damage("theron", "2d6+1", true).unwrap();
+        assert_eq!(critical.critical_bonus, 12);
+        assert_eq!(critical.raw_damage, critical.roll.total + 12);
+
+        assert!(state.roll_damage("theron", "lots", false).is_err());
+    }
+
+    #[test]
+    fn test_critical_hit_comes_from_the_attack_roll() {
+        let mut state = GameState::new();
+        assert!(
+            !state.take_critical_hit("theron", "goblin"),
+            "no attack yet"
+        );
+
+        state.record_attack("theron", "goblin", true);
+        assert!(!state.take_critical_hit("theron", "wolf"));
+        assert!(state.take_critical_hit("theron", "goblin"));
+        assert!(!state.take_critical_hit("theron", "goblin"), "used up");
+
+        // A later ordinary attack replaces the critical
+        state.record_attack("theron", "goblin", true);
+        state.record_attack("theron", "goblin", false);
+        assert!(!state.take_critical_hit("theron", "goblin"));
+    }
+
+    #[test]
+    fn test_start_combat() {
+        let mut state = GameState::new();
+        
+        assert!(state.combat_encounter.is_none());
+
+        let encounter_id = state.start_combat();
+        
+        assert!(state.combat_encounter.is_some());
+        let encounter = state.combat_encounter.as_ref().unwrap();
+        assert_eq!(encounter.id, encounter_id);
+        assert!(encounter.is_active);
+        assert_eq!(encounter.round, 1);
+        assert_eq!(encounter.action_tracker.pc_tokens, 3);
+        assert_eq!(encounter.action_tracker.adversary_tokens, 3);
+        assert_eq!(encounter.action_tracker.queue.len(), 6);
+
+        // Check event log
+        assert_eq!(state.event_log.len(), 1);
+    }
+
+    #[test]
+    fn test_end_combat() {
+        let mut state = GameState::new();
+        
+        state.start_combat();
+        assert!(state.combat_encounter.is_some());
+
+        state.end_combat("victory");
+        assert!(state.combat_encounter.is_none());
+
+        // Check event log (start + end)
+        assert_eq!(state.event_log.len(), 2);
+    }
+
+    #[test]
+    fn test_action_tracker_get_next() {
+        let tracker = ActionTracker::new();
+        
+        // First token should be PC (from initial queue)
+        let next = tracker.get_next();
+        assert!(next.is_some());
+        assert_eq!(next.unwrap(), TokenType::PC);
+    }
+
+    #[test]
+    fn test_action_tracker_add_tokens() {
+        let mut tracker = ActionTracker::new();
+        
+        let initial_pc = tracker.pc_tokens;
+        let initial_adv = tracker.adversary_tokens;
+        let initial_queue_len = tracker.queue.len();
+
+        tracker.add_pc_token();
+        assert_eq!(tracker.pc_tokens, initial_pc + 1);
+        assert_eq!(tracker.queue.len(), initial_queue_len + 1);
+
+        tracker.add_adversary_token();
+        assert_eq!(tracker.adversary_tokens, initial_adv + 1);
+        assert_eq!(tracker.queue.len(), initial_queue_len + 2);
+    }
+
+    #[test]
+    fn test_capture_keyframes() {
+        let mut state = GameState::new();
+        assert!(state.capture_keyframe().is_none(), "no combat");
+
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(100.0, 100.0))
+            .unwrap();
+        let encounter_id = state.start_combat();
+
+        let (id, first) = state.capture_keyframe().unwrap();
+        assert_eq!(id, encounter_id);
+        assert_eq!(first.tokens.len(), 1);
+        assert!(state.capture_keyframe().is_none(), "nothing moved");
+
+        state.adversaries.get_mut(&goblin.id).unwrap().position = Position::new(150.0, 100.0);
+        let (_, second) = state.capture_keyframe().unwrap();
+        assert_eq!(second.tokens[0].position, Position::new(150.0, 100.0));
+
+        state.end_combat("test");
+        assert!(state.capture_keyframe().is_none());
+        assert_eq!(
+            state
+                .combat_recording(&encounter_id)
+                .unwrap()
+                .keyframes
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_spend_tracker_tokens() {
+        let mut state = GameState::new();
+        assert!(state.spend_tracker_token().is_err());
+
+        state.start_combat();
+        let initial = state.get_combat().unwrap().action_tracker.queue.len();
+        for _ in 1..initial {
+            let spend = state.spend_tracker_token().unwrap();
+            assert!(spend.round_advanced.is_none());
+        }
+        assert_eq!(state.get_combat().unwrap().round, 1);
+
+        // The last token refills the tracker and starts round 2
+        let spend = state.spend_tracker_token().unwrap();
+        assert_eq!(spend.token, TokenType::Adversary);
+        assert_eq!(spend.round_advanced.map(|(round, _)| round), Some(2));
+        let encounter = state.get_combat().unwrap();
+        assert_eq!(encounter.round, 2);
+        assert_eq!(encounter.action_tracker.queue.len(), initial);
+    }
+
+    #[test]
+    fn test_advance_tracker() {
+        let mut state = GameState::new();
+        assert!(state.advance_tracker(true).is_err());
+
+        state.start_combat();
+        assert_eq!(state.advance_tracker(true), Ok(TokenType::PC));
+        let tracker = &state.get_combat().unwrap().action_tracker;
+        assert_eq!(tracker.pc_tokens, 2);
+        assert_eq!(tracker.queue.last(), Some(&TokenType::PC));
+
+        state.advance_tracker(true).unwrap();
+        state.advance_tracker(true).unwrap();
+        assert!(state.advance_tracker(true).is_err(), "PC pool is empty");
+
+        state.set_combat_mode(CombatMode::Spotlight).unwrap();
+        assert!(state.advance_tracker(false).is_err());
+    }
+
+    #[test]
+    fn test_update_adversary_hp() {
+        let mut state = GameState::new();
+        let position = crate::protocol::Position::new(100.0, 100.0);
+        
+        let adversary = state.spawn_adversary("goblin", position).unwrap();
+        let adversary_id = adversary.id.clone();
+
+        // Apply damage
+        let result = state.update_adversary_hp(&adversary_id, 1, 0);
+        assert!(result.is_ok());
+        assert!(!result.unwrap()); // Not taken out
+
+        let updated = state.adversaries.get(&adversary_id).unwrap();
+        assert_eq!(updated.hp, 2); // 3 - 1
+    }
+
+    #[test]
+    fn test_get_active_adversaries() {
+        let mut state = GameState::new();
+        let pos1 = crate::protocol::Position::new(100.0, 100.0);
+        let pos2 = crate::protocol::Position::new(200.0, 100.0);
+
+        let goblin1 = state.spawn_adversary("goblin", pos1).unwrap();
+        let goblin2 = state.spawn_adversary("goblin", pos2).unwrap();
+
+        // Both active
+        assert_eq!(state.get_active_adversaries().len(), 2);
+
+        // Take out goblin1
+        state.update_adversary_hp(&goblin1.id, 3, 0).ok(); // Reduce HP to 0
+        state.update_adversary_hp(&goblin1.id, 0, 3).ok(); // Fill stress
+
+        // Only goblin2 active
+        let active = state.get_active_adversaries();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, goblin2.id);
+        assert_eq!(state.get_adversaries().len(), 2); // Both still exist
+    }
+
+    #[test]
+    fn test_all_adversary_templates_valid() {
+        use crate::adversaries::AdversaryTemplate;
+        
+        let templates = AdversaryTemplate::get_all_templates();
+        assert!(!templates.is_empty());
+
+        // Test each template can spawn
+        let mut state = GameState::new();
+        let position = crate::protocol::Position::new(100.0, 100.0);
+
+        for template in templates {
+            let result = state.spawn_adversary(&template.id, position);
+            assert!(result.is_ok(), "Failed to spawn: {}", template.id);
+            
+            let adversary = result.unwrap();
+            assert_eq!(adversary.hp, adversary.max_hp);
+            assert_eq!(adversary.role, template.role);
+            assert!(adversary.is_active);
+
+            // Stay under the adversary quota
+            state.remove_adversary(&adversary.id);
+        }
+    }
+
+    #[test]
+    fn test_homebrew_template_overrides_builtin() {
+        use crate::adversaries::AdversaryTemplate;
+
+        let mut state = GameState::new();
+        let builtin_count = state.adversary_templates().len();
+        state.homebrew_templates = vec![
+            AdversaryTemplate {
+                id: "goblin".to_string(),
+                name: "Bog Goblin".to_string(),
+                tier: 1,
+                role: AdversaryRole::Standard,
+                hp: 2,
+                evasion: 9,
+                armor: 0,
+                attack_modifier: 1,
+                damage: "1d4".to_string(),
+                damage_type: DamageType::physical(),
+                defenses: Defenses::default(),
+                description: String::new(),
+                motives_and_tactics: String::new(),
+            },
+            AdversaryTemplate {
+                id: "wyrmling".to_string(),
+                name: "Wyrmling".to_string(),
+                tier: 2,
+                role: AdversaryRole::Solo,
+                hp: 8,
+                evasion: 13,
+                armor: 3,
+                attack_modifier: 3,
+                damage: "2d8".to_string(),
+                damage_type: DamageType::physical(),
+                defenses: Defenses::default(),
+                description: String::new(),
+                motives_and_tactics: String::new(),
+            },
+        ];
+
+        assert_eq!(state.adversary_templates().len(), builtin_count + 1);
+
+        let position = crate::protocol::Position::new(100.0, 100.0);
+        let goblin = state.spawn_adversary("goblin", position).unwrap();
+        assert_eq!(goblin.max_hp, 2);
+        assert!(state.spawn_adversary("wyrmling", position).is_ok());
+    }
+
+    // ===== Damage Type Tests =====
+
+    #[test]
+    fn test_area_damage_rolls_once_for_everyone() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state.characters.get_mut(&theron.id).unwrap().position = Position::new(10.0, 0.0);
+        let near = state
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+        let far = state
+            .spawn_adversary("goblin", Position::new(500.0, 0.0))
+            .unwrap();
+        let area = HazardArea {
+            center: Position::new(0.0, 0.0),
+            radius: 50.0,
+        };
+
+        // A bad target leaves everyone untouched
+        let missing = vec!["nobody".to_string()];
+        assert!(state
+            .apply_area_damage(
+                "Fireball",
+                Some(&area),
+                &missing,
+                "2d6",
+                DamageType::physical()
+            )
+            .is_err());
+        assert_eq!(state.adversaries[&near.id].hp, near.hp);
+
+        let result = state
+            .apply_area_damage(
+                "Fireball",
+                Some(&area),
+                std::slice::from_ref(&far.id),
+                "2d6",
+                DamageType::physical(),
+            )
+            .unwrap();
+        assert_eq!(result.roll.dice.len(), 2);
+        let mut hit: Vec<_> = result.targets.iter().map(|t| t.target_id.clone()).collect();
+        hit.sort();
+        let mut expected = vec![theron.id.to_string(), near.id.clone(), far.id.clone()];
+        expected.sort();
+        assert_eq!(hit, expected);
+        assert!(result
+            .targets
+            .iter()
+            .all(|t| t.adjusted_damage == result.roll.total));
+        assert!(state
+            .apply_area_damage("Nothing", None, &[], "2d6", DamageType::physical())
+            .is_err());
+    }
+
+    #[test]
+    fn test_resolve_damage_adjustments() {
+        use crate::damage::{DamageKind, DamageTag, Element};
+
+        let fire = DamageType::magical(vec![Element::Fire]);
+        let resistant = Defenses {
+            resistances: vec![DamageTag::Kind(DamageKind::Magical)],
+            ..Defenses::default()
+        };
+        let halved = resolve_damage(9, 0, fire.clone(), &resistant);
+        assert_eq!(halved.raw_damage, 9);
+        assert_eq!(halved.adjustment, DamageAdjustment::Halved);
+        assert_eq!(halved.adjusted_damage, 4);
+
+        let immune = Defenses {
+            immunities: vec![DamageTag::Element(Element::Fire)],
+            ..Defenses::default()
+        };
+        let ignored = resolve_damage(9, 0, fire, &immune);
+        assert_eq!(ignored.adjusted_damage, 0);
+        assert_eq!(ignored.hp_lost, 0);
+        assert_eq!(ignored.stress_gained, 0);
+
+        let plain = resolve_damage(9, 0, DamageType::physical(), &immune);
+        assert_eq!(plain.adjustment, DamageAdjustment::None);
+        assert_eq!(plain.adjusted_damage, 9);
+    }
+
+    #[test]
+    fn test_set_defenses_and_attack_damage_type() {
+        use crate::damage::{DamageTag, Element};
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let id = character.id.to_string();
+
+        let defenses = Defenses {
+            vulnerabilities: vec![DamageTag::Element(Element::Cold)],
+            ..Defenses::default()
+        };
+        state.set_defenses(&id, defenses.clone()).unwrap();
+        assert_eq!(state.defenses_of(&id), defenses);
+        assert!(state.set_defenses("nobody", Defenses::default()).is_err());
+
+        // Unarmed characters deal physical damage
+        assert_eq!(state.attack_damage_type(&id), DamageType::physical());
+
+        let position = crate::protocol::Position::new(100.0, 100.0);
+        let beast = state.spawn_adversary("shadow_beast", position).unwrap();
+        assert_eq!(
+            state.attack_damage_type(&beast.id),
+            DamageType::magical(vec![Element::Necrotic])
+        );
+        assert_eq!(
+            state
+                .defenses_of(&beast.id)
+                .adjustment(&DamageType::physical()),
+            DamageAdjustment::Halved
+        );
+    }
+
+    // ===== Environment Tests =====
+
+    #[test]
+    fn test_environment_feature_activation_spends_fear() {
+        let mut state = GameState::new();
+        let environment = state.spawn_environment("local_tavern").unwrap();
+        assert!(state.spawn_environment("no_such_place").is_err());
+        assert_eq!(environment.to_info().difficulty, 10);
+
+        // Passive features are always on
+        assert!(state
+            .activate_environment_feature(&environment.id, "What's the Talk?")
+            .is_err());
+
+        state.fear_pool = 1;
+        let feature = state
+            .activate_environment_feature(&environment.id, "bar fight!")
+            .unwrap();
+        assert_eq!(feature.fear_cost, 1);
+        assert_eq!(state.fear_pool, 0);
+
+        // Out of Fear
+        assert!(state
+            .activate_environment_feature(&environment.id, "Bar Fight!")
+            .is_err());
+
+        assert!(state.remove_environment(&environment.id).is_some());
+        assert!(state.environments.is_empty());
+    }
+
+    // ===== Aura Tests =====
+
+    fn guarding_aura() -> Aura {
+        Aura {
+            name: "Guardian's Ward".to_string(),
+            radius: 100.0,
+            effect: AuraEffect {
+                evasion: 1,
+                attack: 0,
+            },
+            targets: crate::auras::AuraTargets::Allies,
+            include_self: false,
+        }
+    }
+
+    #[test]
+    fn test_aura_membership_follows_movement() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let guardian = state.create_character(
+            "Theron".to_string(),
+            Class::Guardian,
+            Ancestry::Human,
+            attrs,
+        );
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let ally =
+            state.create_character("Elara".to_string(), Class::Wizard, Ancestry::Faerie, attrs);
+        state
+            .update_character_position(&guardian.id, Position::new(100.0, 100.0))
+            .unwrap();
+        state
+            .update_character_position(&ally.id, Position::new(500.0, 500.0))
+            .unwrap();
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(120.0, 100.0))
+            .unwrap();
+
+        let aura_id = state
+            .attach_aura(&guardian.id.to_string(), guarding_aura())
+            .unwrap();
+        // Owner excluded and goblins are not allies
+        assert!(state.refresh_auras().is_empty());
+
+        let ally_id = ally.id.to_string();
+        let base_evasion = state.effective_evasion(&ally_id).unwrap();
+
+        state
+            .update_character_position(&ally.id, Position::new(150.0, 120.0))
+            .unwrap();
+        let changes = state.refresh_auras();
+        assert_eq!(changes.len(), 1);
+        assert!(
+            matches!(&changes[0], AuraChange::Entered { token_id, .. } if *token_id == ally_id)
+        );
+        assert_eq!(state.effective_evasion(&ally_id), Some(base_evasion + 1));
+        assert_eq!(
+            state.effective_evasion(&goblin.id),
+            Some(goblin.evasion as i32)
+        );
+
+        state
+            .update_character_position(&ally.id, Position::new(500.0, 500.0))
+            .unwrap();
+        let changes = state.refresh_auras();
+        assert!(matches!(&changes[0], AuraChange::Left { .. }));
+        assert_eq!(state.effective_evasion(&ally_id), Some(base_evasion));
+
+        state.detach_aura(&aura_id).unwrap();
+        assert!(state.auras.is_empty());
+    }
+
+    #[test]
+    fn test_aura_removed_with_owner() {
+        let mut state = GameState::new();
+        let ogre = state
+            .spawn_adversary("ogre", Position::new(0.0, 0.0))
+            .unwrap();
+        let mut aura = guarding_aura();
+        aura.targets = crate::auras::AuraTargets::Enemies;
+        state.attach_aura(&ogre.id, aura).unwrap();
+        assert!(state.attach_aura("nobody", guarding_aura()).is_err());
+
+        state.remove_adversary(&ogre.id);
+        let changes = state.refresh_auras();
+        assert!(matches!(&changes[0], AuraChange::Removed { .. }));
+        assert!(state.auras.is_empty());
+    }
+
+    #[test]
+    fn test_spotlight_mode() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let char_id = character.id.to_string();
+
+        assert!(state.set_combat_mode(CombatMode::Spotlight).is_err());
+        state.start_combat();
+        assert!(
+            state.pass_spotlight(Some(&char_id)).is_err(),
+            "tracker mode"
+        );
+
+        state.set_combat_mode(CombatMode::Spotlight).unwrap();
+        let (previous, name) = state.pass_spotlight(Some(&char_id)).unwrap();
+        assert_eq!(previous, None);
+        assert_eq!(name, "Theron");
+        assert_eq!(state.get_combat().unwrap().spotlight, Some(char_id.clone()));
+
+        assert!(state.pass_spotlight(Some("nobody")).is_err());
+        let (previous, name) = state.pass_spotlight(None).unwrap();
+        assert_eq!(previous, Some(char_id));
+        assert_eq!(name, "GM");
+    }
+
+    #[test]
+    fn test_encounter_priced_and_spawned() {
+        let mut state = GameState::new();
+        let entry = |template_id: &str, count| EncounterEntry {
+            template_id: template_id.to_string(),
+            count,
+            positions: Vec::new(),
+        };
+        let mut plan = EncounterPlan {
+            party_size: 4,
+            tier: 1,
+            entries: vec![entry("goblin", 3), entry("giant_rat", 4), entry("ogre", 1)],
+            difficulty: Default::default(),
+            extra_damage: false,
+        };
+        let budget = state.price_encounter(&plan).unwrap();
+        assert_eq!(budget.budget, 14);
+        assert_eq!(budget.spent, 6 + 1 + 5);
+        assert_eq!(budget.lines[2].name, "Ogre");
+
+        plan.tier = 5;
+        assert!(state.price_encounter(&plan).is_err());
+
+        // Nothing spawns if any template is unknown
+        let bad = [entry("goblin", 2), entry("dragon_king", 1)];
+        assert!(state.spawn_encounter(&bad).is_err());
+        assert!(state.adversaries.is_empty());
+
+        let spawned = state.spawn_encounter(&plan.entries).unwrap();
+        assert_eq!(spawned.len(), 8);
+        assert_eq!(state.adversaries.len(), 8);
+        assert!(spawned.iter().all(|a| state.layout.contains(&a.position)));
+    }
+
+    #[test]
+    fn test_encounter_taken_from_map_and_respawned() {
+        let mut state = GameState::new();
+        assert!(state.encounter_from_map().is_err());
+
+        let first = state
+            .spawn_adversary("goblin", Position::new(100.0, 100.0))
+            .unwrap();
+        state
+            .spawn_adversary("goblin", Position::new(200.0, 100.0))
+            .unwrap();
+        state
+            .spawn_adversary("ogre", Position::new(300.0, 300.0))
+            .unwrap();
+        state
+            .create_custom_adversary(CustomAdversary {
+                name: "Cultist".to_string(),
+                position: Position::new(50.0, 50.0),
+                hp: 5,
+                evasion: 10,
+                armor: 2,
+                attack_modifier: 1,
+                damage_dice: "1d6".to_string(),
+            })
+            .unwrap();
+
+        let (plan, skipped) = state.encounter_from_map().unwrap();
+        assert_eq!(skipped, vec!["Cultist".to_string()]);
+        assert_eq!((plan.party_size, plan.tier), (1, 1));
+        assert_eq!(plan.entries.len(), 2);
+        assert_eq!(plan.entries[0].template_id, "goblin");
+        assert_eq!(plan.entries[0].count, 2);
+        assert_eq!(plan.entries[0].positions[0], first.position);
+
+        state.adversaries.clear();
+        let spawned = state.spawn_encounter(&plan.entries).unwrap();
+        let positions: Vec<Position> = spawned.iter().map(|a| a.position).collect();
+        let expected: Vec<Position> = plan
+            .entries
+            .iter()
+            .flat_map(|e| e.positions.iter().copied())
+            .collect();
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn test_adversary_group_acts_as_one() {
+        let mut state = GameState::new();
+        let goblins: Vec<String> = [100.0, 140.0, 180.0]
+            .into_iter()
+            .map(|x| {
+                state
+                    .spawn_adversary("goblin", Position::new(x, 100.0))
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        assert!(state
+            .create_adversary_group("Warband", &goblins[..1])
+            .is_err());
+        let group = state.create_adversary_group("Warband", &goblins).unwrap();
+        assert_eq!(group.members.len(), 3);
+        assert_eq!(group.hp, group.max_hp);
+
+        // Moving the centre keeps the formation
+        let moves = state
+            .move_adversary_group(&group.id, Position::new(400.0, 300.0))
+            .unwrap();
+        assert_eq!(moves[0].1, Position::new(360.0, 300.0));
+        assert_eq!(
+            state.adversaries[&goblins[2]].position,
+            Position::new(440.0, 300.0)
+        );
+        assert!(state
+            .move_adversary_group(&group.id, Position::new(5.0, 5.0))
+            .is_err());
+        assert_eq!(
+            state.adversaries[&goblins[0]].position,
+            Position::new(360.0, 300.0)
+        );
+
+        // Damage dealt to the group hits every member; the group goes down
+        // with its last
+        let area = state
+            .apply_area_damage(
+                "Fireball",
+                None,
+                std::slice::from_ref(&group.id),
+                "2d6",
+                DamageType::physical(),
+            )
+            .unwrap();
+        assert_eq!(area.targets.len(), 3);
+        for id in &goblins {
+            let adversary = state.adversaries.get_mut(id).unwrap();
+            adversary.take_damage(adversary.max_hp, adversary.max_stress);
+        }
+        let changed = state.refresh_adversary_groups();
+        assert_eq!(changed.len(), 1);
+        assert!(!changed[0].is_active);
+        assert!(!state.adversary_groups[&group.id].is_active);
+        assert!(state.refresh_adversary_groups().is_empty());
+
+        // One activation for the whole group
+        let mut state = GameState::new();
+        let ids: Vec<String> = (0..2)
+            .map(|_| {
+                state
+                    .spawn_adversary("goblin", Position::new(200.0, 200.0))
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        let group = state.create_adversary_group("Pair", &ids).unwrap();
+        assert!(
+            state.activate_adversary_group(&group.id).is_err(),
+            "no combat"
+        );
+        state.start_combat();
+        state
+            .get_combat_mut()
+            .unwrap()
+            .action_tracker
+            .queue
+            .insert(0, TokenType::Adversary);
+        let queued = state.get_combat().unwrap().action_tracker.queue.len();
+        let (_, activation) = state.activate_adversary_group(&group.id).unwrap();
+        assert!(matches!(activation, GroupActivation::Tracker(_)));
+        assert_eq!(
+            state.get_combat().unwrap().action_tracker.queue.len(),
+            queued - 1
+        );
+
+        state.set_combat_mode(CombatMode::Spotlight).unwrap();
+        state.activate_adversary_group(&group.id).unwrap();
+        assert_eq!(
+            state.get_combat().unwrap().spotlight.as_deref(),
+            Some(group.members[0].id.as_str())
+        );
+
+        // Removing the group takes its members with it
+        let (name, removed) = state.remove_adversary_group(&group.id).unwrap();
+        assert_eq!((name.as_str(), removed.len()), ("Pair", 2));
+        assert!(state.adversaries.is_empty());
+        assert!(state.adversary_groups.is_empty());
+    }
+
+    #[test]
+    fn test_rounds_keep_their_history() {
+        let mut state = GameState::new();
+        assert!(state.end_round().is_err(), "no combat");
+        state.add_event(
+            GameEventType::CombatAction,
+            "Before the fight".to_string(),
+            None,
+            None,
+        );
+
+        state.start_combat();
+        let action = |state: &mut GameState, who: &str, message: &str| {
+            state.add_event(
+                GameEventType::CombatAction,
+                message.to_string(),
+                Some(who.to_string()),
+                None,
+            )
+        };
+        action(&mut state, "Mira", "Mira attacks Goblin #1");
+        action(&mut state, "Goblin #1", "Goblin #1 took 6 damage");
+        state.spend_tracker_token().unwrap();
+        state.spend_tracker_token().unwrap();
+
+        let (ended, round, _) = state.end_round().unwrap();
+        assert_eq!((ended.round, round), (1, 2));
+        assert_eq!(ended.who_acted(), vec!["Mira", "Goblin #1"]);
+        assert_eq!(ended.actions[0].action, "Mira attacks Goblin #1");
+        let tracker = &state.get_combat().unwrap().action_tracker;
+        assert_eq!(tracker.queue.len(), 6);
+
+        action(&mut state, "Mira", "Mira rolls Agility");
+        let history = state.round_history().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].round, 2);
+        assert_eq!(history[1].actions.len(), 1);
+        assert_eq!(state.get_combat().unwrap().current_round().round, 2);
+    }
+
+    #[test]
+    fn test_marked_scenes_split_the_log() {
+        let mut state = GameState::new();
+        assert!(state.mark_scene("   ").is_err());
+        assert_eq!(
+            state.mark_scene(" The Ambush at Thornwood ").unwrap(),
+            "The Ambush at Thornwood"
+        );
+        state.start_combat();
+        state.end_combat("Victory");
+        state.mark_scene("Camp").unwrap();
+
+        let scenes = state.scene_log(&EventLogQuery::default(), false);
+        assert_eq!(scenes.len(), 2);
+        assert_eq!(scenes[0].scene.as_deref(), Some("The Ambush at Thornwood"));
+        assert!(scenes[0].parts.iter().any(|p| p.combat == Some(1)));
+        assert_eq!(scenes[1].parts[0].events[0].message, "Scene: Camp");
+    }
+
+    #[test]
+    fn test_spending_fear_in_combat() {
+        let mut state = GameState::new();
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+        let interrupt = FearSpend::Interrupt {
+            adversary_id: goblin.id.clone(),
+        };
+        assert!(
+            state.spend_fear(&FearSpend::AdversaryToken).is_err(),
+            "no combat"
+        );
+
+        state.start_combat();
+        state.fear_pool = 2;
+        state.spend_fear(&FearSpend::AdversaryToken).unwrap();
+        let queue = &state.get_combat().unwrap().action_tracker.queue;
+        assert_eq!(
+            (queue.len(), queue.last()),
+            (7, Some(&TokenType::Adversary))
+        );
+
+        let message = state.spend_fear(&interrupt).unwrap();
+        assert!(message.contains(&goblin.name));
+        assert_eq!(state.get_next_actor(), Some(TokenType::Adversary));
+        assert_eq!(state.fear_pool, 0);
+        assert_eq!(
+            state.spend_fear(&interrupt).unwrap_err(),
+            "No Fear to spend"
+        );
+
+        // In spotlight flow the interrupting adversary takes the spotlight
+        state.fear_pool = 1;
+        state.set_combat_mode(CombatMode::Spotlight).unwrap();
+        assert!(state.spend_fear(&FearSpend::AdversaryToken).is_err());
+        state.spend_fear(&interrupt).unwrap();
+        assert_eq!(state.get_combat().unwrap().spotlight, Some(goblin.id));
+    }
+
+    #[test]
+    fn test_healing_rules() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let mira = state.create_character("Mira".to_string(), Class::Bard, Ancestry::Human, attrs);
+        let tend = HealingSource::Downtime {
+            rest: RestKind::Short,
+            downtime_move: DowntimeMove::TendToWounds,
+        };
+        assert_eq!(
+            state.heal(&theron.id, &tend).unwrap_err(),
+            "Theron has no HP marked"
+        );
+
+        let character = state.characters.get_mut(&theron.id).unwrap();
+        character.sync_resources();
+        character.hp_current = 1;
+        character.stress_current = 2;
+        character.restore_resources();
+        let hp_max = character.hp_max;
+
+        let healed = state.heal(&theron.id, &tend).unwrap();
+        let die = healed.roll.as_ref().unwrap().value as u8;
+        assert_eq!(healed.hp_cleared, (die + 1).min(hp_max - 1));
+        assert!(state.event_log.last().unwrap().message.contains("cleared"));
+
+        // An ally's ability clears both, no more than is marked
+        let mend = HealingSource::Ally {
+            ally_id: mira.id.to_string(),
+            ability: "Healing Hands".to_string(),
+            hp: 0,
+            stress: 5,
+        };
+        let before = state.event_log.len();
+        let healed = state.heal(&theron.id, &mend).unwrap();
+        assert_eq!(
+            (healed.stress_cleared, healed.source.as_str()),
+            (2, "Mira's Healing Hands")
+        );
+        assert_eq!(state.event_log.len(), before + 1);
+        assert!(state
+            .heal(
+                &mira.id,
+                &HealingSource::Ally {
+                    ally_id: mira.id.to_string(),
+                    ability: "Healing Hands".to_string(),
+                    hp: 1,
+                    stress: 0,
+                }
+            )
+            .is_err());
+
+        // Hope can't be overspent, and nobody rests mid-fight
+        let hope = state.characters[&theron.id].hope_current;
+        assert!(state
+            .heal(
+                &theron.id,
+                &HealingSource::Hope {
+                    hope: hope + 1,
+                    hp: 1
+                }
+            )
+            .is_err());
+        state.start_combat();
+        let long = HealingSource::Downtime {
+            rest: RestKind::Long,
+            downtime_move: DowntimeMove::TendToWounds,
+        };
+        assert_eq!(
+            state.heal(&theron.id, &long).unwrap_err(),
+            "Can't rest during combat"
+        );
+    }
+
+    #[test]
+    fn test_modifiers_apply_and_expire() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+        let token_id = theron.id.to_string();
+        let modifier = |name: &str, stat, value, duration| Modifier {
+            name: name.to_string(),
+            stat,
+            value,
+            duration,
+        };
+
+        let dazed = modifier("Dazed", ModifierStat::Evasion, -2, ModifierDuration::Scene);
+        state.add_modifier(&token_id, dazed).unwrap();
+        let bless = modifier(
+            "Bless",
+            ModifierStat::Attack,
+            1,
+            ModifierDuration::UntilRest,
+        );
+        state.add_modifier(&token_id, bless.clone()).unwrap();
+        let focus = modifier(
+            "Focus",
+            ModifierStat::Roll,
+            2,
+            ModifierDuration::UntilRemoved,
+        );
+        let focus = state.add_modifier(&token_id, focus).unwrap();
+        assert_eq!(state.effective_evasion(&token_id), Some(theron.evasion - 2));
+        assert_eq!(state.attack_bonus(&token_id), 3);
+        let spec = RollSpec {
+            roll_type: RollType::Action,
+            attribute: None,
+            difficulty: 12,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+        };
+        let preview = state.preview_roll(&[theron.id], &spec).unwrap();
+        assert!(preview[0].conditions.iter().any(|c| c.starts_with("Focus")));
+        assert!(state.add_modifier(&goblin.id, bless).is_err(), "no rests");
+        assert!(state
+            .add_modifier("nobody", focus.modifier.clone())
+            .is_err());
+
+        // A rest ends Bless, a new scene ends Dazed, Focus stays. Preparing
+        // only goes ahead with Hope to gain, so spend some first.
+        let char_mut = state.get_character_mut(&theron.id).unwrap();
+        let _ = char_mut.hope.spend(1);
+        char_mut.sync_resources();
+        let prepare = HealingSource::Downtime {
+            rest: RestKind::Short,
+            downtime_move: DowntimeMove::Prepare,
+        };
+        state.heal(&theron.id, &prepare).unwrap();
+        assert_eq!(state.attack_bonus(&token_id), 2);
+        state.set_map(None);
+        assert_eq!(state.effective_evasion(&token_id), Some(theron.evasion));
+        assert_eq!(state.modifier_list(), vec![focus.clone()]);
+        state.remove_modifier(&focus.id).unwrap();
+        assert!(state.modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_adversary_stress_moves() {
+        let mut state = GameState::new();
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+        let id = goblin.id.as_str();
+
+        assert!(state.mark_adversary_stress(id, 0, None).is_err());
+        assert!(state
+            .mark_adversary_stress(id, goblin.max_stress + 1, None)
+            .is_err());
+        assert!(
+            state
+                .mark_adversary_stress(id, 1, Some(&StressMove::ActAgain))
+                .is_err(),
+            "acting again needs combat"
+        );
+
+        state
+            .mark_adversary_stress(id, 1, Some(&StressMove::ExtraDamageDie))
+            .unwrap();
+        assert_eq!(state.adversaries[id].stress, 1);
+        assert_eq!(state.damage_dice_with_extras(id, "1d8+2"), "1d8+2+1d8");
+        assert_eq!(
+            state.damage_dice_with_extras(id, "1d8+2"),
+            "1d8+2",
+            "used up"
+        );
+
+        state.start_combat();
+        let message = state
+            .mark_adversary_stress(id, 1, Some(&StressMove::ActAgain))
+            .unwrap();
+        assert!(message.contains("act again"));
+        assert_eq!(state.get_next_actor(), Some(TokenType::Adversary));
+        assert_eq!(state.adversaries[id].stress, 2);
+    }
+
+    #[test]
+    fn test_tactics_for_next_token() {
+        let mut state = GameState::new();
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+        assert!(!goblin.motives_and_tactics.is_empty());
+        let custom = Adversary::custom(
+            "Thug".to_string(),
+            Position::new(0.0, 0.0),
+            3,
+            10,
+            1,
+            1,
+            "1d6".to_string(),
+        );
+        assert!(custom.tactics().is_none());
+        state.adversaries.insert(custom.id.clone(), custom);
+
+        assert!(state.tactics_for_next_token().is_empty(), "no combat");
+        state.start_combat();
+        assert!(state.tactics_for_next_token().is_empty(), "PC token first");
+
+        for _ in 0..3 {
+            state.spend_tracker_token().unwrap();
+        }
+        let tactics = state.tactics_for_next_token();
+        assert_eq!(tactics.len(), 1);
+        assert_eq!(tactics[0].adversary_id, goblin.id);
+    }
+
+    // ===== Hazard Tests =====
+
+    fn collapsing_ceiling(cadence: crate::hazards::HazardCadence) -> Hazard {
+        Hazard {
+            name: "Collapsing Ceiling".to_string(),
+            description: String::new(),
+            area: crate::hazards::HazardArea {
+                center: Position::new(0.0, 0.0),
+                radius: 100.0,
+            },
+            cadence,
+            outcome: HazardOutcome::Roll {
+                attribute: "agility".to_string(),
+                difficulty: 13,
+            },
+        }
+    }
+
+    #[test]
+    fn test_hazard_countdown_triggers_on_tokens_in_area() {
+        use crate::hazards::HazardCadence;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let inside =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let outside =
+            state.create_character("Mira".to_string(), Class::Bard, Ancestry::Human, attrs);
+        state
+            .update_character_position(&inside.id, Position::new(50.0, 0.0))
+            .unwrap();
+        state
+            .update_character_position(&outside.id, Position::new(500.0, 0.0))
+            .unwrap();
+
+        let hazard_id = state
+            .add_hazard(collapsing_ceiling(HazardCadence::Countdown { ticks: 2 }))
+            .unwrap();
+
+        assert!(state.advance_round().is_err(), "rounds need combat");
+        state.start_combat();
+
+        let (round, triggers) = state.advance_round().unwrap();
+        assert_eq!(round, 2);
+        assert!(triggers.is_empty());
+
+        let (_, triggers) = state.advance_round().unwrap();
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(
+            triggers[0].targets,
+            vec![(inside.id.to_string(), "Theron".to_string())]
+        );
+        assert_eq!(state.hazards[&hazard_id].ticks_remaining, 2);
+
+        let request = state.hazard_roll_request(&triggers[0]).unwrap();
+        assert_eq!(request.target_character_ids, vec![inside.id]);
+        assert_eq!(request.difficulty, 13);
+        assert!(state.pending_roll_requests.contains_key(&request.id));
+    }
+
+    #[test]
+    fn test_inactive_hazard_does_not_tick() {
+        use crate::hazards::HazardCadence;
+
+        let mut state = GameState::new();
+        let hazard_id = state
+            .add_hazard(collapsing_ceiling(HazardCadence::EachRound))
+            .unwrap();
+        state.start_combat();
+
+        state.set_hazard_active(&hazard_id, false).unwrap();
+        assert!(state.advance_round().unwrap().1.is_empty());
+        assert!(state.tick_hazard(&hazard_id).is_err());
+
+        state.set_hazard_active(&hazard_id, true).unwrap();
+        let trigger = state.tick_hazard(&hazard_id).unwrap().unwrap();
+        assert!(trigger.targets.is_empty());
+        assert!(state.hazard_roll_request(&trigger).is_none());
+
+        state.remove_hazard(&hazard_id).unwrap();
+        assert!(state.hazards.is_empty());
+    }
+
+    #[test]
+    fn test_move_character_crosses_hazards() {
+        use crate::hazards::HazardCadence;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state
+            .update_character_position(&character.id, Position::new(200.0, 300.0))
+            .unwrap();
+        state
+            .add_hazard(collapsing_ceiling(HazardCadence::EachRound))
+            .unwrap();
+
+        // Straight east stays clear of the ceiling at the origin
+        let movement = state
+            .move_character(&character.id, &[], Position::new(400.0, 300.0), None)
+            .unwrap();
+        assert_eq!(movement.path.len(), 2);
+        assert!(movement.crossed_hazards.is_empty());
+
+        // Detour through the corner
+        let movement = state
+            .move_character(
+                &character.id,
+                &[Position::new(20.0, 20.0)],
+                Position::new(400.0, 100.0),
+                None,
+            )
+            .unwrap();
+        assert_eq!(movement.path.len(), 3);
+        assert_eq!(movement.crossed_hazards.len(), 1);
+        assert_eq!(
+            state.get_character(&character.id).unwrap().position,
+            Position::new(400.0, 100.0)
+        );
+
+        assert!(state
+            .move_character(&character.id, &[], Position::new(-5.0, 100.0), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_combat_movement_budget_and_lock() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let other =
+            state.create_character("Mira".to_string(), Class::Rogue, Ancestry::Faerie, attrs);
+        state.select_character(&conn.id, &character.id).unwrap();
+        state
+            .update_character_position(&character.id, Position::new(100.0, 100.0))
+            .unwrap();
+
+        assert_eq!(state.check_can_move(&conn.id, None, true), Ok(character.id));
+        assert!(state
+            .check_can_move(&conn.id, Some(&other.id.to_string()), false)
+            .is_err());
+
+        // 30 ft a turn is 300 units at the default scale
+        state.settings.combat_move_feet = 30;
+        assert_eq!(state.remaining_movement(&character.id), None, "no combat");
+        state.start_combat();
+        assert!(state.check_can_move(&conn.id, None, true).is_err());
+        state
+            .move_character(&character.id, &[], Position::new(300.0, 100.0), None)
+            .unwrap();
+        assert_eq!(state.remaining_movement(&character.id), Some(10.0));
+        assert!(state
+            .move_character(&character.id, &[], Position::new(500.0, 100.0), None)
+            .is_err());
+
+        state.spend_tracker_token().unwrap();
+        assert_eq!(state.remaining_movement(&character.id), Some(30.0));
+        state
+            .move_character(&character.id, &[], Position::new(500.0, 100.0), None)
+            .unwrap();
+
+        state.set_map_locked(true);
+        assert_eq!(
+            state.check_can_move(&conn.id, None, false),
+            Err("The GM has locked the map".to_string())
+        );
+    }
+
+    #[test]
+    fn test_transfer_and_release_control() {
+        let mut state = GameState::new();
+        let phone = state.add_connection();
+        let tablet = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let mira =
+            state.create_character("Mira".to_string(), Class::Rogue, Ancestry::Faerie, attrs);
+        state.select_character(&phone.id, &theron.id).unwrap();
+        state.select_character(&tablet.id, &mira.id).unwrap();
+
+        // The tablet takes Theron as well as Mira
+        assert_eq!(
+            state.transfer_control(&theron.id, &tablet.id),
+            Ok(Some(phone.id))
+        );
+        assert_eq!(state.controller_of(&theron.id), Some(tablet.id));
+        assert_eq!(
+            state.controlled_characters(&tablet.id),
+            [mira.id, theron.id]
+        );
+        assert!(!state.control_mapping.contains_key(&phone.id));
+        assert!(state.transfer_control(&theron.id, &tablet.id).is_err());
+        assert!(state.transfer_control(&theron.id, &Uuid::new_v4()).is_err());
+
+        assert_eq!(state.release_control(&theron.id), Ok(Some(tablet.id)));
+        assert_eq!(state.controller_of(&theron.id), None);
+        assert_eq!(state.release_control(&theron.id), Ok(None));
+        state.select_character(&phone.id, &theron.id).unwrap();
+    }
+
+    #[test]
+    fn test_one_connection_controls_several_characters() {
+        let mut state = GameState::new();
+        let parent = state.add_connection();
+        let other = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let mira =
+            state.create_character("Mira".to_string(), Class::Rogue, Ancestry::Faerie, attrs);
+
+        state.select_character(&parent.id, &theron.id).unwrap();
+        state
+            .add_controlled_character(&parent.id, &mira.id)
+            .unwrap();
+        assert_eq!(
+            state.controlled_characters(&parent.id),
+            [theron.id, mira.id]
+        );
+        assert!(state.add_controlled_character(&other.id, &mira.id).is_err());
+
+        // Without a name, a message acts as the character selected last
+        assert_eq!(state.acting_character(&parent.id, None), Ok(mira.id));
+        let theron_id = theron.id.to_string();
+        assert_eq!(
+            state.acting_character(&parent.id, Some(&theron_id)),
+            Ok(theron.id)
+        );
+        assert!(state.acting_character(&other.id, Some(&theron_id)).is_err());
+        assert_eq!(
+            state.check_can_move(&parent.id, Some(&theron_id), false),
+            Ok(theron.id)
+        );
+
+        // Selecting again (as single-character clients do) switches instead
+        state.select_character(&parent.id, &theron.id).unwrap();
+        assert_eq!(state.controlled_characters(&parent.id), [theron.id]);
+        assert_eq!(state.controller_of(&mira.id), None);
+
+        // Dropping mid-combat ghosts every character the connection had
+        state
+            .add_controlled_character(&parent.id, &mira.id)
+            .unwrap();
+        state.start_combat();
+        state.remove_connection(&parent.id);
+        assert!(state.is_ghosted(&theron.id));
+        assert!(state.is_ghosted(&mira.id));
+    }
+
+    #[test]
+    fn test_gm_sets_resources() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let id = character.id.to_string();
+
+        assert_eq!(
+            state.set_resource(&id, ResourceKind::Hp, -2, true),
+            Ok(character.hp.maximum - 2)
+        );
+        assert_eq!(
+            state.characters[&character.id].hp.current,
+            character.hp.maximum - 2
+        );
+        assert_eq!(
+            state.set_resource(&id, ResourceKind::Hope, 99, false),
+            Ok(character.hope.maximum)
+        );
+        assert_eq!(
+            state.set_resource(&id, ResourceKind::Stress, -3, true),
+            Ok(0)
+        );
+        assert_eq!(
+            state.set_resource(&id, ResourceKind::Stress, 2, false),
+            Ok(2)
+        );
+        assert_eq!(state.characters[&character.id].stress.current, 2);
+
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(100.0, 100.0))
+            .unwrap();
+        assert!(state
+            .set_resource(&goblin.id, ResourceKind::Hope, 1, false)
+            .is_err());
+        state
+            .set_resource(&goblin.id, ResourceKind::Hp, 0, false)
+            .unwrap();
+        state
+            .set_resource(&goblin.id, ResourceKind::Stress, 99, false)
+            .unwrap();
+        assert!(!state.adversaries[&goblin.id].is_active, "taken out");
+        state
+            .set_resource(&goblin.id, ResourceKind::Hp, 1, true)
+            .unwrap();
+        assert!(state.adversaries[&goblin.id].is_active);
+        assert!(state
+            .set_resource("missing", ResourceKind::Hp, 1, false)
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_character() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Thoren".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let changed = state
+            .update_character(
+                &character.id,
+                CharacterEdit {
+                    name: Some(" Theron ".to_string()),
+                    attributes: Some([1, 2, 1, 0, 0, -1]),
+                    level: Some(1),
+                    evasion: Some(12),
+                    color: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(changed, ["name", "attributes", "Evasion"]);
+        let updated = state.get_character(&character.id).unwrap();
+        assert_eq!(updated.name, "Theron");
+        assert_eq!(updated.attributes.strength, 2);
+        assert_eq!(updated.evasion, 12);
+
+        // One bad field changes nothing
+        let bad = CharacterEdit {
+            name: Some("Someone else".to_string()),
+            level: Some(leveling::MAX_LEVEL + 1),
+            ..CharacterEdit::default()
+        };
+        assert!(state.update_character(&character.id, bad).is_err());
+        assert_eq!(state.get_character(&character.id).unwrap().name, "Theron");
+        let too_dark = CharacterEdit {
+            color: Some("#000000".to_string()),
+            ..CharacterEdit::default()
+        };
+        assert!(state.update_character(&character.id, too_dark).is_err());
+        assert_eq!(
+            state.update_character(&character.id, CharacterEdit::default()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_delete_character() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state.select_character(&conn.id, &character.id).unwrap();
+
+        state.start_combat();
+        assert!(state.delete_character(&character.id, false).is_err());
+        assert!(state.get_character(&character.id).is_some());
+        state.end_combat("test");
+
+        let deleted = state.delete_character(&character.id, true).unwrap();
+        assert_eq!(deleted.name, "Theron");
+        assert!(state.get_character(&character.id).is_none());
+        assert!(!state.control_mapping.contains_key(&conn.id));
+        assert!(state.event_log.last().unwrap().message.contains("retired"));
+        assert!(state.delete_character(&character.id, false).is_err());
+    }
+
+    #[test]
+    fn test_gm_moves_and_renames_any_token() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(500.0, 500.0))
+            .unwrap();
+
+        // The GM ignores the movement budget and the lock
+        state.settings.combat_move_feet = 5;
+        state.map_locked = true;
+        state.start_combat();
+        let far = Position::new(750.0, 50.0);
+        assert_eq!(state.move_token(&character.id.to_string(), far), Ok(far));
+        assert_eq!(state.get_character(&character.id).unwrap().position, far);
+        assert_eq!(
+            state.move_token(&goblin.id, Position::new(10.0, 10.0)),
+            Ok(Position::new(10.0, 10.0))
+        );
+        assert!(state
+            .move_token(&goblin.id, Position::new(10.0, 900.0))
+            .is_err());
+        assert!(state.move_token("missing", far).is_err());
+
+        assert_eq!(
+            state.rename_token(&goblin.id, " Goblin Captain "),
+            Ok("Goblin Captain".to_string())
+        );
+        assert_eq!(state.adversaries[&goblin.id].name, "Goblin Captain");
+        assert!(state.rename_token(&goblin.id, " ").is_err());
+        assert!(state
+            .rename_token(&character.id.to_string(), &"x".repeat(41))
+            .is_err());
+    }
+
+    #[test]
+    fn test_token_facing_and_size() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state
+            .update_character_position(&character.id, Position::new(100.0, 100.0))
+            .unwrap();
+
+        // Walking east turns the token to face east
+        let movement = state
+            .move_character(&character.id, &[], Position::new(300.0, 100.0), None)
+            .unwrap();
+        assert!((movement.rotation - 90.0).abs() < 0.01);
+        let movement = state
+            .move_character(&character.id, &[], Position::new(300.0, 300.0), Some(-45.0))
+            .unwrap();
+        assert_eq!(movement.rotation, 315.0);
+        assert_eq!(
+            state.get_character(&character.id).unwrap().token.rotation,
+            315.0
+        );
+
+        let large = TokenDetails {
+            size: tokens::TokenSize::Large,
+            elevation: Some(20),
+            ..TokenDetails::default()
+        };
+        state
+            .set_token_details(&character.id.to_string(), large)
+            .unwrap();
+        state
+            .set_layout(SceneLayout {
+                grid: Some(crate::protocol::maps::GridSettings {
+                    kind: GridKind::Square,
+                    cell_size: 50.0,
+                    offset_x: 0.0,
+                    offset_y: 0.0,
+                }),
+                ..SceneLayout::default()
+            })
+            .unwrap();
+        let movement = state
+            .move_character(&character.id, &[], Position::new(140.0, 160.0), None)
+            .unwrap();
+        assert_eq!(
+            movement.path.last(),
+            Some(&Position::new(150.0, 150.0)),
+            "a large token sits on a grid corner"
+        );
+
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(500.0, 500.0))
+            .unwrap();
+        let turned = TokenDetails {
+            rotation: 450.0,
+            ..TokenDetails::default()
+        };
+        assert_eq!(
+            state
+                .set_token_details(&goblin.id, turned)
+                .unwrap()
+                .rotation,
+            90.0
+        );
+        assert!(state
+            .set_token_details("missing", TokenDetails::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_emote_limits() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        assert!(state.emote(&character.id, "   ", false).is_err());
+        assert!(state
+            .emote(&character.id, &"!".repeat(MAX_EMOTE_LENGTH + 1), false)
+            .is_err());
+
+        assert_eq!(state.emote(&character.id, " 😂 ", true).unwrap(), "😂");
+        assert_eq!(state.event_log.len(), 1);
+
+        // Rate limited
+        assert!(state.emote(&character.id, "again", false).is_err());
+    }
+
+    // ===== Inventory Tests =====
+
+    #[test]
+    fn test_add_and_remove_item() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let item = state
+            .add_item(
+                &character.id,
+                "Torch".to_string(),
+                "Lights the way".to_string(),
+                3,
+            )
+            .unwrap();
+        assert_eq!(item.quantity, 3);
+
+        let removed = state.remove_item(&character.id, &item.id, 1).unwrap();
+        assert_eq!(removed.quantity, 1);
+
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.inventory.items[0].quantity, 2);
+        assert_eq!(state.event_log.len(), 2);
+    }
+
+    #[test]
+    fn test_transfer_item() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let giver =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let receiver =
+            state.create_character("Elara".to_string(), Class::Wizard, Ancestry::Faerie, attrs);
+
+        let item = state
+            .add_item(&giver.id, "Rope".to_string(), "50 feet".to_string(), 1)
+            .unwrap();
+
+        let transferred = state
+            .transfer_item(&giver.id, &receiver.id, &item.id, 1)
+            .unwrap();
+        assert_eq!(transferred.name, "Rope");
+
+        assert!(state
+            .get_character(&giver.id)
+            .unwrap()
+            .inventory
+            .items
+            .is_empty());
+        assert_eq!(
+            state.get_character(&receiver.id).unwrap().inventory.items[0].name,
+            "Rope"
+        );
+
+        // Can't give away what you no longer have
+        assert!(state
+            .transfer_item(&giver.id, &receiver.id, &item.id, 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_award_gold() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let gold = state.award_gold(&character.id, 12).unwrap();
+        assert_eq!(gold.bags, 1);
+        assert_eq!(gold.handfuls, 2);
+
+        assert!(state.award_gold(&Uuid::new_v4(), 1).is_err());
+    }
+
+    // ===== Ghosting Tests =====
+
+    #[test]
+    fn test_disconnect_outside_combat_does_not_ghost() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        state.select_character(&conn.id, &character.id).unwrap();
+        state.remove_connection(&conn.id);
+
+        assert!(!state.is_ghosted(&character.id));
+    }
+
+    #[test]
+    fn test_disconnect_in_combat_ghosts_character() {
+        let mut state = GameState::new();
+        let conn = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        state.select_character(&conn.id, &character.id).unwrap();
+        state.start_combat();
+        state.remove_connection(&conn.id);
+
+        assert!(state.is_ghosted(&character.id));
+
+        // Ghosting ends with the fight
+        state.end_combat("victory");
+        assert!(!state.is_ghosted(&character.id));
+    }
+
+    #[test]
+    fn test_gm_assume_control_and_player_reclaim() {
+        let mut state = GameState::new();
+        let player = state.add_connection();
+        let gm = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        state.select_character(&player.id, &character.id).unwrap();
+        state.start_combat();
+
+        // Can't take over a character whose player is still here
+        assert!(state.assume_control(&gm.id, &character.id).is_err());
+
+        state.remove_connection(&player.id);
+        state.assume_control(&gm.id, &character.id).unwrap();
+        assert_eq!(
+            state.get_controlled_character(&gm.id).unwrap().id,
+            character.id
+        );
+
+        // Player reconnects and reclaims the character
+        let returning = state.add_connection();
+        state
+            .select_character(&returning.id, &character.id)
+            .unwrap();
+
+        assert!(!state.is_ghosted(&character.id));
+        assert!(state.get_controlled_character(&gm.id).is_none());
+        assert_eq!(
+            state.get_controlled_character(&returning.id).unwrap().id,
+            character.id
+        );
+    }
+
+    #[test]
+    fn test_stand_in_disconnect_keeps_character_ghosted() {
+        let mut state = GameState::new();
+        let player = state.add_connection();
+        let gm = state.add_connection();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        state.select_character(&player.id, &character.id).unwrap();
+        state.start_combat();
+        state.remove_connection(&player.id);
+        state.assume_control(&gm.id, &character.id).unwrap();
+        state.remove_connection(&gm.id);
+
+        assert_eq!(state.ghosted_characters.get(&character.id), Some(&None));
+    }
+
+    // ===== Equipment Tests =====
+
+    #[test]
+    fn test_equip_weapon_supplies_trait_and_dice() {
+        use crate::equipment::WeaponRange;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Unarmed
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.weapon_trait_modifier(), 0);
+        assert!(char.weapon_damage_dice().is_none());
+
+        let weapon = Weapon {
+            name: "Longsword".to_string(),
+            trait_name: "strength".to_string(),
+            range: WeaponRange::Melee,
+            damage_dice: "1d10+3".to_string(),
+            damage_type: DamageType::physical(),
+            features: Vec::new(),
+        };
+        state.equip_weapon(&character.id, Some(weapon)).unwrap();
+
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.weapon_trait_modifier(), 1); // Strength
+        assert_eq!(char.weapon_damage_dice(), Some("1d10+3".to_string()));
+
+        state.equip_weapon(&character.id, None).unwrap();
+        let char = state.get_character(&character.id).unwrap();
+        assert!(char.equipment.weapon.is_none());
+    }
+
+    #[test]
+    fn test_weapon_range_checked_against_target() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state
+            .update_character_position(&character.id, Position::new(100.0, 100.0))
+            .unwrap();
+        let goblin = state
+            .spawn_adversary("goblin", Position::new(140.0, 100.0))
+            .unwrap();
+        let attacker_id = character.id.to_string();
+
+        let (feet, band) = state.measure_range(&attacker_id, &goblin.id).unwrap();
+        assert_eq!(feet, 4.0);
+        assert_eq!(band, Some(WeaponRange::Melee));
+        assert!(state.measure_range(&attacker_id, "nobody").is_err());
+
+        // Unarmed attacks aren't checked
+        state.adversaries.get_mut(&goblin.id).unwrap().position = Position::new(700.0, 100.0);
+        assert!(state.check_weapon_range(&attacker_id, &goblin.id).is_ok());
+
+        let weapon = Weapon {
+            name: "Longsword".to_string(),
+            trait_name: "strength".to_string(),
+            range: WeaponRange::Melee,
+            damage_dice: "1d10+3".to_string(),
+            damage_type: DamageType::physical(),
+            features: Vec::new(),
+        };
+        state.equip_weapon(&character.id, Some(weapon)).unwrap();
+        let err = state
+            .check_weapon_range(&attacker_id, &goblin.id)
+            .unwrap_err();
+        assert!(err.contains("out of range"), "{}", err);
+        assert!(err.contains("target is Far"), "{}", err);
+
+        state.adversaries.get_mut(&goblin.id).unwrap().position = Position::new(150.0, 100.0);
+        assert!(state.check_weapon_range(&attacker_id, &goblin.id).is_ok());
+    }
+
+    #[test]
+    fn test_equip_armor() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let mut armor = Armor {
+            name: "Chainmail".to_string(),
+            base_score: 4,
+            major_threshold: 7,
+            severe_threshold: 15,
+            features: Vec::new(),
+        };
+        state
+            .equip_armor(&character.id, Some(armor.clone()))
+            .unwrap();
+        assert_eq!(state.get_character(&character.id).unwrap().armor_score(), 4);
+
+        // Invalid thresholds are rejected and leave the old armor in place
+        armor.major_threshold = 20;
+        assert!(state.equip_armor(&character.id, Some(armor)).is_err());
+        assert_eq!(state.get_character(&character.id).unwrap().armor_score(), 4);
+    }
+
+    // ===== Experience Tests =====
+
+    #[test]
+    fn test_add_and_edit_experience() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        let experience = state
+            .add_experience(&character.id, "Former acrobat".to_string(), None)
+            .unwrap();
+        assert_eq!(experience.modifier, DEFAULT_EXPERIENCE_MODIFIER);
+
+        // Names are unique per character (case insensitive)
+        assert!(state
+            .add_experience(&character.id, "former ACROBAT".to_string(), Some(3))
+            .is_err());
+
+        let edited = state
+            .edit_experience(
+                &character.id,
+                "former acrobat",
+                Some("Circus performer".to_string()),
+                Some(3),
+            )
+            .unwrap();
+        assert_eq!(edited.name, "Circus performer");
+        assert_eq!(edited.modifier, 3);
+
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.experiences.len(), 1);
+        assert!(char.get_experience("Former acrobat").is_none());
+        assert!(state
+            .edit_experience(&character.id, "Unknown", None, Some(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_execute_roll_spending_hope_on_experience() {
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state
+            .add_experience(&character.id, "Former acrobat".to_string(), Some(3))
+            .unwrap();
+
+        let request = PendingRollRequest {
+            id: "test-request".to_string(),
+            target_character_ids: vec![character.id],
+            roll_type: RollType::Action,
+            attribute: Some("agility".to_string()),
+            difficulty: 14,
+            context: "Test roll".to_string(),
+            narrative_stakes: None,
+            situational_modifier: 0,
+            has_advantage: false,
+            has_disadvantage: false,
+            is_combat: false,
+            completed_by: Vec::new(),
+            timestamp: std::time::SystemTime::now(),
+            group: None,
+            tag_team: None,
+            help: Vec::new(),
+            hidden: false,
+        };
+        state
+            .pending_roll_requests
+            .insert("test-request".to_string(), request);
+
+        // Spending Hope requires a matching Experience, and costs nothing if rejected
+        let result = state.execute_roll(&character.id, "test-request", true, None);
+        assert_eq!(result.unwrap_err(), "Choose an Experience to spend Hope on");
+        let result = state.execute_roll(&character.id, "test-request", true, Some("Sailor"));
+        assert!(result.is_err());
+        assert_eq!(state.get_character(&character.id).unwrap().hope.current, 5);
+
+        let roll = state
+            .execute_roll(&character.id, "test-request", true, Some("former acrobat"))
+            .unwrap();
+        assert_eq!(roll.hope_bonus, 3);
+        assert_eq!(roll.total_modifier, 2 + 3); // Agility + Experience
+    }
+
+    // ===== Level Up Tests =====
+
+    #[test]
+    fn test_level_up_flow() {
+        use crate::leveling::{Advancement, AdvancementKind};
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let hp_max = character.hp.maximum;
+
+        // Choices are rejected until the GM marks a level up
+        assert!(state
+            .apply_level_up(&character.id, vec![Advancement::Evasion], None)
+            .is_err());
+
+        let (new_level, options) = state.mark_level_up(&character.id).unwrap();
+        assert_eq!(new_level, 2);
+        assert!(options
+            .iter()
+            .any(|o| o.kind == AdvancementKind::HitPointSlot));
+        assert!(state.mark_level_up(&character.id).is_err());
+
+        // Level 2 is a tier achievement and needs a new Experience
+        let choices = vec![Advancement::Evasion, Advancement::HitPointSlot];
+        assert!(state
+            .apply_level_up(&character.id, choices.clone(), None)
+            .is_err());
+
+        let level = state
+            .apply_level_up(&character.id, choices, Some("Sellsword".to_string()))
+            .unwrap();
+        assert_eq!(level, 2);
+
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.level, 2);
+        assert_eq!(char.hp.maximum, hp_max + 1);
+        assert_eq!(char.evasion, character.evasion + 1);
+        assert!(char.get_experience("Sellsword").is_some());
+        assert!(!char.progression.pending_level_up);
+    }
+
+    #[test]
+    fn test_level_up_rejects_illegal_choices() {
+        use crate::leveling::Advancement;
+
+        let mut state = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character =
+            state.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        state.mark_level_up(&character.id).unwrap();
+
+        // Proficiency is a tier 3 option
+        let result = state.apply_level_up(
+            &character.id,
+            vec![Advancement::Proficiency],
+            Some("Sellsword".to_string()),
+        );
+        assert!(result.is_err());
+
+        // Nothing was applied, so the level up is still pending
+        let char = state.get_character(&character.id).unwrap();
+        assert_eq!(char.level, 1);
+        assert!(char.experiences.is_empty());
+        assert!(char.progression.pending_level_up);
+    }
+
+    #[test]
+    fn test_narrative_mode() {
+        let mut game = GameState::new();
+
+        let line = game
+            .narrate(CombatBeat::Miss, "Theron", "the ogre", None)
+            .expect("Flavorful by default");
+        assert!(line.contains("the ogre"));
+        assert!(!line.contains('{'));
+
+        game.settings.narrative_mode = NarrativeMode::Plain;
+        assert_eq!(
+            game.narrate(CombatBeat::Hit, "Theron", "the ogre", None),
+            None
+        );
+
+        assert!(game
+            .set_narrative_templates(NarrativeTemplates::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! Session history: every logged event and the state change behind it, as
+//! an append-only record that can be replayed to any point
+//!
+//! A history starts with a snapshot of the game (everything a save holds).
+//! Each event after that carries a JSON merge patch (RFC 7396) of what it
+//! changed, and changes that log no event, like a token moving, are written
+//! as bare patches. Replaying applies the patches in order from the last
+//! snapshot before the chosen point, so the state there is exact without
+//! re-running any dice.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::game::{GameEvent, GameState};
+use crate::save::SavedSession;
+
+/// One line of a history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// Position in the history, counting up from 1 across server runs
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub change: HistoryChange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryChange {
+    /// The whole game, written when recording starts
+    Snapshot { state: Value },
+    /// A logged event and what it changed
+    Event {
+        event: GameEvent,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        patch: Option<Value>,
+    },
+    /// A change that logged no event
+    Mutation { patch: Value },
+}
+
+impl HistoryRecord {
+    /// Serialize as a single history line (no trailing newline)
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Read a whole history, skipping blank lines
+///
+/// A last line cut short by a crash is dropped; any other bad line is an
+/// error naming its line number.
+pub fn parse_history(contents: &str) -> Result<Vec<HistoryRecord>, String> {
+    let lines: Vec<_> = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let mut records = Vec::with_capacity(lines.len());
+    for (n, (i, line)) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) if n + 1 == lines.len() => break,
+            Err(e) => return Err(format!("line {}: Invalid history line: {}", i + 1, e)),
+        }
+    }
+    Ok(records)
+}
+
+/// The recorded form of a game: its save, minus the parts that change on
+/// every save
+pub fn state_of(game: &GameState) -> Value {
+    let mut session = SavedSession::from_game_state(game, String::new());
+    session.id = String::new();
+    session.created_at = DateTime::default();
+    session.last_saved = DateTime::default();
+    serde_json::to_value(&session).unwrap_or(Value::Null)
+}
+
+/// The merge patch turning `old` into `new`, `None` if they're equal
+pub fn diff(old: &Value, new: &Value) -> Option<Value> {
+    if old == new {
+        return None;
+    }
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return Some(new.clone());
+    };
+    let mut patch = Map::new();
+    for (key, value) in new {
+        match old.get(key) {
+            Some(previous) => {
+                if let Some(change) = diff(previous, value) {
+                    patch.insert(key.clone(), change);
+                }
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        patch.insert(key.clone(), Value::Null);
+    }
+    Some(Value::Object(patch))
+}
+
+/// Apply a merge patch; `null` removes a field
+pub fn apply(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(fields) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                fields.remove(key);
+            } else {
+                apply(fields.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// The game as it stood at one point in a history
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub session: SavedSession,
+    /// Events since the snapshot it started from, oldest first
+    pub events: Vec<GameEvent>,
+}
+
+/// Rebuild the game at `until` (the latest point when `None`)
+pub fn replay(records: &[HistoryRecord], until: Option<u64>) -> Result<Replay, String> {
+    let mut state = None;
+    let mut events = Vec::new();
+    let mut last = None;
+
+    for record in records
+        .iter()
+        .take_while(|r| until.is_none_or(|until| r.seq <= until))
+    {
+        match &record.change {
+            HistoryChange::Snapshot { state: snapshot } => {
+                state = Some(snapshot.clone());
+                events.clear();
+            }
+            HistoryChange::Event { event, patch } => {
+                events.push(event.clone());
+                if let (Some(state), Some(patch)) = (state.as_mut(), patch) {
+                    apply(state, patch);
+                }
+            }
+            HistoryChange::Mutation { patch } => {
+                if let Some(state) = state.as_mut() {
+                    apply(state, patch);
+                }
+            }
+        }
+        last = Some(record);
+    }
+
+    let (Some(state), Some(last)) = (state, last) else {
+        return Err("No history recorded before that point".to_string());
+    };
+    if until.is_some_and(|until| until > last.seq) {
+        return Err(format!("History only goes up to #{}", last.seq));
+    }
+    let mut session = SavedSession::from_value(state)
+        .map_err(|e| format!("Failed to rebuild the game at #{}: {}", last.seq, e))?;
+    session.name = format!("History #{}", last.seq);
+    session.created_at = last.at;
+    session.last_saved = last.at;
+
+    Ok(Replay {
+        seq: last.seq,
+        at: last.at,
+        session,
+        events,
+    })
+}
+
+impl Replay {
+    /// Put the game back as it was, events included; connections stay
+    pub fn restore_into(&self, game: &mut GameState) -> Result<(), String> {
+        self.session.apply_to_game(game)?;
+        let skip = self.events.len().saturating_sub(game.limits.max_event_log);
+        game.event_log = self.events[skip..].to_vec();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEventType;
+    use daggerheart_engine::character::{Ancestry, Attributes, Class};
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patches() {
+        let old = json!({"a": 1, "b": {"c": 2, "d": 3}, "e": [1, 2]});
+        let new = json!({"a": 1, "b": {"c": 4}, "e": [1], "f": null});
+        let patch = diff(&old, &new).unwrap();
+        assert_eq!(
+            patch,
+            json!({"b": {"c": 4, "d": null}, "e": [1], "f": null})
+        );
+        assert_eq!(diff(&new, &new), None);
+
+        let mut patched = old;
+        apply(&mut patched, &patch);
+        assert_eq!(patched, json!({"a": 1, "b": {"c": 4}, "e": [1]}));
+    }
+
+    #[test]
+    fn test_replay_rebuilds_each_point() {
+        let mut game = GameState::new();
+        let mut records = Vec::new();
+        let mut state = state_of(&game);
+        let mut record = |change| {
+            records.push(HistoryRecord {
+                seq: records.len() as u64 + 1,
+                at: Utc::now(),
+                change,
+            })
+        };
+        record(HistoryChange::Snapshot {
+            state: state.clone(),
+        });
+
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        game.add_event(
+            GameEventType::CharacterCreated,
+            "Theron joined the game".to_string(),
+            Some("Theron".to_string()),
+            None,
+        );
+        let next = state_of(&game);
+        record(HistoryChange::Event {
+            event: game.event_log.last().unwrap().clone(),
+            patch: diff(&state, &next),
+        });
+        state = next;
+
+        game.get_character_mut(&theron.id)
+            .unwrap()
+            .hp
+            .take_damage(2);
+        game.get_character_mut(&theron.id).unwrap().sync_resources();
+        let next = state_of(&game);
+        record(HistoryChange::Mutation {
+            patch: diff(&state, &next).unwrap(),
+        });
+
+        let before = replay(&records, Some(1)).unwrap();
+        assert!(before.session.characters.is_empty());
+
+        let joined = replay(&records, Some(2)).unwrap();
+        assert_eq!(joined.events.len(), 1);
+        assert_eq!(joined.session.characters[0].name, "Theron");
+
+        let latest = replay(&records, None).unwrap();
+        assert_eq!(latest.seq, 3);
+        assert_eq!(
+            latest.session.characters[0].hp_current,
+            joined.session.characters[0].hp_current - 2
+        );
+        assert!(replay(&records, Some(9)).is_err());
+
+        let mut restored = GameState::new();
+        latest.restore_into(&mut restored).unwrap();
+        assert_eq!(restored.get_characters().len(), 1);
+        assert_eq!(restored.event_log.len(), 1);
+    }
+
+    #[test]
+    fn test_torn_last_line_is_dropped() {
+        let line = HistoryRecord {
+            seq: 1,
+            at: Utc::now(),
+            change: HistoryChange::Mutation { patch: json!({}) },
+        }
+        .to_line();
+        let records = parse_history(&format!("{}\n{{\"seq\":2,\"at", line)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(parse_history(&format!("oops\n{}", line)).is_err());
+    }
+}
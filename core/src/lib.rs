@@ -0,0 +1,26 @@
+//! Daggerheart VTT core: game rules, state and the client/server protocol
+//!
+//! Has no web server or WebSocket dependencies, so bots, CLIs and native
+//! clients can embed the same rules the server runs.
+
+pub mod adversaries;
+pub mod cutaway;
+pub mod demo;
+pub mod environments;
+pub mod game;
+pub mod history;
+pub mod limits;
+pub mod narrative;
+pub mod recap;
+pub mod rng;
+pub mod save;
+pub mod schedule;
+pub mod settings;
+pub mod snapshot;
+pub mod validation;
+pub mod visibility;
+
+pub use daggerheart_vtt_protocol as protocol;
+pub use daggerheart_vtt_protocol::{
+    auras, damage, dice, equipment, hazards, inventory, leveling, preferences, subclasses,
+};
@@ -0,0 +1,151 @@
+//! Resource quotas that keep one runaway game from eating all memory and disk
+//! on a shared host
+
+use std::fs;
+use std::path::Path;
+
+/// Environment variables overriding the default quotas
+pub const MAX_CHARACTERS_ENV: &str = "DAGGERHEART_MAX_CHARACTERS";
+pub const MAX_ADVERSARIES_ENV: &str = "DAGGERHEART_MAX_ADVERSARIES";
+pub const MAX_EVENT_LOG_ENV: &str = "DAGGERHEART_MAX_EVENT_LOG";
+pub const MAX_SAVES_ENV: &str = "DAGGERHEART_MAX_SAVES";
+pub const MAX_SCENES_ENV: &str = "DAGGERHEART_MAX_SCENES";
+pub const MAX_UPLOAD_MB_ENV: &str = "DAGGERHEART_MAX_UPLOAD_MB";
+
+/// Quotas enforced whenever the game state is mutated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_characters: usize,
+    pub max_adversaries: usize,
+    /// Oldest events are trimmed once the log grows past this
+    pub max_event_log: usize,
+    /// Save files kept on disk
+    pub max_saves: usize,
+    /// Uploaded maps, each the backdrop of a scene
+    pub max_scenes: usize,
+    /// Disk space the maps, and separately the handouts, may take up, in MB
+    pub max_upload_mb: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_characters: 50,
+            max_adversaries: 100,
+            max_event_log: 500,
+            max_saves: 100,
+            max_scenes: 50,
+            max_upload_mb: 500,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Load quotas from environment variables, falling back to the defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_characters: parse_limit(MAX_CHARACTERS_ENV, defaults.max_characters),
+            max_adversaries: parse_limit(MAX_ADVERSARIES_ENV, defaults.max_adversaries),
+            max_event_log: parse_limit(MAX_EVENT_LOG_ENV, defaults.max_event_log),
+            max_saves: parse_limit(MAX_SAVES_ENV, defaults.max_saves),
+            max_scenes: parse_limit(MAX_SCENES_ENV, defaults.max_scenes),
+            max_upload_mb: parse_limit(MAX_UPLOAD_MB_ENV, defaults.max_upload_mb),
+        }
+    }
+
+    pub fn check_characters(&self, current: usize) -> Result<(), String> {
+        check("Character", current, self.max_characters)
+    }
+
+    pub fn check_adversaries(&self, current: usize) -> Result<(), String> {
+        check("Adversary", current, self.max_adversaries)
+    }
+
+    pub fn check_saves(&self, current: usize) -> Result<(), String> {
+        check("Save file", current, self.max_saves)
+    }
+
+    pub fn check_scenes(&self, current: usize) -> Result<(), String> {
+        check("Scene", current, self.max_scenes)
+    }
+
+    /// Fail if writing `adding` more bytes to an upload directory already
+    /// holding `used` would go over its quota
+    pub fn check_upload(&self, used: u64, adding: usize) -> Result<(), String> {
+        let max = self.max_upload_mb as u64 * 1024 * 1024;
+        if used.saturating_add(adding as u64) > max {
+            return Err(format!(
+                "Upload storage limit reached ({} MB max)",
+                self.max_upload_mb
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Bytes the files directly inside `dir` take up (0 if it doesn't exist)
+pub fn dir_bytes(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Read a positive limit from the environment
+fn parse_limit(var: &str, default: usize) -> usize {
+    std::env::var(var)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&limit| limit > 0)
+        .unwrap_or(default)
+}
+
+/// Fail if adding one more would exceed the limit
+fn check(kind: &str, current: usize, max: usize) -> Result<(), String> {
+    if current >= max {
+        return Err(format!("{} limit reached ({} max)", kind, max));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_limits() {
+        let limits = ResourceLimits {
+            max_characters: 2,
+            ..ResourceLimits::default()
+        };
+
+        assert!(limits.check_characters(1).is_ok());
+        assert_eq!(
+            limits.check_characters(2).unwrap_err(),
+            "Character limit reached (2 max)"
+        );
+    }
+
+    #[test]
+    fn test_check_upload() {
+        let limits = ResourceLimits {
+            max_upload_mb: 1,
+            ..ResourceLimits::default()
+        };
+
+        assert!(limits.check_upload(0, 1024 * 1024).is_ok());
+        assert!(limits.check_upload(1, 1024 * 1024).is_err());
+        assert!(limits.check_upload(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_parse_limit_falls_back_to_default() {
+        assert_eq!(parse_limit("DAGGERHEART_TEST_UNSET_LIMIT", 7), 7);
+    }
+}
@@ -0,0 +1,46 @@
+//! Built-in narrative templates for combat events
+//!
+//! Compiled in from `data/narrative.json`; the GM can replace them at runtime.
+
+use std::sync::OnceLock;
+
+pub use crate::protocol::narrative::{CombatBeat, NarrativeMode, NarrativeTemplates};
+
+/// The built-in combat phrasings
+const BUILTIN_NARRATIVE: &str = include_str!("../data/narrative.json");
+
+/// Get the built-in templates
+pub fn builtin_templates() -> NarrativeTemplates {
+    static TEMPLATES: OnceLock<NarrativeTemplates> = OnceLock::new();
+    TEMPLATES
+        .get_or_init(|| {
+            serde_json::from_str(BUILTIN_NARRATIVE).expect("built-in narrative templates are valid")
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_templates_are_valid() {
+        let templates = builtin_templates();
+        assert!(templates.validate().is_ok());
+
+        for beat in [
+            CombatBeat::Hit,
+            CombatBeat::Miss,
+            CombatBeat::Critical,
+            CombatBeat::TakenOut,
+        ] {
+            for template in templates.for_beat(beat) {
+                assert!(
+                    template.contains("{target}"),
+                    "Template doesn't name the target: {}",
+                    template
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! Session recaps: the public event log written up for the GM to post
+//!
+//! The log is split into sections the way [`events::group_by_scene`] splits
+//! it: each scene the GM marks or environment they set starts a scene, and
+//! each combat is a section of its own, closed by how much damage everyone
+//! took and who was taken out. Out-of-character chat and token moves are
+//! left out, and the log it starts from already leaves out a running
+//! cutaway's events.
+
+use std::collections::BTreeMap;
+
+use crate::protocol::events::{LogMarker, SceneLog, SCENE_PREFIX};
+use crate::protocol::GameEventData;
+
+/// A session write-up, ready to render
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recap {
+    pub title: String,
+    pub sections: Vec<RecapSection>,
+}
+
+/// A scene or a combat encounter
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecapSection {
+    pub heading: String,
+    pub is_combat: bool,
+    pub entries: Vec<RecapEntry>,
+    /// Damage taken, by name
+    pub damage_taken: BTreeMap<String, u32>,
+    /// Who was taken out, in order
+    pub taken_out: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecapEntry {
+    /// A new round of combat
+    Round(u32),
+    Event {
+        /// "HH:MM:SS"
+        time: String,
+        text: String,
+        details: Option<String>,
+        is_roll: bool,
+    },
+}
+
+impl RecapSection {
+    fn new(heading: String, is_combat: bool) -> Self {
+        Self {
+            heading,
+            is_combat,
+            entries: Vec::new(),
+            damage_taken: BTreeMap::new(),
+            taken_out: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Whether an event belongs in a recap: token moves and out-of-character
+/// chat don't
+fn worth_telling(event: &GameEventData) -> bool {
+    match event.event_type.as_str() {
+        "CharacterMoved" => false,
+        "Chat" => !event.message.starts_with("(OOC)"),
+        _ => true,
+    }
+}
+
+/// Damage from a "{target} took {n} damage…" event
+fn damage_taken(event: &GameEventData) -> Option<(String, u32)> {
+    let target = event.character_name.as_ref()?;
+    let rest = event.message.strip_prefix(target.as_str())?;
+    let amount = rest
+        .strip_prefix(" took ")?
+        .split(' ')
+        .next()?
+        .parse()
+        .ok()?;
+    Some((target.clone(), amount))
+}
+
+/// Who a "{target} took … damage" or "{adversary} taken out!" event took out
+fn taken_out(event: &GameEventData) -> Option<String> {
+    if let Some(name) = event.message.strip_suffix(" taken out!") {
+        return Some(name.to_string());
+    }
+    damage_taken(event)?;
+    event
+        .details
+        .as_ref()
+        .filter(|d| d.contains("Taken out"))
+        .and(event.character_name.clone())
+}
+
+impl Recap {
+    /// Write up a log split into scenes, as
+    /// [`GameState::scene_log`](crate::game::GameState::scene_log) gives it
+    pub fn from_scenes(title: impl Into<String>, scenes: Vec<SceneLog>) -> Self {
+        let mut sections: Vec<RecapSection> = Vec::new();
+        for scene in scenes {
+            for (index, part) in scene.parts.into_iter().enumerate() {
+                let heading = match (part.combat, &scene.scene) {
+                    (Some(combat), _) => format!("Combat {}", combat),
+                    (None, Some(name)) if index == 0 => format!("{}{}", SCENE_PREFIX, name),
+                    (None, Some(name)) => format!("{}{} (continued)", SCENE_PREFIX, name),
+                    (None, None) if index == 0 => "Opening".to_string(),
+                    (None, None) => "After the fight".to_string(),
+                };
+                // A fight that ran into the next scene stays one section
+                let same_fight = sections
+                    .last()
+                    .is_some_and(|s| s.is_combat && s.heading == heading);
+                if !same_fight {
+                    sections.push(RecapSection::new(heading, part.combat.is_some()));
+                }
+                let section = sections.last_mut().expect("a section was just pushed");
+
+                for event in part.events.iter().filter(|e| worth_telling(e)) {
+                    if event.marker == Some(LogMarker::CombatStarted) {
+                        continue;
+                    }
+                    if let Some(round) = event
+                        .message
+                        .strip_prefix("Round ")
+                        .and_then(|n| n.parse().ok())
+                        .filter(|_| section.is_combat)
+                    {
+                        section.entries.push(RecapEntry::Round(round));
+                        continue;
+                    }
+                    if let Some((target, amount)) = damage_taken(event) {
+                        *section.damage_taken.entry(target).or_default() += amount;
+                    }
+                    if let Some(name) = taken_out(event) {
+                        section.taken_out.push(name);
+                    }
+                    section.entries.push(RecapEntry::Event {
+                        time: event.timestamp.clone(),
+                        text: event.message.clone(),
+                        details: event.details.clone(),
+                        is_roll: event.event_type == "RollExecuted",
+                    });
+                }
+            }
+        }
+
+        sections.retain(|s| !s.is_empty());
+        Self {
+            title: title.into(),
+            sections,
+        }
+    }
+
+    /// Markdown, for pasting into a forum or chat
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.title);
+        if self.sections.is_empty() {
+            out.push_str("\nNothing happened yet.\n");
+        }
+        for section in &self.sections {
+            let icon = if section.is_combat { "⚔️ " } else { "" };
+            out.push_str(&format!("\n## {}{}\n\n", icon, section.heading));
+            for entry in &section.entries {
+                match entry {
+                    RecapEntry::Round(round) => {
+                        out.push_str(&format!("\n**Round {}**\n\n", round));
+                    }
+                    RecapEntry::Event {
+                        time,
+                        text,
+                        details,
+                        is_roll,
+                    } => {
+                        let icon = if *is_roll { "🎲 " } else { "" };
+                        out.push_str(&format!("- `{}` {}{}", time, icon, text));
+                        if let Some(details) = details {
+                            out.push_str(&format!(" — {}", details));
+                        }
+                        out.push('\n');
+                    }
+                }
+            }
+            if !section.damage_taken.is_empty() {
+                out.push_str(&format!("\n**Damage taken:** {}\n", damage_list(section)));
+            }
+            if !section.taken_out.is_empty() {
+                out.push_str(&format!(
+                    "\n**Taken out:** {}\n",
+                    section.taken_out.join(", ")
+                ));
+            }
+        }
+        out
+    }
+
+    /// A standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+            escape_html(&self.title)
+        );
+        if self.sections.is_empty() {
+            out.push_str("<p>Nothing happened yet.</p>\n");
+        }
+        for section in &self.sections {
+            let icon = if section.is_combat { "⚔️ " } else { "" };
+            out.push_str(&format!(
+                "<h2>{}{}</h2>\n<ul>\n",
+                icon,
+                escape_html(&section.heading)
+            ));
+            for entry in &section.entries {
+                match entry {
+                    RecapEntry::Round(round) => {
+                        out.push_str(&format!("</ul>\n<h3>Round {}</h3>\n<ul>\n", round));
+                    }
+                    RecapEntry::Event {
+                        time,
+                        text,
+                        details,
+                        is_roll,
+                    } => {
+                        let icon = if *is_roll { "🎲 " } else { "" };
+                        out.push_str(&format!(
+                            "<li><code>{}</code> {}{}",
+                            time,
+                            icon,
+                            escape_html(text)
+                        ));
+                        if let Some(details) = details {
+                            out.push_str(&format!(" — <small>{}</small>", escape_html(details)));
+                        }
+                        out.push_str("</li>\n");
+                    }
+                }
+            }
+            out.push_str("</ul>\n");
+            if !section.damage_taken.is_empty() {
+                out.push_str(&format!(
+                    "<p><strong>Damage taken:</strong> {}</p>\n",
+                    escape_html(&damage_list(section))
+                ));
+            }
+            if !section.taken_out.is_empty() {
+                out.push_str(&format!(
+                    "<p><strong>Taken out:</strong> {}</p>\n",
+                    escape_html(&section.taken_out.join(", "))
+                ));
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// "Goblin 12, Theron 5", most damage first
+fn damage_list(section: &RecapSection) -> String {
+    let mut damage: Vec<_> = section.damage_taken.iter().collect();
+    damage.sort_by(|a, b| b.1.cmp(a.1));
+    damage
+        .iter()
+        .map(|(name, amount)| format!("{} {}", name, amount))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEventType, GameState};
+    use crate::protocol::events::EventLogQuery;
+
+    #[test]
+    fn test_recap_sections_and_damage() {
+        let mut state = GameState::new();
+        let event = |state: &mut GameState, kind, message: &str, who: Option<&str>| {
+            state.add_event(kind, message.to_string(), who.map(str::to_string), None);
+        };
+        event(
+            &mut state,
+            GameEventType::SystemMessage,
+            "Theron joined the game",
+            None,
+        );
+        state.mark_scene("Abandoned Grove").unwrap();
+        event(
+            &mut state,
+            GameEventType::Chat,
+            "(OOC) Sam: brb",
+            Some("Theron"),
+        );
+        // Only the marker the GM set starts a scene
+        event(
+            &mut state,
+            GameEventType::Chat,
+            "Scene: a character called Scene talking",
+            Some("Scene"),
+        );
+        state.start_combat();
+        let _ = state.advance_round();
+        event(
+            &mut state,
+            GameEventType::CombatAction,
+            "Theron took 3 damage (1 HP, 0 Stress)",
+            Some("Theron"),
+        );
+        event(
+            &mut state,
+            GameEventType::CombatAction,
+            "Theron took 2 damage (1 HP, 0 Stress)",
+            Some("Theron"),
+        );
+        event(
+            &mut state,
+            GameEventType::CombatAction,
+            "Goblin taken out!",
+            None,
+        );
+        state.end_combat("Victory");
+        event(
+            &mut state,
+            GameEventType::RollExecuted,
+            "Theron rolled 1d20: 14",
+            Some("Theron"),
+        );
+
+        // Someone's off in a cutaway
+        event(
+            &mut state,
+            GameEventType::RollExecuted,
+            "Mira picks the lock",
+            Some("Mira"),
+        );
+        state.event_log.last_mut().unwrap().cutaway = true;
+
+        let scenes = state.scene_log(&EventLogQuery::default(), false);
+        let recap = Recap::from_scenes("Session recap", scenes);
+        let headings: Vec<_> = recap.sections.iter().map(|s| s.heading.as_str()).collect();
+        assert_eq!(
+            headings,
+            [
+                "Opening",
+                "Scene: Abandoned Grove",
+                "Combat 1",
+                "Scene: Abandoned Grove (continued)"
+            ]
+        );
+        let combat = &recap.sections[2];
+        assert!(combat.entries.contains(&RecapEntry::Round(2)));
+        assert_eq!(combat.damage_taken.get("Theron"), Some(&5));
+        assert_eq!(combat.taken_out, ["Goblin"]);
+
+        let markdown = recap.to_markdown();
+        assert!(markdown.contains("## ⚔️ Combat 1"));
+        assert!(markdown.contains("**Damage taken:** Theron 5"));
+        assert!(markdown.contains("🎲 Theron rolled 1d20: 14"));
+        assert!(!markdown.contains("brb"));
+        assert!(!markdown.contains("picks the lock"));
+        assert!(recap.to_html().contains("<h3>Round 2</h3>"));
+    }
+}
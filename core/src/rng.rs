@@ -0,0 +1,106 @@
+//! Where the dice get their randomness
+//!
+//! Every roll the game makes draws from its [`DiceRng`]. That's seeded from
+//! the OS unless a seed is given (`dice_seed` in the server config, or the
+//! GM reseeding the session), and then the same rolls in the same order
+//! come up the same: roll logic can be tested, and a session replayed.
+
+use std::fmt;
+use std::sync::Mutex;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The game's dice
+pub struct DiceRng {
+    seed: Option<u64>,
+    rng: Mutex<StdRng>,
+}
+
+impl DiceRng {
+    /// Unpredictable dice, as at a real table
+    pub fn from_entropy() -> Self {
+        Self {
+            seed: None,
+            rng: Mutex::new(StdRng::from_entropy()),
+        }
+    }
+
+    /// Dice that roll the same sequence every time for the same seed
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// The seed, if the dice were given one
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Draw from the generator, e.g. to roll a dice expression
+    pub fn with<T>(&self, f: impl FnOnce(&mut StdRng) -> T) -> T {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut rng)
+    }
+
+    /// One die with this many sides
+    pub fn die(&self, sides: u8) -> u8 {
+        self.with(|rng| rng.gen_range(1..=sides))
+    }
+
+    /// The Hope and Fear d12s of a duality roll
+    pub fn duality(&self) -> (u8, u8) {
+        self.with(|rng| (rng.gen_range(1..=12), rng.gen_range(1..=12)))
+    }
+}
+
+impl Default for DiceRng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+
+/// A copy carries on from where the original is
+impl Clone for DiceRng {
+    fn clone(&self) -> Self {
+        Self {
+            seed: self.seed,
+            rng: Mutex::new(self.with(|rng| rng.clone())),
+        }
+    }
+}
+
+impl fmt::Debug for DiceRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiceRng").field("seed", &self.seed).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_rolls_the_same() {
+        let rolls = |dice: &DiceRng| -> Vec<u8> {
+            (0..20)
+                .map(|i| {
+                    if i % 2 == 0 {
+                        dice.die(20)
+                    } else {
+                        dice.duality().0
+                    }
+                })
+                .collect()
+        };
+        assert_eq!(rolls(&DiceRng::seeded(7)), rolls(&DiceRng::seeded(7)));
+        assert_ne!(rolls(&DiceRng::seeded(7)), rolls(&DiceRng::seeded(8)));
+
+        let dice = DiceRng::seeded(7);
+        dice.die(6);
+        assert_eq!(dice.clone().duality(), dice.duality());
+        assert!((0..200).all(|_| (1..=12).contains(&dice.duality().1)));
+    }
+}
@@ -0,0 +1,1024 @@
+//! Save/Load system - Phase 5A: Refactored for Character/Connection architecture
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+use daggerheart_engine::character::{Ancestry, Attributes, Class};
+
+use crate::damage::Defenses;
+use crate::equipment::Equipment;
+use crate::game::{
+    Adversary, AdversaryGroup, Character, Experience, GameEvent, GameEventType, GameState,
+};
+use crate::inventory::Inventory;
+use crate::leveling::Progression;
+use crate::protocol::drawings::Drawing;
+use crate::protocol::fog::FogState;
+use crate::protocol::maps::{MapInfo, SceneLayout};
+use crate::protocol::modifiers::TimedModifier;
+use crate::protocol::notes::Note;
+use crate::protocol::quests::Quest;
+use crate::protocol::tokens::TokenDetails;
+use crate::protocol::Position;
+use crate::schedule::ScheduledSession;
+use crate::settings::TableSettings;
+use crate::subclasses::Subclass;
+
+/// Directory saves are kept in unless configured otherwise, relative to the
+/// server's working directory
+pub const SAVES_DIR: &str = "saves";
+
+/// Longest save name, in characters
+pub const MAX_SAVE_NAME_LENGTH: usize = 64;
+
+/// Name given to saves the GM didn't name
+pub const DEFAULT_SAVE_NAME: &str = "Manual Save";
+
+/// Version of the save format this build writes
+///
+/// 1. Everything before versioning (no `schema_version` field)
+/// 2. Adds the adversaries on the map and the Fear pool
+pub const SAVE_SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades a save from one version to the next, indexed by the version it
+/// upgrades from (1 at index 0)
+type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2];
+
+/// Version 1 saves may predate character levels and experiences; fill them
+/// in and say the adversaries and Fear weren't saved
+fn migrate_v1_to_v2(save: &mut serde_json::Value) -> Result<(), String> {
+    let save = save
+        .as_object_mut()
+        .ok_or("Save file is not a JSON object")?;
+    if let Some(characters) = save.get_mut("characters").and_then(|c| c.as_array_mut()) {
+        for character in characters.iter_mut().filter_map(|c| c.as_object_mut()) {
+            character
+                .entry("level")
+                .or_insert(serde_json::json!(default_level()));
+            character
+                .entry("experiences")
+                .or_insert(serde_json::json!([]));
+        }
+    }
+    save.insert("adversaries".to_string(), serde_json::Value::Null);
+    save.insert("fear_pool".to_string(), serde_json::Value::Null);
+    Ok(())
+}
+
+/// Bring a save of any known version up to [`SAVE_SCHEMA_VERSION`]
+fn migrate(save: &mut serde_json::Value) -> Result<(), String> {
+    let version = match save.get("schema_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v >= 1)
+            .ok_or("Save file has an invalid schema_version")?,
+    };
+    if version > SAVE_SCHEMA_VERSION {
+        return Err(format!(
+            "Save file is format version {}, but this server only reads up to version {}; update the server to load it",
+            version, SAVE_SCHEMA_VERSION
+        ));
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migration(save)
+            .map_err(|e| format!("Failed to upgrade save from version {}: {}", from + 1, e))?;
+    }
+    if let Some(save) = save.as_object_mut() {
+        save.insert("schema_version".to_string(), SAVE_SCHEMA_VERSION.into());
+    }
+    Ok(())
+}
+
+/// A save name with surrounding space trimmed, or why it can't be used
+pub fn validate_save_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Save name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_SAVE_NAME_LENGTH {
+        return Err(format!(
+            "Save name is too long (max {} characters)",
+            MAX_SAVE_NAME_LENGTH
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Save name cannot contain control characters".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// The save file a client asked for, as listed by `GET /api/saves` or by
+/// file name alone
+///
+/// Only `.json` files directly inside `dir` resolve; anything reaching
+/// outside it is refused.
+pub fn resolve_save_path(dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    let requested = Path::new(requested);
+    let file = requested.strip_prefix(dir).unwrap_or(requested);
+    let mut components = file.components();
+    let name = match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) => name,
+        _ => return Err("Invalid save path".to_string()),
+    };
+    let path = dir.join(name);
+    let hidden = name.to_string_lossy().starts_with('.');
+    if hidden || path.extension().and_then(|e| e.to_str()) != Some("json") {
+        return Err("Invalid save path".to_string());
+    }
+    Ok(path)
+}
+
+/// A file name stem for a save name: letters, digits, `-` and `_` only
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Saved character data (without runtime resources)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedCharacter {
+    pub id: String,
+    pub name: String,
+    pub class: String,
+    #[serde(default)]
+    pub subclass: Option<Subclass>,
+    pub ancestry: String,
+    pub attributes: [i8; 6],
+    pub hp_current: u8,
+    pub hp_max: u8,
+    pub stress: u8,
+    pub hope_current: u8,
+    pub hope_max: u8,
+    pub evasion: i32,
+    pub position: Position,
+    pub color: String,
+    pub is_npc: bool,
+    /// Facing, size and elevation (absent from older saves)
+    #[serde(default)]
+    pub token: TokenDetails,
+    #[serde(default)]
+    pub adversary_template: Option<String>,
+    #[serde(default = "default_level")]
+    pub level: u8,
+    #[serde(default)]
+    pub experiences: Vec<Experience>,
+    #[serde(default)]
+    pub inventory: Inventory,
+    #[serde(default)]
+    pub equipment: Equipment,
+    #[serde(default)]
+    pub progression: Progression,
+    #[serde(default)]
+    pub defenses: Defenses,
+}
+
+fn default_level() -> u8 {
+    1
+}
+
+/// A saved game session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Save format version, see [`SAVE_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_saved: DateTime<Utc>,
+    pub characters: Vec<SavedCharacter>,
+    /// Per-template adversary spawn counters, so numbering continues after a load
+    #[serde(default)]
+    pub adversary_spawn_counts: HashMap<String, usize>,
+    /// When the table next meets
+    #[serde(default)]
+    pub next_session: Option<ScheduledSession>,
+    /// The table's house rules and options (absent from older saves)
+    #[serde(default)]
+    pub settings: Option<TableSettings>,
+    /// The map on the table
+    #[serde(default)]
+    pub map: Option<MapInfo>,
+    /// The scene's size and grid (absent from older saves)
+    #[serde(default)]
+    pub layout: Option<SceneLayout>,
+    /// Fog of war for each scene, keyed by map id
+    #[serde(default)]
+    pub fog: HashMap<String, FogState>,
+    /// The GM's drawings for each scene, keyed by map id
+    #[serde(default)]
+    pub drawings: HashMap<String, Vec<Drawing>>,
+    /// Adversaries on the map, by id (`None` in version 1 saves, which
+    /// leave the current ones in place)
+    #[serde(default)]
+    pub adversaries: Option<Vec<Adversary>>,
+    /// The GM's Fear (`None` in version 1 saves, which leave it as it is)
+    #[serde(default)]
+    pub fear_pool: Option<u8>,
+    /// Buffs and debuffs still running
+    #[serde(default)]
+    pub modifiers: Vec<TimedModifier>,
+    /// Adversaries run as one, kept with the adversaries
+    #[serde(default)]
+    pub adversary_groups: Vec<AdversaryGroup>,
+    /// The GM's notes and the table's (`None` in older saves, which leave
+    /// the current notes in place)
+    #[serde(default)]
+    pub notes: Option<Vec<Note>>,
+    /// The quest board (`None` in older saves, which leave the current
+    /// board in place)
+    #[serde(default)]
+    pub quests: Option<Vec<Quest>>,
+}
+
+impl SavedCharacter {
+    pub fn from_character(character: &Character) -> Self {
+        Self {
+            id: character.id.to_string(),
+            name: character.name.clone(),
+            class: format!("{:?}", character.class),
+            subclass: character.subclass.clone(),
+            ancestry: format!("{:?}", character.ancestry),
+            attributes: [
+                character.attributes.agility,
+                character.attributes.strength,
+                character.attributes.finesse,
+                character.attributes.instinct,
+                character.attributes.presence,
+                character.attributes.knowledge,
+            ],
+            hp_current: character.hp.current,
+            hp_max: character.hp.maximum,
+            stress: character.stress.current,
+            hope_current: character.hope.current,
+            hope_max: character.hope.maximum,
+            evasion: character.evasion,
+            position: character.position,
+            color: character.color.clone(),
+            is_npc: character.is_npc,
+            token: character.token.clone(),
+            adversary_template: character.adversary_template.clone(),
+            level: character.level,
+            experiences: character.experiences.clone(),
+            inventory: character.inventory.clone(),
+            equipment: character.equipment.clone(),
+            progression: character.progression.clone(),
+            defenses: character.defenses.clone(),
+        }
+    }
+
+    fn to_character(&self) -> Result<Character, String> {
+        let id = Uuid::parse_str(&self.id).map_err(|e| format!("Invalid character ID: {}", e))?;
+
+        let class = match self.class.as_str() {
+            "Bard" => Class::Bard,
+            "Druid" => Class::Druid,
+            "Guardian" => Class::Guardian,
+            "Ranger" => Class::Ranger,
+            "Rogue" => Class::Rogue,
+            "Seraph" => Class::Seraph,
+            "Sorcerer" => Class::Sorcerer,
+            "Warrior" => Class::Warrior,
+            "Wizard" => Class::Wizard,
+            _ => return Err(format!("Invalid class: {}", self.class)),
+        };
+
+        let ancestry = match self.ancestry.as_str() {
+            "Clank" => Ancestry::Clank,
+            "Daemon" => Ancestry::Daemon,
+            "Drakona" => Ancestry::Drakona,
+            "Dwarf" => Ancestry::Dwarf,
+            "Faerie" => Ancestry::Faerie,
+            "Faun" => Ancestry::Faun,
+            "Fungril" => Ancestry::Fungril,
+            "Galapa" => Ancestry::Galapa,
+            "Giant" => Ancestry::Giant,
+            "Goblin" => Ancestry::Goblin,
+            "Halfling" => Ancestry::Halfling,
+            "Human" => Ancestry::Human,
+            "Inferis" => Ancestry::Inferis,
+            "Katari" => Ancestry::Katari,
+            "Orc" => Ancestry::Orc,
+            "Ribbet" => Ancestry::Ribbet,
+            "Simiah" => Ancestry::Simiah,
+            _ => return Err(format!("Invalid ancestry: {}", self.ancestry)),
+        };
+
+        let attributes = Attributes::from_array(self.attributes)
+            .map_err(|e| format!("Invalid attributes: {}", e))?;
+
+        let mut character = if self.is_npc {
+            Character::new_npc(
+                self.name.clone(),
+                class,
+                ancestry,
+                attributes,
+                self.position,
+                self.color.clone(),
+                self.hp_max,
+            )
+        } else {
+            Character::new(
+                self.name.clone(),
+                class,
+                ancestry,
+                attributes,
+                self.position,
+                self.color.clone(),
+            )
+        };
+
+        // Override ID to preserve it
+        character.id = id;
+
+        // Restore resources to saved values
+        character.hp_current = self.hp_current;
+        character.hp_max = self.hp_max;
+        character.stress_current = self.stress;
+        character.hope_current = self.hope_current;
+        character.hope_max = self.hope_max;
+        character.evasion = self.evasion;
+        character.adversary_template = self.adversary_template.clone();
+        character.position = self.position;
+        character.token = self.token.clone();
+        character.level = self.level;
+        character.subclass = self.subclass.clone();
+        character.experiences = self.experiences.clone();
+        character.inventory = self.inventory.clone();
+        character.equipment = self.equipment.clone();
+        character.progression = self.progression.clone();
+        character.defenses = self.defenses.clone();
+
+        character.restore_resources();
+
+        Ok(character)
+    }
+}
+
+impl SavedSession {
+    /// Create a new saved session from game state
+    pub fn from_game_state(game: &GameState, name: String) -> Self {
+        let characters = game
+            .get_characters()
+            .iter()
+            .map(|c| SavedCharacter::from_character(c))
+            .collect();
+
+        let mut adversaries: Vec<_> = game.adversaries.values().cloned().collect();
+        adversaries.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut adversary_groups: Vec<_> = game.adversary_groups.values().cloned().collect();
+        adversary_groups.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            schema_version: SAVE_SCHEMA_VERSION,
+            id: Uuid::new_v4().to_string(),
+            name,
+            created_at: Utc::now(),
+            last_saved: Utc::now(),
+            characters,
+            adversary_spawn_counts: game.adversary_spawn_counts.clone(),
+            next_session: game.next_session.clone(),
+            settings: Some(game.settings.clone()),
+            map: game.map.clone(),
+            layout: Some(game.layout),
+            fog: game.fog.clone(),
+            drawings: game.drawings.clone(),
+            adversaries: Some(adversaries),
+            fear_pool: Some(game.fear_pool),
+            modifiers: game.modifier_list(),
+            adversary_groups,
+            notes: Some(game.notes.clone()),
+            quests: Some(game.quests.clone()),
+        }
+    }
+
+    /// Read a save of any known version, upgrading older ones
+    pub fn from_value(mut save: serde_json::Value) -> Result<Self, String> {
+        migrate(&mut save)?;
+        serde_json::from_value(save).map_err(|e| format!("Failed to parse save file: {}", e))
+    }
+
+    /// Save to a JSON file in `saves_dir`
+    pub fn save_to_file(&self, saves_dir: &Path) -> Result<PathBuf, String> {
+        // Create saves directory if it doesn't exist
+        if !saves_dir.exists() {
+            fs::create_dir_all(saves_dir)
+                .map_err(|e| format!("Failed to create saves directory: {}", e))?;
+        }
+
+        // Generate filename with timestamp
+        let timestamp = self.last_saved.format("%Y%m%d_%H%M%S");
+        let filename = format!("{}_{}.json", file_stem(&self.name), timestamp);
+        let path = saves_dir.join(filename);
+
+        // Serialize and save
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+
+        fs::write(&path, json).map_err(|e| format!("Failed to write save file: {}", e))?;
+
+        Ok(path)
+    }
+
+    /// Load from JSON file
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let json =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read save file: {}", e))?;
+        let save =
+            serde_json::from_str(&json).map_err(|e| format!("Failed to parse save file: {}", e))?;
+
+        Self::from_value(save)
+    }
+
+    /// Give a save a new name; the file stays where it is
+    pub fn rename_file(path: &Path, name: String) -> Result<Self, String> {
+        let mut session = Self::load_from_file(path)?;
+        session.name = name;
+        let json = serde_json::to_string_pretty(&session)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write save file: {}", e))?;
+        Ok(session)
+    }
+
+    /// Delete a save for good
+    pub fn delete_file(path: &Path) -> Result<(), String> {
+        if !path.exists() {
+            return Err("Save not found".to_string());
+        }
+        fs::remove_file(path).map_err(|e| format!("Failed to delete save file: {}", e))
+    }
+
+    /// List all saved sessions in `saves_dir`
+    pub fn list_saves(saves_dir: &Path) -> Result<Vec<(PathBuf, String, DateTime<Utc>)>, String> {
+        if !saves_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(saves_dir)
+            .map_err(|e| format!("Failed to read saves directory: {}", e))?;
+
+        let mut saves = Vec::new();
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Ok(session) = Self::load_from_file(&path) {
+                    saves.push((path, session.name, session.last_saved));
+                }
+            }
+        }
+
+        // Sort by timestamp (newest first)
+        saves.sort_by_key(|save| std::cmp::Reverse(save.2));
+
+        Ok(saves)
+    }
+
+    /// Apply this saved session to a game state
+    /// This replaces all characters but does NOT touch connections
+    pub fn apply_to_game(&self, game: &mut GameState) -> Result<(), String> {
+        // The GM can roll back if this was the wrong save
+        game.create_checkpoint(format!("Before loading {}", self.name));
+
+        // An older save keeps the table's current settings
+        if let Some(settings) = &self.settings {
+            game.set_settings(settings.clone())?;
+        }
+
+        // Clear existing characters
+        game.characters.clear();
+        game.control_mapping.clear(); // Clear control mappings since characters are gone
+        game.ghosted_characters.clear();
+
+        // Restore all characters
+        for saved_char in &self.characters {
+            let character = saved_char.to_character()?;
+            game.characters.insert(character.id, character);
+        }
+
+        // Never lower a counter: adversaries still on the map keep their numbers
+        for (template, count) in &self.adversary_spawn_counts {
+            let current = game
+                .adversary_spawn_counts
+                .entry(template.clone())
+                .or_insert(0);
+            *current = (*current).max(*count);
+        }
+
+        // An older save without a date (or a past one) doesn't replace the one that's set
+        if let Some(session) = self.next_session.as_ref().filter(|s| s.at > Utc::now()) {
+            game.next_session = Some(session.clone());
+        }
+        game.map = self.map.clone();
+        // Older saves only have the map's layout
+        game.layout = self
+            .layout
+            .filter(|layout| layout.validate().is_ok())
+            .or(self.map.as_ref().map(|m| m.layout))
+            .unwrap_or_default();
+        game.fog = self.fog.clone();
+        game.drawings = self.drawings.clone();
+        if let Some(notes) = &self.notes {
+            game.notes = notes.clone();
+        }
+        if let Some(quests) = &self.quests {
+            game.quests = quests.clone();
+        }
+        if let Some(adversaries) = &self.adversaries {
+            game.adversaries = adversaries
+                .iter()
+                .map(|a| (a.id.clone(), a.clone()))
+                .collect();
+            game.adversary_groups = self
+                .adversary_groups
+                .iter()
+                .map(|g| {
+                    let mut group = g.clone();
+                    group
+                        .member_ids
+                        .retain(|id| game.adversaries.contains_key(id));
+                    (group.id.clone(), group)
+                })
+                .filter(|(_, g)| !g.member_ids.is_empty())
+                .collect();
+        }
+        if let Some(fear) = self.fear_pool {
+            game.fear_pool = fear.min(game.settings.max_fear);
+        }
+        game.modifiers = self
+            .modifiers
+            .iter()
+            .filter(|m| {
+                game.adversaries.contains_key(&m.target_id)
+                    || game
+                        .characters
+                        .keys()
+                        .any(|id| id.to_string() == m.target_id)
+            })
+            .map(|m| (m.id.clone(), m.clone()))
+            .collect();
+
+        println!("✅ Loaded {} characters from save", self.characters.len());
+
+        Ok(())
+    }
+}
+
+/// A character on its own, for taking to another table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterExport {
+    /// Save format version the sheet was written with
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub character: SavedCharacter,
+}
+
+impl CharacterExport {
+    pub fn new(character: &Character) -> Self {
+        Self {
+            schema_version: SAVE_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            character: SavedCharacter::from_character(character),
+        }
+    }
+
+    /// Suggested file name: the character's name made safe for a path
+    pub fn file_name(&self) -> String {
+        format!("{}.json", file_stem(&self.character.name))
+    }
+
+    /// Add the character to `game` as it was exported
+    ///
+    /// It comes in uncontrolled, under a new ID if its own is already taken
+    /// at this table.
+    pub fn import_into(&self, game: &mut GameState) -> Result<Character, String> {
+        if self.schema_version > SAVE_SCHEMA_VERSION {
+            return Err(format!(
+                "Character was exported in format version {}, but this server only reads up to version {}; update the server to import it",
+                self.schema_version, SAVE_SCHEMA_VERSION
+            ));
+        }
+        game.limits.check_characters(game.characters.len())?;
+
+        let mut character = self.character.to_character()?;
+        if game.characters.contains_key(&character.id) {
+            character.id = Uuid::new_v4();
+        }
+        game.characters.insert(character.id, character.clone());
+        game.add_event(
+            GameEventType::CharacterCreated,
+            format!("{} joined the game", character.name),
+            Some(character.name.clone()),
+            Some(format!(
+                "Imported: level {} {:?}",
+                character.level, character.class
+            )),
+        );
+        Ok(character)
+    }
+}
+
+/// A save as listed for the GM
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSummary {
+    /// What names the save to the store (a path for files, an id in SQLite)
+    pub slot: String,
+    pub name: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Where saves are kept
+///
+/// Slots come from clients, so each store checks them before touching
+/// anything.
+pub trait SaveStore: Send + Sync {
+    /// Every save, newest first
+    fn list(&self) -> Result<Vec<SaveSummary>, String>;
+    /// Store a new save along with the event log; returns its slot
+    fn save(&self, session: &SavedSession, events: &[GameEvent]) -> Result<String, String>;
+    fn load(&self, slot: &str) -> Result<SavedSession, String>;
+    /// The event log stored with a save, oldest first (empty if the store
+    /// doesn't keep them)
+    fn load_events(&self, _slot: &str) -> Result<Vec<GameEvent>, String> {
+        Ok(Vec::new())
+    }
+    fn rename(&self, slot: &str, name: String) -> Result<SavedSession, String>;
+    fn delete(&self, slot: &str) -> Result<(), String>;
+    /// The directory the saves are files in; `None` for a database, which
+    /// can't be copied file by file while it's open
+    fn files_dir(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// JSON files in a directory, one per save (the default); the event log
+/// isn't kept
+#[derive(Debug, Clone)]
+pub struct FileSaveStore {
+    dir: PathBuf,
+}
+
+impl FileSaveStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Default for FileSaveStore {
+    fn default() -> Self {
+        Self::new(SAVES_DIR)
+    }
+}
+
+impl SaveStore for FileSaveStore {
+    fn list(&self) -> Result<Vec<SaveSummary>, String> {
+        Ok(SavedSession::list_saves(&self.dir)?
+            .into_iter()
+            .map(|(path, name, saved_at)| SaveSummary {
+                slot: path.display().to_string(),
+                name,
+                saved_at,
+            })
+            .collect())
+    }
+
+    fn save(&self, session: &SavedSession, _events: &[GameEvent]) -> Result<String, String> {
+        session
+            .save_to_file(&self.dir)
+            .map(|path| path.display().to_string())
+    }
+
+    fn load(&self, slot: &str) -> Result<SavedSession, String> {
+        SavedSession::load_from_file(&resolve_save_path(&self.dir, slot)?)
+    }
+
+    fn rename(&self, slot: &str, name: String) -> Result<SavedSession, String> {
+        SavedSession::rename_file(&resolve_save_path(&self.dir, slot)?, name)
+    }
+
+    fn delete(&self, slot: &str) -> Result<(), String> {
+        SavedSession::delete_file(&resolve_save_path(&self.dir, slot)?)
+    }
+
+    fn files_dir(&self) -> Option<&Path> {
+        Some(&self.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+    use crate::protocol::notes::NoteDraft;
+    use crate::protocol::quests::QuestDraft;
+
+    #[test]
+    fn test_save_and_load() {
+        let mut game = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+
+        // Create a character
+        let character =
+            game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+
+        // Save session
+        let session = SavedSession::from_game_state(&game, "Test Session".to_string());
+        assert_eq!(session.characters.len(), 1);
+        assert_eq!(session.characters[0].name, "Theron");
+
+        // Modify character resources
+        let char_mut = game.get_character_mut(&character.id).unwrap();
+        char_mut.hp.take_damage(2);
+        char_mut.stress.gain(1);
+        char_mut.sync_resources();
+
+        // Save again
+        let session2 = SavedSession::from_game_state(&game, "Modified Session".to_string());
+        assert!(session2.characters[0].hp_current < session2.characters[0].hp_max);
+        assert!(session2.characters[0].stress > 0);
+    }
+
+    #[test]
+    fn test_save_paths_stay_in_the_saves_directory() {
+        let dir = Path::new(SAVES_DIR);
+        let listed = resolve_save_path(dir, "saves/Manual_Save_20240101_120000.json").unwrap();
+        assert_eq!(listed, Path::new("saves/Manual_Save_20240101_120000.json"));
+        assert_eq!(
+            resolve_save_path(dir, "Manual_Save_20240101_120000.json").unwrap(),
+            listed
+        );
+
+        for path in [
+            "../Cargo.toml",
+            "saves/../secrets.json",
+            "/etc/passwd.json",
+            "saves/nested/save.json",
+            "saves/.hidden.json",
+            "saves/notes.txt",
+            "",
+        ] {
+            assert!(resolve_save_path(dir, path).is_err(), "{} resolved", path);
+        }
+
+        assert_eq!(
+            file_stem("Session 3: ../the Vault"),
+            "Session_3_____the_Vault"
+        );
+        assert_eq!(
+            validate_save_name("  Before the heist ").unwrap(),
+            "Before the heist"
+        );
+        assert!(validate_save_name("   ").is_err());
+        assert!(validate_save_name(&"x".repeat(MAX_SAVE_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_older_saves_are_upgraded() {
+        let v1 = serde_json::json!({
+            "id": "s-1",
+            "name": "Old",
+            "created_at": "2024-01-01T12:00:00Z",
+            "last_saved": "2024-01-01T12:00:00Z",
+            "characters": [{
+                "id": Uuid::new_v4().to_string(),
+                "name": "Theron",
+                "class": "Warrior",
+                "ancestry": "Human",
+                "attributes": [2, 1, 1, 0, 0, -1],
+                "hp_current": 6,
+                "hp_max": 6,
+                "stress": 0,
+                "hope_current": 2,
+                "hope_max": 6,
+                "evasion": 12,
+                "position": {"x": 0.0, "y": 0.0},
+                "color": "#3b82f6",
+                "is_npc": false
+            }]
+        });
+        let session = SavedSession::from_value(v1).unwrap();
+        assert_eq!(session.schema_version, SAVE_SCHEMA_VERSION);
+        assert_eq!(session.characters[0].level, 1);
+        assert!(session.adversaries.is_none());
+
+        // A version 1 save leaves the Fear pool alone
+        let mut game = GameState::new();
+        game.fear_pool = 3;
+        session.apply_to_game(&mut game).unwrap();
+        assert_eq!(game.fear_pool, 3);
+
+        let future = serde_json::json!({"schema_version": SAVE_SCHEMA_VERSION + 1});
+        let err = SavedSession::from_value(future).unwrap_err();
+        assert!(err.contains("update the server"), "{}", err);
+    }
+
+    #[test]
+    fn test_adversaries_and_fear_are_saved() {
+        let mut game = GameState::new();
+        game.spawn_adversary("goblin", Position::new(10.0, 10.0))
+            .unwrap();
+        game.fear_pool = 4;
+        game.add_note(&NoteDraft {
+            title: "Goblin camp".to_string(),
+            body: "Their chief is hiding the map".to_string(),
+            visibility: Default::default(),
+        })
+        .unwrap();
+        game.add_quest(&QuestDraft {
+            title: "Raid the camp".to_string(),
+            description: String::new(),
+            status: Default::default(),
+            character_ids: Vec::new(),
+            objectives: vec!["Find the map".to_string()],
+            countdowns: Vec::new(),
+        })
+        .unwrap();
+
+        let json = serde_json::to_value(SavedSession::from_game_state(&game, "Fight".to_string()))
+            .unwrap();
+        let session = SavedSession::from_value(json).unwrap();
+
+        let mut loaded = GameState::new();
+        session.apply_to_game(&mut loaded).unwrap();
+        assert_eq!(loaded.adversaries.len(), 1);
+        assert_eq!(loaded.fear_pool, 4);
+        assert_eq!(loaded.notes, game.notes);
+        assert_eq!(loaded.quests, game.quests);
+    }
+
+    #[test]
+    fn test_apply_to_game() {
+        let mut game = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+
+        // Create characters
+        game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        game.create_character("Elara".to_string(), Class::Wizard, Ancestry::Faerie, attrs);
+        game.settings.player_whispers = false;
+
+        // Save
+        let session = SavedSession::from_game_state(&game, "Test".to_string());
+
+        // Create new game state and apply
+        let mut new_game = GameState::new();
+        session.apply_to_game(&mut new_game).unwrap();
+
+        assert_eq!(new_game.character_count(), 2);
+        assert_eq!(new_game.get_player_characters().len(), 2);
+        assert!(!new_game.settings.player_whispers);
+    }
+
+    #[test]
+    fn test_character_round_trip() {
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let mut character = Character::new(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            attrs,
+            Position::new(100.0, 200.0),
+            "#3b82f6".to_string(),
+        );
+
+        // Modify resources
+        character.hp.take_damage(3);
+        character.stress.gain(2);
+        let _ = character.hope.spend(1);
+        character.sync_resources();
+        character
+            .inventory
+            .add_item("Torch".to_string(), "Lights the way".to_string(), 2);
+        character.inventory.gold.add_handfuls(15);
+        character
+            .experiences
+            .push(Experience::new("Former acrobat".to_string(), 2));
+        character.token.rotation = 180.0;
+        character.token.note = Some("Prone".to_string());
+
+        // Convert to saved character and back
+        let saved = SavedCharacter::from_character(&character);
+        let restored = saved.to_character().unwrap();
+
+        assert_eq!(restored.name, character.name);
+        assert_eq!(restored.hp.current, character.hp.current);
+        assert_eq!(restored.stress.current, character.stress.current);
+        assert_eq!(restored.hope.current, character.hope.current);
+        assert_eq!(restored.position.x, character.position.x);
+        assert_eq!(restored.position.y, character.position.y);
+        assert_eq!(restored.inventory, character.inventory);
+        assert_eq!(restored.experiences, character.experiences);
+        assert_eq!(restored.token, character.token);
+    }
+
+    #[test]
+    fn test_leveled_character_round_trip() {
+        use crate::leveling::Advancement;
+
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let mut character = Character::new(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            attrs,
+            Position::new(100.0, 200.0),
+            "#3b82f6".to_string(),
+        );
+        character.subclass = Some(Subclass::new("Warrior", "Call of the Brave", 1).unwrap());
+        character.progression.pending_level_up = true;
+        character
+            .apply_level_up(
+                vec![
+                    Advancement::TraitBoost {
+                        traits: vec!["agility".to_string(), "knowledge".to_string()],
+                    },
+                    Advancement::HitPointSlot,
+                ],
+                Some("Sellsword".to_string()),
+            )
+            .unwrap();
+
+        let saved = SavedCharacter::from_character(&character);
+        let restored = saved.to_character().unwrap();
+
+        assert_eq!(restored.level, 2);
+        assert_eq!(restored.get_attribute("agility"), Some(3));
+        assert_eq!(restored.hp.maximum, character.hp.maximum);
+        assert_eq!(restored.progression, character.progression);
+        assert_eq!(restored.subclass, character.subclass);
+    }
+
+    #[test]
+    fn test_npc_round_trip() {
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let mut npc = Character::new_npc(
+            "Goblin".to_string(),
+            Class::Rogue,
+            Ancestry::Goblin,
+            attrs,
+            Position::new(50.0, 50.0),
+            "#ff0000".to_string(),
+            8,
+        );
+
+        npc.hp.take_damage(2);
+        npc.sync_resources();
+
+        let saved = SavedCharacter::from_character(&npc);
+        let restored = saved.to_character().unwrap();
+
+        assert!(restored.is_npc);
+        assert_eq!(restored.name, "Goblin");
+        assert_eq!(restored.hp.current, 6); // 8 - 2
+    }
+
+    #[test]
+    fn test_character_import_regenerates_taken_ids() {
+        let mut game = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        game.get_character_mut(&theron.id)
+            .unwrap()
+            .experiences
+            .push(Experience::new("Former acrobat".to_string(), 2));
+
+        let json = serde_json::to_string(&CharacterExport::new(
+            game.get_character(&theron.id).unwrap(),
+        ))
+        .unwrap();
+        let export: CharacterExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(export.file_name(), "Theron.json");
+
+        let imported = export.import_into(&mut game).unwrap();
+        assert_ne!(imported.id, theron.id);
+        assert_eq!(imported.experiences.len(), 1);
+        assert_eq!(game.get_characters().len(), 2);
+
+        let mut elsewhere = GameState::new();
+        assert_eq!(export.import_into(&mut elsewhere).unwrap().id, theron.id);
+
+        let future = CharacterExport {
+            schema_version: SAVE_SCHEMA_VERSION + 1,
+            ..export
+        };
+        assert!(future.import_into(&mut elsewhere).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! The next scheduled session
+//!
+//! Kept on the game state so the GM manages it from the same screen as
+//! everything else, and saved with the campaign.
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::schedule::{NextSession, MAX_SESSION_NOTES_LENGTH};
+
+/// When the table next meets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledSession {
+    /// Kept in the offset it was entered in, so it reads back the same way
+    pub at: DateTime<FixedOffset>,
+    #[serde(default)]
+    pub notes: String,
+    /// Whether the reminder has gone out
+    #[serde(skip)]
+    pub reminded: bool,
+}
+
+impl ScheduledSession {
+    /// Parse an RFC 3339 time such as "2026-11-07T19:00:00-05:00"
+    pub fn new(scheduled_at: &str, notes: &str, now: DateTime<Utc>) -> Result<Self, String> {
+        let at = DateTime::parse_from_rfc3339(scheduled_at.trim()).map_err(|_| {
+            format!(
+                "Invalid session time '{}' (expected e.g. 2026-11-07T19:00:00-05:00)",
+                scheduled_at
+            )
+        })?;
+        if at <= now {
+            return Err("Session time is in the past".to_string());
+        }
+
+        let notes = notes.trim();
+        if notes.chars().count() > MAX_SESSION_NOTES_LENGTH {
+            return Err(format!(
+                "Session notes are too long (max {} characters)",
+                MAX_SESSION_NOTES_LENGTH
+            ));
+        }
+
+        Ok(Self {
+            at,
+            notes: notes.to_string(),
+            reminded: false,
+        })
+    }
+
+    pub fn to_info(&self) -> NextSession {
+        NextSession {
+            scheduled_at: self.at.to_rfc3339(),
+            notes: self.notes.clone(),
+        }
+    }
+
+    /// Whether it's within `lead` of starting and no reminder has gone out
+    pub fn reminder_due(&self, now: DateTime<Utc>, lead: Duration) -> bool {
+        !self.reminded && now < self.at && now + lead >= self.at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduled_session() {
+        let now = DateTime::parse_from_rfc3339("2026-11-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let session =
+            ScheduledSession::new(" 2026-11-07T19:00:00-05:00 ", " Bring snacks ", now).unwrap();
+        assert_eq!(session.notes, "Bring snacks");
+        assert_eq!(session.to_info().scheduled_at, "2026-11-07T19:00:00-05:00");
+
+        assert!(ScheduledSession::new("next friday", "", now).is_err());
+        assert!(ScheduledSession::new("2026-10-31T19:00:00Z", "", now).is_err());
+        assert!(ScheduledSession::new(
+            "2026-11-07T19:00:00Z",
+            &"x".repeat(MAX_SESSION_NOTES_LENGTH + 1),
+            now
+        )
+        .is_err());
+
+        // Session starts 2026-11-08 00:00 UTC
+        let lead = Duration::hours(24);
+        assert!(!session.reminder_due(now, lead));
+        assert!(session.reminder_due(now + Duration::days(6) + Duration::hours(1), lead));
+        assert!(
+            !session.reminder_due(now + Duration::days(7), lead),
+            "no reminder once it's started"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//! Default table settings from the server's config file
+//!
+//! The file is TOML and may set any of the [`TableSettings`] fields; the
+//! rest keep their built-in defaults. The GM can change them all during
+//! play, and saves carry the table's settings with them.
+
+use std::fs;
+use std::path::Path;
+
+pub use crate::protocol::settings::{TableSettings, TableSettingsPatch};
+
+/// Path of the settings file
+pub const SETTINGS_FILE_ENV: &str = "DAGGERHEART_SETTINGS";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_SETTINGS_FILE: &str = "settings.toml";
+
+/// Read the settings file, falling back to the built-in defaults without one
+pub fn load_defaults(path: &Path) -> Result<TableSettings, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(TableSettings::default()),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let settings: TableSettings =
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    settings
+        .validate()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::narrative::NarrativeMode;
+
+    #[test]
+    fn test_load_defaults() {
+        let dir = std::env::temp_dir().join(format!("settings-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.toml");
+
+        assert_eq!(load_defaults(&path).unwrap(), TableSettings::default());
+
+        fs::write(&path, "narrative_mode = \"plain\"\nmax_fear = 10\n").unwrap();
+        let settings = load_defaults(&path).unwrap();
+        assert_eq!(settings.narrative_mode, NarrativeMode::Plain);
+        assert_eq!(settings.max_fear, 10);
+        assert!(settings.player_whispers);
+
+        fs::write(&path, "starting_fear = 20\n").unwrap();
+        assert!(load_defaults(&path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
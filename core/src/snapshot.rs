@@ -0,0 +1,302 @@
+//! Catch-up snapshots of a session in progress
+//!
+//! [`SnapshotBuilder`] assembles a [`SessionSnapshot`] from the game state.
+//! The WebSocket handshake and the REST API both use it, so every transport
+//! catches a late joiner up the same way.
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::game::GameState;
+use crate::protocol::groups::AdversaryGroupInfo;
+use crate::protocol::snapshot::{
+    CombatSnapshot, HazardSnapshot, NpcInfo, OpenRollRequest, PartyMember, SessionSnapshot,
+};
+use crate::protocol::{ConnectionRole, ResourceData};
+
+/// Events included in a catch-up snapshot by default
+pub const CATCH_UP_EVENTS: usize = 50;
+
+/// Builds a catch-up snapshot for one joiner
+pub struct SnapshotBuilder<'a> {
+    game: &'a GameState,
+    /// Characters to scope open roll requests to; empty for all of them
+    character_ids: Vec<Uuid>,
+    event_count: usize,
+    /// Tokens under the fog, left out unless it's for the GM
+    fogged: HashSet<String>,
+}
+
+impl<'a> SnapshotBuilder<'a> {
+    pub fn new(game: &'a GameState) -> Self {
+        Self {
+            game,
+            character_ids: Vec::new(),
+            event_count: CATCH_UP_EVENTS,
+            fogged: game.fogged_tokens(),
+        }
+    }
+
+    /// Scope open roll requests to the characters a connection controls;
+    /// the GM's connection also sees what's under the fog
+    pub fn for_connection(mut self, conn_id: &Uuid) -> Self {
+        self.character_ids = self.game.controlled_characters(conn_id).to_vec();
+        if self
+            .game
+            .connections
+            .get(conn_id)
+            .is_some_and(|c| c.role == ConnectionRole::Gm)
+        {
+            self.fogged.clear();
+        }
+        self
+    }
+
+    /// Scope open roll requests to one character
+    pub fn for_character(mut self, character_id: Uuid) -> Self {
+        self.character_ids = vec![character_id];
+        self
+    }
+
+    /// How many recent events to include
+    pub fn events(mut self, count: usize) -> Self {
+        self.event_count = count;
+        self
+    }
+
+    pub fn build(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            environments: self.environments(),
+            hazards: self.hazards(),
+            combat: self.combat(),
+            fear_pool: self.game.fear_pool,
+            events: self
+                .game
+                .get_recent_events(self.event_count)
+                .iter()
+                .filter(|event| !event.cutaway || self.in_cutaway())
+                .map(|event| event.to_data())
+                .collect(),
+            open_rolls: self.open_rolls(),
+            party: self.party(),
+            npcs: self.npcs(),
+            cutaway: self.game.cutaway.as_ref().map(|c| c.to_info()),
+            map: self.game.map.clone(),
+            layout: self.game.layout,
+            fog: self.game.current_fog(),
+            drawings: self.game.current_drawings().to_vec(),
+            map_locked: self.game.map_locked,
+            modifiers: self
+                .game
+                .modifier_list()
+                .into_iter()
+                .filter(|m| !self.fogged.contains(&m.target_id))
+                .collect(),
+            adversary_groups: self.adversary_groups(),
+            quests: self.game.quests.clone(),
+        }
+    }
+
+    /// Whether one of the joiner's characters is in the running cutaway
+    fn in_cutaway(&self) -> bool {
+        self.game
+            .cutaway
+            .as_ref()
+            .is_some_and(|cutaway| self.character_ids.iter().any(|id| cutaway.includes(id)))
+    }
+
+    /// Groups with a member in view, and only those members
+    fn adversary_groups(&self) -> Vec<AdversaryGroupInfo> {
+        self.game
+            .adversary_group_list()
+            .into_iter()
+            .filter_map(|mut group| {
+                group.retain_members(|m| !self.fogged.contains(&m.id));
+                (!group.members.is_empty()).then_some(group)
+            })
+            .collect()
+    }
+
+    fn environments(&self) -> Vec<crate::environments::EnvironmentInfo> {
+        let mut environments: Vec<_> = self
+            .game
+            .environments
+            .values()
+            .map(|e| e.to_info())
+            .collect();
+        environments.sort_by(|a, b| a.name.cmp(&b.name));
+        environments
+    }
+
+    fn hazards(&self) -> Vec<HazardSnapshot> {
+        let mut hazards: Vec<HazardSnapshot> = self
+            .game
+            .hazards
+            .values()
+            .map(|h| HazardSnapshot {
+                hazard_id: h.id.clone(),
+                hazard: h.hazard.clone(),
+                active: h.active,
+                ticks_remaining: h.ticks_remaining,
+            })
+            .collect();
+        hazards.sort_by(|a, b| a.hazard.name.cmp(&b.hazard.name));
+        hazards
+    }
+
+    fn combat(&self) -> Option<CombatSnapshot> {
+        let encounter = self.game.get_combat().filter(|e| e.is_active)?;
+        let tracker = &encounter.action_tracker;
+        Some(CombatSnapshot {
+            encounter_id: encounter.id.clone(),
+            round: encounter.round,
+            mode: encounter.mode,
+            spotlight: encounter.spotlight.clone(),
+            pc_tokens: tracker.pc_tokens,
+            adversary_tokens: tracker.adversary_tokens,
+            queue: tracker.queue.iter().map(|t| t.name().to_string()).collect(),
+        })
+    }
+
+    fn open_rolls(&self) -> Vec<OpenRollRequest> {
+        let mut requests: Vec<_> = self.game.pending_roll_requests.values().collect();
+        requests.sort_by_key(|r| r.timestamp);
+
+        requests
+            .into_iter()
+            .filter_map(|request| {
+                let pending: Vec<&Uuid> = request
+                    .target_character_ids
+                    .iter()
+                    .filter(|id| !request.completed_by.contains(id))
+                    .filter(|id| self.character_ids.is_empty() || self.character_ids.contains(*id))
+                    .collect();
+                if pending.is_empty() {
+                    return None;
+                }
+                Some(OpenRollRequest {
+                    request_id: request.id.clone(),
+                    roll_type: request.roll_type.clone(),
+                    attribute: request.attribute.clone(),
+                    difficulty: request.difficulty,
+                    context: request.context.clone(),
+                    pending_character_ids: pending.iter().map(|id| id.to_string()).collect(),
+                })
+            })
+            .collect()
+    }
+
+    fn party(&self) -> Vec<PartyMember> {
+        let mut party: Vec<PartyMember> = self
+            .game
+            .get_player_characters()
+            .into_iter()
+            .map(|c| PartyMember {
+                character_id: c.id.to_string(),
+                name: c.name.clone(),
+                hp: ResourceData {
+                    current: c.hp.current as i32,
+                    maximum: c.hp.maximum as i32,
+                },
+                stress: c.stress.current as i32,
+                hope: ResourceData {
+                    current: c.hope.current as i32,
+                    maximum: c.hope.maximum as i32,
+                },
+                player_name: self.game.display_name_of_controller(&c.id),
+                connected: self.game.controller_of(&c.id).is_some(),
+            })
+            .collect();
+        party.sort_by(|a, b| a.name.cmp(&b.name));
+        party
+    }
+
+    fn npcs(&self) -> Vec<NpcInfo> {
+        let mut npcs: Vec<NpcInfo> = self
+            .game
+            .get_npcs()
+            .into_iter()
+            .filter(|c| !self.fogged.contains(&c.id.to_string()))
+            .map(|c| NpcInfo {
+                character_id: c.id.to_string(),
+                name: c.name.clone(),
+                hp: ResourceData {
+                    current: c.hp.current as i32,
+                    maximum: c.hp.maximum as i32,
+                },
+                evasion: c.evasion,
+                template: c.adversary_template.clone(),
+                position: c.position,
+            })
+            .collect();
+        npcs.sort_by(|a, b| a.name.cmp(&b.name));
+        npcs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GroupRollRequest;
+    use crate::protocol::Position;
+    use daggerheart_engine::character::{Ancestry, Attributes, Class};
+
+    #[test]
+    fn test_snapshot_for_late_joiner() {
+        let mut game = GameState::new();
+        let theron = game.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        let elara = game.create_character(
+            "Elara".to_string(),
+            Class::Wizard,
+            Ancestry::Elf,
+            Attributes::from_array([0, -1, 1, 2, 1, 0]).unwrap(),
+        );
+        game.create_npc("Innkeeper".to_string(), Some(5), None, None, None)
+            .unwrap();
+        game.spawn_adversary("goblin", Position::new(100.0, 100.0))
+            .unwrap();
+        game.start_combat();
+        game.request_group_roll(GroupRollRequest {
+            leader_id: theron.id.to_string(),
+            helper_ids: vec![elara.id.to_string()],
+            attribute: None,
+            difficulty: 12,
+            context: "Hold the gate".to_string(),
+            has_advantage: false,
+            has_disadvantage: false,
+        })
+        .unwrap();
+
+        let snapshot = SnapshotBuilder::new(&game).build();
+        assert_eq!(snapshot.combat.as_ref().map(|c| c.round), Some(1));
+        assert_eq!(snapshot.party.len(), 2);
+        assert_eq!(snapshot.npcs[0].name, "Innkeeper");
+        assert_eq!(snapshot.open_rolls[0].pending_character_ids.len(), 2);
+        assert!(!snapshot.events.is_empty());
+
+        let snapshot = SnapshotBuilder::new(&game)
+            .for_character(elara.id)
+            .events(1)
+            .build();
+        assert_eq!(
+            snapshot.open_rolls[0].pending_character_ids,
+            vec![elara.id.to_string()]
+        );
+        assert_eq!(snapshot.events.len(), 1);
+
+        // The innkeeper is off in the fog for everyone but the GM
+        game.set_fog_enabled(true);
+        assert!(SnapshotBuilder::new(&game).build().npcs.is_empty());
+        let gm = game.add_connection();
+        game.connections.get_mut(&gm.id).unwrap().role = ConnectionRole::Gm;
+        let snapshot = SnapshotBuilder::new(&game).for_connection(&gm.id).build();
+        assert_eq!(snapshot.npcs.len(), 1);
+        assert!(snapshot.fog.enabled);
+    }
+}
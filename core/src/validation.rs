@@ -0,0 +1,379 @@
+//! Consistency checks over the live game state
+//!
+//! A safety net for bugs in the subsystems that share `GameState`: finds
+//! references to things that no longer exist and values out of range, and
+//! can repair them in place.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::game::{GameEventType, GameState};
+
+/// Something inconsistent in the game state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StateIssue {
+    /// A control mapping points at a missing connection or character
+    OrphanedControl {
+        connection_id: String,
+        character_id: String,
+    },
+    /// A ghosted character no longer exists
+    OrphanedGhost { character_id: String },
+    /// A ghosted character's stand-in connection has gone
+    StaleStandIn {
+        character_id: String,
+        connection_id: String,
+    },
+    /// Every target has rolled but the request is still pending
+    CompletedRollRequest { request_id: String, context: String },
+    /// A pending roll request targets characters that no longer exist
+    MissingRollTargets {
+        request_id: String,
+        character_ids: Vec<String>,
+    },
+    /// An adversary has more HP or Stress than its maximum
+    AdversaryOverMax {
+        adversary_id: String,
+        name: String,
+        hp: u8,
+        max_hp: u8,
+        stress: u8,
+        max_stress: u8,
+    },
+    /// An aura's owner no longer exists
+    OrphanedAura { aura_id: String, owner_id: String },
+    /// An aura lists tokens that no longer exist
+    StaleAuraMembers {
+        aura_id: String,
+        member_ids: Vec<String>,
+    },
+    /// The spotlight is on a token that no longer exists
+    MissingSpotlight { token_id: String },
+}
+
+impl StateIssue {
+    pub fn description(&self) -> String {
+        match self {
+            Self::OrphanedControl {
+                connection_id,
+                character_id,
+            } => format!(
+                "Connection {} controls missing character or connection {}",
+                connection_id, character_id
+            ),
+            Self::OrphanedGhost { character_id } => {
+                format!("Ghosted character {} no longer exists", character_id)
+            }
+            Self::StaleStandIn {
+                character_id,
+                connection_id,
+            } => format!(
+                "Stand-in {} for {} has disconnected",
+                connection_id, character_id
+            ),
+            Self::CompletedRollRequest {
+                request_id,
+                context,
+            } => format!(
+                "Roll request {} (\"{}\") was never cleaned up",
+                request_id, context
+            ),
+            Self::MissingRollTargets {
+                request_id,
+                character_ids,
+            } => format!(
+                "Roll request {} targets {} missing character(s)",
+                request_id,
+                character_ids.len()
+            ),
+            Self::AdversaryOverMax {
+                name,
+                hp,
+                max_hp,
+                stress,
+                max_stress,
+                ..
+            } => format!(
+                "{} has {}/{} HP and {}/{} Stress",
+                name, hp, max_hp, stress, max_stress
+            ),
+            Self::OrphanedAura { aura_id, owner_id } => {
+                format!("Aura {} belongs to missing token {}", aura_id, owner_id)
+            }
+            Self::StaleAuraMembers {
+                aura_id,
+                member_ids,
+            } => format!(
+                "Aura {} lists {} missing token(s)",
+                aura_id,
+                member_ids.len()
+            ),
+            Self::MissingSpotlight { token_id } => {
+                format!("Spotlight is on missing token {}", token_id)
+            }
+        }
+    }
+}
+
+/// Result of a validation pass
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub issues: Vec<StateIssue>,
+    /// Whether the issues were repaired
+    pub repaired: bool,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Scan the game state for inconsistencies, repairing them if asked
+pub fn validate(game: &mut GameState, repair: bool) -> ValidationReport {
+    let issues = find_issues(game);
+    let repaired = repair && !issues.is_empty();
+
+    if repaired {
+        for issue in &issues {
+            fix(game, issue);
+        }
+        game.add_event(
+            GameEventType::SystemMessage,
+            format!("State check repaired {} issue(s)", issues.len()),
+            None,
+            Some(
+                issues
+                    .iter()
+                    .map(StateIssue::description)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+        );
+    }
+
+    ValidationReport { issues, repaired }
+}
+
+fn find_issues(game: &GameState) -> Vec<StateIssue> {
+    let mut issues = Vec::new();
+    let tokens: HashSet<String> = game
+        .characters
+        .keys()
+        .map(|id| id.to_string())
+        .chain(game.adversaries.keys().cloned())
+        .collect();
+
+    let controlled = game
+        .control_mapping
+        .iter()
+        .flat_map(|(conn_id, char_ids)| char_ids.iter().map(move |char_id| (conn_id, char_id)));
+    for (conn_id, char_id) in controlled {
+        if !game.connections.contains_key(conn_id) || !game.characters.contains_key(char_id) {
+            issues.push(StateIssue::OrphanedControl {
+                connection_id: conn_id.to_string(),
+                character_id: char_id.to_string(),
+            });
+        }
+    }
+
+    for (char_id, stand_in) in &game.ghosted_characters {
+        if !game.characters.contains_key(char_id) {
+            issues.push(StateIssue::OrphanedGhost {
+                character_id: char_id.to_string(),
+            });
+        } else if let Some(conn_id) = stand_in.filter(|c| !game.connections.contains_key(c)) {
+            issues.push(StateIssue::StaleStandIn {
+                character_id: char_id.to_string(),
+                connection_id: conn_id.to_string(),
+            });
+        }
+    }
+
+    for request in game.pending_roll_requests.values() {
+        let awaiting_tag_team_pick = request
+            .tag_team
+            .as_ref()
+            .is_some_and(|t| t.accepted && t.chosen.is_none());
+        let everyone_rolled = request
+            .target_character_ids
+            .iter()
+            .all(|id| request.completed_by.contains(id));
+        if everyone_rolled && !awaiting_tag_team_pick {
+            issues.push(StateIssue::CompletedRollRequest {
+                request_id: request.id.clone(),
+                context: request.context.clone(),
+            });
+            continue;
+        }
+
+        let missing: Vec<String> = request
+            .target_character_ids
+            .iter()
+            .filter(|id| !game.characters.contains_key(id))
+            .map(|id| id.to_string())
+            .collect();
+        if !missing.is_empty() {
+            issues.push(StateIssue::MissingRollTargets {
+                request_id: request.id.clone(),
+                character_ids: missing,
+            });
+        }
+    }
+
+    for adversary in game.adversaries.values() {
+        if adversary.hp > adversary.max_hp || adversary.stress > adversary.max_stress {
+            issues.push(StateIssue::AdversaryOverMax {
+                adversary_id: adversary.id.clone(),
+                name: adversary.name.clone(),
+                hp: adversary.hp,
+                max_hp: adversary.max_hp,
+                stress: adversary.stress,
+                max_stress: adversary.max_stress,
+            });
+        }
+    }
+
+    for attached in game.auras.values() {
+        if !tokens.contains(&attached.owner_id) {
+            issues.push(StateIssue::OrphanedAura {
+                aura_id: attached.id.clone(),
+                owner_id: attached.owner_id.clone(),
+            });
+            continue;
+        }
+        let mut member_ids: Vec<String> = attached
+            .members
+            .iter()
+            .filter(|id| !tokens.contains(*id))
+            .cloned()
+            .collect();
+        if !member_ids.is_empty() {
+            member_ids.sort();
+            issues.push(StateIssue::StaleAuraMembers {
+                aura_id: attached.id.clone(),
+                member_ids,
+            });
+        }
+    }
+
+    if let Some(token_id) = game
+        .combat_encounter
+        .as_ref()
+        .and_then(|e| e.spotlight.as_ref())
+        .filter(|id| !tokens.contains(*id))
+    {
+        issues.push(StateIssue::MissingSpotlight {
+            token_id: token_id.clone(),
+        });
+    }
+
+    issues
+}
+
+fn fix(game: &mut GameState, issue: &StateIssue) {
+    match issue {
+        StateIssue::OrphanedControl {
+            connection_id,
+            character_id,
+        } => {
+            game.control_mapping.retain(|conn_id, char_ids| {
+                if conn_id.to_string() == *connection_id {
+                    char_ids.retain(|id| id.to_string() != *character_id);
+                }
+                !char_ids.is_empty()
+            });
+        }
+        StateIssue::OrphanedGhost { character_id } => {
+            game.ghosted_characters
+                .retain(|id, _| id.to_string() != *character_id);
+        }
+        StateIssue::StaleStandIn { character_id, .. } => {
+            // Back to waiting for the player
+            for (id, stand_in) in game.ghosted_characters.iter_mut() {
+                if id.to_string() == *character_id {
+                    *stand_in = None;
+                }
+            }
+        }
+        StateIssue::CompletedRollRequest { request_id, .. } => {
+            game.pending_roll_requests.remove(request_id);
+        }
+        StateIssue::MissingRollTargets { request_id, .. } => {
+            let characters = &game.characters;
+            if let Some(request) = game.pending_roll_requests.get_mut(request_id) {
+                request
+                    .target_character_ids
+                    .retain(|id| characters.contains_key(id));
+                if request.target_character_ids.is_empty() {
+                    game.pending_roll_requests.remove(request_id);
+                }
+            }
+        }
+        StateIssue::AdversaryOverMax { adversary_id, .. } => {
+            if let Some(adversary) = game.adversaries.get_mut(adversary_id) {
+                adversary.hp = adversary.hp.min(adversary.max_hp);
+                adversary.stress = adversary.stress.min(adversary.max_stress);
+            }
+        }
+        StateIssue::OrphanedAura { aura_id, .. } => {
+            game.auras.remove(aura_id);
+        }
+        StateIssue::StaleAuraMembers {
+            aura_id,
+            member_ids,
+        } => {
+            if let Some(attached) = game.auras.get_mut(aura_id) {
+                attached.members.retain(|id| !member_ids.contains(id));
+            }
+        }
+        StateIssue::MissingSpotlight { .. } => {
+            if let Some(encounter) = game.combat_encounter.as_mut() {
+                encounter.spotlight = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::Position;
+    use daggerheart_engine::character::{Ancestry, Attributes, Class};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_validate_and_repair() {
+        let mut game = GameState::new();
+        let conn = game.add_connection();
+        let theron = game.create_character(
+            "Theron".to_string(),
+            Class::Warrior,
+            Ancestry::Human,
+            Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap(),
+        );
+        game.control_mapping.insert(conn.id, vec![theron.id]);
+        let goblin = game
+            .spawn_adversary("goblin", Position::new(0.0, 0.0))
+            .unwrap();
+        assert!(validate(&mut game, false).is_clean());
+
+        // Break a few things
+        game.control_mapping.insert(Uuid::new_v4(), vec![theron.id]);
+        game.adversaries.get_mut(&goblin.id).unwrap().hp = goblin.max_hp + 3;
+        game.ghosted_characters.insert(Uuid::new_v4(), None);
+
+        let report = validate(&mut game, false);
+        assert_eq!(report.issues.len(), 3);
+        assert!(!report.repaired);
+        assert_eq!(game.control_mapping.len(), 2, "a dry run changes nothing");
+
+        let report = validate(&mut game, true);
+        assert!(report.repaired);
+        assert_eq!(game.control_mapping.len(), 1);
+        assert_eq!(game.adversaries[&goblin.id].hp, goblin.max_hp);
+        assert!(game.ghosted_characters.is_empty());
+        assert!(validate(&mut game, false).is_clean());
+    }
+}
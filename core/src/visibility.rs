@@ -0,0 +1,791 @@
+//! Who gets to see which broadcast
+//!
+//! Every [`ServerMessage`] passes through [`visible_to`] once per recipient
+//! before it is sent. Each rule covers one concern; messages a rule doesn't
+//! care about pass it. Handlers broadcast to everyone and leave the
+//! filtering to this module. Messages that do go out pass through
+//! [`redact`], which blanks fields only the GM should read and drops the
+//! notes and tokens players can't see.
+//!
+//! Replies built for one connection (the catch-up's lists, a page of the
+//! event log) go through the same two functions, or ask the [`Recipient`].
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::game::GameState;
+use crate::protocol::chat::ChatChannel;
+use crate::protocol::handouts::Handout;
+use crate::protocol::{ConnectionRole, ServerMessage};
+
+/// The connection a broadcast is about to be sent to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub connection_id: Uuid,
+    pub role: ConnectionRole,
+    /// The characters the connection controls
+    pub character_ids: Vec<Uuid>,
+    /// Characters in the running cutaway, if there is one
+    pub cutaway: Option<Vec<Uuid>>,
+    /// Tokens under the fog of war (always empty for the GM)
+    pub fogged: HashSet<String>,
+}
+
+impl Recipient {
+    /// Look up a connection, `None` once it has gone
+    pub fn of(game: &GameState, connection_id: &Uuid) -> Option<Self> {
+        let connection = game.connections.get(connection_id)?;
+        Some(Self {
+            connection_id: *connection_id,
+            role: connection.role,
+            character_ids: game.controlled_characters(connection_id).to_vec(),
+            cutaway: game.cutaway.as_ref().map(|c| c.character_ids.clone()),
+            fogged: if connection.role == ConnectionRole::Gm {
+                HashSet::new()
+            } else {
+                game.fogged_tokens()
+            },
+        })
+    }
+
+    pub fn is_gm(&self) -> bool {
+        self.role == ConnectionRole::Gm
+    }
+
+    /// Whether this is the connection the id names
+    fn is_connection(&self, connection_id: &str) -> bool {
+        self.connection_id.to_string() == connection_id
+    }
+
+    /// Whether the recipient controls the character the id names
+    fn controls(&self, character_id: &str) -> bool {
+        self.character_ids
+            .iter()
+            .any(|id| id.to_string() == character_id)
+    }
+
+    /// Whether a cutaway is running without the recipient in it
+    pub fn outside_cutaway(&self) -> bool {
+        self.cutaway.as_ref().is_some_and(|scene| {
+            !self.is_gm() && !self.character_ids.iter().any(|id| scene.contains(id))
+        })
+    }
+}
+
+/// A visibility rule: `false` withholds the message from the recipient
+type Rule = fn(&ServerMessage, &Recipient) -> bool;
+
+/// Applied in order; a message is sent only if every rule allows it
+const RULES: &[Rule] = &[
+    gm_only,
+    secret_rolls,
+    whispers,
+    cutaways,
+    own_connection,
+    own_character,
+    handouts,
+    shared_displays,
+    fog,
+];
+
+/// Whether a broadcast should reach this recipient
+pub fn visible_to(message: &ServerMessage, recipient: &Recipient) -> bool {
+    RULES.iter().all(|rule| rule(message, recipient))
+}
+
+/// Blank the GM-only parts of a message for this recipient
+pub fn redact(mut message: ServerMessage, recipient: &Recipient) -> ServerMessage {
+    if !recipient.is_gm() {
+        if let ServerMessage::AdversarySpawned {
+            motives_and_tactics,
+            ..
+        } = &mut message
+        {
+            motives_and_tactics.clear();
+        }
+        // A note the GM keeps (or just took back) is gone as far as
+        // players know
+        if let ServerMessage::NoteSaved { note } = &message {
+            if !note.is_shared() {
+                return ServerMessage::NoteDeleted {
+                    note_id: note.id.clone(),
+                };
+            }
+        }
+        if let ServerMessage::Notes { notes, .. } = &mut message {
+            notes.retain(|n| n.is_shared());
+        }
+        if let ServerMessage::Handouts { handouts, .. } = &mut message {
+            handouts.retain(|h| handout_visible(h, recipient));
+        }
+    }
+    match &mut message {
+        ServerMessage::CharactersList { characters } => {
+            characters.retain(|c| !recipient.fogged.contains(&c.id));
+        }
+        ServerMessage::AdversariesList { adversaries } => {
+            adversaries.retain(|a| !recipient.fogged.contains(&a.id));
+        }
+        ServerMessage::ModifiersUpdated { modifiers } => {
+            modifiers.retain(|m| !recipient.fogged.contains(&m.target_id));
+        }
+        ServerMessage::AdversaryGroupsUpdated { groups } => {
+            for group in groups.iter_mut() {
+                group.retain_members(|m| !recipient.fogged.contains(&m.id));
+            }
+            groups.retain(|g| !g.members.is_empty());
+        }
+        _ => {}
+    }
+    message
+}
+
+/// Odds for a roll the GM hasn't sent yet, and adversaries' tactics
+fn gm_only(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::RollPreview { .. }
+        | ServerMessage::TacticsReminder { .. }
+        | ServerMessage::EncounterPresets { .. }
+        | ServerMessage::ControlRequested { .. } => recipient.is_gm(),
+        _ => true,
+    }
+}
+
+/// Results of rolls the GM made or requested in secret
+fn secret_rolls(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::DetailedRollResult { hidden: true, .. }
+        | ServerMessage::GroupRollResult { hidden: true, .. }
+        | ServerMessage::DiceRolled { hidden: true, .. } => recipient.is_gm(),
+        _ => true,
+    }
+}
+
+/// Whispers reach their sender, their recipient's player and the GM
+fn whispers(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::ChatMessage { message } => match &message.channel {
+            ChatChannel::Whisper { to_character_id } => {
+                recipient.is_gm()
+                    || recipient.is_connection(&message.sender_connection_id)
+                    || to_character_id
+                        .as_deref()
+                        .is_some_and(|id| recipient.controls(id))
+            }
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+/// A cutaway's scene reaches only its characters' players and the GM
+fn cutaways(message: &ServerMessage, recipient: &Recipient) -> bool {
+    if !recipient.outside_cutaway() {
+        return true;
+    }
+    let in_scene = |character_id: &str| {
+        recipient
+            .cutaway
+            .iter()
+            .flatten()
+            .any(|id| id.to_string() == character_id)
+    };
+    match message {
+        ServerMessage::GameEvent { cutaway, .. } => !cutaway,
+        ServerMessage::ChatMessage { message } => !message.cutaway,
+        ServerMessage::CharacterMoved { character_id, .. }
+        | ServerMessage::CharacterEmoted { character_id, .. }
+        | ServerMessage::RollResult { character_id, .. }
+        | ServerMessage::RollRequested { character_id, .. }
+        | ServerMessage::DetailedRollResult { character_id, .. }
+        | ServerMessage::AllyHelped { character_id, .. } => !in_scene(character_id),
+        ServerMessage::DiceRolled {
+            character_id: Some(character_id),
+            ..
+        } => !in_scene(character_id),
+        ServerMessage::GroupRollResult { leader_id, .. } => !in_scene(leader_id),
+        _ => true,
+    }
+}
+
+/// Settings and answers that belong to one connection
+fn own_connection(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::PreferencesUpdated { connection_id, .. }
+        | ServerMessage::RangeMeasured { connection_id, .. } => {
+            recipient.is_connection(connection_id)
+        }
+        ServerMessage::Error {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::CharacterUpdated {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::EventLog {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::SceneLog {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::Notes {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::Handouts {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::RollStats {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::EncounterPriced {
+            connection_id: Some(connection_id),
+            ..
+        }
+        | ServerMessage::RoundHistory {
+            connection_id: Some(connection_id),
+            ..
+        } => recipient.is_connection(connection_id),
+        _ => true,
+    }
+}
+
+/// Prompts for one character's player (and the GM)
+fn own_character(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::LevelUpOffered { character_id, .. } => {
+            recipient.is_gm() || recipient.controls(character_id)
+        }
+        // The sheet goes only to whoever just took control
+        ServerMessage::CharacterSelected { character_id, .. } => recipient.controls(character_id),
+        _ => true,
+    }
+}
+
+/// Handouts given to particular characters
+fn handouts(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::HandoutShared { handout } => handout_visible(handout, recipient),
+        _ => true,
+    }
+}
+
+/// Whether the recipient may see a handout
+fn handout_visible(handout: &Handout, recipient: &Recipient) -> bool {
+    handout.is_public()
+        || recipient.is_gm()
+        || handout
+            .character_ids
+            .iter()
+            .any(|id| recipient.controls(id))
+}
+
+/// Table-wide progress shown by the GM and the TV view, not players' phones
+fn shared_displays(message: &ServerMessage, recipient: &Recipient) -> bool {
+    match message {
+        ServerMessage::RollRequestStatus { .. } => {
+            recipient.is_gm() || recipient.character_ids.is_empty()
+        }
+        _ => true,
+    }
+}
+
+/// NPCs and adversaries under the fog of war stay off players' maps
+fn fog(message: &ServerMessage, recipient: &Recipient) -> bool {
+    let hidden = |token_id: &String| recipient.fogged.contains(token_id);
+    match message {
+        ServerMessage::CharacterSpawned { character_id, .. }
+        | ServerMessage::CharacterMoved { character_id, .. }
+        | ServerMessage::CharacterEmoted { character_id, .. } => !hidden(character_id),
+        ServerMessage::AdversarySpawned { adversary_id, .. } => !hidden(adversary_id),
+        ServerMessage::TokenDetailsChanged { token_id, .. }
+        | ServerMessage::TokenRenamed { token_id, .. } => !hidden(token_id),
+        ServerMessage::AdversaryMoved { adversary_id, .. }
+        | ServerMessage::AdversaryUpdated { adversary_id, .. } => !hidden(adversary_id),
+        ServerMessage::TokenHidden { .. } => !recipient.is_gm(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::errors::ErrorCode;
+
+    fn recipient(role: ConnectionRole, character_id: Option<Uuid>) -> Recipient {
+        Recipient {
+            connection_id: Uuid::new_v4(),
+            role,
+            character_ids: character_id.into_iter().collect(),
+            cutaway: None,
+            fogged: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_roll_preview_is_gm_only() {
+        let message = ServerMessage::RollPreview {
+            previews: Vec::new(),
+        };
+        assert!(visible_to(&message, &recipient(ConnectionRole::Gm, None)));
+        assert!(!visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, None)
+        ));
+    }
+
+    #[test]
+    fn test_adversary_tactics_are_gm_only() {
+        let gm = recipient(ConnectionRole::Gm, None);
+        let player = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+        let reminder = ServerMessage::TacticsReminder {
+            adversaries: Vec::new(),
+        };
+        assert!(visible_to(&reminder, &gm));
+        assert!(!visible_to(&reminder, &player));
+
+        let spawned = ServerMessage::AdversarySpawned {
+            adversary_id: "a1".to_string(),
+            name: "Goblin".to_string(),
+            template: "goblin".to_string(),
+            tier: 1,
+            role: Default::default(),
+            position: crate::protocol::Position { x: 0.0, y: 0.0 },
+            hp: 3,
+            max_hp: 3,
+            evasion: 10,
+            armor: 1,
+            attack_modifier: 1,
+            damage_dice: "1d6".to_string(),
+            motives_and_tactics: "Ambush".to_string(),
+            token: Default::default(),
+        };
+        let tactics = |message: ServerMessage| match message {
+            ServerMessage::AdversarySpawned {
+                motives_and_tactics,
+                ..
+            } => motives_and_tactics,
+            _ => unreachable!(),
+        };
+        assert_eq!(tactics(redact(spawned.clone(), &gm)), "Ambush");
+        assert_eq!(tactics(redact(spawned, &player)), "");
+    }
+
+    #[test]
+    fn test_hidden_rolls_are_gm_only() {
+        let roll = |hidden| ServerMessage::DiceRolled {
+            character_id: None,
+            roller_name: "GM".to_string(),
+            label: None,
+            roll: crate::dice::roll("1d20", &mut rand::thread_rng()).unwrap(),
+            hidden,
+        };
+        let player = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+        assert!(visible_to(&roll(false), &player));
+        assert!(!visible_to(&roll(true), &player));
+        assert!(visible_to(
+            &roll(true),
+            &recipient(ConnectionRole::Gm, None)
+        ));
+    }
+
+    #[test]
+    fn test_whispers_reach_sender_recipient_and_gm() {
+        let sender = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+        let target = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+        let message = ServerMessage::ChatMessage {
+            message: crate::protocol::chat::ChatEntry {
+                id: "m1".to_string(),
+                channel: ChatChannel::Whisper {
+                    to_character_id: target.character_ids.first().map(|id| id.to_string()),
+                },
+                sender_connection_id: sender.connection_id.to_string(),
+                character_id: sender.character_ids.first().map(|id| id.to_string()),
+                sender_name: "Sam".to_string(),
+                to_name: Some("Mira".to_string()),
+                text: "psst".to_string(),
+                timestamp: "12:00:00".to_string(),
+                cutaway: false,
+            },
+        };
+        assert!(visible_to(&message, &sender));
+        assert!(visible_to(&message, &target));
+        assert!(visible_to(&message, &recipient(ConnectionRole::Gm, None)));
+        assert!(!visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        ));
+    }
+
+    #[test]
+    fn test_cutaway_scene_reaches_only_those_in_it() {
+        let in_scene = Uuid::new_v4();
+        let scene = Some(vec![in_scene]);
+        let member = Recipient {
+            cutaway: scene.clone(),
+            ..recipient(ConnectionRole::Player, Some(in_scene))
+        };
+        let gm = Recipient {
+            cutaway: scene.clone(),
+            ..recipient(ConnectionRole::Gm, None)
+        };
+        let outsider = Recipient {
+            cutaway: scene,
+            ..recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        };
+
+        let moved = ServerMessage::CharacterMoved {
+            character_id: in_scene.to_string(),
+            position: crate::protocol::Position { x: 1.0, y: 2.0 },
+            path: Vec::new(),
+            teleported: true,
+            crossed_hazards: Vec::new(),
+            rotation: 0.0,
+        };
+        assert!(visible_to(&moved, &member));
+        assert!(visible_to(&moved, &gm));
+        assert!(!visible_to(&moved, &outsider));
+
+        let event = |cutaway| ServerMessage::GameEvent {
+            timestamp: "12:00:00".to_string(),
+            event_type: "SystemMessage".to_string(),
+            message: "The vault door creaks".to_string(),
+            character_name: None,
+            details: None,
+            player_name: None,
+            cutaway,
+        };
+        assert!(visible_to(&event(true), &member));
+        assert!(!visible_to(&event(true), &outsider));
+        assert!(visible_to(&event(false), &outsider));
+
+        let no_cutaway = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+        assert!(visible_to(&moved, &no_cutaway));
+
+        assert!(outsider.outside_cutaway());
+        assert!(!member.outside_cutaway());
+        assert!(!gm.outside_cutaway());
+        assert!(!no_cutaway.outside_cutaway());
+    }
+
+    #[test]
+    fn test_preferences_go_to_their_connection() {
+        let player = recipient(ConnectionRole::Player, None);
+        let message = ServerMessage::PreferencesUpdated {
+            connection_id: player.connection_id.to_string(),
+            preferences: Default::default(),
+        };
+        assert!(visible_to(&message, &player));
+        assert!(!visible_to(&message, &recipient(ConnectionRole::Gm, None)));
+    }
+
+    #[test]
+    fn test_errors_go_to_the_connection_that_failed() {
+        let player = recipient(ConnectionRole::Player, None);
+        let gm = recipient(ConnectionRole::Gm, None);
+        let error = |connection_id: Option<String>| ServerMessage::Error {
+            code: ErrorCode::Forbidden,
+            message: "Only the GM can lock the map".to_string(),
+            request_type: Some("set_map_locked".to_string()),
+            request_id: None,
+            connection_id,
+        };
+
+        let reply = error(Some(player.connection_id.to_string()));
+        assert!(visible_to(&reply, &player));
+        assert!(!visible_to(&reply, &gm));
+
+        // Notices without a connection reach everyone
+        assert!(visible_to(&error(None), &gm));
+    }
+
+    #[test]
+    fn test_level_up_offer_goes_to_its_player_and_gm() {
+        let character_id = Uuid::new_v4();
+        let message = ServerMessage::LevelUpOffered {
+            character_id: character_id.to_string(),
+            new_level: 2,
+            tier: 2,
+            tier_achievement: true,
+            options: Vec::new(),
+        };
+        assert!(visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, Some(character_id))
+        ));
+        assert!(visible_to(&message, &recipient(ConnectionRole::Gm, None)));
+        assert!(!visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        ));
+        assert!(!visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, None)
+        ));
+    }
+
+    #[test]
+    fn test_control_messages_reach_the_right_connections() {
+        let mut game = crate::game::GameState::new();
+        let attrs =
+            daggerheart_engine::character::Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let character = game.create_character(
+            "Theron".to_string(),
+            daggerheart_engine::character::Class::Warrior,
+            daggerheart_engine::character::Ancestry::Human,
+            attrs,
+        );
+        let selected = ServerMessage::CharacterSelected {
+            character_id: character.id.to_string(),
+            character: character.to_data(),
+        };
+        assert!(visible_to(
+            &selected,
+            &recipient(ConnectionRole::Player, Some(character.id))
+        ));
+        assert!(!visible_to(
+            &selected,
+            &recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        ));
+        assert!(!visible_to(&selected, &recipient(ConnectionRole::Gm, None)));
+
+        let requested = ServerMessage::ControlRequested {
+            character_id: character.id.to_string(),
+            name: "Theron".to_string(),
+            connection_id: Uuid::new_v4().to_string(),
+            display_name: None,
+        };
+        assert!(visible_to(&requested, &recipient(ConnectionRole::Gm, None)));
+        assert!(!visible_to(
+            &requested,
+            &recipient(ConnectionRole::Player, None)
+        ));
+    }
+
+    #[test]
+    fn test_private_handouts_reach_their_characters() {
+        let character_id = Uuid::new_v4();
+        let handout = |id: &str, character_ids: Vec<String>| Handout {
+            id: id.to_string(),
+            title: "A Letter".to_string(),
+            kind: crate::protocol::handouts::HandoutKind::Text,
+            character_ids,
+            shared_at: "2026-11-07T19:00:00Z".to_string(),
+            url: format!("/api/handouts/{}", id),
+        };
+        let shared = |character_ids| ServerMessage::HandoutShared {
+            handout: handout("h1", character_ids),
+        };
+        let private = shared(vec![character_id.to_string()]);
+        let owner = recipient(ConnectionRole::Player, Some(character_id));
+        let other = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+
+        assert!(visible_to(&private, &owner));
+        assert!(visible_to(&private, &recipient(ConnectionRole::Gm, None)));
+        assert!(!visible_to(&private, &other));
+        assert!(visible_to(&shared(Vec::new()), &other));
+
+        // Asking for the list again doesn't turn up anyone else's
+        let list = ServerMessage::Handouts {
+            handouts: vec![
+                handout("h1", Vec::new()),
+                handout("h2", vec![character_id.to_string()]),
+            ],
+            connection_id: None,
+        };
+        let found = |message: ServerMessage| match message {
+            ServerMessage::Handouts { handouts, .. } => {
+                handouts.into_iter().map(|h| h.id).collect::<Vec<_>>()
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(found(redact(list.clone(), &owner)), ["h1", "h2"]);
+        assert_eq!(found(redact(list, &other)), ["h1"]);
+    }
+
+    #[test]
+    fn test_gm_notes_are_gone_for_players() {
+        use crate::protocol::notes::{Note, NoteVisibility};
+
+        let saved = |visibility| ServerMessage::NoteSaved {
+            note: Note {
+                id: "n1".to_string(),
+                title: "Vess".to_string(),
+                body: "The baron's sister".to_string(),
+                visibility,
+                created_at: "2026-11-07T19:00:00Z".to_string(),
+                updated_at: "2026-11-07T19:00:00Z".to_string(),
+            },
+        };
+        let gm = recipient(ConnectionRole::Gm, None);
+        let player = recipient(ConnectionRole::Player, Some(Uuid::new_v4()));
+
+        let private = saved(NoteVisibility::Gm);
+        assert!(matches!(
+            redact(private.clone(), &gm),
+            ServerMessage::NoteSaved { .. }
+        ));
+        assert!(matches!(
+            redact(private, &player),
+            ServerMessage::NoteDeleted { note_id } if note_id == "n1"
+        ));
+        assert!(matches!(
+            redact(saved(NoteVisibility::Table), &player),
+            ServerMessage::NoteSaved { .. }
+        ));
+
+        let note = |id: &str, visibility| Note {
+            id: id.to_string(),
+            title: "Vess".to_string(),
+            body: String::new(),
+            visibility,
+            created_at: "2026-11-07T19:00:00Z".to_string(),
+            updated_at: "2026-11-07T19:00:00Z".to_string(),
+        };
+        let search = ServerMessage::Notes {
+            notes: vec![
+                note("n1", NoteVisibility::Gm),
+                note("n2", NoteVisibility::Table),
+            ],
+            connection_id: None,
+        };
+        let found = |message: ServerMessage| match message {
+            ServerMessage::Notes { notes, .. } => {
+                notes.into_iter().map(|n| n.id).collect::<Vec<_>>()
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(found(redact(search.clone(), &gm)), ["n1", "n2"]);
+        assert_eq!(found(redact(search, &player)), ["n2"]);
+    }
+
+    #[test]
+    fn test_roll_request_status_skips_players_phones() {
+        let message = ServerMessage::RollRequestStatus {
+            request_id: "r1".to_string(),
+            pending_characters: Vec::new(),
+            completed_characters: Vec::new(),
+        };
+        assert!(visible_to(&message, &recipient(ConnectionRole::Gm, None)));
+        assert!(visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, None)
+        ));
+        assert!(!visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        ));
+    }
+
+    #[test]
+    fn test_fogged_tokens_are_withheld_from_players() {
+        let goblin = "goblin-1".to_string();
+        let player = Recipient {
+            fogged: HashSet::from([goblin.clone()]),
+            ..recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        };
+        let gm = recipient(ConnectionRole::Gm, None);
+
+        let moved = |character_id: &str| ServerMessage::CharacterMoved {
+            character_id: character_id.to_string(),
+            position: crate::protocol::Position { x: 1.0, y: 2.0 },
+            path: Vec::new(),
+            teleported: true,
+            crossed_hazards: Vec::new(),
+            rotation: 0.0,
+        };
+        assert!(!visible_to(&moved(&goblin), &player));
+        assert!(visible_to(&moved("guard-1"), &player));
+        assert!(visible_to(&moved(&goblin), &gm));
+
+        let hidden = ServerMessage::TokenHidden {
+            token_id: goblin.clone(),
+        };
+        assert!(visible_to(&hidden, &player));
+        assert!(!visible_to(&hidden, &gm));
+
+        let adversary = |id: &str| crate::protocol::AdversaryInfo {
+            id: id.to_string(),
+            name: "Goblin".to_string(),
+            template: "goblin".to_string(),
+            tier: 1,
+            role: crate::protocol::AdversaryRole::default(),
+            position: crate::protocol::Position { x: 1.0, y: 2.0 },
+            hp: 3,
+            max_hp: 3,
+            stress: 0,
+            evasion: 10,
+            armor: 1,
+            attack_modifier: 0,
+            damage_dice: "1d6".to_string(),
+            damage_type: crate::damage::DamageType::physical(),
+            defenses: crate::damage::Defenses::default(),
+            is_active: true,
+            token: Default::default(),
+        };
+        let list = ServerMessage::AdversariesList {
+            adversaries: vec![adversary(&goblin), adversary("wolf-1")],
+        };
+        let count = |message: ServerMessage| match message {
+            ServerMessage::AdversariesList { adversaries } => adversaries.len(),
+            _ => unreachable!(),
+        };
+        assert_eq!(count(redact(list.clone(), &player)), 1);
+        assert_eq!(count(redact(list, &gm)), 2);
+
+        let character = |id: &str| crate::protocol::CharacterInfo {
+            id: id.to_string(),
+            name: "Guard".to_string(),
+            class: "Guardian".to_string(),
+            ancestry: "Human".to_string(),
+            position: crate::protocol::Position { x: 1.0, y: 2.0 },
+            color: "#3b82f6".to_string(),
+            is_npc: true,
+            controlled_by_me: false,
+            controlled_by_other: false,
+            player_name: None,
+            controller_id: None,
+            token: Default::default(),
+        };
+        let list = ServerMessage::CharactersList {
+            characters: vec![character(&goblin), character("guard-1")],
+        };
+        let listed = |message: ServerMessage| match message {
+            ServerMessage::CharactersList { characters } => characters.len(),
+            _ => unreachable!(),
+        };
+        assert_eq!(listed(redact(list.clone(), &player)), 1);
+        assert_eq!(listed(redact(list, &gm)), 2);
+    }
+
+    #[test]
+    fn test_public_messages_reach_everyone() {
+        let message = ServerMessage::RoundAdvanced { round: 2 };
+        assert!(visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, None)
+        ));
+        assert!(visible_to(
+            &message,
+            &recipient(ConnectionRole::Player, Some(Uuid::new_v4()))
+        ));
+    }
+
+    #[test]
+    fn test_recipient_of_connection() {
+        let mut game = GameState::new();
+        let conn = game.add_connection();
+        game.connections.get_mut(&conn.id).unwrap().role = ConnectionRole::Gm;
+
+        let recipient = Recipient::of(&game, &conn.id).unwrap();
+        assert!(recipient.is_gm());
+        assert!(recipient.character_ids.is_empty());
+        assert!(Recipient::of(&game, &Uuid::new_v4()).is_none());
+    }
+}
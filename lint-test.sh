@@ -17,17 +17,17 @@ echo ""
 cd server
 
 echo -e "${YELLOW}📝 Running cargo fmt check...${NC}"
-cargo fmt -- --check
+cargo fmt --all -- --check
 echo -e "${GREEN}✓ Formatting OK${NC}"
 echo ""
 
 echo -e "${YELLOW}📋 Running cargo clippy...${NC}"
-cargo clippy -- -D warnings
+cargo clippy --workspace --all-targets -- -D warnings
 echo -e "${GREEN}✓ Clippy OK (no warnings)${NC}"
 echo ""
 
 echo -e "${YELLOW}🧪 Running tests...${NC}"
-cargo test --verbose
+cargo test --workspace --verbose
 echo -e "${GREEN}✓ Tests passed${NC}"
 echo ""
 
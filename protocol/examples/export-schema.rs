@@ -0,0 +1,18 @@
+//! Write the protocol's JSON Schemas to a directory (default `schemas/`)
+
+fn main() {
+    let dir = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "schemas".to_string());
+    match daggerheart_vtt_protocol::schema::write_schemas(dir.as_ref()) {
+        Ok(written) => {
+            for path in written {
+                println!("{}", path);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Auras: effects projected around a character or adversary
+//!
+//! An aura covers every token within its radius of the owner that passes its
+//! friend/foe filter. Characters are allies of characters and adversaries of
+//! adversaries. While a token is inside, the aura's effect applies to it.
+
+use serde::{Deserialize, Serialize};
+
+/// Which tokens an aura affects, relative to its owner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AuraTargets {
+    #[default]
+    Allies,
+    Enemies,
+    All,
+}
+
+/// Modifiers granted to tokens inside an aura
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AuraEffect {
+    /// Added to the token's Evasion when it is attacked
+    #[serde(default)]
+    pub evasion: i8,
+    /// Added to the token's attack rolls
+    #[serde(default)]
+    pub attack: i8,
+}
+
+impl AuraEffect {
+    pub fn is_empty(&self) -> bool {
+        self.evasion == 0 && self.attack == 0
+    }
+
+    /// Combine two effects, e.g. from overlapping auras
+    pub fn combine(self, other: AuraEffect) -> AuraEffect {
+        AuraEffect {
+            evasion: self.evasion.saturating_add(other.evasion),
+            attack: self.attack.saturating_add(other.attack),
+        }
+    }
+
+    /// Short summary, e.g. "+1 Evasion, -2 attack"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.evasion != 0 {
+            parts.push(format!("{:+} Evasion", self.evasion));
+        }
+        if self.attack != 0 {
+            parts.push(format!("{:+} attack", self.attack));
+        }
+        if parts.is_empty() {
+            "no effect".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// An aura definition, attached to a token by the GM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Aura {
+    pub name: String,
+    /// Reach from the owner's position, in map units
+    pub radius: f32,
+    pub effect: AuraEffect,
+    #[serde(default)]
+    pub targets: AuraTargets,
+    /// Whether the owner benefits from its own aura
+    #[serde(default)]
+    pub include_self: bool,
+}
+
+impl Aura {
+    /// Check the aura can be attached
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Aura name cannot be empty".to_string());
+        }
+        if !self.radius.is_finite() || self.radius <= 0.0 {
+            return Err("Aura radius must be positive".to_string());
+        }
+        if self.effect.is_empty() {
+            return Err("Aura has no effect".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a token on `same_side` as the owner is affected
+    pub fn affects_side(&self, same_side: bool) -> bool {
+        match self.targets {
+            AuraTargets::Allies => same_side,
+            AuraTargets::Enemies => !same_side,
+            AuraTargets::All => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bolstering_presence() -> Aura {
+        Aura {
+            name: "Bolstering Presence".to_string(),
+            radius: 150.0,
+            effect: AuraEffect {
+                evasion: 1,
+                attack: 0,
+            },
+            targets: AuraTargets::Allies,
+            include_self: false,
+        }
+    }
+
+    #[test]
+    fn test_aura_validate() {
+        assert!(bolstering_presence().validate().is_ok());
+
+        let mut aura = bolstering_presence();
+        aura.radius = 0.0;
+        assert!(aura.validate().is_err());
+
+        let mut aura = bolstering_presence();
+        aura.effect = AuraEffect::default();
+        assert!(aura.validate().is_err());
+    }
+
+    #[test]
+    fn test_affects_side() {
+        let mut aura = bolstering_presence();
+        assert!(aura.affects_side(true));
+        assert!(!aura.affects_side(false));
+
+        aura.targets = AuraTargets::Enemies;
+        assert!(!aura.affects_side(true));
+        assert!(aura.affects_side(false));
+    }
+
+    #[test]
+    fn test_effect_combine_and_describe() {
+        let combined = AuraEffect {
+            evasion: 1,
+            attack: 0,
+        }
+        .combine(AuraEffect {
+            evasion: 0,
+            attack: -2,
+        });
+        assert_eq!(combined.describe(), "+1 Evasion, -2 attack");
+    }
+}
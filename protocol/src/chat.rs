@@ -0,0 +1,80 @@
+//! Table chat
+
+use serde::{Deserialize, Serialize};
+
+/// Longest chat message accepted, in characters
+pub const MAX_CHAT_LENGTH: usize = 500;
+
+/// Where a chat message goes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChatChannel {
+    /// Spoken by the sender's character
+    InCharacter,
+    /// Table talk
+    OutOfCharacter,
+    /// Seen only by the sender, the character's player and the GM; without
+    /// a character it goes to the GM alone
+    Whisper {
+        #[serde(default)]
+        to_character_id: Option<String>,
+    },
+}
+
+/// A chat message as sent to clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ChatEntry {
+    pub id: String,
+    pub channel: ChatChannel,
+    pub sender_connection_id: String,
+    /// The sender's character, if they control one
+    pub character_id: Option<String>,
+    /// The character's name in character, otherwise the player's
+    pub sender_name: String,
+    /// Who a whisper went to
+    #[serde(default)]
+    pub to_name: Option<String>,
+    pub text: String,
+    /// "HH:MM:SS"
+    pub timestamp: String,
+    /// Said in a running cutaway, seen only by those in it until it ends
+    #[serde(default)]
+    pub cutaway: bool,
+}
+
+impl ChatEntry {
+    pub fn is_whisper(&self) -> bool {
+        matches!(self.channel, ChatChannel::Whisper { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_channel_serialization() {
+        let json = serde_json::to_string(&ChatChannel::OutOfCharacter).unwrap();
+        assert_eq!(json, r#"{"kind":"out_of_character"}"#);
+
+        let channel: ChatChannel =
+            serde_json::from_str(r#"{"kind":"whisper","to_character_id":"c1"}"#).unwrap();
+        assert_eq!(
+            channel,
+            ChatChannel::Whisper {
+                to_character_id: Some("c1".to_string())
+            }
+        );
+        let channel: ChatChannel = serde_json::from_str(r#"{"kind":"whisper"}"#).unwrap();
+        assert_eq!(
+            channel,
+            ChatChannel::Whisper {
+                to_character_id: None
+            }
+        );
+    }
+}
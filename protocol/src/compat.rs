@@ -0,0 +1,126 @@
+//! Wire format for clients built before outcomes were sent as enums
+//!
+//! Messages carry `SuccessType` and `ControllingDie` values and leave the
+//! wording to the client. Older clients expect English strings instead;
+//! they connect with `?compat=legacy` and get them added back here.
+
+use serde_json::Value;
+
+use crate::{ControllingDie, ServerMessage};
+
+/// Serialize a message the way clients before the enum change expect
+pub fn to_legacy_json(message: &ServerMessage) -> String {
+    let mut json = serde_json::to_value(message).unwrap_or(Value::Null);
+    if let Some(payload) = json.get_mut("payload") {
+        patch(message, payload);
+    }
+    json.to_string()
+}
+
+fn patch(message: &ServerMessage, payload: &mut Value) {
+    match message {
+        ServerMessage::DetailedRollResult {
+            roll_type,
+            roll_details,
+            ..
+        } => {
+            payload["outcome_description"] = roll_details
+                .success_type
+                .label(roll_type.is_reaction())
+                .into();
+        }
+        ServerMessage::GroupRollResult {
+            roll_type,
+            success_type,
+            ..
+        } => {
+            payload["outcome_description"] = success_type.label(roll_type.is_reaction()).into();
+        }
+        ServerMessage::RollResult { roll, .. } => {
+            payload["roll"]["controlling_die"] = match roll.controlling_die {
+                ControllingDie::Hope => "Hope",
+                ControllingDie::Fear => "Fear",
+                ControllingDie::Tied => "Tied",
+            }
+            .into();
+        }
+        ServerMessage::AttackResult {
+            controlling_die, ..
+        } => {
+            // Attacks used to report a tie as Fear
+            payload["controlling_die"] = match controlling_die {
+                ControllingDie::Hope => "hope",
+                ControllingDie::Fear | ControllingDie::Tied => "fear",
+            }
+            .into();
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupHelperResult, RollType, SuccessType};
+
+    #[test]
+    fn test_legacy_json_adds_display_strings() {
+        let message = ServerMessage::GroupRollResult {
+            request_id: "r1".to_string(),
+            context: "Sneak past".to_string(),
+            leader_id: "a".to_string(),
+            leader_name: "Theron".to_string(),
+            roll_type: RollType::Action,
+            helpers: vec![GroupHelperResult {
+                character_id: "b".to_string(),
+                character_name: "Mira".to_string(),
+                success: true,
+            }],
+            group_modifier: 1,
+            total: 14,
+            difficulty: 12,
+            success_type: SuccessType::SuccessWithFear,
+            hidden: false,
+        };
+
+        let current: Value = serde_json::from_str(&message.to_json()).unwrap();
+        assert!(current["payload"].get("outcome_description").is_none());
+        assert_eq!(current["payload"]["success_type"], "success_with_fear");
+
+        let legacy: Value = serde_json::from_str(&to_legacy_json(&message)).unwrap();
+        assert_eq!(
+            legacy["payload"]["outcome_description"],
+            "SUCCESS WITH FEAR"
+        );
+        assert_eq!(legacy["payload"]["success_type"], "success_with_fear");
+    }
+
+    #[test]
+    fn test_legacy_json_attack_reports_lowercase_die() {
+        let message = ServerMessage::AttackResult {
+            attacker_id: "a".to_string(),
+            attacker_name: "Theron".to_string(),
+            target_id: "g".to_string(),
+            target_name: "Goblin".to_string(),
+            hope: 7,
+            fear: 7,
+            modifier: 2,
+            advantage_die: None,
+            disadvantage_die: None,
+            total: 16,
+            target_evasion: 10,
+            hit: true,
+            controlling_die: ControllingDie::Tied,
+            is_critical: true,
+            narration: None,
+        };
+
+        let legacy: Value = serde_json::from_str(&to_legacy_json(&message)).unwrap();
+        assert_eq!(legacy["payload"]["controlling_die"], "fear");
+        assert_eq!(
+            serde_json::from_str::<Value>(&message.to_json()).unwrap()["payload"]
+                ["controlling_die"],
+            "tied"
+        );
+    }
+}
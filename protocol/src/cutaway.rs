@@ -0,0 +1,19 @@
+//! Cutaways: a scene with part of the party while the rest wait
+
+use serde::{Deserialize, Serialize};
+
+/// Longest scene label accepted
+pub const MAX_CUTAWAY_LABEL_LENGTH: usize = 80;
+
+/// A running cutaway, as shown to clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CutawayInfo {
+    pub id: String,
+    /// What the GM called the scene, e.g. "The vault"; may be empty
+    pub label: String,
+    /// Characters in the scene
+    pub character_ids: Vec<String>,
+    pub character_names: Vec<String>,
+}
@@ -0,0 +1,267 @@
+//! Damage types, resistances, immunities and vulnerabilities
+//!
+//! Every source of damage is physical or magical and may carry elemental
+//! tags. A target resistant to any of those tags takes half damage, a
+//! vulnerable target takes double, and an immune target takes none.
+//! Resistance and vulnerability cancel out and neither stacks.
+
+use serde::{Deserialize, Serialize};
+
+/// Physical or magical damage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DamageKind {
+    #[default]
+    Physical,
+    Magical,
+}
+
+impl DamageKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageKind::Physical => "physical",
+            DamageKind::Magical => "magical",
+        }
+    }
+}
+
+/// Elemental tag carried by a damage source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Element {
+    Fire,
+    Cold,
+    Lightning,
+    Acid,
+    Poison,
+    Necrotic,
+    Radiant,
+    Psychic,
+}
+
+impl Element {
+    pub fn name(self) -> &'static str {
+        match self {
+            Element::Fire => "fire",
+            Element::Cold => "cold",
+            Element::Lightning => "lightning",
+            Element::Acid => "acid",
+            Element::Poison => "poison",
+            Element::Necrotic => "necrotic",
+            Element::Radiant => "radiant",
+            Element::Psychic => "psychic",
+        }
+    }
+}
+
+/// The type of a weapon, adversary attack or spell
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DamageType {
+    pub kind: DamageKind,
+    #[serde(default)]
+    pub elements: Vec<Element>,
+}
+
+impl DamageType {
+    pub fn physical() -> Self {
+        Self::default()
+    }
+
+    pub fn magical(elements: Vec<Element>) -> Self {
+        Self {
+            kind: DamageKind::Magical,
+            elements,
+        }
+    }
+
+    /// Whether a defense entry applies to this damage
+    pub fn has(&self, tag: DamageTag) -> bool {
+        match tag {
+            DamageTag::Kind(kind) => self.kind == kind,
+            DamageTag::Element(element) => self.elements.contains(&element),
+        }
+    }
+}
+
+/// An entry in a resistance, immunity or vulnerability list
+///
+/// Serialized as the bare name, e.g. `"physical"` or `"fire"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum DamageTag {
+    Kind(DamageKind),
+    Element(Element),
+}
+
+impl DamageTag {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageTag::Kind(kind) => kind.name(),
+            DamageTag::Element(element) => element.name(),
+        }
+    }
+}
+
+/// Damage types a character or adversary shrugs off or suffers from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Defenses {
+    #[serde(default)]
+    pub resistances: Vec<DamageTag>,
+    #[serde(default)]
+    pub immunities: Vec<DamageTag>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageTag>,
+}
+
+impl Defenses {
+    pub fn is_empty(&self) -> bool {
+        self.resistances.is_empty() && self.immunities.is_empty() && self.vulnerabilities.is_empty()
+    }
+
+    /// Summary for the event log, e.g. "Resists physical; Immune to fire"
+    pub fn describe(&self) -> String {
+        let list = |tags: &[DamageTag]| {
+            tags.iter()
+                .map(|tag| tag.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut parts = Vec::new();
+        if !self.resistances.is_empty() {
+            parts.push(format!("Resists {}", list(&self.resistances)));
+        }
+        if !self.immunities.is_empty() {
+            parts.push(format!("Immune to {}", list(&self.immunities)));
+        }
+        if !self.vulnerabilities.is_empty() {
+            parts.push(format!("Vulnerable to {}", list(&self.vulnerabilities)));
+        }
+
+        if parts.is_empty() {
+            "No resistances".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+
+    /// How incoming damage of this type is adjusted
+    pub fn adjustment(&self, damage: &DamageType) -> DamageAdjustment {
+        let matches = |tags: &[DamageTag]| tags.iter().any(|tag| damage.has(*tag));
+
+        if matches(&self.immunities) {
+            return DamageAdjustment::Immune;
+        }
+        match (matches(&self.resistances), matches(&self.vulnerabilities)) {
+            (true, false) => DamageAdjustment::Halved,
+            (false, true) => DamageAdjustment::Doubled,
+            _ => DamageAdjustment::None,
+        }
+    }
+}
+
+/// Adjustment applied to rolled damage before armor and thresholds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DamageAdjustment {
+    #[default]
+    None,
+    Halved,
+    Doubled,
+    Immune,
+}
+
+impl DamageAdjustment {
+    /// Adjust rolled damage (halving rounds down)
+    pub fn apply(self, raw_damage: u16) -> u16 {
+        match self {
+            DamageAdjustment::None => raw_damage,
+            DamageAdjustment::Halved => raw_damage / 2,
+            DamageAdjustment::Doubled => raw_damage.saturating_mul(2),
+            DamageAdjustment::Immune => 0,
+        }
+    }
+}
+
+/// What one roll of area damage did to one target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AreaDamageTarget {
+    pub target_id: String,
+    pub target_name: String,
+    /// From the target's own defenses
+    pub adjustment: DamageAdjustment,
+    pub adjusted_damage: u16,
+    /// After the target's own armor
+    pub after_armor: u16,
+    pub hp_lost: u8,
+    pub stress_gained: u8,
+    pub new_hp: u8,
+    pub new_stress: u8,
+    pub taken_out: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fire_bolt() -> DamageType {
+        DamageType::magical(vec![Element::Fire])
+    }
+
+    #[test]
+    fn test_adjustments() {
+        let mut defenses = Defenses::default();
+        assert_eq!(defenses.adjustment(&fire_bolt()), DamageAdjustment::None);
+
+        defenses.resistances = vec![DamageTag::Kind(DamageKind::Magical)];
+        assert_eq!(defenses.adjustment(&fire_bolt()), DamageAdjustment::Halved);
+        assert_eq!(
+            defenses.adjustment(&DamageType::physical()),
+            DamageAdjustment::None
+        );
+
+        defenses.vulnerabilities = vec![DamageTag::Element(Element::Fire)];
+        assert_eq!(defenses.adjustment(&fire_bolt()), DamageAdjustment::None);
+
+        defenses.immunities = vec![DamageTag::Element(Element::Fire)];
+        assert_eq!(defenses.adjustment(&fire_bolt()), DamageAdjustment::Immune);
+    }
+
+    #[test]
+    fn test_apply_adjustment() {
+        assert_eq!(DamageAdjustment::None.apply(7), 7);
+        assert_eq!(DamageAdjustment::Halved.apply(7), 3);
+        assert_eq!(DamageAdjustment::Doubled.apply(7), 14);
+        assert_eq!(DamageAdjustment::Immune.apply(7), 0);
+    }
+
+    #[test]
+    fn test_defenses_deserialize_bare_names() {
+        let json = r#"{"resistances":["physical"],"vulnerabilities":["cold"]}"#;
+        let defenses: Defenses = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            defenses.resistances,
+            vec![DamageTag::Kind(DamageKind::Physical)]
+        );
+        assert_eq!(
+            defenses.vulnerabilities,
+            vec![DamageTag::Element(Element::Cold)]
+        );
+        assert!(defenses.immunities.is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+//! Dice expressions
+//!
+//! Parses and rolls expressions like "2d8+3", "2d6+1d4+3", "4d6kh3",
+//! "2d20kl1" and "1d6!" (exploding), keeping every die so clients can
+//! animate each one.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Most dice a single expression may roll (before explosions)
+pub const MAX_DICE: u16 = 100;
+
+/// Largest die accepted
+pub const MAX_SIDES: u16 = 1000;
+
+/// Times a single exploding die may re-roll
+pub const MAX_EXPLOSIONS: u8 = 10;
+
+/// Which dice of a group count toward the total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    Highest(u16),
+    Lowest(u16),
+}
+
+/// One "XdY" term of an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceGroup {
+    pub count: u16,
+    pub sides: u16,
+    pub keep: Option<Keep>,
+    /// A die showing its maximum is rolled again and added
+    pub exploding: bool,
+    /// Subtracted rather than added
+    pub negative: bool,
+}
+
+impl DiceGroup {
+    /// Number of dice that count toward the total
+    fn kept_count(&self) -> u16 {
+        match self.keep {
+            Some(Keep::Highest(n)) | Some(Keep::Lowest(n)) => n.min(self.count),
+            None => self.count,
+        }
+    }
+}
+
+/// A parsed dice expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceExpression {
+    /// The expression as parsed, lowercase without spaces
+    pub text: String,
+    pub groups: Vec<DiceGroup>,
+    /// Sum of the flat terms
+    pub modifier: i32,
+}
+
+/// One die as rolled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DieResult {
+    pub sides: u16,
+    /// Face shown, including any explosion re-rolls added on
+    pub value: u16,
+    /// Whether it counts toward the total (false when dropped by kh/kl)
+    pub kept: bool,
+    /// Whether it was subtracted
+    #[serde(default)]
+    pub negative: bool,
+    /// Times it exploded
+    #[serde(default)]
+    pub explosions: u8,
+}
+
+/// The outcome of rolling a dice expression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DiceRoll {
+    pub expression: String,
+    pub dice: Vec<DieResult>,
+    pub modifier: i32,
+    /// Kept dice plus modifier, never below 0
+    pub total: u16,
+}
+
+impl DiceExpression {
+    /// Parse an expression such as "2d6+1d4+3" or "4d6kh3"
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let text: String = input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        if text.is_empty() {
+            return Err("Dice expression is empty".to_string());
+        }
+
+        let mut groups = Vec::new();
+        let mut modifier: i32 = 0;
+        let mut total_dice: u16 = 0;
+
+        for (negative, term) in split_terms(&text)? {
+            match term.find('d') {
+                Some(d_pos) => {
+                    let group = parse_group(&term[..d_pos], &term[d_pos + 1..], negative)
+                        .map_err(|e| format!("Invalid dice term '{}': {}", term, e))?;
+                    total_dice = total_dice.saturating_add(group.count);
+                    groups.push(group);
+                }
+                None => {
+                    let value: i32 = term
+                        .parse()
+                        .map_err(|_| format!("Invalid dice term '{}'", term))?;
+                    modifier = modifier.saturating_add(if negative { -value } else { value });
+                }
+            }
+        }
+
+        if total_dice > MAX_DICE {
+            return Err(format!("At most {} dice may be rolled at once", MAX_DICE));
+        }
+
+        Ok(Self {
+            text,
+            groups,
+            modifier,
+        })
+    }
+
+    /// Roll every group
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> DiceRoll {
+        let mut dice = Vec::new();
+        let mut sum: i32 = self.modifier;
+
+        for group in &self.groups {
+            let start = dice.len();
+            for _ in 0..group.count {
+                let mut value = rng.gen_range(1..=group.sides);
+                let mut explosions = 0;
+                if group.exploding && group.sides > 1 {
+                    let mut last = value;
+                    while last == group.sides && explosions < MAX_EXPLOSIONS {
+                        last = rng.gen_range(1..=group.sides);
+                        value = value.saturating_add(last);
+                        explosions += 1;
+                    }
+                }
+                dice.push(DieResult {
+                    sides: group.sides,
+                    value,
+                    kept: true,
+                    negative: group.negative,
+                    explosions,
+                });
+            }
+
+            // Drop the dice kh/kl leaves out
+            let rolled = &mut dice[start..];
+            let mut order: Vec<usize> = (0..rolled.len()).collect();
+            match group.keep {
+                Some(Keep::Highest(_)) => {
+                    order.sort_by_key(|&i| std::cmp::Reverse(rolled[i].value))
+                }
+                Some(Keep::Lowest(_)) => order.sort_by_key(|&i| rolled[i].value),
+                None => {}
+            }
+            for &i in order.iter().skip(group.kept_count() as usize) {
+                rolled[i].kept = false;
+            }
+
+            for die in rolled.iter().filter(|d| d.kept) {
+                let value = die.value as i32;
+                sum += if die.negative { -value } else { value };
+            }
+        }
+
+        DiceRoll {
+            expression: self.text.clone(),
+            dice,
+            modifier: self.modifier,
+            total: sum.clamp(0, u16::MAX as i32) as u16,
+        }
+    }
+
+    /// Highest total the added dice can show, ignoring explosions and the modifier
+    ///
+    /// "2d8+3" gives 16, "4d6kh3" gives 18.
+    pub fn max_dice(&self) -> u16 {
+        self.groups
+            .iter()
+            .filter(|g| !g.negative)
+            .fold(0u16, |total, g| {
+                total.saturating_add(g.kept_count().saturating_mul(g.sides))
+            })
+    }
+}
+
+/// Parse and roll in one go
+pub fn roll<R: Rng + ?Sized>(expression: &str, rng: &mut R) -> Result<DiceRoll, String> {
+    Ok(DiceExpression::parse(expression)?.roll(rng))
+}
+
+/// Split "2d6+1d4-1" into signed terms
+fn split_terms(text: &str) -> Result<Vec<(bool, &str)>, String> {
+    let mut terms = Vec::new();
+    let mut negative = false;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if c == '+' || c == '-' {
+            if i > start {
+                terms.push((negative, &text[start..i]));
+            } else if i > 0 {
+                return Err(format!("Misplaced '{}' in dice expression", c));
+            }
+            negative = c == '-';
+            start = i + 1;
+        }
+    }
+    if start >= text.len() {
+        return Err("Dice expression ends with an operator".to_string());
+    }
+    terms.push((negative, &text[start..]));
+    Ok(terms)
+}
+
+/// Parse the parts either side of the 'd' in "4d6!kh3"
+fn parse_group(count: &str, rest: &str, negative: bool) -> Result<DiceGroup, String> {
+    let count = if count.is_empty() {
+        1
+    } else {
+        count.parse::<u16>().map_err(|_| "bad dice count")?
+    };
+
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let sides = rest[..digits]
+        .parse::<u16>()
+        .map_err(|_| "missing die size")?;
+    let mut suffix = &rest[digits..];
+
+    let exploding = suffix.starts_with('!');
+    if exploding {
+        suffix = &suffix[1..];
+    }
+
+    let keep = if suffix.is_empty() {
+        None
+    } else {
+        let (highest, n) = if let Some(n) = suffix.strip_prefix("kh") {
+            (true, n)
+        } else if let Some(n) = suffix.strip_prefix("kl") {
+            (false, n)
+        } else {
+            return Err(format!("unknown suffix '{}'", suffix));
+        };
+        let n = if n.is_empty() {
+            1
+        } else {
+            n.parse::<u16>().map_err(|_| "bad keep count")?
+        };
+        if n == 0 {
+            return Err("must keep at least one die".to_string());
+        }
+        Some(if highest {
+            Keep::Highest(n)
+        } else {
+            Keep::Lowest(n)
+        })
+    };
+
+    if count == 0 {
+        return Err("must roll at least one die".to_string());
+    }
+    if sides == 0 || sides > MAX_SIDES {
+        return Err(format!("dice must have 1-{} sides", MAX_SIDES));
+    }
+
+    Ok(DiceGroup {
+        count,
+        sides,
+        keep,
+        exploding,
+        negative,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dice_expressions() {
+        let expr = DiceExpression::parse("2d6 + 1d4 + 3").unwrap();
+        assert_eq!(expr.groups.len(), 2);
+        assert_eq!(expr.modifier, 3);
+        assert_eq!(expr.max_dice(), 16);
+
+        let expr = DiceExpression::parse("4d6kh3").unwrap();
+        assert_eq!(expr.groups[0].keep, Some(Keep::Highest(3)));
+        assert_eq!(expr.max_dice(), 18);
+
+        let expr = DiceExpression::parse("d12!-1").unwrap();
+        assert!(expr.groups[0].exploding);
+        assert_eq!(expr.groups[0].count, 1);
+        assert_eq!(expr.modifier, -1);
+        assert_eq!(
+            expr.max_dice(),
+            12,
+            "the modifier isn't part of the maximum"
+        );
+        assert_eq!(DiceExpression::parse("5").unwrap().max_dice(), 0);
+
+        for bad in ["", "2d", "d0", "2d6+", "3x4", "2d6kq", "101d6", "4d6kh0"] {
+            assert!(
+                DiceExpression::parse(bad).is_err(),
+                "{} should not parse",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_roll_keeps_and_explodes() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let result = roll("4d6kh3", &mut rng).unwrap();
+            assert_eq!(result.dice.len(), 4);
+            assert_eq!(result.dice.iter().filter(|d| d.kept).count(), 3);
+            let lowest = result.dice.iter().map(|d| d.value).min().unwrap();
+            let sum: u16 = result.dice.iter().map(|d| d.value).sum();
+            assert_eq!(result.total, sum - lowest);
+
+            let result = roll("1d2!", &mut rng).unwrap();
+            let die = &result.dice[0];
+            assert!(die.value >= 1 && die.value <= 2 * (MAX_EXPLOSIONS as u16 + 1));
+            assert_eq!(die.explosions > 0, die.value >= 3, "a 2 always explodes");
+
+            let result = roll("1d4-10", &mut rng).unwrap();
+            assert_eq!(result.total, 0, "totals never go negative");
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! GM drawings on the map: lines, circles and zones marking areas of effect
+//! and hazards, kept with the scene they were drawn on
+
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/// Most drawings in one scene
+pub const MAX_DRAWINGS: usize = 100;
+
+/// Most points in a line or zone
+pub const MAX_DRAWING_POINTS: usize = 200;
+
+/// Longest drawing label accepted, in characters
+pub const MAX_DRAWING_LABEL_LENGTH: usize = 40;
+
+/// What was drawn
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DrawingShape {
+    /// An open path
+    Line {
+        points: Vec<Position>,
+    },
+    Circle {
+        center: Position,
+        radius: f32,
+    },
+    /// A closed, shaded polygon
+    Zone {
+        points: Vec<Position>,
+    },
+}
+
+/// A drawing the GM left on the map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Drawing {
+    pub id: String,
+    pub shape: DrawingShape,
+    /// CSS hex color, e.g. "#e74c3c"
+    pub color: String,
+    /// Shown next to the drawing, e.g. "Fire wall"
+    #[serde(default)]
+    pub label: String,
+}
+
+impl DrawingShape {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            DrawingShape::Line { points } => check_points(points, 2, "line"),
+            DrawingShape::Zone { points } => check_points(points, 3, "zone"),
+            DrawingShape::Circle { center, radius } => {
+                if !is_finite(center) || !radius.is_finite() || *radius <= 0.0 {
+                    return Err("A circle needs a centre and a radius above zero".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn is_finite(position: &Position) -> bool {
+    position.x.is_finite() && position.y.is_finite()
+}
+
+fn check_points(points: &[Position], min: usize, shape: &str) -> Result<(), String> {
+    if points.len() < min || points.len() > MAX_DRAWING_POINTS {
+        return Err(format!(
+            "A {} needs between {} and {} points",
+            shape, min, MAX_DRAWING_POINTS
+        ));
+    }
+    if !points.iter().all(is_finite) {
+        return Err("Drawing points must be numbers".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_shapes() {
+        let line = DrawingShape::Line {
+            points: vec![Position::new(0.0, 0.0), Position::new(10.0, 0.0)],
+        };
+        assert!(line.validate().is_ok());
+
+        let zone = DrawingShape::Zone {
+            points: vec![Position::new(0.0, 0.0), Position::new(10.0, 0.0)],
+        };
+        assert!(zone.validate().is_err(), "a zone needs three corners");
+
+        let circle = DrawingShape::Circle {
+            center: Position::new(100.0, 100.0),
+            radius: 0.0,
+        };
+        assert!(circle.validate().is_err());
+    }
+
+    #[test]
+    fn test_shape_json() {
+        let circle: DrawingShape =
+            serde_json::from_str(r#"{"kind":"circle","center":{"x":5,"y":6},"radius":30}"#)
+                .unwrap();
+        assert_eq!(
+            circle,
+            DrawingShape::Circle {
+                center: Position::new(5.0, 6.0),
+                radius: 30.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Wire formats for protocol messages
+//!
+//! JSON is the default. A client can ask for MessagePack instead when it
+//! connects (`/ws?encoding=msgpack`); the same message types go out as
+//! binary frames, which are smaller and cheaper to parse on slow phones.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A way of turning protocol messages into bytes and back
+pub trait Codec {
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, String>;
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String>;
+}
+
+/// UTF-8 JSON text
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(value).map_err(|e| format!("Failed to encode JSON: {}", e))
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid JSON: {}", e))
+    }
+}
+
+/// MessagePack, with structs as maps so they keep their field names
+pub struct MessagePackCodec;
+
+impl Codec for MessagePackCodec {
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        rmp_serde::to_vec_named(value).map_err(|e| format!("Failed to encode MessagePack: {}", e))
+    }
+
+    fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+        rmp_serde::from_slice(bytes).map_err(|e| format!("Invalid MessagePack: {}", e))
+    }
+}
+
+/// The wire format a connection chose
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    #[default]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+}
+
+impl Encoding {
+    /// Whether messages travel in binary frames rather than text
+    pub fn is_binary(self) -> bool {
+        self != Encoding::Json
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Encoding::Json => JsonCodec::encode(value),
+            Encoding::MessagePack => MessagePackCodec::encode(value),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            Encoding::Json => JsonCodec::decode(bytes),
+            Encoding::MessagePack => MessagePackCodec::decode(bytes),
+        }
+    }
+
+    /// Re-encode a message as JSON text, e.g. to journal a binary frame
+    pub fn to_json_text(self, bytes: &[u8]) -> Result<String, String> {
+        let value: serde_json::Value = self.decode(bytes)?;
+        Ok(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientMessage, Position, ServerMessage};
+
+    #[test]
+    fn test_messages_round_trip_through_messagepack() {
+        let sent = ServerMessage::CharacterMoved {
+            character_id: "char-1".to_string(),
+            position: Position::new(100.0, 200.0),
+            path: vec![Position::new(90.0, 200.0)],
+            teleported: false,
+            crossed_hazards: vec![],
+            rotation: 90.0,
+        };
+        let bytes = Encoding::MessagePack.encode(&sent).unwrap();
+        assert!(bytes.len() < Encoding::Json.encode(&sent).unwrap().len());
+
+        // Decoded generically, it reads like the JSON message
+        let json = Encoding::MessagePack.to_json_text(&bytes).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::to_value(&sent).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_client_messages_decode_from_messagepack() {
+        let json = r#"{"type":"move_character","payload":{"x":1.0,"y":2.0}}"#;
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let bytes = Encoding::MessagePack.encode(&value).unwrap();
+
+        let message: ClientMessage = Encoding::MessagePack.decode(&bytes).unwrap();
+        assert!(matches!(message, ClientMessage::MoveCharacter { .. }));
+        assert!(Encoding::MessagePack
+            .decode::<ClientMessage>(b"nope")
+            .is_err());
+
+        let encoding: Encoding = serde_json::from_str(r#""msgpack""#).unwrap();
+        assert!(encoding.is_binary());
+    }
+}
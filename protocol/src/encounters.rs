@@ -0,0 +1,259 @@
+//! Building fights against a Battle Points budget
+//!
+//! As the SRD has it, a party gets (3 × its size) + 2 Battle Points to spend
+//! on adversaries. Each role costs a set amount, and Minions are bought a
+//! group at a time, one group being as many Minions as there are characters.
+//! The budget then shifts for how the fight is meant to go:
+//!
+//! - -1 for an easier or shorter fight, +2 for a harder or longer one
+//! - -2 when two or more Solos are in it
+//! - -2 when every adversary deals an extra 1d4 (or +2) damage
+//! - +1 when any adversary is from a lower tier than the party
+//! - +1 when there are no Bruisers, Hordes, Leaders or Solos
+//!
+//! A preset can also be taken from the adversaries already on the map, each
+//! entry then keeping where its adversaries stood so the same ambush can be
+//! set up again.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AdversaryRole, Position};
+
+/// Largest party the builder budgets for
+pub const MAX_PARTY_SIZE: u8 = 10;
+
+/// Battle Points before adjustments
+pub fn base_budget(party_size: u8) -> i16 {
+    3 * party_size as i16 + 2
+}
+
+/// What one adversary of a role costs (for Minions, one group of them)
+pub fn role_cost(role: AdversaryRole) -> u8 {
+    match role {
+        AdversaryRole::Minion | AdversaryRole::Support => 1,
+        AdversaryRole::Horde
+        | AdversaryRole::Ranged
+        | AdversaryRole::Skulk
+        | AdversaryRole::Standard => 2,
+        AdversaryRole::Leader => 3,
+        AdversaryRole::Bruiser => 4,
+        AdversaryRole::Solo => 5,
+    }
+}
+
+/// How hard the GM means the fight to be
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EncounterDifficulty {
+    /// Easier or shorter
+    Easier,
+    #[default]
+    Standard,
+    /// Harder or longer
+    Harder,
+}
+
+/// Some adversaries of one template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EncounterEntry {
+    pub template_id: String,
+    pub count: u8,
+    /// Where each one spawns, in order; any without a spot (or whose spot
+    /// is off the scene) lands somewhere random
+    #[serde(default)]
+    pub positions: Vec<Position>,
+}
+
+/// A fight in the making: who it's for and who's in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EncounterPlan {
+    pub party_size: u8,
+    /// The party's tier, 1 to 4
+    pub tier: u8,
+    pub entries: Vec<EncounterEntry>,
+    #[serde(default)]
+    pub difficulty: EncounterDifficulty,
+    /// Every adversary deals an extra 1d4 (or +2) damage
+    #[serde(default)]
+    pub extra_damage: bool,
+}
+
+/// One entry of a plan, priced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EncounterLine {
+    pub template_id: String,
+    pub name: String,
+    pub role: AdversaryRole,
+    pub tier: u8,
+    pub count: u8,
+    pub cost: u8,
+}
+
+impl EncounterLine {
+    /// Price `count` adversaries of a role; Minions come in groups of
+    /// `party_size`, a part-group costing a whole one
+    pub fn new(
+        template_id: impl Into<String>,
+        name: impl Into<String>,
+        role: AdversaryRole,
+        tier: u8,
+        count: u8,
+        party_size: u8,
+    ) -> Self {
+        let units = match role {
+            AdversaryRole::Minion => count.div_ceil(party_size.max(1)),
+            _ => count,
+        };
+        Self {
+            template_id: template_id.into(),
+            name: name.into(),
+            role,
+            tier,
+            count,
+            cost: units.saturating_mul(role_cost(role)),
+        }
+    }
+}
+
+/// A change to the budget and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BudgetAdjustment {
+    pub reason: String,
+    pub points: i8,
+}
+
+/// A plan against its budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EncounterBudget {
+    pub party_size: u8,
+    pub tier: u8,
+    pub base_budget: i16,
+    pub adjustments: Vec<BudgetAdjustment>,
+    /// Base budget plus adjustments
+    pub budget: i16,
+    pub lines: Vec<EncounterLine>,
+    pub spent: i16,
+    /// Negative when over budget
+    pub remaining: i16,
+}
+
+impl EncounterBudget {
+    /// Total up priced lines against a party's budget
+    pub fn new(plan: &EncounterPlan, lines: Vec<EncounterLine>) -> Self {
+        let mut adjustments = Vec::new();
+        let mut adjust = |reason: &str, points: i8| {
+            adjustments.push(BudgetAdjustment {
+                reason: reason.to_string(),
+                points,
+            })
+        };
+        match plan.difficulty {
+            EncounterDifficulty::Easier => adjust("Easier or shorter fight", -1),
+            EncounterDifficulty::Harder => adjust("Harder or longer fight", 2),
+            EncounterDifficulty::Standard => {}
+        }
+        let solos: u32 = lines
+            .iter()
+            .filter(|l| l.role == AdversaryRole::Solo)
+            .map(|l| l.count as u32)
+            .sum();
+        if solos >= 2 {
+            adjust("Two or more Solos", -2);
+        }
+        if plan.extra_damage {
+            adjust("Extra damage on every adversary", -2);
+        }
+        if lines.iter().any(|l| l.tier < plan.tier) {
+            adjust("Adversaries from a lower tier", 1);
+        }
+        let heavy = [
+            AdversaryRole::Bruiser,
+            AdversaryRole::Horde,
+            AdversaryRole::Leader,
+            AdversaryRole::Solo,
+        ];
+        if !lines.is_empty() && !lines.iter().any(|l| heavy.contains(&l.role)) {
+            adjust("No Bruisers, Hordes, Leaders or Solos", 1);
+        }
+
+        let base_budget = base_budget(plan.party_size);
+        let budget = base_budget + adjustments.iter().map(|a| a.points as i16).sum::<i16>();
+        let spent = lines.iter().map(|l| l.cost as i16).sum();
+        Self {
+            party_size: plan.party_size,
+            tier: plan.tier,
+            base_budget,
+            adjustments,
+            budget,
+            lines,
+            spent,
+            remaining: budget - spent,
+        }
+    }
+
+    pub fn over_budget(&self) -> bool {
+        self.remaining < 0
+    }
+}
+
+/// A plan the GM kept for later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EncounterPreset {
+    pub name: String,
+    pub plan: EncounterPlan,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(difficulty: EncounterDifficulty) -> EncounterPlan {
+        EncounterPlan {
+            party_size: 4,
+            tier: 2,
+            entries: Vec::new(),
+            difficulty,
+            extra_damage: false,
+        }
+    }
+
+    #[test]
+    fn test_battle_points_budget() {
+        assert_eq!(base_budget(4), 14);
+        // Five Minions for four characters are two groups
+        let minions = EncounterLine::new("m", "Minion", AdversaryRole::Minion, 2, 5, 4);
+        assert_eq!(minions.cost, 2);
+        let bruiser = EncounterLine::new("b", "Bruiser", AdversaryRole::Bruiser, 2, 1, 4);
+        let budget = EncounterBudget::new(
+            &plan(EncounterDifficulty::Standard),
+            vec![minions.clone(), bruiser],
+        );
+        assert_eq!((budget.budget, budget.spent, budget.remaining), (14, 6, 8));
+        assert!(budget.adjustments.is_empty());
+
+        // A lower-tier skulk, nothing heavy, and a harder fight
+        let skulk = EncounterLine::new("s", "Skulk", AdversaryRole::Skulk, 1, 8, 4);
+        let budget = EncounterBudget::new(&plan(EncounterDifficulty::Harder), vec![minions, skulk]);
+        assert_eq!(budget.budget, 18);
+        assert_eq!(budget.spent, 18);
+        assert!(!budget.over_budget());
+
+        let solos = EncounterLine::new("d", "Dragon", AdversaryRole::Solo, 2, 2, 4);
+        let budget = EncounterBudget::new(&plan(EncounterDifficulty::Easier), vec![solos]);
+        assert_eq!((budget.budget, budget.remaining), (11, 1));
+    }
+}
@@ -0,0 +1,93 @@
+//! Environments: scenes with mechanical weight
+//!
+//! An environment is an SRD stat block for a place or situation — an
+//! abandoned grove, a bar fight, a castle siege. It has a tier, a type, a
+//! Difficulty for rolls made against it, the adversaries likely to appear,
+//! and features the GM activates during play.
+
+use serde::{Deserialize, Serialize};
+
+/// What kind of scene an environment is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EnvironmentType {
+    #[default]
+    Exploration,
+    Social,
+    Traversal,
+    Event,
+}
+
+impl EnvironmentType {
+    pub fn name(self) -> &'static str {
+        match self {
+            EnvironmentType::Exploration => "Exploration",
+            EnvironmentType::Social => "Social",
+            EnvironmentType::Traversal => "Traversal",
+            EnvironmentType::Event => "Event",
+        }
+    }
+}
+
+/// How an environment feature comes into play
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureKind {
+    /// Always true of the scene
+    #[default]
+    Passive,
+    /// The GM spends the spotlight to make it happen
+    Action,
+    /// Happens in response to something the PCs do
+    Reaction,
+}
+
+/// A feature of an environment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EnvironmentFeature {
+    pub name: String,
+    #[serde(default)]
+    pub kind: FeatureKind,
+    pub description: String,
+    /// Fear the GM spends to activate it
+    #[serde(default)]
+    pub fear_cost: u8,
+}
+
+/// An environment in play, as sent to clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EnvironmentInfo {
+    pub id: String,
+    pub template: String,
+    pub name: String,
+    pub tier: u8,
+    pub kind: EnvironmentType,
+    pub difficulty: u16,
+    pub description: String,
+    pub impulses: Vec<String>,
+    pub features: Vec<EnvironmentFeature>,
+    /// Adversary template ids likely to appear here
+    pub potential_adversaries: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_defaults() {
+        let json = r#"{"name":"Overgrown Battlefield","description":"Old conflict lingers"}"#;
+        let feature: EnvironmentFeature = serde_json::from_str(json).unwrap();
+
+        assert_eq!(feature.kind, FeatureKind::Passive);
+        assert_eq!(feature.fear_cost, 0);
+    }
+}
@@ -0,0 +1,200 @@
+//! Weapons and armor
+
+use serde::{Deserialize, Serialize};
+
+use crate::damage::DamageType;
+
+/// Character traits a weapon can use for its attack roll
+pub const TRAITS: &[&str] = &[
+    "agility",
+    "strength",
+    "finesse",
+    "instinct",
+    "presence",
+    "knowledge",
+];
+
+/// Weapon range band, nearest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WeaponRange {
+    Melee,
+    VeryClose,
+    Close,
+    Far,
+    VeryFar,
+}
+
+impl WeaponRange {
+    /// Every band, nearest first
+    pub const ALL: [WeaponRange; 5] = [
+        WeaponRange::Melee,
+        WeaponRange::VeryClose,
+        WeaponRange::Close,
+        WeaponRange::Far,
+        WeaponRange::VeryFar,
+    ];
+
+    /// Farthest a band reaches, in feet
+    pub fn max_feet(self) -> f32 {
+        match self {
+            WeaponRange::Melee => 5.0,
+            WeaponRange::VeryClose => 10.0,
+            WeaponRange::Close => 30.0,
+            WeaponRange::Far => 100.0,
+            WeaponRange::VeryFar => 300.0,
+        }
+    }
+
+    /// The band a distance falls in, or `None` if it's out of range entirely
+    pub fn for_distance(feet: f32) -> Option<Self> {
+        Self::ALL.into_iter().find(|band| feet <= band.max_feet())
+    }
+
+    /// Whether a weapon with this range can hit something in `band`
+    pub fn reaches(self, band: WeaponRange) -> bool {
+        band <= self
+    }
+}
+
+impl std::fmt::Display for WeaponRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            WeaponRange::Melee => "Melee",
+            WeaponRange::VeryClose => "Very Close",
+            WeaponRange::Close => "Close",
+            WeaponRange::Far => "Far",
+            WeaponRange::VeryFar => "Very Far",
+        })
+    }
+}
+
+/// An equippable weapon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Weapon {
+    pub name: String,
+    #[serde(rename = "trait")]
+    pub trait_name: String, // "agility", "strength", etc.
+    pub range: WeaponRange,
+    pub damage_dice: String, // e.g., "1d8+1"
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl Weapon {
+    /// Check the weapon uses a real trait and has usable damage dice
+    pub fn validate(&self) -> Result<(), String> {
+        if !TRAITS.contains(&self.trait_name.to_lowercase().as_str()) {
+            return Err(format!("Invalid weapon trait: {}", self.trait_name));
+        }
+        if self.damage_dice.trim().is_empty() {
+            return Err("Weapon damage dice cannot be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Equippable armor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Armor {
+    pub name: String,
+    pub base_score: u8,
+    pub major_threshold: u8,
+    pub severe_threshold: u8,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl Armor {
+    /// Check the damage thresholds are in order
+    pub fn validate(&self) -> Result<(), String> {
+        if self.major_threshold >= self.severe_threshold {
+            return Err(format!(
+                "Major threshold ({}) must be below severe threshold ({})",
+                self.major_threshold, self.severe_threshold
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A character's equipped weapon and armor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Equipment {
+    pub weapon: Option<Weapon>,
+    pub armor: Option<Armor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn longsword() -> Weapon {
+        Weapon {
+            name: "Longsword".to_string(),
+            trait_name: "agility".to_string(),
+            range: WeaponRange::Melee,
+            damage_dice: "1d10+3".to_string(),
+            damage_type: DamageType::physical(),
+            features: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_range_bands() {
+        assert_eq!(WeaponRange::for_distance(0.0), Some(WeaponRange::Melee));
+        assert_eq!(WeaponRange::for_distance(5.0), Some(WeaponRange::Melee));
+        assert_eq!(WeaponRange::for_distance(25.0), Some(WeaponRange::Close));
+        assert_eq!(WeaponRange::for_distance(150.0), Some(WeaponRange::VeryFar));
+        assert_eq!(WeaponRange::for_distance(301.0), None);
+
+        assert!(WeaponRange::Far.reaches(WeaponRange::Close));
+        assert!(WeaponRange::Melee.reaches(WeaponRange::Melee));
+        assert!(!WeaponRange::Melee.reaches(WeaponRange::VeryClose));
+    }
+
+    #[test]
+    fn test_weapon_validate() {
+        assert!(longsword().validate().is_ok());
+
+        let mut weapon = longsword();
+        weapon.trait_name = "luck".to_string();
+        assert!(weapon.validate().is_err());
+
+        let mut weapon = longsword();
+        weapon.damage_dice = " ".to_string();
+        assert!(weapon.validate().is_err());
+    }
+
+    #[test]
+    fn test_armor_validate() {
+        let mut armor = Armor {
+            name: "Chainmail".to_string(),
+            base_score: 4,
+            major_threshold: 7,
+            severe_threshold: 15,
+            features: vec!["Heavy: -1 to Evasion".to_string()],
+        };
+        assert!(armor.validate().is_ok());
+
+        armor.severe_threshold = 7;
+        assert!(armor.validate().is_err());
+    }
+
+    #[test]
+    fn test_weapon_serializes_trait() {
+        let json = serde_json::to_string(&longsword()).unwrap();
+        assert!(json.contains(r#""trait":"agility""#));
+        assert!(json.contains(r#""range":"melee""#));
+    }
+}
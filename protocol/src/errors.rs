@@ -0,0 +1,85 @@
+//! Error replies: what went wrong with a request, and which request it was
+//!
+//! A client may add a `request_id` next to `type` and `payload` on any
+//! message; an error caused by that message echoes it back, so the client
+//! can tell which of its requests failed.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest correlation id echoed back, in characters
+pub const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// What kind of error a request ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The message wasn't JSON, or not a message the server knows
+    MalformedMessage,
+    /// The connection's role doesn't allow it (GM-only, spectators)
+    Forbidden,
+    /// An ID that can't be one, e.g. not a UUID
+    InvalidId,
+    /// Nothing has that ID
+    NotFound,
+    /// The game refused it, e.g. not enough Hope or an out-of-range value
+    Rejected,
+    /// Not a reply: the whole game was replaced (a save or the demo loaded)
+    /// and clients should reload
+    GameReplaced,
+    /// The connection sent too many messages too fast; this one was dropped
+    RateLimited,
+}
+
+/// The parts of a client message read before (and regardless of) the
+/// message itself, so even an unreadable message gets a useful error
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RequestHeader {
+    /// The message's `type`
+    #[serde(rename = "type", default)]
+    pub request_type: Option<String>,
+    /// Client-chosen correlation id
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+impl RequestHeader {
+    /// Read the header of a raw message, empty if it isn't a JSON object;
+    /// over-long correlation ids are dropped rather than echoed
+    pub fn parse(text: &str) -> Self {
+        let mut header: Self = serde_json::from_str(text).unwrap_or_default();
+        header.request_id = header
+            .request_id
+            .filter(|id| id.chars().count() <= MAX_REQUEST_ID_LENGTH);
+        header
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientMessage;
+
+    #[test]
+    fn test_request_header() {
+        let text = r#"{"type":"roll_duality","payload":{"modifier":1,"with_advantage":false},"request_id":"r-7"}"#;
+        let header = RequestHeader::parse(text);
+        assert_eq!(header.request_type.as_deref(), Some("roll_duality"));
+        assert_eq!(header.request_id.as_deref(), Some("r-7"));
+        // The message itself ignores the correlation id
+        assert!(serde_json::from_str::<ClientMessage>(text).is_ok());
+
+        assert_eq!(RequestHeader::parse("not json"), RequestHeader::default());
+        let long = format!(r#"{{"type":"connect","request_id":"{}"}}"#, "x".repeat(100));
+        assert_eq!(RequestHeader::parse(&long).request_id, None);
+    }
+
+    #[test]
+    fn test_error_code_json() {
+        assert_eq!(
+            serde_json::to_string(&ErrorCode::InvalidId).unwrap(),
+            r#""invalid_id""#
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Reading back the event log a page at a time
+//!
+//! Pages count back from the newest event: `offset` skips that many of the
+//! newest matching events, and a page lists what's left oldest first, so
+//! `offset = 0` is always the latest page.
+//!
+//! The log can also be read back by scene: an event marked
+//! [`LogMarker::Scene`], whether the GM marked the scene or set an
+//! environment, starts a scene, and within a scene each fight from
+//! [`LogMarker::CombatStarted`] to [`LogMarker::CombatEnded`] is a part of
+//! its own. The markers are set by the server, so no event's text (a
+//! character called "Scene" speaking, say) can open a scene or a fight.
+
+use serde::{Deserialize, Serialize};
+
+use crate::GameEventData;
+
+/// Events in a page when the request doesn't say
+pub const DEFAULT_EVENT_PAGE: usize = 50;
+
+/// Most events in one page
+pub const MAX_EVENT_PAGE: usize = 200;
+
+/// Message prefix of the event that starts a scene
+pub const SCENE_PREFIX: &str = "Scene: ";
+
+/// Where the log turns to a new scene, or a fight starts or ends
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LogMarker {
+    Scene { title: String },
+    CombatStarted,
+    CombatEnded,
+}
+
+/// Which events to list (`GET /api/events` query, `request_event_log`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct EventLogQuery {
+    /// Only this kind, e.g. "RollExecuted" or "roll_executed"
+    pub event_type: Option<String>,
+    /// Only events about the character with this name (any case)
+    pub character: Option<String>,
+    /// Newest matching events to skip
+    pub offset: usize,
+    /// Events in the page, [`DEFAULT_EVENT_PAGE`] if not given
+    pub limit: Option<usize>,
+}
+
+impl EventLogQuery {
+    /// Page size, within [`MAX_EVENT_PAGE`]
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_EVENT_PAGE).min(MAX_EVENT_PAGE)
+    }
+
+    /// Whether an event passes the filters
+    pub fn matches(&self, event: &GameEventData) -> bool {
+        let type_matches = self.event_type.as_ref().is_none_or(|wanted| {
+            let plain = |s: &str| s.replace('_', "").to_ascii_lowercase();
+            plain(wanted) == plain(&event.event_type)
+        });
+        let character_matches = self.character.as_ref().is_none_or(|wanted| {
+            event
+                .character_name
+                .as_ref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(wanted))
+        });
+        type_matches && character_matches
+    }
+
+    /// The requested page of a log (oldest first), and how many events
+    /// matched in all
+    pub fn page(&self, events: &[GameEventData]) -> (Vec<GameEventData>, usize) {
+        let matching: Vec<_> = events.iter().filter(|e| self.matches(e)).collect();
+        let total = matching.len();
+        let end = total.saturating_sub(self.offset);
+        let start = end.saturating_sub(self.page_size());
+        let page = matching[start..end].iter().map(|e| (*e).clone()).collect();
+        (page, total)
+    }
+}
+
+/// A stretch of the log inside or outside a fight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SceneLogPart {
+    /// Which fight of the session, from 1; `None` outside a fight
+    pub combat: Option<u32>,
+    pub events: Vec<GameEventData>,
+}
+
+/// The events of one scene, split at its fights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SceneLog {
+    /// `None` for whatever came before the first scene
+    pub scene: Option<String>,
+    pub parts: Vec<SceneLogPart>,
+}
+
+impl SceneLog {
+    fn new(scene: Option<String>, combat: Option<u32>) -> Self {
+        Self {
+            scene,
+            parts: vec![SceneLogPart {
+                combat,
+                events: Vec::new(),
+            }],
+        }
+    }
+
+    fn part(&mut self) -> &mut SceneLogPart {
+        self.parts.last_mut().expect("a scene always has a part")
+    }
+
+    fn start_part(&mut self, combat: Option<u32>) {
+        if self.part().events.is_empty() {
+            self.part().combat = combat;
+        } else {
+            self.parts.push(SceneLogPart {
+                combat,
+                events: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Split a log (oldest first) into scenes and fights, keeping only the
+/// events `query` matches; scene markers and combat starts and ends stay
+/// in so every part shows where it begins. Empty parts and scenes are
+/// dropped.
+pub fn group_by_scene(events: &[GameEventData], query: &EventLogQuery) -> Vec<SceneLog> {
+    let mut scenes = vec![SceneLog::new(None, None)];
+    let mut combats = 0;
+    let mut combat = None;
+
+    for event in events {
+        match &event.marker {
+            // A fight running into the next scene carries on there
+            Some(LogMarker::Scene { title }) => {
+                scenes.push(SceneLog::new(Some(title.clone()), combat));
+            }
+            Some(LogMarker::CombatStarted) => {
+                combats += 1;
+                combat = Some(combats);
+                if let Some(scene) = scenes.last_mut() {
+                    scene.start_part(combat);
+                }
+            }
+            _ => {}
+        }
+
+        if event.marker.is_some() || query.matches(event) {
+            if let Some(scene) = scenes.last_mut() {
+                scene.part().events.push(event.clone());
+            }
+        }
+        if combat.is_some() && event.marker == Some(LogMarker::CombatEnded) {
+            combat = None;
+            if let Some(scene) = scenes.last_mut() {
+                scene.start_part(None);
+            }
+        }
+    }
+
+    for scene in &mut scenes {
+        scene.parts.retain(|p| !p.events.is_empty());
+    }
+    scenes.retain(|s| !s.parts.is_empty());
+    scenes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, character: Option<&str>, message: &str) -> GameEventData {
+        GameEventData {
+            timestamp: "12:00:00".to_string(),
+            event_type: event_type.to_string(),
+            message: message.to_string(),
+            character_name: character.map(str::to_string),
+            details: None,
+            player_name: None,
+            cutaway: false,
+            marker: None,
+        }
+    }
+
+    fn marker(message: &str, marker: LogMarker) -> GameEventData {
+        GameEventData {
+            marker: Some(marker),
+            ..event("SystemMessage", None, message)
+        }
+    }
+
+    fn scene(title: &str) -> GameEventData {
+        let title = title.to_string();
+        marker(
+            &format!("{}{}", SCENE_PREFIX, title),
+            LogMarker::Scene { title },
+        )
+    }
+
+    #[test]
+    fn test_pages_count_back_from_the_newest() {
+        let log: Vec<_> = (0..5)
+            .map(|i| event("SystemMessage", None, &i.to_string()))
+            .collect();
+        let messages = |query: EventLogQuery| {
+            let (page, total) = query.page(&log);
+            assert_eq!(total, 5);
+            page.into_iter().map(|e| e.message).collect::<Vec<_>>()
+        };
+
+        let latest = EventLogQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(messages(latest.clone()), ["3", "4"]);
+        let older = EventLogQuery {
+            offset: 4,
+            ..latest
+        };
+        assert_eq!(messages(older), ["0"]);
+        let past_the_start = EventLogQuery {
+            offset: 9,
+            ..Default::default()
+        };
+        assert!(messages(past_the_start).is_empty());
+    }
+
+    #[test]
+    fn test_filters() {
+        let log = vec![
+            event("RollExecuted", Some("Theron"), "Theron rolled"),
+            event("CharacterMoved", Some("Theron"), "Theron moved"),
+            event("RollExecuted", Some("Lyra"), "Lyra rolled"),
+        ];
+        let query = EventLogQuery {
+            event_type: Some("roll_executed".to_string()),
+            character: Some("theron".to_string()),
+            ..Default::default()
+        };
+        let (page, total) = query.page(&log);
+        assert_eq!(total, 1);
+        assert_eq!(page[0].message, "Theron rolled");
+
+        let huge = EventLogQuery {
+            limit: Some(10_000),
+            ..Default::default()
+        };
+        assert_eq!(huge.page_size(), MAX_EVENT_PAGE);
+    }
+
+    #[test]
+    fn test_grouped_by_scene_and_fight() {
+        let log = vec![
+            event("SystemMessage", None, "Theron joined the game"),
+            scene("The Ambush at Thornwood"),
+            event("RollExecuted", Some("Theron"), "Theron rolled"),
+            marker("Combat started", LogMarker::CombatStarted),
+            event("CombatAction", Some("Theron"), "Theron took 3 damage"),
+            scene("The Burning Bridge"),
+            event("CombatAction", Some("Lyra"), "Lyra took 2 damage"),
+            marker("Combat ended: Victory", LogMarker::CombatEnded),
+            event("RollExecuted", Some("Lyra"), "Lyra rolled"),
+        ];
+        let scenes = group_by_scene(&log, &EventLogQuery::default());
+        let titles: Vec<_> = scenes.iter().map(|s| s.scene.as_deref()).collect();
+        assert_eq!(
+            titles,
+            [
+                None,
+                Some("The Ambush at Thornwood"),
+                Some("The Burning Bridge")
+            ]
+        );
+        let ambush: Vec<_> = scenes[1].parts.iter().map(|p| p.combat).collect();
+        assert_eq!(ambush, [None, Some(1)]);
+        let bridge = &scenes[2].parts;
+        assert_eq!((bridge[0].combat, bridge[0].events.len()), (Some(1), 3));
+        assert_eq!((bridge[1].combat, bridge[1].events.len()), (None, 1));
+
+        // Only Lyra's events, under the markers that place them
+        let lyra = EventLogQuery {
+            character: Some("Lyra".to_string()),
+            ..Default::default()
+        };
+        let scenes = group_by_scene(&log, &lyra);
+        assert_eq!(scenes.len(), 2);
+        assert_eq!(scenes[0].parts[0].events.len(), 1);
+        assert_eq!(scenes[1].parts[0].events[1].message, "Lyra took 2 damage");
+    }
+
+    #[test]
+    fn test_only_marked_events_open_scenes_and_fights() {
+        let log = vec![
+            scene("The Ambush at Thornwood"),
+            event("Chat", Some("Scene"), "Scene: I'm not a scene"),
+            event("SystemMessage", None, "Combat started"),
+            event("RollExecuted", Some("Theron"), "Theron rolled"),
+        ];
+        let scenes = group_by_scene(&log, &EventLogQuery::default());
+        assert_eq!(scenes.len(), 1);
+        assert_eq!(scenes[0].parts.len(), 1);
+        assert_eq!(scenes[0].parts[0].combat, None);
+        assert_eq!(scenes[0].parts[0].events.len(), 4);
+    }
+}
@@ -0,0 +1,126 @@
+//! Fog of war: the parts of a scene the GM has revealed to the players
+
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/// Most corners a revealed area may have
+pub const MAX_FOG_AREA_POINTS: usize = 64;
+
+/// Most revealed areas in one scene
+pub const MAX_FOG_AREAS: usize = 200;
+
+/// A revealed polygon (a grid cell is just a small one)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FogArea {
+    pub id: String,
+    /// Corners in order, in map units
+    pub points: Vec<Position>,
+}
+
+impl FogArea {
+    /// Whether a point is inside the polygon (even-odd rule)
+    pub fn contains(&self, position: &Position) -> bool {
+        let mut inside = false;
+        let mut previous = match self.points.last() {
+            Some(point) => point,
+            None => return false,
+        };
+        for point in &self.points {
+            if (point.y > position.y) != (previous.y > position.y) {
+                let crossing_x = point.x
+                    + (position.y - point.y) * (previous.x - point.x) / (previous.y - point.y);
+                if position.x < crossing_x {
+                    inside = !inside;
+                }
+            }
+            previous = point;
+        }
+        inside
+    }
+}
+
+/// One scene's fog; with fog off the whole scene is visible
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct FogState {
+    pub enabled: bool,
+    pub revealed: Vec<FogArea>,
+}
+
+impl FogState {
+    /// Whether players can see a point
+    pub fn is_revealed(&self, position: &Position) -> bool {
+        !self.enabled || self.revealed.iter().any(|area| area.contains(position))
+    }
+}
+
+/// Check the corners of an area the GM wants to reveal
+pub fn validate_area(points: &[Position]) -> Result<(), String> {
+    if points.len() < 3 || points.len() > MAX_FOG_AREA_POINTS {
+        return Err(format!(
+            "An area needs between 3 and {} corners",
+            MAX_FOG_AREA_POINTS
+        ));
+    }
+    if points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+        return Err("Area corners must be numbers".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> FogArea {
+        FogArea {
+            id: "a".to_string(),
+            points: vec![
+                Position::new(0.0, 0.0),
+                Position::new(100.0, 0.0),
+                Position::new(100.0, 100.0),
+                Position::new(0.0, 100.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_area_contains() {
+        let area = square();
+        assert!(area.contains(&Position::new(50.0, 50.0)));
+        assert!(!area.contains(&Position::new(150.0, 50.0)));
+        assert!(!area.contains(&Position::new(50.0, -1.0)));
+
+        let triangle = FogArea {
+            id: "b".to_string(),
+            points: vec![
+                Position::new(0.0, 0.0),
+                Position::new(100.0, 0.0),
+                Position::new(0.0, 100.0),
+            ],
+        };
+        assert!(triangle.contains(&Position::new(20.0, 20.0)));
+        assert!(!triangle.contains(&Position::new(80.0, 80.0)));
+    }
+
+    #[test]
+    fn test_fog_reveals() {
+        let mut fog = FogState::default();
+        let far_away = Position::new(500.0, 500.0);
+        assert!(fog.is_revealed(&far_away), "no fog, nothing hidden");
+
+        fog.enabled = true;
+        assert!(!fog.is_revealed(&far_away));
+        fog.revealed.push(square());
+        assert!(fog.is_revealed(&Position::new(10.0, 10.0)));
+        assert!(!fog.is_revealed(&far_away));
+
+        assert!(validate_area(&square().points).is_ok());
+        assert!(validate_area(&square().points[..2]).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Adversary groups: several adversaries run as one, like a goblin warband
+//!
+//! The GM moves a group in formation, activates it once for every member to
+//! act, deals damage to all of it at once and removes it in one go. An
+//! adversary belongs to one group at most. A group stays active while any
+//! member is still standing, and goes down with its last one.
+
+use serde::{Deserialize, Serialize};
+
+/// One adversary of a group and how it's holding up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GroupMember {
+    pub id: String,
+    pub name: String,
+    pub hp: u8,
+    pub max_hp: u8,
+    pub is_active: bool,
+}
+
+/// A group and its members' HP added up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AdversaryGroupInfo {
+    pub id: String,
+    pub name: String,
+    pub members: Vec<GroupMember>,
+    pub hp: u16,
+    pub max_hp: u16,
+    /// Members still standing
+    pub active_members: usize,
+    pub is_active: bool,
+}
+
+impl AdversaryGroupInfo {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, members: Vec<GroupMember>) -> Self {
+        let mut info = Self {
+            id: id.into(),
+            name: name.into(),
+            members,
+            hp: 0,
+            max_hp: 0,
+            active_members: 0,
+            is_active: false,
+        };
+        info.total();
+        info
+    }
+
+    /// Keep only the members `keep` allows, totalling the rest again
+    pub fn retain_members(&mut self, keep: impl FnMut(&GroupMember) -> bool) {
+        self.members.retain(keep);
+        self.total();
+    }
+
+    /// Short standing, e.g. "Goblin Warband: 3/4 standing, 9/16 HP"
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {}/{} standing, {}/{} HP",
+            self.name,
+            self.active_members,
+            self.members.len(),
+            self.hp,
+            self.max_hp
+        )
+    }
+
+    fn total(&mut self) {
+        self.hp = self.members.iter().map(|m| m.hp as u16).sum();
+        self.max_hp = self.members.iter().map(|m| m.max_hp as u16).sum();
+        self.active_members = self.members.iter().filter(|m| m.is_active).count();
+        self.is_active = self.active_members > 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goblin(n: u8, hp: u8) -> GroupMember {
+        GroupMember {
+            id: n.to_string(),
+            name: format!("Goblin #{}", n),
+            hp,
+            max_hp: 4,
+            is_active: hp > 0,
+        }
+    }
+
+    #[test]
+    fn test_group_totals() {
+        let mut group = AdversaryGroupInfo::new(
+            "g",
+            "Goblin Warband",
+            vec![goblin(1, 4), goblin(2, 0), goblin(3, 2)],
+        );
+        assert_eq!(group.summary(), "Goblin Warband: 2/3 standing, 6/12 HP");
+
+        group.retain_members(|m| m.id == "2");
+        assert_eq!((group.hp, group.max_hp, group.is_active), (0, 4, false));
+    }
+}
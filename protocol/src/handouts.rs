@@ -0,0 +1,52 @@
+//! Handouts the GM shares with the table: notes, letters, maps
+
+use serde::{Deserialize, Serialize};
+
+/// Longest handout title accepted, in characters
+pub const MAX_HANDOUT_TITLE_LENGTH: usize = 100;
+
+/// Longest text or markdown handout accepted, in characters
+pub const MAX_HANDOUT_TEXT_LENGTH: usize = 20_000;
+
+/// Largest image handout accepted, in bytes
+pub const MAX_HANDOUT_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// What a handout holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum HandoutKind {
+    Text,
+    Markdown,
+    /// PNG, JPEG, GIF or WebP
+    Image,
+}
+
+/// A shared handout; its content is fetched from `url`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Handout {
+    pub id: String,
+    pub title: String,
+    pub kind: HandoutKind,
+    /// Characters it was shared with; empty when shared with everyone
+    #[serde(default)]
+    pub character_ids: Vec<String>,
+    /// RFC 3339
+    pub shared_at: String,
+    pub url: String,
+}
+
+impl Handout {
+    /// Whether everyone was given it
+    pub fn is_public(&self) -> bool {
+        self.character_ids.is_empty()
+    }
+
+    /// Whether the character was given it
+    pub fn is_for(&self, character_id: &str) -> bool {
+        self.is_public() || self.character_ids.iter().any(|id| id == character_id)
+    }
+}
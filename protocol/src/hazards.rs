@@ -0,0 +1,185 @@
+//! Ambient scene hazards: collapsing ceilings, rising water and the like
+//!
+//! A hazard covers a circular area of the map and triggers on a cadence,
+//! either every combat round or when its countdown runs out. When it
+//! triggers, every character and active adversary inside the area is asked
+//! for a roll or takes damage, as the GM configured.
+
+use serde::{Deserialize, Serialize};
+
+use crate::damage::DamageType;
+use crate::dice::DiceExpression;
+use crate::Position;
+
+/// The part of the map a hazard affects
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HazardArea {
+    pub center: Position,
+    /// Reach from the center, in map units
+    pub radius: f32,
+}
+
+impl HazardArea {
+    pub fn contains(&self, position: &Position) -> bool {
+        self.center.distance_to(position) <= self.radius
+    }
+
+    /// Whether a straight move from `from` to `to` passes through the area
+    pub fn crosses(&self, from: &Position, to: &Position) -> bool {
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let length_sq = dx * dx + dy * dy;
+        if length_sq == 0.0 {
+            return self.contains(from);
+        }
+
+        // Closest point on the segment to the center
+        let t = (((self.center.x - from.x) * dx + (self.center.y - from.y) * dy) / length_sq)
+            .clamp(0.0, 1.0);
+        self.contains(&Position::new(from.x + t * dx, from.y + t * dy))
+    }
+}
+
+/// When a hazard triggers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HazardCadence {
+    /// Every time the combat round advances
+    EachRound,
+    /// After this many ticks, then the countdown starts over
+    Countdown { ticks: u8 },
+}
+
+/// What happens to tokens in the area when a hazard triggers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HazardOutcome {
+    /// Characters in the area must make a reaction roll
+    Roll { attribute: String, difficulty: u16 },
+    /// Everything in the area takes damage
+    Damage {
+        dice: String,
+        #[serde(default)]
+        damage_type: DamageType,
+    },
+}
+
+/// A hazard definition, placed on the scene by the GM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Hazard {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub area: HazardArea,
+    pub cadence: HazardCadence,
+    pub outcome: HazardOutcome,
+}
+
+impl Hazard {
+    /// Check the hazard can be placed
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Hazard name cannot be empty".to_string());
+        }
+        if !self.area.radius.is_finite() || self.area.radius <= 0.0 {
+            return Err("Hazard radius must be positive".to_string());
+        }
+        if self.cadence == (HazardCadence::Countdown { ticks: 0 }) {
+            return Err("Hazard countdown needs at least one tick".to_string());
+        }
+        match &self.outcome {
+            HazardOutcome::Roll { attribute, .. } if attribute.trim().is_empty() => {
+                Err("Hazard roll needs an attribute".to_string())
+            }
+            HazardOutcome::Damage { dice, .. } if dice.trim().is_empty() => {
+                Err("Hazard damage needs dice".to_string())
+            }
+            HazardOutcome::Damage { dice, .. } => DiceExpression::parse(dice).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ticks until the first trigger (1 for hazards that trigger every round)
+    pub fn countdown_length(&self) -> u8 {
+        match self.cadence {
+            HazardCadence::EachRound => 1,
+            HazardCadence::Countdown { ticks } => ticks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rising_water() -> Hazard {
+        Hazard {
+            name: "Rising Water".to_string(),
+            description: String::new(),
+            area: HazardArea {
+                center: Position::new(100.0, 100.0),
+                radius: 50.0,
+            },
+            cadence: HazardCadence::Countdown { ticks: 3 },
+            outcome: HazardOutcome::Roll {
+                attribute: "strength".to_string(),
+                difficulty: 12,
+            },
+        }
+    }
+
+    #[test]
+    fn test_hazard_validate() {
+        assert!(rising_water().validate().is_ok());
+
+        let mut hazard = rising_water();
+        hazard.cadence = HazardCadence::Countdown { ticks: 0 };
+        assert!(hazard.validate().is_err());
+
+        let mut hazard = rising_water();
+        hazard.outcome = HazardOutcome::Damage {
+            dice: " ".to_string(),
+            damage_type: DamageType::physical(),
+        };
+        assert!(hazard.validate().is_err());
+    }
+
+    #[test]
+    fn test_area_contains() {
+        let area = rising_water().area;
+        assert!(area.contains(&Position::new(130.0, 140.0)));
+        assert!(!area.contains(&Position::new(160.0, 100.0)));
+
+        // Straight through the middle, and clean past the edge
+        assert!(area.crosses(&Position::new(0.0, 100.0), &Position::new(200.0, 100.0)));
+        assert!(!area.crosses(&Position::new(0.0, 200.0), &Position::new(200.0, 200.0)));
+    }
+
+    #[test]
+    fn test_hazard_deserialize() {
+        let json = r#"{
+            "name": "Collapsing Ceiling",
+            "area": {"center": {"x": 0.0, "y": 0.0}, "radius": 80.0},
+            "cadence": {"type": "each_round"},
+            "outcome": {"type": "damage", "dice": "1d10"}
+        }"#;
+        let hazard: Hazard = serde_json::from_str(json).unwrap();
+
+        assert_eq!(hazard.cadence, HazardCadence::EachRound);
+        assert_eq!(hazard.countdown_length(), 1);
+        assert_eq!(
+            hazard.outcome,
+            HazardOutcome::Damage {
+                dice: "1d10".to_string(),
+                damage_type: DamageType::physical(),
+            }
+        );
+    }
+}
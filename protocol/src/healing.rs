@@ -0,0 +1,115 @@
+//! Clearing HP and Stress: downtime moves, Hope features and allies' help
+//!
+//! On a short rest a downtime move clears 1d4 + the character's tier; on a
+//! long rest it clears everything. Prepare gains a Hope instead. Resting
+//! isn't possible mid-combat. A Hope feature spends the character's own Hope
+//! to clear HP, and an ally's spell or ability clears what the table says it
+//! does.
+
+use serde::{Deserialize, Serialize};
+
+use crate::dice::DieResult;
+
+/// Die rolled by a downtime move on a short rest, before the tier is added
+pub const SHORT_REST_DIE: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RestKind {
+    Short,
+    Long,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DowntimeMove {
+    TendToWounds,
+    ClearStress,
+    Prepare,
+}
+
+impl DowntimeMove {
+    pub fn name(self, rest: RestKind) -> &'static str {
+        match (self, rest) {
+            (DowntimeMove::TendToWounds, RestKind::Short) => "Tend to Wounds",
+            (DowntimeMove::TendToWounds, RestKind::Long) => "Tend to All Wounds",
+            (DowntimeMove::ClearStress, RestKind::Short) => "Clear Stress",
+            (DowntimeMove::ClearStress, RestKind::Long) => "Clear All Stress",
+            (DowntimeMove::Prepare, _) => "Prepare",
+        }
+    }
+}
+
+/// Where healing comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HealingSource {
+    /// A downtime move on a rest
+    Downtime {
+        rest: RestKind,
+        downtime_move: DowntimeMove,
+    },
+    /// A feature of the character's that spends Hope to clear HP
+    Hope { hope: u8, hp: u8 },
+    /// Another character's spell or ability
+    Ally {
+        ally_id: String,
+        ability: String,
+        #[serde(default)]
+        hp: u8,
+        #[serde(default)]
+        stress: u8,
+    },
+}
+
+/// What healing did to a character
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Healing {
+    pub character_id: String,
+    pub character_name: String,
+    /// e.g. "Tend to Wounds (short rest)" or "Mira's Healing Hands"
+    pub source: String,
+    pub hp_cleared: u8,
+    pub stress_cleared: u8,
+    /// Gained by Prepare, spent by a Hope feature
+    pub hope_change: i8,
+    /// The short rest die, when one was rolled
+    #[serde(default)]
+    pub roll: Option<DieResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_json() {
+        let source: HealingSource = serde_json::from_str(
+            r#"{"kind": "downtime", "rest": "short", "downtime_move": "tend_to_wounds"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            source,
+            HealingSource::Downtime {
+                rest: RestKind::Short,
+                downtime_move: DowntimeMove::TendToWounds,
+            }
+        );
+        let source: HealingSource =
+            serde_json::from_str(r#"{"kind": "ally", "ally_id": "m", "ability": "Mend", "hp": 2}"#)
+                .unwrap();
+        assert!(matches!(source, HealingSource::Ally { stress: 0, .. }));
+        assert_eq!(
+            DowntimeMove::TendToWounds.name(RestKind::Long),
+            "Tend to All Wounds"
+        );
+    }
+}
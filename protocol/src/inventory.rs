@@ -0,0 +1,210 @@
+//! Inventory and gold
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Handfuls that make up a bag, and bags that make up a chest
+pub const HANDFULS_PER_BAG: u32 = 10;
+pub const BAGS_PER_CHEST: u32 = 10;
+
+/// An item carried by a character
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub quantity: u32,
+}
+
+impl Item {
+    pub fn new(name: String, description: String, quantity: u32) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            quantity,
+        }
+    }
+}
+
+/// Gold in Daggerheart denominations (handfuls, bags, chests)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Gold {
+    pub handfuls: u32,
+    pub bags: u32,
+    pub chests: u32,
+}
+
+impl Gold {
+    /// Create from a total number of handfuls
+    pub fn from_handfuls(total: u32) -> Self {
+        let handfuls_per_chest = HANDFULS_PER_BAG * BAGS_PER_CHEST;
+        Self {
+            chests: total / handfuls_per_chest,
+            bags: (total % handfuls_per_chest) / HANDFULS_PER_BAG,
+            handfuls: total % HANDFULS_PER_BAG,
+        }
+    }
+
+    /// Total value expressed in handfuls
+    pub fn total_handfuls(&self) -> u32 {
+        self.chests * HANDFULS_PER_BAG * BAGS_PER_CHEST
+            + self.bags * HANDFULS_PER_BAG
+            + self.handfuls
+    }
+
+    /// Add handfuls, rolling over into bags and chests
+    pub fn add_handfuls(&mut self, amount: u32) {
+        *self = Self::from_handfuls(self.total_handfuls().saturating_add(amount));
+    }
+
+    /// Spend handfuls, breaking bags and chests as needed
+    pub fn spend_handfuls(&mut self, amount: u32) -> Result<(), String> {
+        let total = self.total_handfuls();
+        if amount > total {
+            return Err("Not enough gold".to_string());
+        }
+        *self = Self::from_handfuls(total - amount);
+        Ok(())
+    }
+}
+
+/// A character's carried items and gold
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Inventory {
+    pub items: Vec<Item>,
+    pub gold: Gold,
+}
+
+impl Inventory {
+    /// Add an item, stacking onto an existing item with the same name
+    pub fn add_item(&mut self, name: String, description: String, quantity: u32) -> Item {
+        if let Some(existing) = self.items.iter_mut().find(|i| i.name == name) {
+            existing.quantity = existing.quantity.saturating_add(quantity);
+            return existing.clone();
+        }
+
+        let item = Item::new(name, description, quantity);
+        self.items.push(item.clone());
+        item
+    }
+
+    /// Remove some quantity of an item, returning what was removed
+    pub fn remove_item(&mut self, item_id: &str, quantity: u32) -> Result<Item, String> {
+        let index = self
+            .items
+            .iter()
+            .position(|i| i.id == item_id)
+            .ok_or_else(|| format!("Item not found: {}", item_id))?;
+
+        if quantity == 0 || quantity > self.items[index].quantity {
+            return Err(format!(
+                "Invalid quantity: {} (have {})",
+                quantity, self.items[index].quantity
+            ));
+        }
+
+        let mut removed = self.items[index].clone();
+        removed.quantity = quantity;
+
+        self.items[index].quantity -= quantity;
+        if self.items[index].quantity == 0 {
+            self.items.remove(index);
+        }
+
+        Ok(removed)
+    }
+
+    /// Get an item by ID
+    pub fn get_item(&self, item_id: &str) -> Option<&Item> {
+        self.items.iter().find(|i| i.id == item_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gold_rolls_over() {
+        let mut gold = Gold::default();
+        gold.add_handfuls(9);
+        assert_eq!(
+            gold,
+            Gold {
+                handfuls: 9,
+                bags: 0,
+                chests: 0
+            }
+        );
+
+        gold.add_handfuls(2);
+        assert_eq!(
+            gold,
+            Gold {
+                handfuls: 1,
+                bags: 1,
+                chests: 0
+            }
+        );
+
+        gold.add_handfuls(100);
+        assert_eq!(
+            gold,
+            Gold {
+                handfuls: 1,
+                bags: 1,
+                chests: 1
+            }
+        );
+        assert_eq!(gold.total_handfuls(), 111);
+    }
+
+    #[test]
+    fn test_gold_spend() {
+        let mut gold = Gold::from_handfuls(100);
+        assert!(gold.spend_handfuls(3).is_ok());
+        assert_eq!(
+            gold,
+            Gold {
+                handfuls: 7,
+                bags: 9,
+                chests: 0
+            }
+        );
+
+        assert!(gold.spend_handfuls(1000).is_err());
+        assert_eq!(gold.total_handfuls(), 97);
+    }
+
+    #[test]
+    fn test_add_item_stacks_by_name() {
+        let mut inventory = Inventory::default();
+        let torch = inventory.add_item("Torch".to_string(), "Lights the way".to_string(), 2);
+        let again = inventory.add_item("Torch".to_string(), String::new(), 3);
+
+        assert_eq!(torch.id, again.id);
+        assert_eq!(inventory.items.len(), 1);
+        assert_eq!(inventory.items[0].quantity, 5);
+    }
+
+    #[test]
+    fn test_remove_item() {
+        let mut inventory = Inventory::default();
+        let rope = inventory.add_item("Rope".to_string(), "50 feet".to_string(), 2);
+
+        let removed = inventory.remove_item(&rope.id, 1).unwrap();
+        assert_eq!(removed.quantity, 1);
+        assert_eq!(inventory.get_item(&rope.id).unwrap().quantity, 1);
+
+        assert!(inventory.remove_item(&rope.id, 2).is_err());
+        inventory.remove_item(&rope.id, 1).unwrap();
+        assert!(inventory.items.is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+//! Session journal: a JSON-lines recording of a play session
+//!
+//! The server writes one entry per line when journaling is enabled; the
+//! replay bot reads them back and re-sends the client messages. Messages are
+//! kept as the raw JSON text that crossed the wire so old journals replay
+//! even after message types gain fields.
+//!
+//! During combat the server also records position keyframes, a lightweight
+//! record of how the battle moved for a post-fight scrubber.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/// One line of a session journal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct JournalEntry {
+    /// Milliseconds since the server started recording
+    pub at_ms: u64,
+    pub record: JournalRecord,
+}
+
+/// What happened at a point in the session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind")]
+pub enum JournalRecord {
+    /// A WebSocket client connected
+    #[serde(rename = "connected")]
+    Connected { connection: String },
+
+    /// A WebSocket client disconnected
+    #[serde(rename = "disconnected")]
+    Disconnected { connection: String },
+
+    /// A client → server message, as received
+    #[serde(rename = "client")]
+    Client { connection: String, message: String },
+
+    /// A server → all clients broadcast, as sent
+    #[serde(rename = "server")]
+    Server { message: String },
+
+    /// Token positions during a combat encounter
+    #[serde(rename = "keyframe")]
+    Keyframe {
+        encounter_id: String,
+        keyframe: PositionKeyframe,
+    },
+}
+
+/// Where one character or adversary stood in a keyframe
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TokenPosition {
+    pub id: String,
+    pub name: String,
+    pub is_adversary: bool,
+    pub position: Position,
+}
+
+/// Every token's position at a moment in a combat encounter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PositionKeyframe {
+    /// Milliseconds since the encounter's first keyframe
+    pub at_ms: u64,
+    pub round: u32,
+    /// Sorted by id
+    pub tokens: Vec<TokenPosition>,
+}
+
+impl JournalEntry {
+    pub fn new(at_ms: u64, record: JournalRecord) -> Self {
+        Self { at_ms, record }
+    }
+
+    /// Serialize as a single journal line (no trailing newline)
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Parse one journal line
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|e| format!("Invalid journal line: {}", e))
+    }
+}
+
+/// Read a whole journal, skipping blank lines
+///
+/// Errors name the offending line number.
+pub fn parse_journal(contents: &str) -> Result<Vec<JournalEntry>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            JournalEntry::from_line(line).map_err(|e| format!("line {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// The id of a character or adversary created by a server broadcast, if any
+///
+/// Ids differ between the recorded and replayed sessions, so the replay bot
+/// pairs these up in order to rewrite ids in later client messages.
+pub fn spawned_entity_id(server_message: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(server_message).ok()?;
+    let field = match value.get("type")?.as_str()? {
+        "character_spawned" => "character_id",
+        "adversary_spawned" => "adversary_id",
+        _ => return None,
+    };
+    value
+        .get("payload")?
+        .get(field)?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_round_trip() {
+        let entries = vec![
+            JournalEntry::new(
+                0,
+                JournalRecord::Connected {
+                    connection: "c1".to_string(),
+                },
+            ),
+            JournalEntry::new(
+                250,
+                JournalRecord::Client {
+                    connection: "c1".to_string(),
+                    message: r#"{"type":"move_character","payload":{"x":1.0,"y":2.0}}"#.to_string(),
+                },
+            ),
+        ];
+
+        let text: Vec<String> = entries.iter().map(JournalEntry::to_line).collect();
+        let parsed = parse_journal(&format!("{}\n\n", text.join("\n"))).unwrap();
+        assert_eq!(parsed, entries);
+    }
+
+    #[test]
+    fn test_parse_journal_reports_line() {
+        let err = parse_journal(
+            "{\"at_ms\":0,\"record\":{\"kind\":\"server\",\"message\":\"{}\"}}\nnot json",
+        )
+        .unwrap_err();
+        assert!(err.starts_with("line 2:"));
+    }
+
+    #[test]
+    fn test_keyframe_record() {
+        let line = r#"{"at_ms":5000,"record":{"kind":"keyframe","encounter_id":"e-1","keyframe":{"at_ms":2000,"round":1,"tokens":[{"id":"a-1","name":"Goblin","is_adversary":true,"position":{"x":10.0,"y":20.0}}]}}}"#;
+        let entry = JournalEntry::from_line(line).unwrap();
+
+        let JournalRecord::Keyframe {
+            encounter_id,
+            keyframe,
+        } = entry.record
+        else {
+            panic!("Wrong record kind");
+        };
+        assert_eq!(encounter_id, "e-1");
+        assert_eq!(keyframe.tokens[0].position, Position::new(10.0, 20.0));
+    }
+
+    #[test]
+    fn test_spawned_entity_id() {
+        let spawned =
+            r#"{"type":"adversary_spawned","payload":{"adversary_id":"a-1","name":"Goblin"}}"#;
+        assert_eq!(spawned_entity_id(spawned), Some("a-1".to_string()));
+
+        let moved = r#"{"type":"character_moved","payload":{"character_id":"c-1"}}"#;
+        assert_eq!(spawned_entity_id(moved), None);
+    }
+}
@@ -0,0 +1,416 @@
+//! Level-up advancements by tier
+//!
+//! Each level up grants two advancement slots spent on the options of the new
+//! level's tier. Levels 2, 5 and 8 also grant a tier achievement: a new
+//! Experience, and at 5 and 8 boosted traits become unmarked again.
+
+use serde::{Deserialize, Serialize};
+
+use crate::equipment::TRAITS;
+use crate::Experience;
+
+/// Highest character level
+pub const MAX_LEVEL: u8 = 10;
+
+/// Advancement slots spent on each level up
+pub const ADVANCEMENT_SLOTS_PER_LEVEL: u8 = 2;
+
+/// Stress slots every character starts with
+pub const BASE_STRESS_SLOTS: u8 = 6;
+
+/// Tier for a level (1: level 1, 2: levels 2-4, 3: levels 5-7, 4: levels 8-10)
+pub fn tier_for_level(level: u8) -> u8 {
+    match level {
+        0..=1 => 1,
+        2..=4 => 2,
+        5..=7 => 3,
+        _ => 4,
+    }
+}
+
+/// Whether reaching this level grants a tier achievement
+pub fn is_tier_achievement(level: u8) -> bool {
+    matches!(level, 2 | 5 | 8)
+}
+
+/// Whether reaching this level unmarks boosted traits
+pub fn clears_marked_traits(level: u8) -> bool {
+    matches!(level, 5 | 8)
+}
+
+/// Kinds of advancement offered on level up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AdvancementKind {
+    TraitBoost,
+    HitPointSlot,
+    StressSlot,
+    ExperienceBoost,
+    DomainCard,
+    Evasion,
+    Proficiency,
+}
+
+impl AdvancementKind {
+    pub const ALL: [AdvancementKind; 7] = [
+        AdvancementKind::TraitBoost,
+        AdvancementKind::HitPointSlot,
+        AdvancementKind::StressSlot,
+        AdvancementKind::ExperienceBoost,
+        AdvancementKind::DomainCard,
+        AdvancementKind::Evasion,
+        AdvancementKind::Proficiency,
+    ];
+
+    /// Advancement slots this option uses
+    pub fn cost(self) -> u8 {
+        match self {
+            AdvancementKind::Proficiency => 2,
+            _ => 1,
+        }
+    }
+
+    /// Times this option can be taken within one tier
+    pub fn max_per_tier(self) -> u8 {
+        match self {
+            AdvancementKind::TraitBoost => 3,
+            AdvancementKind::HitPointSlot | AdvancementKind::StressSlot => 2,
+            _ => 1,
+        }
+    }
+
+    /// Lowest tier offering this option
+    pub fn min_tier(self) -> u8 {
+        match self {
+            AdvancementKind::Proficiency => 3,
+            _ => 2,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            AdvancementKind::TraitBoost => "Gain +1 to two unmarked traits and mark them",
+            AdvancementKind::HitPointSlot => "Permanently gain one Hit Point slot",
+            AdvancementKind::StressSlot => "Permanently gain one Stress slot",
+            AdvancementKind::ExperienceBoost => "Permanently gain +1 to two Experiences",
+            AdvancementKind::DomainCard => "Take an additional domain card of your level or lower",
+            AdvancementKind::Evasion => "Permanently gain +1 to Evasion",
+            AdvancementKind::Proficiency => "Increase your Proficiency by +1 (uses both slots)",
+        }
+    }
+}
+
+/// A chosen advancement with the details it needs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Advancement {
+    TraitBoost { traits: Vec<String> },
+    HitPointSlot,
+    StressSlot,
+    ExperienceBoost { experiences: Vec<String> },
+    DomainCard { card: String },
+    Evasion,
+    Proficiency,
+}
+
+impl Advancement {
+    pub fn kind(&self) -> AdvancementKind {
+        match self {
+            Advancement::TraitBoost { .. } => AdvancementKind::TraitBoost,
+            Advancement::HitPointSlot => AdvancementKind::HitPointSlot,
+            Advancement::StressSlot => AdvancementKind::StressSlot,
+            Advancement::ExperienceBoost { .. } => AdvancementKind::ExperienceBoost,
+            Advancement::DomainCard { .. } => AdvancementKind::DomainCard,
+            Advancement::Evasion => AdvancementKind::Evasion,
+            Advancement::Proficiency => AdvancementKind::Proficiency,
+        }
+    }
+}
+
+/// An advancement taken when reaching a level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TakenAdvancement {
+    pub level: u8,
+    pub advancement: Advancement,
+}
+
+/// A level-up option offered to the player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AdvancementOption {
+    pub kind: AdvancementKind,
+    pub description: String,
+    pub cost: u8,
+    /// Times it can still be taken in this tier
+    pub remaining: u8,
+}
+
+/// Character growth from leveling up
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Progression {
+    /// GM has marked a level up the player has not chosen advancements for yet
+    pub pending_level_up: bool,
+    pub advancements: Vec<TakenAdvancement>,
+    /// Traits boosted since the last tier achievement that cleared them
+    pub marked_traits: Vec<String>,
+}
+
+impl Progression {
+    /// Total bonus to a trait from trait boosts
+    pub fn trait_bonus(&self, trait_name: &str) -> i8 {
+        self.advancements
+            .iter()
+            .filter_map(|taken| match &taken.advancement {
+                Advancement::TraitBoost { traits } => Some(traits),
+                _ => None,
+            })
+            .flatten()
+            .filter(|t| t.eq_ignore_ascii_case(trait_name))
+            .count() as i8
+    }
+
+    /// Stress slots including those gained from advancements
+    pub fn stress_slots(&self) -> u8 {
+        BASE_STRESS_SLOTS + self.count(AdvancementKind::StressSlot) as u8
+    }
+
+    /// Proficiency gained from advancements
+    pub fn proficiency_bonus(&self) -> i8 {
+        self.count(AdvancementKind::Proficiency) as i8
+    }
+
+    /// Domain cards taken as advancements
+    pub fn domain_cards(&self) -> Vec<String> {
+        self.advancements
+            .iter()
+            .filter_map(|taken| match &taken.advancement {
+                Advancement::DomainCard { card } => Some(card.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count(&self, kind: AdvancementKind) -> usize {
+        self.advancements
+            .iter()
+            .filter(|taken| taken.advancement.kind() == kind)
+            .count()
+    }
+
+    /// Times an advancement kind has been taken within a tier
+    pub fn taken_in_tier(&self, kind: AdvancementKind, tier: u8) -> u8 {
+        self.advancements
+            .iter()
+            .filter(|taken| taken.advancement.kind() == kind && tier_for_level(taken.level) == tier)
+            .count() as u8
+    }
+
+    /// Options available when leveling up into `new_level`
+    pub fn options_for(&self, new_level: u8) -> Vec<AdvancementOption> {
+        let tier = tier_for_level(new_level);
+        AdvancementKind::ALL
+            .iter()
+            .filter(|kind| tier >= kind.min_tier())
+            .map(|&kind| AdvancementOption {
+                kind,
+                description: kind.description().to_string(),
+                cost: kind.cost(),
+                remaining: kind
+                    .max_per_tier()
+                    .saturating_sub(self.taken_in_tier(kind, tier)),
+            })
+            .filter(|option| option.remaining > 0)
+            .collect()
+    }
+
+    /// Check a set of level-up choices is legal
+    ///
+    /// `experiences` should include any Experience gained from the tier
+    /// achievement at this level.
+    pub fn validate_choices(
+        &self,
+        new_level: u8,
+        choices: &[Advancement],
+        experiences: &[Experience],
+    ) -> Result<(), String> {
+        let slots: u8 = choices.iter().map(|c| c.kind().cost()).sum();
+        if slots != ADVANCEMENT_SLOTS_PER_LEVEL {
+            return Err(format!(
+                "Choose advancements using exactly {} slots (got {})",
+                ADVANCEMENT_SLOTS_PER_LEVEL, slots
+            ));
+        }
+
+        let options = self.options_for(new_level);
+        for kind in AdvancementKind::ALL {
+            let chosen = choices.iter().filter(|c| c.kind() == kind).count() as u8;
+            if chosen == 0 {
+                continue;
+            }
+            let remaining = options
+                .iter()
+                .find(|o| o.kind == kind)
+                .map(|o| o.remaining)
+                .unwrap_or(0);
+            if chosen > remaining {
+                return Err(format!(
+                    "\"{}\" is not available at level {}",
+                    kind.description(),
+                    new_level
+                ));
+            }
+        }
+
+        let marked: &[String] = if clears_marked_traits(new_level) {
+            &[]
+        } else {
+            &self.marked_traits
+        };
+
+        let mut boosted: Vec<String> = Vec::new();
+        for choice in choices {
+            match choice {
+                Advancement::TraitBoost { traits } => {
+                    if traits.len() != 2 {
+                        return Err("A trait boost needs exactly two traits".to_string());
+                    }
+                    for t in traits {
+                        let t = t.to_lowercase();
+                        if !TRAITS.contains(&t.as_str()) {
+                            return Err(format!("Invalid trait: {}", t));
+                        }
+                        if marked.iter().any(|m| m.eq_ignore_ascii_case(&t)) || boosted.contains(&t)
+                        {
+                            return Err(format!("Trait already marked: {}", t));
+                        }
+                        boosted.push(t);
+                    }
+                }
+                Advancement::ExperienceBoost { experiences: names } => {
+                    if names.len() != 2 || names[0].eq_ignore_ascii_case(&names[1]) {
+                        return Err(
+                            "An Experience boost needs two different Experiences".to_string()
+                        );
+                    }
+                    for name in names {
+                        if !experiences
+                            .iter()
+                            .any(|e| e.name.eq_ignore_ascii_case(name))
+                        {
+                            return Err(format!("Unknown Experience: {}", name));
+                        }
+                    }
+                }
+                Advancement::DomainCard { card } if card.trim().is_empty() => {
+                    return Err("Domain card name cannot be empty".to_string());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers() {
+        assert_eq!(tier_for_level(1), 1);
+        assert_eq!(tier_for_level(4), 2);
+        assert_eq!(tier_for_level(5), 3);
+        assert_eq!(tier_for_level(10), 4);
+        assert!(is_tier_achievement(5));
+        assert!(!is_tier_achievement(6));
+    }
+
+    #[test]
+    fn test_options_by_tier() {
+        let progression = Progression::default();
+        let tier2 = progression.options_for(2);
+        assert!(!tier2.iter().any(|o| o.kind == AdvancementKind::Proficiency));
+        assert!(progression
+            .options_for(5)
+            .iter()
+            .any(|o| o.kind == AdvancementKind::Proficiency));
+    }
+
+    #[test]
+    fn test_per_tier_limit() {
+        let progression = Progression {
+            advancements: vec![TakenAdvancement {
+                level: 2,
+                advancement: Advancement::Evasion,
+            }],
+            ..Progression::default()
+        };
+
+        let choices = [Advancement::Evasion, Advancement::HitPointSlot];
+        assert!(progression.validate_choices(3, &choices, &[]).is_err());
+        // A new tier resets the limit
+        assert!(progression.validate_choices(5, &choices, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_validate_trait_boosts() {
+        let progression = Progression {
+            marked_traits: vec!["agility".to_string()],
+            ..Progression::default()
+        };
+        let boost = |a: &str, b: &str| Advancement::TraitBoost {
+            traits: vec![a.to_string(), b.to_string()],
+        };
+
+        assert!(progression
+            .validate_choices(
+                3,
+                &[boost("agility", "strength"), Advancement::Evasion],
+                &[]
+            )
+            .is_err());
+        assert!(progression
+            .validate_choices(
+                3,
+                &[boost("finesse", "strength"), boost("strength", "presence")],
+                &[]
+            )
+            .is_err());
+        assert!(progression
+            .validate_choices(
+                3,
+                &[boost("finesse", "strength"), Advancement::Evasion],
+                &[]
+            )
+            .is_ok());
+        // Marks are cleared at level 5
+        assert!(progression
+            .validate_choices(
+                5,
+                &[boost("agility", "strength"), Advancement::Evasion],
+                &[]
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_slot_count() {
+        let progression = Progression::default();
+        assert!(progression
+            .validate_choices(2, &[Advancement::Evasion], &[])
+            .is_err());
+        assert!(progression
+            .validate_choices(5, &[Advancement::Proficiency], &[])
+            .is_ok());
+    }
+}
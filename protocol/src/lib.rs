@@ -0,0 +1,2838 @@
+//! WebSocket message protocol - Phase 5A: Refactored for Character/Connection architecture
+//!
+//! Kept free of server and engine dependencies so it also builds for
+//! wasm32 browser clients. Enable the `ts` feature to export TypeScript
+//! definitions of every message type, or `schema` for JSON Schemas (see
+//! README).
+
+pub mod auras;
+pub mod chat;
+pub mod compat;
+pub mod cutaway;
+pub mod damage;
+pub mod dice;
+pub mod drawings;
+pub mod encoding;
+pub mod encounters;
+pub mod environments;
+pub mod equipment;
+pub mod errors;
+pub mod events;
+pub mod fog;
+pub mod groups;
+pub mod handouts;
+pub mod hazards;
+pub mod healing;
+pub mod inventory;
+pub mod journal;
+pub mod leveling;
+pub mod maps;
+pub mod modifiers;
+pub mod narrative;
+pub mod notes;
+pub mod preferences;
+pub mod quests;
+pub mod rounds;
+pub mod schedule;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod settings;
+pub mod sheets;
+pub mod snapshot;
+pub mod stats;
+pub mod subclasses;
+pub mod tokens;
+
+use serde::{Deserialize, Serialize};
+
+use crate::auras::Aura;
+use crate::chat::{ChatChannel, ChatEntry};
+use crate::cutaway::CutawayInfo;
+use crate::damage::{AreaDamageTarget, DamageAdjustment, DamageType, Defenses};
+use crate::dice::{DiceRoll, DieResult};
+use crate::drawings::{Drawing, DrawingShape};
+use crate::encounters::{EncounterBudget, EncounterPlan, EncounterPreset};
+use crate::environments::{EnvironmentFeature, EnvironmentInfo};
+use crate::equipment::{Armor, Equipment, Weapon, WeaponRange};
+use crate::errors::ErrorCode;
+use crate::fog::FogState;
+use crate::groups::AdversaryGroupInfo;
+use crate::handouts::{Handout, HandoutKind};
+use crate::hazards::{Hazard, HazardArea};
+use crate::inventory::Inventory;
+use crate::leveling::{Advancement, AdvancementOption, Progression};
+use crate::maps::{MapInfo, SceneLayout};
+use crate::modifiers::{Modifier, TimedModifier};
+use crate::notes::{Note, NoteDraft};
+use crate::preferences::UserPreferences;
+use crate::quests::{Quest, QuestDraft};
+use crate::schedule::NextSession;
+use crate::settings::{TableSettings, TableSettingsPatch};
+use crate::sheets::CharacterChanges;
+use crate::snapshot::SessionSnapshot;
+use crate::subclasses::Subclass;
+use crate::tokens::TokenDetails;
+
+/// Position on the map
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Position {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Position {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Random position within map bounds
+    pub fn random(width: f32, height: f32) -> Self {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        Self {
+            x: rng.gen_range(50.0..width - 50.0),
+            y: rng.gen_range(50.0..height - 50.0),
+        }
+    }
+
+    /// Straight-line distance in map units
+    pub fn distance_to(&self, other: &Position) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+/// Default bonus granted by an Experience when Hope is spent on it
+pub const DEFAULT_EXPERIENCE_MODIFIER: i8 = 2;
+
+/// A character Experience (e.g. "Former acrobat") that can add its modifier
+/// to a roll by spending Hope
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Experience {
+    pub name: String,
+    pub modifier: i8,
+}
+
+impl Experience {
+    pub fn new(name: String, modifier: i8) -> Self {
+        Self { name, modifier }
+    }
+}
+
+/// Character data (simplified for protocol)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CharacterData {
+    pub name: String,
+    pub class: String,
+    pub subclass: Option<Subclass>,
+    pub ancestry: String,
+    pub attributes: AttributesData,
+    pub hp: ResourceData,
+    pub stress: i32,
+    pub stress_max: i32,
+    pub hope: ResourceData,
+    pub evasion: i32,
+    pub level: u8,
+    pub experiences: Vec<Experience>,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+    pub progression: Progression,
+    pub defenses: Defenses,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AttributesData {
+    pub agility: i8,
+    pub strength: i8,
+    pub finesse: i8,
+    pub instinct: i8,
+    pub presence: i8,
+    pub knowledge: i8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ResourceData {
+    pub current: i32,
+    pub maximum: i32,
+}
+
+/// A resource the GM can set on any character or adversary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Hp,
+    Stress,
+    /// Characters only
+    Hope,
+}
+
+/// Most Evasion a character sheet can be given by hand
+pub const MAX_EVASION: i32 = 30;
+
+/// Corrections to a character sheet; fields left out stay as they are
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct CharacterEdit {
+    pub name: Option<String>,
+    /// [agility, strength, finesse, instinct, presence, knowledge]
+    pub attributes: Option<[i8; 6]>,
+    pub level: Option<u8>,
+    pub evasion: Option<i32>,
+    /// `#rrggbb`
+    pub color: Option<String>,
+}
+
+/// Dice roll result (legacy - kept for compatibility)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RollResult {
+    pub hope: i32,
+    pub fear: i32,
+    pub modifier: i32,
+    #[serde(default)]
+    pub advantage_die: Option<u8>, // 1-6, added to the total
+    #[serde(default)]
+    pub disadvantage_die: Option<u8>, // 1-6, subtracted from the total
+    pub total: i32,
+    pub controlling_die: ControllingDie,
+    pub is_critical: bool,
+    pub is_success: bool,
+}
+
+/// Roll target type for GM requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RollTargetType {
+    Specific, // One or more specific characters
+    All,      // All player characters
+    Npc,      // GM-controlled character
+}
+
+/// Type of roll being requested
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RollType {
+    Action,    // General action check (use attribute)
+    Attack,    // Melee/ranged attack
+    Spellcast, // Casting a spell
+    Save,      // Reactive save (rolled as a reaction)
+    Reaction,  // Reaction roll: no Hope or Fear is generated
+}
+
+impl RollType {
+    /// Reaction rolls use the duality dice but never generate Hope or Fear
+    pub fn is_reaction(&self) -> bool {
+        matches!(self, RollType::Reaction | RollType::Save)
+    }
+}
+
+/// Success type of a roll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SuccessType {
+    Failure,
+    SuccessWithHope,
+    SuccessWithFear,
+    CriticalSuccess,
+}
+
+impl SuccessType {
+    /// English label for logs and legacy clients; reactions never say
+    /// "with Hope" or "with Fear"
+    pub fn label(&self, is_reaction: bool) -> &'static str {
+        match self {
+            SuccessType::CriticalSuccess => "CRITICAL SUCCESS",
+            SuccessType::SuccessWithHope | SuccessType::SuccessWithFear if is_reaction => "SUCCESS",
+            SuccessType::SuccessWithHope => "SUCCESS WITH HOPE",
+            SuccessType::SuccessWithFear => "SUCCESS WITH FEAR",
+            SuccessType::Failure => "FAILURE",
+        }
+    }
+}
+
+/// Which die is controlling the outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ControllingDie {
+    Hope,
+    Fear,
+    Tied, // Only when doubles
+}
+
+/// Detailed roll result for Phase 1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DetailedRollResult {
+    // The roll
+    pub hope_die: u8,              // 1-12
+    pub fear_die: u8,              // 1-12
+    pub advantage_die: Option<u8>, // 1-6 if had advantage
+    #[serde(default)]
+    pub disadvantage_die: Option<u8>, // 1-6 if had disadvantage, subtracted
+
+    // Modifiers breakdown
+    pub attribute_modifier: i8,
+    pub proficiency_modifier: i8,
+    pub situational_modifier: i8,
+    pub hope_bonus: i8, // +2 if spent Hope via Experience
+    #[serde(default)]
+    pub group_modifier: i8, // Group roll leader: +1 per helper success, -1 per failure
+    pub total_modifier: i8,
+    #[serde(default)]
+    pub help_dice: Vec<HelpDieRoll>, // d6s from allies who helped
+    #[serde(default)]
+    pub help_bonus: u8, // Highest help die, added to the total
+    #[serde(default)]
+    pub timed_modifier: i8, // Buffs and debuffs on the character's rolls
+
+    // Result
+    pub total: u16,
+    pub difficulty: u16,
+
+    // Outcome
+    pub success_type: SuccessType,
+    pub controlling_die: ControllingDie,
+    pub is_critical: bool,
+
+    // Resource changes
+    pub hope_change: i8, // +1, -1, or 0
+    pub fear_change: i8, // +1 or 0
+}
+
+/// How a prospective roll would go for one target (GM preview)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RollPreview {
+    pub character_id: String,
+    pub character_name: String,
+    pub attribute_modifier: i8,
+    pub proficiency_modifier: i8,
+    pub situational_modifier: i8,
+    pub total_modifier: i8,
+    pub hope: u8,
+    pub can_spend_hope: bool,
+    /// Anything affecting the roll, e.g. "Vulnerable (Stress full)"
+    pub conditions: Vec<String>,
+    /// 0.0-1.0, counting criticals as successes
+    pub success_chance: f32,
+}
+
+/// A d6 an ally spent Hope to add to a roll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HelpDieRoll {
+    pub helper_id: String,
+    pub helper_name: String,
+    pub value: u8,
+}
+
+/// How one helper fared in a group action roll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GroupHelperResult {
+    pub character_id: String,
+    pub character_name: String,
+    pub success: bool,
+}
+
+/// One half of a tag team roll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TagTeamRollResult {
+    pub character_id: String,
+    pub character_name: String,
+    pub total: u16,
+    pub success_type: SuccessType,
+}
+
+/// Character info for listing (includes control status)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CharacterInfo {
+    pub id: String,
+    pub name: String,
+    pub class: String,
+    pub ancestry: String,
+    pub position: Position,
+    pub color: String,
+    pub is_npc: bool,
+    pub controlled_by_me: bool, // True if this connection controls this character
+    pub controlled_by_other: bool, // True if another connection controls this character
+    pub player_name: Option<String>, // Display name of the controlling player
+    /// Connection controlling the character, so the GM can transfer or kick it
+    #[serde(default)]
+    pub controller_id: Option<String>,
+    #[serde(default)]
+    pub token: TokenDetails,
+}
+
+/// How turns flow in a combat encounter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CombatMode {
+    /// PC and adversary tokens on the Action Tracker
+    #[default]
+    ActionTracker,
+    /// Free spotlight flow: whoever holds it acts, then passes it on
+    Spotlight,
+}
+
+/// What the GM spends a Fear on in combat
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FearSpend {
+    /// One more adversary token at the back of the Action Tracker
+    AdversaryToken,
+    /// An adversary acts now: its token goes to the front of the tracker,
+    /// or it takes the spotlight
+    Interrupt { adversary_id: String },
+}
+
+/// What an adversary marks Stress for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StressMove {
+    /// Its next damage roll adds one more of its damage dice
+    ExtraDamageDie,
+    /// It acts again straight away, as if the GM spent a Fear on it
+    ActAgain,
+    /// A move or reaction from its stat block, resolved at the table
+    Feature { name: String },
+}
+
+/// Adversary role from the SRD stat blocks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AdversaryRole {
+    Bruiser,
+    Horde,
+    Leader,
+    Minion,
+    Ranged,
+    Skulk,
+    Solo,
+    #[default]
+    Standard,
+    Support,
+}
+
+impl AdversaryRole {
+    pub fn name(self) -> &'static str {
+        match self {
+            AdversaryRole::Bruiser => "Bruiser",
+            AdversaryRole::Horde => "Horde",
+            AdversaryRole::Leader => "Leader",
+            AdversaryRole::Minion => "Minion",
+            AdversaryRole::Ranged => "Ranged",
+            AdversaryRole::Skulk => "Skulk",
+            AdversaryRole::Solo => "Solo",
+            AdversaryRole::Standard => "Standard",
+            AdversaryRole::Support => "Support",
+        }
+    }
+}
+
+/// Adversary information for listing
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AdversaryInfo {
+    pub id: String,
+    pub name: String,
+    pub template: String,
+    pub tier: u8,
+    pub role: AdversaryRole,
+    pub position: Position,
+    pub hp: u8,
+    pub max_hp: u8,
+    pub stress: u8,
+    pub evasion: u8,
+    pub armor: u8,
+    pub attack_modifier: i8,
+    pub damage_dice: String,
+    pub damage_type: DamageType,
+    pub defenses: Defenses,
+    pub is_active: bool,
+    pub token: TokenDetails,
+}
+
+/// An adversary's motives & tactics, as a reminder for the GM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AdversaryTactics {
+    pub adversary_id: String,
+    pub name: String,
+    pub motives_and_tactics: String,
+}
+
+/// What a connection is allowed to see and do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionRole {
+    /// Players on their phones
+    #[default]
+    Player,
+    Gm,
+    /// Read-only displays like the TV view or a stream: they see what
+    /// players see but can't change anything
+    Spectator,
+}
+
+/// An in-memory checkpoint the GM can roll back to
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CheckpointInfo {
+    pub id: String,
+    /// What it was taken before, e.g. "Combat started"
+    pub label: String,
+    /// "HH:MM:SS"
+    pub created_at: String,
+}
+
+/// Client → Server messages
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "payload")]
+pub enum ClientMessage {
+    /// Client connects (no name needed - connections are anonymous)
+    #[serde(rename = "connect")]
+    Connect,
+
+    /// Client selects a character to control
+    ///
+    /// This switches characters unless `keep_others` is set, which adds it
+    /// to the ones the connection already controls (a GM or a parent
+    /// running a kid's character).
+    #[serde(rename = "select_character")]
+    SelectCharacter {
+        character_id: String,
+        #[serde(default)]
+        keep_others: bool,
+    },
+
+    /// Client sets the name other players see for it
+    #[serde(rename = "set_display_name")]
+    SetDisplayName { name: String },
+
+    /// Save the connection's preferences under its session token
+    #[serde(rename = "set_preferences")]
+    SetPreferences { preferences: UserPreferences },
+
+    /// Change a character's token color (`#rrggbb`)
+    #[serde(rename = "set_character_color")]
+    SetCharacterColor { character_id: String, color: String },
+
+    /// Client creates a new character
+    #[serde(rename = "create_character")]
+    CreateCharacter {
+        name: String,
+        class: String,
+        #[serde(default)]
+        subclass: Option<String>, // e.g. "Call of the Brave"
+        ancestry: String,
+        attributes: [i8; 6], // [agility, strength, finesse, instinct, presence, knowledge]
+    },
+
+    /// Walk the controlled character to (x, y)
+    ///
+    /// `path` lists the waypoints between the current position and the
+    /// destination; empty means a straight line.
+    #[serde(rename = "move_character")]
+    MoveCharacter {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        path: Vec<Position>,
+        /// The token the client means to move; refused unless the
+        /// connection controls it (the one selected last when left out)
+        #[serde(default)]
+        character_id: Option<String>,
+        /// Degrees to face afterwards; without it the token turns to face
+        /// the way it moved
+        #[serde(default)]
+        facing: Option<f32>,
+    },
+
+    /// Show a short speech bubble or emoji over the controlled character's token
+    #[serde(rename = "emote")]
+    Emote {
+        text: String,
+        /// Also record it in the event feed
+        #[serde(default)]
+        log: bool,
+    },
+
+    /// Post to the table chat
+    #[serde(rename = "chat_message")]
+    ChatMessage { channel: ChatChannel, text: String },
+
+    /// Place the controlled character at (x, y) without crossing the space between
+    #[serde(rename = "teleport_character")]
+    TeleportCharacter {
+        x: f32,
+        y: f32,
+        /// As for `move_character`
+        #[serde(default)]
+        character_id: Option<String>,
+    },
+
+    /// GM locks the map so players can't move their tokens, or unlocks it
+    #[serde(rename = "set_map_locked")]
+    SetMapLocked { locked: bool },
+
+    /// Roll duality dice for the controlled character
+    #[serde(rename = "roll_duality")]
+    RollDuality {
+        modifier: i32,
+        with_advantage: bool,
+        #[serde(default)]
+        with_disadvantage: bool, // cancels out with advantage
+        /// Which of the connection's characters rolls (the one selected
+        /// last when left out)
+        #[serde(default)]
+        character_id: Option<String>,
+    },
+
+    /// Free-form roll of any dice expression ("2d6+1d4+3", "4d6kh3"), outside
+    /// the duality system
+    #[serde(rename = "roll_dice")]
+    RollDice {
+        expression: String,
+        /// What the roll is for, e.g. "Loot"
+        #[serde(default)]
+        label: Option<String>,
+        /// GM only: the result goes to GM connections alone
+        #[serde(default)]
+        hidden: bool,
+        /// Which of the connection's characters rolls (the one selected
+        /// last when left out)
+        #[serde(default)]
+        character_id: Option<String>,
+    },
+
+    /// Update resource for the controlled character
+    #[serde(rename = "update_resource")]
+    UpdateResource {
+        resource: String, // "hp", "stress", or "hope"
+        amount: i32,      // positive = gain, negative = lose
+    },
+
+    /// Clear a character's HP or Stress by a rest, a Hope feature or an
+    /// ally's ability (see [`healing`])
+    #[serde(rename = "heal")]
+    Heal {
+        character_id: String,
+        source: healing::HealingSource,
+    },
+
+    /// GM sets HP, Stress or Hope on any character or adversary
+    ///
+    /// `relative` adds `value` (which may be negative) to the current amount
+    /// instead of replacing it.
+    #[serde(rename = "set_resource")]
+    SetResource {
+        token_id: String,
+        resource: ResourceKind,
+        value: i32,
+        #[serde(default)]
+        relative: bool,
+    },
+
+    /// GM requests a dice roll (Phase 1)
+    #[serde(rename = "request_roll")]
+    RequestRoll {
+        target_type: RollTargetType,
+        target_character_ids: Vec<String>,
+        roll_type: RollType,
+        attribute: Option<String>, // "agility", "strength", etc.
+        difficulty: u16,
+        context: String, // "Leap across the chasm"
+        narrative_stakes: Option<String>,
+        situational_modifier: i8,
+        has_advantage: bool,
+        #[serde(default)]
+        has_disadvantage: bool, // cancels out with advantage
+        is_combat: bool,
+        /// Only the GM sees the result; the table sees that a roll was made
+        #[serde(default)]
+        hidden: bool,
+    },
+
+    /// GM previews a roll request without sending it
+    #[serde(rename = "preview_roll_request")]
+    PreviewRollRequest {
+        target_type: RollTargetType,
+        target_character_ids: Vec<String>,
+        roll_type: RollType,
+        attribute: Option<String>,
+        difficulty: u16,
+        #[serde(default)]
+        situational_modifier: i8,
+        #[serde(default)]
+        has_advantage: bool,
+        #[serde(default)]
+        has_disadvantage: bool,
+    },
+
+    /// GM requests a group action roll: helpers react, then the leader rolls
+    #[serde(rename = "request_group_roll")]
+    RequestGroupRoll {
+        leader_id: String,
+        helper_ids: Vec<String>,
+        attribute: Option<String>,
+        difficulty: u16,
+        context: String,
+        #[serde(default)]
+        has_advantage: bool,
+        #[serde(default)]
+        has_disadvantage: bool,
+    },
+
+    /// Player executes a requested roll (Phase 1)
+    #[serde(rename = "execute_roll")]
+    ExecuteRoll {
+        request_id: String,
+        spend_hope_for_bonus: bool,
+        chosen_experience: Option<String>,
+        /// Which of the connection's characters answers, for requests
+        /// aimed at more than one of them (`character_id` of the
+        /// `roll_requested` message)
+        #[serde(default)]
+        character_id: Option<String>,
+    },
+
+    /// Propose a tag team on a pending roll: both characters spend 3 Hope
+    #[serde(rename = "propose_tag_team")]
+    ProposeTagTeam {
+        request_id: String,
+        partner_id: String,
+    },
+
+    /// The proposed partner accepts or turns down a tag team
+    #[serde(rename = "respond_tag_team")]
+    RespondTagTeam { request_id: String, accept: bool },
+
+    /// Spend a Hope to help another character with their pending roll
+    #[serde(rename = "help_ally")]
+    HelpAlly {
+        request_id: String,
+        character_id: String,
+    },
+
+    /// Pick which tag team roll applies
+    #[serde(rename = "choose_tag_team_result")]
+    ChooseTagTeamResult {
+        request_id: String,
+        character_id: String,
+    },
+
+    /// Add an Experience to the controlled character (modifier defaults to +2)
+    #[serde(rename = "add_experience")]
+    AddExperience { name: String, modifier: Option<i8> },
+
+    /// Rename an Experience or change its modifier
+    #[serde(rename = "edit_experience")]
+    EditExperience {
+        name: String,
+        new_name: Option<String>,
+        modifier: Option<i8>,
+    },
+
+    // ===== Combat & Adversary Messages =====
+    
+    /// GM spawns an adversary from template
+    #[serde(rename = "spawn_adversary")]
+    SpawnAdversary {
+        template: String, // "goblin", "bandit", etc.
+        position: Position,
+    },
+
+    /// Price a fight against the party's Battle Points budget
+    #[serde(rename = "price_encounter")]
+    PriceEncounter { plan: EncounterPlan },
+
+    /// GM keeps a fight for later, replacing one of the same name
+    #[serde(rename = "save_encounter_preset")]
+    SaveEncounterPreset { preset: EncounterPreset },
+
+    /// GM forgets a kept fight
+    #[serde(rename = "delete_encounter_preset")]
+    DeleteEncounterPreset { name: String },
+
+    /// GM keeps the adversaries on the map, and where they stand, as a
+    /// fight to set up again later
+    #[serde(rename = "save_encounter_from_map")]
+    SaveEncounterFromMap { name: String },
+
+    /// GM puts every adversary of a kept fight on the map
+    #[serde(rename = "spawn_encounter")]
+    SpawnEncounter { name: String },
+
+    /// GM creates a custom adversary
+    #[serde(rename = "spawn_custom_adversary")]
+    SpawnCustomAdversary {
+        name: String,
+        position: Position,
+        hp: u8,
+        evasion: u8,
+        armor: u8,
+        attack_modifier: i8,
+        damage_dice: String,
+    },
+
+    /// GM removes an adversary
+    #[serde(rename = "remove_adversary")]
+    RemoveAdversary { adversary_id: String },
+
+    /// GM closes gaps in adversary numbering ("Goblin #1..n")
+    ///
+    /// Renumbers a single template, or every template when `template` is None.
+    #[serde(rename = "renumber_adversaries")]
+    RenumberAdversaries {
+        #[serde(default)]
+        template: Option<String>,
+    },
+
+    /// GM runs adversaries as one group, taking them out of any other
+    #[serde(rename = "create_adversary_group")]
+    CreateAdversaryGroup {
+        name: String,
+        adversary_ids: Vec<String>,
+    },
+
+    /// GM splits a group up, leaving its adversaries on the map
+    #[serde(rename = "disband_adversary_group")]
+    DisbandAdversaryGroup { group_id: String },
+
+    /// GM moves a group in formation, its centre to `position`
+    #[serde(rename = "move_adversary_group")]
+    MoveAdversaryGroup {
+        group_id: String,
+        position: Position,
+    },
+
+    /// GM gives a group one activation for all its members: the spotlight,
+    /// or the adversary token at the front of the tracker
+    #[serde(rename = "activate_adversary_group")]
+    ActivateAdversaryGroup { group_id: String },
+
+    /// GM removes a group and every adversary in it
+    #[serde(rename = "remove_adversary_group")]
+    RemoveAdversaryGroup { group_id: String },
+
+    /// GM creates an NPC
+    ///
+    /// `hp` and `evasion` override the stats of the linked adversary
+    /// `template`; without a template, `hp` is required.
+    #[serde(rename = "create_npc")]
+    CreateNpc {
+        name: String,
+        #[serde(default)]
+        hp: Option<u8>,
+        #[serde(default)]
+        evasion: Option<i32>,
+        #[serde(default)]
+        template: Option<String>,
+        #[serde(default)]
+        position: Option<Position>,
+    },
+
+    /// GM turns an NPC into an adversary
+    #[serde(rename = "convert_npc")]
+    ConvertNpc { character_id: String },
+
+    /// Fix a character's sheet (its controller or the GM)
+    #[serde(rename = "update_character")]
+    UpdateCharacter {
+        character_id: String,
+        changes: CharacterEdit,
+    },
+
+    /// GM takes a character off the table for good
+    ///
+    /// A retired character is kept in the vault; a deleted one is gone.
+    #[serde(rename = "delete_character")]
+    DeleteCharacter {
+        character_id: String,
+        #[serde(default)]
+        retire: bool,
+    },
+
+    /// GM sets the scene with an environment from the compendium
+    #[serde(rename = "spawn_environment")]
+    SpawnEnvironment { template: String },
+
+    /// GM removes an environment
+    #[serde(rename = "remove_environment")]
+    RemoveEnvironment { environment_id: String },
+
+    /// GM activates one of an environment's features (spending its Fear cost)
+    #[serde(rename = "activate_environment_feature")]
+    ActivateEnvironmentFeature {
+        environment_id: String,
+        feature: String, // Feature name
+    },
+
+    /// GM attaches an aura to a character or adversary
+    #[serde(rename = "attach_aura")]
+    AttachAura { owner_id: String, aura: Aura },
+
+    /// GM removes an aura
+    #[serde(rename = "detach_aura")]
+    DetachAura { aura_id: String },
+
+    /// Put a buff or debuff on a character or adversary (players only on
+    /// their own characters)
+    #[serde(rename = "add_modifier")]
+    AddModifier {
+        target_id: String,
+        modifier: Modifier,
+    },
+
+    /// Take a buff or debuff off before it runs out
+    #[serde(rename = "remove_modifier")]
+    RemoveModifier { modifier_id: String },
+
+    /// GM starts combat
+    #[serde(rename = "start_combat")]
+    StartCombat,
+
+    /// GM restores an in-memory checkpoint, the latest when `checkpoint_id` is None
+    #[serde(rename = "rollback_to_checkpoint")]
+    RollbackToCheckpoint {
+        #[serde(default)]
+        checkpoint_id: Option<String>,
+    },
+
+    /// GM sets when the table next meets (RFC 3339), replacing any earlier date
+    #[serde(rename = "schedule_next_session")]
+    ScheduleNextSession {
+        scheduled_at: String,
+        #[serde(default)]
+        notes: String,
+    },
+
+    /// GM cancels the next scheduled session
+    #[serde(rename = "clear_next_session")]
+    ClearNextSession,
+
+    /// GM writes a new note, or rewrites the one `note_id` names
+    #[serde(rename = "save_note")]
+    SaveNote {
+        #[serde(default)]
+        note_id: Option<String>,
+        note: NoteDraft,
+    },
+
+    /// GM throws a note away
+    #[serde(rename = "delete_note")]
+    DeleteNote { note_id: String },
+
+    /// Ask for the notes this connection can read, optionally only those
+    /// with every word of `search`
+    #[serde(rename = "request_notes")]
+    RequestNotes {
+        #[serde(default)]
+        search: String,
+    },
+
+    /// GM puts a new quest on the board, or rewrites the one `quest_id`
+    /// names (objectives and countdowns of the same text keep where they
+    /// stand)
+    #[serde(rename = "save_quest")]
+    SaveQuest {
+        #[serde(default)]
+        quest_id: Option<String>,
+        quest: QuestDraft,
+    },
+
+    /// GM takes a quest off the board
+    #[serde(rename = "remove_quest")]
+    RemoveQuest { quest_id: String },
+
+    /// GM ticks an objective off, or back on
+    #[serde(rename = "set_objective_completed")]
+    SetObjectiveCompleted {
+        quest_id: String,
+        objective_id: String,
+        completed: bool,
+    },
+
+    /// GM ticks a quest's countdown down (or back up, for a negative count)
+    #[serde(rename = "tick_quest_countdown")]
+    TickQuestCountdown {
+        quest_id: String,
+        countdown_id: String,
+        ticks: i8,
+    },
+
+    /// GM shares a handout with everyone, or only the listed characters
+    #[serde(rename = "share_handout")]
+    ShareHandout {
+        title: String,
+        kind: HandoutKind,
+        /// The text or markdown, or the image base64-encoded (a `data:` URL
+        /// works too)
+        content: String,
+        #[serde(default)]
+        character_ids: Vec<String>,
+    },
+
+    /// Ask for the handouts this connection may see
+    #[serde(rename = "request_handouts")]
+    RequestHandouts,
+
+    /// GM puts an uploaded map on the table, or clears it with no id
+    #[serde(rename = "set_map")]
+    SetMap {
+        #[serde(default)]
+        map_id: Option<String>,
+    },
+
+    /// GM sets the scene's size and grid; saved with the map on the table
+    #[serde(rename = "set_scene_layout")]
+    SetSceneLayout { layout: SceneLayout },
+
+    /// GM turns fog of war on or off for the current scene
+    #[serde(rename = "set_fog_enabled")]
+    SetFogEnabled { enabled: bool },
+
+    /// GM reveals a polygon of the current scene to the players
+    #[serde(rename = "reveal_area")]
+    RevealArea { points: Vec<Position> },
+
+    /// GM covers a revealed area again
+    #[serde(rename = "hide_area")]
+    HideArea { area_id: String },
+
+    /// Turn a token, resize it or set its elevation or note
+    ///
+    /// Players may change their own character's token; the GM any token.
+    #[serde(rename = "set_token_details")]
+    SetTokenDetails {
+        token_id: String,
+        token: TokenDetails,
+    },
+
+    /// GM puts any character's or adversary's token somewhere
+    ///
+    /// Skips the mover's movement rules; `teleport` only changes how
+    /// clients animate it.
+    #[serde(rename = "move_token")]
+    MoveToken {
+        token_id: String,
+        position: Position,
+        #[serde(default)]
+        teleport: bool,
+    },
+
+    /// GM renames any token, or changes its facing, size, elevation or note
+    #[serde(rename = "edit_token")]
+    EditToken {
+        token_id: String,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        token: Option<TokenDetails>,
+    },
+
+    /// Point something out: everyone sees a pulse there
+    #[serde(rename = "ping")]
+    Ping { position: Position },
+
+    /// GM draws on the current scene
+    #[serde(rename = "add_drawing")]
+    AddDrawing {
+        shape: DrawingShape,
+        color: String,
+        #[serde(default)]
+        label: String,
+    },
+
+    /// GM erases a drawing from the current scene
+    #[serde(rename = "remove_drawing")]
+    RemoveDrawing { drawing_id: String },
+
+    /// GM erases every drawing from the current scene
+    #[serde(rename = "clear_drawings")]
+    ClearDrawings,
+
+    /// GM cuts away to a scene with some of the characters
+    #[serde(rename = "start_cutaway")]
+    StartCutaway {
+        character_ids: Vec<String>,
+        #[serde(default)]
+        label: String,
+    },
+
+    /// GM returns from the cutaway to the whole table
+    #[serde(rename = "end_cutaway")]
+    EndCutaway,
+
+    /// GM ends combat
+    #[serde(rename = "end_combat")]
+    EndCombat,
+
+    /// GM switches the current encounter between the tracker and spotlight flow
+    #[serde(rename = "set_combat_mode")]
+    SetCombatMode { mode: CombatMode },
+
+    /// Hand the spotlight to a character or adversary (`None` gives it to the GM)
+    #[serde(rename = "pass_spotlight")]
+    PassSpotlight { to: Option<String> },
+
+    /// GM advances the combat round, ticking every active hazard
+    #[serde(rename = "advance_round")]
+    AdvanceRound,
+
+    /// GM closes out the round: the Action Tracker refills and the next
+    /// round starts
+    #[serde(rename = "end_round")]
+    EndRound,
+
+    /// Ask what happened in each round of the fight so far
+    #[serde(rename = "request_round_history")]
+    RequestRoundHistory,
+
+    /// GM places an ambient hazard on the scene (starts active)
+    #[serde(rename = "add_hazard")]
+    AddHazard { hazard: Hazard },
+
+    /// GM removes a hazard from the scene
+    #[serde(rename = "remove_hazard")]
+    RemoveHazard { hazard_id: String },
+
+    /// GM switches a hazard on or off
+    #[serde(rename = "set_hazard_active")]
+    SetHazardActive { hazard_id: String, active: bool },
+
+    /// GM ticks one hazard's countdown outside the round cadence
+    #[serde(rename = "tick_hazard")]
+    TickHazard { hazard_id: String },
+
+    /// GM adds a token to the action tracker
+    #[serde(rename = "add_tracker_token")]
+    AddTrackerToken { token_type: String }, // "pc" or "adversary"
+
+    /// GM spends the next token on the action tracker
+    #[serde(rename = "spend_tracker_token")]
+    SpendTrackerToken,
+
+    /// GM moves a token from the pool onto the tracker after a roll
+    #[serde(rename = "advance_tracker")]
+    AdvanceTracker { success_with_hope: bool },
+
+    /// GM spends a Fear from the pool to act in combat
+    #[serde(rename = "spend_fear")]
+    SpendFear { spend: FearSpend },
+
+    /// GM marks an adversary's Stress, optionally to power one of its moves
+    #[serde(rename = "mark_adversary_stress")]
+    MarkAdversaryStress {
+        adversary_id: String,
+        stress: u8,
+        #[serde(default)]
+        stress_move: Option<StressMove>,
+    },
+
+    /// Ask how far apart two tokens are (answered only to the asker)
+    #[serde(rename = "measure_range")]
+    MeasureRange {
+        from_id: String, // character or adversary ID
+        to_id: String,   // character or adversary ID
+    },
+
+    /// Player or GM rolls an attack; an equipped weapon must reach the target
+    #[serde(rename = "attack")]
+    Attack {
+        attacker_id: String, // character or adversary ID
+        target_id: String,   // character or adversary ID
+        modifier: i8,        // situational; equipped weapon trait is added automatically
+        with_advantage: bool,
+        #[serde(default)]
+        with_disadvantage: bool, // cancels out with advantage
+    },
+
+    /// An adversary attacks a character: d20 vs Evasion, damage applied on a hit (GM)
+    #[serde(rename = "adversary_attack")]
+    AdversaryAttack {
+        adversary_id: String,
+        target_id: String,
+        #[serde(default)]
+        with_advantage: bool,
+        #[serde(default)]
+        with_disadvantage: bool,
+    },
+
+    /// Roll damage after a successful attack; if the server rolled that
+    /// attack as a critical hit, the dice's maximum is added
+    #[serde(rename = "roll_damage")]
+    RollDamage {
+        attacker_id: String,
+        target_id: String,
+        damage_dice: Option<String>, // "1d8+2"; defaults to the attacker's equipped weapon
+        armor: Option<u8>,           // defaults to the target's armor score
+        /// Spells and effects name their type; defaults to the attacker's weapon or attack
+        #[serde(default)]
+        damage_type: Option<DamageType>,
+    },
+
+    /// Roll damage once and apply it to everyone in an area and/or the
+    /// given targets, each with their own armor and defenses
+    #[serde(rename = "apply_damage_to_area")]
+    ApplyDamageToArea {
+        /// What deals the damage, e.g. "Fireball"
+        source: String,
+        #[serde(default)]
+        area: Option<HazardArea>,
+        /// Characters, adversaries or adversary groups hit whether or not
+        /// they're in the area
+        #[serde(default)]
+        target_ids: Vec<String>,
+        damage_dice: String,
+        #[serde(default)]
+        damage_type: Option<DamageType>,
+    },
+
+    /// Set a character's or adversary's resistances, immunities and vulnerabilities (GM)
+    #[serde(rename = "set_defenses")]
+    SetDefenses {
+        target_id: String,
+        defenses: Defenses,
+    },
+
+    /// Change some of the table settings (GM)
+    #[serde(rename = "update_table_settings")]
+    UpdateTableSettings { settings: TableSettingsPatch },
+
+    // ===== Inventory Messages =====
+    /// Add an item to the controlled character's inventory
+    #[serde(rename = "add_item")]
+    AddItem {
+        name: String,
+        description: String,
+        quantity: u32,
+    },
+
+    /// Remove an item from the controlled character's inventory
+    #[serde(rename = "remove_item")]
+    RemoveItem { item_id: String, quantity: u32 },
+
+    /// Give an item from the controlled character to another character
+    #[serde(rename = "transfer_item")]
+    TransferItem {
+        item_id: String,
+        target_character_id: String,
+        quantity: u32,
+    },
+
+    /// GM grants an item to any character
+    #[serde(rename = "grant_item")]
+    GrantItem {
+        character_id: String,
+        name: String,
+        description: String,
+        quantity: u32,
+    },
+
+    /// GM awards gold (in handfuls) to a character
+    #[serde(rename = "award_gold")]
+    AwardGold { character_id: String, handfuls: u32 },
+
+    /// Equip a weapon on the controlled character (null unequips)
+    #[serde(rename = "equip_weapon")]
+    EquipWeapon { weapon: Option<Weapon> },
+
+    /// Equip armor on the controlled character (null unequips)
+    #[serde(rename = "equip_armor")]
+    EquipArmor { armor: Option<Armor> },
+
+    /// GM temporarily takes control of a character whose player dropped mid-combat
+    #[serde(rename = "assume_control")]
+    AssumeControl { character_id: String },
+
+    /// Player asks the GM to hand them a character another connection
+    /// controls (e.g. after switching devices)
+    #[serde(rename = "request_control")]
+    RequestControl { character_id: String },
+
+    /// GM hands a character to a connection, taking it from whoever had it
+    #[serde(rename = "transfer_control")]
+    TransferControl {
+        character_id: String,
+        to_connection_id: String,
+    },
+
+    /// GM frees a character from whoever controls it, so anyone can pick it
+    #[serde(rename = "release_control")]
+    ReleaseControl { character_id: String },
+
+    /// Ask for a character's whole sheet again, after missing a change
+    #[serde(rename = "request_sheet")]
+    RequestSheet { character_id: String },
+
+    /// Ask for a page of the event log (see [`events::EventLogQuery`])
+    #[serde(rename = "request_event_log")]
+    RequestEventLog {
+        #[serde(default)]
+        event_type: Option<String>,
+        #[serde(default)]
+        character: Option<String>,
+        #[serde(default)]
+        offset: usize,
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+
+    /// Ask for the event log grouped by scene and fight, optionally only
+    /// one kind of event or one character's
+    #[serde(rename = "request_scene_log")]
+    RequestSceneLog {
+        #[serde(default)]
+        event_type: Option<String>,
+        #[serde(default)]
+        character: Option<String>,
+    },
+
+    /// GM starts a new scene in the log, e.g. "The Ambush at Thornwood"
+    #[serde(rename = "mark_scene")]
+    MarkScene { title: String },
+
+    /// Ask how everyone's duality dice have been rolling
+    #[serde(rename = "request_roll_stats")]
+    RequestRollStats,
+
+    /// GM closes a connection, optionally banning its address until the
+    /// server restarts
+    #[serde(rename = "kick_connection")]
+    KickConnection {
+        connection_id: String,
+        #[serde(default)]
+        ban: bool,
+    },
+
+    /// GM marks that a character can level up
+    #[serde(rename = "mark_level_up")]
+    MarkLevelUp { character_id: String },
+
+    /// Player picks advancements for the controlled character's pending level up
+    #[serde(rename = "choose_level_up")]
+    ChooseLevelUp {
+        advancements: Vec<Advancement>,
+        new_experience: Option<String>, // Required at tier achievements (levels 2, 5, 8)
+    },
+}
+
+/// Server → Client messages
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "payload")]
+pub enum ServerMessage {
+    /// Connection established, returns connection ID
+    #[serde(rename = "connected")]
+    Connected {
+        connection_id: String,
+        /// Reconnect with `/ws?session=<token>` to keep these preferences
+        #[serde(default)]
+        session_token: String,
+        #[serde(default)]
+        preferences: UserPreferences,
+        /// `gm` only when requested with `/ws?role=gm` (and the API token, if one is set)
+        #[serde(default)]
+        role: ConnectionRole,
+    },
+
+    /// Catch-up snapshot sent to each new connection
+    #[serde(rename = "catch_up")]
+    CatchUp { snapshot: SessionSnapshot },
+
+    /// List of all characters in the game
+    #[serde(rename = "characters_list")]
+    CharactersList { characters: Vec<CharacterInfo> },
+
+    /// List of all adversaries in the game
+    #[serde(rename = "adversaries_list")]
+    AdversariesList {
+        adversaries: Vec<AdversaryInfo>,
+    },
+
+    /// Character was selected successfully
+    #[serde(rename = "character_selected")]
+    CharacterSelected {
+        character_id: String,
+        character: CharacterData,
+    },
+
+    /// A character spawned in the game
+    #[serde(rename = "character_spawned")]
+    CharacterSpawned {
+        character_id: String,
+        name: String,
+        position: Position,
+        color: String,
+        is_npc: bool,
+        token: TokenDetails,
+    },
+
+    /// A character was removed from the game
+    #[serde(rename = "character_removed")]
+    CharacterRemoved { character_id: String, name: String },
+
+    /// A character's player dropped mid-combat and it is awaiting a player or GM stand-in
+    #[serde(rename = "character_ghosted")]
+    CharacterGhosted { character_id: String, name: String },
+
+    /// GM took temporary control of a ghosted character
+    #[serde(rename = "character_control_assumed")]
+    CharacterControlAssumed {
+        character_id: String,
+        name: String,
+        connection_id: String,
+    },
+
+    /// A player asked for a character someone else controls (GM only)
+    #[serde(rename = "control_requested")]
+    ControlRequested {
+        character_id: String,
+        name: String,
+        /// The connection asking
+        connection_id: String,
+        display_name: Option<String>,
+    },
+
+    /// The GM moved control of a character; `None` on either side means nobody
+    #[serde(rename = "control_transferred")]
+    ControlTransferred {
+        character_id: String,
+        name: String,
+        from_connection_id: Option<String>,
+        to_connection_id: Option<String>,
+    },
+
+    /// The GM kicked a connection; it is closed right after this message
+    #[serde(rename = "kicked")]
+    Kicked {
+        connection_id: String,
+        display_name: Option<String>,
+        banned: bool,
+    },
+
+    /// A returning player reclaimed their ghosted character
+    #[serde(rename = "character_reclaimed")]
+    CharacterReclaimed { character_id: String, name: String },
+
+    /// A character can level up: advancement options for the new level
+    #[serde(rename = "level_up_offered")]
+    LevelUpOffered {
+        character_id: String,
+        new_level: u8,
+        tier: u8,
+        tier_achievement: bool, // Choose a new Experience too
+        options: Vec<AdvancementOption>,
+    },
+
+    /// A character moved
+    #[serde(rename = "character_moved")]
+    CharacterMoved {
+        character_id: String,
+        position: Position,
+        /// Route taken from the old position to `position`, for animation
+        /// (empty when teleported)
+        path: Vec<Position>,
+        teleported: bool,
+        /// Names of hazards the route passed through
+        crossed_hazards: Vec<String>,
+        /// Degrees the token faces once it arrives
+        rotation: f32,
+    },
+
+    /// A connection's preferences were saved
+    #[serde(rename = "preferences_updated")]
+    PreferencesUpdated {
+        connection_id: String,
+        preferences: UserPreferences,
+    },
+
+    /// A connection chose a display name
+    #[serde(rename = "display_name_changed")]
+    DisplayNameChanged {
+        connection_id: String,
+        display_name: String,
+        character_id: Option<String>, // Character the connection controls
+    },
+
+    /// A character's token color changed
+    #[serde(rename = "character_color_changed")]
+    CharacterColorChanged { character_id: String, color: String },
+
+    /// A character emoted; clients show a bubble over its token for `duration_ms`
+    #[serde(rename = "character_emoted")]
+    CharacterEmoted {
+        character_id: String,
+        character_name: String,
+        text: String,
+        duration_ms: u32,
+    },
+
+    /// A chat message; whispers reach only their sender, recipient and the GM
+    #[serde(rename = "chat_message")]
+    ChatMessage { message: ChatEntry },
+
+    /// Character was created
+    #[serde(rename = "character_created")]
+    CharacterCreated {
+        character_id: String,
+        character: CharacterData,
+    },
+
+    /// A character's whole sheet, the first time it changes and whenever a
+    /// client asks for it again
+    #[serde(rename = "character_updated")]
+    CharacterUpdated {
+        character_id: String,
+        character: CharacterData,
+        /// See [`sheets`]
+        sequence: u64,
+        /// The connection that asked for it; `None` when sent to everyone
+        connection_id: Option<String>,
+    },
+
+    /// Fields of a character's sheet that changed since sequence `since`
+    #[serde(rename = "character_changed")]
+    CharacterChanged {
+        character_id: String,
+        sequence: u64,
+        since: u64,
+        changes: CharacterChanges,
+    },
+
+    /// Dice roll result (legacy)
+    #[serde(rename = "roll_result")]
+    RollResult {
+        character_id: String,
+        character_name: String,
+        roll: RollResult,
+    },
+
+    /// A free-form dice roll, with every die for animating
+    #[serde(rename = "dice_rolled")]
+    DiceRolled {
+        /// The rolling character, absent when the GM rolled
+        character_id: Option<String>,
+        roller_name: String,
+        label: Option<String>,
+        roll: DiceRoll,
+        /// Sent to GM connections only
+        #[serde(default)]
+        hidden: bool,
+    },
+
+    /// Roll requested by GM (Phase 1)
+    #[serde(rename = "roll_requested")]
+    RollRequested {
+        request_id: String,
+        character_id: String,
+        roll_type: RollType,
+        attribute: Option<String>,
+        difficulty: u16,
+        context: String,
+        narrative_stakes: Option<String>,
+        base_modifier: i8,
+        situational_modifier: i8,
+        total_modifier: i8,
+        has_advantage: bool,
+        #[serde(default)]
+        has_disadvantage: bool,
+        your_attribute_value: i8,
+        your_proficiency: i8,
+        can_spend_hope: bool,
+        experiences: Vec<Experience>,
+        /// The result will go to the GM only
+        #[serde(default)]
+        hidden: bool,
+    },
+
+    /// Detailed roll result (Phase 1)
+    #[serde(rename = "detailed_roll_result")]
+    DetailedRollResult {
+        request_id: String,
+        character_id: String,
+        character_name: String,
+        roll_type: RollType,
+        context: String,
+        roll_details: DetailedRollResult,
+        new_hope: u8,
+        new_fear: u8,
+        /// Sent to GM connections only
+        #[serde(default)]
+        hidden: bool,
+    },
+
+    /// A group action roll finished: the leader's result with every helper's
+    #[serde(rename = "group_roll_result")]
+    GroupRollResult {
+        request_id: String,
+        context: String,
+        leader_id: String,
+        leader_name: String,
+        roll_type: RollType,
+        helpers: Vec<GroupHelperResult>,
+        group_modifier: i8,
+        total: u16,
+        difficulty: u16,
+        success_type: SuccessType,
+        /// Sent to GM connections only
+        #[serde(default)]
+        hidden: bool,
+    },
+
+    /// An ally spent Hope to add a help die to a character's pending roll
+    #[serde(rename = "ally_helped")]
+    AllyHelped {
+        request_id: String,
+        helper_id: String,
+        helper_name: String,
+        character_id: String,
+        character_name: String,
+    },
+
+    /// Each target's modifiers and odds for a previewed roll request (GM-only)
+    #[serde(rename = "roll_preview")]
+    RollPreview { previews: Vec<RollPreview> },
+
+    /// A player proposed a tag team; the partner should respond
+    #[serde(rename = "tag_team_proposed")]
+    TagTeamProposed {
+        request_id: String,
+        context: String,
+        initiator_id: String,
+        initiator_name: String,
+        partner_id: String,
+        partner_name: String,
+    },
+
+    /// The partner accepted (both paid 3 Hope) or turned down a tag team
+    #[serde(rename = "tag_team_response")]
+    TagTeamResponse {
+        request_id: String,
+        partner_id: String,
+        partner_name: String,
+        accepted: bool,
+    },
+
+    /// Both halves of a tag team rolled; the group picks one result
+    #[serde(rename = "tag_team_rolled")]
+    TagTeamRolled {
+        request_id: String,
+        results: Vec<TagTeamRollResult>,
+    },
+
+    /// The group picked a tag team result, and Hope and Fear followed it
+    #[serde(rename = "tag_team_resolved")]
+    TagTeamResolved {
+        request_id: String,
+        character_id: String,
+        character_name: String,
+        total: u16,
+        difficulty: u16,
+        success_type: SuccessType,
+        /// Hope each character gained
+        hope_change: i8,
+        fear_change: u8,
+        new_fear: u8,
+    },
+
+    /// Roll request status (GM-only, Phase 1)
+    #[serde(rename = "roll_request_status")]
+    RollRequestStatus {
+        request_id: String,
+        pending_characters: Vec<String>,
+        completed_characters: Vec<String>,
+    },
+    
+    /// Game event (for event log)
+    #[serde(rename = "game_event")]
+    GameEvent {
+        timestamp: String,
+        event_type: String,
+        message: String,
+        character_name: Option<String>,
+        details: Option<String>,
+        player_name: Option<String>,
+        /// Part of a running cutaway, seen only by those in it until it ends
+        #[serde(default)]
+        cutaway: bool,
+    },
+
+    /// A page of the event log, oldest first, for the connection that asked
+    #[serde(rename = "event_log")]
+    EventLog {
+        events: Vec<GameEventData>,
+        /// Events matching the query in all
+        #[serde(default)]
+        total: usize,
+        /// Newest matching events skipped before this page
+        #[serde(default)]
+        offset: usize,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    /// The event log by scene and fight, for the connection that asked
+    #[serde(rename = "scene_log")]
+    SceneLog {
+        scenes: Vec<events::SceneLog>,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    /// Duality roll tallies, for the connection that asked
+    #[serde(rename = "roll_stats")]
+    RollStats {
+        stats: stats::RollStatsReport,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    // ===== Combat & Adversary Messages =====
+    /// A fight priced against its Battle Points budget, for the connection
+    /// that asked
+    #[serde(rename = "encounter_priced")]
+    EncounterPriced {
+        budget: EncounterBudget,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    /// The GM's kept fights, after one was saved or deleted (GM-only)
+    #[serde(rename = "encounter_presets")]
+    EncounterPresets {
+        presets: Vec<EncounterPreset>,
+        /// Adversaries left out of a preset just taken from the map
+        #[serde(default)]
+        skipped: Vec<String>,
+    },
+
+    /// Adversary spawned
+    #[serde(rename = "adversary_spawned")]
+    AdversarySpawned {
+        adversary_id: String,
+        name: String,
+        template: String,
+        tier: u8,
+        role: AdversaryRole,
+        position: Position,
+        hp: u8,
+        max_hp: u8,
+        evasion: u8,
+        armor: u8,
+        attack_modifier: i8,
+        damage_dice: String,
+        /// From the stat block; sent to GM connections only, empty for players
+        #[serde(default)]
+        motives_and_tactics: String,
+        token: TokenDetails,
+    },
+
+    /// An adversary token reached the front of the action tracker, or an
+    /// adversary took the spotlight: how the adversaries that may act fight
+    /// (GM-only)
+    #[serde(rename = "tactics_reminder")]
+    TacticsReminder { adversaries: Vec<AdversaryTactics> },
+
+    /// Adversary removed
+    #[serde(rename = "adversary_removed")]
+    AdversaryRemoved {
+        adversary_id: String,
+        name: String,
+    },
+
+    /// Every adversary group and its members' HP, after a group changed or
+    /// its members were hurt
+    #[serde(rename = "adversary_groups_updated")]
+    AdversaryGroupsUpdated { groups: Vec<AdversaryGroupInfo> },
+
+    /// An environment was added to the scene
+    #[serde(rename = "environment_spawned")]
+    EnvironmentSpawned { environment: EnvironmentInfo },
+
+    /// An environment was removed
+    #[serde(rename = "environment_removed")]
+    EnvironmentRemoved {
+        environment_id: String,
+        name: String,
+    },
+
+    /// The GM activated an environment feature
+    #[serde(rename = "environment_feature_activated")]
+    EnvironmentFeatureActivated {
+        environment_id: String,
+        environment_name: String,
+        feature: EnvironmentFeature,
+        fear_pool: u8,
+    },
+
+    /// An aura was attached to a token
+    #[serde(rename = "aura_attached")]
+    AuraAttached {
+        aura_id: String,
+        owner_id: String,
+        aura: Aura,
+    },
+
+    /// An aura was removed (detached, or its owner left the map)
+    #[serde(rename = "aura_detached")]
+    AuraDetached { aura_id: String, name: String },
+
+    /// Every buff and debuff on the table, after one was added, removed or
+    /// ran out
+    #[serde(rename = "modifiers_updated")]
+    ModifiersUpdated { modifiers: Vec<TimedModifier> },
+
+    /// A token moved into an aura and gained its effect
+    #[serde(rename = "aura_entered")]
+    AuraEntered {
+        aura_id: String,
+        aura_name: String,
+        token_id: String,
+        token_name: String,
+    },
+
+    /// A token left an aura and lost its effect
+    #[serde(rename = "aura_left")]
+    AuraLeft {
+        aura_id: String,
+        aura_name: String,
+        token_id: String,
+        token_name: String,
+    },
+
+    /// Adversary updated (HP/Stress changed)
+    #[serde(rename = "adversary_updated")]
+    AdversaryUpdated {
+        adversary_id: String,
+        hp: u8,
+        stress: u8,
+        is_active: bool,
+    },
+
+    /// Combat started
+    #[serde(rename = "combat_started")]
+    CombatStarted {
+        encounter_id: String,
+        pc_tokens: u8,
+        adversary_tokens: u8,
+    },
+
+    /// Combat ended
+    #[serde(rename = "combat_ended")]
+    CombatEnded { reason: String },
+
+    /// The encounter's combat mode changed
+    #[serde(rename = "combat_mode_changed")]
+    CombatModeChanged { mode: CombatMode },
+
+    /// The spotlight moved (`holder_id` is `None` while the GM holds it)
+    #[serde(rename = "spotlight_changed")]
+    SpotlightChanged {
+        holder_id: Option<String>,
+        holder_name: String,
+        previous_id: Option<String>,
+    },
+
+    /// The combat round advanced
+    #[serde(rename = "round_advanced")]
+    RoundAdvanced { round: u32 },
+
+    /// The GM closed out a round and the next began, with a fresh Action
+    /// Tracker
+    #[serde(rename = "round_started")]
+    RoundStarted {
+        round: u32,
+        /// What happened in the round that ended
+        previous: rounds::RoundHistory,
+    },
+
+    /// Each round of the fight so far, for the connection that asked
+    #[serde(rename = "round_history")]
+    RoundHistory {
+        rounds: Vec<rounds::RoundHistory>,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    /// A hazard was placed on the scene
+    #[serde(rename = "hazard_added")]
+    HazardAdded {
+        hazard_id: String,
+        hazard: Hazard,
+        active: bool,
+        ticks_remaining: u8,
+    },
+
+    /// A hazard was removed from the scene
+    #[serde(rename = "hazard_removed")]
+    HazardRemoved { hazard_id: String, name: String },
+
+    /// A hazard was switched on or off, or its countdown ticked
+    #[serde(rename = "hazard_updated")]
+    HazardUpdated {
+        hazard_id: String,
+        active: bool,
+        ticks_remaining: u8,
+    },
+
+    /// A hazard went off; roll requests or damage results follow
+    #[serde(rename = "hazard_triggered")]
+    HazardTriggered {
+        hazard_id: String,
+        name: String,
+        affected: Vec<String>, // Names of tokens in the area
+    },
+
+    /// Action tracker updated
+    #[serde(rename = "tracker_updated")]
+    TrackerUpdated {
+        pc_tokens: u8,
+        adversary_tokens: u8,
+        next_token: String, // "pc" or "adversary"
+        queue: Vec<String>, // Tokens in acting order
+        round: u32,
+    },
+
+    /// An adversary marked Stress; for `act_again` the tracker or spotlight
+    /// change follows
+    #[serde(rename = "adversary_stress_marked")]
+    AdversaryStressMarked {
+        adversary_id: String,
+        name: String,
+        stress: u8,
+        max_stress: u8,
+        stress_move: Option<StressMove>,
+        /// Announcement for the table
+        message: String,
+    },
+
+    /// The GM spent a Fear; the tracker or spotlight change follows
+    #[serde(rename = "fear_spent")]
+    FearSpent {
+        spend: FearSpend,
+        /// Announcement for the table
+        message: String,
+        fear_pool: u8,
+    },
+
+    /// How far apart two tokens are, for the connection that asked
+    #[serde(rename = "range_measured")]
+    RangeMeasured {
+        connection_id: String,
+        from_id: String,
+        to_id: String,
+        /// At the scene's scale
+        feet: f32,
+        /// `None` past Very Far
+        band: Option<WeaponRange>,
+    },
+
+    /// Attack result
+    #[serde(rename = "attack_result")]
+    AttackResult {
+        attacker_id: String,
+        attacker_name: String,
+        target_id: String,
+        target_name: String,
+        hope: u16,
+        fear: u16,
+        modifier: i8,
+        #[serde(default)]
+        advantage_die: Option<u8>,
+        #[serde(default)]
+        disadvantage_die: Option<u8>,
+        total: u16,
+        target_evasion: u8,
+        hit: bool,
+        controlling_die: ControllingDie,
+        is_critical: bool,
+        /// Flavor text, absent in plain narrative mode
+        #[serde(default)]
+        narration: Option<String>,
+    },
+
+    /// An adversary's attack roll; a `damage_result` follows on a hit
+    #[serde(rename = "adversary_attack_result")]
+    AdversaryAttackResult {
+        adversary_id: String,
+        adversary_name: String,
+        target_id: String,
+        target_name: String,
+        rolls: Vec<u8>, // d20s rolled; two with advantage or disadvantage
+        die: u8,        // the d20 that counts
+        modifier: i8,
+        total: i16,
+        target_evasion: i32,
+        hit: bool,
+        is_critical: bool, // natural 20
+        #[serde(default)]
+        narration: Option<String>,
+    },
+
+    /// Damage result
+    #[serde(rename = "damage_result")]
+    DamageResult {
+        target_id: String,
+        target_name: String,
+        raw_damage: u16, // includes any critical bonus
+        #[serde(default)]
+        is_critical: bool,
+        #[serde(default)]
+        critical_bonus: u16, // maximum of the damage dice, added on a critical
+        /// Each damage die as rolled, for animating
+        #[serde(default)]
+        dice: Vec<DieResult>,
+        damage_type: DamageType,
+        adjustment: DamageAdjustment, // resistance halves, vulnerability doubles
+        adjusted_damage: u16,
+        after_armor: u16,
+        hp_lost: u8,
+        stress_gained: u8,
+        new_hp: u8,
+        new_stress: u8,
+        taken_out: bool,
+        /// Flavor text when the target is taken out, absent in plain narrative mode
+        #[serde(default)]
+        narration: Option<String>,
+    },
+
+    /// A character was healed; their sheet follows
+    #[serde(rename = "character_healed")]
+    CharacterHealed { healing: healing::Healing },
+
+    /// Area damage was rolled once and dealt to every target
+    #[serde(rename = "area_damage_result")]
+    AreaDamageResult {
+        source: String,
+        raw_damage: u16,
+        /// Each damage die as rolled, for animating
+        dice: Vec<DieResult>,
+        damage_type: DamageType,
+        targets: Vec<AreaDamageTarget>,
+    },
+
+    /// The game was rolled back to a checkpoint; clients should reload
+    #[serde(rename = "checkpoint_restored")]
+    CheckpointRestored { checkpoint: CheckpointInfo },
+
+    /// The next session was scheduled or cancelled (`None`)
+    #[serde(rename = "next_session_updated")]
+    NextSessionUpdated { next_session: Option<NextSession> },
+
+    /// A note was written or changed; players get a note taken private as
+    /// `note_deleted`
+    #[serde(rename = "note_saved")]
+    NoteSaved { note: Note },
+
+    #[serde(rename = "note_deleted")]
+    NoteDeleted { note_id: String },
+
+    /// A quest went on the board or changed
+    #[serde(rename = "quest_updated")]
+    QuestUpdated { quest: Quest },
+
+    #[serde(rename = "quest_removed")]
+    QuestRemoved { quest_id: String, title: String },
+
+    /// Notes matching a search, for the connection that asked
+    #[serde(rename = "notes")]
+    Notes {
+        notes: Vec<Note>,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    /// The GM shared a handout; private ones reach only their characters'
+    /// players and the GM
+    #[serde(rename = "handout_shared")]
+    HandoutShared { handout: Handout },
+
+    /// Handouts shared so far, oldest first, in answer to `request_handouts`;
+    /// each connection gets only those it may see
+    #[serde(rename = "handouts")]
+    Handouts {
+        handouts: Vec<Handout>,
+        #[serde(default)]
+        connection_id: Option<String>,
+    },
+
+    /// The map on the table changed (`None` = no map)
+    #[serde(rename = "map_changed")]
+    MapChanged { map: Option<MapInfo> },
+
+    /// The scene's size or grid changed (also sent when the map changes)
+    #[serde(rename = "scene_layout_changed")]
+    SceneLayoutChanged { layout: SceneLayout },
+
+    /// The current scene's fog changed (also sent when the map changes)
+    #[serde(rename = "fog_changed")]
+    FogChanged { fog: FogState },
+
+    /// The GM locked or unlocked token movement
+    #[serde(rename = "map_lock_changed")]
+    MapLockChanged { locked: bool },
+
+    /// A token went under the fog; players drop it until it's revealed again
+    #[serde(rename = "token_hidden")]
+    TokenHidden { token_id: String },
+
+    /// The GM moved an adversary
+    #[serde(rename = "adversary_moved")]
+    AdversaryMoved {
+        adversary_id: String,
+        position: Position,
+    },
+
+    /// The GM renamed a character's or adversary's token
+    #[serde(rename = "token_renamed")]
+    TokenRenamed { token_id: String, name: String },
+
+    /// A token turned, changed size or elevation, or got a new note
+    #[serde(rename = "token_details_changed")]
+    TokenDetailsChanged {
+        token_id: String,
+        token: TokenDetails,
+    },
+
+    /// Someone pinged the map; clients show a brief pulse
+    #[serde(rename = "pinged")]
+    Pinged {
+        position: Position,
+        name: String,
+        color: String,
+    },
+
+    /// The current scene's drawings changed (also sent when the map changes)
+    #[serde(rename = "drawings_changed")]
+    DrawingsChanged { drawings: Vec<Drawing> },
+
+    /// The GM cut away to a scene; everyone not in it sees a placeholder
+    #[serde(rename = "cutaway_started")]
+    CutawayStarted { cutaway: CutawayInfo },
+
+    /// The cutaway ended; `events` are what happened in it, now in the
+    /// public log
+    #[serde(rename = "cutaway_ended")]
+    CutawayEnded {
+        cutaway: CutawayInfo,
+        events: Vec<GameEventData>,
+    },
+
+    /// The table settings changed
+    #[serde(rename = "settings_updated")]
+    SettingsUpdated { settings: TableSettings },
+
+    /// A request failed; sent only to the connection that made it
+    #[serde(rename = "error")]
+    Error {
+        code: ErrorCode,
+        message: String,
+        /// `type` of the failed request
+        request_type: Option<String>,
+        /// The request's `request_id`, if the client gave one
+        request_id: Option<String>,
+        /// The connection that made the request; `None` for notices to everyone
+        connection_id: Option<String>,
+    },
+}
+
+/// Game event data for serialization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GameEventData {
+    pub timestamp: String,
+    pub event_type: String,
+    pub message: String,
+    pub character_name: Option<String>,
+    pub details: Option<String>,
+    pub player_name: Option<String>,
+    /// Part of a running cutaway, seen only by those in it until it ends
+    #[serde(default)]
+    pub cutaway: bool,
+    /// Set on the events that start a scene or start or end a fight
+    #[serde(default)]
+    pub marker: Option<events::LogMarker>,
+}
+
+impl ClientMessage {
+    /// Whether the message leaves the game as it is, so a spectator may send it
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            ClientMessage::Connect
+                | ClientMessage::SetPreferences { .. }
+                | ClientMessage::MeasureRange { .. }
+                | ClientMessage::RequestSheet { .. }
+                | ClientMessage::RequestEventLog { .. }
+                | ClientMessage::RequestSceneLog { .. }
+                | ClientMessage::RequestNotes { .. }
+                | ClientMessage::RequestHandouts
+                | ClientMessage::RequestRollStats
+                | ClientMessage::RequestRoundHistory
+                | ClientMessage::PriceEncounter { .. }
+        )
+    }
+
+    /// Whether only a GM connection may send the message
+    pub fn is_gm_only(&self) -> bool {
+        matches!(
+            self,
+            ClientMessage::SetMapLocked { .. }
+                | ClientMessage::SetResource { .. }
+                | ClientMessage::RequestRoll { .. }
+                | ClientMessage::PreviewRollRequest { .. }
+                | ClientMessage::RequestGroupRoll { .. }
+                | ClientMessage::SpawnAdversary { .. }
+                | ClientMessage::SaveEncounterPreset { .. }
+                | ClientMessage::DeleteEncounterPreset { .. }
+                | ClientMessage::SaveEncounterFromMap { .. }
+                | ClientMessage::SpawnEncounter { .. }
+                | ClientMessage::SpawnCustomAdversary { .. }
+                | ClientMessage::RemoveAdversary { .. }
+                | ClientMessage::RenumberAdversaries { .. }
+                | ClientMessage::CreateAdversaryGroup { .. }
+                | ClientMessage::DisbandAdversaryGroup { .. }
+                | ClientMessage::MoveAdversaryGroup { .. }
+                | ClientMessage::ActivateAdversaryGroup { .. }
+                | ClientMessage::RemoveAdversaryGroup { .. }
+                | ClientMessage::DeleteCharacter { .. }
+                | ClientMessage::SpawnEnvironment { .. }
+                | ClientMessage::RemoveEnvironment { .. }
+                | ClientMessage::ActivateEnvironmentFeature { .. }
+                | ClientMessage::AttachAura { .. }
+                | ClientMessage::DetachAura { .. }
+                | ClientMessage::StartCombat
+                | ClientMessage::EndCombat
+                | ClientMessage::SetCombatMode { .. }
+                | ClientMessage::AdvanceRound
+                | ClientMessage::EndRound
+                | ClientMessage::SaveNote { .. }
+                | ClientMessage::DeleteNote { .. }
+                | ClientMessage::SaveQuest { .. }
+                | ClientMessage::RemoveQuest { .. }
+                | ClientMessage::SetObjectiveCompleted { .. }
+                | ClientMessage::TickQuestCountdown { .. }
+                | ClientMessage::ShareHandout { .. }
+                | ClientMessage::SetMap { .. }
+                | ClientMessage::SetSceneLayout { .. }
+                | ClientMessage::SetFogEnabled { .. }
+                | ClientMessage::RevealArea { .. }
+                | ClientMessage::HideArea { .. }
+                | ClientMessage::MoveToken { .. }
+                | ClientMessage::EditToken { .. }
+                | ClientMessage::AddDrawing { .. }
+                | ClientMessage::RemoveDrawing { .. }
+                | ClientMessage::ClearDrawings
+                | ClientMessage::AddHazard { .. }
+                | ClientMessage::RemoveHazard { .. }
+                | ClientMessage::SetHazardActive { .. }
+                | ClientMessage::TickHazard { .. }
+                | ClientMessage::AddTrackerToken { .. }
+                | ClientMessage::SpendTrackerToken
+                | ClientMessage::AdvanceTracker { .. }
+                | ClientMessage::SpendFear { .. }
+                | ClientMessage::MarkAdversaryStress { .. }
+                | ClientMessage::AdversaryAttack { .. }
+                | ClientMessage::SetDefenses { .. }
+                | ClientMessage::UpdateTableSettings { .. }
+                | ClientMessage::GrantItem { .. }
+                | ClientMessage::AwardGold { .. }
+                | ClientMessage::CreateNpc { .. }
+                | ClientMessage::ConvertNpc { .. }
+                | ClientMessage::RollbackToCheckpoint { .. }
+                | ClientMessage::ScheduleNextSession { .. }
+                | ClientMessage::ClearNextSession
+                | ClientMessage::StartCutaway { .. }
+                | ClientMessage::EndCutaway
+                | ClientMessage::AssumeControl { .. }
+                | ClientMessage::TransferControl { .. }
+                | ClientMessage::ReleaseControl { .. }
+                | ClientMessage::MarkScene { .. }
+                | ClientMessage::KickConnection { .. }
+                | ClientMessage::MarkLevelUp { .. }
+        )
+    }
+}
+
+impl ServerMessage {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_deserialize() {
+        let json = r#"{"type":"connect"}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::Connect => (),
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_read_only_and_gm_only_messages() {
+        let read_only: ClientMessage = serde_json::from_str(
+            r#"{"type":"measure_range","payload":{"from_id":"a","to_id":"b"}}"#,
+        )
+        .unwrap();
+        assert!(read_only.is_read_only());
+
+        let move_token: ClientMessage =
+            serde_json::from_str(r#"{"type":"move_character","payload":{"x":1.0,"y":2.0}}"#)
+                .unwrap();
+        assert!(!move_token.is_read_only());
+        assert!(!move_token.is_gm_only());
+
+        let start_combat: ClientMessage =
+            serde_json::from_str(r#"{"type":"start_combat"}"#).unwrap();
+        assert!(start_combat.is_gm_only());
+        assert!(!start_combat.is_read_only());
+
+        let role: ConnectionRole = serde_json::from_str(r#""spectator""#).unwrap();
+        assert_eq!(role, ConnectionRole::Spectator);
+    }
+
+    #[test]
+    fn test_select_character_deserialize() {
+        let json = r#"{"type":"select_character","payload":{"character_id":"abc-123"}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::SelectCharacter {
+                character_id,
+                keep_others,
+            } => {
+                assert_eq!(character_id, "abc-123");
+                assert!(!keep_others, "older clients switch characters");
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_move_character_deserialize() {
+        let json = r#"{"type":"move_character","payload":{"x":100.0,"y":200.0}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::MoveCharacter {
+                x, y, path, facing, ..
+            } => {
+                assert_eq!(x, 100.0);
+                assert_eq!(y, 200.0);
+                assert!(path.is_empty());
+                assert_eq!(facing, None);
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_create_character_deserialize() {
+        let json = r#"{"type":"create_character","payload":{"name":"Theron","class":"Warrior","ancestry":"Human","attributes":[2,1,1,0,0,-1]}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::CreateCharacter {
+                name,
+                class,
+                subclass,
+                ancestry,
+                attributes,
+            } => {
+                assert_eq!(subclass, None);
+                assert_eq!(name, "Theron");
+                assert_eq!(class, "Warrior");
+                assert_eq!(ancestry, "Human");
+                assert_eq!(attributes, [2, 1, 1, 0, 0, -1]);
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_roll_duality_deserialize() {
+        let json = r#"{"type":"roll_duality","payload":{"modifier":2,"with_advantage":true}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::RollDuality {
+                modifier,
+                with_advantage,
+                with_disadvantage,
+                character_id,
+            } => {
+                assert_eq!(modifier, 2);
+                assert!(with_advantage);
+                assert!(!with_disadvantage);
+                assert_eq!(character_id, None);
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_server_message_serialize() {
+        let msg = ServerMessage::CharacterSpawned {
+            character_id: "char-123".to_string(),
+            name: "Theron".to_string(),
+            position: Position::new(100.0, 200.0),
+            color: "#3b82f6".to_string(),
+            is_npc: false,
+            token: TokenDetails::default(),
+        };
+
+        let json = msg.to_json();
+        assert!(json.contains("character_spawned"));
+        assert!(json.contains("Theron"));
+    }
+
+    #[test]
+    fn test_character_data_serialize() {
+        let char_data = CharacterData {
+            name: "Theron".to_string(),
+            class: "Warrior".to_string(),
+            subclass: None,
+            ancestry: "Human".to_string(),
+            attributes: AttributesData {
+                agility: 2,
+                strength: 1,
+                finesse: 1,
+                instinct: 0,
+                presence: 0,
+                knowledge: -1,
+            },
+            hp: ResourceData {
+                current: 6,
+                maximum: 6,
+            },
+            stress: 0,
+            stress_max: 6,
+            hope: ResourceData {
+                current: 5,
+                maximum: 5,
+            },
+            evasion: 12,
+            level: 1,
+            experiences: Vec::new(),
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
+            progression: Progression::default(),
+            defenses: Defenses::default(),
+        };
+
+        let json = serde_json::to_string(&char_data).unwrap();
+        assert!(json.contains("Theron"));
+        assert!(json.contains("Warrior"));
+    }
+
+    #[test]
+    fn test_position_random() {
+        let pos = Position::random(800.0, 600.0);
+        assert!(pos.x >= 50.0 && pos.x <= 750.0);
+        assert!(pos.y >= 50.0 && pos.y <= 550.0);
+    }
+
+    #[test]
+    fn test_position_distance() {
+        let a = Position::new(0.0, 0.0);
+        let b = Position::new(30.0, 40.0);
+        assert_eq!(a.distance_to(&b), 50.0);
+    }
+
+    #[test]
+    fn test_character_info_serialize() {
+        let info = CharacterInfo {
+            id: "char-123".to_string(),
+            name: "Theron".to_string(),
+            class: "Warrior".to_string(),
+            ancestry: "Human".to_string(),
+            position: Position::new(100.0, 200.0),
+            color: "#3b82f6".to_string(),
+            is_npc: false,
+            controlled_by_me: true,
+            controlled_by_other: false,
+            player_name: Some("Alice".to_string()),
+            controller_id: None,
+            token: TokenDetails::default(),
+        };
+
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(json.contains("Theron"));
+        assert!(json.contains("controlled_by_me"));
+    }
+
+    #[test]
+    fn test_all_client_messages() {
+        // Test all client message variants can be constructed
+        let messages = vec![
+            ClientMessage::Connect,
+            ClientMessage::SelectCharacter {
+                character_id: "char-1".to_string(),
+                keep_others: false,
+            },
+            ClientMessage::CreateCharacter {
+                name: "Test".to_string(),
+                class: "Warrior".to_string(),
+                subclass: Some("Call of the Brave".to_string()),
+                ancestry: "Human".to_string(),
+                attributes: [2, 1, 1, 0, 0, -1],
+            },
+            ClientMessage::MoveCharacter {
+                x: 100.0,
+                y: 200.0,
+                path: vec![Position::new(50.0, 150.0)],
+                character_id: None,
+                facing: None,
+            },
+            ClientMessage::RollDuality {
+                modifier: 0,
+                with_advantage: false,
+                with_disadvantage: false,
+                character_id: None,
+            },
+            ClientMessage::UpdateResource {
+                resource: "hp".to_string(),
+                amount: -2,
+            },
+            ClientMessage::RequestRoll {
+                target_type: RollTargetType::Specific,
+                target_character_ids: vec!["char-1".to_string()],
+                roll_type: RollType::Action,
+                attribute: Some("agility".to_string()),
+                difficulty: 14,
+                context: "Leap across chasm".to_string(),
+                narrative_stakes: None,
+                situational_modifier: 0,
+                has_advantage: false,
+                has_disadvantage: false,
+                is_combat: false,
+                hidden: false,
+            },
+            ClientMessage::ExecuteRoll {
+                request_id: "req-1".to_string(),
+                spend_hope_for_bonus: false,
+                chosen_experience: None,
+                character_id: None,
+            },
+            ClientMessage::SetDisplayName {
+                name: "Alice".to_string(),
+            },
+            ClientMessage::SetCharacterColor {
+                character_id: "char-1".to_string(),
+                color: "#facc15".to_string(),
+            },
+        ];
+
+        assert_eq!(messages.len(), 10);
+    }
+
+    #[test]
+    fn test_all_server_messages() {
+        // Test all server message variants can be constructed
+        let messages = [
+            ServerMessage::Connected {
+                connection_id: "conn-1".to_string(),
+                session_token: "token-1".to_string(),
+                preferences: UserPreferences::default(),
+                role: ConnectionRole::Player,
+            },
+            ServerMessage::CharactersList { characters: vec![] },
+            ServerMessage::CharacterSelected {
+                character_id: "char-1".to_string(),
+                character: CharacterData {
+                    name: "Test".to_string(),
+                    class: "Warrior".to_string(),
+                    subclass: None,
+                    ancestry: "Human".to_string(),
+                    attributes: AttributesData {
+                        agility: 2,
+                        strength: 1,
+                        finesse: 1,
+                        instinct: 0,
+                        presence: 0,
+                        knowledge: -1,
+                    },
+                    hp: ResourceData {
+                        current: 6,
+                        maximum: 6,
+                    },
+                    stress: 0,
+                    stress_max: 6,
+                    hope: ResourceData {
+                        current: 5,
+                        maximum: 5,
+                    },
+                    evasion: 12,
+                    level: 1,
+                    experiences: Vec::new(),
+                    inventory: Inventory::default(),
+                    equipment: Equipment::default(),
+                    progression: Progression::default(),
+                    defenses: Defenses::default(),
+                },
+            },
+            ServerMessage::CharacterSpawned {
+                character_id: "char-1".to_string(),
+                name: "Test".to_string(),
+                position: Position::new(100.0, 200.0),
+                color: "#3b82f6".to_string(),
+                is_npc: false,
+                token: TokenDetails::default(),
+            },
+            ServerMessage::CharacterRemoved {
+                character_id: "char-1".to_string(),
+                name: "Test".to_string(),
+            },
+            ServerMessage::CharacterMoved {
+                character_id: "char-1".to_string(),
+                position: Position::new(100.0, 200.0),
+                path: vec![],
+                teleported: false,
+                crossed_hazards: vec![],
+                rotation: 0.0,
+            },
+            ServerMessage::Error {
+                code: ErrorCode::Rejected,
+                message: "Test error".to_string(),
+                request_type: Some("move_character".to_string()),
+                request_id: None,
+                connection_id: Some("conn-1".to_string()),
+            },
+        ];
+
+        assert_eq!(messages.len(), 7);
+    }
+
+    // Phase 1: GM-Initiated Dice Rolls Tests
+
+    #[test]
+    fn test_request_roll_deserialize() {
+        let json = r#"{
+            "type":"request_roll",
+            "payload":{
+                "target_type":"specific",
+                "target_character_ids":["char-123"],
+                "roll_type":"action",
+                "attribute":"agility",
+                "difficulty":14,
+                "context":"Leap across chasm",
+                "narrative_stakes":null,
+                "situational_modifier":0,
+                "has_advantage":false,
+                "is_combat":false
+            }
+        }"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::RequestRoll {
+                target_type,
+                difficulty,
+                context,
+                ..
+            } => {
+                assert!(matches!(target_type, RollTargetType::Specific));
+                assert_eq!(difficulty, 14);
+                assert_eq!(context, "Leap across chasm");
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_execute_roll_deserialize() {
+        let json = r#"{
+            "type":"execute_roll",
+            "payload":{
+                "request_id":"req-123",
+                "spend_hope_for_bonus":true,
+                "chosen_experience":"Former acrobat"
+            }
+        }"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::ExecuteRoll {
+                request_id,
+                spend_hope_for_bonus,
+                chosen_experience,
+                ..
+            } => {
+                assert_eq!(request_id, "req-123");
+                assert!(spend_hope_for_bonus);
+                assert_eq!(chosen_experience, Some("Former acrobat".to_string()));
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_success_type_serialization() {
+        let success = SuccessType::CriticalSuccess;
+        let json = serde_json::to_string(&success).unwrap();
+        assert_eq!(json, r#""critical_success""#);
+
+        let loaded: SuccessType = serde_json::from_str(&json).unwrap();
+        assert_eq!(success, loaded);
+    }
+
+    #[test]
+    fn test_success_type_label() {
+        assert_eq!(
+            SuccessType::SuccessWithFear.label(false),
+            "SUCCESS WITH FEAR"
+        );
+        assert_eq!(SuccessType::SuccessWithFear.label(true), "SUCCESS");
+        assert_eq!(SuccessType::CriticalSuccess.label(true), "CRITICAL SUCCESS");
+    }
+
+    #[test]
+    fn test_controlling_die_serialization() {
+        let die = ControllingDie::Hope;
+        let json = serde_json::to_string(&die).unwrap();
+        assert_eq!(json, r#""hope""#);
+
+        let loaded: ControllingDie = serde_json::from_str(&json).unwrap();
+        assert_eq!(die, loaded);
+    }
+
+    #[test]
+    fn test_roll_type_serialization() {
+        let roll_type = RollType::Action;
+        let json = serde_json::to_string(&roll_type).unwrap();
+        assert_eq!(json, r#""action""#);
+
+        let loaded: RollType = serde_json::from_str(&json).unwrap();
+        assert!(matches!(loaded, RollType::Action));
+
+        let loaded: RollType = serde_json::from_str(r#""reaction""#).unwrap();
+        assert!(loaded.is_reaction());
+        assert!(RollType::Save.is_reaction());
+        assert!(!RollType::Attack.is_reaction());
+    }
+
+    #[test]
+    fn test_transfer_item_deserialize() {
+        let json = r#"{"type":"transfer_item","payload":{"item_id":"item-1","target_character_id":"char-2","quantity":2}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::TransferItem {
+                item_id,
+                target_character_id,
+                quantity,
+            } => {
+                assert_eq!(item_id, "item-1");
+                assert_eq!(target_character_id, "char-2");
+                assert_eq!(quantity, 2);
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_choose_level_up_deserialize() {
+        let json = r#"{"type":"choose_level_up","payload":{"advancements":[{"kind":"trait_boost","traits":["agility","finesse"]},{"kind":"hit_point_slot"}],"new_experience":"Sellsword"}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::ChooseLevelUp {
+                advancements,
+                new_experience,
+            } => {
+                assert_eq!(advancements.len(), 2);
+                assert_eq!(advancements[1], Advancement::HitPointSlot);
+                assert_eq!(new_experience, Some("Sellsword".to_string()));
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_add_experience_modifier_optional() {
+        let json = r#"{"type":"add_experience","payload":{"name":"Former acrobat"}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::AddExperience { name, modifier } => {
+                assert_eq!(name, "Former acrobat");
+                assert_eq!(modifier, None);
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_roll_damage_without_dice_or_armor() {
+        let json =
+            r#"{"type":"roll_damage","payload":{"attacker_id":"char-1","target_id":"adv-1"}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+
+        match msg {
+            ClientMessage::RollDamage {
+                damage_dice, armor, ..
+            } => {
+                assert!(damage_dice.is_none());
+                assert!(armor.is_none());
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+}
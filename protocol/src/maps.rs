@@ -0,0 +1,324 @@
+//! Battle maps: background images the GM uploads for each scene, and the
+//! scene's size and grid
+
+use serde::{Deserialize, Serialize};
+
+use crate::equipment::WeaponRange;
+use crate::tokens::TokenSize;
+use crate::Position;
+
+/// Longest scene name accepted, in characters
+pub const MAX_SCENE_NAME_LENGTH: usize = 80;
+
+/// Largest map image accepted, in bytes
+pub const MAX_MAP_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+
+/// Size of a scene nobody has set up, in map units
+pub const DEFAULT_SCENE_WIDTH: f32 = 800.0;
+pub const DEFAULT_SCENE_HEIGHT: f32 = 600.0;
+
+/// Largest scene width or height accepted
+pub const MAX_SCENE_SIZE: f32 = 4000.0;
+
+/// Smallest grid cell accepted
+pub const MIN_GRID_CELL_SIZE: f32 = 10.0;
+
+/// Map units to the foot in a scene nobody has set up (a 50-unit cell is 5 feet)
+pub const DEFAULT_UNITS_PER_FOOT: f32 = 10.0;
+
+fn default_units_per_foot() -> f32 {
+    DEFAULT_UNITS_PER_FOOT
+}
+
+/// Shape of the grid's cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GridKind {
+    Square,
+    /// Pointy-topped hexes in rows, odd rows shifted half a cell right
+    Hex,
+}
+
+/// A grid tokens snap to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GridSettings {
+    pub kind: GridKind,
+    /// Width of a cell (flat side to flat side for hexes)
+    pub cell_size: f32,
+    /// Where the first cell starts, to line the grid up with a map image
+    #[serde(default)]
+    pub offset_x: f32,
+    #[serde(default)]
+    pub offset_y: f32,
+}
+
+/// A scene's size and grid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SceneLayout {
+    pub width: f32,
+    pub height: f32,
+    /// Tokens move freely without one
+    #[serde(default)]
+    pub grid: Option<GridSettings>,
+    /// The scene's scale, for range bands
+    #[serde(default = "default_units_per_foot")]
+    pub units_per_foot: f32,
+}
+
+impl Default for SceneLayout {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_SCENE_WIDTH,
+            height: DEFAULT_SCENE_HEIGHT,
+            grid: None,
+            units_per_foot: DEFAULT_UNITS_PER_FOOT,
+        }
+    }
+}
+
+impl SceneLayout {
+    pub fn validate(&self) -> Result<(), String> {
+        let size_ok = |size: f32| size.is_finite() && (1.0..=MAX_SCENE_SIZE).contains(&size);
+        if !size_ok(self.width) || !size_ok(self.height) {
+            return Err(format!(
+                "Scene must be between 1 and {} units on each side",
+                MAX_SCENE_SIZE
+            ));
+        }
+        if !self.units_per_foot.is_finite() || self.units_per_foot <= 0.0 {
+            return Err("Scene scale must be above zero".to_string());
+        }
+        if let Some(grid) = &self.grid {
+            if !grid.cell_size.is_finite()
+                || grid.cell_size < MIN_GRID_CELL_SIZE
+                || grid.cell_size > self.width.min(self.height)
+            {
+                return Err(format!(
+                    "Grid cells must be at least {} units and fit in the scene",
+                    MIN_GRID_CELL_SIZE
+                ));
+            }
+            if !grid.offset_x.is_finite() || !grid.offset_y.is_finite() {
+                return Err("Grid offset must be a number".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a point is inside the scene
+    pub fn contains(&self, position: &Position) -> bool {
+        (0.0..=self.width).contains(&position.x) && (0.0..=self.height).contains(&position.y)
+    }
+
+    /// The centre of the grid cell a point is in (the point itself without a grid)
+    pub fn snap(&self, position: Position) -> Position {
+        self.snap_token(position, TokenSize::Normal)
+    }
+
+    /// Where a token of the given size centred near a point settles
+    ///
+    /// On a square grid a token an even number of cells across is centred
+    /// on the nearest grid corner, so it covers whole cells. Hex grids
+    /// centre every token on a hex.
+    pub fn snap_token(&self, position: Position, token_size: TokenSize) -> Position {
+        let Some(grid) = &self.grid else {
+            return position;
+        };
+        let size = grid.cell_size;
+        let (x, y) = (position.x - grid.offset_x, position.y - grid.offset_y);
+
+        let (x, y) = match grid.kind {
+            GridKind::Square if token_size.cells().is_multiple_of(2) => {
+                ((x / size).round() * size, (y / size).round() * size)
+            }
+            GridKind::Square => (
+                ((x / size).floor() + 0.5) * size,
+                ((y / size).floor() + 0.5) * size,
+            ),
+            GridKind::Hex => {
+                // The nearest centre is the hex the point is in; check the
+                // rows either side of the one it's closest to
+                let radius = size / 3f32.sqrt();
+                let row_height = 1.5 * radius;
+                let row = ((y - radius) / row_height).round() as i64;
+                (row - 1..=row + 1)
+                    .map(|row| {
+                        let shift = if row.rem_euclid(2) == 1 {
+                            size / 2.0
+                        } else {
+                            0.0
+                        };
+                        let column = ((x - shift - size / 2.0) / size).round();
+                        (
+                            column * size + size / 2.0 + shift,
+                            row as f32 * row_height + radius,
+                        )
+                    })
+                    .min_by(|a, b| {
+                        let distance = |(cx, cy): &(f32, f32)| (cx - x).powi(2) + (cy - y).powi(2);
+                        distance(a).total_cmp(&distance(b))
+                    })
+                    .expect("three rows checked")
+            }
+        };
+        Position::new(x + grid.offset_x, y + grid.offset_y)
+    }
+
+    /// Distance between two points in feet, at the scene's scale
+    pub fn feet_between(&self, a: &Position, b: &Position) -> f32 {
+        a.distance_to(b) / self.units_per_foot
+    }
+
+    /// The range band one point is in from another, `None` past Very Far
+    pub fn range_between(&self, a: &Position, b: &Position) -> Option<WeaponRange> {
+        WeaponRange::for_distance(self.feet_between(a, b))
+    }
+
+    /// Snap a point to the grid, failing if it ends up off the scene
+    pub fn place(&self, position: Position) -> Result<Position, String> {
+        self.place_token(position, TokenSize::Normal)
+    }
+
+    /// Snap a token of the given size to the grid, failing if its centre
+    /// ends up off the scene
+    pub fn place_token(
+        &self,
+        position: Position,
+        token_size: TokenSize,
+    ) -> Result<Position, String> {
+        let snapped = self.snap_token(position, token_size);
+        if !self.contains(&snapped) {
+            return Err("Position is outside the scene".to_string());
+        }
+        Ok(snapped)
+    }
+}
+
+/// An uploaded map; the image is served from `url`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MapInfo {
+    pub id: String,
+    /// The scene it's for, e.g. "The Sunken Vault"
+    pub scene: String,
+    pub url: String,
+    /// RFC 3339
+    pub uploaded_at: String,
+    /// The scene's size and grid, used whenever this map is shown
+    #[serde(default)]
+    pub layout: SceneLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(kind: GridKind) -> SceneLayout {
+        SceneLayout {
+            grid: Some(GridSettings {
+                kind,
+                cell_size: 50.0,
+                offset_x: 10.0,
+                offset_y: 0.0,
+            }),
+            ..SceneLayout::default()
+        }
+    }
+
+    #[test]
+    fn test_square_grid_snaps_to_cell_centres() {
+        let layout = grid(GridKind::Square);
+        assert_eq!(
+            layout.snap(Position::new(12.0, 49.0)),
+            Position::new(35.0, 25.0)
+        );
+        assert_eq!(
+            layout.snap(Position::new(61.0, 51.0)),
+            Position::new(85.0, 75.0)
+        );
+
+        // Two cells across: centred on the nearest grid corner
+        assert_eq!(
+            layout.snap_token(Position::new(12.0, 49.0), TokenSize::Large),
+            Position::new(10.0, 50.0)
+        );
+        assert_eq!(
+            layout.snap_token(Position::new(12.0, 49.0), TokenSize::Huge),
+            Position::new(35.0, 25.0)
+        );
+
+        let free = SceneLayout::default();
+        assert_eq!(
+            free.snap(Position::new(12.0, 49.0)),
+            Position::new(12.0, 49.0)
+        );
+        assert!(free.place(Position::new(801.0, 10.0)).is_err());
+        // The partial cell along the left edge snaps off the scene
+        assert!(layout.place(Position::new(5.0, 10.0)).is_err());
+    }
+
+    #[test]
+    fn test_hex_grid_snaps_to_nearest_centre() {
+        let layout = grid(GridKind::Hex);
+        let radius = 50.0 / 3f32.sqrt();
+
+        // Row 0 centres sit at x = 35, 85, ...; row 1 is shifted by 25
+        assert_eq!(
+            layout.snap(Position::new(40.0, radius + 3.0)),
+            Position::new(35.0, radius)
+        );
+        let snapped = layout.snap(Position::new(58.0, 2.5 * radius));
+        assert!((snapped.x - 60.0).abs() < 0.01);
+        assert!((snapped.y - 2.5 * radius).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_range_between() {
+        let layout = SceneLayout::default();
+        let origin = Position::new(0.0, 0.0);
+        assert_eq!(
+            layout.range_between(&origin, &Position::new(50.0, 0.0)),
+            Some(WeaponRange::Melee)
+        );
+        assert_eq!(
+            layout.range_between(&origin, &Position::new(150.0, 200.0)),
+            Some(WeaponRange::Close)
+        );
+
+        let zoomed_out = SceneLayout {
+            units_per_foot: 1.0,
+            ..SceneLayout::default()
+        };
+        assert_eq!(
+            zoomed_out.range_between(&origin, &Position::new(150.0, 200.0)),
+            Some(WeaponRange::VeryFar)
+        );
+        assert_eq!(
+            zoomed_out.range_between(&origin, &Position::new(800.0, 600.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_layout() {
+        assert!(SceneLayout::default().validate().is_ok());
+        assert!(grid(GridKind::Hex).validate().is_ok());
+
+        let mut layout = grid(GridKind::Square);
+        layout.grid.as_mut().unwrap().cell_size = 2.0;
+        assert!(layout.validate().is_err());
+        layout.grid = None;
+        layout.width = MAX_SCENE_SIZE + 1.0;
+        assert!(layout.validate().is_err());
+        layout.width = DEFAULT_SCENE_WIDTH;
+        layout.units_per_foot = 0.0;
+        assert!(layout.validate().is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Timed modifiers: buffs and debuffs on a character or adversary
+//!
+//! A modifier adds to one number, e.g. +1 to attack rolls or -2 to Evasion,
+//! until it runs out: at the character's next rest, when the scene changes
+//! (a new map or the end of combat), or when it's removed. Modifiers on the
+//! same token add up, and stack with auras.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest bonus or penalty one modifier can give
+pub const MAX_MODIFIER: i8 = 10;
+
+/// What a modifier adds to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ModifierStat {
+    /// Every action roll, attacks included
+    Roll,
+    /// Attack rolls only
+    Attack,
+    Evasion,
+}
+
+impl ModifierStat {
+    pub fn name(self) -> &'static str {
+        match self {
+            ModifierStat::Roll => "rolls",
+            ModifierStat::Attack => "attack rolls",
+            ModifierStat::Evasion => "Evasion",
+        }
+    }
+}
+
+/// When a modifier runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ModifierDuration {
+    /// The character's next rest (adversaries don't rest)
+    UntilRest,
+    /// A new map or the end of combat
+    Scene,
+    /// Until it's removed
+    UntilRemoved,
+}
+
+impl ModifierDuration {
+    pub fn describe(self) -> &'static str {
+        match self {
+            ModifierDuration::UntilRest => "until the next rest",
+            ModifierDuration::Scene => "for the scene",
+            ModifierDuration::UntilRemoved => "until removed",
+        }
+    }
+}
+
+/// A buff or debuff, as the GM or a player adds it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Modifier {
+    /// e.g. "Bless" or "Dazed"
+    pub name: String,
+    pub stat: ModifierStat,
+    pub value: i8,
+    pub duration: ModifierDuration,
+}
+
+impl Modifier {
+    /// Check the modifier can be added
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Modifier name cannot be empty".to_string());
+        }
+        if self.value == 0 {
+            return Err("Modifier has no effect".to_string());
+        }
+        if self.value.unsigned_abs() > MAX_MODIFIER as u8 {
+            return Err(format!("Modifiers go up to ±{}", MAX_MODIFIER));
+        }
+        Ok(())
+    }
+
+    /// Short summary, e.g. "+1 attack rolls until the next rest"
+    pub fn describe(&self) -> String {
+        format!(
+            "{:+} {} {}",
+            self.value,
+            self.stat.name(),
+            self.duration.describe()
+        )
+    }
+}
+
+/// A modifier on a token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TimedModifier {
+    pub id: String,
+    /// The character or adversary it's on
+    pub target_id: String,
+    pub target_name: String,
+    pub modifier: Modifier,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dazed() -> Modifier {
+        Modifier {
+            name: "Dazed".to_string(),
+            stat: ModifierStat::Evasion,
+            value: -2,
+            duration: ModifierDuration::Scene,
+        }
+    }
+
+    #[test]
+    fn test_modifier_validate_and_describe() {
+        assert!(dazed().validate().is_ok());
+        assert_eq!(dazed().describe(), "-2 Evasion for the scene");
+
+        let mut modifier = dazed();
+        modifier.value = 0;
+        assert!(modifier.validate().is_err());
+        modifier.value = -11;
+        assert!(modifier.validate().is_err());
+        modifier.value = 1;
+        modifier.name = " ".to_string();
+        assert!(modifier.validate().is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Narrative flavor for combat events
+//!
+//! In flavorful mode the server phrases attack and damage events from
+//! templates, picking one at random each time so the log doesn't repeat
+//! itself. Templates use `{attacker}`, `{target}` and `{damage}`
+//! placeholders.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How combat events are phrased
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NarrativeMode {
+    /// Just the numbers
+    Plain,
+    /// Randomly chosen flavor text from the templates
+    #[default]
+    Flavorful,
+}
+
+/// A combat moment that has flavor text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CombatBeat {
+    Hit,
+    Miss,
+    Critical,
+    TakenOut,
+}
+
+/// Phrasings for each combat beat
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NarrativeTemplates {
+    pub hit: Vec<String>,
+    pub miss: Vec<String>,
+    pub critical: Vec<String>,
+    pub taken_out: Vec<String>,
+}
+
+impl NarrativeTemplates {
+    /// Check every beat has at least one usable phrasing
+    pub fn validate(&self) -> Result<(), String> {
+        for (beat, templates) in [
+            ("hit", &self.hit),
+            ("miss", &self.miss),
+            ("critical", &self.critical),
+            ("taken_out", &self.taken_out),
+        ] {
+            if templates.iter().all(|t| t.trim().is_empty()) {
+                return Err(format!("No narrative templates for {}", beat));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn for_beat(&self, beat: CombatBeat) -> &[String] {
+        match beat {
+            CombatBeat::Hit => &self.hit,
+            CombatBeat::Miss => &self.miss,
+            CombatBeat::Critical => &self.critical,
+            CombatBeat::TakenOut => &self.taken_out,
+        }
+    }
+
+    /// Phrase a beat with a randomly chosen template
+    pub fn render<R: Rng + ?Sized>(
+        &self,
+        beat: CombatBeat,
+        attacker: &str,
+        target: &str,
+        damage: Option<u16>,
+        rng: &mut R,
+    ) -> Option<String> {
+        let template = self.for_beat(beat).choose(rng)?;
+        let damage = damage.map(|d| d.to_string()).unwrap_or_default();
+        Some(
+            template
+                .replace("{attacker}", attacker)
+                .replace("{target}", target)
+                .replace("{damage}", &damage),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let templates = NarrativeTemplates {
+            hit: vec!["{attacker} hits {target}".to_string()],
+            miss: vec!["{target} dodges".to_string()],
+            critical: vec!["Crit!".to_string()],
+            taken_out: vec!["{target} falls after {damage} damage".to_string()],
+        };
+        assert!(templates.validate().is_ok());
+
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            templates.render(CombatBeat::Hit, "Theron", "the ogre", None, &mut rng),
+            Some("Theron hits the ogre".to_string())
+        );
+        assert_eq!(
+            templates.render(
+                CombatBeat::TakenOut,
+                "Theron",
+                "the ogre",
+                Some(7),
+                &mut rng
+            ),
+            Some("the ogre falls after 7 damage".to_string())
+        );
+
+        let empty = NarrativeTemplates {
+            critical: vec![" ".to_string()],
+            ..templates
+        };
+        assert!(empty.validate().is_err());
+    }
+}
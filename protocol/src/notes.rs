@@ -0,0 +1,145 @@
+//! Session notes: the GM's journal and notes shared with the table
+//!
+//! Notes are kept with the campaign and saved with it, so session prep and
+//! what was jotted down at the table live side by side. A note is either
+//! the GM's alone or shared with everyone; only the GM writes them.
+
+use serde::{Deserialize, Serialize};
+
+/// Most notes in one campaign
+pub const MAX_NOTES: usize = 500;
+
+/// Longest note title accepted, in characters
+pub const MAX_NOTE_TITLE_LENGTH: usize = 100;
+
+/// Longest note accepted, in characters
+pub const MAX_NOTE_BODY_LENGTH: usize = 20_000;
+
+/// Who can read a note
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NoteVisibility {
+    /// The GM alone
+    #[default]
+    Gm,
+    /// Everyone at the table
+    Table,
+}
+
+/// A note as written or edited, before it's kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NoteDraft {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub visibility: NoteVisibility,
+}
+
+impl NoteDraft {
+    /// The draft trimmed, if it fits
+    pub fn validate(&self) -> Result<Self, String> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err("Give the note a title".to_string());
+        }
+        if title.chars().count() > MAX_NOTE_TITLE_LENGTH {
+            return Err(format!(
+                "Note titles go up to {} characters",
+                MAX_NOTE_TITLE_LENGTH
+            ));
+        }
+        if title.chars().any(char::is_control) {
+            return Err("Note title contains invalid characters".to_string());
+        }
+        let body = self.body.trim();
+        if body.chars().count() > MAX_NOTE_BODY_LENGTH {
+            return Err(format!(
+                "Notes go up to {} characters",
+                MAX_NOTE_BODY_LENGTH
+            ));
+        }
+        Ok(Self {
+            title: title.to_string(),
+            body: body.to_string(),
+            visibility: self.visibility,
+        })
+    }
+}
+
+/// A kept note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Note {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub visibility: NoteVisibility,
+    /// RFC 3339
+    pub created_at: String,
+    /// RFC 3339
+    pub updated_at: String,
+}
+
+impl Note {
+    pub fn is_shared(&self) -> bool {
+        self.visibility == NoteVisibility::Table
+    }
+
+    /// Whether every word of `search` is in the title or body, ignoring
+    /// case; a blank search matches every note
+    pub fn matches(&self, search: &str) -> bool {
+        let title = self.title.to_lowercase();
+        let body = self.body.to_lowercase();
+        search
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| title.contains(word) || body.contains(word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drafts_and_search() {
+        let draft = NoteDraft {
+            title: "  Thornwood ambush ".to_string(),
+            body: " The bandits answer to Captain Vess.\n".to_string(),
+            visibility: NoteVisibility::Gm,
+        };
+        let draft = draft.validate().unwrap();
+        assert_eq!(draft.title, "Thornwood ambush");
+        assert_eq!(draft.body, "The bandits answer to Captain Vess.");
+
+        let blank = NoteDraft {
+            title: " ".to_string(),
+            ..draft.clone()
+        };
+        assert!(blank.validate().is_err());
+        let long = NoteDraft {
+            body: "x".repeat(MAX_NOTE_BODY_LENGTH + 1),
+            ..draft.clone()
+        };
+        assert!(long.validate().is_err());
+
+        let note = Note {
+            id: "n".to_string(),
+            title: draft.title,
+            body: draft.body,
+            visibility: draft.visibility,
+            created_at: "2026-10-16T19:00:00+00:00".to_string(),
+            updated_at: "2026-10-16T19:00:00+00:00".to_string(),
+        };
+        assert!(note.matches("vess THORNWOOD"));
+        assert!(note.matches("  "));
+        assert!(!note.matches("vess dragon"));
+        assert!(!note.is_shared());
+    }
+}
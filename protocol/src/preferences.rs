@@ -0,0 +1,92 @@
+//! Per-player preferences
+//!
+//! Stored on the server under the player's session token, so they follow
+//! the player across refreshes and devices without client-side storage.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest locale or theme name accepted
+pub const MAX_PREFERENCE_LEN: usize = 32;
+
+/// A player's display and notification preferences
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct UserPreferences {
+    /// Skip animations and heavy rendering on low-end devices
+    pub lite_mode: bool,
+    /// BCP 47 language tag, e.g. "en-US"
+    pub locale: Option<String>,
+    pub notifications: NotificationSettings,
+    /// Color theme hint, e.g. "dark", "light" or "high_contrast"
+    pub theme: Option<String>,
+}
+
+impl UserPreferences {
+    /// Check the free-text fields are sensible
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, value) in [("Locale", &self.locale), ("Theme", &self.theme)] {
+            if let Some(value) = value {
+                if value.is_empty() || value.len() > MAX_PREFERENCE_LEN {
+                    return Err(format!(
+                        "{} must be 1-{} characters",
+                        field, MAX_PREFERENCE_LEN
+                    ));
+                }
+                if !value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(format!(
+                        "{} may only contain letters, digits, - and _",
+                        field
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which alerts a player wants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Alert when the GM requests a roll
+    pub roll_requests: bool,
+    pub sounds: bool,
+    pub vibration: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            roll_requests: true,
+            sounds: true,
+            vibration: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_defaults_and_validation() {
+        let prefs: UserPreferences = serde_json::from_str(r#"{"lite_mode":true}"#).unwrap();
+        assert!(prefs.lite_mode);
+        assert!(prefs.notifications.roll_requests);
+        assert!(prefs.validate().is_ok());
+
+        let prefs = UserPreferences {
+            locale: Some("en-US".to_string()),
+            theme: Some("<script>".to_string()),
+            ..Default::default()
+        };
+        assert!(prefs.validate().is_err());
+    }
+}
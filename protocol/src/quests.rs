@@ -0,0 +1,296 @@
+//! The quest board: what the party is after and how close they are
+//!
+//! The GM writes each quest with its objectives, the characters it
+//! concerns and any countdowns. As in the SRD, a progress countdown ticks
+//! down as the party gets closer to their goal, and a consequence countdown
+//! ticks down toward something going wrong; either is done at zero. The
+//! whole board is shown to everyone.
+
+use serde::{Deserialize, Serialize};
+
+/// Most quests on the board
+pub const MAX_QUESTS: usize = 100;
+
+/// Longest quest title accepted, in characters
+pub const MAX_QUEST_TITLE_LENGTH: usize = 100;
+
+/// Longest quest description accepted, in characters
+pub const MAX_QUEST_DESCRIPTION_LENGTH: usize = 5_000;
+
+/// Longest objective or countdown label accepted, in characters
+pub const MAX_OBJECTIVE_LENGTH: usize = 200;
+
+/// Most objectives, and most countdowns, in one quest
+pub const MAX_OBJECTIVES: usize = 20;
+
+/// Longest countdown
+pub const MAX_COUNTDOWN: u8 = 12;
+
+/// Where a quest stands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum QuestStatus {
+    #[default]
+    Active,
+    Completed,
+    Failed,
+}
+
+impl QuestStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuestStatus::Active => "Active",
+            QuestStatus::Completed => "Completed",
+            QuestStatus::Failed => "Failed",
+        }
+    }
+}
+
+/// Whether a countdown runs toward the party's goal or against them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CountdownKind {
+    #[default]
+    Progress,
+    Consequence,
+}
+
+/// Something to get done along the way
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Objective {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+/// A countdown on a quest, e.g. "Reach the pass before the snows" from 6
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct QuestCountdown {
+    pub id: String,
+    pub label: String,
+    #[serde(default)]
+    pub kind: CountdownKind,
+    /// Where it starts, 1 to [`MAX_COUNTDOWN`]
+    pub length: u8,
+    pub remaining: u8,
+}
+
+impl QuestCountdown {
+    /// Tick down by `ticks`, or back up for a negative count, within
+    /// 0..=length
+    pub fn tick(&mut self, ticks: i8) {
+        let remaining = self.remaining as i16 - ticks as i16;
+        self.remaining = remaining.clamp(0, self.length as i16) as u8;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// A quest on the board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Quest {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub status: QuestStatus,
+    /// The characters it concerns; empty for the whole party
+    #[serde(default)]
+    pub character_ids: Vec<String>,
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub countdowns: Vec<QuestCountdown>,
+}
+
+impl Quest {
+    /// Objectives done, out of how many
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self.objectives.iter().filter(|o| o.completed).count();
+        (done, self.objectives.len())
+    }
+}
+
+/// A countdown as the GM sets it up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CountdownDraft {
+    pub label: String,
+    #[serde(default)]
+    pub kind: CountdownKind,
+    pub length: u8,
+}
+
+/// A quest as written or rewritten, before it goes on the board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct QuestDraft {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub status: QuestStatus,
+    #[serde(default)]
+    pub character_ids: Vec<String>,
+    /// Each objective's text, in order
+    #[serde(default)]
+    pub objectives: Vec<String>,
+    #[serde(default)]
+    pub countdowns: Vec<CountdownDraft>,
+}
+
+impl QuestDraft {
+    /// The draft trimmed, if it fits (character ids aren't checked here)
+    pub fn validate(&self) -> Result<Self, String> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err("Give the quest a title".to_string());
+        }
+        if title.chars().count() > MAX_QUEST_TITLE_LENGTH {
+            return Err(format!(
+                "Quest titles go up to {} characters",
+                MAX_QUEST_TITLE_LENGTH
+            ));
+        }
+        let description = self.description.trim();
+        if description.chars().count() > MAX_QUEST_DESCRIPTION_LENGTH {
+            return Err(format!(
+                "Quest descriptions go up to {} characters",
+                MAX_QUEST_DESCRIPTION_LENGTH
+            ));
+        }
+        if self.objectives.len() > MAX_OBJECTIVES || self.countdowns.len() > MAX_OBJECTIVES {
+            return Err(format!(
+                "A quest has up to {} objectives and as many countdowns",
+                MAX_OBJECTIVES
+            ));
+        }
+
+        let objectives = self
+            .objectives
+            .iter()
+            .map(|text| check_label(text, "Objectives"))
+            .filter(|text| !matches!(text, Ok(t) if t.is_empty()))
+            .collect::<Result<_, _>>()?;
+        let countdowns = self
+            .countdowns
+            .iter()
+            .map(|countdown| {
+                if countdown.length == 0 || countdown.length > MAX_COUNTDOWN {
+                    return Err(format!("Countdowns run from 1 to {}", MAX_COUNTDOWN));
+                }
+                let label = check_label(&countdown.label, "Countdown labels")?;
+                if label.is_empty() {
+                    return Err("Give the countdown a label".to_string());
+                }
+                Ok(CountdownDraft {
+                    label,
+                    ..countdown.clone()
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut character_ids: Vec<String> = Vec::new();
+        for id in &self.character_ids {
+            if !character_ids.contains(id) {
+                character_ids.push(id.clone());
+            }
+        }
+        Ok(Self {
+            title: title.to_string(),
+            description: description.to_string(),
+            status: self.status,
+            character_ids,
+            objectives,
+            countdowns,
+        })
+    }
+}
+
+fn check_label(text: &str, what: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.chars().count() > MAX_OBJECTIVE_LENGTH {
+        return Err(format!(
+            "{} go up to {} characters",
+            what, MAX_OBJECTIVE_LENGTH
+        ));
+    }
+    if text.chars().any(char::is_control) {
+        return Err(format!("{} can't hold control characters", what));
+    }
+    Ok(text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft() -> QuestDraft {
+        QuestDraft {
+            title: " The Missing Caravan ".to_string(),
+            description: "Find the merchants lost on the Thornwood road".to_string(),
+            status: QuestStatus::Active,
+            character_ids: Vec::new(),
+            objectives: vec![
+                "Question the innkeeper".to_string(),
+                "  ".to_string(),
+                "Track the wagons".to_string(),
+            ],
+            countdowns: vec![CountdownDraft {
+                label: "The bandits move on".to_string(),
+                kind: CountdownKind::Consequence,
+                length: 4,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_drafts_are_checked() {
+        let checked = draft().validate().unwrap();
+        assert_eq!(checked.title, "The Missing Caravan");
+        assert_eq!(checked.objectives.len(), 2, "blank objectives are dropped");
+
+        let mut untitled = draft();
+        untitled.title = " ".to_string();
+        assert!(untitled.validate().is_err());
+        let mut endless = draft();
+        endless.countdowns[0].length = MAX_COUNTDOWN + 1;
+        assert!(endless.validate().is_err());
+        let mut unlabelled = draft();
+        unlabelled.countdowns[0].label = String::new();
+        assert!(unlabelled.validate().is_err());
+    }
+
+    #[test]
+    fn test_countdowns_tick_within_their_length() {
+        let mut countdown = QuestCountdown {
+            id: "c".to_string(),
+            label: "The bandits move on".to_string(),
+            kind: CountdownKind::Consequence,
+            length: 4,
+            remaining: 4,
+        };
+        countdown.tick(3);
+        assert_eq!(countdown.remaining, 1);
+        countdown.tick(5);
+        assert!(countdown.is_done());
+        countdown.tick(-9);
+        assert_eq!(countdown.remaining, 4);
+    }
+}
@@ -0,0 +1,93 @@
+//! Round by round: what happened in each round of a fight
+//!
+//! While a fight runs, every roll and combat action is noted against the
+//! round it happened in, with the character or adversary it was by or on.
+//! The GM moves the fight on with `advance_round`, or closes a round out
+//! with `end_round`, which also refills the Action Tracker.
+
+use serde::{Deserialize, Serialize};
+
+/// Most actions noted in one round; later ones are left out
+pub const MAX_ROUND_ACTIONS: usize = 200;
+
+/// One thing that happened in a round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RoundAction {
+    /// "HH:MM:SS"
+    pub timestamp: String,
+    /// The character or adversary it was by or on
+    pub who: Option<String>,
+    pub action: String,
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+/// Everything noted in one round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RoundHistory {
+    pub round: u32,
+    pub actions: Vec<RoundAction>,
+}
+
+impl RoundHistory {
+    pub fn new(round: u32) -> Self {
+        Self {
+            round,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Note an action, unless the round already has `MAX_ROUND_ACTIONS`
+    pub fn record(&mut self, action: RoundAction) -> bool {
+        if self.actions.len() >= MAX_ROUND_ACTIONS {
+            return false;
+        }
+        self.actions.push(action);
+        true
+    }
+
+    /// Everyone named in the round, in the order they first came up
+    pub fn who_acted(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for name in self.actions.iter().filter_map(|a| a.who.as_ref()) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(who: Option<&str>, action: &str) -> RoundAction {
+        RoundAction {
+            timestamp: "12:00:00".to_string(),
+            who: who.map(str::to_string),
+            action: action.to_string(),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_round_history() {
+        let mut round = RoundHistory::new(2);
+        round.record(action(Some("Mira"), "Mira attacks"));
+        round.record(action(None, "The GM spends a Fear"));
+        round.record(action(Some("Goblin #1"), "Goblin #1 attacks"));
+        round.record(action(Some("Mira"), "Mira took 3 damage"));
+        assert_eq!(round.who_acted(), vec!["Mira", "Goblin #1"]);
+
+        for _ in round.actions.len()..MAX_ROUND_ACTIONS {
+            assert!(round.record(action(None, "...")));
+        }
+        assert!(!round.record(action(None, "one too many")));
+        assert_eq!(round.actions.len(), MAX_ROUND_ACTIONS);
+    }
+}
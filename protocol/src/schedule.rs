@@ -0,0 +1,18 @@
+//! Campaign logistics: when the table next meets
+
+use serde::{Deserialize, Serialize};
+
+/// Longest notes accepted for a scheduled session
+pub const MAX_SESSION_NOTES_LENGTH: usize = 500;
+
+/// The next scheduled session, as shown to clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NextSession {
+    /// RFC 3339 in the GM's offset, e.g. "2026-11-07T19:00:00-05:00"
+    pub scheduled_at: String,
+    /// What to bring, where to meet, who's hosting
+    #[serde(default)]
+    pub notes: String,
+}
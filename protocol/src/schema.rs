@@ -0,0 +1,59 @@
+//! JSON Schemas of the WebSocket protocol (the `schema` feature)
+//!
+//! One schema for each direction: [`ClientMessage`] for what clients send,
+//! [`ServerMessage`] for what they receive. Every type a message carries is
+//! in the schema's `definitions`. To write them out:
+//!
+//! ```bash
+//! cargo run -p daggerheart-vtt-protocol --features schema --example export-schema -- schemas/
+//! ```
+
+use std::path::Path;
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::{ClientMessage, ServerMessage};
+
+/// File name and schema of each direction of the protocol
+pub fn protocol_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("client_message.schema.json", schema_for!(ClientMessage)),
+        ("server_message.schema.json", schema_for!(ServerMessage)),
+    ]
+}
+
+/// Write every schema into `dir`, returning the files written
+pub fn write_schemas(dir: &Path) -> Result<Vec<String>, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let mut written = Vec::new();
+    for (name, schema) in protocol_schemas() {
+        let path = dir.join(name);
+        let json = serde_json::to_string_pretty(&schema)
+            .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+        std::fs::write(&path, json + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path.display().to_string());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schemas_cover_every_message() {
+        let schemas = protocol_schemas();
+        let client = serde_json::to_string(&schemas[0].1).unwrap();
+        let server = serde_json::to_string(&schemas[1].1).unwrap();
+
+        // Message types are the `type` tag's allowed values
+        assert!(client.contains("\"roll_duality\""));
+        assert!(server.contains("\"character_moved\""));
+        // Types the messages carry come along as definitions
+        assert!(schemas[1].1.definitions.contains_key("Position"));
+        assert!(schemas[1].1.definitions.contains_key("CharacterChanges"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Table settings: the house rules and options the GM sets for the table
+//!
+//! Every field has a default, so settings written by an older version (or a
+//! config file naming only a few of them) fill in the rest.
+
+use serde::{Deserialize, Serialize};
+
+use crate::narrative::NarrativeMode;
+
+/// Current settings version, bumped when a field changes meaning
+pub const TABLE_SETTINGS_VERSION: u32 = 1;
+
+/// Highest Fear cap the GM can set
+pub const MAX_FEAR_CAP: u8 = 30;
+
+/// Highest Hope cost of a tag team roll (a character's full Hope)
+pub const MAX_TAG_TEAM_HOPE_COST: u8 = 6;
+
+/// Largest per-turn movement budget (Very Far)
+pub const MAX_COMBAT_MOVE_FEET: u16 = 300;
+
+/// Everything the GM can change about how the table runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct TableSettings {
+    pub version: u32,
+    /// Whether combat events get flavor text
+    pub narrative_mode: NarrativeMode,
+    /// The GM's Fear at the start of a game
+    pub starting_fear: u8,
+    /// Fear stops growing here (12 in the core rules)
+    pub max_fear: u8,
+    /// Hope each character spends on a tag team roll
+    pub tag_team_hope_cost: u8,
+    /// Whether players may whisper each other (they can always whisper the GM)
+    pub player_whispers: bool,
+    /// How far a character may move each turn during combat, in feet (0 for
+    /// no limit; Close range is 30)
+    pub combat_move_feet: u16,
+}
+
+impl Default for TableSettings {
+    fn default() -> Self {
+        Self {
+            version: TABLE_SETTINGS_VERSION,
+            narrative_mode: NarrativeMode::default(),
+            starting_fear: 5,
+            max_fear: 12,
+            tag_team_hope_cost: 3,
+            player_whispers: true,
+            combat_move_feet: 0,
+        }
+    }
+}
+
+/// A change to some of the settings; fields left out keep their value
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct TableSettingsPatch {
+    pub narrative_mode: Option<NarrativeMode>,
+    pub starting_fear: Option<u8>,
+    pub max_fear: Option<u8>,
+    pub tag_team_hope_cost: Option<u8>,
+    pub player_whispers: Option<bool>,
+    pub combat_move_feet: Option<u16>,
+}
+
+impl TableSettings {
+    /// Check the settings make sense together
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version > TABLE_SETTINGS_VERSION {
+            return Err(format!(
+                "Settings version {} is newer than this server supports ({})",
+                self.version, TABLE_SETTINGS_VERSION
+            ));
+        }
+        if self.max_fear == 0 || self.max_fear > MAX_FEAR_CAP {
+            return Err(format!("Fear cap must be between 1 and {}", MAX_FEAR_CAP));
+        }
+        if self.starting_fear > self.max_fear {
+            return Err(format!(
+                "Starting Fear ({}) is above the Fear cap ({})",
+                self.starting_fear, self.max_fear
+            ));
+        }
+        if self.tag_team_hope_cost > MAX_TAG_TEAM_HOPE_COST {
+            return Err(format!(
+                "Tag team Hope cost must be at most {}",
+                MAX_TAG_TEAM_HOPE_COST
+            ));
+        }
+        if self.combat_move_feet > MAX_COMBAT_MOVE_FEET {
+            return Err(format!(
+                "Combat movement must be at most {} ft",
+                MAX_COMBAT_MOVE_FEET
+            ));
+        }
+        Ok(())
+    }
+
+    /// The settings with a patch applied, if the result is valid
+    pub fn patched(&self, patch: &TableSettingsPatch) -> Result<Self, String> {
+        let settings = Self {
+            version: TABLE_SETTINGS_VERSION,
+            narrative_mode: patch.narrative_mode.unwrap_or(self.narrative_mode),
+            starting_fear: patch.starting_fear.unwrap_or(self.starting_fear),
+            max_fear: patch.max_fear.unwrap_or(self.max_fear),
+            tag_team_hope_cost: patch.tag_team_hope_cost.unwrap_or(self.tag_team_hope_cost),
+            player_whispers: patch.player_whispers.unwrap_or(self.player_whispers),
+            combat_move_feet: patch.combat_move_feet.unwrap_or(self.combat_move_feet),
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let settings: TableSettings = serde_json::from_str(r#"{"max_fear":10}"#).unwrap();
+        assert_eq!(settings.max_fear, 10);
+        assert_eq!(settings.starting_fear, 5);
+        assert_eq!(settings.version, TABLE_SETTINGS_VERSION);
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_patched() {
+        let settings = TableSettings::default();
+        let patch: TableSettingsPatch =
+            serde_json::from_str(r#"{"narrative_mode":"plain","player_whispers":false}"#).unwrap();
+        let patched = settings.patched(&patch).unwrap();
+        assert_eq!(patched.narrative_mode, NarrativeMode::Plain);
+        assert!(!patched.player_whispers);
+        assert_eq!(patched.max_fear, settings.max_fear);
+
+        let too_low = TableSettingsPatch {
+            max_fear: Some(3),
+            ..TableSettingsPatch::default()
+        };
+        assert_eq!(
+            settings.patched(&too_low).unwrap_err(),
+            "Starting Fear (5) is above the Fear cap (3)"
+        );
+        let too_costly = TableSettingsPatch {
+            tag_team_hope_cost: Some(MAX_TAG_TEAM_HOPE_COST + 1),
+            ..TableSettingsPatch::default()
+        };
+        assert!(settings.patched(&too_costly).is_err());
+        let too_far = TableSettingsPatch {
+            combat_move_feet: Some(MAX_COMBAT_MOVE_FEET + 1),
+            ..TableSettingsPatch::default()
+        };
+        assert!(settings.patched(&too_far).is_err());
+    }
+}
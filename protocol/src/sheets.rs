@@ -0,0 +1,194 @@
+//! Character sheet changes: after a client has a full sheet, the server
+//! sends only the fields that changed
+//!
+//! Every sheet sent carries a sequence number that only goes up. A change
+//! says which sequence it builds on (`since`); a client holding a different
+//! one has missed something and asks for the full sheet again.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::damage::Defenses;
+use crate::equipment::Equipment;
+use crate::inventory::Inventory;
+use crate::leveling::Progression;
+use crate::subclasses::Subclass;
+use crate::{AttributesData, CharacterData, Experience, ResourceData};
+
+/// The fields of a [`CharacterData`] that changed; the rest are left out
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct CharacterChanges {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// `null` when the subclass was cleared
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "present")]
+    pub subclass: Option<Option<Subclass>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ancestry: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<AttributesData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hp: Option<ResourceData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stress: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stress_max: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hope: Option<ResourceData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evasion: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experiences: Option<Vec<Experience>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory: Option<Inventory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<Equipment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progression: Option<Progression>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defenses: Option<Defenses>,
+}
+
+/// A field that was sent, even as `null`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// `Some(new)` if it differs from `old`
+fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+    (old != new).then(|| new.clone())
+}
+
+impl CharacterChanges {
+    /// What changed from one sheet to the next
+    pub fn between(old: &CharacterData, new: &CharacterData) -> Self {
+        Self {
+            name: changed(&old.name, &new.name),
+            class: changed(&old.class, &new.class),
+            subclass: changed(&old.subclass, &new.subclass),
+            ancestry: changed(&old.ancestry, &new.ancestry),
+            attributes: changed(&old.attributes, &new.attributes),
+            hp: changed(&old.hp, &new.hp),
+            stress: changed(&old.stress, &new.stress),
+            stress_max: changed(&old.stress_max, &new.stress_max),
+            hope: changed(&old.hope, &new.hope),
+            evasion: changed(&old.evasion, &new.evasion),
+            level: changed(&old.level, &new.level),
+            experiences: changed(&old.experiences, &new.experiences),
+            inventory: changed(&old.inventory, &new.inventory),
+            equipment: changed(&old.equipment, &new.equipment),
+            progression: changed(&old.progression, &new.progression),
+            defenses: changed(&old.defenses, &new.defenses),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Bring a sheet up to date
+    pub fn apply_to(self, sheet: &mut CharacterData) {
+        fn set<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        set(&mut sheet.name, self.name);
+        set(&mut sheet.class, self.class);
+        set(&mut sheet.subclass, self.subclass);
+        set(&mut sheet.ancestry, self.ancestry);
+        set(&mut sheet.attributes, self.attributes);
+        set(&mut sheet.hp, self.hp);
+        set(&mut sheet.stress, self.stress);
+        set(&mut sheet.stress_max, self.stress_max);
+        set(&mut sheet.hope, self.hope);
+        set(&mut sheet.evasion, self.evasion);
+        set(&mut sheet.level, self.level);
+        set(&mut sheet.experiences, self.experiences);
+        set(&mut sheet.inventory, self.inventory);
+        set(&mut sheet.equipment, self.equipment);
+        set(&mut sheet.progression, self.progression);
+        set(&mut sheet.defenses, self.defenses);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet() -> CharacterData {
+        CharacterData {
+            name: "Theron".to_string(),
+            class: "Warrior".to_string(),
+            subclass: None,
+            ancestry: "Human".to_string(),
+            attributes: AttributesData {
+                agility: 2,
+                strength: 1,
+                finesse: 1,
+                instinct: 0,
+                presence: 0,
+                knowledge: -1,
+            },
+            hp: ResourceData {
+                current: 6,
+                maximum: 6,
+            },
+            stress: 0,
+            stress_max: 6,
+            hope: ResourceData {
+                current: 2,
+                maximum: 6,
+            },
+            evasion: 12,
+            level: 1,
+            experiences: Vec::new(),
+            inventory: Inventory::default(),
+            equipment: Equipment::default(),
+            progression: Progression::default(),
+            defenses: Defenses::default(),
+        }
+    }
+
+    #[test]
+    fn test_changes_carry_only_changed_fields() {
+        let old = sheet();
+        let mut new = sheet();
+        new.hp.current = 4;
+        new.stress = 1;
+
+        let changes = CharacterChanges::between(&old, &new);
+        let json = serde_json::to_value(&changes).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"hp": {"current": 4, "maximum": 6}, "stress": 1})
+        );
+        assert!(CharacterChanges::between(&new, &new).is_empty());
+
+        let mut patched = old;
+        serde_json::from_value::<CharacterChanges>(json)
+            .unwrap()
+            .apply_to(&mut patched);
+        assert_eq!(patched, new);
+    }
+
+    #[test]
+    fn test_cleared_subclass_is_sent_as_null() {
+        let changes: CharacterChanges = serde_json::from_str(r#"{"subclass":null}"#).unwrap();
+        assert_eq!(changes.subclass, Some(None));
+        assert!(!changes.is_empty());
+
+        let untouched: CharacterChanges = serde_json::from_str("{}").unwrap();
+        assert_eq!(untouched.subclass, None);
+    }
+}
@@ -0,0 +1,131 @@
+//! Catch-up snapshot for players joining mid-session
+//!
+//! Sent once on connect so a late joiner sees the scene, the fight, recent
+//! events and anything waiting on them without replaying the session.
+
+use serde::{Deserialize, Serialize};
+
+use crate::cutaway::CutawayInfo;
+use crate::drawings::Drawing;
+use crate::environments::EnvironmentInfo;
+use crate::fog::FogState;
+use crate::groups::AdversaryGroupInfo;
+use crate::hazards::Hazard;
+use crate::maps::{MapInfo, SceneLayout};
+use crate::modifiers::TimedModifier;
+use crate::quests::Quest;
+use crate::{CombatMode, GameEventData, Position, ResourceData, RollType};
+
+/// Everything a late joiner needs to catch up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionSnapshot {
+    pub environments: Vec<EnvironmentInfo>,
+    /// Hazards on the scene, with their countdowns
+    pub hazards: Vec<HazardSnapshot>,
+    pub combat: Option<CombatSnapshot>,
+    pub fear_pool: u8,
+    /// Most recent events, oldest first
+    pub events: Vec<GameEventData>,
+    /// Roll requests still waiting on the joiner's character (or anyone, before
+    /// they pick one)
+    pub open_rolls: Vec<OpenRollRequest>,
+    pub party: Vec<PartyMember>,
+    pub npcs: Vec<NpcInfo>,
+    /// The cutaway running, if any
+    #[serde(default)]
+    pub cutaway: Option<CutawayInfo>,
+    /// The map on the table, if any
+    #[serde(default)]
+    pub map: Option<MapInfo>,
+    /// The scene's size and grid
+    #[serde(default)]
+    pub layout: SceneLayout,
+    /// The current scene's fog of war
+    #[serde(default)]
+    pub fog: FogState,
+    /// The GM's drawings on the current scene
+    #[serde(default)]
+    pub drawings: Vec<Drawing>,
+    /// Whether the GM has locked token movement
+    #[serde(default)]
+    pub map_locked: bool,
+    /// Buffs and debuffs on the tokens in view
+    #[serde(default)]
+    pub modifiers: Vec<TimedModifier>,
+    /// Adversary groups, with only the members in view
+    #[serde(default)]
+    pub adversary_groups: Vec<AdversaryGroupInfo>,
+    /// The quest board
+    #[serde(default)]
+    pub quests: Vec<Quest>,
+}
+
+/// A hazard on the scene and where its countdown stands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HazardSnapshot {
+    pub hazard_id: String,
+    pub hazard: Hazard,
+    pub active: bool,
+    pub ticks_remaining: u8,
+}
+
+/// The fight in progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CombatSnapshot {
+    pub encounter_id: String,
+    pub round: u32,
+    pub mode: CombatMode,
+    /// Spotlight holder in spotlight mode (`None` = the GM)
+    pub spotlight: Option<String>,
+    pub pc_tokens: u8,
+    pub adversary_tokens: u8,
+    pub queue: Vec<String>, // "pc" or "adversary", in acting order
+}
+
+/// A roll request some characters have yet to roll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OpenRollRequest {
+    pub request_id: String,
+    pub roll_type: RollType,
+    pub attribute: Option<String>,
+    pub difficulty: u16,
+    pub context: String,
+    pub pending_character_ids: Vec<String>,
+}
+
+/// How a player character is doing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PartyMember {
+    pub character_id: String,
+    pub name: String,
+    pub hp: ResourceData,
+    pub stress: i32,
+    pub hope: ResourceData,
+    /// Display name of the controlling player
+    pub player_name: Option<String>,
+    pub connected: bool,
+}
+
+/// A GM-controlled NPC on the scene
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NpcInfo {
+    pub character_id: String,
+    pub name: String,
+    pub hp: ResourceData,
+    pub evasion: i32,
+    /// Adversary template the NPC takes its stats from
+    pub template: Option<String>,
+    pub position: Position,
+}
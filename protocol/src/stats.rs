@@ -0,0 +1,149 @@
+//! How each character's duality dice have been coming up
+//!
+//! Every duality roll a character makes (action rolls, free rolls and
+//! attacks) is tallied, and the averages are kept up to date as it goes so
+//! clients can show them as they are. A fair pair of d12s averages
+//! [`FAIR_AVERAGE`] on each die and rolls a critical [`FAIR_CRIT_RATE`] of
+//! the time.
+
+use serde::{Deserialize, Serialize};
+
+/// What a fair d12 averages
+pub const FAIR_AVERAGE: f32 = 6.5;
+
+/// How often a fair pair of d12s match
+pub const FAIR_CRIT_RATE: f32 = 1.0 / 12.0;
+
+/// One character's duality rolls (or the whole table's)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RollStats {
+    /// Empty for the table's totals
+    pub character_id: String,
+    pub name: String,
+    pub rolls: u32,
+    /// Times each face came up on the Hope die, 1 to 12
+    pub hope_faces: [u32; 12],
+    /// Times each face came up on the Fear die, 1 to 12
+    pub fear_faces: [u32; 12],
+    /// Doubles
+    pub criticals: u32,
+    /// Rolls where the Hope die was higher
+    pub with_hope: u32,
+    /// Rolls where the Fear die was higher
+    pub with_fear: u32,
+    pub average_hope: f32,
+    pub average_fear: f32,
+    /// Criticals out of all rolls, 0 to 1
+    pub crit_rate: f32,
+    /// Rolls with Hope for each roll with Fear; none until one goes to Fear
+    pub hope_fear_ratio: Option<f32>,
+}
+
+impl RollStats {
+    pub fn new(character_id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            character_id: character_id.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Count one roll of the two dice; faces outside 1 to 12 are ignored
+    pub fn record(&mut self, hope: u8, fear: u8) {
+        if !(1..=12).contains(&hope) || !(1..=12).contains(&fear) {
+            return;
+        }
+        self.rolls += 1;
+        self.hope_faces[hope as usize - 1] += 1;
+        self.fear_faces[fear as usize - 1] += 1;
+        match hope.cmp(&fear) {
+            std::cmp::Ordering::Equal => self.criticals += 1,
+            std::cmp::Ordering::Greater => self.with_hope += 1,
+            std::cmp::Ordering::Less => self.with_fear += 1,
+        }
+        self.update_rates();
+    }
+
+    /// Add another tally's rolls to this one
+    pub fn absorb(&mut self, other: &RollStats) {
+        self.rolls += other.rolls;
+        for face in 0..12 {
+            self.hope_faces[face] += other.hope_faces[face];
+            self.fear_faces[face] += other.fear_faces[face];
+        }
+        self.criticals += other.criticals;
+        self.with_hope += other.with_hope;
+        self.with_fear += other.with_fear;
+        self.update_rates();
+    }
+
+    fn update_rates(&mut self) {
+        let average = |faces: &[u32; 12]| {
+            let sum: u32 = faces
+                .iter()
+                .enumerate()
+                .map(|(face, count)| (face as u32 + 1) * count)
+                .sum();
+            sum as f32 / self.rolls.max(1) as f32
+        };
+        self.average_hope = average(&self.hope_faces);
+        self.average_fear = average(&self.fear_faces);
+        self.crit_rate = self.criticals as f32 / self.rolls.max(1) as f32;
+        self.hope_fear_ratio =
+            (self.with_fear > 0).then(|| self.with_hope as f32 / self.with_fear as f32);
+    }
+}
+
+/// Every character's tally, and the table's
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RollStatsReport {
+    /// By name
+    pub characters: Vec<RollStats>,
+    pub table: RollStats,
+}
+
+impl RollStatsReport {
+    pub fn new<'a>(tallies: impl IntoIterator<Item = &'a RollStats>) -> Self {
+        let mut characters: Vec<_> = tallies.into_iter().cloned().collect();
+        characters.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut table = RollStats::new("", "Table");
+        for character in &characters {
+            table.absorb(character);
+        }
+        Self { characters, table }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolls_tallied() {
+        let mut mira = RollStats::new("m", "Mira");
+        mira.record(10, 2);
+        mira.record(4, 4);
+        mira.record(3, 9);
+        mira.record(12, 1);
+        mira.record(0, 5);
+
+        assert_eq!(mira.rolls, 4);
+        assert_eq!(mira.hope_faces[3], 1);
+        assert_eq!(mira.average_hope, 7.25);
+        assert_eq!(mira.average_fear, 4.0);
+        assert_eq!(mira.crit_rate, 0.25);
+        assert_eq!(mira.hope_fear_ratio, Some(2.0));
+
+        let mut theron = RollStats::new("t", "Theron");
+        theron.record(6, 6);
+        let report = RollStatsReport::new([&theron, &mira]);
+        assert_eq!(report.characters[0].name, "Mira");
+        assert_eq!(report.table.rolls, 5);
+        assert_eq!(report.table.criticals, 2);
+        assert_eq!(RollStats::new("x", "X").hope_fear_ratio, None);
+    }
+}
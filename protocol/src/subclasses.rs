@@ -0,0 +1,143 @@
+//! Subclasses and their feature cards
+
+use serde::{Deserialize, Serialize};
+
+/// Subclasses available to each class: (class, subclass)
+pub const SUBCLASSES: &[(&str, &str)] = &[
+    ("Bard", "Troubadour"),
+    ("Bard", "Wordsmith"),
+    ("Druid", "Warden of the Elements"),
+    ("Druid", "Warden of Renewal"),
+    ("Guardian", "Stalwart"),
+    ("Guardian", "Vengeance"),
+    ("Ranger", "Beastbound"),
+    ("Ranger", "Wayfinder"),
+    ("Rogue", "Nightwalker"),
+    ("Rogue", "Syndicate"),
+    ("Seraph", "Divine Wielder"),
+    ("Seraph", "Winged Sentinel"),
+    ("Sorcerer", "Elemental Origin"),
+    ("Sorcerer", "Primal Origin"),
+    ("Warrior", "Call of the Brave"),
+    ("Warrior", "Call of the Slayer"),
+    ("Wizard", "School of Knowledge"),
+    ("Wizard", "School of War"),
+];
+
+/// Subclass feature cards, in unlock order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SubclassCard {
+    Foundation,
+    Specialization,
+    Mastery,
+}
+
+impl SubclassCard {
+    pub const ALL: [SubclassCard; 3] = [
+        SubclassCard::Foundation,
+        SubclassCard::Specialization,
+        SubclassCard::Mastery,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SubclassCard::Foundation => "foundation",
+            SubclassCard::Specialization => "specialization",
+            SubclassCard::Mastery => "mastery",
+        }
+    }
+
+    /// Level at which the card unlocks
+    pub fn unlock_level(self) -> u8 {
+        match self {
+            SubclassCard::Foundation => 1,
+            SubclassCard::Specialization => 5,
+            SubclassCard::Mastery => 8,
+        }
+    }
+}
+
+/// A character's subclass and the feature cards unlocked so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Subclass {
+    pub name: String,
+    pub cards: Vec<SubclassCard>,
+}
+
+impl Subclass {
+    /// Choose a subclass for a class, unlocking the cards for the given level
+    pub fn new(class_name: &str, name: &str, level: u8) -> Result<Self, String> {
+        let (_, subclass) = SUBCLASSES
+            .iter()
+            .find(|(c, s)| *c == class_name && s.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("{} is not a {} subclass", name, class_name))?;
+
+        let mut subclass = Self {
+            name: subclass.to_string(),
+            cards: Vec::new(),
+        };
+        subclass.unlock_for_level(level);
+        Ok(subclass)
+    }
+
+    /// Unlock every card available at this level, returning the newly unlocked ones
+    pub fn unlock_for_level(&mut self, level: u8) -> Vec<SubclassCard> {
+        let unlocked: Vec<SubclassCard> = SubclassCard::ALL
+            .into_iter()
+            .filter(|card| card.unlock_level() <= level && !self.cards.contains(card))
+            .collect();
+        self.cards.extend(&unlocked);
+        unlocked
+    }
+}
+
+/// Subclass names available to a class
+pub fn subclasses_for(class_name: &str) -> Vec<&'static str> {
+    SUBCLASSES
+        .iter()
+        .filter(|(c, _)| *c == class_name)
+        .map(|(_, s)| *s)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_class_has_two_subclasses() {
+        for class in [
+            "Bard", "Druid", "Guardian", "Ranger", "Rogue", "Seraph", "Sorcerer", "Warrior",
+            "Wizard",
+        ] {
+            assert_eq!(subclasses_for(class).len(), 2, "{}", class);
+        }
+    }
+
+    #[test]
+    fn test_subclass_must_match_class() {
+        let subclass = Subclass::new("Warrior", "call of the brave", 1).unwrap();
+        assert_eq!(subclass.name, "Call of the Brave");
+        assert_eq!(subclass.cards, vec![SubclassCard::Foundation]);
+
+        assert!(Subclass::new("Wizard", "Call of the Brave", 1).is_err());
+    }
+
+    #[test]
+    fn test_cards_unlock_with_level() {
+        let mut subclass = Subclass::new("Rogue", "Syndicate", 1).unwrap();
+        assert!(subclass.unlock_for_level(4).is_empty());
+        assert_eq!(
+            subclass.unlock_for_level(5),
+            vec![SubclassCard::Specialization]
+        );
+
+        let veteran = Subclass::new("Rogue", "Nightwalker", 8).unwrap();
+        assert_eq!(veteran.cards.len(), 3);
+    }
+}
@@ -0,0 +1,144 @@
+//! How a token sits on the map beyond its position: which way it faces,
+//! how many cells it covers, and how high it is
+
+use serde::{Deserialize, Serialize};
+
+/// Longest token note accepted, in characters
+pub const MAX_TOKEN_NOTE_LENGTH: usize = 60;
+
+/// Longest name the GM can give a token, in characters
+pub const MAX_TOKEN_NAME_LENGTH: usize = 40;
+
+/// Highest (or deepest) elevation accepted, in feet
+pub const MAX_ELEVATION: i32 = 1000;
+
+/// Size class of a token
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSize {
+    /// One cell
+    #[default]
+    Normal,
+    /// Two cells across
+    Large,
+    /// Three cells across
+    Huge,
+}
+
+impl TokenSize {
+    /// Cells the token covers along each side
+    pub fn cells(self) -> u8 {
+        match self {
+            TokenSize::Normal => 1,
+            TokenSize::Large => 2,
+            TokenSize::Huge => 3,
+        }
+    }
+}
+
+/// A token's facing, size and elevation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct TokenDetails {
+    /// Degrees clockwise from facing up the map
+    pub rotation: f32,
+    pub size: TokenSize,
+    /// Feet above the ground (negative below it), if it matters
+    pub elevation: Option<i32>,
+    /// Shown under the token, e.g. "Flying" or "Prone"
+    pub note: Option<String>,
+}
+
+impl TokenDetails {
+    /// Check the details, returning them with the rotation in 0..360 and a
+    /// blank note dropped
+    pub fn normalized(mut self) -> Result<Self, String> {
+        if !self.rotation.is_finite() {
+            return Err("Rotation must be a number".to_string());
+        }
+        self.rotation = normalize_rotation(self.rotation);
+        if self
+            .elevation
+            .is_some_and(|elevation| elevation.abs() > MAX_ELEVATION)
+        {
+            return Err(format!(
+                "Elevation must be within {} feet of the ground",
+                MAX_ELEVATION
+            ));
+        }
+        self.note = self
+            .note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        if self
+            .note
+            .as_ref()
+            .is_some_and(|note| note.chars().count() > MAX_TOKEN_NOTE_LENGTH)
+        {
+            return Err(format!(
+                "Token note is too long (max {} characters)",
+                MAX_TOKEN_NOTE_LENGTH
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// An angle in degrees, wrapped into 0..360
+pub fn normalize_rotation(degrees: f32) -> f32 {
+    let wrapped = degrees.rem_euclid(360.0);
+    // rem_euclid can round up to exactly 360 for tiny negative angles
+    if wrapped >= 360.0 {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_details() {
+        let details = TokenDetails {
+            rotation: -90.0,
+            size: TokenSize::Large,
+            elevation: Some(30),
+            note: Some("  Flying ".to_string()),
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(details.rotation, 270.0);
+        assert_eq!(details.note.as_deref(), Some("Flying"));
+
+        let blank = TokenDetails {
+            note: Some(" ".to_string()),
+            ..TokenDetails::default()
+        };
+        assert_eq!(blank.normalized().unwrap().note, None);
+
+        let too_high = TokenDetails {
+            elevation: Some(MAX_ELEVATION + 1),
+            ..TokenDetails::default()
+        };
+        assert!(too_high.normalized().is_err());
+        let spinning = TokenDetails {
+            rotation: f32::NAN,
+            ..TokenDetails::default()
+        };
+        assert!(spinning.normalized().is_err());
+    }
+
+    #[test]
+    fn test_details_json_defaults() {
+        let details: TokenDetails = serde_json::from_str(r#"{"size":"huge"}"#).unwrap();
+        assert_eq!(details.size.cells(), 3);
+        assert_eq!(details.rotation, 0.0);
+        assert_eq!(details.elevation, None);
+    }
+}
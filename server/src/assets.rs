@@ -0,0 +1,102 @@
+//! The web client: pages, scripts and styles
+//!
+//! The `client/` directory is built into the server, so the binary runs
+//! from anywhere. With `--client-dir` (or `client_dir` in the config) the
+//! files are read from disk on every request instead, so client changes show
+//! up on refresh without a rebuild.
+
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+use axum::{
+    extract::{Path as UrlPath, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use rust_embed::RustEmbed;
+
+use crate::websocket::AppState;
+
+#[derive(RustEmbed)]
+#[folder = "../client/"]
+struct Embedded;
+
+/// Where client files come from
+#[derive(Debug, Clone)]
+pub enum ClientAssets {
+    /// The copy built into the server
+    Embedded,
+    /// A directory on disk
+    Dir(PathBuf),
+}
+
+impl ClientAssets {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        match dir {
+            Some(dir) => Self::Dir(dir),
+            None => Self::Embedded,
+        }
+    }
+
+    /// A file by its path under the client directory, e.g. `js/app.js`
+    pub fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        match self {
+            Self::Embedded => Embedded::get(path).map(|file| file.data),
+            Self::Dir(dir) => {
+                // Only plain names, so a request can't climb out of the directory
+                let relative = Path::new(path);
+                if !relative
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+                {
+                    return None;
+                }
+                std::fs::read(dir.join(relative)).ok().map(Cow::Owned)
+            }
+        }
+    }
+
+    /// An HTML page, or an error page saying which one is missing
+    pub fn page(&self, name: &str) -> Html<String> {
+        match self.get(name) {
+            Some(bytes) => Html(String::from_utf8_lossy(&bytes).into_owned()),
+            None => Html(format!("<h1>Error loading {}</h1>", name)),
+        }
+    }
+}
+
+/// `/static/*path`
+pub async fn static_file(
+    State(state): State<AppState>,
+    UrlPath(path): UrlPath<String>,
+) -> Response {
+    match state.client.get(&path) {
+        Some(bytes) => {
+            let mime = mime_guess::from_path(&path).first_or_octet_stream();
+            (
+                [(header::CONTENT_TYPE, mime.to_string())],
+                bytes.into_owned(),
+            )
+                .into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_files_are_served() {
+        let embedded = ClientAssets::Embedded;
+        assert!(embedded.get("index.html").is_some());
+        assert!(embedded.get("js/gm.js").is_some());
+        assert!(embedded.get("missing.html").is_none());
+
+        let dir = ClientAssets::new(Some(PathBuf::from("../client")));
+        assert_eq!(dir.get("css/style.css"), embedded.get("css/style.css"));
+        assert!(dir.get("../Cargo.toml").is_none());
+        assert!(dir.get("/etc/passwd").is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! Backups: everything a table keeps on disk, in one zip
+//!
+//! A backup holds the save files, uploaded maps and handouts, each under a
+//! folder of its own (`saves/`, `maps/`, `handouts/`), and the vault as
+//! `vault.json`. Restoring one writes those files back, replacing any with
+//! the same name; nothing else is touched. Every entry is checked, then
+//! unpacked into a hidden staging directory beside where it goes, and only
+//! once the whole zip has been read is each file renamed into place, so a
+//! bad zip changes nothing.
+//!
+//! Saves kept in SQLite aren't files a backup can copy safely while the
+//! server has the database open; the server refuses to back up or restore
+//! then (see `routes::backup_layout`).
+
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Largest backup the server accepts, and the most a restore may unpack
+pub const MAX_BACKUP_BYTES: usize = 512 * 1024 * 1024;
+
+/// Zip name of the vault file
+const VAULT_ENTRY: &str = "vault.json";
+
+/// Where each part of a backup lives on this server
+#[derive(Debug, Clone)]
+pub struct BackupLayout {
+    /// Folder name in the zip, and the directory it comes from
+    pub dirs: Vec<(&'static str, PathBuf)>,
+    pub vault: PathBuf,
+}
+
+/// How much a restore wrote back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Restored {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+fn zip_error(e: zip::result::ZipError) -> String {
+    format!("Invalid backup: {}", e)
+}
+
+/// Where a restore unpacks files before moving them into place; the
+/// directories are removed once it's done, whether it worked or not
+struct Staging {
+    id: Uuid,
+    dirs: Vec<PathBuf>,
+}
+
+impl Staging {
+    fn new() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            dirs: Vec::new(),
+        }
+    }
+
+    /// Where to unpack a file bound for `path`: a hidden directory beside
+    /// it, so moving it into place is a rename on the same disk
+    fn path_for(&mut self, path: &Path) -> Result<PathBuf, String> {
+        let parent = path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let dir = parent.join(format!(".restore-{}", self.id));
+        if !self.dirs.contains(&dir) {
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            self.dirs.push(dir.clone());
+        }
+        let file = path
+            .file_name()
+            .ok_or_else(|| format!("Nowhere to restore {}", path.display()))?;
+        Ok(dir.join(file))
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        for dir in &self.dirs {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+impl BackupLayout {
+    /// Zip up every file there is (missing directories are just left out)
+    pub fn create(&self) -> Result<Vec<u8>, String> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        let mut add = |name: String, path: &Path| -> Result<(), String> {
+            let bytes =
+                fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(&bytes)
+                .map_err(|e| format!("Failed to write backup: {}", e))
+        };
+
+        for (folder, dir) in &self.dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            let mut files: Vec<_> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect();
+            files.sort();
+            for path in files {
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    add(format!("{}/{}", folder, name), &path)?;
+                }
+            }
+        }
+        if self.vault.is_file() {
+            add(VAULT_ENTRY.to_string(), &self.vault)?;
+        }
+
+        zip.finish()
+            .map(Cursor::into_inner)
+            .map_err(|e| format!("Failed to write backup: {}", e))
+    }
+
+    /// Where a zip entry goes, or why it can't be restored
+    fn destination(&self, name: &str) -> Result<PathBuf, String> {
+        if name == VAULT_ENTRY {
+            return Ok(self.vault.clone());
+        }
+        let rejected = || format!("Backup contains an unexpected file: {}", name);
+        let mut components = Path::new(name).components();
+        let (Some(Component::Normal(folder)), Some(Component::Normal(file)), None) =
+            (components.next(), components.next(), components.next())
+        else {
+            return Err(rejected());
+        };
+        let file = file.to_str().ok_or_else(rejected)?;
+        if file.starts_with('.') {
+            return Err(rejected());
+        }
+        self.dirs
+            .iter()
+            .find(|(dir_name, _)| folder == *dir_name)
+            .map(|(_, dir)| dir.join(file))
+            .ok_or_else(rejected)
+    }
+
+    /// Write a backup's files back into place
+    pub fn restore(&self, bytes: &[u8]) -> Result<Restored, String> {
+        let mut zip = ZipArchive::new(Cursor::new(bytes)).map_err(zip_error)?;
+
+        let mut planned = Vec::new();
+        let mut total: u64 = 0;
+        for i in 0..zip.len() {
+            let entry = zip.by_index(i).map_err(zip_error)?;
+            if entry.is_dir() {
+                continue;
+            }
+            total += entry.size();
+            if total > MAX_BACKUP_BYTES as u64 {
+                return Err(format!(
+                    "Backup unpacks to more than {} MB",
+                    MAX_BACKUP_BYTES / (1024 * 1024)
+                ));
+            }
+            planned.push((i, self.destination(entry.name())?));
+        }
+
+        let mut staging = Staging::new();
+        let mut staged = Vec::new();
+        let mut restored = Restored { files: 0, bytes: 0 };
+        for (i, path) in planned {
+            let mut entry = zip.by_index(i).map_err(zip_error)?;
+            let mut contents = Vec::new();
+            // Sizes in the zip can lie; never read past the limit
+            (&mut entry)
+                .take(MAX_BACKUP_BYTES as u64 + 1 - restored.bytes)
+                .read_to_end(&mut contents)
+                .map_err(|e| format!("Invalid backup: {}", e))?;
+            restored.bytes += contents.len() as u64;
+            if restored.bytes > MAX_BACKUP_BYTES as u64 {
+                return Err(format!(
+                    "Backup unpacks to more than {} MB",
+                    MAX_BACKUP_BYTES / (1024 * 1024)
+                ));
+            }
+
+            let temp = staging.path_for(&path)?;
+            fs::write(&temp, &contents)
+                .map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+            staged.push((temp, path));
+        }
+
+        for (temp, path) in staged {
+            fs::rename(&temp, &path)
+                .map_err(|e| format!("Failed to restore {}: {}", path.display(), e))?;
+            restored.files += 1;
+        }
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(root: &Path) -> BackupLayout {
+        BackupLayout {
+            dirs: vec![("saves", root.join("saves")), ("maps", root.join("maps"))],
+            vault: root.join("vault.json"),
+        }
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let from = std::env::temp_dir().join(format!("backup-{}", uuid::Uuid::new_v4()));
+        let to = std::env::temp_dir().join(format!("backup-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(from.join("saves")).unwrap();
+        fs::write(from.join("saves/session.json"), "{}").unwrap();
+        fs::write(from.join("vault.json"), "[]").unwrap();
+
+        let zip = layout(&from).create().unwrap();
+        let restored = layout(&to).restore(&zip).unwrap();
+        assert_eq!(restored.files, 2);
+        assert_eq!(
+            fs::read_to_string(to.join("saves/session.json")).unwrap(),
+            "{}"
+        );
+        assert!(to.join("vault.json").exists());
+
+        let _ = fs::remove_dir_all(&from);
+        let _ = fs::remove_dir_all(&to);
+    }
+
+    #[test]
+    fn test_restore_refuses_paths_outside_the_layout() {
+        let root = std::env::temp_dir().join(format!("backup-{}", uuid::Uuid::new_v4()));
+        for name in [
+            "../escape.json",
+            "saves/../../escape.json",
+            "/etc/passwd",
+            "other/file",
+        ] {
+            let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+            zip.start_file("saves/fine.json", SimpleFileOptions::default())
+                .unwrap();
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            let bytes = zip.finish().unwrap().into_inner();
+
+            assert!(layout(&root).restore(&bytes).is_err(), "{}", name);
+            assert!(!root.join("saves/fine.json").exists(), "nothing written");
+        }
+    }
+
+    #[test]
+    fn test_restore_that_fails_part_way_changes_nothing() {
+        let root = std::env::temp_dir().join(format!("backup-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("saves")).unwrap();
+        fs::write(root.join("saves/session.json"), "old").unwrap();
+
+        // The second entry is corrupt, which shows only once it's read
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("saves/session.json", options).unwrap();
+        zip.write_all(b"new").unwrap();
+        zip.start_file("maps/map.png", options).unwrap();
+        zip.write_all(b"not really an image").unwrap();
+        let mut broken = zip.finish().unwrap().into_inner();
+        let data = broken
+            .windows(6)
+            .position(|w| w == b"really")
+            .expect("stored as is");
+        broken[data] = b'R';
+
+        assert!(layout(&root).restore(&broken).is_err());
+        assert_eq!(
+            fs::read_to_string(root.join("saves/session.json")).unwrap(),
+            "old"
+        );
+        let leftovers: Vec<_> = fs::read_dir(root.join("saves"))
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name())
+            .collect();
+        assert_eq!(leftovers, ["session.json"], "staging is cleaned up");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,240 @@
+//! Server configuration
+//!
+//! Each setting starts at its default, then takes the config file's value,
+//! then the environment's, then the command line's, so a flag always wins.
+//! The config file is TOML, `daggerheart.toml` in the working directory
+//! unless `--config` or `DAGGERHEART_CONFIG` names another:
+//!
+//! ```toml
+//! port = 8080
+//! bind = "127.0.0.1"
+//! client_dir = "../client"
+//! saves_dir = "/var/lib/daggerheart/saves"
+//! broadcast_capacity = 256
+//! move_updates_per_second = 10
+//! ```
+//!
+//! WebSocket rate limits go in a `[rate_limit]` table; see
+//! [`crate::ratelimit`].
+//!
+//! Everything else is read from the environment by the module that uses it
+//! (the API token, allowed origins, storage backend, map, handout and
+//! adversary directories, vault, encounter, history, journal and settings
+//! files, reminders and resource quotas), so it has no flag and no file
+//! key. The README lists them.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::ratelimit::RateLimitConfig;
+use crate::save::SAVES_DIR;
+
+/// Config file path
+pub const CONFIG_FILE_ENV: &str = "DAGGERHEART_CONFIG";
+
+/// Read if it exists when no other config file is named
+pub const DEFAULT_CONFIG_FILE: &str = "daggerheart.toml";
+
+pub const PORT_ENV: &str = "DAGGERHEART_PORT";
+pub const BIND_ENV: &str = "DAGGERHEART_BIND";
+pub const CLIENT_DIR_ENV: &str = "DAGGERHEART_CLIENT_DIR";
+pub const SAVES_DIR_ENV: &str = "DAGGERHEART_SAVES_DIR";
+pub const BROADCAST_CAPACITY_ENV: &str = "DAGGERHEART_BROADCAST_CAPACITY";
+pub const DICE_SEED_ENV: &str = "DAGGERHEART_DICE_SEED";
+
+/// `--help` output
+pub const USAGE: &str = "\
+Usage: daggerheart-vtt-server [OPTIONS]
+
+Options:
+  --config <FILE>              Config file (default: daggerheart.toml, if present)
+  --port <PORT>                Port to listen on (default: 3000)
+  --bind <ADDRESS>             Address to listen on (default: 0.0.0.0)
+  --client-dir <DIR>           Serve the web client from here instead of the built-in copy
+  --saves-dir <DIR>            Where save files go (default: saves)
+  --broadcast-capacity <N>     Messages buffered per client before it lags (default: 100)
+  --dice-seed <N>              Seed the dice so every run rolls the same (for tests and replays)
+  -h, --help                   Print this help
+
+Each option can also be set in the config file or with an environment
+variable (DAGGERHEART_PORT, DAGGERHEART_BIND, DAGGERHEART_CLIENT_DIR,
+DAGGERHEART_SAVES_DIR, DAGGERHEART_BROADCAST_CAPACITY, DAGGERHEART_DICE_SEED);
+flags win over the environment, which wins over the file.
+
+Other settings (DAGGERHEART_API_TOKEN, DAGGERHEART_STORAGE, DAGGERHEART_MAP_DIR
+and the rest listed in the README) are environment variables only.";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub bind: IpAddr,
+    /// Serve the HTML, scripts and styles from this directory instead of the
+    /// copy built into the server, e.g. to try client changes without a
+    /// rebuild
+    pub client_dir: Option<PathBuf>,
+    /// Where save files go (when saves are kept as files)
+    pub saves_dir: PathBuf,
+    /// Broadcast messages a slow client can fall behind by before it's
+    /// resynced
+    pub broadcast_capacity: usize,
+    /// Broadcasts a second of one token's moves while it's dragged; moves
+    /// in between are joined up (0 sends every one; file only)
+    pub move_updates_per_second: f64,
+    /// Seed for the dice, so the same rolls come up every run; random
+    /// when not set
+    pub dice_seed: Option<u64>,
+    /// Per-connection message budgets (`[rate_limit]`, file only)
+    pub rate_limit: RateLimitConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 3000,
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            client_dir: None,
+            saves_dir: PathBuf::from(SAVES_DIR),
+            broadcast_capacity: 100,
+            move_updates_per_second: 10.0,
+            dice_seed: None,
+            rate_limit: RateLimitConfig::default(),
+        }
+    }
+}
+
+/// A setting given on the command line or in the environment
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid {}: {}", name, value))
+}
+
+impl ServerConfig {
+    /// Build the configuration from command-line `args` (without the
+    /// program name) and the environment, as read by `env`
+    pub fn load(args: &[String], env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let mut flags = Vec::new();
+        let mut config_file = None;
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--config" => config_file = Some(PathBuf::from(value)),
+                "--port"
+                | "--bind"
+                | "--client-dir"
+                | "--saves-dir"
+                | "--broadcast-capacity"
+                | "--dice-seed" => flags.push((&flag[2..], value.as_str())),
+                _ => return Err(format!("Unknown option: {}", flag)),
+            }
+        }
+
+        let mut config = match config_file.or_else(|| env(CONFIG_FILE_ENV).map(PathBuf::from)) {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+
+        for (name, var) in [
+            ("port", PORT_ENV),
+            ("bind", BIND_ENV),
+            ("client-dir", CLIENT_DIR_ENV),
+            ("saves-dir", SAVES_DIR_ENV),
+            ("broadcast-capacity", BROADCAST_CAPACITY_ENV),
+            ("dice-seed", DICE_SEED_ENV),
+        ] {
+            if let Some(value) = env(var).filter(|v| !v.is_empty()) {
+                config.set(name, &value)?;
+            }
+        }
+        for (name, value) in flags {
+            config.set(name, value)?;
+        }
+
+        if config.broadcast_capacity == 0 {
+            return Err("broadcast_capacity must be at least 1".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Read a config file; settings it leaves out keep their defaults
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "port" => self.port = parse(name, value)?,
+            "bind" => self.bind = parse(name, value)?,
+            "client-dir" => self.client_dir = Some(PathBuf::from(value)),
+            "saves-dir" => self.saves_dir = PathBuf::from(value),
+            "broadcast-capacity" => self.broadcast_capacity = parse(name, value)?,
+            "dice-seed" => self.dice_seed = Some(parse(name, value)?),
+            _ => return Err(format!("Unknown option: --{}", name)),
+        }
+        Ok(())
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind, self.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_flags_beat_env_beat_file() {
+        let path = std::env::temp_dir().join(format!("config-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "port = 8080\nsaves_dir = \"/data/saves\"\n").unwrap();
+        let env = |var: &str| match var {
+            CONFIG_FILE_ENV => Some(path.display().to_string()),
+            PORT_ENV => Some("9000".to_string()),
+            BIND_ENV => Some("127.0.0.1".to_string()),
+            _ => None,
+        };
+
+        let config = ServerConfig::load(&args(&[]), env).unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.saves_dir, Path::new("/data/saves"));
+        assert_eq!(config.addr().to_string(), "127.0.0.1:9000");
+        assert_eq!(config.broadcast_capacity, 100);
+
+        let config =
+            ServerConfig::load(&args(&["--port", "7000", "--dice-seed", "42"]), env).unwrap();
+        assert_eq!(config.port, 7000);
+        assert_eq!(config.dice_seed, Some(42));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_bad_settings_are_errors() {
+        let no_env = |_: &str| None;
+        for bad in [
+            &["--port"][..],
+            &["--port", "http"],
+            &["--bind", "localhost"],
+            &["--broadcast-capacity", "0"],
+            &["--verbose", "yes"],
+            &["--config", "/nonexistent/daggerheart.toml"],
+        ] {
+            assert!(ServerConfig::load(&args(bad), no_env).is_err(), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Encounter presets: fights the GM built ahead of time
+//!
+//! Presets are kept in one JSON file, in the order they were first saved.
+//! Saving under a name that's taken (ignoring case) replaces that preset.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::protocol::encounters::EncounterPreset;
+
+/// Preset file path
+pub const ENCOUNTERS_FILE_ENV: &str = "DAGGERHEART_ENCOUNTERS";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_ENCOUNTERS_FILE: &str = "encounters.json";
+
+/// Longest preset name
+pub const MAX_PRESET_NAME_LENGTH: usize = 60;
+
+/// Encounter presets on disk
+#[derive(Debug, Clone)]
+pub struct EncounterStore {
+    path: PathBuf,
+}
+
+impl EncounterStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Use the file named by `DAGGERHEART_ENCOUNTERS`
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var(ENCOUNTERS_FILE_ENV)
+                .unwrap_or_else(|_| DEFAULT_ENCOUNTERS_FILE.to_string()),
+        )
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Every preset (none before the first is saved)
+    pub fn list(&self) -> Result<Vec<EncounterPreset>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let json = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Corrupt encounter presets: {}", e))
+    }
+
+    /// The preset with this name, ignoring case
+    pub fn get(&self, name: &str) -> Result<EncounterPreset, String> {
+        self.list()?
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("No encounter named {}", name))
+    }
+
+    /// Keep a preset, replacing one of the same name; returns every preset
+    pub fn save(&self, mut preset: EncounterPreset) -> Result<Vec<EncounterPreset>, String> {
+        preset.name = preset.name.trim().to_string();
+        if preset.name.is_empty() {
+            return Err("Name the encounter".to_string());
+        }
+        if preset.name.chars().count() > MAX_PRESET_NAME_LENGTH {
+            return Err(format!(
+                "Encounter names go up to {} characters",
+                MAX_PRESET_NAME_LENGTH
+            ));
+        }
+
+        let mut presets = self.list()?;
+        match presets
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(&preset.name))
+        {
+            Some(existing) => *existing = preset,
+            None => presets.push(preset),
+        }
+        self.write(&presets)?;
+        Ok(presets)
+    }
+
+    /// Forget a preset; returns the rest
+    pub fn delete(&self, name: &str) -> Result<Vec<EncounterPreset>, String> {
+        let mut presets = self.list()?;
+        let before = presets.len();
+        presets.retain(|p| !p.name.eq_ignore_ascii_case(name.trim()));
+        if presets.len() == before {
+            return Err(format!("No encounter named {}", name));
+        }
+        self.write(&presets)?;
+        Ok(presets)
+    }
+
+    fn write(&self, presets: &[EncounterPreset]) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(presets)
+            .map_err(|e| format!("Failed to serialize encounter presets: {}", e))?;
+        fs::write(&self.path, json)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::encounters::{EncounterEntry, EncounterPlan};
+    use uuid::Uuid;
+
+    fn preset(name: &str, count: u8) -> EncounterPreset {
+        EncounterPreset {
+            name: name.to_string(),
+            plan: EncounterPlan {
+                party_size: 4,
+                tier: 1,
+                entries: vec![EncounterEntry {
+                    template_id: "goblin".to_string(),
+                    count,
+                    positions: Vec::new(),
+                }],
+                difficulty: Default::default(),
+                extra_damage: false,
+            },
+        }
+    }
+
+    #[test]
+    fn test_presets_saved_replaced_and_deleted() {
+        let store = EncounterStore::new(
+            std::env::temp_dir().join(format!("encounters-{}.json", Uuid::new_v4())),
+        );
+        assert!(store.list().unwrap().is_empty());
+
+        store.save(preset("Goblin Ambush", 3)).unwrap();
+        store.save(preset(" Bridge Trolls ", 1)).unwrap();
+        let presets = store.save(preset("goblin ambush", 5)).unwrap();
+        assert_eq!(presets.len(), 2);
+        assert_eq!(store.get("GOBLIN AMBUSH").unwrap().plan.entries[0].count, 5);
+        assert_eq!(presets[1].name, "Bridge Trolls");
+        assert!(store.save(preset("  ", 1)).is_err());
+
+        assert_eq!(store.delete("bridge trolls").unwrap().len(), 1);
+        assert!(store.delete("bridge trolls").is_err());
+
+        fs::remove_file(store.path()).unwrap();
+    }
+}
@@ -0,0 +1,311 @@
+//! Handout storage
+//!
+//! Each handout is two files in the handout directory: `<id>.json` with its
+//! metadata and `<id>.<ext>` with the content. They outlive the server, so
+//! players can look back at what they were given in earlier sessions.
+
+use std::fs;
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::limits::{self, ResourceLimits};
+use crate::protocol::handouts::{
+    Handout, HandoutKind, MAX_HANDOUT_IMAGE_BYTES, MAX_HANDOUT_TEXT_LENGTH,
+    MAX_HANDOUT_TITLE_LENGTH,
+};
+use crate::security::constant_time_eq;
+
+/// Directory handouts are stored in
+pub const HANDOUT_DIR_ENV: &str = "DAGGERHEART_HANDOUT_DIR";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_HANDOUT_DIR: &str = "handouts";
+
+/// The metadata file: the handout plus where its content is
+#[derive(Serialize, Deserialize)]
+struct StoredHandout {
+    #[serde(flatten)]
+    handout: Handout,
+    file: String,
+    content_type: String,
+    /// Opens a private handout to those who were given its link
+    #[serde(default)]
+    key: Option<String>,
+}
+
+/// Handouts on disk
+#[derive(Debug, Clone)]
+pub struct HandoutStore {
+    dir: PathBuf,
+    /// How many bytes of handouts may be stored
+    pub limits: ResourceLimits,
+}
+
+impl HandoutStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            limits: ResourceLimits::default(),
+        }
+    }
+
+    /// Use the directory named by `DAGGERHEART_HANDOUT_DIR`, and the quotas
+    /// from the environment
+    pub fn from_env() -> Self {
+        Self {
+            limits: ResourceLimits::from_env(),
+            ..Self::new(
+                std::env::var(HANDOUT_DIR_ENV).unwrap_or_else(|_| DEFAULT_HANDOUT_DIR.to_string()),
+            )
+        }
+    }
+
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    /// Check and store a handout
+    pub fn save(
+        &self,
+        title: &str,
+        kind: HandoutKind,
+        content: &str,
+        character_ids: Vec<String>,
+    ) -> Result<Handout, String> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err("Handout title cannot be empty".to_string());
+        }
+        if title.chars().count() > MAX_HANDOUT_TITLE_LENGTH {
+            return Err(format!(
+                "Handout title is too long (max {} characters)",
+                MAX_HANDOUT_TITLE_LENGTH
+            ));
+        }
+
+        let (bytes, extension, content_type) = match kind {
+            HandoutKind::Text => (check_text(content)?, "txt", "text/plain; charset=utf-8"),
+            HandoutKind::Markdown => (check_text(content)?, "md", "text/markdown; charset=utf-8"),
+            HandoutKind::Image => decode_image(content)?,
+        };
+        self.limits
+            .check_upload(limits::dir_bytes(&self.dir), bytes.len())?;
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+
+        let id = Uuid::new_v4().to_string();
+        let file = format!("{}.{}", id, extension);
+        let key = (!character_ids.is_empty()).then(|| Uuid::new_v4().simple().to_string());
+        let url = match &key {
+            Some(key) => format!("/api/handouts/{}?key={}", id, key),
+            None => format!("/api/handouts/{}", id),
+        };
+        let stored = StoredHandout {
+            handout: Handout {
+                id: id.clone(),
+                title: title.to_string(),
+                kind,
+                character_ids,
+                shared_at: chrono::Utc::now().to_rfc3339(),
+                url,
+            },
+            file: file.clone(),
+            content_type: content_type.to_string(),
+            key,
+        };
+
+        fs::write(self.dir.join(&file), bytes)
+            .map_err(|e| format!("Failed to write handout: {}", e))?;
+        let json = serde_json::to_string_pretty(&stored)
+            .map_err(|e| format!("Failed to serialize handout: {}", e))?;
+        fs::write(self.dir.join(format!("{}.json", id)), json)
+            .map_err(|e| format!("Failed to write handout: {}", e))?;
+
+        Ok(stored.handout)
+    }
+
+    /// Every stored handout, oldest first
+    pub fn list(&self) -> Vec<Handout> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut handouts: Vec<Handout> = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .filter_map(|json| serde_json::from_str::<StoredHandout>(&json).ok())
+            .map(|stored| stored.handout)
+            .collect();
+        handouts.sort_by(|a, b| a.shared_at.cmp(&b.shared_at));
+        handouts
+    }
+
+    /// A handout's content and its MIME type
+    ///
+    /// A private handout also needs the key from its link, unless the caller
+    /// is `trusted` (holds the API token).
+    pub fn content(
+        &self,
+        id: &str,
+        key: Option<&str>,
+        trusted: bool,
+    ) -> Result<(Vec<u8>, String), String> {
+        // Only ever look up ids we handed out, never arbitrary paths
+        let id = Uuid::parse_str(id).map_err(|_| format!("Handout not found: {}", id))?;
+        let json = fs::read_to_string(self.dir.join(format!("{}.json", id)))
+            .map_err(|_| format!("Handout not found: {}", id))?;
+        let stored: StoredHandout =
+            serde_json::from_str(&json).map_err(|e| format!("Corrupt handout {}: {}", id, e))?;
+        let unlocked = match (&stored.key, key) {
+            (Some(expected), Some(key)) => constant_time_eq(expected.as_bytes(), key.as_bytes()),
+            _ => false,
+        };
+        if !stored.handout.is_public() && !trusted && !unlocked {
+            // Same answer as for a missing one, so ids can't be probed
+            return Err(format!("Handout not found: {}", id));
+        }
+        let bytes = fs::read(self.dir.join(&stored.file))
+            .map_err(|e| format!("Failed to read handout {}: {}", id, e))?;
+        Ok((bytes, stored.content_type))
+    }
+}
+
+/// The bytes of a text or markdown handout
+fn check_text(content: &str) -> Result<Vec<u8>, String> {
+    if content.trim().is_empty() {
+        return Err("Handout is empty".to_string());
+    }
+    if content.chars().count() > MAX_HANDOUT_TEXT_LENGTH {
+        return Err(format!(
+            "Handout is too long (max {} characters)",
+            MAX_HANDOUT_TEXT_LENGTH
+        ));
+    }
+    Ok(content.as_bytes().to_vec())
+}
+
+/// Decode a base64 image (optionally a `data:` URL), returning its bytes,
+/// file extension and MIME type
+fn decode_image(content: &str) -> Result<(Vec<u8>, &'static str, &'static str), String> {
+    let encoded = match content.split_once(";base64,") {
+        Some((prefix, data)) if prefix.starts_with("data:") => data,
+        _ => content,
+    };
+    let bytes = general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| "Image is not valid base64".to_string())?;
+    if bytes.len() > MAX_HANDOUT_IMAGE_BYTES {
+        return Err(format!(
+            "Image is too large (max {} MB)",
+            MAX_HANDOUT_IMAGE_BYTES / (1024 * 1024)
+        ));
+    }
+
+    let (extension, content_type) = match image::guess_format(&bytes) {
+        Ok(image::ImageFormat::Png) => ("png", "image/png"),
+        Ok(image::ImageFormat::Jpeg) => ("jpg", "image/jpeg"),
+        Ok(image::ImageFormat::Gif) => ("gif", "image/gif"),
+        Ok(image::ImageFormat::WebP) => ("webp", "image/webp"),
+        _ => return Err("Images must be PNG, JPEG, GIF or WebP".to_string()),
+    };
+    Ok((bytes, extension, content_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1x1 PNG
+    const PIXEL_PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    fn temp_store() -> HandoutStore {
+        HandoutStore::new(std::env::temp_dir().join(format!("handouts-{}", Uuid::new_v4())))
+    }
+
+    #[test]
+    fn test_save_and_fetch_handouts() {
+        let store = temp_store();
+        assert!(store.list().is_empty(), "no directory yet");
+
+        let letter = store
+            .save(
+                " A Letter ",
+                HandoutKind::Markdown,
+                "# Dear Theron",
+                Vec::new(),
+            )
+            .unwrap();
+        assert_eq!(letter.title, "A Letter");
+        assert!(letter.is_public());
+
+        let map = store
+            .save(
+                "Vault map",
+                HandoutKind::Image,
+                &format!("data:image/png;base64,{}", PIXEL_PNG),
+                vec!["c1".to_string()],
+            )
+            .unwrap();
+        assert!(map.is_for("c1"));
+        assert!(!map.is_for("c2"));
+
+        let (bytes, content_type) = store.content(&letter.id, None, false).unwrap();
+        assert_eq!(bytes, b"# Dear Theron");
+        assert_eq!(content_type, "text/markdown; charset=utf-8");
+
+        // A private handout opens with the key from its link, or the API token
+        let (_, key) = map.url.split_once("?key=").unwrap();
+        let (_, content_type) = store.content(&map.id, Some(key), false).unwrap();
+        assert_eq!(content_type, "image/png");
+        assert!(store.content(&map.id, None, false).is_err());
+        assert!(store.content(&map.id, Some("guess"), false).is_err());
+        assert!(store.content(&map.id, None, true).is_ok());
+        assert!(!letter.url.contains("key="));
+
+        assert_eq!(store.list().len(), 2);
+        assert!(store.content("../secrets", None, true).is_err());
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bad_handouts() {
+        let store = temp_store();
+        assert!(store.save("", HandoutKind::Text, "hi", Vec::new()).is_err());
+        assert!(store
+            .save("Note", HandoutKind::Text, "  ", Vec::new())
+            .is_err());
+        assert!(store
+            .save("Map", HandoutKind::Image, "not base64!", Vec::new())
+            .is_err());
+        let text_as_image = general_purpose::STANDARD.encode("just text");
+        assert!(store
+            .save("Map", HandoutKind::Image, &text_as_image, Vec::new())
+            .is_err());
+        assert!(store.list().is_empty(), "nothing written");
+    }
+
+    #[test]
+    fn test_handout_storage_quota() {
+        let mut store = temp_store();
+        store.limits.max_upload_mb = 1;
+
+        store
+            .save("Note", HandoutKind::Text, "hi", Vec::new())
+            .unwrap();
+        fs::write(store.dir().join("filler"), vec![0; 1024 * 1024]).unwrap();
+        assert_eq!(
+            store
+                .save("Note", HandoutKind::Text, "hi", Vec::new())
+                .unwrap_err(),
+            "Upload storage limit reached (1 MB max)"
+        );
+        assert_eq!(store.list().len(), 1);
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+//! End-to-end test support: the whole app on a local port, driven by
+//! scripted WebSocket clients
+//!
+//! ```ignore
+//! let server = TestServer::start().await;
+//! let mut gm = server.gm().await;
+//! let mut player = server.player().await;
+//! player.send("create_character", json!({ ... })).await;
+//! let created = player.expect("character_created").await;
+//! ```
+//!
+//! Clients speak JSON as the browser does, and [`TestClient::expect`] skips
+//! whatever else arrives until the message it's waiting for. Each server
+//! keeps its saves, maps and handouts in a directory of its own, removed
+//! when it's dropped.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, RwLock};
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::assets::ClientAssets;
+use crate::config::ServerConfig;
+use crate::encounters::EncounterStore;
+use crate::game::GameState;
+use crate::handouts::HandoutStore;
+use crate::maps::MapStore;
+use crate::metrics::Metrics;
+use crate::movement::MoveThrottle;
+use crate::save::FileSaveStore;
+use crate::security::SecurityConfig;
+use crate::sheets::SheetTracker;
+use crate::vault::Vault;
+use crate::websocket::AppState;
+
+/// How long [`TestClient::expect`] waits before failing the test
+const EXPECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The server, listening on a free local port
+pub struct TestServer {
+    pub state: AppState,
+    addr: SocketAddr,
+    dir: PathBuf,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        let dir = std::env::temp_dir().join(format!("harness-{}", uuid::Uuid::new_v4()));
+        let config = ServerConfig {
+            saves_dir: dir.join("saves"),
+            // Scripted clients send faster than anyone drags
+            move_updates_per_second: 0.0,
+            ..Default::default()
+        };
+        let (broadcaster, _) = broadcast::channel(config.broadcast_capacity);
+        let state = AppState {
+            game: Arc::new(RwLock::new(GameState::new())),
+            broadcaster,
+            security: Arc::new(SecurityConfig::default()),
+            journal: None,
+            handouts: Arc::new(HandoutStore::new(dir.join("handouts"))),
+            maps: Arc::new(MapStore::new(dir.join("maps"))),
+            vault: Arc::new(Vault::new(dir.join("vault.json"))),
+            encounters: Arc::new(EncounterStore::new(dir.join("encounters.json"))),
+            sheets: Arc::new(SheetTracker::new()),
+            history: None,
+            saves: Arc::new(FileSaveStore::new(dir.join("saves"))),
+            client: Arc::new(ClientAssets::Embedded),
+            metrics: Arc::new(Metrics::new()),
+            moves: Arc::new(MoveThrottle::new(config.move_updates_per_second)),
+            config: Arc::new(config),
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = crate::router(state.clone());
+        tokio::spawn(async move {
+            let _ = axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await;
+        });
+
+        Self { state, addr, dir }
+    }
+
+    /// Open a WebSocket with these query parameters (e.g. `role=gm` or
+    /// `session=...`), once the server has said who it is
+    pub async fn connect(&self, query: &str) -> TestClient {
+        let url = format!("ws://{}/ws?{}", self.addr, query);
+        let (ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .expect("WebSocket connects");
+        let mut client = TestClient {
+            ws,
+            connection_id: String::new(),
+            session_token: String::new(),
+        };
+        let connected = client.expect("connected").await;
+        client.connection_id = connected["connection_id"].as_str().unwrap().to_string();
+        client.session_token = connected["session_token"].as_str().unwrap().to_string();
+        client
+    }
+
+    pub async fn player(&self) -> TestClient {
+        self.connect("").await
+    }
+
+    pub async fn gm(&self) -> TestClient {
+        self.connect("role=gm").await
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A scripted player or GM
+pub struct TestClient {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    pub connection_id: String,
+    pub session_token: String,
+}
+
+impl TestClient {
+    /// Send a client message; `Value::Null` sends it without a payload
+    pub async fn send(&mut self, message_type: &str, payload: Value) {
+        let message = if payload.is_null() {
+            json!({ "type": message_type })
+        } else {
+            json!({ "type": message_type, "payload": payload })
+        };
+        self.ws
+            .send(Message::Text(message.to_string()))
+            .await
+            .expect("message sent");
+    }
+
+    /// The payload of the next message of this type, skipping the rest
+    pub async fn expect(&mut self, message_type: &str) -> Value {
+        self.expect_where(message_type, |_| true).await
+    }
+
+    /// The payload of the next message of this type that `matches`
+    pub async fn expect_where(
+        &mut self,
+        message_type: &str,
+        matches: impl Fn(&Value) -> bool,
+    ) -> Value {
+        let wait = async {
+            while let Some(frame) = self.ws.next().await {
+                let Ok(Message::Text(text)) = frame else {
+                    continue;
+                };
+                let message: Value = serde_json::from_str(&text).expect("server sends JSON");
+                if message["type"] == message_type && matches(&message["payload"]) {
+                    return Some(message["payload"].clone());
+                }
+            }
+            None
+        };
+        match tokio::time::timeout(EXPECT_TIMEOUT, wait).await {
+            Ok(Some(payload)) => payload,
+            Ok(None) => panic!("connection closed waiting for {}", message_type),
+            Err(_) => panic!("no {} within {:?}", message_type, EXPECT_TIMEOUT),
+        }
+    }
+
+    /// Hang up, as a phone dropping off the WiFi would
+    pub async fn close(mut self) {
+        let _ = self.ws.close(None).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A player joins with a new character; its id
+    async fn create_character(player: &mut TestClient, name: &str) -> String {
+        player
+            .send(
+                "create_character",
+                json!({
+                    "name": name,
+                    "class": "Warrior",
+                    "ancestry": "Human",
+                    "attributes": [2, 1, 1, 0, 0, -1],
+                }),
+            )
+            .await;
+        let created = player
+            .expect_where("character_created", |p| p["character"]["name"] == name)
+            .await;
+        created["character_id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_roll_request_reaches_player_and_result_reaches_gm() {
+        let server = TestServer::start().await;
+        let mut gm = server.gm().await;
+        let mut player = server.player().await;
+        let character_id = create_character(&mut player, "Theron").await;
+
+        gm.send(
+            "request_roll",
+            json!({
+                "target_type": "specific",
+                "target_character_ids": [character_id],
+                "roll_type": "action",
+                "attribute": "agility",
+                "difficulty": 12,
+                "context": "Leap the chasm",
+                "narrative_stakes": null,
+                "situational_modifier": 0,
+                "has_advantage": false,
+                "is_combat": false,
+            }),
+        )
+        .await;
+        let requested = player.expect("roll_requested").await;
+        assert_eq!(requested["character_id"], character_id);
+
+        player
+            .send(
+                "execute_roll",
+                json!({
+                    "request_id": requested["request_id"],
+                    "spend_hope_for_bonus": false,
+                    "chosen_experience": null,
+                }),
+            )
+            .await;
+        let result = gm.expect("detailed_roll_result").await;
+        assert_eq!(result["character_id"], character_id);
+        assert_eq!(result["request_id"], requested["request_id"]);
+        assert_eq!(server.state.game.read().await.characters.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_players_are_refused_gm_messages() {
+        let server = TestServer::start().await;
+        let mut player = server.player().await;
+        let character_id = create_character(&mut player, "Theron").await;
+
+        for (message_type, payload) in [
+            (
+                "grant_item",
+                json!({
+                    "character_id": character_id,
+                    "name": "Vorpal Blade",
+                    "description": "Snicker-snack",
+                    "quantity": 1,
+                }),
+            ),
+            (
+                "award_gold",
+                json!({ "character_id": character_id, "handfuls": 50 }),
+            ),
+            ("create_npc", json!({ "name": "Mysterious Stranger" })),
+            ("convert_npc", json!({ "character_id": character_id })),
+            ("rollback_to_checkpoint", json!({ "checkpoint_id": null })),
+            (
+                "schedule_next_session",
+                json!({ "scheduled_at": "2026-11-01T19:00:00Z", "notes": "" }),
+            ),
+            ("clear_next_session", Value::Null),
+            (
+                "start_cutaway",
+                json!({ "character_ids": [character_id], "label": "Alone at last" }),
+            ),
+            ("end_cutaway", Value::Null),
+        ] {
+            player.send(message_type, payload).await;
+            let refused = player.expect("error").await;
+            assert_eq!(refused["code"], "forbidden");
+            assert_eq!(refused["request_type"], message_type);
+        }
+
+        let game = server.state.game.read().await;
+        assert_eq!(game.characters.len(), 1);
+        assert!(game.adversaries.is_empty());
+        let theron = game.characters.values().next().unwrap();
+        assert_eq!(theron.inventory, Default::default());
+    }
+
+    #[tokio::test]
+    async fn test_player_reconnecting_mid_combat_reclaims_character() {
+        let server = TestServer::start().await;
+        let mut gm = server.gm().await;
+        let mut player = server.player().await;
+        let character_id = create_character(&mut player, "Mira").await;
+
+        gm.send("start_combat", Value::Null).await;
+        player.expect("combat_started").await;
+        gm.expect("combat_started").await;
+
+        // Dropping out mid-fight leaves the character on the map
+        let session = player.session_token.clone();
+        let old_connection = player.connection_id.clone();
+        player.close().await;
+        let ghosted = gm.expect("character_ghosted").await;
+        assert_eq!(ghosted["character_id"], character_id);
+
+        let mut player = server.connect(&format!("session={}", session)).await;
+        assert_ne!(player.connection_id, old_connection);
+        player
+            .send("select_character", json!({ "character_id": character_id }))
+            .await;
+        player.expect("character_selected").await;
+        let reclaimed = gm.expect("character_reclaimed").await;
+        assert_eq!(reclaimed["character_id"], character_id);
+    }
+}
@@ -0,0 +1,55 @@
+//! Session journaling for the replay bot
+//!
+//! Set `DAGGERHEART_JOURNAL` to a file path to record every connection,
+//! client message and broadcast of this server run (the file is truncated on
+//! startup). Replay it with `tools/replay-bot`.
+
+use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::protocol::journal::{JournalEntry, JournalRecord};
+
+/// Journal file path; journaling is off when unset
+pub const JOURNAL_ENV: &str = "DAGGERHEART_JOURNAL";
+
+/// An open session journal
+pub struct Journal {
+    started: Instant,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// Open the journal named by `DAGGERHEART_JOURNAL`, if set
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var(JOURNAL_ENV).ok()?;
+        match Self::create(&path) {
+            Ok(journal) => Some(journal),
+            Err(e) => {
+                tracing::warn!("Session journal disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Start a new journal at `path`
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        Ok(Self {
+            started: Instant::now(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append a record stamped with the time since recording started
+    pub fn record(&self, record: JournalRecord) {
+        let entry = JournalEntry::new(self.started.elapsed().as_millis() as u64, record);
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        if let Err(e) = writeln!(file, "{}", entry.to_line()) {
+            tracing::warn!("Failed to write session journal: {}", e);
+        }
+    }
+}
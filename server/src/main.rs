@@ -1,15 +1,41 @@
 // Daggerheart VTT Server
 // Phase 4: Save/Load & GM Controls
 
-mod adversaries;
-mod game;
-mod protocol;
+mod assets;
+mod backup;
+mod config;
+mod encounters;
+mod handouts;
+#[cfg(test)]
+mod harness;
+mod journal;
+mod maps;
+mod metrics;
+mod movement;
+mod openapi;
+mod ratelimit;
+mod recorder;
+mod reminders;
 mod routes;
-mod save;
+mod security;
+mod sheets;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod storage;
+mod vault;
 mod websocket;
 
+// Game rules live in the core crate; re-exported so `crate::game` etc. resolve
+use daggerheart_vtt_core::{
+    adversaries, auras, damage, demo, dice, environments, equipment, game, hazards, history,
+    leveling, limits, narrative, preferences, protocol, recap, rng, save, schedule, settings,
+    snapshot, subclasses, validation, visibility,
+};
+
 use axum::{
-    routing::{any, get},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{any, delete, get, post, put},
     Router,
 };
 use std::net::UdpSocket;
@@ -17,7 +43,26 @@ use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use tower_http::services::ServeDir;
 
+use crate::adversaries::{ADVERSARY_DIR_ENV, DEFAULT_ADVERSARY_DIR};
+use crate::assets::ClientAssets;
+use crate::backup::MAX_BACKUP_BYTES;
+use crate::config::ServerConfig;
+use crate::encounters::EncounterStore;
 use crate::game::GameState;
+use crate::handouts::HandoutStore;
+use crate::journal::Journal;
+use crate::limits::ResourceLimits;
+use crate::maps::{MapStore, MAP_URL_PREFIX};
+use crate::metrics::Metrics;
+use crate::movement::MoveThrottle;
+use crate::protocol::journal::JournalRecord;
+use crate::protocol::maps::MAX_MAP_IMAGE_BYTES;
+use crate::recorder::HistoryRecorder;
+use crate::rng::DiceRng;
+use crate::security::SecurityConfig;
+use crate::settings::{DEFAULT_SETTINGS_FILE, SETTINGS_FILE_ENV};
+use crate::sheets::SheetTracker;
+use crate::vault::Vault;
 use crate::websocket::AppState;
 
 /// Get the local network IP address
@@ -32,8 +77,99 @@ fn get_local_ip() -> String {
     "localhost".to_string()
 }
 
+/// Every route the server answers
+fn router(app_state: AppState) -> Router {
+    // REST routes that change game state or hand out everything on disk
+    // (token-protected when configured)
+    let mutation_routes = Router::new()
+        .route("/api/save", post(routes::save_game))
+        .route("/api/load", post(routes::load_game))
+        .route("/api/saves", delete(routes::delete_save))
+        .route("/api/saves/rename", post(routes::rename_save))
+        .route("/api/backup", get(routes::backup))
+        .route(
+            "/api/restore",
+            post(routes::restore_backup).layer(DefaultBodyLimit::max(MAX_BACKUP_BYTES)),
+        )
+        .route("/api/demo", post(routes::load_demo))
+        .route("/api/dice/seed", post(routes::seed_dice))
+        .route("/api/notes", post(routes::create_note))
+        .route("/api/notes/all", get(routes::all_notes))
+        .route(
+            "/api/notes/:id",
+            put(routes::update_note).delete(routes::delete_note),
+        )
+        .route("/api/adversaries/reload", post(routes::reload_adversaries))
+        .route("/api/narrative/templates", post(routes::set_narrative))
+        .route(
+            "/api/maps",
+            post(routes::upload_map).layer(DefaultBodyLimit::max(MAX_MAP_IMAGE_BYTES + 64 * 1024)),
+        )
+        .route("/api/admin/validate", post(routes::validate_state))
+        // A replayed game holds the GM's private notes
+        .route("/api/history", get(routes::history))
+        .route("/api/history/:seq", get(routes::history_at))
+        .route("/api/history/:seq/restore", post(routes::restore_history))
+        .route("/api/characters/import", post(routes::import_character))
+        .route("/api/characters/:id", delete(routes::delete_character))
+        .route("/api/characters/:id/retire", post(routes::retire_character))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            security::require_api_token,
+        ));
+
+    Router::new()
+        .route("/", get(routes::index))
+        .route("/mobile", get(routes::mobile))
+        .route("/gm", get(routes::gm))
+        .route("/api/qr-code", get(routes::qr_code))
+        .route("/healthz", get(routes::healthz))
+        .route("/readyz", get(routes::readyz))
+        .route("/metrics", get(routes::metrics))
+        .route("/api/game-state", get(routes::game_state))
+        .route("/api/events", get(routes::events))
+        .route("/api/events/scenes", get(routes::scene_log))
+        .route("/api/roll-stats", get(routes::roll_stats))
+        .route("/api/recap", get(routes::recap))
+        .route("/api/snapshot", get(routes::snapshot))
+        .route("/api/saves", get(routes::list_saves))
+        .route("/api/adversaries", get(routes::adversary_templates))
+        .route("/api/environments", get(routes::environment_templates))
+        .route("/api/narrative", get(routes::narrative))
+        .route("/api/settings", get(routes::settings))
+        .route("/api/checkpoints", get(routes::checkpoints))
+        .route("/api/next-session", get(routes::next_session))
+        .route("/api/notes", get(routes::notes))
+        .route("/api/quests", get(routes::quests))
+        .route("/api/chat", get(routes::chat))
+        .route("/api/handouts", get(routes::handouts))
+        .route("/api/handouts/:id", get(routes::handout_content))
+        .route("/api/maps", get(routes::maps))
+        .route("/api/vault", get(routes::vault))
+        .route("/api/encounters", get(routes::encounter_presets))
+        .route("/api/encounters/budget", post(routes::encounter_budget))
+        .route("/api/characters/:id/export", get(routes::export_character))
+        .route("/api/combats/:id/keyframes", get(routes::combat_keyframes))
+        .merge(mutation_routes)
+        // The REST API's OpenAPI spec, and Swagger UI to try it
+        .merge(openapi::swagger_ui())
+        .route("/ws", any(websocket::websocket_handler))
+        // Client scripts and styles, built in or from --client-dir
+        .route("/static/*path", get(assets::static_file))
+        .nest_service(MAP_URL_PREFIX, ServeDir::new(app_state.maps.dir()))
+        .with_state(app_state)
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", config::USAGE);
+        return Ok(());
+    }
+    let config =
+        ServerConfig::load(&args, |var| std::env::var(var).ok()).map_err(anyhow::Error::msg)?;
+
     // Initialize logging
     tracing_subscriber::fmt::init();
 
@@ -44,51 +180,192 @@ async fn main() -> anyhow::Result<()> {
     let local_ip = get_local_ip();
 
     // Create game state
-    let game_state = Arc::new(RwLock::new(GameState::new()));
+    let mut game = GameState::new();
+    game.limits = ResourceLimits::from_env();
+    game.adversary_dir = std::env::var(ADVERSARY_DIR_ENV)
+        .unwrap_or_else(|_| DEFAULT_ADVERSARY_DIR.to_string())
+        .into();
+    match game.reload_adversary_templates() {
+        Ok(count) => tracing::info!(
+            "👹 Loaded {} homebrew adversary templates from {}",
+            count,
+            game.adversary_dir.display()
+        ),
+        Err(e) => tracing::warn!("Failed to load homebrew adversaries: {}", e),
+    }
+    let settings_file =
+        std::env::var(SETTINGS_FILE_ENV).unwrap_or_else(|_| DEFAULT_SETTINGS_FILE.to_string());
+    match settings::load_defaults(settings_file.as_ref()) {
+        Ok(settings) => {
+            game.fear_pool = settings.starting_fear;
+            game.settings = settings;
+        }
+        Err(e) => tracing::warn!("Failed to load table settings, using defaults: {}", e),
+    }
+    if let Some(seed) = config.dice_seed {
+        game.dice = DiceRng::seeded(seed);
+        tracing::info!("🎲 Dice seeded with {}; every run rolls the same", seed);
+    }
+    let history = HistoryRecorder::from_env(&game).map(Arc::new);
+    let game_state = Arc::new(RwLock::new(game));
 
     // Create broadcast channel for WebSocket messages
-    let (broadcaster, _) = broadcast::channel::<String>(100);
+    let (broadcaster, _) = broadcast::channel::<protocol::ServerMessage>(config.broadcast_capacity);
+
+    let security = SecurityConfig::from_env();
+    if security.api_token.is_some() {
+        tracing::info!("🔒 API token required for save/load routes");
+    }
 
     let app_state = AppState {
         game: game_state,
         broadcaster,
+        security: Arc::new(security),
+        journal: Journal::from_env().map(Arc::new),
+        handouts: Arc::new(HandoutStore::from_env()),
+        maps: Arc::new(MapStore::from_env()),
+        vault: Arc::new(Vault::from_env()),
+        encounters: Arc::new(EncounterStore::from_env()),
+        sheets: Arc::new(SheetTracker::new()),
+        history,
+        saves: storage::from_env(&config.saves_dir),
+        client: Arc::new(ClientAssets::new(config.client_dir.clone())),
+        metrics: Arc::new(Metrics::new()),
+        moves: Arc::new(MoveThrottle::new(config.move_updates_per_second)),
+        config: Arc::new(config),
     };
+    tracing::info!(
+        "📜 Handouts stored in {}",
+        app_state.handouts.dir().display()
+    );
+    tracing::info!("🗺️ Maps stored in {}", app_state.maps.dir().display());
+    if let ClientAssets::Dir(dir) = app_state.client.as_ref() {
+        tracing::info!("🧩 Serving the client from {}", dir.display());
+    }
+    tracing::info!(
+        "📦 Retired characters kept in {}",
+        app_state.vault.path().display()
+    );
+    tracing::info!(
+        "⚔️ Encounter presets kept in {}",
+        app_state.encounters.path().display()
+    );
 
-    // Build application routes
-    let app = Router::new()
-        .route("/", get(routes::index))
-        .route("/mobile", get(routes::mobile))
-        .route("/gm", get(routes::gm))
-        .route("/api/qr-code", get(routes::qr_code))
-        .route("/api/game-state", get(routes::game_state))
-        .route("/api/events", get(routes::events))
-        .route("/api/save", axum::routing::post(routes::save_game))
-        .route("/api/saves", get(routes::list_saves))
-        .route("/api/load", axum::routing::post(routes::load_game))
-        .route("/ws", any(websocket::websocket_handler))
-        // Serve static files from client directory
-        .nest_service("/static", ServeDir::new("../client"))
-        .with_state(app_state);
+    // Record every broadcast alongside the client messages
+    if let Some(journal) = app_state.journal.clone() {
+        tracing::info!("📼 Recording session journal");
+        let mut rx = app_state.broadcaster.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(message) => journal.record(JournalRecord::Server {
+                        message: message.to_json(),
+                    }),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    // Append each change to the session history as it's broadcast
+    if let Some(history) = app_state.history.clone() {
+        tracing::info!(
+            "🗂️ Recording session history to {}",
+            history.path().display()
+        );
+        let history_state = app_state.clone();
+        let mut rx = app_state.broadcaster.subscribe();
+        tokio::spawn(async move {
+            // Falling behind still means something changed; only a closed
+            // channel ends it
+            while let Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) = rx.recv().await {
+                // One look covers a burst of broadcasts
+                while matches!(
+                    rx.try_recv(),
+                    Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_))
+                ) {}
+                let game = history_state.game.read().await;
+                history.record(&game);
+            }
+        });
+    }
+
+    // Snapshot token positions during combat for the post-fight scrubber
+    {
+        let keyframe_state = app_state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(game::KEYFRAME_INTERVAL);
+            loop {
+                interval.tick().await;
+                let mut game = keyframe_state.game.write().await;
+                websocket::capture_keyframe(&keyframe_state, &mut game);
+            }
+        });
+    }
+
+    // Post a reminder to a webhook (e.g. Discord) ahead of the next session
+    if let Some(config) = reminders::ReminderConfig::from_env() {
+        tracing::info!(
+            "⏰ Session reminders go out {} hours ahead",
+            config.lead.num_hours()
+        );
+        reminders::spawn(app_state.clone(), config);
+    }
+
+    // SIGHUP reloads homebrew adversary templates without a restart
+    #[cfg(unix)]
+    {
+        let reload_state = app_state.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!("Cannot listen for SIGHUP: {}", e);
+                    return;
+                }
+            };
+            while hangups.recv().await.is_some() {
+                match routes::reload_adversary_templates(&reload_state).await {
+                    Ok(count) => {
+                        tracing::info!("👹 Reloaded {} homebrew adversary templates", count)
+                    }
+                    Err(e) => tracing::warn!("Failed to reload homebrew adversaries: {}", e),
+                }
+            }
+        });
+    }
+
+    let config = app_state.config.clone();
+    let app = router(app_state);
 
     // Determine server address
-    let addr = "0.0.0.0:3000";
+    let addr = config.addr();
+    let port = addr.port();
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
     tracing::info!("✅ Server listening on http://{}", addr);
     tracing::info!("");
     tracing::info!("📡 Network Access:");
-    tracing::info!("   Local IP:    http://{}:3000", local_ip);
-    tracing::info!("   Localhost:   http://localhost:3000");
+    tracing::info!("   Local IP:    http://{}:{}", local_ip, port);
+    tracing::info!("   Localhost:   http://localhost:{}", port);
     tracing::info!("");
-    tracing::info!("🖥️  TV View:     http://{}:3000", local_ip);
-    tracing::info!("📱 Mobile View: http://{}:3000/mobile", local_ip);
-    tracing::info!("🔌 WebSocket:   ws://{}:3000/ws", local_ip);
+    tracing::info!("🖥️  TV View:     http://{}:{}", local_ip, port);
+    tracing::info!("📱 Mobile View: http://{}:{}/mobile", local_ip, port);
+    tracing::info!("🔌 WebSocket:   ws://{}:{}/ws", local_ip, port);
     tracing::info!("");
     tracing::info!("💡 Scan the QR code on TV to join from your phone!");
     tracing::info!("Press Ctrl+C to stop the server");
 
     // Start server
-    axum::serve(listener, app).await?;
+    // Connection addresses are kept so the GM can ban them
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,257 @@
+//! Battle map storage
+//!
+//! Each uploaded map is `<id>.<ext>` in the map directory, served as-is
+//! under `/maps`, plus `<id>.json` with its scene, upload time and layout.
+
+use std::fs;
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use crate::limits::{self, ResourceLimits};
+use crate::protocol::maps::{
+    MapInfo, SceneLayout, DEFAULT_SCENE_HEIGHT, DEFAULT_SCENE_WIDTH, MAX_MAP_IMAGE_BYTES,
+    MAX_SCENE_NAME_LENGTH,
+};
+
+/// Directory maps are stored in
+pub const MAP_DIR_ENV: &str = "DAGGERHEART_MAP_DIR";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_MAP_DIR: &str = "maps";
+
+/// URL prefix the map directory is served under
+pub const MAP_URL_PREFIX: &str = "/maps";
+
+/// Maps on disk
+#[derive(Debug, Clone)]
+pub struct MapStore {
+    dir: PathBuf,
+    /// How many maps, and how many bytes of them, may be stored
+    pub limits: ResourceLimits,
+}
+
+impl MapStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            limits: ResourceLimits::default(),
+        }
+    }
+
+    /// Use the directory named by `DAGGERHEART_MAP_DIR`, and the quotas from
+    /// the environment
+    pub fn from_env() -> Self {
+        Self {
+            limits: ResourceLimits::from_env(),
+            ..Self::new(std::env::var(MAP_DIR_ENV).unwrap_or_else(|_| DEFAULT_MAP_DIR.to_string()))
+        }
+    }
+
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    /// Check and store an uploaded map image
+    pub fn save(&self, scene: &str, bytes: &[u8]) -> Result<MapInfo, String> {
+        let scene = scene.trim();
+        if scene.is_empty() {
+            return Err("Name the scene the map is for".to_string());
+        }
+        if scene.chars().count() > MAX_SCENE_NAME_LENGTH {
+            return Err(format!(
+                "Scene name is too long (max {} characters)",
+                MAX_SCENE_NAME_LENGTH
+            ));
+        }
+        if bytes.len() > MAX_MAP_IMAGE_BYTES {
+            return Err(format!(
+                "Map is too large (max {} MB)",
+                MAX_MAP_IMAGE_BYTES / (1024 * 1024)
+            ));
+        }
+        let extension = match image::guess_format(bytes) {
+            Ok(image::ImageFormat::Png) => "png",
+            Ok(image::ImageFormat::Jpeg) => "jpg",
+            Ok(image::ImageFormat::WebP) => "webp",
+            _ => return Err("Maps must be PNG, JPEG or WebP".to_string()),
+        };
+        self.limits.check_scenes(self.list().len())?;
+        self.limits
+            .check_upload(limits::dir_bytes(&self.dir), bytes.len())?;
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+
+        let id = Uuid::new_v4().to_string();
+        let map = MapInfo {
+            id: id.clone(),
+            scene: scene.to_string(),
+            url: format!("{}/{}.{}", MAP_URL_PREFIX, id, extension),
+            uploaded_at: chrono::Utc::now().to_rfc3339(),
+            layout: SceneLayout::default(),
+        };
+
+        fs::write(self.dir.join(format!("{}.{}", id, extension)), bytes)
+            .map_err(|e| format!("Failed to write map: {}", e))?;
+        self.write_info(&map)?;
+
+        Ok(map)
+    }
+
+    /// Change the size and grid of an uploaded map's scene
+    pub fn set_layout(&self, id: &str, layout: SceneLayout) -> Result<MapInfo, String> {
+        layout.validate()?;
+        let mut map = self.get(id)?;
+        map.layout = layout;
+        self.write_info(&map)?;
+        Ok(map)
+    }
+
+    fn write_info(&self, map: &MapInfo) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(map)
+            .map_err(|e| format!("Failed to serialize map: {}", e))?;
+        fs::write(self.dir.join(format!("{}.json", map.id)), json)
+            .map_err(|e| format!("Failed to write map: {}", e))
+    }
+
+    /// Every uploaded map, oldest first
+    pub fn list(&self) -> Vec<MapInfo> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut maps: Vec<MapInfo> = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        maps.sort_by(|a, b| a.uploaded_at.cmp(&b.uploaded_at));
+        maps
+    }
+
+    /// The demo campaign's background, painted and stored the first time
+    pub fn demo_background(&self, scene: &str) -> Result<MapInfo, String> {
+        if let Some(map) = self.list().into_iter().find(|m| m.scene == scene) {
+            return Ok(map);
+        }
+        let png = paint_demo_background()?;
+        self.save(scene, &png)
+    }
+
+    /// Look up an uploaded map
+    pub fn get(&self, id: &str) -> Result<MapInfo, String> {
+        let not_found = || format!("Map not found: {}", id);
+        // Only ever look up ids we handed out, never arbitrary paths
+        let id = Uuid::parse_str(id).map_err(|_| not_found())?;
+        let json =
+            fs::read_to_string(self.dir.join(format!("{}.json", id))).map_err(|_| not_found())?;
+        serde_json::from_str(&json).map_err(|e| format!("Corrupt map {}: {}", id, e))
+    }
+}
+
+/// A clearing in dusky grass with a campfire where the demo's goblins camp
+fn paint_demo_background() -> Result<Vec<u8>, String> {
+    let (width, height) = (DEFAULT_SCENE_WIDTH as u32, DEFAULT_SCENE_HEIGHT as u32);
+    let (fire_x, fire_y) = (620.0, 290.0);
+    let image = image::RgbImage::from_fn(width, height, |x, y| {
+        let distance = (x as f32 - fire_x).hypot(y as f32 - fire_y);
+        // The road in from the west, which the party comes along
+        let on_road = (y as f32 - (290.0 + (x as f32 / 90.0).sin() * 25.0)).abs() < 30.0
+            && (x as f32) < fire_x;
+        let shade = ((x * 7 + y * 13) % 17) as u8;
+        match distance {
+            d if d < 18.0 => image::Rgb([235, 120, 40]),
+            d if d < 26.0 => image::Rgb([90, 80, 75]),
+            d if d < 190.0 => image::Rgb([120 + shade, 95 + shade, 65]),
+            _ if on_road => image::Rgb([105 + shade, 85 + shade, 60]),
+            _ => image::Rgb([45, 75 + shade, 50]),
+        }
+    });
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to paint the demo map: {}", e))?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose, Engine as _};
+
+    /// A 1x1 PNG
+    const PIXEL_PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn test_save_and_get_maps() {
+        let store = MapStore::new(std::env::temp_dir().join(format!("maps-{}", Uuid::new_v4())));
+        let png = general_purpose::STANDARD.decode(PIXEL_PNG).unwrap();
+
+        assert!(store.save(" ", &png).is_err());
+        assert!(store.save("The Vault", b"not an image").is_err());
+        assert!(store.list().is_empty(), "nothing written");
+
+        let map = store.save(" The Vault ", &png).unwrap();
+        assert_eq!(map.scene, "The Vault");
+        assert_eq!(map.url, format!("/maps/{}.png", map.id));
+        assert!(store.dir().join(format!("{}.png", map.id)).exists());
+
+        assert_eq!(store.get(&map.id).unwrap(), map);
+        assert_eq!(store.list(), vec![map.clone()]);
+        assert!(store.get("../settings").is_err());
+
+        let layout = SceneLayout {
+            width: 1200.0,
+            ..SceneLayout::default()
+        };
+        let map = store.set_layout(&map.id, layout).unwrap();
+        assert_eq!(store.get(&map.id).unwrap().layout, layout);
+        let too_big = SceneLayout {
+            width: 1.0e6,
+            ..SceneLayout::default()
+        };
+        assert!(store.set_layout(&map.id, too_big).is_err());
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_map_quotas() {
+        let mut store =
+            MapStore::new(std::env::temp_dir().join(format!("maps-{}", Uuid::new_v4())));
+        store.limits.max_scenes = 2;
+        store.limits.max_upload_mb = 1;
+        let png = general_purpose::STANDARD.decode(PIXEL_PNG).unwrap();
+
+        store.save("The Vault", &png).unwrap();
+        // Something else fills the directory up
+        fs::write(store.dir().join("filler"), vec![0; 1024 * 1024]).unwrap();
+        assert_eq!(
+            store.save("The Docks", &png).unwrap_err(),
+            "Upload storage limit reached (1 MB max)"
+        );
+        fs::remove_file(store.dir().join("filler")).unwrap();
+        store.save("The Docks", &png).unwrap();
+        assert_eq!(
+            store.save("The Tower", &png).unwrap_err(),
+            "Scene limit reached (2 max)"
+        );
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_demo_background_is_painted_once() {
+        let store = MapStore::new(std::env::temp_dir().join(format!("maps-{}", Uuid::new_v4())));
+
+        let map = store.demo_background("Goblin Camp").unwrap();
+        assert_eq!(map.scene, "Goblin Camp");
+        assert!(store.dir().join(format!("{}.png", map.id)).exists());
+        assert_eq!(store.demo_background("Goblin Camp").unwrap(), map);
+        assert_eq!(store.list().len(), 1);
+
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+}
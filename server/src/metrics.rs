@@ -0,0 +1,223 @@
+//! Counters for `/metrics`, in the Prometheus text format
+//!
+//! Gauges (connections, characters…) are read from the game when scraped;
+//! the rest is counted as it happens. Message counts are labelled with the
+//! client message's `type`, and only messages that parsed are counted, so
+//! the label can't grow past the protocol's message types.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the save duration histogram, in seconds
+const SAVE_BUCKETS: [f64; 8] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 5.0];
+
+/// What the game looks like when scraped
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gauges {
+    pub connections: usize,
+    pub characters: usize,
+    pub adversaries: usize,
+    /// Broadcasts the slowest client has yet to receive
+    pub broadcast_queue: usize,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Count per bucket in `SAVE_BUCKETS` (not cumulative)
+    buckets: [u64; SAVE_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    messages: Mutex<BTreeMap<String, u64>>,
+    lagged_messages: AtomicU64,
+    lagged_connections: AtomicU64,
+    rate_limited: AtomicU64,
+    save_durations: Mutex<Histogram>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            messages: Mutex::new(BTreeMap::new()),
+            lagged_messages: AtomicU64::new(0),
+            lagged_connections: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0),
+            save_durations: Mutex::new(Histogram::default()),
+        }
+    }
+
+    /// A client message of this `type` was received
+    pub fn message_received(&self, message_type: &str) {
+        if let Ok(mut messages) = self.messages.lock() {
+            *messages.entry(message_type.to_string()).or_default() += 1;
+        }
+    }
+
+    /// A client fell this many broadcasts behind
+    pub fn broadcast_lagged(&self, skipped: u64) {
+        self.lagged_connections.fetch_add(1, Ordering::Relaxed);
+        self.lagged_messages.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    /// A client message was dropped for going over its rate limit
+    pub fn rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn save_finished(&self, took: Duration) {
+        let seconds = took.as_secs_f64();
+        if let Ok(mut histogram) = self.save_durations.lock() {
+            if let Some(bucket) = SAVE_BUCKETS.iter().position(|&bound| seconds <= bound) {
+                histogram.buckets[bucket] += 1;
+            }
+            histogram.count += 1;
+            histogram.sum += seconds;
+        }
+    }
+
+    /// Everything, as a Prometheus scrape
+    pub fn render(&self, gauges: Gauges) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{}{} {}", name, labels, value);
+            }
+        };
+        let plain = |value: String| vec![(String::new(), value)];
+
+        metric(
+            "daggerheart_uptime_seconds",
+            "gauge",
+            "Seconds since the server started.",
+            &plain(self.started.elapsed().as_secs().to_string()),
+        );
+        metric(
+            "daggerheart_connections",
+            "gauge",
+            "Open WebSocket connections.",
+            &plain(gauges.connections.to_string()),
+        );
+        metric(
+            "daggerheart_characters",
+            "gauge",
+            "Characters in the game.",
+            &plain(gauges.characters.to_string()),
+        );
+        metric(
+            "daggerheart_adversaries",
+            "gauge",
+            "Adversaries on the map.",
+            &plain(gauges.adversaries.to_string()),
+        );
+        metric(
+            "daggerheart_broadcast_queue",
+            "gauge",
+            "Broadcasts the slowest client has yet to receive.",
+            &plain(gauges.broadcast_queue.to_string()),
+        );
+        metric(
+            "daggerheart_broadcast_lagged_connections_total",
+            "counter",
+            "Times a client fell too far behind the broadcasts.",
+            &plain(self.lagged_connections.load(Ordering::Relaxed).to_string()),
+        );
+        metric(
+            "daggerheart_broadcast_lagged_messages_total",
+            "counter",
+            "Broadcasts lagging clients missed.",
+            &plain(self.lagged_messages.load(Ordering::Relaxed).to_string()),
+        );
+
+        metric(
+            "daggerheart_rate_limited_messages_total",
+            "counter",
+            "Client messages dropped for going over a rate limit.",
+            &plain(self.rate_limited.load(Ordering::Relaxed).to_string()),
+        );
+
+        let messages: Vec<_> = self
+            .messages
+            .lock()
+            .map(|messages| {
+                messages
+                    .iter()
+                    .map(|(kind, count)| (format!("{{type=\"{}\"}}", kind), count.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        metric(
+            "daggerheart_client_messages_total",
+            "counter",
+            "Client messages received, by type.",
+            &messages,
+        );
+
+        let mut saves = Vec::new();
+        if let Ok(histogram) = self.save_durations.lock() {
+            let mut cumulative = 0;
+            for (bound, count) in SAVE_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                saves.push((
+                    format!("_bucket{{le=\"{}\"}}", bound),
+                    cumulative.to_string(),
+                ));
+            }
+            saves.push((
+                "_bucket{le=\"+Inf\"}".to_string(),
+                histogram.count.to_string(),
+            ));
+            saves.push(("_sum".to_string(), histogram.sum.to_string()));
+            saves.push(("_count".to_string(), histogram.count.to_string()));
+        }
+        metric(
+            "daggerheart_save_duration_seconds",
+            "histogram",
+            "Time taken to write a save.",
+            &saves,
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_text() {
+        let metrics = Metrics::new();
+        metrics.message_received("roll_duality");
+        metrics.message_received("roll_duality");
+        metrics.message_received("move_character");
+        metrics.broadcast_lagged(12);
+        metrics.save_finished(Duration::from_millis(20));
+
+        let text = metrics.render(Gauges {
+            connections: 3,
+            characters: 2,
+            ..Default::default()
+        });
+        assert!(text.contains("daggerheart_connections 3\n"));
+        assert!(text.contains("daggerheart_client_messages_total{type=\"roll_duality\"} 2\n"));
+        assert!(text.contains("daggerheart_broadcast_lagged_messages_total 12\n"));
+        assert!(text.contains("daggerheart_save_duration_seconds_bucket{le=\"0.01\"} 0\n"));
+        assert!(text.contains("daggerheart_save_duration_seconds_bucket{le=\"0.025\"} 1\n"));
+        assert!(text.contains("daggerheart_save_duration_seconds_count 1\n"));
+    }
+}
@@ -0,0 +1,222 @@
+//! Coalescing token moves
+//!
+//! Dragging a token sends a move for every step, and each one went out to
+//! every screen. Now each token's moves go out at most
+//! `move_updates_per_second` times a second: a move arriving sooner is held,
+//! later moves of the same token are folded into it (their routes joined, so
+//! the TV still animates the whole way), and it's sent when the token's
+//! interval is up. The last move of a drag is always sent. Teleports go out
+//! at once, replacing anything held for that token.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::protocol::ServerMessage;
+
+/// What to do with a move
+#[derive(Debug, Clone)]
+pub enum Offer {
+    /// Broadcast it now
+    Send(Box<ServerMessage>),
+    /// Held; call [`MoveThrottle::flush`] for this token after `delay`
+    Held { token_id: String, delay: Duration },
+    /// Folded into a move already held (and already due to be flushed)
+    Merged,
+}
+
+#[derive(Debug)]
+struct TokenMoves {
+    last_sent: Instant,
+    pending: Option<ServerMessage>,
+}
+
+/// Moves held back per token
+#[derive(Debug)]
+pub struct MoveThrottle {
+    /// Shortest time between two moves of one token (zero sends everything)
+    interval: Duration,
+    tokens: Mutex<HashMap<String, TokenMoves>>,
+}
+
+impl MoveThrottle {
+    /// At most `per_second` moves a second for each token (0 turns
+    /// coalescing off)
+    pub fn new(per_second: f64) -> Self {
+        let interval = if per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / per_second)
+        } else {
+            Duration::ZERO
+        };
+        Self {
+            interval,
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decide what to do with a `CharacterMoved` or `AdversaryMoved`; any
+    /// other message is sent straight away
+    pub fn offer(&self, msg: ServerMessage, now: Instant) -> Offer {
+        let (token_id, teleported) = match &msg {
+            ServerMessage::CharacterMoved {
+                character_id,
+                teleported,
+                ..
+            } => (character_id.clone(), *teleported),
+            ServerMessage::AdversaryMoved { adversary_id, .. } => (adversary_id.clone(), false),
+            _ => return Offer::Send(Box::new(msg)),
+        };
+        if self.interval.is_zero() {
+            return Offer::Send(Box::new(msg));
+        }
+
+        let mut tokens = self.tokens.lock().unwrap();
+        let Some(moves) = tokens.get_mut(&token_id) else {
+            tokens.insert(
+                token_id,
+                TokenMoves {
+                    last_sent: now,
+                    pending: None,
+                },
+            );
+            return Offer::Send(Box::new(msg));
+        };
+
+        if teleported {
+            moves.pending = None;
+            moves.last_sent = now;
+            return Offer::Send(Box::new(msg));
+        }
+        if let Some(pending) = moves.pending.take() {
+            moves.pending = Some(merge(pending, msg));
+            return Offer::Merged;
+        }
+        let due = moves.last_sent + self.interval;
+        if now >= due {
+            moves.last_sent = now;
+            return Offer::Send(Box::new(msg));
+        }
+        moves.pending = Some(msg);
+        Offer::Held {
+            token_id,
+            delay: due - now,
+        }
+    }
+
+    /// The move held for a token, now due
+    pub fn flush(&self, token_id: &str, now: Instant) -> Option<ServerMessage> {
+        let mut tokens = self.tokens.lock().unwrap();
+        let moves = tokens.get_mut(token_id)?;
+        let msg = moves.pending.take()?;
+        moves.last_sent = now;
+        Some(msg)
+    }
+}
+
+/// One move standing for two of the same token, `earlier` then `later`
+fn merge(earlier: ServerMessage, later: ServerMessage) -> ServerMessage {
+    match (earlier, later) {
+        (
+            ServerMessage::CharacterMoved {
+                path: mut route,
+                crossed_hazards: mut hazards,
+                ..
+            },
+            ServerMessage::CharacterMoved {
+                character_id,
+                position,
+                path,
+                teleported,
+                crossed_hazards,
+                rotation,
+            },
+        ) => {
+            // The later route starts where the earlier one ended
+            let skip = usize::from(route.last().is_some() && route.last() == path.first());
+            route.extend(path.into_iter().skip(skip));
+            for hazard in crossed_hazards {
+                if !hazards.contains(&hazard) {
+                    hazards.push(hazard);
+                }
+            }
+            ServerMessage::CharacterMoved {
+                character_id,
+                position,
+                path: route,
+                teleported,
+                crossed_hazards: hazards,
+                rotation,
+            }
+        }
+        (_, later) => later,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::Position;
+
+    fn json(msg: Option<ServerMessage>) -> serde_json::Value {
+        serde_json::to_value(msg).unwrap()
+    }
+
+    fn walk(path: &[(f32, f32)]) -> ServerMessage {
+        let path: Vec<_> = path.iter().map(|&(x, y)| Position::new(x, y)).collect();
+        ServerMessage::CharacterMoved {
+            character_id: "theron".to_string(),
+            position: *path.last().unwrap(),
+            path,
+            teleported: false,
+            crossed_hazards: Vec::new(),
+            rotation: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_drag_is_coalesced_and_final_position_flushed() {
+        let throttle = MoveThrottle::new(10.0);
+        let start = Instant::now();
+        let soon = start + Duration::from_millis(20);
+
+        assert!(matches!(
+            throttle.offer(walk(&[(0.0, 0.0), (1.0, 0.0)]), start),
+            Offer::Send(_)
+        ));
+        let Offer::Held { token_id, delay } = throttle.offer(walk(&[(1.0, 0.0), (2.0, 0.0)]), soon)
+        else {
+            panic!("second move within the interval should be held");
+        };
+        assert_eq!(delay, Duration::from_millis(80));
+        assert!(matches!(
+            throttle.offer(walk(&[(2.0, 0.0), (3.0, 0.0)]), soon),
+            Offer::Merged
+        ));
+
+        let flushed = throttle.flush(&token_id, start + Duration::from_millis(100));
+        assert_eq!(
+            json(flushed),
+            json(Some(walk(&[(1.0, 0.0), (2.0, 0.0), (3.0, 0.0)])))
+        );
+        assert!(throttle.flush(&token_id, start).is_none());
+    }
+
+    #[test]
+    fn test_teleport_replaces_held_move() {
+        let throttle = MoveThrottle::new(10.0);
+        let start = Instant::now();
+        throttle.offer(walk(&[(0.0, 0.0)]), start);
+        throttle.offer(walk(&[(1.0, 0.0)]), start);
+
+        let teleport = ServerMessage::CharacterMoved {
+            character_id: "theron".to_string(),
+            position: Position::new(9.0, 9.0),
+            path: Vec::new(),
+            teleported: true,
+            crossed_hazards: Vec::new(),
+            rotation: 0.0,
+        };
+        assert!(matches!(throttle.offer(teleport, start), Offer::Send(_)));
+        assert!(throttle.flush("theron", start).is_none());
+    }
+}
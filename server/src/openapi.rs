@@ -0,0 +1,137 @@
+//! The REST API described as OpenAPI, at `/api/openapi.json`, with Swagger
+//! UI at `/api/docs`
+//!
+//! The spec is built from the `#[utoipa::path]` on each route handler, so a
+//! new route only shows up once it's listed in [`ApiDoc`]. Routes guarded by
+//! the API token are marked with the `api_token` scheme (the `X-Api-Token`
+//! header). Most answers are JSON with `success` and, when that's false, an
+//! `error` saying why.
+
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::routes;
+
+/// Where the spec is served
+pub const SPEC_PATH: &str = "/api/openapi.json";
+
+/// Where Swagger UI is served
+pub const DOCS_PATH: &str = "/api/docs";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Daggerheart VTT",
+        description = "REST API of the Daggerheart virtual tabletop server. Live play goes over the WebSocket at `/ws`."
+    ),
+    paths(
+        routes::qr_code,
+        routes::game_state,
+        routes::snapshot,
+        routes::events,
+        routes::scene_log,
+        routes::roll_stats,
+        routes::recap,
+        routes::history,
+        routes::history_at,
+        routes::restore_history,
+        routes::healthz,
+        routes::readyz,
+        routes::metrics,
+        routes::save_game,
+        routes::list_saves,
+        routes::delete_save,
+        routes::rename_save,
+        routes::load_game,
+        routes::backup,
+        routes::restore_backup,
+        routes::adversary_templates,
+        routes::environment_templates,
+        routes::validate_state,
+        routes::checkpoints,
+        routes::chat,
+        routes::handouts,
+        routes::handout_content,
+        routes::next_session,
+        routes::notes,
+        routes::all_notes,
+        routes::create_note,
+        routes::update_note,
+        routes::delete_note,
+        routes::quests,
+        routes::delete_character,
+        routes::retire_character,
+        routes::export_character,
+        routes::import_character,
+        routes::vault,
+        routes::encounter_presets,
+        routes::encounter_budget,
+        routes::maps,
+        routes::upload_map,
+        routes::settings,
+        routes::narrative,
+        routes::set_narrative,
+        routes::combat_keyframes,
+        routes::reload_adversaries,
+        routes::load_demo,
+        routes::seed_dice,
+    ),
+    components(schemas(
+        routes::SaveRequest,
+        routes::SaveSlotRequest,
+        routes::MapUpload,
+        routes::DiceSeedRequest
+    )),
+    modifiers(&ApiToken),
+    tags(
+        (name = "game", description = "The session as it stands"),
+        (name = "characters", description = "Exporting, importing and retiring characters"),
+        (name = "saves", description = "Saves, loads and backups"),
+        (name = "history", description = "Session history and combat recordings"),
+        (name = "content", description = "Adversaries, environments, maps, handouts and phrasings"),
+        (name = "admin", description = "GM tools, notes included"),
+        (name = "monitoring", description = "Health checks and metrics"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the `X-Api-Token` header the guarded routes ask for
+struct ApiToken;
+
+impl Modify for ApiToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "api_token",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Token"))),
+            );
+        }
+    }
+}
+
+/// Swagger UI, and the spec it reads
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new(DOCS_PATH).url(SPEC_PATH, ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_lists_routes_and_guards() {
+        let spec: serde_json::Value =
+            serde_json::from_str(&ApiDoc::openapi().to_json().unwrap()).unwrap();
+        let paths = &spec["paths"];
+        for path in ["/api/save", "/api/load", "/api/game-state", "/api/saves"] {
+            assert!(paths.get(path).is_some(), "{} missing", path);
+        }
+        assert!(paths["/api/save"]["post"]["security"].is_array());
+        assert!(paths["/api/saves"]["get"]["security"].is_null());
+        assert_eq!(
+            spec["components"]["securitySchemes"]["api_token"]["name"],
+            "X-Api-Token"
+        );
+    }
+}
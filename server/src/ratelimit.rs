@@ -0,0 +1,203 @@
+//! Per-connection rate limiting for WebSocket messages
+//!
+//! Every connection gets a token bucket for all its messages, plus one per
+//! message type that has a budget of its own (token moves, by default). A
+//! message over budget is dropped, and the client is told once per
+//! [`VIOLATION_WINDOW`]. A client that keeps going after that, with more
+//! than `max_dropped` messages dropped inside one window, is disconnected.
+//!
+//! Budgets go in the config file:
+//!
+//! ```toml
+//! [rate_limit]
+//! per_second = 20
+//! burst = 40
+//! max_dropped = 100
+//!
+//! [rate_limit.per_type]
+//! move_character = 15
+//! ping = 2
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// How long dropped messages count towards a disconnect
+pub const VIOLATION_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Messages a second a connection may send, of any type (0 turns rate
+    /// limiting off)
+    pub per_second: f64,
+    /// How many it may send at once before the rate applies
+    pub burst: f64,
+    /// Messages a second for particular types, by `type`; each may burst to
+    /// twice its rate
+    pub per_type: BTreeMap<String, f64>,
+    /// Dropped messages in one window before the connection is closed
+    pub max_dropped: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_second: 20.0,
+            burst: 40.0,
+            per_type: BTreeMap::from([("move_character".to_string(), 15.0)]),
+            max_dropped: 100,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn enabled(&self) -> bool {
+        self.per_second > 0.0
+    }
+}
+
+/// What to do with a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Drop it and tell the client
+    Warn,
+    /// Drop it quietly; the client was already told
+    Drop,
+    /// Drop it and close the connection
+    Disconnect,
+}
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    rate: f64,
+    capacity: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(rate: f64, capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            rate,
+            capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+}
+
+/// One connection's budgets
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    overall: Bucket,
+    per_type: HashMap<String, Bucket>,
+    window_started: Instant,
+    dropped: u32,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, now: Instant) -> Self {
+        Self {
+            overall: Bucket::new(config.per_second, config.burst.max(1.0), now),
+            config,
+            per_type: HashMap::new(),
+            window_started: now,
+            dropped: 0,
+        }
+    }
+
+    /// Charge a message of this `type` (if it has one) against the budgets
+    pub fn check(&mut self, message_type: Option<&str>, now: Instant) -> Verdict {
+        if !self.config.enabled() {
+            return Verdict::Allow;
+        }
+        self.overall.refill(now);
+        let typed = message_type.and_then(|kind| {
+            let rate = *self.config.per_type.get(kind)?;
+            let bucket = self
+                .per_type
+                .entry(kind.to_string())
+                .or_insert_with(|| Bucket::new(rate, (rate * 2.0).max(1.0), now));
+            bucket.refill(now);
+            Some(bucket)
+        });
+
+        let allowed = self.overall.tokens >= 1.0 && typed.as_ref().is_none_or(|b| b.tokens >= 1.0);
+        if allowed {
+            self.overall.tokens -= 1.0;
+            if let Some(bucket) = typed {
+                bucket.tokens -= 1.0;
+            }
+            return Verdict::Allow;
+        }
+
+        if now.saturating_duration_since(self.window_started) > VIOLATION_WINDOW {
+            self.window_started = now;
+            self.dropped = 0;
+        }
+        self.dropped += 1;
+        if self.dropped > self.config.max_dropped {
+            Verdict::Disconnect
+        } else if self.dropped == 1 {
+            Verdict::Warn
+        } else {
+            Verdict::Drop
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flooding_is_dropped_then_disconnected() {
+        let config = RateLimitConfig {
+            per_second: 10.0,
+            burst: 10.0,
+            per_type: BTreeMap::from([("move_character".to_string(), 2.0)]),
+            max_dropped: 5,
+        };
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(config, start);
+
+        // Moves have their own, smaller budget (a burst of 4)...
+        let moves: Vec<_> = (0..5)
+            .map(|_| limiter.check(Some("move_character"), start))
+            .collect();
+        assert_eq!(moves[3], Verdict::Allow);
+        assert_eq!(moves[4], Verdict::Warn);
+        // ...which doesn't hold up other messages
+        assert_eq!(limiter.check(Some("roll_duality"), start), Verdict::Allow);
+
+        // A second later the budget has refilled
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check(Some("move_character"), later), Verdict::Allow);
+
+        let flood: Vec<_> = (0..20).map(|_| limiter.check(None, later)).collect();
+        assert!(flood.contains(&Verdict::Drop));
+        assert_eq!(flood.last(), Some(&Verdict::Disconnect));
+    }
+
+    #[test]
+    fn test_zero_rate_turns_limiting_off() {
+        let config = RateLimitConfig {
+            per_second: 0.0,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(config, now);
+        assert!((0..1000).all(|_| limiter.check(Some("move_character"), now) == Verdict::Allow));
+    }
+}
@@ -0,0 +1,187 @@
+//! Records the session history to disk as the game changes
+//!
+//! Every broadcast prompts a look at the game: new events are appended with
+//! the patch of what changed, and changes without an event as bare patches.
+//! The file is only ever appended to; each server run starts with a
+//! snapshot, so a history survives crashes and restarts. Set
+//! `DAGGERHEART_HISTORY` to an empty string to turn recording off.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::game::GameState;
+use crate::history::{self, HistoryChange, HistoryRecord};
+
+/// History file path
+pub const HISTORY_FILE_ENV: &str = "DAGGERHEART_HISTORY";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_HISTORY_FILE: &str = "history.jsonl";
+
+/// An open history file
+pub struct HistoryRecorder {
+    path: PathBuf,
+    inner: Mutex<Recorder>,
+}
+
+struct Recorder {
+    file: File,
+    /// Last sequence number written
+    seq: u64,
+    /// The game's event count when last recorded
+    events_logged: u64,
+    /// The game as last recorded
+    state: Value,
+}
+
+impl Recorder {
+    fn write(&mut self, change: HistoryChange) {
+        self.seq += 1;
+        let record = HistoryRecord {
+            seq: self.seq,
+            at: Utc::now(),
+            change,
+        };
+        if let Err(e) = writeln!(self.file, "{}", record.to_line()) {
+            tracing::warn!("Failed to write session history: {}", e);
+        }
+    }
+}
+
+impl HistoryRecorder {
+    /// Record to the file named by `DAGGERHEART_HISTORY`, unless it's empty
+    pub fn from_env(game: &GameState) -> Option<Self> {
+        let path =
+            std::env::var(HISTORY_FILE_ENV).unwrap_or_else(|_| DEFAULT_HISTORY_FILE.to_string());
+        if path.is_empty() {
+            return None;
+        }
+        match Self::open(&path, game) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                tracing::warn!("Session history disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Append to the history at `path`, starting with a snapshot of `game`
+    pub fn open(path: impl Into<PathBuf>, game: &GameState) -> Result<Self, String> {
+        let path = path.into();
+        let existing = if path.exists() {
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        } else {
+            String::new()
+        };
+        let seq = history::parse_history(&existing)?
+            .last()
+            .map_or(0, |record| record.seq);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        // Don't append to a line a crash cut short
+        if !existing.is_empty() && !existing.ends_with('\n') {
+            writeln!(file).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+
+        let state = history::state_of(game);
+        let mut recorder = Recorder {
+            file,
+            seq,
+            events_logged: game.events_logged,
+            state: state.clone(),
+        };
+        recorder.write(HistoryChange::Snapshot { state });
+        Ok(Self {
+            path,
+            inner: Mutex::new(recorder),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append whatever changed since the last call
+    pub fn record(&self, game: &GameState) {
+        let Ok(mut recorder) = self.inner.lock() else {
+            return;
+        };
+        let state = history::state_of(game);
+        let mut patch = history::diff(&recorder.state, &state);
+        recorder.state = state;
+
+        let new_events = game.events_logged.saturating_sub(recorder.events_logged) as usize;
+        recorder.events_logged = game.events_logged;
+        let new_events = &game.event_log[game.event_log.len().saturating_sub(new_events)..];
+
+        // The change goes with the last of the events that made it
+        for (i, event) in new_events.iter().enumerate() {
+            let patch = if i + 1 == new_events.len() {
+                patch.take()
+            } else {
+                None
+            };
+            recorder.write(HistoryChange::Event {
+                event: event.clone(),
+                patch,
+            });
+        }
+        if let Some(patch) = patch {
+            recorder.write(HistoryChange::Mutation { patch });
+        }
+    }
+
+    /// Everything recorded so far, oldest first
+    pub fn records(&self) -> Result<Vec<HistoryRecord>, String> {
+        let contents = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?;
+        history::parse_history(&contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEventType;
+
+    #[test]
+    fn test_history_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("history-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut game = GameState::new();
+
+        let recorder = HistoryRecorder::open(&path, &game).unwrap();
+        recorder.record(&game);
+        assert_eq!(recorder.records().unwrap().len(), 1, "nothing changed");
+
+        game.add_event(
+            GameEventType::SystemMessage,
+            "Combat started".to_string(),
+            None,
+            None,
+        );
+        game.fog.insert(String::new(), Default::default());
+        recorder.record(&game);
+        drop(recorder);
+
+        let reopened = HistoryRecorder::open(&path, &game).unwrap();
+        let records = reopened.records().unwrap();
+        let seqs: Vec<_> = records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, [1, 2, 3]);
+        assert!(matches!(
+            records[1].change,
+            HistoryChange::Event { patch: Some(_), .. }
+        ));
+        assert!(matches!(records[2].change, HistoryChange::Snapshot { .. }));
+        let _ = std::fs::remove_file(&path);
+    }
+}
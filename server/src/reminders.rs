@@ -0,0 +1,122 @@
+//! Webhook reminders ahead of the next scheduled session
+//!
+//! Off unless a webhook URL is configured. The message is posted as
+//! `{"content": "..."}`, which Discord webhooks accept as-is.
+
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+use crate::game::GameEventType;
+use crate::schedule::ScheduledSession;
+use crate::websocket::{broadcast_event, AppState};
+
+/// Webhook URL the reminder is posted to
+pub const REMINDER_WEBHOOK_ENV: &str = "DAGGERHEART_REMINDER_WEBHOOK";
+
+/// Hours before the session the reminder goes out (default 24)
+pub const REMINDER_HOURS_ENV: &str = "DAGGERHEART_REMINDER_HOURS";
+
+const DEFAULT_REMINDER_HOURS: i64 = 24;
+
+/// How often the schedule is checked
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct ReminderConfig {
+    pub webhook_url: String,
+    /// How long before the session the reminder goes out
+    pub lead: Duration,
+}
+
+impl ReminderConfig {
+    /// Load settings from environment variables, `None` without a webhook
+    pub fn from_env() -> Option<Self> {
+        let webhook_url = std::env::var(REMINDER_WEBHOOK_ENV)
+            .ok()
+            .filter(|url| !url.is_empty())?;
+        let hours = std::env::var(REMINDER_HOURS_ENV)
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|&h| h > 0)
+            .unwrap_or(DEFAULT_REMINDER_HOURS);
+
+        Some(Self {
+            webhook_url,
+            lead: Duration::hours(hours),
+        })
+    }
+}
+
+/// Check the schedule every minute, posting the reminder once it's due
+pub fn spawn(state: AppState, config: ReminderConfig) {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let Some(session) = state
+                .game
+                .write()
+                .await
+                .take_due_reminder(Utc::now(), config.lead)
+            else {
+                continue;
+            };
+
+            let result = client
+                .post(&config.webhook_url)
+                .json(&json!({ "content": reminder_text(&session) }))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            let mut game = state.game.write().await;
+            if let Err(e) = result {
+                // Try again on the next check
+                tracing::warn!("Failed to send session reminder: {}", e);
+                if let Some(session) = game.next_session.as_mut() {
+                    session.reminded = false;
+                }
+                continue;
+            }
+            game.add_event(
+                GameEventType::SystemMessage,
+                "Session reminder sent".to_string(),
+                None,
+                None,
+            );
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(&state, event).await;
+            }
+        }
+    });
+}
+
+/// The message posted for a session
+fn reminder_text(session: &ScheduledSession) -> String {
+    let mut text = format!(
+        "🎲 Next Daggerheart session: {}",
+        session.at.format("%A %-d %B, %H:%M (UTC%:z)")
+    );
+    if !session.notes.is_empty() {
+        text.push('\n');
+        text.push_str(&session.notes);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reminder_text() {
+        let now = Utc::now();
+        let at = (now + Duration::days(2)).to_rfc3339();
+        let session = ScheduledSession::new(&at, "Bring dice", now).unwrap();
+        let text = reminder_text(&session);
+        assert!(text.starts_with("🎲 Next Daggerheart session: "));
+        assert!(text.ends_with("\nBring dice"));
+    }
+}
@@ -1,17 +1,33 @@
 //! HTTP routes
 
 use axum::{
-    extract::State,
-    response::{Html, IntoResponse},
+    body::Bytes,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
     Json,
 };
 use base64::{engine::general_purpose, Engine as _};
 use qrcode::QrCode;
+use serde::Deserialize;
 use serde_json::json;
 use std::io::Cursor;
 use std::net::UdpSocket;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::save::SavedSession;
+use crate::adversaries::TemplateQuery;
+use crate::backup::BackupLayout;
+use crate::environments::EnvironmentTemplate;
+use crate::history::{replay, HistoryChange, HistoryRecord};
+use crate::metrics::Gauges;
+use crate::narrative::NarrativeTemplates;
+use crate::protocol::encounters::EncounterPlan;
+use crate::protocol::events::EventLogQuery;
+use crate::protocol::notes::{Note, NoteDraft};
+use crate::recap::Recap;
+use crate::rng::DiceRng;
+use crate::save::{self, CharacterExport, SavedSession, DEFAULT_SAVE_NAME};
+use crate::snapshot::SnapshotBuilder;
 use crate::websocket::AppState;
 
 /// Get the local network IP address
@@ -30,24 +46,28 @@ fn get_local_ip() -> String {
 }
 
 /// Root route - serve index.html
-pub async fn index() -> Html<String> {
-    let html = std::fs::read_to_string("../client/index.html")
-        .unwrap_or_else(|_| "<h1>Error loading index.html</h1>".to_string());
-    Html(html)
+pub async fn index(State(state): State<AppState>) -> Html<String> {
+    state.client.page("index.html")
 }
 
 /// Mobile route - serve mobile.html
-pub async fn mobile() -> Html<String> {
-    let html = std::fs::read_to_string("../client/mobile.html")
-        .unwrap_or_else(|_| "<h1>Error loading mobile.html</h1>".to_string());
-    Html(html)
+pub async fn mobile(State(state): State<AppState>) -> Html<String> {
+    state.client.page("mobile.html")
 }
 
 /// Generate QR code for connection URL
-pub async fn qr_code() -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/api/qr-code",
+    tag = "game",
+    responses(
+        (status = 200, description = "Join URL and its QR code as a PNG data URL", body = serde_json::Value),
+    )
+)]
+pub async fn qr_code(State(state): State<AppState>) -> impl IntoResponse {
     // Get server address - use local IP instead of localhost
     let ip = get_local_ip();
-    let url = format!("http://{}:3000/mobile", ip);
+    let url = format!("http://{}:{}/mobile", ip, state.config.port);
 
     tracing::info!("Generating QR code for: {}", url);
 
@@ -73,6 +93,14 @@ pub async fn qr_code() -> impl IntoResponse {
 }
 
 /// Get current game state
+#[utoipa::path(
+    get,
+    path = "/api/game-state",
+    tag = "game",
+    responses(
+        (status = 200, description = "Characters and connection counts", body = serde_json::Value),
+    )
+)]
 pub async fn game_state(State(state): State<AppState>) -> impl IntoResponse {
     let game = state.game.read().await;
     let characters = game.get_characters();
@@ -80,59 +108,431 @@ pub async fn game_state(State(state): State<AppState>) -> impl IntoResponse {
     Json(json!({
         "character_count": characters.len(),
         "connection_count": game.connection_count(),
+        "spectator_count": game.spectator_count(),
         "characters": characters
     }))
 }
 
-/// Get event log
-pub async fn events(State(state): State<AppState>) -> impl IntoResponse {
-    use std::time::UNIX_EPOCH;
-    
+/// Catch-up snapshot of the session, as sent to new WebSocket connections
+#[utoipa::path(
+    get,
+    path = "/api/snapshot",
+    tag = "game",
+    responses(
+        (status = 200, description = "Scene, fight and recent events", body = serde_json::Value),
+    )
+)]
+pub async fn snapshot(State(state): State<AppState>) -> impl IntoResponse {
     let game = state.game.read().await;
-    let events: Vec<serde_json::Value> = game.get_all_events()
+    Json(SnapshotBuilder::new(&game).build())
+}
+
+/// A page of the event log, oldest first (without a running cutaway's
+/// events): `?event_type=&character=&offset=&limit=`
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "game",
+    params(
+        ("event_type" = Option<String>, Query, description = "Only this kind, e.g. `RollExecuted`"),
+        ("character" = Option<String>, Query, description = "Only events about this character (by name)"),
+        ("offset" = Option<usize>, Query, description = "Newest matching events to skip"),
+        ("limit" = Option<usize>, Query, description = "Events in the page"),
+    ),
+    responses(
+        (status = 200, description = "A page of events with the total matching", body = serde_json::Value),
+    )
+)]
+pub async fn events(
+    State(state): State<AppState>,
+    Query(query): Query<EventLogQuery>,
+) -> impl IntoResponse {
+    let game = state.game.read().await;
+    let (events, total) = game.event_page(&query, false);
+
+    Json(json!({
+        "events": events,
+        "count": events.len(),
+        "total": total,
+        "offset": query.offset,
+    }))
+}
+
+/// The event log split into scenes and the fights in them, oldest first
+/// (without a running cutaway's events): `?event_type=&character=`
+#[utoipa::path(
+    get,
+    path = "/api/events/scenes",
+    tag = "game",
+    params(
+        ("event_type" = Option<String>, Query, description = "Only this kind, e.g. `RollExecuted`"),
+        ("character" = Option<String>, Query, description = "Only events about this character (by name)"),
+    ),
+    responses(
+        (status = 200, description = "Scenes, each split at its fights", body = serde_json::Value),
+    )
+)]
+pub async fn scene_log(
+    State(state): State<AppState>,
+    Query(query): Query<EventLogQuery>,
+) -> impl IntoResponse {
+    let game = state.game.read().await;
+    let scenes = game.scene_log(&query, false);
+
+    Json(json!({
+        "scenes": scenes,
+        "count": scenes.len(),
+    }))
+}
+
+/// How each character's duality dice have been rolling: averages, crit
+/// rate and Hope against Fear, with the table's totals
+#[utoipa::path(
+    get,
+    path = "/api/roll-stats",
+    tag = "game",
+    responses(
+        (status = 200, description = "Tallies by character, and the table's", body = serde_json::Value),
+    )
+)]
+pub async fn roll_stats(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.game.read().await.roll_stats_report())
+}
+
+/// Query for `GET /api/history`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+    /// Only what came after this point
+    #[serde(default)]
+    pub since: u64,
+}
+
+/// The recorded history, or why there is none
+fn history_records(state: &AppState) -> Result<Vec<HistoryRecord>, String> {
+    state
+        .history
+        .as_ref()
+        .ok_or_else(|| "Session history is turned off".to_string())?
+        .records()
+}
+
+/// What happened since a point in the session history: events, snapshots
+/// (server starts) and how many other changes were recorded, without a
+/// running cutaway's events
+#[utoipa::path(
+    get,
+    path = "/api/history",
+    tag = "history",
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Entries since the point asked for", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<serde_json::Value> {
+    let records = match history_records(&state) {
+        Ok(records) => records,
+        Err(e) => return Json(json!({ "success": false, "error": e })),
+    };
+    let cutaway_running = state.game.read().await.cutaway.is_some();
+
+    let entries: Vec<_> = records
         .iter()
-        .map(|event| {
-            let timestamp = event.timestamp
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-            
-            let timestamp_str = chrono::DateTime::from_timestamp(timestamp as i64, 0)
-                .map(|dt| dt.format("%H:%M:%S").to_string())
-                .unwrap_or_else(|| "??:??:??".to_string());
-            
-            json!({
-                "timestamp": timestamp_str,
-                "event_type": format!("{:?}", event.event_type),
-                "message": event.message,
-                "character_name": event.character_name,
-                "details": event.details,
-            })
+        .filter(|r| r.seq > query.since)
+        .filter_map(|r| match &r.change {
+            HistoryChange::Snapshot { .. } => Some(json!({
+                "seq": r.seq,
+                "at": r.at.to_rfc3339(),
+                "kind": "snapshot",
+            })),
+            HistoryChange::Event { event, .. } if !(cutaway_running && event.cutaway) => {
+                Some(json!({
+                    "seq": r.seq,
+                    "at": r.at.to_rfc3339(),
+                    "kind": "event",
+                    "event": event.to_data(),
+                }))
+            }
+            _ => None,
         })
         .collect();
-    
+
     Json(json!({
-        "events": events,
-        "count": events.len()
+        "success": true,
+        "latest": records.last().map(|r| r.seq),
+        "changes": records.iter().filter(|r| r.seq > query.since).count(),
+        "entries": entries,
+    }))
+}
+
+/// The game as it stood at a point in the session history
+#[utoipa::path(
+    get,
+    path = "/api/history/{seq}",
+    tag = "history",
+    params(
+        ("seq" = u64, Path, description = "Point in the session history"),
+    ),
+    responses(
+        (status = 200, description = "The saved session and events at that point", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn history_at(
+    State(state): State<AppState>,
+    Path(seq): Path<u64>,
+) -> Json<serde_json::Value> {
+    match history_records(&state).and_then(|records| replay(&records, Some(seq))) {
+        Ok(replay) => Json(json!({
+            "success": true,
+            "seq": replay.seq,
+            "at": replay.at.to_rfc3339(),
+            "session": replay.session,
+            "events": replay.events.iter().map(|e| e.to_data()).collect::<Vec<_>>(),
+        })),
+        Err(e) => Json(json!({ "success": false, "error": e })),
+    }
+}
+
+/// Put the game back as it stood at a point in the session history, e.g.
+/// after a crash; who's connected stays
+#[utoipa::path(
+    post,
+    path = "/api/history/{seq}/restore",
+    tag = "history",
+    params(
+        ("seq" = u64, Path, description = "Point in the session history"),
+    ),
+    responses(
+        (status = 200, description = "The point restored to", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn restore_history(
+    State(state): State<AppState>,
+    Path(seq): Path<u64>,
+) -> Json<serde_json::Value> {
+    let replay = match history_records(&state).and_then(|records| replay(&records, Some(seq))) {
+        Ok(replay) => replay,
+        Err(e) => return Json(json!({ "success": false, "error": e })),
+    };
+
+    let mut game = state.game.write().await;
+    if let Err(e) = replay.restore_into(&mut game) {
+        return Json(json!({
+            "success": false,
+            "error": format!("Failed to restore #{}: {}", seq, e)
+        }));
+    }
+    drop(game);
+
+    notify_game_replaced(
+        &state,
+        &format!(
+            "Game restored to #{}. Please refresh your browser.",
+            replay.seq
+        ),
+    );
+    Json(json!({
+        "success": true,
+        "seq": replay.seq,
+        "character_count": replay.session.characters.len(),
     }))
 }
 
+/// Query for `GET /api/recap`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecapQuery {
+    /// "markdown" (the default, as a download) or "html"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// The event log written up as a session recap, grouped by scene and
+/// combat (without a running cutaway's events)
+#[utoipa::path(
+    get,
+    path = "/api/recap",
+    tag = "game",
+    params(RecapQuery),
+    responses(
+        (status = 200, description = "The recap as Markdown or HTML", content_type = "text/markdown"),
+        (status = 400, description = "Unknown format", body = serde_json::Value),
+    )
+)]
+pub async fn recap(State(state): State<AppState>, Query(query): Query<RecapQuery>) -> Response {
+    let game = state.game.read().await;
+    let date = chrono::Utc::now().format("%Y-%m-%d");
+    // The same public log as /api/events/scenes, so a running cutaway stays out
+    let scenes = game.scene_log(&EventLogQuery::default(), false);
+    let recap = Recap::from_scenes(format!("Session recap – {}", date), scenes);
+
+    match query.format.as_deref() {
+        Some("html") => Html(recap.to_html()).into_response(),
+        None | Some("markdown") | Some("md") => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    "text/markdown; charset=utf-8".to_string(),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"session-recap-{}.md\"", date),
+                ),
+            ],
+            recap.to_markdown(),
+        )
+            .into_response(),
+        Some(other) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "success": false,
+                "error": format!("Unknown recap format: {}", other)
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// Liveness: the server is up and answering
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "The server is up", body = String),
+    )
+)]
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Readiness: the game isn't stuck behind a lock and saves can be listed
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "Ready", body = serde_json::Value),
+        (status = 503, description = "The game state is stuck or saves can't be listed", body = serde_json::Value),
+    )
+)]
+pub async fn readyz(State(state): State<AppState>) -> Response {
+    let game_ready = tokio::time::timeout(std::time::Duration::from_secs(2), state.game.read())
+        .await
+        .is_ok();
+    let saves_ready = state.saves.list().is_ok();
+    let status = if game_ready && saves_ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(json!({
+            "ready": status == StatusCode::OK,
+            "game": game_ready,
+            "saves": saves_ready
+        })),
+    )
+        .into_response()
+}
+
+/// Prometheus metrics
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "Prometheus text", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    let gauges = {
+        let game = state.game.read().await;
+        Gauges {
+            connections: game.connections.len(),
+            characters: game.characters.len(),
+            adversaries: game.adversaries.len(),
+            broadcast_queue: state.broadcaster.len(),
+        }
+    };
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        state.metrics.render(gauges),
+    )
+        .into_response()
+}
+
 /// GM view - serve gm.html
-pub async fn gm() -> Html<String> {
-    let html = std::fs::read_to_string("../client/gm.html")
-        .unwrap_or_else(|_| "<h1>Error loading gm.html</h1>".to_string());
-    Html(html)
+pub async fn gm(State(state): State<AppState>) -> Html<String> {
+    state.client.page("gm.html")
+}
+
+/// Body of `POST /api/save`
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct SaveRequest {
+    /// "Manual Save" if not given
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
-/// Save current game state
-pub async fn save_game(State(state): State<AppState>) -> Json<serde_json::Value> {
+/// Save current game state, under the given name if there is one
+#[utoipa::path(
+    post,
+    path = "/api/save",
+    tag = "saves",
+    request_body(content = Option<SaveRequest>, description = "Save name (optional)"),
+    responses(
+        (status = 200, description = "Where the session was saved", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn save_game(
+    State(state): State<AppState>,
+    payload: Option<Json<SaveRequest>>,
+) -> Json<serde_json::Value> {
+    let name = match payload.and_then(|Json(request)| request.name) {
+        Some(name) => match save::validate_save_name(&name) {
+            Ok(name) => name,
+            Err(e) => {
+                return Json(json!({
+                    "success": false,
+                    "error": e
+                }))
+            }
+        },
+        None => DEFAULT_SAVE_NAME.to_string(),
+    };
+
     let game = state.game.read().await;
-    let session = SavedSession::from_game_state(&game, "Manual Save".to_string());
 
-    match session.save_to_file() {
-        Ok(path) => Json(json!({
+    let save_count = state.saves.list().map(|s| s.len()).unwrap_or(0);
+    if let Err(e) = game.limits.check_saves(save_count) {
+        return Json(json!({
+            "success": false,
+            "error": e
+        }));
+    }
+
+    let session = SavedSession::from_game_state(&game, name);
+
+    let started = std::time::Instant::now();
+    let saved = state.saves.save(&session, &game.event_log);
+    state.metrics.save_finished(started.elapsed());
+
+    match saved {
+        Ok(slot) => Json(json!({
             "success": true,
-            "path": path.display().to_string(),
+            "path": slot,
             "session": session
         })),
         Err(e) => Json(json!({
@@ -143,16 +543,24 @@ pub async fn save_game(State(state): State<AppState>) -> Json<serde_json::Value>
 }
 
 /// List all saved sessions
-pub async fn list_saves() -> Json<serde_json::Value> {
-    match SavedSession::list_saves() {
+#[utoipa::path(
+    get,
+    path = "/api/saves",
+    tag = "saves",
+    responses(
+        (status = 200, description = "Saved sessions", body = serde_json::Value),
+    )
+)]
+pub async fn list_saves(State(state): State<AppState>) -> Json<serde_json::Value> {
+    match state.saves.list() {
         Ok(saves) => {
             let saves_data: Vec<_> = saves
                 .into_iter()
-                .map(|(path, name, timestamp)| {
+                .map(|save| {
                     json!({
-                        "path": path.display().to_string(),
-                        "name": name,
-                        "timestamp": timestamp.to_rfc3339()
+                        "path": save.slot,
+                        "name": save.name,
+                        "timestamp": save.saved_at.to_rfc3339()
                     })
                 })
                 .collect();
@@ -169,7 +577,197 @@ pub async fn list_saves() -> Json<serde_json::Value> {
     }
 }
 
+/// Body of `DELETE /api/saves` and `POST /api/saves/rename` (and
+/// `POST /api/load`, which only needs the path)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveSlotRequest {
+    /// As listed by `GET /api/saves`
+    pub path: String,
+    /// The new name, when renaming
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Delete a save
+#[utoipa::path(
+    delete,
+    path = "/api/saves",
+    tag = "saves",
+    request_body = SaveSlotRequest,
+    responses(
+        (status = 200, description = "Whether the save was deleted", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn delete_save(
+    State(state): State<AppState>,
+    Json(payload): Json<SaveSlotRequest>,
+) -> Json<serde_json::Value> {
+    match state.saves.delete(&payload.path) {
+        Ok(()) => Json(json!({ "success": true })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Give a save a new name
+#[utoipa::path(
+    post,
+    path = "/api/saves/rename",
+    tag = "saves",
+    request_body = SaveSlotRequest,
+    responses(
+        (status = 200, description = "The save's new name", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn rename_save(
+    State(state): State<AppState>,
+    Json(payload): Json<SaveSlotRequest>,
+) -> Json<serde_json::Value> {
+    let renamed = save::validate_save_name(payload.name.as_deref().unwrap_or_default())
+        .and_then(|name| state.saves.rename(&payload.path, name));
+    match renamed {
+        Ok(session) => Json(json!({
+            "success": true,
+            "name": session.name
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// What a backup covers on this server; saves kept in a database can't be
+/// copied as files, so there's no backup of those
+fn backup_layout(state: &AppState) -> Result<BackupLayout, String> {
+    let saves = state.saves.files_dir().ok_or_else(|| {
+        format!(
+            "Backups cover saves kept as files, and {} is set to sqlite; copy the database file instead",
+            crate::storage::STORAGE_ENV
+        )
+    })?;
+    Ok(BackupLayout {
+        dirs: vec![
+            ("saves", saves.to_path_buf()),
+            ("maps", state.maps.dir().to_path_buf()),
+            ("handouts", state.handouts.dir().to_path_buf()),
+        ],
+        vault: state.vault.path().to_path_buf(),
+    })
+}
+
+/// Run disk-heavy work (zips, painting the demo map) off the async threads
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))?
+}
+
+/// Every save, map and handout, and the vault, as one zip
+#[utoipa::path(
+    get,
+    path = "/api/backup",
+    tag = "saves",
+    responses(
+        (status = 200, description = "Zip of every save, map and handout, and the vault", body = Vec<u8>, content_type = "application/zip"),
+        (status = 500, description = "The backup couldn't be written", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn backup(State(state): State<AppState>) -> Response {
+    let zip = match backup_layout(&state) {
+        Ok(layout) => blocking(move || layout.create()).await,
+        Err(e) => Err(e),
+    };
+    match zip {
+        Ok(zip) => (
+            [
+                (header::CONTENT_TYPE, "application/zip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"daggerheart-backup-{}.zip\"",
+                        chrono::Utc::now().format("%Y-%m-%d")
+                    ),
+                ),
+            ],
+            zip,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "error": e
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// Unpack a backup from `GET /api/backup` (the zip is the request body)
+#[utoipa::path(
+    post,
+    path = "/api/restore",
+    tag = "saves",
+    request_body(content = Vec<u8>, description = "A zip from `GET /api/backup`", content_type = "application/zip"),
+    responses(
+        (status = 200, description = "How many files were restored", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn restore_backup(State(state): State<AppState>, body: Bytes) -> Json<serde_json::Value> {
+    let restored = match backup_layout(&state) {
+        Ok(layout) => blocking(move || layout.restore(&body)).await,
+        Err(e) => Err(e),
+    };
+    match restored {
+        Ok(restored) => {
+            println!(
+                "📦 Backup restored: {} files ({} bytes)",
+                restored.files, restored.bytes
+            );
+            // Maps, handouts and the vault are read from disk as needed;
+            // only the map on the table is held in memory
+            {
+                let mut game = state.game.write().await;
+                let map_id = game.map.as_ref().map(|m| m.id.clone());
+                if let Some(map) = map_id.and_then(|id| state.maps.get(&id).ok()) {
+                    game.layout = map.layout;
+                    game.map = Some(map);
+                }
+            }
+            // Clients fetch the map, handout and vault lists again
+            notify_game_replaced(&state, "Backup restored. Please refresh your browser.");
+            Json(json!({
+                "success": true,
+                "files": restored.files
+            }))
+        }
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
 /// Load a saved session
+#[utoipa::path(
+    post,
+    path = "/api/load",
+    tag = "saves",
+    request_body(content = SaveSlotRequest, description = "The save's `path`"),
+    responses(
+        (status = 200, description = "The session loaded", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
 pub async fn load_game(
     State(state): State<AppState>,
     Json(payload): Json<serde_json::Value>,
@@ -184,10 +782,13 @@ pub async fn load_game(
         }
     };
 
-    let path = std::path::Path::new(path_str);
+    let loaded = state
+        .saves
+        .load(path_str)
+        .and_then(|session| Ok((session, state.saves.load_events(path_str)?)));
 
-    match SavedSession::load_from_file(path) {
-        Ok(session) => {
+    match loaded {
+        Ok((session, events)) => {
             // Apply to game state
             let mut game = state.game.write().await;
 
@@ -197,12 +798,14 @@ pub async fn load_game(
                     "error": format!("Failed to apply session: {}", e)
                 }));
             }
+            // Saves that kept their event log pick it back up
+            if !events.is_empty() {
+                let skip = events.len().saturating_sub(game.limits.max_event_log);
+                game.event_log = events[skip..].to_vec();
+            }
 
             // Notify all connected clients to refresh
-            let msg = crate::protocol::ServerMessage::Error {
-                message: "Session loaded. Please refresh your browser.".to_string(),
-            };
-            let _ = state.broadcaster.send(msg.to_json());
+            notify_game_replaced(&state, "Session loaded. Please refresh your browser.");
 
             Json(json!({
                 "success": true,
@@ -215,3 +818,934 @@ pub async fn load_game(
         })),
     }
 }
+
+/// Browse and search the adversary templates the GM can spawn
+///
+/// Filters: `?tier=2&role=skulk&q=thief`
+#[utoipa::path(
+    get,
+    path = "/api/adversaries",
+    tag = "content",
+    params(
+        ("tier" = Option<u8>, Query, description = "Only this tier"),
+        ("role" = Option<String>, Query, description = "Only this role, e.g. `skulk`"),
+        ("q" = Option<String>, Query, description = "Text matched against name, id and description"),
+    ),
+    responses(
+        (status = 200, description = "Matching templates", body = serde_json::Value),
+    )
+)]
+pub async fn adversary_templates(
+    State(state): State<AppState>,
+    Query(query): Query<TemplateQuery>,
+) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+
+    let templates: Vec<_> = game
+        .adversary_templates()
+        .into_iter()
+        .filter(|t| query.matches(t))
+        .collect();
+
+    Json(json!({
+        "templates": templates,
+        "homebrew_count": game.homebrew_templates.len()
+    }))
+}
+
+/// List the environments the GM can set a scene with
+#[utoipa::path(
+    get,
+    path = "/api/environments",
+    tag = "content",
+    responses(
+        (status = 200, description = "Environment templates", body = serde_json::Value),
+    )
+)]
+pub async fn environment_templates() -> Json<serde_json::Value> {
+    Json(json!({
+        "templates": EnvironmentTemplate::get_all_templates()
+    }))
+}
+
+/// Query for `POST /api/admin/validate`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ValidateQuery {
+    /// Fix what the scan finds rather than just reporting it
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// Scan the live game state for inconsistencies, optionally repairing them
+#[utoipa::path(
+    post,
+    path = "/api/admin/validate",
+    tag = "admin",
+    params(ValidateQuery),
+    responses(
+        (status = 200, description = "What the scan found, and whether it was repaired", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn validate_state(
+    State(state): State<AppState>,
+    Query(query): Query<ValidateQuery>,
+) -> Json<serde_json::Value> {
+    let mut game = state.game.write().await;
+    let report = crate::validation::validate(&mut game, query.repair);
+    let issues: Vec<serde_json::Value> = report
+        .issues
+        .iter()
+        .map(|issue| {
+            let mut value = json!(issue);
+            value["description"] = json!(issue.description());
+            value
+        })
+        .collect();
+
+    if report.repaired {
+        if let Some(event) = game.event_log.last().cloned() {
+            drop(game);
+            crate::websocket::broadcast_event(&state, &event).await;
+        }
+    }
+
+    Json(json!({
+        "success": true,
+        "clean": issues.is_empty(),
+        "repaired": report.repaired,
+        "issues": issues
+    }))
+}
+
+/// In-memory checkpoints the GM can roll back to, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/checkpoints",
+    tag = "game",
+    responses(
+        (status = 200, description = "Checkpoints, oldest first", body = serde_json::Value),
+    )
+)]
+pub async fn checkpoints(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+    let checkpoints: Vec<_> = game.checkpoints.iter().map(|c| c.to_info()).collect();
+    Json(json!({ "checkpoints": checkpoints }))
+}
+
+/// Query for `GET /api/chat`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChatQuery {
+    /// Most recent messages to return (default all kept)
+    pub limit: Option<usize>,
+}
+
+/// Recent chat, oldest first; whispers are never listed, and a running
+/// cutaway's dialogue only once it ends
+#[utoipa::path(
+    get,
+    path = "/api/chat",
+    tag = "game",
+    params(ChatQuery),
+    responses(
+        (status = 200, description = "Chat messages, oldest first", body = serde_json::Value),
+    )
+)]
+pub async fn chat(
+    State(state): State<AppState>,
+    Query(query): Query<ChatQuery>,
+) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+    let messages: Vec<_> = game
+        .chat_log
+        .iter()
+        .filter(|m| !m.is_whisper() && !m.cutaway)
+        .collect();
+    let skip = query
+        .limit
+        .map_or(0, |limit| messages.len().saturating_sub(limit));
+    Json(json!({ "messages": &messages[skip..] }))
+}
+
+/// Query for `GET /api/handouts`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HandoutQuery {
+    /// Also list what was shared with this character alone (needs the API
+    /// token)
+    pub character_id: Option<String>,
+}
+
+/// Handouts shared so far, oldest first: those for everyone, plus the
+/// character's own when one is given along with the API token
+///
+/// Players get their own over the WebSocket with `request_handouts`.
+#[utoipa::path(
+    get,
+    path = "/api/handouts",
+    tag = "content",
+    params(HandoutQuery),
+    responses(
+        (status = 200, description = "Handouts, oldest first", body = serde_json::Value),
+    )
+)]
+pub async fn handouts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<HandoutQuery>,
+) -> Json<serde_json::Value> {
+    let character_id = query
+        .character_id
+        .filter(|_| state.security.token_valid(&headers));
+    let handouts: Vec<_> = state
+        .handouts
+        .list()
+        .into_iter()
+        .filter(|h| match &character_id {
+            Some(id) => h.is_for(id),
+            None => h.is_public(),
+        })
+        .collect();
+    Json(json!({ "handouts": handouts }))
+}
+
+/// Query for `GET /api/handouts/:id`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HandoutContentQuery {
+    /// From a private handout's link; not needed with the API token
+    pub key: Option<String>,
+}
+
+/// A handout's content, served with its own content type
+///
+/// A private handout needs its link's key or the API token.
+#[utoipa::path(
+    get,
+    path = "/api/handouts/{id}",
+    tag = "content",
+    params(
+        ("id" = String, Path, description = "Handout id"),
+        HandoutContentQuery,
+    ),
+    responses(
+        (status = 200, description = "The handout, with its own content type", body = Vec<u8>),
+        (status = 404, description = "No such handout", body = serde_json::Value),
+    )
+)]
+pub async fn handout_content(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<HandoutContentQuery>,
+) -> Response {
+    let trusted = state.security.token_valid(&headers);
+    match state.handouts.content(&id, query.key.as_deref(), trusted) {
+        Ok((bytes, content_type)) => {
+            ([(header::CONTENT_TYPE, content_type)], bytes).into_response()
+        }
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "error": e
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// When the table next meets; public so it can be shared or embedded
+#[utoipa::path(
+    get,
+    path = "/api/next-session",
+    tag = "game",
+    responses(
+        (status = 200, description = "The next session, if one is scheduled", body = serde_json::Value),
+    )
+)]
+pub async fn next_session(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+    let next_session = game.next_session.as_ref().map(|s| s.to_info());
+    Json(json!({ "next_session": next_session }))
+}
+
+/// The quest board, in the order quests went up
+#[utoipa::path(
+    get,
+    path = "/api/quests",
+    tag = "game",
+    responses(
+        (status = 200, description = "Every quest with its objectives and countdowns", body = serde_json::Value),
+    )
+)]
+pub async fn quests(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+    Json(json!({ "quests": game.quests }))
+}
+
+/// Query for `GET /api/notes` and `GET /api/notes/all`
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NoteQuery {
+    /// Only notes with every one of these words, in the title or the text
+    #[serde(default)]
+    pub q: String,
+}
+
+/// Notes the GM shared with the table, most recently changed first
+#[utoipa::path(
+    get,
+    path = "/api/notes",
+    tag = "game",
+    params(NoteQuery),
+    responses(
+        (status = 200, description = "Shared notes matching the search", body = serde_json::Value),
+    )
+)]
+pub async fn notes(
+    State(state): State<AppState>,
+    Query(query): Query<NoteQuery>,
+) -> Json<serde_json::Value> {
+    let notes = state.game.read().await.search_notes(&query.q, false);
+    Json(json!({ "notes": notes }))
+}
+
+/// Every note, the GM's own included, most recently changed first
+#[utoipa::path(
+    get,
+    path = "/api/notes/all",
+    tag = "admin",
+    params(NoteQuery),
+    responses(
+        (status = 200, description = "Notes matching the search", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn all_notes(
+    State(state): State<AppState>,
+    Query(query): Query<NoteQuery>,
+) -> Json<serde_json::Value> {
+    let notes = state.game.read().await.search_notes(&query.q, true);
+    Json(json!({ "notes": notes }))
+}
+
+/// Write a note: `title`, `body` and `visibility` ("gm", the default, or
+/// "table")
+#[utoipa::path(
+    post,
+    path = "/api/notes",
+    tag = "admin",
+    request_body(content = serde_json::Value, description = "The note's title, text and visibility"),
+    responses(
+        (status = 200, description = "The note as kept", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn create_note(
+    State(state): State<AppState>,
+    Json(draft): Json<NoteDraft>,
+) -> Json<serde_json::Value> {
+    note_saved(crate::websocket::save_note(&state, None, &draft).await)
+}
+
+/// Rewrite a note
+#[utoipa::path(
+    put,
+    path = "/api/notes/{id}",
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "Note id"),
+    ),
+    request_body(content = serde_json::Value, description = "The note's title, text and visibility"),
+    responses(
+        (status = 200, description = "The note as kept", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn update_note(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(draft): Json<NoteDraft>,
+) -> Json<serde_json::Value> {
+    note_saved(crate::websocket::save_note(&state, Some(&id), &draft).await)
+}
+
+fn note_saved(result: Result<Note, String>) -> Json<serde_json::Value> {
+    match result {
+        Ok(note) => Json(json!({
+            "success": true,
+            "note": note
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Throw a note away
+#[utoipa::path(
+    delete,
+    path = "/api/notes/{id}",
+    tag = "admin",
+    params(
+        ("id" = String, Path, description = "Note id"),
+    ),
+    responses(
+        (status = 200, description = "Whether the note was deleted", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn delete_note(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<serde_json::Value> {
+    match crate::websocket::delete_note(&state, &id).await {
+        Ok(note) => Json(json!({
+            "success": true,
+            "title": note.title
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Delete a character for good
+#[utoipa::path(
+    delete,
+    path = "/api/characters/{id}",
+    tag = "characters",
+    params(
+        ("id" = String, Path, description = "Character id"),
+    ),
+    responses(
+        (status = 200, description = "The deleted character's name", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn delete_character(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<serde_json::Value> {
+    remove_character(&state, &id, false).await
+}
+
+/// Retire a character into the vault
+#[utoipa::path(
+    post,
+    path = "/api/characters/{id}/retire",
+    tag = "characters",
+    params(
+        ("id" = String, Path, description = "Character id"),
+    ),
+    responses(
+        (status = 200, description = "The retired character's name", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn retire_character(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Json<serde_json::Value> {
+    remove_character(&state, &id, true).await
+}
+
+async fn remove_character(state: &AppState, id: &str, retire: bool) -> Json<serde_json::Value> {
+    match crate::websocket::remove_character(state, id, retire).await {
+        Ok(name) => Json(json!({
+            "success": true,
+            "name": name
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// A character's full sheet as a JSON file, for taking to another table
+#[utoipa::path(
+    get,
+    path = "/api/characters/{id}/export",
+    tag = "characters",
+    params(
+        ("id" = String, Path, description = "Character id"),
+    ),
+    responses(
+        (status = 200, description = "The character file", body = serde_json::Value),
+        (status = 404, description = "No such character", body = serde_json::Value),
+    )
+)]
+pub async fn export_character(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let game = state.game.read().await;
+    let character = uuid::Uuid::parse_str(&id)
+        .ok()
+        .and_then(|id| game.get_character(&id));
+    let Some(character) = character else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "error": "Character not found"
+            })),
+        )
+            .into_response();
+    };
+
+    let export = CharacterExport::new(character);
+    (
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", export.file_name()),
+        )],
+        Json(export),
+    )
+        .into_response()
+}
+
+/// Bring in a character exported from this or another table
+#[utoipa::path(
+    post,
+    path = "/api/characters/import",
+    tag = "characters",
+    request_body(content = serde_json::Value, description = "A file from `GET /api/characters/{id}/export`"),
+    responses(
+        (status = 200, description = "The imported character's id and name", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn import_character(
+    State(state): State<AppState>,
+    Json(payload): Json<serde_json::Value>,
+) -> Json<serde_json::Value> {
+    let imported = match serde_json::from_value::<CharacterExport>(payload) {
+        Ok(export) => crate::websocket::import_character(&state, &export).await,
+        Err(e) => Err(format!("Invalid character file: {}", e)),
+    };
+    match imported {
+        Ok(character) => Json(json!({
+            "success": true,
+            "character_id": character.id.to_string(),
+            "name": character.name
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Characters retired so far, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/vault",
+    tag = "characters",
+    responses(
+        (status = 200, description = "Retired characters, oldest first", body = serde_json::Value),
+    )
+)]
+pub async fn vault(State(state): State<AppState>) -> Json<serde_json::Value> {
+    match state.vault.list() {
+        Ok(retired) => Json(json!({
+            "success": true,
+            "characters": retired
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Encounter presets the GM has kept, in the order they were first saved
+#[utoipa::path(
+    get,
+    path = "/api/encounters",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Encounter presets", body = serde_json::Value),
+    )
+)]
+pub async fn encounter_presets(State(state): State<AppState>) -> Json<serde_json::Value> {
+    match state.encounters.list() {
+        Ok(presets) => Json(json!({
+            "success": true,
+            "presets": presets
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Price a fight against the party's Battle Points budget: what each
+/// adversary costs, the adjustments that apply and what's left
+#[utoipa::path(
+    post,
+    path = "/api/encounters/budget",
+    tag = "admin",
+    request_body(content = serde_json::Value, description = "Party size, tier and the adversaries in the fight"),
+    responses(
+        (status = 200, description = "The budget, spent and remaining", body = serde_json::Value),
+    )
+)]
+pub async fn encounter_budget(
+    State(state): State<AppState>,
+    Json(plan): Json<EncounterPlan>,
+) -> Json<serde_json::Value> {
+    match state.game.read().await.price_encounter(&plan) {
+        Ok(budget) => Json(json!({
+            "success": true,
+            "budget": budget
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Maps uploaded so far, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/maps",
+    tag = "content",
+    responses(
+        (status = 200, description = "Uploaded maps, oldest first", body = serde_json::Value),
+    )
+)]
+pub async fn maps(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(json!({ "maps": state.maps.list() }))
+}
+
+/// Upload a battle map and put it on the table
+///
+/// Multipart form with a `scene` name and the image as `file`.
+#[utoipa::path(
+    post,
+    path = "/api/maps",
+    tag = "content",
+    request_body(content = MapUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The map now on the table", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn upload_map(
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Json<serde_json::Value> {
+    let map = match read_map_upload(multipart)
+        .await
+        .and_then(|upload| state.maps.save(&upload.scene, &upload.file))
+    {
+        Ok(map) => map,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": e
+            }))
+        }
+    };
+
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    let modifiers_before = game.modifiers.len();
+    game.set_map(Some(map.clone()));
+    let events: Vec<_> = game.event_log.iter().skip(events_before).cloned().collect();
+
+    let _ = state
+        .broadcaster
+        .send(crate::protocol::ServerMessage::MapChanged {
+            map: Some(map.clone()),
+        });
+    let _ = state
+        .broadcaster
+        .send(crate::protocol::ServerMessage::SceneLayoutChanged { layout: map.layout });
+    crate::websocket::broadcast_fog(&state, &game);
+    let _ = state
+        .broadcaster
+        .send(crate::protocol::ServerMessage::DrawingsChanged {
+            drawings: game.current_drawings().to_vec(),
+        });
+    if game.modifiers.len() != modifiers_before {
+        crate::websocket::broadcast_modifiers(&state, &game);
+    }
+    drop(game);
+    for event in &events {
+        crate::websocket::broadcast_event(&state, event).await;
+    }
+
+    Json(json!({
+        "success": true,
+        "map": map
+    }))
+}
+
+/// Form of `POST /api/maps`
+#[derive(Debug, ToSchema)]
+pub struct MapUpload {
+    /// Scene the map is for
+    pub scene: String,
+    /// The image
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+/// The scene name and image bytes from a map upload form
+async fn read_map_upload(mut multipart: Multipart) -> Result<MapUpload, String> {
+    let mut scene = String::new();
+    let mut bytes = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| format!("Invalid upload: {}", e))?
+    {
+        match field.name() {
+            Some("scene") => {
+                scene = field
+                    .text()
+                    .await
+                    .map_err(|e| format!("Invalid upload: {}", e))?
+            }
+            Some("file") => {
+                bytes = Some(
+                    field
+                        .bytes()
+                        .await
+                        .map_err(|e| format!("Invalid upload: {}", e))?,
+                )
+            }
+            _ => {}
+        }
+    }
+    let bytes = bytes.ok_or_else(|| "No map image in the upload".to_string())?;
+    Ok(MapUpload {
+        scene,
+        file: bytes.to_vec(),
+    })
+}
+
+/// The table's house rules and options
+#[utoipa::path(
+    get,
+    path = "/api/settings",
+    tag = "game",
+    responses(
+        (status = 200, description = "House rules and options", body = serde_json::Value),
+    )
+)]
+pub async fn settings(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+    Json(json!({ "settings": game.settings }))
+}
+
+/// Current narrative mode and combat phrasings
+#[utoipa::path(
+    get,
+    path = "/api/narrative",
+    tag = "content",
+    responses(
+        (status = 200, description = "Narrative mode and phrasings", body = serde_json::Value),
+    )
+)]
+pub async fn narrative(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+    Json(json!({
+        "mode": game.settings.narrative_mode,
+        "templates": game.narrative_templates
+    }))
+}
+
+/// Replace the combat phrasings
+#[utoipa::path(
+    post,
+    path = "/api/narrative/templates",
+    tag = "content",
+    request_body(content = serde_json::Value, description = "Phrasings, as from `GET /api/narrative`"),
+    responses(
+        (status = 200, description = "Whether the phrasings were replaced", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn set_narrative(
+    State(state): State<AppState>,
+    Json(templates): Json<NarrativeTemplates>,
+) -> Json<serde_json::Value> {
+    let mut game = state.game.write().await;
+    match game.set_narrative_templates(templates) {
+        Ok(()) => Json(json!({ "success": true })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Position keyframes of a combat encounter, for the post-fight scrubber
+#[utoipa::path(
+    get,
+    path = "/api/combats/{id}/keyframes",
+    tag = "history",
+    params(
+        ("id" = String, Path, description = "Encounter id"),
+    ),
+    responses(
+        (status = 200, description = "Keyframes of the encounter", body = serde_json::Value),
+    )
+)]
+pub async fn combat_keyframes(
+    State(state): State<AppState>,
+    Path(encounter_id): Path<String>,
+) -> Json<serde_json::Value> {
+    let game = state.game.read().await;
+
+    match game.combat_recording(&encounter_id) {
+        Some(recording) => Json(json!({
+            "success": true,
+            "encounter_id": recording.encounter_id,
+            "interval_ms": crate::game::KEYFRAME_INTERVAL.as_millis() as u64,
+            "keyframes": recording.keyframes
+        })),
+        None => Json(json!({
+            "success": false,
+            "error": format!("No keyframes for combat {}", encounter_id)
+        })),
+    }
+}
+
+/// Re-read homebrew adversary templates from disk
+#[utoipa::path(
+    post,
+    path = "/api/adversaries/reload",
+    tag = "admin",
+    responses(
+        (status = 200, description = "How many homebrew templates were read", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn reload_adversaries(State(state): State<AppState>) -> Json<serde_json::Value> {
+    match reload_adversary_templates(&state).await {
+        Ok(count) => Json(json!({
+            "success": true,
+            "homebrew_count": count
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// Reload homebrew templates and announce the result to every client
+pub async fn reload_adversary_templates(state: &AppState) -> Result<usize, String> {
+    let mut game = state.game.write().await;
+    let count = game.reload_adversary_templates()?;
+    let event = game.event_log.last().cloned();
+    drop(game);
+
+    if let Some(event) = event {
+        crate::websocket::broadcast_event(state, &event).await;
+    }
+
+    Ok(count)
+}
+
+/// Body of `POST /api/dice/seed`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DiceSeedRequest {
+    /// Left out (or `null`) for unpredictable dice again
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Reseed the session's dice, so the same rolls come up again (e.g. to
+/// replay a session); the seed is never shown to players
+#[utoipa::path(
+    post,
+    path = "/api/dice/seed",
+    tag = "admin",
+    request_body = DiceSeedRequest,
+    responses(
+        (status = 200, description = "The seed now in use", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn seed_dice(
+    State(state): State<AppState>,
+    Json(request): Json<DiceSeedRequest>,
+) -> Json<serde_json::Value> {
+    let mut game = state.game.write().await;
+    game.dice = match request.seed {
+        Some(seed) => DiceRng::seeded(seed),
+        None => DiceRng::from_entropy(),
+    };
+    println!("🎲 Dice reseeded: {:?}", request.seed);
+    Json(json!({
+        "success": true,
+        "seed": request.seed
+    }))
+}
+
+/// Replace the current game with the demo campaign
+#[utoipa::path(
+    post,
+    path = "/api/demo",
+    tag = "admin",
+    responses(
+        (status = 200, description = "What the demo campaign holds", body = serde_json::Value),
+    ),
+    security(("api_token" = []))
+)]
+pub async fn load_demo(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let maps = state.maps.clone();
+    let background = match blocking(move || maps.demo_background(crate::demo::DEMO_SCENE)).await {
+        Ok(map) => map,
+        Err(e) => {
+            return Json(json!({
+                "success": false,
+                "error": format!("Failed to load demo: {}", e)
+            }))
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    if let Err(e) = crate::demo::populate_demo(&mut game, Some(background)) {
+        return Json(json!({
+            "success": false,
+            "error": format!("Failed to load demo: {}", e)
+        }));
+    }
+
+    let character_count = game.character_count();
+    let adversary_count = game.adversaries.len();
+    drop(game);
+
+    // Notify all connected clients to refresh
+    notify_game_replaced(&state, "Demo campaign loaded. Please refresh your browser.");
+
+    Json(json!({
+        "success": true,
+        "character_count": character_count,
+        "adversary_count": adversary_count
+    }))
+}
+
+/// Tell every client the game under them was replaced, so they reload
+fn notify_game_replaced(state: &AppState, message: &str) {
+    let msg = crate::protocol::ServerMessage::Error {
+        code: crate::protocol::errors::ErrorCode::GameReplaced,
+        message: message.to_string(),
+        request_type: None,
+        request_id: None,
+        connection_id: None,
+    };
+    let _ = state.broadcaster.send(msg);
+}
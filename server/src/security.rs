@@ -0,0 +1,172 @@
+//! Basic request hardening: WebSocket Origin checks and an optional API token
+//! for REST routes that change game state
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::websocket::AppState;
+
+/// Extra allowed WebSocket origins, comma-separated (e.g. "https://vtt.example.com")
+pub const ALLOWED_ORIGINS_ENV: &str = "DAGGERHEART_ALLOWED_ORIGINS";
+
+/// Token required on REST mutation routes when set
+pub const API_TOKEN_ENV: &str = "DAGGERHEART_API_TOKEN";
+
+/// Header carrying the API token
+pub const API_TOKEN_HEADER: &str = "x-api-token";
+
+/// Security settings shared by all handlers
+#[derive(Debug, Clone, Default)]
+pub struct SecurityConfig {
+    pub allowed_origins: Vec<String>,
+    pub api_token: Option<String>,
+}
+
+impl SecurityConfig {
+    /// Load settings from environment variables
+    pub fn from_env() -> Self {
+        let allowed_origins = std::env::var(ALLOWED_ORIGINS_ENV)
+            .map(|v| {
+                v.split(',')
+                    .map(|o| o.trim().trim_end_matches('/').to_string())
+                    .filter(|o| !o.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let api_token = std::env::var(API_TOKEN_ENV).ok().filter(|t| !t.is_empty());
+
+        Self {
+            allowed_origins,
+            api_token,
+        }
+    }
+
+    /// Check the Origin header of a WebSocket upgrade
+    ///
+    /// Requests without an Origin (non-browser clients) are allowed. Browser
+    /// requests must come from the host they connected to or an allowed origin.
+    pub fn origin_allowed(&self, headers: &HeaderMap) -> bool {
+        let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) else {
+            return true;
+        };
+        let origin = origin.trim_end_matches('/');
+
+        if self.allowed_origins.iter().any(|o| o == origin) {
+            return true;
+        }
+
+        let host = headers.get(header::HOST).and_then(|v| v.to_str().ok());
+        match (origin.split_once("://"), host) {
+            (Some((_, origin_host)), Some(host)) => origin_host.eq_ignore_ascii_case(host),
+            _ => false,
+        }
+    }
+
+    /// Check the API token header (always valid when no token is configured)
+    pub fn token_valid(&self, headers: &HeaderMap) -> bool {
+        self.token_matches(headers.get(API_TOKEN_HEADER).and_then(|v| v.to_str().ok()))
+    }
+
+    /// Check a token passed some other way, e.g. in the WebSocket URL
+    pub fn token_matches(&self, token: Option<&str>) -> bool {
+        let Some(expected) = &self.api_token else {
+            return true;
+        };
+
+        token.is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+    }
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Middleware rejecting REST mutations that lack a valid API token
+pub async fn require_api_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.security.token_valid(request.headers()) {
+        return next.run(request).await;
+    }
+
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({
+            "success": false,
+            "error": "Missing or invalid API token"
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(origin: Option<&'static str>, host: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static(host));
+        if let Some(origin) = origin {
+            headers.insert(header::ORIGIN, HeaderValue::from_static(origin));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_origin_same_host_allowed() {
+        let config = SecurityConfig::default();
+        assert!(config.origin_allowed(&headers(
+            Some("http://192.168.1.10:3000"),
+            "192.168.1.10:3000"
+        )));
+        assert!(config.origin_allowed(&headers(None, "192.168.1.10:3000")));
+    }
+
+    #[test]
+    fn test_origin_cross_site_rejected() {
+        let config = SecurityConfig::default();
+        assert!(!config.origin_allowed(&headers(
+            Some("https://evil.example.com"),
+            "192.168.1.10:3000"
+        )));
+    }
+
+    #[test]
+    fn test_origin_allowlist() {
+        let config = SecurityConfig {
+            allowed_origins: vec!["https://vtt.example.com".to_string()],
+            api_token: None,
+        };
+        assert!(config.origin_allowed(&headers(Some("https://vtt.example.com"), "127.0.0.1:3000")));
+    }
+
+    #[test]
+    fn test_api_token() {
+        let open = SecurityConfig::default();
+        assert!(open.token_valid(&HeaderMap::new()));
+
+        let locked = SecurityConfig {
+            allowed_origins: Vec::new(),
+            api_token: Some("secret".to_string()),
+        };
+        assert!(!locked.token_valid(&HeaderMap::new()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(API_TOKEN_HEADER, HeaderValue::from_static("wrong!"));
+        assert!(!locked.token_valid(&headers));
+
+        headers.insert(API_TOKEN_HEADER, HeaderValue::from_static("secret"));
+        assert!(locked.token_valid(&headers));
+    }
+}
@@ -0,0 +1,135 @@
+//! Character sheet broadcasts: the whole sheet the first time, then only
+//! what changed
+//!
+//! The tracker remembers the last sheet sent for each character and the
+//! sequence number it went out with, so each change can be diffed against
+//! what clients already hold.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::protocol::{sheets::CharacterChanges, CharacterData, ServerMessage};
+
+/// Last sheet sent per character
+#[derive(Debug, Default)]
+pub struct SheetTracker {
+    inner: Mutex<Sheets>,
+}
+
+#[derive(Debug, Default)]
+struct Sheets {
+    /// Highest sequence number used so far
+    sequence: u64,
+    /// character_id -> (sequence, sheet)
+    sent: HashMap<String, (u64, CharacterData)>,
+}
+
+impl SheetTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The message bringing everyone up to date with a character's sheet,
+    /// `None` if nothing changed since it was last sent
+    pub fn update(&self, character_id: &str, sheet: CharacterData) -> Option<ServerMessage> {
+        let mut sheets = self.inner.lock().unwrap();
+        let changes = match sheets.sent.get(character_id) {
+            Some((since, old)) => {
+                let changes = CharacterChanges::between(old, &sheet);
+                if changes.is_empty() {
+                    return None;
+                }
+                Some((*since, changes))
+            }
+            None => None,
+        };
+
+        sheets.sequence += 1;
+        let sequence = sheets.sequence;
+        sheets
+            .sent
+            .insert(character_id.to_string(), (sequence, sheet.clone()));
+        Some(match changes {
+            Some((since, changes)) => ServerMessage::CharacterChanged {
+                character_id: character_id.to_string(),
+                sequence,
+                since,
+                changes,
+            },
+            None => ServerMessage::CharacterUpdated {
+                character_id: character_id.to_string(),
+                character: sheet,
+                sequence,
+                connection_id: None,
+            },
+        })
+    }
+
+    /// The last sheet sent for a character, whole, for one connection (or
+    /// everyone with `None`)
+    pub fn full(&self, character_id: &str, connection_id: Option<String>) -> Option<ServerMessage> {
+        let sheets = self.inner.lock().unwrap();
+        let (sequence, sheet) = sheets.sent.get(character_id)?;
+        Some(ServerMessage::CharacterUpdated {
+            character_id: character_id.to_string(),
+            character: sheet.clone(),
+            sequence: *sequence,
+            connection_id,
+        })
+    }
+
+    /// Stop tracking a character that left the game
+    pub fn forget(&self, character_id: &str) {
+        self.inner.lock().unwrap().sent.remove(character_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+    use daggerheart_engine::character::{Ancestry, Attributes, Class};
+
+    #[test]
+    fn test_sheet_updates_send_changes_after_the_first() {
+        let mut game = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let id = theron.id.to_string();
+        let tracker = SheetTracker::new();
+
+        let Some(ServerMessage::CharacterUpdated {
+            sequence: first, ..
+        }) = tracker.update(&id, theron.to_data())
+        else {
+            panic!("expected the whole sheet first");
+        };
+        assert!(tracker.update(&id, theron.to_data()).is_none());
+
+        let mut hurt = theron.to_data();
+        hurt.hp.current -= 2;
+        match tracker.update(&id, hurt.clone()) {
+            Some(ServerMessage::CharacterChanged {
+                sequence,
+                since,
+                changes,
+                ..
+            }) => {
+                assert_eq!(since, first);
+                assert!(sequence > first);
+                assert_eq!(changes.hp, Some(hurt.hp.clone()));
+                assert_eq!(changes.name, None);
+            }
+            other => panic!("expected a change, got {:?}", other),
+        }
+
+        // Catching up gets the latest sheet
+        let Some(ServerMessage::CharacterUpdated { character, .. }) =
+            tracker.full(&id, Some("conn-1".to_string()))
+        else {
+            panic!("expected the whole sheet");
+        };
+        assert_eq!(character, hurt);
+    }
+}
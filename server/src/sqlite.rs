@@ -0,0 +1,315 @@
+//! SQLite save storage (`--features sqlite`)
+//!
+//! Each save is a row in `sessions`, with its characters, adversaries and
+//! event log in tables of their own so a campaign's history can be queried
+//! directly. The rest of the save (map, fog, settings…) stays JSON in
+//! `sessions.data`, and rows are read back through the same format
+//! migrations as save files.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use crate::game::GameEvent;
+use crate::save::{SaveStore, SaveSummary, SavedSession};
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS sessions (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        last_saved TEXT NOT NULL,
+        schema_version INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS characters (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (session_id, id)
+    );
+    CREATE TABLE IF NOT EXISTS adversaries (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (session_id, id)
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        seq INTEGER NOT NULL,
+        event_type TEXT NOT NULL,
+        message TEXT NOT NULL,
+        character_name TEXT,
+        data TEXT NOT NULL,
+        PRIMARY KEY (session_id, seq)
+    );
+";
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("Database error: {}", e)
+}
+
+fn json_error(e: serde_json::Error) -> String {
+    format!("Failed to serialize session: {}", e)
+}
+
+/// Saves in one SQLite database
+pub struct SqliteSaveStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteSaveStore {
+    /// Open (or create) the database at `path`
+    pub fn open(path: &str) -> Result<Self, String> {
+        Self::with_connection(Connection::open(path).map_err(db_error)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|_| "Database connection poisoned".to_string())
+    }
+
+    /// The `data` column of a character or adversary table, in save order
+    fn rows(conn: &Connection, table: &str, session_id: &str) -> Result<Vec<Value>, String> {
+        let mut statement = conn
+            .prepare(&format!(
+                "SELECT data FROM {} WHERE session_id = ?1 ORDER BY position",
+                table
+            ))
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map(params![session_id], |row| row.get::<_, String>(0))
+            .map_err(db_error)?;
+        rows.map(|data| {
+            serde_json::from_str(&data.map_err(db_error)?)
+                .map_err(|e| format!("Corrupt {} row: {}", table, e))
+        })
+        .collect()
+    }
+}
+
+impl SaveStore for SqliteSaveStore {
+    fn list(&self) -> Result<Vec<SaveSummary>, String> {
+        let conn = self.lock()?;
+        let mut statement = conn
+            .prepare("SELECT id, name, last_saved FROM sessions ORDER BY last_saved DESC")
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(db_error)?;
+
+        let mut saves = Vec::new();
+        for row in rows {
+            let (slot, name, saved_at) = row.map_err(db_error)?;
+            let saved_at = DateTime::parse_from_rfc3339(&saved_at)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| format!("Corrupt save time for {}: {}", slot, e))?;
+            saves.push(SaveSummary {
+                slot,
+                name,
+                saved_at,
+            });
+        }
+        Ok(saves)
+    }
+
+    fn save(&self, session: &SavedSession, events: &[GameEvent]) -> Result<String, String> {
+        let mut data = serde_json::to_value(session).map_err(json_error)?;
+        let (characters, adversaries) = match data.as_object_mut() {
+            Some(fields) => (
+                fields.remove("characters").unwrap_or_default(),
+                fields.remove("adversaries").unwrap_or_default(),
+            ),
+            None => return Err("Session did not serialize to an object".to_string()),
+        };
+
+        let mut conn = self.lock()?;
+        let tx = conn.transaction().map_err(db_error)?;
+        tx.execute(
+            "INSERT INTO sessions (id, name, created_at, last_saved, schema_version, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                session.id,
+                session.name,
+                session.created_at.to_rfc3339(),
+                session.last_saved.to_rfc3339(),
+                session.schema_version,
+                data.to_string()
+            ],
+        )
+        .map_err(db_error)?;
+
+        for (table, rows) in [("characters", characters), ("adversaries", adversaries)] {
+            for (position, row) in rows.as_array().into_iter().flatten().enumerate() {
+                let text = |field: &str| row.get(field).and_then(Value::as_str).unwrap_or_default();
+                tx.execute(
+                    &format!(
+                        "INSERT INTO {} (session_id, position, id, name, data)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        table
+                    ),
+                    params![
+                        session.id,
+                        position as i64,
+                        text("id"),
+                        text("name"),
+                        row.to_string()
+                    ],
+                )
+                .map_err(db_error)?;
+            }
+        }
+
+        for (seq, event) in events.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO events (session_id, seq, event_type, message, character_name, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    session.id,
+                    seq as i64,
+                    format!("{:?}", event.event_type),
+                    event.message,
+                    event.character_name,
+                    serde_json::to_string(event).map_err(json_error)?
+                ],
+            )
+            .map_err(db_error)?;
+        }
+
+        tx.commit().map_err(db_error)?;
+        Ok(session.id.clone())
+    }
+
+    fn load(&self, slot: &str) -> Result<SavedSession, String> {
+        let conn = self.lock()?;
+        let data: String = conn
+            .query_row(
+                "SELECT data FROM sessions WHERE id = ?1",
+                params![slot],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_error)?
+            .ok_or_else(|| "Save not found".to_string())?;
+
+        let mut save: Value =
+            serde_json::from_str(&data).map_err(|e| format!("Corrupt save {}: {}", slot, e))?;
+        let characters = Self::rows(&conn, "characters", slot)?;
+        let adversaries = Self::rows(&conn, "adversaries", slot)?;
+        if let Some(fields) = save.as_object_mut() {
+            fields.insert("characters".to_string(), Value::Array(characters));
+            fields.insert("adversaries".to_string(), Value::Array(adversaries));
+        }
+        SavedSession::from_value(save)
+    }
+
+    fn load_events(&self, slot: &str) -> Result<Vec<GameEvent>, String> {
+        let conn = self.lock()?;
+        let mut statement = conn
+            .prepare("SELECT data FROM events WHERE session_id = ?1 ORDER BY seq")
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map(params![slot], |row| row.get::<_, String>(0))
+            .map_err(db_error)?;
+        rows.map(|data| {
+            serde_json::from_str(&data.map_err(db_error)?)
+                .map_err(|e| format!("Corrupt event row: {}", e))
+        })
+        .collect()
+    }
+
+    fn rename(&self, slot: &str, name: String) -> Result<SavedSession, String> {
+        let updated = self
+            .lock()?
+            .execute(
+                "UPDATE sessions SET name = ?1, data = json_set(data, '$.name', ?1) WHERE id = ?2",
+                params![name, slot],
+            )
+            .map_err(db_error)?;
+        if updated == 0 {
+            return Err("Save not found".to_string());
+        }
+        self.load(slot)
+    }
+
+    fn delete(&self, slot: &str) -> Result<(), String> {
+        let deleted = self
+            .lock()?
+            .execute("DELETE FROM sessions WHERE id = ?1", params![slot])
+            .map_err(db_error)?;
+        if deleted == 0 {
+            return Err("Save not found".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEventType, GameState};
+    use crate::protocol::Position;
+    use daggerheart_engine::character::{Ancestry, Attributes, Class};
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let store =
+            SqliteSaveStore::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let mut game = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        game.spawn_adversary("goblin", Position::new(10.0, 10.0))
+            .unwrap();
+        game.add_event(
+            GameEventType::SystemMessage,
+            "Combat started".to_string(),
+            None,
+            None,
+        );
+
+        let session = SavedSession::from_game_state(&game, "Campaign".to_string());
+        let slot = store.save(&session, &game.event_log).unwrap();
+        assert_eq!(store.list().unwrap()[0].name, "Campaign");
+
+        let loaded = store.load(&slot).unwrap();
+        assert_eq!(loaded.characters[0].name, "Theron");
+        assert_eq!(loaded.adversaries.map(|a| a.len()), Some(1));
+        assert_eq!(
+            store.load_events(&slot).unwrap().len(),
+            game.event_log.len()
+        );
+
+        assert_eq!(
+            store.rename(&slot, "Renamed".to_string()).unwrap().name,
+            "Renamed"
+        );
+        store.delete(&slot).unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(store.load(&slot).is_err());
+        assert!(
+            store.load_events(&slot).unwrap().is_empty(),
+            "events go too"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Where saves are kept
+//!
+//! `DAGGERHEART_STORAGE=files` (the default) writes one JSON file per save
+//! to the saves directory. `DAGGERHEART_STORAGE=sqlite` keeps sessions, characters,
+//! adversaries and event logs in one SQLite database at
+//! `DAGGERHEART_SQLITE`, for servers built with `--features sqlite`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::save::{FileSaveStore, SaveStore};
+
+/// "files" or "sqlite"
+pub const STORAGE_ENV: &str = "DAGGERHEART_STORAGE";
+
+/// SQLite database path
+#[cfg(feature = "sqlite")]
+pub const SQLITE_FILE_ENV: &str = "DAGGERHEART_SQLITE";
+
+/// Used when `DAGGERHEART_SQLITE` is not set
+#[cfg(feature = "sqlite")]
+pub const DEFAULT_SQLITE_FILE: &str = "daggerheart.db";
+
+/// The store named by `DAGGERHEART_STORAGE`, falling back to files in
+/// `saves_dir`
+pub fn from_env(saves_dir: &Path) -> Arc<dyn SaveStore> {
+    let files = FileSaveStore::new(saves_dir);
+    match std::env::var(STORAGE_ENV).as_deref() {
+        Ok("sqlite") => sqlite_store(files),
+        Ok("files") | Ok("") | Err(_) => {
+            tracing::info!("💾 Saves kept as files in {}", saves_dir.display());
+            Arc::new(files)
+        }
+        Ok(other) => {
+            tracing::warn!("Unknown {} \"{}\", saving to files", STORAGE_ENV, other);
+            Arc::new(files)
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_store(files: FileSaveStore) -> Arc<dyn SaveStore> {
+    let path = std::env::var(SQLITE_FILE_ENV).unwrap_or_else(|_| DEFAULT_SQLITE_FILE.to_string());
+    match crate::sqlite::SqliteSaveStore::open(&path) {
+        Ok(store) => {
+            tracing::info!("💾 Saves kept in SQLite at {}", path);
+            Arc::new(store)
+        }
+        Err(e) => {
+            tracing::warn!("Cannot open {}, saving to files: {}", path, e);
+            Arc::new(files)
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_store(files: FileSaveStore) -> Arc<dyn SaveStore> {
+    tracing::warn!("This server was built without SQLite (--features sqlite), saving to files");
+    Arc::new(files)
+}
@@ -0,0 +1,103 @@
+//! The vault: characters retired from play
+//!
+//! Retiring a character takes it off the table but keeps its sheet, so it
+//! can be looked at (or brought back in a save) later. The vault is one
+//! JSON file holding every retired character, oldest first.
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::game::Character;
+use crate::save::SavedCharacter;
+
+/// Vault file path
+pub const VAULT_FILE_ENV: &str = "DAGGERHEART_VAULT";
+
+/// Used when the environment variable is not set
+pub const DEFAULT_VAULT_FILE: &str = "vault.json";
+
+/// A character in the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiredCharacter {
+    pub character: SavedCharacter,
+    pub retired_at: DateTime<Utc>,
+}
+
+/// Retired characters on disk
+#[derive(Debug, Clone)]
+pub struct Vault {
+    path: PathBuf,
+}
+
+impl Vault {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Use the file named by `DAGGERHEART_VAULT`
+    pub fn from_env() -> Self {
+        Self::new(std::env::var(VAULT_FILE_ENV).unwrap_or_else(|_| DEFAULT_VAULT_FILE.to_string()))
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Every retired character, oldest first (none before the first retirement)
+    pub fn list(&self) -> Result<Vec<RetiredCharacter>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let json = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Corrupt vault: {}", e))
+    }
+
+    /// Add a character to the vault
+    pub fn retire(&self, character: &Character) -> Result<RetiredCharacter, String> {
+        let mut retired = self.list()?;
+        let entry = RetiredCharacter {
+            character: SavedCharacter::from_character(character),
+            retired_at: Utc::now(),
+        };
+        retired.push(entry.clone());
+
+        let json = serde_json::to_string_pretty(&retired)
+            .map_err(|e| format!("Failed to serialize vault: {}", e))?;
+        fs::write(&self.path, json)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+    use daggerheart_engine::character::{Ancestry, Attributes, Class};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_retire_keeps_every_character() {
+        let vault = Vault::new(std::env::temp_dir().join(format!("vault-{}.json", Uuid::new_v4())));
+        assert!(vault.list().unwrap().is_empty());
+
+        let mut game = GameState::new();
+        let attrs = Attributes::from_array([2, 1, 1, 0, 0, -1]).unwrap();
+        let theron =
+            game.create_character("Theron".to_string(), Class::Warrior, Ancestry::Human, attrs);
+        let mira = game.create_character("Mira".to_string(), Class::Rogue, Ancestry::Faerie, attrs);
+        vault.retire(&theron).unwrap();
+        vault.retire(&mira).unwrap();
+
+        let retired = vault.list().unwrap();
+        let names: Vec<_> = retired.iter().map(|r| r.character.name.as_str()).collect();
+        assert_eq!(names, ["Theron", "Mira"]);
+        assert_eq!(retired[0].character.id, theron.id.to_string());
+
+        fs::remove_file(vault.path()).unwrap();
+    }
+}
@@ -3,67 +3,328 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ConnectInfo, Query, State, WebSocketUpgrade,
     },
-    response::Response,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use futures::{sink::SinkExt, stream::StreamExt};
-use tokio::sync::broadcast;
+use serde::Deserialize;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
 
 use daggerheart_engine::character::{Ancestry, Attributes, Class};
 
 use crate::{
-    game::{self, GameState, SharedGameState},
-    protocol::{self, CharacterInfo, ClientMessage, ServerMessage},
+    assets::ClientAssets,
+    auras::Aura,
+    config::ServerConfig,
+    damage::{DamageAdjustment, DamageType, Defenses},
+    dice::{self, DiceRoll},
+    encounters::EncounterStore,
+    equipment::{Armor, Weapon},
+    game::{self, GameState},
+    handouts::HandoutStore,
+    hazards::{Hazard, HazardArea, HazardOutcome},
+    journal::Journal,
+    leveling::{self, Advancement},
+    maps::MapStore,
+    metrics::Metrics,
+    movement::{MoveThrottle, Offer},
+    narrative::CombatBeat,
+    preferences::UserPreferences,
+    protocol::{
+        self, compat,
+        drawings::Drawing,
+        encoding::Encoding,
+        errors::{ErrorCode, RequestHeader},
+        events::EventLogQuery,
+        fog::FogState,
+        journal::JournalRecord,
+        maps::SceneLayout,
+        notes::{Note, NoteDraft},
+        quests::QuestDraft,
+        CharacterInfo, ClientMessage, ConnectionRole, ServerMessage,
+    },
+    ratelimit::{RateLimiter, Verdict},
+    recorder::HistoryRecorder,
+    rng::DiceRng,
+    save::{CharacterExport, SaveStore},
+    security::SecurityConfig,
+    sheets::SheetTracker,
+    snapshot::SnapshotBuilder,
+    subclasses::Subclass,
+    vault::Vault,
+    visibility::{self, Recipient},
 };
 
-pub type Broadcaster = broadcast::Sender<String>;
+/// How often each client is pinged
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A client silent this long (not even answering pings) is dropped, e.g. a
+/// phone that went to sleep without closing its socket
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// The request a handler is working on, so errors reach (and name) the
+/// connection that sent it without threading it through every handler
+#[derive(Debug, Clone)]
+struct RequestContext {
+    connection_id: Uuid,
+    header: RequestHeader,
+}
+
+tokio::task_local! {
+    static REQUEST: RequestContext;
+}
+
+/// Every broadcast goes through `visibility` per recipient before it's sent
+pub type Broadcaster = broadcast::Sender<ServerMessage>;
+
+/// Shared game state wrapped for concurrent access
+pub type SharedGameState = Arc<RwLock<GameState>>;
 
 /// Application state passed to handlers
 #[derive(Clone)]
 pub struct AppState {
     pub game: SharedGameState,
     pub broadcaster: Broadcaster,
+    pub security: Arc<SecurityConfig>,
+    /// Session recording, when `DAGGERHEART_JOURNAL` is set
+    pub journal: Option<Arc<Journal>>,
+    pub handouts: Arc<HandoutStore>,
+    pub maps: Arc<MapStore>,
+    pub vault: Arc<Vault>,
+    /// Fights the GM built ahead of time
+    pub encounters: Arc<EncounterStore>,
+    /// Last character sheets sent, so updates only carry what changed
+    pub sheets: Arc<SheetTracker>,
+    /// Session history on disk, unless `DAGGERHEART_HISTORY` is empty
+    pub history: Option<Arc<HistoryRecorder>>,
+    /// Save files or the SQLite database, per `DAGGERHEART_STORAGE`
+    pub saves: Arc<dyn SaveStore>,
+    /// Port, directories and the like, from flags, environment and file
+    pub config: Arc<ServerConfig>,
+    /// The web client's pages, scripts and styles
+    pub client: Arc<ClientAssets>,
+    pub metrics: Arc<Metrics>,
+    /// Token moves held back so a drag isn't broadcast step by step
+    pub moves: Arc<MoveThrottle>,
+}
+
+/// Query parameters on the WebSocket URL
+#[derive(Debug, Deserialize)]
+pub struct ConnectParams {
+    /// Session token from a previous `connected` message
+    session: Option<String>,
+    /// `gm` for the GM view, `spectator` for read-only displays
+    #[serde(default)]
+    role: ConnectionRole,
+    /// API token, required for the GM role when one is configured
+    token: Option<String>,
+    /// `legacy` for clients that expect English outcome strings
+    compat: Option<String>,
+    /// `msgpack` for binary MessagePack frames instead of JSON text
+    #[serde(default)]
+    encoding: Encoding,
+}
+
+/// How a connection wants its messages written
+#[derive(Debug, Clone, Copy)]
+struct WireFormat {
+    /// English outcome strings for older clients (always JSON)
+    legacy: bool,
+    encoding: Encoding,
+}
+
+impl WireFormat {
+    /// A message as a WebSocket frame
+    fn frame(self, msg: &ServerMessage) -> Message {
+        if self.legacy || !self.encoding.is_binary() {
+            let json = if self.legacy {
+                compat::to_legacy_json(msg)
+            } else {
+                msg.to_json()
+            };
+            return Message::Text(json);
+        }
+        match self.encoding.encode(msg) {
+            Ok(bytes) => Message::Binary(bytes),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                Message::Text(msg.to_json())
+            }
+        }
+    }
+
+    /// A client frame as JSON text, `None` for frames that aren't messages
+    /// (pings, pongs, closes)
+    fn read(self, frame: Message) -> Option<Result<String, String>> {
+        match frame {
+            Message::Text(text) => Some(Ok(text)),
+            Message::Binary(bytes) => Some(self.encoding.to_json_text(&bytes)),
+            _ => None,
+        }
+    }
 }
 
 /// Handle WebSocket upgrade request
-pub async fn websocket_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+pub async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Query(params): Query<ConnectParams>,
+    State(state): State<AppState>,
+) -> Response {
+    if !state.security.origin_allowed(&headers) {
+        tracing::warn!(
+            "Rejected WebSocket upgrade from origin {:?}",
+            headers.get(axum::http::header::ORIGIN)
+        );
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let address = peer.ip();
+    if state.game.read().await.is_banned(&address) {
+        tracing::warn!("Rejected WebSocket upgrade from banned address {}", address);
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    // Without the token the GM view still works, but sees what players see
+    let role = match params.role {
+        ConnectionRole::Gm if !state.security.token_matches(params.token.as_deref()) => {
+            tracing::warn!("GM role requested without a valid API token");
+            ConnectionRole::Player
+        }
+        role => role,
+    };
+
+    let format = WireFormat {
+        legacy: params.compat.as_deref() == Some("legacy"),
+        encoding: params.encoding,
+    };
+
+    ws.on_upgrade(move |socket| handle_socket(socket, state, params.session, role, address, format))
 }
 
 /// Handle an individual WebSocket connection
-async fn handle_socket(socket: WebSocket, state: AppState) {
+async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    session: Option<String>,
+    role: ConnectionRole,
+    address: IpAddr,
+    format: WireFormat,
+) {
     let (mut sender, mut receiver) = socket.split();
 
     // Subscribe to broadcasts
     let mut rx = state.broadcaster.subscribe();
 
-    // Create a new connection
-    let conn_id = {
+    // Create a new connection, picking up the player's preferences
+    let (conn_id, session_token, preferences) = {
         let mut game = state.game.write().await;
         let conn = game.add_connection();
-        conn.id
+        if let Some(connection) = game.connections.get_mut(&conn.id) {
+            connection.role = role;
+            connection.address = Some(address);
+        }
+        let (token, preferences) = game
+            .resume_session(&conn.id, session.as_deref())
+            .unwrap_or_default();
+        (conn.id, token, preferences)
     };
 
     println!("📡 New connection: {}", conn_id);
 
+    if let Some(journal) = &state.journal {
+        journal.record(JournalRecord::Connected {
+            connection: conn_id.to_string(),
+        });
+    }
+
     // Send connection established message
     let msg = ServerMessage::Connected {
         connection_id: conn_id.to_string(),
+        session_token,
+        preferences,
+        role,
     };
-    let _ = sender.send(Message::Text(msg.to_json())).await;
+    let _ = sender.send(format.frame(&msg)).await;
 
-    // Send current characters list
-    send_characters_list(&state, &conn_id, &mut sender).await;
-    
-    // Send current adversaries list
-    send_adversaries_list(&state, &mut sender).await;
+    // Catch a mid-session joiner up on the characters, the scene, the fight
+    // and recent events
+    send_catch_up(&state, &conn_id, format, &mut sender).await;
 
-    // Spawn task to forward broadcasts to this client
+    // When the client was last heard from; pongs count
+    let last_seen = Arc::new(Mutex::new(Instant::now()));
+
+    // Spawn task to forward broadcasts this client may see, pinging it
+    // between them
+    let forward_state = state.clone();
+    let forward_last_seen = last_seen.clone();
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            if sender.send(Message::Text(msg)).await.is_err() {
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Ok(msg) => msg,
+                    // Too slow to keep up (bad WiFi): what was missed is gone,
+                    // so send the whole state again rather than drift
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        println!("🐢 Connection {} missed {} broadcasts, resyncing", conn_id, skipped);
+                        forward_state.metrics.broadcast_lagged(skipped);
+                        resync(&forward_state, &conn_id, format, &mut sender).await;
+                        continue;
+                    }
+                    Err(_) => break,
+                },
+                _ = heartbeat.tick() => {
+                    let silent_for = forward_last_seen.lock().unwrap().elapsed();
+                    if silent_for > HEARTBEAT_TIMEOUT {
+                        println!("💤 Connection {} timed out after {:?}", conn_id, silent_for);
+                        break;
+                    }
+                    if sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            // A kicked connection is already gone from the game; tell it why
+            // and hang up
+            let kicked = matches!(
+                &msg,
+                ServerMessage::Kicked { connection_id, .. } if *connection_id == conn_id.to_string()
+            );
+            if kicked {
+                let _ = sender.send(format.frame(&msg)).await;
+                let _ = sender.send(Message::Close(None)).await;
+                break;
+            }
+
+            let recipient = {
+                let game = forward_state.game.read().await;
+                Recipient::of(&game, &conn_id)
+            };
+            let Some(recipient) = recipient.filter(|r| visibility::visible_to(&msg, r)) else {
+                continue;
+            };
+            let msg = visibility::redact(msg, &recipient);
+            // Older clients can't merge changes; send them the whole sheet
+            let msg = match msg {
+                ServerMessage::CharacterChanged { character_id, .. } if format.legacy => {
+                    match forward_state.sheets.full(&character_id, None) {
+                        Some(sheet) => sheet,
+                        None => continue,
+                    }
+                }
+                msg => msg,
+            };
+            if sender.send(format.frame(&msg)).await.is_err() {
                 break;
             }
         }
@@ -72,10 +333,43 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // Main message processing loop
     let state_clone = state.clone();
     let mut recv_task = tokio::spawn(async move {
+        let mut limiter = RateLimiter::new(state_clone.config.rate_limit.clone(), Instant::now());
         while let Some(Ok(msg)) = receiver.next().await {
-            if let Message::Text(text) = msg {
-                handle_client_message(&state_clone, &conn_id, &text).await;
+            *last_seen.lock().unwrap() = Instant::now();
+            let text = match format.read(msg) {
+                Some(Ok(text)) => text,
+                Some(Err(e)) => {
+                    reject_frame(&state_clone, &conn_id, &e).await;
+                    continue;
+                }
+                None => continue,
+            };
+            let verdict = limiter.check(
+                RequestHeader::parse(&text).request_type.as_deref(),
+                Instant::now(),
+            );
+            if verdict != Verdict::Allow {
+                state_clone.metrics.rate_limited();
+            }
+            match verdict {
+                Verdict::Allow => {}
+                Verdict::Warn => {
+                    rate_limited(&state_clone, &conn_id, &text).await;
+                    continue;
+                }
+                Verdict::Drop => continue,
+                Verdict::Disconnect => {
+                    println!("🚫 Connection {} disconnected for flooding", conn_id);
+                    break;
+                }
             }
+            if let Some(journal) = &state_clone.journal {
+                journal.record(JournalRecord::Client {
+                    connection: conn_id.to_string(),
+                    message: text.clone(),
+                });
+            }
+            handle_client_message(&state_clone, &conn_id, &text).await;
         }
     });
 
@@ -87,51 +381,166 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
 
     // Clean up connection on disconnect
     println!("👋 Connection disconnected: {}", conn_id);
-    
-    // Get controlled character info BEFORE removing connection
-    let (controlled_char_id, char_name) = {
-        let game = state.game.read().await;
-        let char_id = game.control_mapping.get(&conn_id).copied();
-        let name = char_id.and_then(|id| {
-            game.get_characters()
-                .iter()
-                .find(|c| c.id == id)
-                .map(|c| c.name.clone())
+
+    if let Some(journal) = &state.journal {
+        journal.record(JournalRecord::Disconnected {
+            connection: conn_id.to_string(),
         });
-        (char_id, name)
+    }
+
+    drop_connection(&state, &conn_id).await;
+}
+
+/// Remove a connection from the game, ghosting or freeing whatever it
+/// controlled (a no-op once it's gone, e.g. a kicked socket closing)
+async fn drop_connection(state: &AppState, conn_id: &Uuid) {
+    // Get controlled characters BEFORE removing connection
+    let controlled: Vec<(Uuid, String)> = {
+        let game = state.game.read().await;
+        game.controlled_characters(conn_id)
+            .iter()
+            .filter_map(|id| game.get_character(id).map(|c| (*id, c.name.clone())))
+            .collect()
     };
-    
-    // Remove connection from game state
-    {
+
+    // Remove connection from game state, noting which characters are ghosted
+    let ghosted: Vec<bool> = {
         let mut game = state.game.write().await;
-        game.remove_connection(&conn_id);
-    }
-    
-    // If they controlled a character, broadcast removal
-    if let (Some(char_id), Some(name)) = (controlled_char_id, char_name) {
-        println!("   📤 Broadcasting character removal: {} ({})", name, char_id);
-        let msg = ServerMessage::CharacterRemoved {
-            character_id: char_id.to_string(),
-            name: name.clone(),
+        let was_ghosted: Vec<bool> = controlled
+            .iter()
+            .map(|(id, _)| game.is_ghosted(id))
+            .collect();
+        game.remove_connection(conn_id);
+        let ghosted: Vec<bool> = controlled
+            .iter()
+            .map(|(id, _)| game.is_ghosted(id))
+            .collect();
+
+        // One "lost their player" event per newly ghosted character
+        let new_events = ghosted
+            .iter()
+            .zip(&was_ghosted)
+            .filter(|(now, before)| **now && !**before)
+            .count();
+        let skip = game.event_log.len().saturating_sub(new_events);
+        for event in game.event_log.iter().skip(skip) {
+            broadcast_event(state, event).await;
+        }
+        refresh_auras(state, &mut game).await;
+        ghosted
+    };
+
+    // Broadcast removal (or ghosting mid-combat) of each character they controlled
+    for ((char_id, name), ghosted) in controlled.iter().zip(ghosted) {
+        let msg = if ghosted {
+            println!("   👻 Character ghosted mid-combat: {} ({})", name, char_id);
+            ServerMessage::CharacterGhosted {
+                character_id: char_id.to_string(),
+                name: name.clone(),
+            }
+        } else {
+            println!(
+                "   📤 Broadcasting character removal: {} ({})",
+                name, char_id
+            );
+            ServerMessage::CharacterRemoved {
+                character_id: char_id.to_string(),
+                name: name.clone(),
+            }
         };
-        let _ = state.broadcaster.send(msg.to_json());
+        let _ = state.broadcaster.send(msg);
     }
 
     println!(
-        "   Connection {} removed, controlled character: {:?}",
-        conn_id, controlled_char_id
+        "   Connection {} removed, controlled characters: {:?}",
+        conn_id,
+        controlled.iter().map(|(id, _)| id).collect::<Vec<_>>()
     );
 }
 
 /// Handle a client message
 async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
+    let request = RequestContext {
+        connection_id: *conn_id,
+        header: RequestHeader::parse(text),
+    };
+    REQUEST
+        .scope(request, handle_request(state, conn_id, text))
+        .await;
+}
+
+/// Tell a connection it's sending too fast and this message was dropped
+async fn rate_limited(state: &AppState, conn_id: &Uuid, text: &str) {
+    let request = RequestContext {
+        connection_id: *conn_id,
+        header: RequestHeader::parse(text),
+    };
+    REQUEST
+        .scope(
+            request,
+            send_error(
+                state,
+                ErrorCode::RateLimited,
+                "Too many messages; slow down (some were dropped)",
+            ),
+        )
+        .await;
+}
+
+/// Tell a connection a frame it sent couldn't be decoded at all
+async fn reject_frame(state: &AppState, conn_id: &Uuid, error: &str) {
+    let request = RequestContext {
+        connection_id: *conn_id,
+        header: RequestHeader::default(),
+    };
+    REQUEST
+        .scope(
+            request,
+            send_error(state, ErrorCode::MalformedMessage, error),
+        )
+        .await;
+}
+
+/// Parse and dispatch a client message, inside its [`REQUEST`] scope
+async fn handle_request(state: &AppState, conn_id: &Uuid, text: &str) {
     let msg: ClientMessage = match serde_json::from_str(text) {
         Ok(m) => m,
         Err(e) => {
             eprintln!("❌ Failed to parse message: {}", e);
+            send_error(
+                state,
+                ErrorCode::MalformedMessage,
+                &format!("Couldn't read message: {}", e),
+            )
+            .await;
             return;
         }
     };
+    if let Ok(Some(message_type)) = REQUEST.try_with(|r| r.header.request_type.clone()) {
+        state.metrics.message_received(&message_type);
+    }
+
+    let role = state
+        .game
+        .read()
+        .await
+        .connections
+        .get(conn_id)
+        .map(|c| c.role);
+    if role == Some(ConnectionRole::Spectator) && !msg.is_read_only() {
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "Spectators can't change the game",
+        )
+        .await;
+        return;
+    }
+    // Handlers of GM messages can take it as read that the GM sent them
+    if role != Some(ConnectionRole::Gm) && msg.is_gm_only() {
+        send_error(state, ErrorCode::Forbidden, "Only the GM can do that").await;
+        return;
+    }
 
     match msg {
         ClientMessage::Connect => {
@@ -141,31 +550,108 @@ async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
         ClientMessage::CreateCharacter {
             name,
             class,
+            subclass,
             ancestry,
             attributes,
         } => {
-            handle_create_character(state, conn_id, name, class, ancestry, attributes).await;
+            handle_create_character(state, conn_id, name, class, subclass, ancestry, attributes)
+                .await;
+        }
+
+        ClientMessage::SetDisplayName { name } => {
+            handle_set_display_name(state, conn_id, name).await;
+        }
+
+        ClientMessage::SetPreferences { preferences } => {
+            handle_set_preferences(state, conn_id, preferences).await;
+        }
+
+        ClientMessage::SetCharacterColor {
+            character_id,
+            color,
+        } => {
+            handle_set_character_color(state, conn_id, character_id, color).await;
+        }
+
+        ClientMessage::SelectCharacter {
+            character_id,
+            keep_others,
+        } => {
+            handle_select_character(state, conn_id, character_id, keep_others).await;
+        }
+
+        ClientMessage::MoveCharacter {
+            x,
+            y,
+            path,
+            character_id,
+            facing,
+        } => {
+            handle_move_character(state, conn_id, x, y, path, character_id, facing).await;
+        }
+
+        ClientMessage::TeleportCharacter { x, y, character_id } => {
+            handle_teleport_character(state, conn_id, x, y, character_id).await;
+        }
+
+        ClientMessage::SetMapLocked { locked } => {
+            handle_set_map_locked(state, locked).await;
         }
 
-        ClientMessage::SelectCharacter { character_id } => {
-            handle_select_character(state, conn_id, character_id).await;
+        ClientMessage::Emote { text, log } => {
+            handle_emote(state, conn_id, text, log).await;
         }
 
-        ClientMessage::MoveCharacter { x, y } => {
-            handle_move_character(state, conn_id, x, y).await;
+        ClientMessage::ChatMessage { channel, text } => {
+            handle_chat_message(state, conn_id, channel, text).await;
         }
 
         ClientMessage::RollDuality {
             modifier,
             with_advantage,
+            with_disadvantage,
+            character_id,
+        } => {
+            handle_roll_duality(
+                state,
+                conn_id,
+                modifier,
+                with_advantage,
+                with_disadvantage,
+                character_id,
+            )
+            .await;
+        }
+
+        ClientMessage::RollDice {
+            expression,
+            label,
+            hidden,
+            character_id,
         } => {
-            handle_roll_duality(state, conn_id, modifier, with_advantage).await;
+            handle_roll_dice(state, conn_id, expression, label, hidden, character_id).await;
         }
 
         ClientMessage::UpdateResource { resource, amount } => {
             handle_update_resource(state, conn_id, resource, amount).await;
         }
 
+        ClientMessage::Heal {
+            character_id,
+            source,
+        } => {
+            handle_heal(state, conn_id, character_id, source).await;
+        }
+
+        ClientMessage::SetResource {
+            token_id,
+            resource,
+            value,
+            relative,
+        } => {
+            handle_set_resource(state, token_id, resource, value, relative).await;
+        }
+
         ClientMessage::RequestRoll {
             target_type,
             target_character_ids,
@@ -176,10 +662,11 @@ async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
             narrative_stakes,
             situational_modifier,
             has_advantage,
+            has_disadvantage,
             is_combat,
+            hidden,
         } => {
-            handle_request_roll(
-                state,
+            let request = game::RollRequest {
                 target_type,
                 target_character_ids,
                 roll_type,
@@ -189,15 +676,85 @@ async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
                 narrative_stakes,
                 situational_modifier,
                 has_advantage,
+                has_disadvantage,
                 is_combat,
-            )
-            .await;
+                hidden,
+            };
+            handle_request_roll(state, request).await;
+        }
+
+        ClientMessage::RequestGroupRoll {
+            leader_id,
+            helper_ids,
+            attribute,
+            difficulty,
+            context,
+            has_advantage,
+            has_disadvantage,
+        } => {
+            let request = game::GroupRollRequest {
+                leader_id,
+                helper_ids,
+                attribute,
+                difficulty,
+                context,
+                has_advantage,
+                has_disadvantage,
+            };
+            handle_request_group_roll(state, request).await;
+        }
+
+        ClientMessage::PreviewRollRequest {
+            target_type,
+            target_character_ids,
+            roll_type,
+            attribute,
+            difficulty,
+            situational_modifier,
+            has_advantage,
+            has_disadvantage,
+        } => {
+            let spec = game::RollSpec {
+                roll_type,
+                attribute,
+                difficulty,
+                situational_modifier,
+                has_advantage,
+                has_disadvantage,
+            };
+            handle_preview_roll_request(state, target_type, target_character_ids, spec).await;
+        }
+
+        ClientMessage::HelpAlly {
+            request_id,
+            character_id,
+        } => {
+            handle_help_ally(state, conn_id, request_id, character_id).await;
+        }
+
+        ClientMessage::ProposeTagTeam {
+            request_id,
+            partner_id,
+        } => {
+            handle_propose_tag_team(state, conn_id, request_id, partner_id).await;
+        }
+
+        ClientMessage::RespondTagTeam { request_id, accept } => {
+            handle_respond_tag_team(state, conn_id, request_id, accept).await;
+        }
+
+        ClientMessage::ChooseTagTeamResult {
+            request_id,
+            character_id,
+        } => {
+            handle_choose_tag_team_result(state, conn_id, request_id, character_id).await;
         }
 
         ClientMessage::ExecuteRoll {
             request_id,
             spend_hope_for_bonus,
             chosen_experience,
+            character_id,
         } => {
             handle_execute_roll(
                 state,
@@ -205,16 +762,49 @@ async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
                 request_id,
                 spend_hope_for_bonus,
                 chosen_experience,
+                character_id,
             )
             .await;
         }
 
+        ClientMessage::AddExperience { name, modifier } => {
+            handle_add_experience(state, conn_id, name, modifier).await;
+        }
+
+        ClientMessage::EditExperience {
+            name,
+            new_name,
+            modifier,
+        } => {
+            handle_edit_experience(state, conn_id, name, new_name, modifier).await;
+        }
+
         // ===== Combat & Adversary Handlers =====
         
         ClientMessage::SpawnAdversary { template, position } => {
             handle_spawn_adversary(state, template, position).await;
         }
 
+        ClientMessage::PriceEncounter { plan } => {
+            handle_price_encounter(state, conn_id, plan).await;
+        }
+
+        ClientMessage::SaveEncounterPreset { preset } => {
+            handle_encounter_preset_change(state, |store| store.save(preset)).await;
+        }
+
+        ClientMessage::DeleteEncounterPreset { name } => {
+            handle_encounter_preset_change(state, |store| store.delete(&name)).await;
+        }
+
+        ClientMessage::SaveEncounterFromMap { name } => {
+            handle_save_encounter_from_map(state, name).await;
+        }
+
+        ClientMessage::SpawnEncounter { name } => {
+            handle_spawn_encounter(state, name).await;
+        }
+
         ClientMessage::SpawnCustomAdversary {
             name,
             position,
@@ -224,8 +814,7 @@ async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
             attack_modifier,
             damage_dice,
         } => {
-            handle_spawn_custom_adversary(
-                state,
+            let custom = game::CustomAdversary {
                 name,
                 position,
                 hp,
@@ -233,67 +822,555 @@ async fn handle_client_message(state: &AppState, conn_id: &Uuid, text: &str) {
                 armor,
                 attack_modifier,
                 damage_dice,
-            )
-            .await;
+            };
+            handle_spawn_custom_adversary(state, custom).await;
+        }
+
+        ClientMessage::CreateAdversaryGroup {
+            name,
+            adversary_ids,
+        } => {
+            handle_create_adversary_group(state, name, adversary_ids).await;
+        }
+
+        ClientMessage::DisbandAdversaryGroup { group_id } => {
+            handle_disband_adversary_group(state, group_id).await;
+        }
+
+        ClientMessage::MoveAdversaryGroup { group_id, position } => {
+            handle_move_adversary_group(state, group_id, position).await;
+        }
+
+        ClientMessage::ActivateAdversaryGroup { group_id } => {
+            handle_activate_adversary_group(state, group_id).await;
+        }
+
+        ClientMessage::RemoveAdversaryGroup { group_id } => {
+            handle_remove_adversary_group(state, group_id).await;
         }
 
         ClientMessage::RemoveAdversary { adversary_id } => {
             handle_remove_adversary(state, adversary_id).await;
         }
 
-        ClientMessage::StartCombat => {
-            handle_start_combat(state).await;
+        ClientMessage::RenumberAdversaries { template } => {
+            handle_renumber_adversaries(state, template).await;
         }
 
-        ClientMessage::EndCombat => {
-            handle_end_combat(state).await;
+        ClientMessage::CreateNpc {
+            name,
+            hp,
+            evasion,
+            template,
+            position,
+        } => {
+            handle_create_npc(state, name, hp, evasion, template, position).await;
         }
 
-        ClientMessage::AddTrackerToken { token_type } => {
-            handle_add_tracker_token(state, token_type).await;
+        ClientMessage::ConvertNpc { character_id } => {
+            handle_convert_npc(state, character_id).await;
         }
 
-        ClientMessage::Attack {
-            attacker_id,
-            target_id,
-            modifier,
-            with_advantage,
+        ClientMessage::UpdateCharacter {
+            character_id,
+            changes,
         } => {
-            handle_attack(state, attacker_id, target_id, modifier, with_advantage).await;
+            handle_update_character(state, conn_id, character_id, changes).await;
         }
 
-        ClientMessage::RollDamage {
-            attacker_id,
-            target_id,
-            damage_dice,
-            armor,
+        ClientMessage::DeleteCharacter {
+            character_id,
+            retire,
         } => {
-            handle_roll_damage(state, attacker_id, target_id, damage_dice, armor).await;
+            handle_delete_character(state, character_id, retire).await;
         }
-    }
-}
 
-/// Handle character creation
-async fn handle_create_character(
-    state: &AppState,
-    conn_id: &Uuid,
-    name: String,
-    class_str: String,
-    ancestry_str: String,
-    attributes: [i8; 6],
-) {
-    let class = match class_str.as_str() {
-        "Bard" => Class::Bard,
-        "Druid" => Class::Druid,
-        "Guardian" => Class::Guardian,
-        "Ranger" => Class::Ranger,
-        "Rogue" => Class::Rogue,
-        "Seraph" => Class::Seraph,
-        "Sorcerer" => Class::Sorcerer,
-        "Warrior" => Class::Warrior,
+        ClientMessage::SpawnEnvironment { template } => {
+            handle_spawn_environment(state, template).await;
+        }
+
+        ClientMessage::RemoveEnvironment { environment_id } => {
+            handle_remove_environment(state, environment_id).await;
+        }
+
+        ClientMessage::ActivateEnvironmentFeature {
+            environment_id,
+            feature,
+        } => {
+            handle_activate_environment_feature(state, environment_id, feature).await;
+        }
+
+        ClientMessage::StartCombat => {
+            handle_start_combat(state).await;
+        }
+
+        ClientMessage::RollbackToCheckpoint { checkpoint_id } => {
+            handle_rollback_to_checkpoint(state, checkpoint_id).await;
+        }
+
+        ClientMessage::ScheduleNextSession {
+            scheduled_at,
+            notes,
+        } => {
+            handle_schedule_next_session(state, Some((scheduled_at, notes))).await;
+        }
+
+        ClientMessage::ShareHandout {
+            title,
+            kind,
+            content,
+            character_ids,
+        } => {
+            handle_share_handout(state, title, kind, content, character_ids).await;
+        }
+
+        ClientMessage::SetMap { map_id } => {
+            handle_set_map(state, map_id).await;
+        }
+
+        ClientMessage::SetSceneLayout { layout } => {
+            handle_set_scene_layout(state, layout).await;
+        }
+
+        ClientMessage::SetFogEnabled { enabled } => {
+            handle_fog_change(state, |game| Ok(game.set_fog_enabled(enabled))).await;
+        }
+
+        ClientMessage::RevealArea { points } => {
+            handle_fog_change(state, |game| game.reveal_area(points)).await;
+        }
+
+        ClientMessage::HideArea { area_id } => {
+            handle_fog_change(state, |game| game.hide_area(&area_id)).await;
+        }
+
+        ClientMessage::SetTokenDetails { token_id, token } => {
+            handle_set_token_details(state, conn_id, token_id, token).await;
+        }
+
+        ClientMessage::MoveToken {
+            token_id,
+            position,
+            teleport,
+        } => {
+            handle_move_token(state, token_id, position, teleport).await;
+        }
+
+        ClientMessage::EditToken {
+            token_id,
+            name,
+            token,
+        } => {
+            handle_edit_token(state, token_id, name, token).await;
+        }
+
+        ClientMessage::Ping { position } => {
+            handle_ping(state, conn_id, position).await;
+        }
+
+        ClientMessage::AddDrawing {
+            shape,
+            color,
+            label,
+        } => {
+            handle_drawing_change(state, |game| game.add_drawing(shape, &color, &label)).await;
+        }
+
+        ClientMessage::RemoveDrawing { drawing_id } => {
+            handle_drawing_change(state, |game| game.remove_drawing(&drawing_id)).await;
+        }
+
+        ClientMessage::ClearDrawings => {
+            handle_drawing_change(state, |game| Ok(game.clear_drawings())).await;
+        }
+
+        ClientMessage::StartCutaway {
+            character_ids,
+            label,
+        } => {
+            handle_start_cutaway(state, character_ids, label).await;
+        }
+
+        ClientMessage::EndCutaway => {
+            handle_end_cutaway(state).await;
+        }
+
+        ClientMessage::ClearNextSession => {
+            handle_schedule_next_session(state, None).await;
+        }
+
+        ClientMessage::SaveNote { note_id, note } => {
+            handle_note_change(state, NoteChange::Save(note_id, note)).await;
+        }
+
+        ClientMessage::DeleteNote { note_id } => {
+            handle_note_change(state, NoteChange::Delete(note_id)).await;
+        }
+
+        ClientMessage::SaveQuest { quest_id, quest } => {
+            handle_quest_change(state, QuestChange::Save(quest_id, quest)).await;
+        }
+
+        ClientMessage::RemoveQuest { quest_id } => {
+            handle_quest_change(state, QuestChange::Remove(quest_id)).await;
+        }
+
+        ClientMessage::SetObjectiveCompleted {
+            quest_id,
+            objective_id,
+            completed,
+        } => {
+            let change = QuestChange::Objective(quest_id, objective_id, completed);
+            handle_quest_change(state, change).await;
+        }
+
+        ClientMessage::TickQuestCountdown {
+            quest_id,
+            countdown_id,
+            ticks,
+        } => {
+            let change = QuestChange::Countdown(quest_id, countdown_id, ticks);
+            handle_quest_change(state, change).await;
+        }
+
+        ClientMessage::RequestNotes { search } => {
+            // The GM's own notes are taken out of players' replies on the way
+            let game = state.game.read().await;
+            let _ = state.broadcaster.send(ServerMessage::Notes {
+                notes: game.search_notes(&search, true),
+                connection_id: Some(conn_id.to_string()),
+            });
+        }
+
+        ClientMessage::EndCombat => {
+            handle_end_combat(state).await;
+        }
+
+        ClientMessage::SetCombatMode { mode } => {
+            handle_set_combat_mode(state, mode).await;
+        }
+
+        ClientMessage::PassSpotlight { to } => {
+            handle_pass_spotlight(state, to).await;
+        }
+
+        ClientMessage::AdvanceRound => {
+            handle_advance_round(state).await;
+        }
+
+        ClientMessage::EndRound => {
+            handle_end_round(state).await;
+        }
+
+        ClientMessage::RequestRoundHistory => {
+            let history = state.game.read().await.round_history();
+            match history {
+                Ok(rounds) => {
+                    let _ = state.broadcaster.send(ServerMessage::RoundHistory {
+                        rounds,
+                        connection_id: Some(conn_id.to_string()),
+                    });
+                }
+                Err(e) => send_error(state, ErrorCode::Rejected, &e).await,
+            }
+        }
+
+        ClientMessage::AddHazard { hazard } => {
+            handle_add_hazard(state, hazard).await;
+        }
+
+        ClientMessage::RemoveHazard { hazard_id } => {
+            handle_remove_hazard(state, hazard_id).await;
+        }
+
+        ClientMessage::SetHazardActive { hazard_id, active } => {
+            handle_set_hazard_active(state, hazard_id, active).await;
+        }
+
+        ClientMessage::TickHazard { hazard_id } => {
+            handle_tick_hazard(state, hazard_id).await;
+        }
+
+        ClientMessage::SpendTrackerToken => {
+            handle_spend_tracker_token(state).await;
+        }
+
+        ClientMessage::AdvanceTracker { success_with_hope } => {
+            handle_advance_tracker(state, success_with_hope).await;
+        }
+
+        ClientMessage::AddTrackerToken { token_type } => {
+            handle_add_tracker_token(state, token_type).await;
+        }
+
+        ClientMessage::SpendFear { spend } => {
+            handle_spend_fear(state, spend).await;
+        }
+
+        ClientMessage::MarkAdversaryStress {
+            adversary_id,
+            stress,
+            stress_move,
+        } => {
+            handle_mark_adversary_stress(state, adversary_id, stress, stress_move).await;
+        }
+
+        ClientMessage::MeasureRange { from_id, to_id } => {
+            handle_measure_range(state, conn_id, from_id, to_id).await;
+        }
+
+        ClientMessage::RequestSheet { character_id } => {
+            handle_request_sheet(state, conn_id, character_id).await;
+        }
+
+        ClientMessage::RequestEventLog {
+            event_type,
+            character,
+            offset,
+            limit,
+        } => {
+            let query = EventLogQuery {
+                event_type,
+                character,
+                offset,
+                limit,
+            };
+            handle_request_event_log(state, conn_id, query).await;
+        }
+
+        ClientMessage::RequestSceneLog {
+            event_type,
+            character,
+        } => {
+            let query = EventLogQuery {
+                event_type,
+                character,
+                ..Default::default()
+            };
+            handle_request_scene_log(state, conn_id, query).await;
+        }
+
+        ClientMessage::MarkScene { title } => {
+            handle_mark_scene(state, title).await;
+        }
+
+        ClientMessage::RequestHandouts => {
+            // Others' private handouts are taken out on the way
+            let _ = state.broadcaster.send(ServerMessage::Handouts {
+                handouts: state.handouts.list(),
+                connection_id: Some(conn_id.to_string()),
+            });
+        }
+
+        ClientMessage::RequestRollStats => {
+            let stats = state.game.read().await.roll_stats_report();
+            let _ = state.broadcaster.send(ServerMessage::RollStats {
+                stats,
+                connection_id: Some(conn_id.to_string()),
+            });
+        }
+
+        ClientMessage::Attack {
+            attacker_id,
+            target_id,
+            modifier,
+            with_advantage,
+            with_disadvantage,
+        } => {
+            handle_attack(
+                state,
+                attacker_id,
+                target_id,
+                modifier,
+                with_advantage,
+                with_disadvantage,
+            )
+            .await;
+        }
+
+        ClientMessage::AdversaryAttack {
+            adversary_id,
+            target_id,
+            with_advantage,
+            with_disadvantage,
+        } => {
+            handle_adversary_attack(
+                state,
+                adversary_id,
+                target_id,
+                with_advantage,
+                with_disadvantage,
+            )
+            .await;
+        }
+
+        ClientMessage::RollDamage {
+            attacker_id,
+            target_id,
+            damage_dice,
+            armor,
+            damage_type,
+        } => {
+            handle_roll_damage(
+                state,
+                attacker_id,
+                target_id,
+                damage_dice,
+                armor,
+                damage_type,
+            )
+            .await;
+        }
+
+        ClientMessage::ApplyDamageToArea {
+            source,
+            area,
+            target_ids,
+            damage_dice,
+            damage_type,
+        } => {
+            handle_apply_damage_to_area(state, source, area, target_ids, damage_dice, damage_type)
+                .await;
+        }
+
+        ClientMessage::SetDefenses {
+            target_id,
+            defenses,
+        } => {
+            handle_set_defenses(state, target_id, defenses).await;
+        }
+
+        ClientMessage::UpdateTableSettings { settings } => {
+            handle_update_table_settings(state, settings).await;
+        }
+
+        ClientMessage::AttachAura { owner_id, aura } => {
+            handle_attach_aura(state, owner_id, aura).await;
+        }
+
+        ClientMessage::DetachAura { aura_id } => {
+            handle_detach_aura(state, aura_id).await;
+        }
+
+        ClientMessage::AddModifier {
+            target_id,
+            modifier,
+        } => {
+            handle_add_modifier(state, conn_id, target_id, modifier).await;
+        }
+
+        ClientMessage::RemoveModifier { modifier_id } => {
+            handle_remove_modifier(state, conn_id, modifier_id).await;
+        }
+
+        // ===== Inventory Handlers =====
+        ClientMessage::AddItem {
+            name,
+            description,
+            quantity,
+        } => {
+            handle_add_item(state, conn_id, name, description, quantity).await;
+        }
+
+        ClientMessage::RemoveItem { item_id, quantity } => {
+            handle_remove_item(state, conn_id, item_id, quantity).await;
+        }
+
+        ClientMessage::TransferItem {
+            item_id,
+            target_character_id,
+            quantity,
+        } => {
+            handle_transfer_item(state, conn_id, item_id, target_character_id, quantity).await;
+        }
+
+        ClientMessage::GrantItem {
+            character_id,
+            name,
+            description,
+            quantity,
+        } => {
+            handle_grant_item(state, character_id, name, description, quantity).await;
+        }
+
+        ClientMessage::AwardGold {
+            character_id,
+            handfuls,
+        } => {
+            handle_award_gold(state, character_id, handfuls).await;
+        }
+
+        ClientMessage::EquipWeapon { weapon } => {
+            handle_equip_weapon(state, conn_id, weapon).await;
+        }
+
+        ClientMessage::EquipArmor { armor } => {
+            handle_equip_armor(state, conn_id, armor).await;
+        }
+
+        ClientMessage::AssumeControl { character_id } => {
+            handle_assume_control(state, conn_id, character_id).await;
+        }
+
+        ClientMessage::RequestControl { character_id } => {
+            handle_request_control(state, conn_id, character_id).await;
+        }
+
+        ClientMessage::TransferControl {
+            character_id,
+            to_connection_id,
+        } => {
+            handle_transfer_control(state, character_id, Some(to_connection_id)).await;
+        }
+
+        ClientMessage::ReleaseControl { character_id } => {
+            handle_transfer_control(state, character_id, None).await;
+        }
+
+        ClientMessage::KickConnection { connection_id, ban } => {
+            handle_kick_connection(state, conn_id, connection_id, ban).await;
+        }
+
+        ClientMessage::MarkLevelUp { character_id } => {
+            handle_mark_level_up(state, character_id).await;
+        }
+
+        ClientMessage::ChooseLevelUp {
+            advancements,
+            new_experience,
+        } => {
+            handle_choose_level_up(state, conn_id, advancements, new_experience).await;
+        }
+    }
+}
+
+/// Handle character creation
+async fn handle_create_character(
+    state: &AppState,
+    conn_id: &Uuid,
+    name: String,
+    class_str: String,
+    subclass_name: Option<String>,
+    ancestry_str: String,
+    attributes: [i8; 6],
+) {
+    let class = match class_str.as_str() {
+        "Bard" => Class::Bard,
+        "Druid" => Class::Druid,
+        "Guardian" => Class::Guardian,
+        "Ranger" => Class::Ranger,
+        "Rogue" => Class::Rogue,
+        "Seraph" => Class::Seraph,
+        "Sorcerer" => Class::Sorcerer,
+        "Warrior" => Class::Warrior,
         "Wizard" => Class::Wizard,
         _ => {
-            send_error(state, &format!("Invalid class: {}", class_str)).await;
+            send_error(
+                state,
+                ErrorCode::Rejected,
+                &format!("Invalid class: {}", class_str),
+            )
+            .await;
             return;
         }
     };
@@ -317,7 +1394,12 @@ async fn handle_create_character(
         "Ribbet" => Ancestry::Ribbet,
         "Simiah" => Ancestry::Simiah,
         _ => {
-            send_error(state, &format!("Invalid ancestry: {}", ancestry_str)).await;
+            send_error(
+                state,
+                ErrorCode::Rejected,
+                &format!("Invalid ancestry: {}", ancestry_str),
+            )
+            .await;
             return;
         }
     };
@@ -325,14 +1407,37 @@ async fn handle_create_character(
     let attrs = match Attributes::from_array(attributes) {
         Ok(a) => a,
         Err(e) => {
-            send_error(state, &format!("Invalid attributes: {}", e)).await;
+            send_error(
+                state,
+                ErrorCode::Rejected,
+                &format!("Invalid attributes: {}", e),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let subclass = match subclass_name.map(|s| Subclass::new(&class_str, &s, 1)) {
+        Some(Ok(s)) => Some(s),
+        Some(Err(e)) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
             return;
         }
+        None => None,
     };
 
     let mut game = state.game.write().await;
-    let character = game.create_character(name, class, ancestry, attrs);
+    if let Err(e) = game.limits.check_characters(game.characters.len()) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+    let mut character = game.create_character(name, class, ancestry, attrs);
     let char_id = character.id;
+    if let Some(created) = game.get_character_mut(&char_id) {
+        created.subclass = subclass.clone();
+    }
+    character.subclass = subclass;
 
     println!("✨ Character created: {} ({})", character.name, char_id);
     
@@ -341,7 +1446,13 @@ async fn handle_create_character(
         game::GameEventType::CharacterCreated,
         format!("{} joined the game", character.name),
         Some(character.name.clone()),
-        Some(format!("Class: {}, Ancestry: {}", class_str, ancestry_str)),
+        Some(match &character.subclass {
+            Some(subclass) => format!(
+                "Class: {} ({}), Ancestry: {}",
+                class_str, subclass.name, ancestry_str
+            ),
+            None => format!("Class: {}, Ancestry: {}", class_str, ancestry_str),
+        }),
     );
     
     let event = game.event_log.last().cloned();
@@ -350,7 +1461,12 @@ async fn handle_create_character(
     if let Err(e) = game.select_character(conn_id, &char_id) {
         eprintln!("❌ Failed to auto-select character: {}", e);
         drop(game);
-        send_error(state, &format!("Failed to select character: {}", e)).await;
+        send_error(
+            state,
+            ErrorCode::Rejected,
+            &format!("Failed to select character: {}", e),
+        )
+        .await;
         return;
     }
 
@@ -369,55 +1485,91 @@ async fn handle_create_character(
         position: character.position,
         color: character.color.clone(),
         is_npc: false,
+        token: character.token.clone(),
     };
-    let _ = state.broadcaster.send(spawn_msg.to_json());
+    let _ = state.broadcaster.send(spawn_msg);
 
     // Send character created confirmation to creator
     let created_msg = ServerMessage::CharacterCreated {
         character_id: char_id.to_string(),
         character: character_data.clone(),
     };
-    let _ = state.broadcaster.send(created_msg.to_json());
+    let _ = state.broadcaster.send(created_msg);
 
     // Send character selected message
     let selected_msg = ServerMessage::CharacterSelected {
         character_id: char_id.to_string(),
         character: character_data,
     };
-    let _ = state.broadcaster.send(selected_msg.to_json());
+    let _ = state.broadcaster.send(selected_msg);
 
     // Broadcast updated characters list
     broadcast_characters_list(state).await;
 }
 
 /// Handle character selection
-async fn handle_select_character(state: &AppState, conn_id: &Uuid, character_id: String) {
-    let char_uuid = match Uuid::parse_str(&character_id) {
-        Ok(id) => id,
+async fn handle_select_character(
+    state: &AppState,
+    conn_id: &Uuid,
+    character_id: String,
+    keep_others: bool,
+) {
+    let char_uuid = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
         Err(_) => {
-            send_error(state, "Invalid character ID").await;
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
             return;
         }
     };
 
     let mut game = state.game.write().await;
+    let was_ghosted = game.is_ghosted(&char_uuid);
 
-    if let Err(e) = game.select_character(conn_id, &char_uuid) {
+    let selected = if keep_others {
+        game.add_controlled_character(conn_id, &char_uuid)
+    } else {
+        game.select_character(conn_id, &char_uuid)
+    };
+    if let Err(e) = selected {
         drop(game);
-        send_error(state, &format!("Failed to select character: {}", e)).await;
+        send_error(
+            state,
+            ErrorCode::Rejected,
+            &format!("Failed to select character: {}", e),
+        )
+        .await;
         return;
     }
 
+    let reclaim_event = if was_ghosted {
+        game.event_log.last().cloned()
+    } else {
+        None
+    };
+
     let character = match game.get_character(&char_uuid) {
         Some(c) => c.clone(),
         None => {
             drop(game);
-            send_error(state, "Character not found").await;
+            send_error(state, ErrorCode::NotFound, "Character not found").await;
             return;
         }
     };
 
     let character_data = character.to_data();
+
+    // Roll requests still waiting on this character
+    let mut open_rolls: Vec<_> = game
+        .pending_roll_requests
+        .values()
+        .filter(|r| r.target_character_ids.contains(&char_uuid))
+        .filter(|r| !r.completed_by.contains(&char_uuid))
+        .collect();
+    open_rolls.sort_by_key(|r| r.timestamp);
+    let open_rolls: Vec<_> = open_rolls
+        .into_iter()
+        .map(|r| roll_requested_message(r, &char_uuid, &character))
+        .collect();
     drop(game);
 
     println!(
@@ -430,932 +1582,4560 @@ async fn handle_select_character(state: &AppState, conn_id: &Uuid, character_id:
         character_id: char_uuid.to_string(),
         character: character_data,
     };
-    let _ = state.broadcaster.send(msg.to_json());
+    let _ = state.broadcaster.send(msg);
+
+    // Let the GM know the player is back
+    if let Some(event) = reclaim_event {
+        let msg = ServerMessage::CharacterReclaimed {
+            character_id: char_uuid.to_string(),
+            name: character.name.clone(),
+        };
+        let _ = state.broadcaster.send(msg);
+        broadcast_event(state, &event).await;
+    }
+
+    // Remind a returning player of an unfinished level up
+    if character.progression.pending_level_up {
+        let _ = state.broadcaster.send(level_up_offer(&character));
+    }
+
+    // Catch a late joiner up on rolls they still owe
+    for msg in open_rolls {
+        let _ = state.broadcaster.send(msg);
+    }
 
     // Broadcast updated characters list
     broadcast_characters_list(state).await;
 }
 
-/// Handle character movement
-async fn handle_move_character(state: &AppState, conn_id: &Uuid, x: f32, y: f32) {
-    let game = state.game.read().await;
-
-    let char_id = match game.control_mapping.get(conn_id) {
-        Some(id) => *id,
-        None => {
-            drop(game);
-            send_error(state, "No character selected").await;
+/// Handle GM taking temporary control of a ghosted character
+async fn handle_assume_control(state: &AppState, conn_id: &Uuid, character_id: String) {
+    let char_uuid = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
             return;
         }
     };
-    drop(game);
 
     let mut game = state.game.write().await;
-    let position = crate::protocol::Position::new(x, y);
 
-    if !game.update_character_position(&char_id, position) {
+    if let Err(e) = game.assume_control(conn_id, &char_uuid) {
         drop(game);
-        send_error(state, "Failed to update position").await;
+        send_error(
+            state,
+            ErrorCode::Rejected,
+            &format!("Failed to take control: {}", e),
+        )
+        .await;
         return;
     }
-    drop(game);
-
-    // Broadcast movement
-    let msg = ServerMessage::CharacterMoved {
-        character_id: char_id.to_string(),
-        position,
-    };
-    let _ = state.broadcaster.send(msg.to_json());
-}
-
-/// Handle dice roll
-async fn handle_roll_duality(
-    state: &AppState,
-    conn_id: &Uuid,
-    modifier: i32,
-    with_advantage: bool,
-) {
-    let game = state.game.read().await;
-
-    let char_id = match game.control_mapping.get(conn_id) {
-        Some(id) => *id,
-        None => {
-            drop(game);
-            send_error(state, "No character selected").await;
-            return;
-        }
-    };
 
-    let character = match game.get_character(&char_id) {
+    let character = match game.get_character(&char_uuid) {
         Some(c) => c.clone(),
         None => {
             drop(game);
-            send_error(state, "Character not found").await;
+            send_error(state, ErrorCode::NotFound, "Character not found").await;
             return;
         }
     };
-
-    let roll = game.roll_duality(modifier, with_advantage);
+    let event = game.event_log.last().cloned();
     drop(game);
 
     println!(
-        "🎲 {} rolled: {}d12 = {}",
-        character.name, roll.hope, roll.fear
+        "🎭 Connection {} standing in for character: {}",
+        conn_id, character.name
     );
 
-    // Broadcast roll result
-    let msg = ServerMessage::RollResult {
-        character_id: char_id.to_string(),
-        character_name: character.name,
-        roll,
+    let msg = ServerMessage::CharacterControlAssumed {
+        character_id: char_uuid.to_string(),
+        name: character.name.clone(),
+        connection_id: conn_id.to_string(),
     };
-    let _ = state.broadcaster.send(msg.to_json());
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(ev) = event {
+        broadcast_event(state, &ev).await;
+    }
 }
 
-/// Handle resource update
-async fn handle_update_resource(state: &AppState, conn_id: &Uuid, resource: String, amount: i32) {
+/// Handle a player asking for a character another connection controls
+///
+/// Only the GM hears the request; they answer with `transfer_control`.
+async fn handle_request_control(state: &AppState, conn_id: &Uuid, character_id: String) {
     let game = state.game.read().await;
+    let character = Uuid::parse_str(&character_id)
+        .ok()
+        .and_then(|id| game.get_character(&id));
+    let Some(character) = character else {
+        drop(game);
+        send_error(state, ErrorCode::NotFound, "Character not found").await;
+        return;
+    };
+    let msg = ServerMessage::ControlRequested {
+        character_id,
+        name: character.name.clone(),
+        connection_id: conn_id.to_string(),
+        display_name: game
+            .connections
+            .get(conn_id)
+            .and_then(|c| c.display_name.clone()),
+    };
+    let _ = state.broadcaster.send(msg);
+}
 
-    let char_id = match game.control_mapping.get(conn_id) {
-        Some(id) => *id,
-        None => {
+/// Handle the GM handing a character to a connection, or freeing it when
+/// there's no connection to hand it to
+async fn handle_transfer_control(
+    state: &AppState,
+    character_id: String,
+    to_connection_id: Option<String>,
+) {
+    let mut game = state.game.write().await;
+    let ids = Uuid::parse_str(&character_id).and_then(|char_id| {
+        to_connection_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map(|to| (char_id, to))
+    });
+    let Ok((char_id, to)) = ids else {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::InvalidId,
+            "Invalid character or connection ID",
+        )
+        .await;
+        return;
+    };
+    let result = match to {
+        Some(to) => game.transfer_control(&char_id, &to),
+        None => game.release_control(&char_id),
+    };
+    let from = match result {
+        Ok(from) => from,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    if to.is_none() && from.is_none() {
+        return;
+    }
+
+    let character = &game.characters[&char_id];
+    println!(
+        "🔁 Control of {} moved: {:?} -> {:?}",
+        character.name, from, to
+    );
+    let _ = state.broadcaster.send(ServerMessage::ControlTransferred {
+        character_id: character_id.clone(),
+        name: character.name.clone(),
+        from_connection_id: from.map(|id| id.to_string()),
+        to_connection_id: to.map(|id| id.to_string()),
+    });
+    if to.is_some() {
+        // Only the new controller is sent the sheet
+        let _ = state.broadcaster.send(ServerMessage::CharacterSelected {
+            character_id,
+            character: character.to_data(),
+        });
+    }
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM closing a connection, optionally banning its address
+async fn handle_kick_connection(
+    state: &AppState,
+    conn_id: &Uuid,
+    connection_id: String,
+    ban: bool,
+) {
+    let mut game = state.game.write().await;
+    let Ok(target) = Uuid::parse_str(&connection_id) else {
+        drop(game);
+        send_error(state, ErrorCode::InvalidId, "Invalid connection ID").await;
+        return;
+    };
+    let name = match game.kick_connection(conn_id, &target, ban) {
+        Ok(name) => name,
+        Err(e) => {
             drop(game);
-            send_error(state, "No character selected").await;
+            send_error(state, ErrorCode::Rejected, &e).await;
             return;
         }
     };
+    let display_name = game.connections[&target].display_name.clone();
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
     drop(game);
 
+    println!("👢 {} kicked ({}), banned: {}", name, target, ban);
+    drop_connection(state, &target).await;
+    let _ = state.broadcaster.send(ServerMessage::Kicked {
+        connection_id,
+        display_name,
+        banned: ban,
+    });
+}
+
+/// Handle character movement along a path
+async fn handle_move_character(
+    state: &AppState,
+    conn_id: &Uuid,
+    x: f32,
+    y: f32,
+    path: Vec<protocol::Position>,
+    character_id: Option<String>,
+    facing: Option<f32>,
+) {
     let mut game = state.game.write().await;
 
-    let character = match game.get_character_mut(&char_id) {
-        Some(c) => c,
-        None => {
+    let char_id = match game.check_can_move(conn_id, character_id.as_deref(), false) {
+        Ok(id) => id,
+        Err(e) => {
             drop(game);
-            send_error(state, "Character not found").await;
+            send_error(state, ErrorCode::Rejected, &e).await;
             return;
         }
     };
 
-    match resource.as_str() {
-        "hp" => {
-            if amount < 0 {
-                character.hp.take_damage((-amount) as u8);
-            } else {
-                character.hp.heal(amount as u8);
-            }
-        }
-        "stress" => {
-            if amount > 0 {
-                character.stress.gain(amount as u8);
-            } else {
-                character.stress.clear();
-            }
-        }
-        "hope" => {
-            if amount < 0 {
-                let _ = character.hope.spend((-amount) as u8);
-            } else {
-                character.hope.gain(amount as u8);
-            }
-        }
-        _ => {
+    let was_fogged = game.fogged_tokens().contains(&char_id.to_string());
+    let destination = crate::protocol::Position::new(x, y);
+    let movement = match game.move_character(&char_id, &path, destination, facing) {
+        Ok(movement) => movement,
+        Err(e) => {
             drop(game);
-            send_error(state, &format!("Invalid resource: {}", resource)).await;
+            send_error(state, ErrorCode::Rejected, &e).await;
             return;
         }
-    }
+    };
+    announce_fog_crossing(state, &game, &char_id.to_string(), was_fogged);
 
-    character.sync_resources();
-    let character_data = character.to_data();
-    drop(game);
+    let crossed_hazards: Vec<String> = movement
+        .crossed_hazards
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    let crossed_any = !crossed_hazards.is_empty();
 
-    // Broadcast character update
-    let msg = ServerMessage::CharacterUpdated {
+    // Broadcast movement with the route for clients to animate; the
+    // destination may have snapped to the grid
+    let msg = ServerMessage::CharacterMoved {
         character_id: char_id.to_string(),
-        character: character_data,
+        position: movement.path.last().copied().unwrap_or(destination),
+        path: movement.path,
+        teleported: false,
+        crossed_hazards,
+        rotation: movement.rotation,
     };
-    let _ = state.broadcaster.send(msg.to_json());
-}
+    broadcast_move(state, msg);
 
-/// Send error message
-async fn send_error(state: &AppState, message: &str) {
-    let msg = ServerMessage::Error {
-        message: message.to_string(),
-    };
-    let _ = state.broadcaster.send(msg.to_json());
+    if crossed_any {
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+
+    refresh_auras(state, &mut game).await;
 }
 
-/// Broadcast a game event to all clients
-async fn broadcast_event(state: &AppState, event: &game::GameEvent) {
-    use std::time::UNIX_EPOCH;
-    
-    let timestamp = event.timestamp
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    
-    let timestamp_str = chrono::DateTime::from_timestamp(timestamp as i64, 0)
-        .map(|dt| dt.format("%H:%M:%S").to_string())
-        .unwrap_or_else(|| "??:??:??".to_string());
-    
-    let event_type_str = format!("{:?}", event.event_type);
-    
-    let msg = protocol::ServerMessage::GameEvent {
-        timestamp: timestamp_str,
-        event_type: event_type_str,
-        message: event.message.clone(),
-        character_name: event.character_name.clone(),
-        details: event.details.clone(),
-    };
-    
-    let _ = state.broadcaster.send(msg.to_json());
+/// Broadcast a token's move, or hold it back if that token moved very
+/// recently (see [`crate::movement`])
+fn broadcast_move(state: &AppState, msg: ServerMessage) {
+    match state.moves.offer(msg, Instant::now()) {
+        Offer::Send(msg) => {
+            let _ = state.broadcaster.send(*msg);
+        }
+        Offer::Held { token_id, delay } => {
+            let state = state.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                if let Some(msg) = state.moves.flush(&token_id, Instant::now()) {
+                    let _ = state.broadcaster.send(msg);
+                }
+            });
+        }
+        Offer::Merged => {}
+    }
 }
 
-/// Send characters list to a specific connection
-async fn send_characters_list(
+/// Handle a character teleporting (no route, so no hazards crossed)
+async fn handle_teleport_character(
     state: &AppState,
     conn_id: &Uuid,
-    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+    x: f32,
+    y: f32,
+    character_id: Option<String>,
 ) {
-    let game = state.game.read().await;
-    let characters = build_character_list(&game, conn_id);
-    drop(game);
+    let mut game = state.game.write().await;
+
+    let char_id = match game.check_can_move(conn_id, character_id.as_deref(), true) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let was_fogged = game.fogged_tokens().contains(&char_id.to_string());
+    let position =
+        match game.update_character_position(&char_id, crate::protocol::Position::new(x, y)) {
+            Ok(position) => position,
+            Err(e) => {
+                drop(game);
+                send_error(state, ErrorCode::Rejected, &e).await;
+                return;
+            }
+        };
+    announce_fog_crossing(state, &game, &char_id.to_string(), was_fogged);
+
+    let msg = ServerMessage::CharacterMoved {
+        character_id: char_id.to_string(),
+        position,
+        path: Vec::new(),
+        teleported: true,
+        crossed_hazards: Vec::new(),
+        rotation: game.characters[&char_id].token.rotation,
+    };
+    broadcast_move(state, msg);
 
-    let msg = ServerMessage::CharactersList { characters };
-    let _ = sender.send(Message::Text(msg.to_json())).await;
+    refresh_auras(state, &mut game).await;
 }
 
-/// Send adversaries list to a specific connection
-async fn send_adversaries_list(
+/// Handle a token turning, resizing or getting a new elevation or note
+///
+/// Players may only change their own character's token.
+async fn handle_set_token_details(
     state: &AppState,
-    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+    conn_id: &Uuid,
+    token_id: String,
+    token: protocol::tokens::TokenDetails,
 ) {
-    let game = state.game.read().await;
-    let adversaries = build_adversaries_list(&game);
-    drop(game);
+    let mut game = state.game.write().await;
+    let is_gm = game
+        .connections
+        .get(conn_id)
+        .is_some_and(|c| c.role == ConnectionRole::Gm);
+    let is_own = game
+        .controlled_characters(conn_id)
+        .iter()
+        .any(|char_id| char_id.to_string() == token_id);
+    if !is_gm && !is_own {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "You can only change your own token",
+        )
+        .await;
+        return;
+    }
 
-    let msg = ServerMessage::AdversariesList { adversaries };
-    let _ = sender.send(Message::Text(msg.to_json())).await;
+    match game.set_token_details(&token_id, token) {
+        Ok(token) => {
+            let _ = state
+                .broadcaster
+                .send(ServerMessage::TokenDetailsChanged { token_id, token });
+        }
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
 }
 
-/// Broadcast characters list to all connections
-async fn broadcast_characters_list(state: &AppState) {
-    println!("📡 Broadcasting characters list to all connections...");
-    // Note: We cannot personalize this broadcast (each connection needs different control info)
-    // For now, we just don't send anything - clients stay with their current state    // TODO: Could send individual messages to each connection with personalized data
-    // or send a generic "refresh" signal
-}
+/// Handle the GM putting any token somewhere
+///
+/// The GM isn't held to control, the map lock or the combat movement
+/// budget, and crossing hazards doesn't trigger them.
+async fn handle_move_token(
+    state: &AppState,
+    token_id: String,
+    position: protocol::Position,
+    teleport: bool,
+) {
+    let mut game = state.game.write().await;
+    let character_id = Uuid::parse_str(&token_id)
+        .ok()
+        .filter(|id| game.characters.contains_key(id));
+    let start = match character_id {
+        Some(char_id) => game.characters[&char_id].position,
+        None => match game.adversaries.get(&token_id) {
+            Some(adversary) => adversary.position,
+            None => {
+                drop(game);
+                send_error(
+                    state,
+                    ErrorCode::NotFound,
+                    &format!("Token not found: {}", token_id),
+                )
+                .await;
+                return;
+            }
+        },
+    };
+    let was_fogged = game.fogged_tokens().contains(&token_id);
+    let position = match game.move_token(&token_id, position) {
+        Ok(position) => position,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    announce_fog_crossing(state, &game, &token_id, was_fogged);
 
-/// Build character list with control information for a specific connection
-fn build_character_list(game: &GameState, conn_id: &Uuid) -> Vec<CharacterInfo> {
-    let my_char_id = game.control_mapping.get(conn_id).copied();
+    let msg = match character_id {
+        Some(char_id) => ServerMessage::CharacterMoved {
+            character_id: token_id,
+            position,
+            path: if teleport {
+                Vec::new()
+            } else {
+                vec![start, position]
+            },
+            teleported: teleport,
+            crossed_hazards: Vec::new(),
+            rotation: game.characters[&char_id].token.rotation,
+        },
+        None => ServerMessage::AdversaryMoved {
+            adversary_id: token_id,
+            position,
+        },
+    };
+    broadcast_move(state, msg);
 
-    game.get_characters()
-        .iter()
-        .map(|character| {
-            let controlled_by_me = Some(character.id) == my_char_id;
-            let controlled_by_other = game
-                .control_mapping
-                .values()
-                .any(|&char_id| char_id == character.id && Some(char_id) != my_char_id);
+    refresh_auras(state, &mut game).await;
+}
 
-            CharacterInfo {
-                id: character.id.to_string(),
-                name: character.name.clone(),
-                class: character.class.to_string(),
-                ancestry: character.ancestry.to_string(),
-                position: character.position,
-                color: character.color.clone(),
-                is_npc: character.is_npc,
-                controlled_by_me,
-                controlled_by_other,
-            }
-        })
-        .collect()
-}
-
-/// Build adversaries list from game state
-fn build_adversaries_list(game: &GameState) -> Vec<protocol::AdversaryInfo> {
-    game.get_adversaries()
-        .iter()
-        .map(|adversary| protocol::AdversaryInfo {
-            id: adversary.id.clone(),
-            name: adversary.name.clone(),
-            template: adversary.template.clone(),
-            position: adversary.position,
-            hp: adversary.hp,
-            max_hp: adversary.max_hp,
-            stress: adversary.stress,
-            evasion: adversary.evasion,
-            armor: adversary.armor,
-            attack_modifier: adversary.attack_modifier,
-            damage_dice: adversary.damage_dice.clone(),
-            is_active: adversary.is_active,
-        })
-        .collect()
-}
-
-// ===== Phase 1: GM-Initiated Dice Rolls =====
-
-/// Handle GM roll request
-async fn handle_request_roll(
+/// Handle the GM renaming any token or changing its details
+async fn handle_edit_token(
     state: &AppState,
-    target_type: protocol::RollTargetType,
-    target_character_ids: Vec<String>,
-    roll_type: protocol::RollType,
-    attribute: Option<String>,
-    difficulty: u16,
-    context: String,
-    narrative_stakes: Option<String>,
-    situational_modifier: i8,
-    has_advantage: bool,
-    is_combat: bool,
+    token_id: String,
+    name: Option<String>,
+    token: Option<protocol::tokens::TokenDetails>,
 ) {
-    use uuid::Uuid;
-
     let mut game = state.game.write().await;
-
-    // Parse target character IDs
-    let mut target_uuids = Vec::new();
-    match target_type {
-        protocol::RollTargetType::Specific => {
-            for id_str in &target_character_ids {
-                if let Ok(uuid) = Uuid::parse_str(id_str) {
-                    if game.characters.contains_key(&uuid) {
-                        target_uuids.push(uuid);
+    // Check the details before renaming, so a bad edit changes nothing
+    let token = match token.map(|token| token.normalized()).transpose() {
+        Ok(token) => token,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    if let Some(name) = name {
+        let events_before = game.event_log.len();
+        match game.rename_token(&token_id, &name) {
+            Ok(name) => {
+                let _ = state.broadcaster.send(ServerMessage::TokenRenamed {
+                    token_id: token_id.clone(),
+                    name,
+                });
+                if game.event_log.len() > events_before {
+                    if let Some(event) = game.event_log.last() {
+                        broadcast_event(state, event).await;
                     }
                 }
             }
+            Err(e) => {
+                drop(game);
+                send_error(state, ErrorCode::Rejected, &e).await;
+                return;
+            }
         }
-        protocol::RollTargetType::All => {
-            target_uuids = game.get_player_characters().iter().map(|c| c.id).collect();
-        }
-        protocol::RollTargetType::Npc => {
-            // For MVP, treat as specific
-            for id_str in &target_character_ids {
-                if let Ok(uuid) = Uuid::parse_str(id_str) {
-                    if game.characters.contains_key(&uuid) {
-                        target_uuids.push(uuid);
-                    }
-                }
+    }
+    if let Some(token) = token {
+        match game.set_token_details(&token_id, token) {
+            Ok(token) => {
+                let _ = state
+                    .broadcaster
+                    .send(ServerMessage::TokenDetailsChanged { token_id, token });
+            }
+            Err(e) => {
+                drop(game);
+                send_error(state, ErrorCode::Rejected, &e).await;
             }
         }
     }
+}
 
-    if target_uuids.is_empty() {
-        send_error(state, "No valid characters targeted").await;
-        return;
+/// Handle the GM locking or unlocking token movement
+async fn handle_set_map_locked(state: &AppState, locked: bool) {
+    let mut game = state.game.write().await;
+    game.set_map_locked(locked);
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::MapLockChanged { locked });
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
     }
+}
 
-    // Create roll request
-    let request_id = Uuid::new_v4().to_string();
-    let request = game::PendingRollRequest {
-        id: request_id.clone(),
-        target_character_ids: target_uuids.clone(),
-        roll_type: roll_type.clone(),
-        attribute: attribute.clone(),
-        difficulty,
-        context: context.clone(),
-        narrative_stakes: narrative_stakes.clone(),
-        situational_modifier,
-        has_advantage,
-        is_combat,
-        completed_by: Vec::new(),
-        timestamp: std::time::SystemTime::now(),
+/// Handle a player emote
+async fn handle_emote(state: &AppState, conn_id: &Uuid, text: String, log: bool) {
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
     };
 
-    game.pending_roll_requests
-        .insert(request_id.clone(), request);
-    
-    // Log event
-    let target_names: Vec<String> = target_uuids
-        .iter()
-        .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
-        .collect();
-    let target_desc = if target_names.len() == game.get_player_characters().len() {
-        "all players".to_string()
-    } else {
-        target_names.join(", ")
+    let text = match game.emote(&char_id, &text, log) {
+        Ok(text) => text,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
     };
-    
-    game.add_event(
-        game::GameEventType::RollRequested,
-        format!("GM requested {} roll: \"{}\"", 
-            attribute.as_deref().unwrap_or("general"),
-            context
-        ),
-        None,
-        Some(format!("Target: {}, DC {}", target_desc, difficulty)),
-    );
 
-    // Send roll request to each targeted character
-    for char_id in &target_uuids {
-        if let Some(character) = game.characters.get(char_id) {
-            // Calculate base modifier
-            let attr_mod = if let Some(ref attr) = attribute {
-                character.get_attribute(attr).unwrap_or(0)
-            } else {
-                0
-            };
+    let msg = ServerMessage::CharacterEmoted {
+        character_id: char_id.to_string(),
+        character_name: game.characters[&char_id].name.clone(),
+        text,
+        duration_ms: game::EMOTE_DURATION_MS,
+    };
+    let _ = state.broadcaster.send(msg);
 
-            let prof_mod = match roll_type {
-                protocol::RollType::Attack | protocol::RollType::Spellcast => {
-                    character.proficiency_bonus()
-                }
-                _ => 0,
-            };
+    if log {
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+}
+
+/// Handle a chat message
+async fn handle_chat_message(
+    state: &AppState,
+    conn_id: &Uuid,
+    channel: protocol::chat::ChatChannel,
+    text: String,
+) {
+    let mut game = state.game.write().await;
 
-            let base_modifier = attr_mod + prof_mod;
-            let total_modifier = base_modifier + situational_modifier;
+    let message = match game.chat(conn_id, channel, &text) {
+        Ok(message) => message,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    let logged = !message.is_whisper();
 
-            let can_spend_hope = character.hope.current >= 1 && !character.experiences.is_empty();
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::ChatMessage { message });
 
-            let msg = protocol::ServerMessage::RollRequested {
-                request_id: request_id.clone(),
-                roll_type: roll_type.clone(),
-                attribute: attribute.clone(),
-                difficulty,
-                context: context.clone(),
-                narrative_stakes: narrative_stakes.clone(),
-                base_modifier,
-                situational_modifier,
-                total_modifier,
-                has_advantage,
-                your_attribute_value: attr_mod,
-                your_proficiency: prof_mod,
-                can_spend_hope,
-                experiences: character.experiences.clone(),
-            };
+    if logged {
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+}
+
+/// Handle a connection choosing its display name
+async fn handle_set_display_name(state: &AppState, conn_id: &Uuid, name: String) {
+    let mut game = state.game.write().await;
+
+    let events_before = game.event_log.len();
+    let display_name = match game.set_display_name(conn_id, &name) {
+        Ok(name) => name,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::DisplayNameChanged {
+        connection_id: conn_id.to_string(),
+        display_name,
+        character_id: game
+            .controlled_characters(conn_id)
+            .last()
+            .map(|id| id.to_string()),
+    };
+    let _ = state.broadcaster.send(msg);
 
-            state.broadcaster.send(msg.to_json()).ok();
+    // Re-sending the same name logs nothing
+    if game.event_log.len() != events_before {
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
         }
     }
+}
 
-    // Send status to GM
-    let pending: Vec<String> = target_uuids
-        .iter()
-        .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
-        .collect();
+/// Handle a player saving their preferences
+async fn handle_set_preferences(state: &AppState, conn_id: &Uuid, preferences: UserPreferences) {
+    let mut game = state.game.write().await;
 
-    let status_msg = protocol::ServerMessage::RollRequestStatus {
-        request_id,
-        pending_characters: pending,
-        completed_characters: Vec::new(),
+    let preferences = match game.set_preferences(conn_id, preferences) {
+        Ok(preferences) => preferences,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
     };
+    drop(game);
 
-    state.broadcaster.send(status_msg.to_json()).ok();
+    let msg = ServerMessage::PreferencesUpdated {
+        connection_id: conn_id.to_string(),
+        preferences,
+    };
+    let _ = state.broadcaster.send(msg);
 }
 
-/// Handle player executing a roll
-async fn handle_execute_roll(
+/// Handle a token color change
+///
+/// Players may recolor their own character, or any character nobody else
+/// controls (the GM's NPCs).
+async fn handle_set_character_color(
     state: &AppState,
     conn_id: &Uuid,
-    request_id: String,
-    spend_hope: bool,
-    chosen_experience: Option<String>,
+    character_id: String,
+    color: String,
 ) {
     let mut game = state.game.write().await;
 
-    // Get character ID for this connection
-    let char_id = match game.control_mapping.get(conn_id) {
-        Some(id) => *id,
-        None => {
-            send_error(state, "No character controlled").await;
+    let char_id = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            drop(game);
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
             return;
         }
     };
+    let controlled_by_other = game
+        .controller_of(&char_id)
+        .is_some_and(|conn| conn != *conn_id);
+    if controlled_by_other {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "Another player controls that character",
+        )
+        .await;
+        return;
+    }
 
-    // Execute the roll
-    let roll_result = match game.execute_roll(&char_id, &request_id, spend_hope) {
-        Ok(result) => result,
+    let color = match game.set_character_color(&char_id, &color) {
+        Ok(color) => color,
         Err(e) => {
-            send_error(state, &e).await;
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
             return;
         }
     };
 
-    // Get character name and request context
-    let character_name = game
-        .characters
-        .get(&char_id)
-        .map(|c| c.name.clone())
-        .unwrap_or_else(|| "Unknown".to_string());
+    let msg = ServerMessage::CharacterColorChanged {
+        character_id,
+        color,
+    };
+    let _ = state.broadcaster.send(msg);
 
-    let request = game.pending_roll_requests.get(&request_id).cloned();
-    let context = request
-        .as_ref()
-        .map(|r| r.context.clone())
-        .unwrap_or_default();
-    let roll_type = request
-        .as_ref()
-        .map(|r| r.roll_type.clone())
-        .unwrap_or(protocol::RollType::Action);
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
 
-    // Get new Hope/Fear values
-    let character = game.characters.get(&char_id).unwrap();
-    let new_hope = character.hope.current;
-    let new_fear = game.fear_pool;
+/// Handle dice roll
+async fn handle_roll_duality(
+    state: &AppState,
+    conn_id: &Uuid,
+    modifier: i32,
+    with_advantage: bool,
+    with_disadvantage: bool,
+    character_id: Option<String>,
+) {
+    let mut game = state.game.write().await;
 
-    // Create outcome description
-    let outcome_description = match roll_result.success_type {
-        protocol::SuccessType::CriticalSuccess => "CRITICAL SUCCESS".to_string(),
-        protocol::SuccessType::SuccessWithHope => "SUCCESS WITH HOPE".to_string(),
-        protocol::SuccessType::SuccessWithFear => "SUCCESS WITH FEAR".to_string(),
-        protocol::SuccessType::Failure => "FAILURE".to_string(),
+    let char_id = match game.acting_character(conn_id, character_id.as_deref()) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
     };
-    
-    // Log event
-    let roll_message = format!(
-        "{} rolled {} for \"{}\"",
-        character_name,
-        outcome_description.to_lowercase(),
-        context
-    );
-    let roll_details = format!(
-        "Hope: {}, Fear: {}, Total: {}",
-        roll_result.hope_die,
-        roll_result.fear_die,
-        roll_result.total
-    );
-    game.add_event(
-        game::GameEventType::RollExecuted,
-        roll_message,
-        Some(character_name.clone()),
-        Some(roll_details),
+
+    let character = match game.get_character(&char_id) {
+        Some(c) => c.clone(),
+        None => {
+            drop(game);
+            send_error(state, ErrorCode::NotFound, "Character not found").await;
+            return;
+        }
+    };
+
+    let roll = game.roll_duality(modifier, with_advantage, with_disadvantage);
+    game.record_duality(&char_id, roll.hope as u8, roll.fear as u8);
+    drop(game);
+
+    println!(
+        "🎲 {} rolled: {}d12 = {}",
+        character.name, roll.hope, roll.fear
     );
-    let event = game.event_log.last().cloned();
 
-    // Broadcast result to all clients
-    let msg = protocol::ServerMessage::DetailedRollResult {
-        request_id: request_id.clone(),
+    // Broadcast roll result
+    let msg = ServerMessage::RollResult {
         character_id: char_id.to_string(),
-        character_name,
-        roll_type,
-        context,
-        roll_details: roll_result,
-        outcome_description,
-        new_hope,
-        new_fear,
+        character_name: character.name,
+        roll,
     };
+    let _ = state.broadcaster.send(msg);
+}
 
-    state.broadcaster.send(msg.to_json()).ok();
-
-    // Update roll request status
-    if let Some(req) = game.pending_roll_requests.get(&request_id) {
-        let pending: Vec<String> = req
-            .target_character_ids
+/// Handle a free-form dice roll
+async fn handle_roll_dice(
+    state: &AppState,
+    conn_id: &Uuid,
+    expression: String,
+    label: Option<String>,
+    hidden: bool,
+    character_id: Option<String>,
+) {
+    let mut game = state.game.write().await;
+
+    let result = match game.roll_dice(
+        conn_id,
+        character_id.as_deref(),
+        &expression,
+        label.as_deref(),
+        hidden,
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    println!(
+        "🎲 {} rolled {} = {}",
+        result.roller_name, result.roll.expression, result.roll.total
+    );
+
+    let msg = ServerMessage::DiceRolled {
+        character_id: result.character_id.map(|id| id.to_string()),
+        roller_name: result.roller_name,
+        label: result.label,
+        roll: result.roll,
+        hidden: result.hidden,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle resource update
+async fn handle_update_resource(state: &AppState, conn_id: &Uuid, resource: String, amount: i32) {
+    let game = state.game.read().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    drop(game);
+
+    let mut game = state.game.write().await;
+
+    let character = match game.get_character_mut(&char_id) {
+        Some(c) => c,
+        None => {
+            drop(game);
+            send_error(state, ErrorCode::NotFound, "Character not found").await;
+            return;
+        }
+    };
+
+    match resource.as_str() {
+        "hp" => {
+            if amount < 0 {
+                character.hp.take_damage((-amount) as u8);
+            } else {
+                character.hp.heal(amount as u8);
+            }
+        }
+        "stress" => {
+            if amount > 0 {
+                character.stress.gain(amount as u8);
+            } else {
+                character.stress.clear();
+            }
+        }
+        "hope" => {
+            if amount < 0 {
+                let _ = character.hope.spend((-amount) as u8);
+            } else {
+                character.hope.gain(amount as u8);
+            }
+        }
+        _ => {
+            drop(game);
+            send_error(
+                state,
+                ErrorCode::Rejected,
+                &format!("Invalid resource: {}", resource),
+            )
+            .await;
+            return;
+        }
+    }
+
+    character.sync_resources();
+    let character_data = character.to_data();
+    drop(game);
+
+    broadcast_sheet(state, &char_id, character_data);
+}
+
+/// Handle the GM setting HP, Stress or Hope on any character or adversary
+async fn handle_set_resource(
+    state: &AppState,
+    token_id: String,
+    resource: protocol::ResourceKind,
+    value: i32,
+    relative: bool,
+) {
+    let mut game = state.game.write().await;
+    if let Err(e) = game.set_resource(&token_id, resource, value, relative) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    let character = Uuid::parse_str(&token_id)
+        .ok()
+        .and_then(|id| game.characters.get(&id));
+    match character {
+        Some(character) => broadcast_sheet(state, &character.id, character.to_data()),
+        None => {
+            let adversary = &game.adversaries[&token_id];
+            let _ = state.broadcaster.send(ServerMessage::AdversaryUpdated {
+                adversary_id: token_id,
+                hp: adversary.hp,
+                stress: adversary.stress,
+                is_active: adversary.is_active,
+            });
+        }
+    }
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+    refresh_adversary_groups(state, &mut game).await;
+}
+
+/// Handle a rest, Hope feature or ally's ability clearing a character's HP
+/// or Stress
+///
+/// The GM and the character's player can heal it, and so can the player of
+/// an ally whose ability does the healing.
+async fn handle_heal(
+    state: &AppState,
+    conn_id: &Uuid,
+    character_id: String,
+    source: protocol::healing::HealingSource,
+) {
+    let mut game = state.game.write().await;
+    let Ok(char_id) = Uuid::parse_str(&character_id) else {
+        drop(game);
+        send_error(state, ErrorCode::NotFound, "Character not found").await;
+        return;
+    };
+    let is_gm = game
+        .connections
+        .get(conn_id)
+        .is_some_and(|c| c.role == ConnectionRole::Gm);
+    let ally_controlled = match &source {
+        protocol::healing::HealingSource::Ally { ally_id, .. } => {
+            Uuid::parse_str(ally_id).is_ok_and(|ally_id| game.controls(conn_id, &ally_id))
+        }
+        _ => false,
+    };
+    if !is_gm && !ally_controlled && !game.controls(conn_id, &char_id) {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "You can't heal a character you don't control",
+        )
+        .await;
+        return;
+    }
+
+    let events_before = game.event_log.len();
+    let modifiers_before = game.modifiers.len();
+    let healing = match game.heal(&char_id, &source) {
+        Ok(healing) => healing,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!("🩹 {} healed by {}", healing.character_name, healing.source);
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::CharacterHealed { healing });
+    if let Some(character) = game.get_character(&char_id) {
+        broadcast_sheet(state, &char_id, character.to_data());
+    }
+    // Resting ends the character's modifiers that lasted until a rest
+    if game.modifiers.len() != modifiers_before {
+        broadcast_modifiers(state, &game);
+    }
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Tell the connection whose request is being handled that it failed
+async fn send_error(state: &AppState, code: ErrorCode, message: &str) {
+    let request = REQUEST.try_with(Clone::clone).ok();
+    let msg = ServerMessage::Error {
+        code,
+        message: message.to_string(),
+        request_type: request.as_ref().and_then(|r| r.header.request_type.clone()),
+        request_id: request.as_ref().and_then(|r| r.header.request_id.clone()),
+        connection_id: request.map(|r| r.connection_id.to_string()),
+    };
+    let _ = state.broadcaster.send(msg);
+}
+
+/// Broadcast a game event to all clients
+pub(crate) async fn broadcast_event(state: &AppState, event: &game::GameEvent) {
+    let data = event.to_data();
+    let msg = protocol::ServerMessage::GameEvent {
+        timestamp: data.timestamp,
+        event_type: data.event_type,
+        message: data.message,
+        character_name: data.character_name,
+        details: data.details,
+        player_name: data.player_name,
+        cutaway: data.cutaway,
+    };
+
+    let _ = state.broadcaster.send(msg);
+}
+
+/// Everything a connection needs to draw the table: the character and
+/// adversary lists and a snapshot of the scene, fight and recent events
+async fn send_catch_up(
+    state: &AppState,
+    conn_id: &Uuid,
+    format: WireFormat,
+    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+) {
+    send_characters_list(state, conn_id, format, sender).await;
+    send_adversaries_list(state, conn_id, format, sender).await;
+
+    let snapshot = {
+        let game = state.game.read().await;
+        SnapshotBuilder::new(&game).for_connection(conn_id).build()
+    };
+    let msg = ServerMessage::CatchUp { snapshot };
+    let _ = sender.send(format.frame(&msg)).await;
+}
+
+/// Bring a connection that fell behind the broadcasts back in step: the
+/// catch-up, then every character sheet whole, since the changes it missed
+/// can't be merged
+async fn resync(
+    state: &AppState,
+    conn_id: &Uuid,
+    format: WireFormat,
+    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+) {
+    send_catch_up(state, conn_id, format, sender).await;
+
+    let (recipient, character_ids) = {
+        let game = state.game.read().await;
+        let ids: Vec<String> = game.characters.keys().map(Uuid::to_string).collect();
+        (Recipient::of(&game, conn_id), ids)
+    };
+    let Some(recipient) = recipient else {
+        return;
+    };
+    for character_id in character_ids {
+        let Some(sheet) = state.sheets.full(&character_id, Some(conn_id.to_string())) else {
+            continue;
+        };
+        if visibility::visible_to(&sheet, &recipient) {
+            let sheet = visibility::redact(sheet, &recipient);
+            let _ = sender.send(format.frame(&sheet)).await;
+        }
+    }
+}
+
+/// Send characters list to a specific connection, leaving out NPCs under the fog
+async fn send_characters_list(
+    state: &AppState,
+    conn_id: &Uuid,
+    format: WireFormat,
+    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+) {
+    let game = state.game.read().await;
+    let msg = ServerMessage::CharactersList {
+        characters: build_character_list(&game, conn_id),
+    };
+    let msg = match Recipient::of(&game, conn_id) {
+        Some(recipient) => visibility::redact(msg, &recipient),
+        None => msg,
+    };
+    drop(game);
+
+    let _ = sender.send(format.frame(&msg)).await;
+}
+
+/// Send adversaries list to a specific connection, leaving out those under the fog
+async fn send_adversaries_list(
+    state: &AppState,
+    conn_id: &Uuid,
+    format: WireFormat,
+    sender: &mut futures::stream::SplitSink<WebSocket, Message>,
+) {
+    let game = state.game.read().await;
+    let msg = ServerMessage::AdversariesList {
+        adversaries: build_adversaries_list(&game),
+    };
+    let msg = match Recipient::of(&game, conn_id) {
+        Some(recipient) => visibility::redact(msg, &recipient),
+        None => msg,
+    };
+    drop(game);
+
+    let _ = sender.send(format.frame(&msg)).await;
+}
+
+/// Broadcast characters list to all connections
+async fn broadcast_characters_list(_state: &AppState) {
+    println!("📡 Broadcasting characters list to all connections...");
+    // Note: We cannot personalize this broadcast (each connection needs different control info)
+    // For now, we just don't send anything - clients stay with their current state    // TODO: Could send individual messages to each connection with personalized data
+    // or send a generic "refresh" signal
+}
+
+/// Build character list with control information for a specific connection
+fn build_character_list(game: &GameState, conn_id: &Uuid) -> Vec<CharacterInfo> {
+    game.get_characters()
+        .iter()
+        .map(|character| {
+            let controller = game.controller_of(&character.id);
+            let controlled_by_me = controller == Some(*conn_id);
+            let controlled_by_other = controller.is_some() && !controlled_by_me;
+
+            CharacterInfo {
+                id: character.id.to_string(),
+                name: character.name.clone(),
+                class: character.class.to_string(),
+                ancestry: character.ancestry.to_string(),
+                position: character.position,
+                color: character.color.clone(),
+                is_npc: character.is_npc,
+                controlled_by_me,
+                controlled_by_other,
+                player_name: game.display_name_of_controller(&character.id),
+                controller_id: controller.map(|id| id.to_string()),
+                token: character.token.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Build adversaries list from game state
+fn build_adversaries_list(game: &GameState) -> Vec<protocol::AdversaryInfo> {
+    game.get_adversaries()
+        .iter()
+        .map(|adversary| protocol::AdversaryInfo {
+            id: adversary.id.clone(),
+            name: adversary.name.clone(),
+            template: adversary.template.clone(),
+            tier: adversary.tier,
+            role: adversary.role,
+            position: adversary.position,
+            hp: adversary.hp,
+            max_hp: adversary.max_hp,
+            stress: adversary.stress,
+            evasion: adversary.evasion,
+            armor: adversary.armor,
+            attack_modifier: adversary.attack_modifier,
+            damage_dice: adversary.damage_dice.clone(),
+            damage_type: adversary.damage_type.clone(),
+            defenses: adversary.defenses.clone(),
+            is_active: adversary.is_active,
+            token: adversary.token.clone(),
+        })
+        .collect()
+}
+
+// ===== Phase 1: GM-Initiated Dice Rolls =====
+
+/// Characters a roll request targets
+fn resolve_roll_targets(
+    game: &GameState,
+    target_type: protocol::RollTargetType,
+    target_character_ids: &[String],
+) -> Vec<Uuid> {
+    match target_type {
+        protocol::RollTargetType::All => {
+            game.get_player_characters().iter().map(|c| c.id).collect()
+        }
+        // For MVP, NPC targets are treated as specific
+        protocol::RollTargetType::Specific | protocol::RollTargetType::Npc => target_character_ids
             .iter()
-            .filter(|id| !req.completed_by.contains(id))
-            .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
-            .collect();
+            .filter_map(|id_str| Uuid::parse_str(id_str).ok())
+            .filter(|uuid| game.characters.contains_key(uuid))
+            .collect(),
+    }
+}
+
+/// Handle the GM previewing a roll request before sending it
+async fn handle_preview_roll_request(
+    state: &AppState,
+    target_type: protocol::RollTargetType,
+    target_character_ids: Vec<String>,
+    spec: game::RollSpec,
+) {
+    let game = state.game.read().await;
+
+    let target_uuids = resolve_roll_targets(&game, target_type, &target_character_ids);
+    let previews = match game.preview_roll(&target_uuids, &spec) {
+        Ok(previews) => previews,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    drop(game);
+
+    let msg = ServerMessage::RollPreview { previews };
+    let _ = state.broadcaster.send(msg);
+}
+
+/// Handle GM roll request
+async fn handle_request_roll(state: &AppState, roll: game::RollRequest) {
+    use uuid::Uuid;
+
+    let game::RollRequest {
+        target_type,
+        target_character_ids,
+        roll_type,
+        attribute,
+        difficulty,
+        context,
+        narrative_stakes,
+        situational_modifier,
+        has_advantage,
+        has_disadvantage,
+        is_combat,
+        hidden,
+    } = roll;
+
+    let mut game = state.game.write().await;
+
+    let target_uuids = resolve_roll_targets(&game, target_type, &target_character_ids);
+    if target_uuids.is_empty() {
+        send_error(state, ErrorCode::Rejected, "No valid characters targeted").await;
+        return;
+    }
+
+    // Create roll request
+    let request_id = Uuid::new_v4().to_string();
+    let request = game::PendingRollRequest {
+        id: request_id.clone(),
+        target_character_ids: target_uuids.clone(),
+        roll_type: roll_type.clone(),
+        attribute: attribute.clone(),
+        difficulty,
+        context: context.clone(),
+        narrative_stakes: narrative_stakes.clone(),
+        situational_modifier,
+        has_advantage,
+        has_disadvantage,
+        is_combat,
+        completed_by: Vec::new(),
+        timestamp: std::time::SystemTime::now(),
+        group: None,
+        tag_team: None,
+        help: Vec::new(),
+        hidden,
+    };
+
+    game.pending_roll_requests
+        .insert(request_id.clone(), request.clone());
+
+    // Log event
+    let target_names: Vec<String> = target_uuids
+        .iter()
+        .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
+        .collect();
+    let target_desc = if target_names.len() == game.get_player_characters().len() {
+        "all players".to_string()
+    } else {
+        target_names.join(", ")
+    };
+    
+    game.add_event(
+        game::GameEventType::RollRequested,
+        format!(
+            "GM requested {}{} roll: \"{}\"",
+            if hidden { "a hidden " } else { "" },
+            attribute.as_deref().unwrap_or("general"),
+            context
+        ),
+        None,
+        Some(format!("Target: {}, DC {}", target_desc, difficulty)),
+    );
+
+    send_roll_request(state, &game, &request).await;
+}
+
+/// Handle GM opening a group action roll
+async fn handle_request_group_roll(state: &AppState, request: game::GroupRollRequest) {
+    let mut game = state.game.write().await;
+    let request = match game.request_group_roll(request) {
+        Ok(request) => request,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+    send_roll_request(state, &game, &request).await;
+}
+
+/// The roll request as sent to one targeted character
+fn roll_requested_message(
+    request: &game::PendingRollRequest,
+    char_id: &Uuid,
+    character: &game::Character,
+) -> protocol::ServerMessage {
+    let roll_type = request.roll_type_for(char_id);
+
+    // Calculate base modifier
+    let (attr_mod, prof_mod) = character.roll_modifiers(&roll_type, request.attribute.as_deref());
+
+    let base_modifier = attr_mod + prof_mod;
+    let total_modifier = base_modifier + request.situational_modifier;
+
+    let can_spend_hope = character.hope.current >= 1 && !character.experiences.is_empty();
+
+    protocol::ServerMessage::RollRequested {
+        request_id: request.id.clone(),
+        character_id: char_id.to_string(),
+        roll_type,
+        attribute: request.attribute.clone(),
+        difficulty: request.difficulty,
+        context: request.context.clone(),
+        narrative_stakes: request.narrative_stakes.clone(),
+        base_modifier,
+        situational_modifier: request.situational_modifier,
+        total_modifier,
+        has_advantage: request.has_advantage,
+        has_disadvantage: request.has_disadvantage,
+        your_attribute_value: attr_mod,
+        your_proficiency: prof_mod,
+        can_spend_hope,
+        experiences: character.experiences.clone(),
+        hidden: request.hidden,
+    }
+}
+
+/// Send a pending roll request to each targeted character, then its status to the GM
+async fn send_roll_request(state: &AppState, game: &GameState, request: &game::PendingRollRequest) {
+    for char_id in &request.target_character_ids {
+        if let Some(character) = game.characters.get(char_id) {
+            let msg = roll_requested_message(request, char_id, character);
+            state.broadcaster.send(msg).ok();
+        }
+    }
+
+    // Send status to GM
+    let pending: Vec<String> = request
+        .target_character_ids
+        .iter()
+        .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
+        .collect();
+
+    let status_msg = protocol::ServerMessage::RollRequestStatus {
+        request_id: request.id.clone(),
+        pending_characters: pending,
+        completed_characters: Vec::new(),
+    };
+
+    state.broadcaster.send(status_msg).ok();
+}
+
+/// Handle player executing a roll
+async fn handle_execute_roll(
+    state: &AppState,
+    conn_id: &Uuid,
+    request_id: String,
+    spend_hope: bool,
+    chosen_experience: Option<String>,
+    character_id: Option<String>,
+) {
+    let mut game = state.game.write().await;
+
+    // The character answering, which the client names if it has several
+    let char_id = match game.acting_character(conn_id, character_id.as_deref()) {
+        Ok(id) => id,
+        Err(e) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    // Execute the roll
+    let roll_result = match game.execute_roll(
+        &char_id,
+        &request_id,
+        spend_hope,
+        chosen_experience.as_deref(),
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    // Get character name and request context
+    let character_name = game
+        .characters
+        .get(&char_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let request = game.pending_roll_requests.get(&request_id).cloned();
+    let context = request
+        .as_ref()
+        .map(|r| r.context.clone())
+        .unwrap_or_default();
+    let roll_type = request
+        .as_ref()
+        .map(|r| r.roll_type_for(&char_id))
+        .unwrap_or(protocol::RollType::Action);
+    let hidden = request.as_ref().is_some_and(|r| r.hidden);
+
+    // Get new Hope/Fear values
+    let character = game.characters.get(&char_id).unwrap();
+    let new_hope = character.hope.current;
+    let new_fear = game.fear_pool;
+
+    // Clients word the outcome themselves; this is only for the log
+    let outcome = roll_result.success_type.label(roll_type.is_reaction());
+
+    // Log event
+    let roll_message = format!(
+        "{} rolled {} for \"{}\"",
+        character_name,
+        outcome.to_lowercase(),
+        context
+    );
+    let mut roll_details = format!(
+        "Hope: {}, Fear: {}, Total: {}",
+        roll_result.hope_die,
+        roll_result.fear_die,
+        roll_result.total
+    );
+    if let (true, Some(experience)) = (spend_hope, &chosen_experience) {
+        roll_details.push_str(&format!(
+            ", Experience: {} ({:+})",
+            experience, roll_result.hope_bonus
+        ));
+    }
+    if !roll_result.help_dice.is_empty() {
+        roll_details.push_str(&format!(
+            ", Help: +{} ({})",
+            roll_result.help_bonus,
+            roll_result
+                .help_dice
+                .iter()
+                .map(|d| format!("{} {}", d.helper_name, d.value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if hidden {
+        // The table only learns that a roll was made
+        game.add_event(
+            game::GameEventType::RollExecuted,
+            format!("{} made a hidden roll", character_name),
+            Some(character_name.clone()),
+            None,
+        );
+    } else {
+        game.add_event(
+            game::GameEventType::RollExecuted,
+            roll_message,
+            Some(character_name.clone()),
+            Some(roll_details),
+        );
+    }
+    let event = game.event_log.last().cloned();
+
+    // Broadcast result to all clients
+    let msg = protocol::ServerMessage::DetailedRollResult {
+        request_id: request_id.clone(),
+        character_id: char_id.to_string(),
+        character_name,
+        roll_type: roll_type.clone(),
+        context,
+        roll_details: roll_result.clone(),
+        new_hope,
+        new_fear,
+        hidden,
+    };
+
+    state.broadcaster.send(msg).ok();
+
+    // The leader finishing a group roll resolves it for everyone
+    let group_event = match request.as_ref().and_then(|r| r.group.as_ref()) {
+        Some(group) if group.leader_id == char_id => {
+            let helpers: Vec<protocol::GroupHelperResult> = group
+                .helper_results
+                .iter()
+                .map(|(id, success)| protocol::GroupHelperResult {
+                    character_id: id.to_string(),
+                    character_name: game
+                        .characters
+                        .get(id)
+                        .map(|c| c.name.clone())
+                        .unwrap_or_default(),
+                    success: *success,
+                })
+                .collect();
+            let leader_name = game.characters[&char_id].name.clone();
+
+            game.add_event(
+                game::GameEventType::RollExecuted,
+                format!("Group roll {}", outcome.to_lowercase()),
+                Some(leader_name.clone()),
+                Some(format!(
+                    "Helpers: {} ({:+}), Total: {}",
+                    helpers
+                        .iter()
+                        .map(|h| format!(
+                            "{} {}",
+                            h.character_name,
+                            if h.success { "✓" } else { "✗" }
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    roll_result.group_modifier,
+                    roll_result.total
+                )),
+            );
+
+            let group_msg = protocol::ServerMessage::GroupRollResult {
+                request_id: request_id.clone(),
+                context: request
+                    .as_ref()
+                    .map(|r| r.context.clone())
+                    .unwrap_or_default(),
+                leader_id: char_id.to_string(),
+                leader_name,
+                roll_type,
+                helpers,
+                group_modifier: roll_result.group_modifier,
+                total: roll_result.total,
+                difficulty: roll_result.difficulty,
+                success_type: roll_result.success_type,
+                hidden,
+            };
+            state.broadcaster.send(group_msg).ok();
+            game.event_log.last().cloned()
+        }
+        _ => None,
+    };
+
+    // Once both halves of a tag team have rolled, the group picks one
+    if let Some(tag_team) = game
+        .pending_roll_requests
+        .get(&request_id)
+        .and_then(|r| r.tag_team.as_ref())
+        .filter(|t| t.includes(&char_id) && t.results.len() == 2)
+    {
+        let results = tag_team
+            .results
+            .iter()
+            .map(|(id, result)| protocol::TagTeamRollResult {
+                character_id: id.to_string(),
+                character_name: game
+                    .characters
+                    .get(id)
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default(),
+                total: result.total,
+                success_type: result.success_type,
+            })
+            .collect();
+        let msg = protocol::ServerMessage::TagTeamRolled {
+            request_id: request_id.clone(),
+            results,
+        };
+        state.broadcaster.send(msg).ok();
+    }
+
+    // Update roll request status
+    if let Some(req) = game.pending_roll_requests.get(&request_id) {
+        let pending: Vec<String> = req
+            .target_character_ids
+            .iter()
+            .filter(|id| !req.completed_by.contains(id))
+            .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
+            .collect();
+
+        let completed: Vec<String> = req
+            .completed_by
+            .iter()
+            .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
+            .collect();
+
+        let status_msg = protocol::ServerMessage::RollRequestStatus {
+            request_id,
+            pending_characters: pending,
+            completed_characters: completed,
+        };
+
+        state.broadcaster.send(status_msg).ok();
+    }
+
+    // Broadcast updated character data
+    if let Some(character) = game.characters.get(&char_id) {
+        broadcast_sheet(state, &char_id, character.to_data());
+    }
+    
+    drop(game);
+    
+    // Broadcast event
+    if let Some(ev) = event {
+        broadcast_event(state, &ev).await;
+    }
+    if let Some(ev) = group_event {
+        broadcast_event(state, &ev).await;
+    }
+}
+
+/// Handle a player spending Hope to help another character's roll
+async fn handle_help_ally(
+    state: &AppState,
+    conn_id: &Uuid,
+    request_id: String,
+    character_id: String,
+) {
+    let target_uuid = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Err(e) = game.help_ally(&request_id, &char_id, &target_uuid) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    let helper = game.characters[&char_id].clone();
+    let msg = ServerMessage::AllyHelped {
+        request_id,
+        helper_id: char_id.to_string(),
+        helper_name: helper.name.clone(),
+        character_id,
+        character_name: game.characters[&target_uuid].name.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    broadcast_sheet(state, &char_id, helper.to_data());
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle a player proposing a tag team on their pending roll
+async fn handle_propose_tag_team(
+    state: &AppState,
+    conn_id: &Uuid,
+    request_id: String,
+    partner_id: String,
+) {
+    let partner_uuid = match Uuid::parse_str(&partner_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Err(e) = game.propose_tag_team(&request_id, &char_id, &partner_uuid) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    let msg = ServerMessage::TagTeamProposed {
+        request_id: request_id.clone(),
+        context: game.pending_roll_requests[&request_id].context.clone(),
+        initiator_id: char_id.to_string(),
+        initiator_name: game.characters[&char_id].name.clone(),
+        partner_id,
+        partner_name: game.characters[&partner_uuid].name.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the proposed partner accepting or turning down a tag team
+async fn handle_respond_tag_team(
+    state: &AppState,
+    conn_id: &Uuid,
+    request_id: String,
+    accept: bool,
+) {
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let initiator_id = game
+        .pending_roll_requests
+        .get(&request_id)
+        .and_then(|r| r.tag_team.as_ref())
+        .map(|t| t.initiator_id);
+    let request = match game.respond_tag_team(&request_id, &char_id, accept) {
+        Ok(request) => request,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let partner = game.characters[&char_id].clone();
+    let msg = ServerMessage::TagTeamResponse {
+        request_id: request_id.clone(),
+        partner_id: char_id.to_string(),
+        partner_name: partner.name.clone(),
+        accepted: accept,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    if accept {
+        // The partner now owes a roll, and both paid Hope
+        let msg = roll_requested_message(&request, &char_id, &partner);
+        let _ = state.broadcaster.send(msg);
+
+        for id in [Some(char_id), initiator_id].into_iter().flatten() {
+            if let Some(character) = game.characters.get(&id) {
+                broadcast_sheet(state, &id, character.to_data());
+            }
+        }
+    }
+}
+
+/// Handle a tag team member picking which roll applies
+async fn handle_choose_tag_team_result(
+    state: &AppState,
+    conn_id: &Uuid,
+    request_id: String,
+    character_id: String,
+) {
+    let chosen_uuid = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let outcome = match game.choose_tag_team_result(&request_id, &char_id, &chosen_uuid) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::TagTeamResolved {
+        request_id,
+        character_id,
+        character_name: game.characters[&chosen_uuid].name.clone(),
+        total: outcome.result.total,
+        difficulty: outcome.result.difficulty,
+        success_type: outcome.result.success_type,
+        hope_change: outcome.hope_change,
+        fear_change: outcome.fear_change,
+        new_fear: game.fear_pool,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    for id in outcome.team {
+        if let Some(character) = game.characters.get(&id) {
+            broadcast_sheet(state, &id, character.to_data());
+        }
+    }
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+// ===== Combat & Adversary Handlers =====
+
+/// Handle spawning an adversary from template
+async fn handle_spawn_adversary(state: &AppState, template: String, position: protocol::Position) {
+    let mut game = state.game.write().await;
+    
+    match game.spawn_adversary(&template, position) {
+        Ok(adversary) => {
+            // Broadcast adversary spawned
+            let msg = adversary_spawned(&adversary);
+            let _ = state.broadcaster.send(msg);
+
+            // Broadcast event
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(state, event).await;
+            }
+
+            refresh_auras(state, &mut game).await;
+        }
+        Err(e) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
+}
+
+/// Handle pricing a fight against the party's Battle Points budget
+async fn handle_price_encounter(
+    state: &AppState,
+    conn_id: &Uuid,
+    plan: protocol::encounters::EncounterPlan,
+) {
+    let priced = state.game.read().await.price_encounter(&plan);
+    match priced {
+        Ok(budget) => {
+            let _ = state.broadcaster.send(ServerMessage::EncounterPriced {
+                budget,
+                connection_id: Some(conn_id.to_string()),
+            });
+        }
+        Err(e) => send_error(state, ErrorCode::Rejected, &e).await,
+    }
+}
+
+/// Handle the GM saving or deleting an encounter preset
+async fn handle_encounter_preset_change(
+    state: &AppState,
+    change: impl FnOnce(&EncounterStore) -> Result<Vec<protocol::encounters::EncounterPreset>, String>,
+) {
+    match change(&state.encounters) {
+        Ok(presets) => {
+            let _ = state.broadcaster.send(ServerMessage::EncounterPresets {
+                presets,
+                skipped: Vec::new(),
+            });
+        }
+        Err(e) => send_error(state, ErrorCode::Rejected, &e).await,
+    }
+}
+
+/// Handle the GM keeping the adversaries on the map as a preset
+async fn handle_save_encounter_from_map(state: &AppState, name: String) {
+    let taken = state.game.read().await.encounter_from_map();
+    let (plan, skipped) = match taken {
+        Ok(taken) => taken,
+        Err(e) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    match state
+        .encounters
+        .save(protocol::encounters::EncounterPreset { name, plan })
+    {
+        Ok(presets) => {
+            let _ = state
+                .broadcaster
+                .send(ServerMessage::EncounterPresets { presets, skipped });
+        }
+        Err(e) => send_error(state, ErrorCode::Rejected, &e).await,
+    }
+}
+
+/// Handle the GM putting a whole encounter preset on the map
+async fn handle_spawn_encounter(state: &AppState, name: String) {
+    let mut game = state.game.write().await;
+    let preset = match state.encounters.get(&name) {
+        Ok(preset) => preset,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::NotFound, &e).await;
+            return;
+        }
+    };
+
+    let events_before = game.event_log.len();
+    let spawned = match game.spawn_encounter(&preset.plan.entries) {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!("⚔️ {} spawned ({} adversaries)", preset.name, spawned.len());
+
+    for adversary in &spawned {
+        let _ = state.broadcaster.send(adversary_spawned(adversary));
+    }
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+    refresh_auras(state, &mut game).await;
+}
+
+/// Announce an adversary joining the scene
+fn adversary_spawned(adversary: &game::Adversary) -> ServerMessage {
+    ServerMessage::AdversarySpawned {
+        adversary_id: adversary.id.clone(),
+        name: adversary.name.clone(),
+        template: adversary.template.clone(),
+        tier: adversary.tier,
+        role: adversary.role,
+        position: adversary.position,
+        hp: adversary.hp,
+        max_hp: adversary.max_hp,
+        evasion: adversary.evasion,
+        armor: adversary.armor,
+        attack_modifier: adversary.attack_modifier,
+        damage_dice: adversary.damage_dice.clone(),
+        motives_and_tactics: adversary.motives_and_tactics.clone(),
+        token: adversary.token.clone(),
+    }
+}
+
+/// Handle spawning a custom adversary
+async fn handle_spawn_custom_adversary(state: &AppState, custom: game::CustomAdversary) {
+    let mut game = state.game.write().await;
+
+    let adversary = match game.create_custom_adversary(custom) {
+        Ok(adversary) => adversary,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    // Broadcast adversary spawned
+    let msg = adversary_spawned(&adversary);
+    let _ = state.broadcaster.send(msg);
+
+    // Broadcast event
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    refresh_auras(state, &mut game).await;
+}
+
+/// Handle removing an adversary
+async fn handle_remove_adversary(state: &AppState, adversary_id: String) {
+    let mut game = state.game.write().await;
+    let grouped = game
+        .adversary_groups
+        .values()
+        .any(|g| g.member_ids.contains(&adversary_id));
+
+    if let Some(adversary) = game.remove_adversary(&adversary_id) {
+        let msg = ServerMessage::AdversaryRemoved {
+            adversary_id,
+            name: adversary.name.clone(),
+        };
+        let _ = state.broadcaster.send(msg);
+
+        // Broadcast event
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+
+        refresh_auras(state, &mut game).await;
+        if grouped {
+            broadcast_adversary_groups(state, &game);
+        }
+    }
+}
+
+// ===== Adversary Group Handlers =====
+
+/// Handle the GM running adversaries as one group
+async fn handle_create_adversary_group(state: &AppState, name: String, adversary_ids: Vec<String>) {
+    let mut game = state.game.write().await;
+    let group = match game.create_adversary_group(&name, &adversary_ids) {
+        Ok(group) => group,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!("👥 {}", group.summary());
+
+    broadcast_adversary_groups(state, &game);
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM splitting a group up
+async fn handle_disband_adversary_group(state: &AppState, group_id: String) {
+    let mut game = state.game.write().await;
+    if let Err(e) = game.disband_adversary_group(&group_id) {
+        drop(game);
+        send_error(state, ErrorCode::NotFound, &e).await;
+        return;
+    }
+
+    broadcast_adversary_groups(state, &game);
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM moving a group in formation
+async fn handle_move_adversary_group(
+    state: &AppState,
+    group_id: String,
+    position: protocol::Position,
+) {
+    let mut game = state.game.write().await;
+    let fogged = game.fogged_tokens();
+    let moves = match game.move_adversary_group(&group_id, position) {
+        Ok(moves) => moves,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    for (adversary_id, position) in moves {
+        announce_fog_crossing(state, &game, &adversary_id, fogged.contains(&adversary_id));
+        broadcast_move(
+            state,
+            ServerMessage::AdversaryMoved {
+                adversary_id,
+                position,
+            },
+        );
+    }
+
+    refresh_auras(state, &mut game).await;
+}
+
+/// Handle the GM giving a group one activation for all its members
+///
+/// Broadcasts the spotlight or tracker as it now stands and every standing
+/// member's tactics, as spending a tracker token does.
+async fn handle_activate_adversary_group(state: &AppState, group_id: String) {
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    let (group, activation) = match game.activate_adversary_group(&group_id) {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let mut triggers = Vec::new();
+    match activation {
+        game::GroupActivation::Spotlight { previous_id, .. } => {
+            broadcast_turn_order(state, &game, previous_id, None);
+        }
+        game::GroupActivation::Tracker(spend) => {
+            if let Some(encounter) = game.get_combat() {
+                let _ = state.broadcaster.send(tracker_updated(encounter));
+            }
+            if let Some((round, round_triggers)) = spend.round_advanced {
+                let _ = state
+                    .broadcaster
+                    .send(ServerMessage::RoundAdvanced { round });
+                triggers = round_triggers;
+            }
+        }
+    }
+
+    let adversaries: Vec<_> = group
+        .members
+        .iter()
+        .filter(|m| m.is_active)
+        .filter_map(|m| game.adversaries.get(&m.id))
+        .filter_map(|a| a.tactics())
+        .collect();
+    if !adversaries.is_empty() {
+        let _ = state
+            .broadcaster
+            .send(ServerMessage::TacticsReminder { adversaries });
+    }
+
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+    for trigger in triggers {
+        resolve_hazard_trigger(state, &mut game, trigger).await;
+    }
+}
+
+/// Handle the GM removing a group and every adversary in it
+async fn handle_remove_adversary_group(state: &AppState, group_id: String) {
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    let (name, removed) = match game.remove_adversary_group(&group_id) {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::NotFound, &e).await;
+            return;
+        }
+    };
+    println!("👥 {} removed ({} adversaries)", name, removed.len());
+
+    for adversary in removed {
+        let _ = state.broadcaster.send(ServerMessage::AdversaryRemoved {
+            adversary_id: adversary.id,
+            name: adversary.name,
+        });
+    }
+    broadcast_adversary_groups(state, &game);
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+
+    refresh_auras(state, &mut game).await;
+}
+
+/// Handle the GM closing gaps in adversary numbering
+async fn handle_renumber_adversaries(state: &AppState, template: Option<String>) {
+    let mut game = state.game.write().await;
+
+    if game.renumber_adversaries(template.as_deref()).is_empty() {
+        return;
+    }
+
+    let msg = ServerMessage::AdversariesList {
+        adversaries: build_adversaries_list(&game),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM creating an NPC
+async fn handle_create_npc(
+    state: &AppState,
+    name: String,
+    hp: Option<u8>,
+    evasion: Option<i32>,
+    template: Option<String>,
+    position: Option<protocol::Position>,
+) {
+    let mut game = state.game.write().await;
+
+    let npc = match game.create_npc(name, hp, evasion, template.as_deref(), position) {
+        Ok(npc) => npc,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    let event = game.event_log.last().cloned();
+    drop(game);
+
+    println!("🎭 NPC created: {} ({})", npc.name, npc.id);
+
+    if let Some(event) = event {
+        broadcast_event(state, &event).await;
+    }
+
+    let msg = ServerMessage::CharacterSpawned {
+        character_id: npc.id.to_string(),
+        name: npc.name.clone(),
+        position: npc.position,
+        color: npc.color.clone(),
+        is_npc: true,
+        token: npc.token.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    broadcast_characters_list(state).await;
+}
+
+/// Handle the GM turning an NPC hostile
+async fn handle_convert_npc(state: &AppState, character_id: String) {
+    let char_uuid = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    let adversary = match game.convert_npc_to_adversary(&char_uuid) {
+        Ok(adversary) => adversary,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    println!(
+        "⚔️ NPC turned hostile: {} ({})",
+        adversary.name, adversary.id
+    );
+
+    let msg = ServerMessage::CharacterRemoved {
+        character_id,
+        name: adversary.name.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+    let _ = state.broadcaster.send(adversary_spawned(&adversary));
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    refresh_auras(state, &mut game).await;
+    drop(game);
+
+    broadcast_characters_list(state).await;
+}
+
+/// Handle a character's sheet being corrected by its controller or the GM
+async fn handle_update_character(
+    state: &AppState,
+    conn_id: &Uuid,
+    character_id: String,
+    changes: protocol::CharacterEdit,
+) {
+    let mut game = state.game.write().await;
+
+    let char_id = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            drop(game);
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+    let is_gm = game
+        .connections
+        .get(conn_id)
+        .is_some_and(|c| c.role == ConnectionRole::Gm);
+    if !is_gm && !game.controls(conn_id, &char_id) {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "You can only edit your own character",
+        )
+        .await;
+        return;
+    }
+
+    let changed = match game.update_character(&char_id, changes) {
+        Ok(changed) => changed,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    if changed.is_empty() {
+        return;
+    }
+
+    let character = &game.characters[&char_id];
+    broadcast_sheet(state, &char_id, character.to_data());
+    // The map and character lists follow names and colors through their own messages
+    if changed.contains(&"name") {
+        let _ = state.broadcaster.send(ServerMessage::TokenRenamed {
+            token_id: character_id.clone(),
+            name: character.name.clone(),
+        });
+    }
+    if changed.contains(&"color") {
+        let _ = state
+            .broadcaster
+            .send(ServerMessage::CharacterColorChanged {
+                character_id,
+                color: character.color.clone(),
+            });
+    }
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM deleting or retiring a character
+async fn handle_delete_character(state: &AppState, character_id: String, retire: bool) {
+    if let Err(e) = remove_character(state, &character_id, retire).await {
+        send_error(state, ErrorCode::Rejected, &e).await;
+    }
+}
+
+/// Take a character off the table for good, putting it in the vault first
+/// when retiring, and tell everyone. Returns the character's name.
+pub(crate) async fn remove_character(
+    state: &AppState,
+    character_id: &str,
+    retire: bool,
+) -> Result<String, String> {
+    let char_id = Uuid::parse_str(character_id).map_err(|_| "Invalid character ID".to_string())?;
+    let mut game = state.game.write().await;
+
+    // Only archive what can actually be removed, so the vault never holds
+    // a character that's still on the table
+    let character = game.removable_character(&char_id)?;
+    if retire {
+        state.vault.retire(character)?;
+    }
+    let character = game.delete_character(&char_id, retire)?;
+    println!(
+        "🗑️ Character {}: {} ({})",
+        if retire { "retired" } else { "deleted" },
+        character.name,
+        char_id
+    );
+    state.sheets.forget(&char_id.to_string());
+
+    let _ = state.broadcaster.send(ServerMessage::CharacterRemoved {
+        character_id: character_id.to_string(),
+        name: character.name.clone(),
+    });
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+    refresh_auras(state, &mut game).await;
+
+    Ok(character.name)
+}
+
+/// Bring an exported character into the game (the REST import route)
+pub(crate) async fn import_character(
+    state: &AppState,
+    export: &CharacterExport,
+) -> Result<game::Character, String> {
+    let mut game = state.game.write().await;
+    let character = export.import_into(&mut game)?;
+    println!(
+        "📥 Character imported: {} ({})",
+        character.name, character.id
+    );
+    let event = game.event_log.last().cloned();
+    drop(game);
+
+    if let Some(event) = event {
+        broadcast_event(state, &event).await;
+    }
+    let _ = state.broadcaster.send(ServerMessage::CharacterSpawned {
+        character_id: character.id.to_string(),
+        name: character.name.clone(),
+        position: character.position,
+        color: character.color.clone(),
+        is_npc: character.is_npc,
+        token: character.token.clone(),
+    });
+    broadcast_characters_list(state).await;
+
+    Ok(character)
+}
+
+/// Handle the GM setting the scene with an environment
+async fn handle_spawn_environment(state: &AppState, template: String) {
+    let mut game = state.game.write().await;
+
+    match game.spawn_environment(&template) {
+        Ok(environment) => {
+            let msg = ServerMessage::EnvironmentSpawned {
+                environment: environment.to_info(),
+            };
+            let _ = state.broadcaster.send(msg);
+
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(state, event).await;
+            }
+        }
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
+}
+
+/// Handle removing an environment
+async fn handle_remove_environment(state: &AppState, environment_id: String) {
+    let mut game = state.game.write().await;
+
+    if let Some(environment) = game.remove_environment(&environment_id) {
+        let msg = ServerMessage::EnvironmentRemoved {
+            environment_id,
+            name: environment.template.name,
+        };
+        let _ = state.broadcaster.send(msg);
+
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+}
+
+/// Handle the GM activating an environment feature
+async fn handle_activate_environment_feature(
+    state: &AppState,
+    environment_id: String,
+    feature: String,
+) {
+    let mut game = state.game.write().await;
+
+    let feature = match game.activate_environment_feature(&environment_id, &feature) {
+        Ok(feature) => feature,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let environment_name = game
+        .environments
+        .get(&environment_id)
+        .map(|e| e.template.name.clone())
+        .unwrap_or_default();
+    let msg = ServerMessage::EnvironmentFeatureActivated {
+        environment_id,
+        environment_name,
+        feature,
+        fear_pool: game.fear_pool,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle starting combat
+async fn handle_start_combat(state: &AppState) {
+    let mut game = state.game.write().await;
+    
+    let encounter_id = game.start_combat();
+    capture_keyframe(state, &mut game);
+
+    if let Some(encounter) = game.get_combat() {
+        let msg = ServerMessage::CombatStarted {
+            encounter_id,
+            pc_tokens: encounter.action_tracker.pc_tokens,
+            adversary_tokens: encounter.action_tracker.adversary_tokens,
+        };
+        let _ = state.broadcaster.send(msg);
+
+        // Broadcast event
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+}
+
+/// Handle the GM rolling the game back to a checkpoint
+async fn handle_rollback_to_checkpoint(state: &AppState, checkpoint_id: Option<String>) {
+    let mut game = state.game.write().await;
+
+    let checkpoint = match game.rollback_to_checkpoint(checkpoint_id.as_deref()) {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    // Too much changed to patch up; clients reload the whole state
+    let msg = ServerMessage::CheckpointRestored { checkpoint };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM setting (or, with `None`, cancelling) the next session
+async fn handle_schedule_next_session(state: &AppState, schedule: Option<(String, String)>) {
+    let mut game = state.game.write().await;
+
+    let result = match schedule {
+        Some((scheduled_at, notes)) => game.schedule_next_session(&scheduled_at, &notes).map(Some),
+        None => game.clear_next_session().map(|_| None),
+    };
+    let next_session = match result {
+        Ok(next_session) => next_session,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::NextSessionUpdated { next_session };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// What the GM is doing to a note
+enum NoteChange {
+    /// Write a new one, or rewrite the one named
+    Save(Option<String>, NoteDraft),
+    Delete(String),
+}
+
+/// Handle the GM writing, rewriting or deleting a note
+async fn handle_note_change(state: &AppState, change: NoteChange) {
+    let result = match change {
+        NoteChange::Save(note_id, draft) => save_note(state, note_id.as_deref(), &draft)
+            .await
+            .map(|_| ()),
+        NoteChange::Delete(note_id) => delete_note(state, &note_id).await.map(|_| ()),
+    };
+    if let Err(e) = result {
+        send_error(state, ErrorCode::Rejected, &e).await;
+    }
+}
+
+/// Write a new note, or rewrite the one `note_id` names, and tell everyone
+/// who can read it (players are told a note taken private is gone)
+pub(crate) async fn save_note(
+    state: &AppState,
+    note_id: Option<&str>,
+    draft: &NoteDraft,
+) -> Result<Note, String> {
+    let mut game = state.game.write().await;
+    let note = match note_id {
+        Some(note_id) => game.update_note(note_id, draft)?,
+        None => game.add_note(draft)?,
+    };
+    println!("📓 Note saved: {} ({:?})", note.title, note.visibility);
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::NoteSaved { note: note.clone() });
+    Ok(note)
+}
+
+/// Throw a note away and tell everyone
+pub(crate) async fn delete_note(state: &AppState, note_id: &str) -> Result<Note, String> {
+    let note = state.game.write().await.delete_note(note_id)?;
+    println!("📓 Note deleted: {}", note.title);
+    let _ = state.broadcaster.send(ServerMessage::NoteDeleted {
+        note_id: note.id.clone(),
+    });
+    Ok(note)
+}
+
+/// What the GM is doing to the quest board
+enum QuestChange {
+    /// Put a new quest up, or rewrite the one named
+    Save(Option<String>, QuestDraft),
+    Remove(String),
+    /// Quest, objective, whether it's done
+    Objective(String, String, bool),
+    /// Quest, countdown, ticks
+    Countdown(String, String, i8),
+}
+
+/// Handle the GM changing the quest board
+async fn handle_quest_change(state: &AppState, change: QuestChange) {
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    let removed = matches!(change, QuestChange::Remove(_));
+    let result = match change {
+        QuestChange::Save(None, draft) => game.add_quest(&draft),
+        QuestChange::Save(Some(quest_id), draft) => game.update_quest(&quest_id, &draft),
+        QuestChange::Remove(quest_id) => game.remove_quest(&quest_id),
+        QuestChange::Objective(quest_id, objective_id, completed) => {
+            game.set_objective_completed(&quest_id, &objective_id, completed)
+        }
+        QuestChange::Countdown(quest_id, countdown_id, ticks) => {
+            game.tick_quest_countdown(&quest_id, &countdown_id, ticks)
+        }
+    };
+    let quest = match result {
+        Ok(quest) => quest,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = if removed {
+        println!("📜 Quest removed: {}", quest.title);
+        ServerMessage::QuestRemoved {
+            quest_id: quest.id,
+            title: quest.title,
+        }
+    } else {
+        println!("📜 Quest updated: {}", quest.title);
+        ServerMessage::QuestUpdated { quest }
+    };
+    let _ = state.broadcaster.send(msg);
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM sharing a handout
+async fn handle_share_handout(
+    state: &AppState,
+    title: String,
+    kind: protocol::handouts::HandoutKind,
+    content: String,
+    character_ids: Vec<String>,
+) {
+    let checked = {
+        let game = state.game.read().await;
+        let unknown = character_ids
+            .iter()
+            .find(|id| !Uuid::parse_str(id).is_ok_and(|id| game.characters.contains_key(&id)));
+        match unknown {
+            Some(id) => Err(format!("Character not found: {}", id)),
+            None => Ok(()),
+        }
+    };
+
+    // Written outside the game lock; images can be a few megabytes
+    let handout =
+        match checked.and_then(|_| state.handouts.save(&title, kind, &content, character_ids)) {
+            Ok(handout) => handout,
+            Err(e) => {
+                send_error(state, ErrorCode::Rejected, &e).await;
+                return;
+            }
+        };
+
+    let mut game = state.game.write().await;
+
+    // Only handouts for everyone make the shared log
+    let public = handout.is_public();
+    if public {
+        game.add_event(
+            game::GameEventType::SystemMessage,
+            format!("📜 GM shared a handout: {}", handout.title),
+            None,
+            None,
+        );
+    }
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::HandoutShared { handout });
+
+    if public {
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+}
+
+/// Handle the GM putting an uploaded map on the table, or clearing it
+async fn handle_set_map(state: &AppState, map_id: Option<String>) {
+    // Read from disk before taking the game lock
+    let map = match map_id.map(|id| state.maps.get(&id)).transpose() {
+        Ok(map) => map,
+        Err(e) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    let modifiers_before = game.modifiers.len();
+    game.set_map(map.clone());
+
+    let _ = state.broadcaster.send(ServerMessage::MapChanged { map });
+    let _ = state.broadcaster.send(ServerMessage::SceneLayoutChanged {
+        layout: game.layout,
+    });
+    broadcast_fog(state, &game);
+    let _ = state.broadcaster.send(ServerMessage::DrawingsChanged {
+        drawings: game.current_drawings().to_vec(),
+    });
+    // A new scene ends the modifiers that lasted the scene
+    if game.modifiers.len() != modifiers_before {
+        broadcast_modifiers(state, &game);
+    }
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM turning fog of war on or off, or revealing or hiding part
+/// of the scene
+async fn handle_fog_change(
+    state: &AppState,
+    change: impl FnOnce(&mut GameState) -> Result<FogState, String>,
+) {
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    if let Err(e) = change(&mut game) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_fog(state, &game);
+    if game.event_log.len() != events_before {
+        if let Some(event) = game.event_log.last() {
+            broadcast_event(state, event).await;
+        }
+    }
+}
+
+/// Handle someone pinging the map
+async fn handle_ping(state: &AppState, conn_id: &Uuid, position: protocol::Position) {
+    let mut game = state.game.write().await;
+    let (name, color) = match game.ping(conn_id, &position) {
+        Ok(pinger) => pinger,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    let _ = state.broadcaster.send(ServerMessage::Pinged {
+        position,
+        name,
+        color,
+    });
+}
+
+/// Handle the GM drawing on the scene or erasing drawings
+async fn handle_drawing_change(
+    state: &AppState,
+    change: impl FnOnce(&mut GameState) -> Result<Vec<Drawing>, String>,
+) {
+    let mut game = state.game.write().await;
+    match change(&mut game) {
+        Ok(drawings) => {
+            let _ = state
+                .broadcaster
+                .send(ServerMessage::DrawingsChanged { drawings });
+        }
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
+}
+
+/// Send the current scene's fog, then the tokens in view
+///
+/// Players' copies of the adversary list and NPC spawns are cut down to what
+/// they can see, so tokens a reveal uncovered appear for them.
+pub(crate) fn broadcast_fog(state: &AppState, game: &GameState) {
+    let _ = state.broadcaster.send(ServerMessage::FogChanged {
+        fog: game.current_fog(),
+    });
+    let _ = state.broadcaster.send(ServerMessage::AdversariesList {
+        adversaries: build_adversaries_list(game),
+    });
+    if !game.adversary_groups.is_empty() {
+        broadcast_adversary_groups(state, game);
+    }
+    for npc in game.get_npcs() {
+        let _ = state.broadcaster.send(ServerMessage::CharacterSpawned {
+            character_id: npc.id.to_string(),
+            name: npc.name.clone(),
+            position: npc.position,
+            color: npc.color.clone(),
+            is_npc: true,
+            token: npc.token.clone(),
+        });
+    }
+}
+
+/// After a token moves, tell players if it went under the fog or came out of it
+fn announce_fog_crossing(state: &AppState, game: &GameState, token_id: &str, was_fogged: bool) {
+    let is_fogged = game.fogged_tokens().contains(token_id);
+    if is_fogged && !was_fogged {
+        let _ = state.broadcaster.send(ServerMessage::TokenHidden {
+            token_id: token_id.to_string(),
+        });
+    } else if was_fogged && !is_fogged {
+        let character = Uuid::parse_str(token_id)
+            .ok()
+            .and_then(|id| game.get_character(&id));
+        if let Some(character) = character {
+            let _ = state.broadcaster.send(ServerMessage::CharacterSpawned {
+                character_id: token_id.to_string(),
+                name: character.name.clone(),
+                position: character.position,
+                color: character.color.clone(),
+                is_npc: character.is_npc,
+                token: character.token.clone(),
+            });
+        } else if let Some(adversary) = game.adversaries.get(token_id) {
+            let _ = state.broadcaster.send(adversary_spawned(adversary));
+        }
+    }
+}
+
+/// Handle the GM changing the scene's size and grid
+///
+/// With a map on the table the layout is saved with the map, so it comes
+/// back whenever that map is shown.
+async fn handle_set_scene_layout(state: &AppState, layout: SceneLayout) {
+    let mut game = state.game.write().await;
+    if let Err(e) = game.set_layout(layout) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::SceneLayoutChanged { layout });
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    let map_id = game.map.as_ref().map(|m| m.id.clone());
+    drop(game);
+    if let Some(map_id) = map_id {
+        if let Err(e) = state.maps.set_layout(&map_id, layout) {
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
+}
+
+/// Handle the GM cutting away to some of the characters
+async fn handle_start_cutaway(state: &AppState, character_ids: Vec<String>, label: String) {
+    let mut game = state.game.write().await;
+
+    let cutaway = match game.start_cutaway(&character_ids, &label) {
+        Ok(cutaway) => cutaway,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::CutawayStarted { cutaway });
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM returning from the cutaway
+async fn handle_end_cutaway(state: &AppState) {
+    let mut game = state.game.write().await;
+
+    let (cutaway, events) = match game.end_cutaway() {
+        Ok(ended) => ended,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    // Everyone else missed the scene's moves; put its tokens where they are now
+    for id in &cutaway.character_ids {
+        let Some(character) = Uuid::parse_str(id)
+            .ok()
+            .and_then(|id| game.characters.get(&id))
+        else {
+            continue;
+        };
+        let msg = ServerMessage::CharacterMoved {
+            character_id: id.clone(),
+            position: character.position,
+            path: Vec::new(),
+            teleported: true,
+            crossed_hazards: Vec::new(),
+            rotation: character.token.rotation,
+        };
+        broadcast_move(state, msg);
+    }
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::CutawayEnded { cutaway, events });
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle ending combat
+async fn handle_end_combat(state: &AppState) {
+    let mut game = state.game.write().await;
+
+    // Final positions, so the scrubber ends where the fight did
+    capture_keyframe(state, &mut game);
+    let events_before = game.event_log.len();
+    let modifiers_before = game.modifiers.len();
+    game.end_combat("manual");
+    
+    let msg = ServerMessage::CombatEnded {
+        reason: "manual".to_string(),
+    };
+    let _ = state.broadcaster.send(msg);
+    if game.modifiers.len() != modifiers_before {
+        broadcast_modifiers(state, &game);
+    }
+
+    // Broadcast events
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Record a position keyframe for the active combat, journaling it if enabled
+pub fn capture_keyframe(state: &AppState, game: &mut GameState) {
+    let Some((encounter_id, keyframe)) = game.capture_keyframe() else {
+        return;
+    };
+    if let Some(journal) = &state.journal {
+        journal.record(JournalRecord::Keyframe {
+            encounter_id,
+            keyframe,
+        });
+    }
+}
+
+/// Handle the GM choosing tracker or spotlight flow for the encounter
+async fn handle_set_combat_mode(state: &AppState, mode: protocol::CombatMode) {
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.set_combat_mode(mode) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    let msg = ServerMessage::CombatModeChanged { mode };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the spotlight being passed
+async fn handle_pass_spotlight(state: &AppState, to: Option<String>) {
+    let mut game = state.game.write().await;
+
+    let (previous_id, holder_name) = match game.pass_spotlight(to.as_deref()) {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let tactics = to
+        .as_deref()
+        .and_then(|id| game.adversaries.get(id))
+        .and_then(|adversary| adversary.tactics());
+
+    let msg = ServerMessage::SpotlightChanged {
+        holder_id: to,
+        holder_name,
+        previous_id,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(tactics) = tactics {
+        let _ = state.broadcaster.send(ServerMessage::TacticsReminder {
+            adversaries: vec![tactics],
+        });
+    }
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle adding a tracker token
+async fn handle_add_tracker_token(state: &AppState, token_type: String) {
+    let mut game = state.game.write().await;
+    
+    if let Some(encounter) = game.get_combat_mut() {
+        match token_type.as_str() {
+            "pc" => encounter.action_tracker.add_pc_token(),
+            "adversary" => encounter.action_tracker.add_adversary_token(),
+            _ => {
+                send_error(
+                    state,
+                    ErrorCode::Rejected,
+                    &format!("Invalid token type: {}", token_type),
+                )
+                .await;
+                return;
+            }
+        }
+
+        let _ = state.broadcaster.send(tracker_updated(encounter));
+    }
+}
+
+/// Handle the GM spending a Fear on an adversary token or an interruption
+async fn handle_spend_fear(state: &AppState, spend: protocol::FearSpend) {
+    let mut game = state.game.write().await;
+    let previous_id = game.get_combat().and_then(|e| e.spotlight.clone());
+    let message = match game.spend_fear(&spend) {
+        Ok(message) => message,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!("😈 {}", message);
+
+    let _ = state.broadcaster.send(ServerMessage::FearSpent {
+        spend: spend.clone(),
+        message,
+        fear_pool: game.fear_pool,
+    });
+
+    let interrupter = match &spend {
+        protocol::FearSpend::Interrupt { adversary_id } => Some(adversary_id.as_str()),
+        protocol::FearSpend::AdversaryToken => None,
+    };
+    broadcast_turn_order(state, &game, previous_id, interrupter);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM marking an adversary's Stress, maybe for one of its moves
+async fn handle_mark_adversary_stress(
+    state: &AppState,
+    adversary_id: String,
+    stress: u8,
+    stress_move: Option<protocol::StressMove>,
+) {
+    let mut game = state.game.write().await;
+    let previous_id = game.get_combat().and_then(|e| e.spotlight.clone());
+    let message = match game.mark_adversary_stress(&adversary_id, stress, stress_move.as_ref()) {
+        Ok(message) => message,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!("💢 {}", message);
+
+    let adversary = &game.adversaries[&adversary_id];
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::AdversaryStressMarked {
+            adversary_id: adversary_id.clone(),
+            name: adversary.name.clone(),
+            stress: adversary.stress,
+            max_stress: adversary.max_stress,
+            stress_move: stress_move.clone(),
+            message,
+        });
+    let _ = state.broadcaster.send(ServerMessage::AdversaryUpdated {
+        adversary_id: adversary_id.clone(),
+        hp: adversary.hp,
+        stress: adversary.stress,
+        is_active: adversary.is_active,
+    });
+    if stress_move == Some(protocol::StressMove::ActAgain) {
+        broadcast_turn_order(state, &game, previous_id, Some(&adversary_id));
+    }
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// After an adversary was put next in line (or an adversary token added):
+/// the tracker or the spotlight as it now stands, and the adversary's
+/// tactics for the GM
+fn broadcast_turn_order(
+    state: &AppState,
+    game: &GameState,
+    previous_id: Option<String>,
+    adversary_id: Option<&str>,
+) {
+    if let Some(encounter) = game.get_combat() {
+        let update = match encounter.mode {
+            protocol::CombatMode::ActionTracker => tracker_updated(encounter),
+            protocol::CombatMode::Spotlight => ServerMessage::SpotlightChanged {
+                holder_id: encounter.spotlight.clone(),
+                holder_name: game
+                    .combatant_name(encounter.spotlight.as_deref().unwrap_or_default())
+                    .unwrap_or_else(|| "GM".to_string()),
+                previous_id,
+            },
+        };
+        let _ = state.broadcaster.send(update);
+    }
+
+    if let Some(tactics) = adversary_id
+        .and_then(|id| game.adversaries.get(id))
+        .and_then(|a| a.tactics())
+    {
+        let _ = state.broadcaster.send(ServerMessage::TacticsReminder {
+            adversaries: vec![tactics],
+        });
+    }
+}
+
+/// Build a `TrackerUpdated` message for an encounter
+fn tracker_updated(encounter: &game::CombatEncounter) -> ServerMessage {
+    let tracker = &encounter.action_tracker;
+    ServerMessage::TrackerUpdated {
+        pc_tokens: tracker.pc_tokens,
+        adversary_tokens: tracker.adversary_tokens,
+        next_token: tracker
+            .get_next()
+            .map(|t| t.name().to_string())
+            .unwrap_or_else(|| "none".to_string()),
+        queue: tracker.queue.iter().map(|t| t.name().to_string()).collect(),
+        round: encounter.round,
+    }
+}
+
+/// Handle the GM spending the next tracker token
+async fn handle_spend_tracker_token(state: &AppState) {
+    let mut game = state.game.write().await;
+
+    let spend = match game.spend_tracker_token() {
+        Ok(spend) => spend,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Some(encounter) = game.get_combat() {
+        let _ = state.broadcaster.send(tracker_updated(encounter));
+    }
+    let adversaries = game.tactics_for_next_token();
+    if !adversaries.is_empty() {
+        let _ = state
+            .broadcaster
+            .send(ServerMessage::TacticsReminder { adversaries });
+    }
+
+    // The spend event, then "Round N" and any hazard events
+    let mut new_events = 1;
+    let mut triggers = Vec::new();
+    if let Some((round, round_triggers)) = spend.round_advanced {
+        let msg = ServerMessage::RoundAdvanced { round };
+        let _ = state.broadcaster.send(msg);
+        new_events += 1 + round_triggers.len();
+        triggers = round_triggers;
+    }
+
+    let skip = game.event_log.len().saturating_sub(new_events);
+    for event in game.event_log.iter().skip(skip) {
+        broadcast_event(state, event).await;
+    }
+
+    for trigger in triggers {
+        resolve_hazard_trigger(state, &mut game, trigger).await;
+    }
+}
+
+/// Handle the GM advancing the tracker after a roll
+async fn handle_advance_tracker(state: &AppState, success_with_hope: bool) {
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.advance_tracker(success_with_hope) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    if let Some(encounter) = game.get_combat() {
+        let _ = state.broadcaster.send(tracker_updated(encounter));
+    }
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle a range query between two tokens
+async fn handle_measure_range(state: &AppState, conn_id: &Uuid, from_id: String, to_id: String) {
+    let game = state.game.read().await;
+    let measured = game.measure_range(&from_id, &to_id);
+    drop(game);
+
+    match measured {
+        Ok((feet, band)) => {
+            let _ = state.broadcaster.send(ServerMessage::RangeMeasured {
+                connection_id: conn_id.to_string(),
+                from_id,
+                to_id,
+                feet,
+                band,
+            });
+        }
+        Err(e) => send_error(state, ErrorCode::Rejected, &e).await,
+    }
+}
+
+/// Handle attack roll
+async fn handle_attack(
+    state: &AppState,
+    attacker_id: String,
+    target_id: String,
+    modifier: i8,
+    with_advantage: bool,
+    with_disadvantage: bool,
+) {
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.check_weapon_range(&attacker_id, &target_id) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    // Get attacker and target names
+    let attacker_name = game
+        .combatant_name(&attacker_id)
+        .unwrap_or_else(|| "Unknown".to_string());
+    let target_name = game
+        .combatant_name(&target_id)
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    // Aura bonuses and modifiers apply to both the target's Evasion and the
+    // attack roll
+    let target_evasion = game
+        .effective_evasion(&target_id)
+        .map(|evasion| evasion.clamp(0, u8::MAX as i32) as u8)
+        .unwrap_or(10);
+
+    // Equipped weapon supplies the trait modifier for character attacks
+    let trait_modifier = game
+        .characters
+        .values()
+        .find(|c| c.id.to_string() == attacker_id)
+        .map(|c| c.weapon_trait_modifier())
+        .unwrap_or(0);
+    let modifier = modifier
+        .saturating_add(trait_modifier)
+        .saturating_add(game.attack_bonus(&attacker_id));
+
+    // Roll attack
+    let (hope_die, fear_die) = game.dice.duality();
+    let (advantage_die, disadvantage_die) =
+        game::roll_advantage_dice(&game.dice, with_advantage, with_disadvantage);
+    if let Ok(character_id) = Uuid::parse_str(&attacker_id) {
+        game.record_duality(&character_id, hope_die, fear_die);
+    }
+
+    let hope = hope_die as u16;
+    let fear = fear_die as u16;
+    let total = (hope as i32 + fear as i32 + modifier as i32 + advantage_die.unwrap_or(0) as i32
+        - disadvantage_die.unwrap_or(0) as i32)
+        .max(0) as u16;
+    let hit = total >= target_evasion as u16;
+    let is_critical = hope_die == fear_die;
+    game.record_attack(&attacker_id, &target_id, hit && is_critical);
+
+    let beat = match (hit, is_critical) {
+        (true, true) => CombatBeat::Critical,
+        (true, false) => CombatBeat::Hit,
+        (false, _) => CombatBeat::Miss,
+    };
+    let narration = game.narrate(beat, &attacker_name, &target_name, None);
+
+    // Broadcast attack result
+    let msg = ServerMessage::AttackResult {
+        attacker_id: attacker_id.clone(),
+        attacker_name: attacker_name.clone(),
+        target_id: target_id.clone(),
+        target_name: target_name.clone(),
+        hope,
+        fear,
+        modifier,
+        advantage_die,
+        disadvantage_die,
+        total,
+        target_evasion,
+        hit,
+        controlling_die: game::duality_controlling_die(hope_die, fear_die),
+        is_critical,
+        narration: narration.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    // Log event
+    let outcome = match beat {
+        CombatBeat::Critical => "critical hit",
+        CombatBeat::Hit => "hit",
+        _ => "miss",
+    };
+    let details = format!("{} vs Evasion {}", total, target_evasion);
+    game.add_event(
+        game::GameEventType::CombatAction,
+        narration
+            .unwrap_or_else(|| format!("{} attacks {}: {}", attacker_name, target_name, outcome)),
+        Some(attacker_name),
+        Some(details),
+    );
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle an adversary attacking a character
+///
+/// Rolls the adversary's d20 against the character's Evasion and, on a hit,
+/// rolls and applies the adversary's damage.
+async fn handle_adversary_attack(
+    state: &AppState,
+    adversary_id: String,
+    target_id: String,
+    with_advantage: bool,
+    with_disadvantage: bool,
+) {
+    let mut game = state.game.write().await;
+
+    let attack = Uuid::parse_str(&target_id)
+        .map_err(|_| format!("Invalid character ID: {}", target_id))
+        .and_then(|char_id| {
+            game.adversary_attack(&adversary_id, &char_id, with_advantage, with_disadvantage)
+        });
+    let attack = match attack {
+        Ok(attack) => attack,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let beat = match (attack.hit, attack.is_critical) {
+        (true, true) => CombatBeat::Critical,
+        (true, false) => CombatBeat::Hit,
+        (false, _) => CombatBeat::Miss,
+    };
+    let narration = game.narrate(beat, &attack.adversary_name, &attack.target_name, None);
+
+    let msg = ServerMessage::AdversaryAttackResult {
+        adversary_id: adversary_id.clone(),
+        adversary_name: attack.adversary_name.clone(),
+        target_id: target_id.clone(),
+        target_name: attack.target_name.clone(),
+        rolls: attack.rolls.clone(),
+        die: attack.die,
+        modifier: attack.modifier,
+        total: attack.total,
+        target_evasion: attack.target_evasion,
+        hit: attack.hit,
+        is_critical: attack.is_critical,
+        narration: narration.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    let outcome = match beat {
+        CombatBeat::Critical => "critical hit",
+        CombatBeat::Hit => "hit",
+        _ => "miss",
+    };
+    game.add_event(
+        game::GameEventType::CombatAction,
+        narration.unwrap_or_else(|| {
+            format!(
+                "{} attacks {}: {}",
+                attack.adversary_name, attack.target_name, outcome
+            )
+        }),
+        Some(attack.adversary_name.clone()),
+        Some(format!(
+            "d20 {} {:+} = {} vs Evasion {}",
+            attack.die, attack.modifier, attack.total, attack.target_evasion
+        )),
+    );
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    if attack.hit {
+        let roll = DamageRoll {
+            attacker_id: &adversary_id,
+            target_id: &target_id,
+            damage_dice: &attack.damage_dice,
+            armor: None,
+            damage_type: Some(attack.damage_type),
+            is_critical: attack.is_critical,
+        };
+        deal_damage(state, &mut game, roll).await;
+    }
+}
+
+/// Handle damage roll; it's a critical hit if the attacker's last attack on
+/// the target was
+async fn handle_roll_damage(
+    state: &AppState,
+    attacker_id: String,
+    target_id: String,
+    damage_dice: Option<String>,
+    armor: Option<u8>,
+    damage_type: Option<DamageType>,
+) {
+    let mut game = state.game.write().await;
+
+    // Fall back to the attacker's equipped weapon
+    let damage_dice = match damage_dice.or_else(|| {
+        game.characters
+            .values()
+            .find(|c| c.id.to_string() == attacker_id)
+            .and_then(|c| c.weapon_damage_dice())
+    }) {
+        Some(dice) => dice,
+        None => {
+            drop(game);
+            send_error(
+                state,
+                ErrorCode::Rejected,
+                "No damage dice given and attacker has no weapon equipped",
+            )
+            .await;
+            return;
+        }
+    };
+    let is_critical = game.take_critical_hit(&attacker_id, &target_id);
+
+    let roll = DamageRoll {
+        attacker_id: &attacker_id,
+        target_id: &target_id,
+        damage_dice: &damage_dice,
+        armor,
+        damage_type,
+        is_critical,
+    };
+    deal_damage(state, &mut game, roll).await;
+}
+
+/// A damage roll against one target; armor and damage type fall back to the
+/// target's and the attacker's when not given
+struct DamageRoll<'a> {
+    attacker_id: &'a str,
+    target_id: &'a str,
+    damage_dice: &'a str,
+    armor: Option<u8>,
+    damage_type: Option<DamageType>,
+    is_critical: bool,
+}
+
+/// Roll damage against a target, apply it and announce the result
+async fn deal_damage(state: &AppState, game: &mut GameState, roll: DamageRoll<'_>) {
+    let DamageRoll {
+        attacker_id,
+        target_id,
+        damage_dice,
+        armor,
+        damage_type,
+        is_critical,
+    } = roll;
+
+    // Fall back to the target's armor
+    let armor = armor.unwrap_or_else(|| game.armor_of(target_id));
+
+    let damage = match game.roll_damage(attacker_id, damage_dice, is_critical) {
+        Ok(damage) => damage,
+        Err(e) => {
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    // Apply resistances, then armor and thresholds
+    let damage_type = damage_type.unwrap_or_else(|| game.attack_damage_type(attacker_id));
+    let defenses = game.defenses_of(target_id);
+    let damage_result = game::resolve_damage(damage.raw_damage, armor, damage_type, &defenses);
+
+    // Apply damage to target
+    let applied = game.apply_damage(target_id, &damage_result);
+    let (target_name, new_hp, new_stress, taken_out) = match applied {
+        Some(a) => (a.target_name, a.new_hp, a.new_stress, a.taken_out),
+        None => ("Unknown".to_string(), 0, 0, false),
+    };
+
+    let narration = if taken_out {
+        let attacker_name = game
+            .combatant_name(attacker_id)
+            .unwrap_or_else(|| "Unknown".to_string());
+        game.narrate(
+            CombatBeat::TakenOut,
+            &attacker_name,
+            &target_name,
+            Some(damage_result.after_armor),
+        )
+    } else {
+        None
+    };
+
+    // Broadcast damage result
+    let msg = ServerMessage::DamageResult {
+        target_id: target_id.to_string(),
+        target_name: target_name.clone(),
+        raw_damage: damage_result.raw_damage,
+        is_critical,
+        critical_bonus: damage.critical_bonus,
+        dice: damage.roll.dice,
+        damage_type: damage_result.damage_type.clone(),
+        adjustment: damage_result.adjustment,
+        adjusted_damage: damage_result.adjusted_damage,
+        after_armor: damage_result.after_armor,
+        hp_lost: damage_result.hp_lost,
+        stress_gained: damage_result.stress_gained,
+        new_hp,
+        new_stress,
+        taken_out,
+        narration: narration.clone(),
+    };
+    let _ = state.broadcaster.send(msg);
+
+    // Characters' sheets show the marked HP and Stress
+    if let Ok(char_id) = Uuid::parse_str(target_id) {
+        if game.characters.contains_key(&char_id) {
+            broadcast_characters_updated(state, game, &[char_id]).await;
+        }
+    }
+
+    // Log event
+    game.add_event(
+        game::GameEventType::CombatAction,
+        format!(
+            "{} took {} damage{} ({} HP, {} Stress)",
+            target_name,
+            damage_result.after_armor,
+            if is_critical {
+                " from a critical hit"
+            } else {
+                ""
+            },
+            damage_result.hp_lost,
+            damage_result.stress_gained
+        ),
+        Some(target_name),
+        match (taken_out, damage_result.adjustment) {
+            (true, _) => Some(narration.unwrap_or_else(|| "Taken out!".to_string())),
+            (false, DamageAdjustment::Halved) => Some("Resisted (halved)".to_string()),
+            (false, DamageAdjustment::Doubled) => Some("Vulnerable (doubled)".to_string()),
+            (false, DamageAdjustment::Immune) => Some("Immune".to_string()),
+            (false, DamageAdjustment::None) => None,
+        },
+    );
+    
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    if taken_out {
+        refresh_auras(state, game).await;
+    }
+    refresh_adversary_groups(state, game).await;
+}
+
+/// Handle area damage: one roll dealt to everyone caught, announced together
+async fn handle_apply_damage_to_area(
+    state: &AppState,
+    source: String,
+    area: Option<HazardArea>,
+    target_ids: Vec<String>,
+    damage_dice: String,
+    damage_type: Option<DamageType>,
+) {
+    let mut game = state.game.write().await;
+
+    let result = match game.apply_area_damage(
+        &source,
+        area.as_ref(),
+        &target_ids,
+        &damage_dice,
+        damage_type.unwrap_or_default(),
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!(
+        "💥 {} rolled {} against {} targets",
+        source,
+        result.roll.total,
+        result.targets.len()
+    );
+
+    let char_ids: Vec<Uuid> = result
+        .targets
+        .iter()
+        .filter_map(|t| Uuid::parse_str(&t.target_id).ok())
+        .collect();
+    let anyone_taken_out = result.targets.iter().any(|t| t.taken_out);
+    let _ = state.broadcaster.send(ServerMessage::AreaDamageResult {
+        source,
+        raw_damage: result.roll.total,
+        dice: result.roll.dice,
+        damage_type: result.damage_type,
+        targets: result.targets,
+    });
+
+    // Sheets of the characters hit, then the event
+    broadcast_characters_updated(state, &game, &char_ids).await;
+    if anyone_taken_out {
+        refresh_auras(state, &mut game).await;
+    }
+    refresh_adversary_groups(state, &mut game).await;
+}
+
+/// Handle the GM setting a character's or adversary's defenses
+async fn handle_set_defenses(state: &AppState, target_id: String, defenses: Defenses) {
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.set_defenses(&target_id, defenses) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    match Uuid::parse_str(&target_id) {
+        Ok(char_id) if game.characters.contains_key(&char_id) => {
+            broadcast_characters_updated(state, &game, &[char_id]).await;
+        }
+        _ => {
+            let msg = ServerMessage::AdversariesList {
+                adversaries: build_adversaries_list(&game),
+            };
+            let _ = state.broadcaster.send(msg);
+
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(state, event).await;
+            }
+        }
+    }
+}
+
+/// Handle the GM changing house rules and table options
+async fn handle_update_table_settings(
+    state: &AppState,
+    patch: protocol::settings::TableSettingsPatch,
+) {
+    let mut game = state.game.write().await;
+    let settings = match game.update_settings(&patch) {
+        Ok(settings) => settings,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::SettingsUpdated { settings });
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+// ===== Hazard Handlers =====
+
+/// Handle the GM advancing the combat round
+async fn handle_advance_round(state: &AppState) {
+    let mut game = state.game.write().await;
+
+    let (round, triggers) = match game.advance_round() {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::RoundAdvanced { round };
+    let _ = state.broadcaster.send(msg);
+
+    // "Round N" comes before any hazard events
+    let skip = game.event_log.len().saturating_sub(triggers.len() + 1);
+    for event in game.event_log.iter().skip(skip) {
+        broadcast_event(state, event).await;
+    }
+
+    for trigger in triggers {
+        resolve_hazard_trigger(state, &mut game, trigger).await;
+    }
+}
+
+/// Handle the GM closing out a round: a fresh tracker, the round that
+/// ended, then "Round N" and any hazards it set off
+async fn handle_end_round(state: &AppState) {
+    let mut game = state.game.write().await;
+    let events_before = game.event_log.len();
+    let (previous, round, triggers) = match game.end_round() {
+        Ok(result) => result,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+    println!("🔔 Round {} ends, round {} begins", previous.round, round);
+
+    if let Some(encounter) = game.get_combat() {
+        let _ = state.broadcaster.send(tracker_updated(encounter));
+    }
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::RoundStarted { round, previous });
+
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+    for trigger in triggers {
+        resolve_hazard_trigger(state, &mut game, trigger).await;
+    }
+}
+
+/// Handle the GM placing a hazard on the scene
+async fn handle_add_hazard(state: &AppState, hazard: Hazard) {
+    let mut game = state.game.write().await;
+
+    let hazard_id = match game.add_hazard(hazard.clone()) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::HazardAdded {
+        hazard_id,
+        active: true,
+        ticks_remaining: hazard.countdown_length(),
+        hazard,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM removing a hazard
+async fn handle_remove_hazard(state: &AppState, hazard_id: String) {
+    let mut game = state.game.write().await;
+
+    match game.remove_hazard(&hazard_id) {
+        Ok(removed) => {
+            let msg = ServerMessage::HazardRemoved {
+                hazard_id,
+                name: removed.hazard.name,
+            };
+            let _ = state.broadcaster.send(msg);
+
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(state, event).await;
+            }
+        }
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
+}
+
+/// Handle the GM switching a hazard on or off
+async fn handle_set_hazard_active(state: &AppState, hazard_id: String, active: bool) {
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.set_hazard_active(&hazard_id, active) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
 
-        let completed: Vec<String> = req
-            .completed_by
-            .iter()
-            .filter_map(|id| game.characters.get(id).map(|c| c.name.clone()))
-            .collect();
+    broadcast_hazard_updated(state, &game, &hazard_id);
 
-        let status_msg = protocol::ServerMessage::RollRequestStatus {
-            request_id,
-            pending_characters: pending,
-            completed_characters: completed,
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle the GM ticking a hazard's countdown
+async fn handle_tick_hazard(state: &AppState, hazard_id: String) {
+    let mut game = state.game.write().await;
+
+    match game.tick_hazard(&hazard_id) {
+        Ok(Some(trigger)) => {
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(state, event).await;
+            }
+            resolve_hazard_trigger(state, &mut game, trigger).await;
+        }
+        Ok(None) => broadcast_hazard_updated(state, &game, &hazard_id),
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+        }
+    }
+}
+
+fn broadcast_hazard_updated(state: &AppState, game: &GameState, hazard_id: &str) {
+    if let Some(scene) = game.hazards.get(hazard_id) {
+        let msg = ServerMessage::HazardUpdated {
+            hazard_id: scene.id.clone(),
+            active: scene.active,
+            ticks_remaining: scene.ticks_remaining,
         };
+        let _ = state.broadcaster.send(msg);
+    }
+}
+
+/// Carry out a triggered hazard: request rolls or deal damage in its area
+async fn resolve_hazard_trigger(
+    state: &AppState,
+    game: &mut GameState,
+    trigger: game::HazardTrigger,
+) {
+    let msg = ServerMessage::HazardTriggered {
+        hazard_id: trigger.hazard_id.clone(),
+        name: trigger.name.clone(),
+        affected: trigger
+            .targets
+            .iter()
+            .map(|(_, name)| name.clone())
+            .collect(),
+    };
+    let _ = state.broadcaster.send(msg);
+    broadcast_hazard_updated(state, game, &trigger.hazard_id);
+
+    match &trigger.outcome {
+        HazardOutcome::Roll { .. } => {
+            if let Some(request) = game.hazard_roll_request(&trigger) {
+                send_roll_request(state, game, &request).await;
+            }
+        }
+        HazardOutcome::Damage { dice, damage_type } => {
+            let mut anyone_taken_out = false;
+
+            for (target_id, _) in &trigger.targets {
+                // Checked when the hazard was placed
+                let Ok(roll) = parse_and_roll_dice(&game.dice, dice) else {
+                    continue;
+                };
+                let raw_damage = roll.total;
+                let armor = game.armor_of(target_id);
+                let defenses = game.defenses_of(target_id);
+                let damage_result =
+                    game::resolve_damage(raw_damage, armor, damage_type.clone(), &defenses);
+
+                let Some(applied) = game.apply_damage(target_id, &damage_result) else {
+                    continue;
+                };
+                anyone_taken_out |= applied.taken_out;
+
+                let narration = applied
+                    .taken_out
+                    .then(|| {
+                        game.narrate(
+                            CombatBeat::TakenOut,
+                            &trigger.name,
+                            &applied.target_name,
+                            Some(damage_result.after_armor),
+                        )
+                    })
+                    .flatten();
+
+                let msg = ServerMessage::DamageResult {
+                    target_id: target_id.clone(),
+                    target_name: applied.target_name.clone(),
+                    raw_damage: damage_result.raw_damage,
+                    is_critical: false,
+                    critical_bonus: 0,
+                    dice: roll.dice,
+                    damage_type: damage_result.damage_type.clone(),
+                    adjustment: damage_result.adjustment,
+                    adjusted_damage: damage_result.adjusted_damage,
+                    after_armor: damage_result.after_armor,
+                    hp_lost: damage_result.hp_lost,
+                    stress_gained: damage_result.stress_gained,
+                    new_hp: applied.new_hp,
+                    new_stress: applied.new_stress,
+                    taken_out: applied.taken_out,
+                    narration: narration.clone(),
+                };
+                let _ = state.broadcaster.send(msg);
+
+                if let Some(character) = Uuid::parse_str(target_id)
+                    .ok()
+                    .and_then(|id| game.get_character(&id))
+                {
+                    broadcast_sheet(state, &character.id, character.to_data());
+                }
+
+                game.add_event(
+                    game::GameEventType::CombatAction,
+                    format!(
+                        "{} took {} damage from {}",
+                        applied.target_name, damage_result.after_armor, trigger.name
+                    ),
+                    Some(applied.target_name),
+                    applied
+                        .taken_out
+                        .then(|| narration.unwrap_or_else(|| "Taken out!".to_string())),
+                );
+                if let Some(event) = game.event_log.last() {
+                    broadcast_event(state, event).await;
+                }
+            }
 
-        state.broadcaster.send(status_msg.to_json()).ok();
+            // Taken-out adversaries drop out of auras
+            if anyone_taken_out {
+                refresh_auras(state, game).await;
+            }
+            refresh_adversary_groups(state, game).await;
+        }
     }
+}
 
-    // Broadcast updated character data
-    if let Some(character) = game.characters.get(&char_id).cloned() {
-        let msg = protocol::ServerMessage::CharacterUpdated {
-            character_id: char_id.to_string(),
-            character: character.to_data(),
+// ===== Aura Handlers =====
+
+/// Handle the GM attaching an aura to a character or adversary
+async fn handle_attach_aura(state: &AppState, owner_id: String, aura: Aura) {
+    let mut game = state.game.write().await;
+
+    let aura_id = match game.attach_aura(&owner_id, aura.clone()) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::AuraAttached {
+        aura_id,
+        owner_id,
+        aura,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+
+    refresh_auras(state, &mut game).await;
+}
+
+/// Handle the GM removing an aura
+async fn handle_detach_aura(state: &AppState, aura_id: String) {
+    let mut game = state.game.write().await;
+
+    let detached = match game.detach_aura(&aura_id) {
+        Ok(detached) => detached,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    let msg = ServerMessage::AuraDetached {
+        aura_id,
+        name: detached.aura.name,
+    };
+    let _ = state.broadcaster.send(msg);
+
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Whether a connection may put modifiers on (or take them off) a token:
+/// the GM anything, players their own characters
+fn can_modify(game: &GameState, conn_id: &Uuid, target_id: &str) -> bool {
+    game.connections
+        .get(conn_id)
+        .is_some_and(|c| c.role == ConnectionRole::Gm)
+        || Uuid::parse_str(target_id).is_ok_and(|id| game.controls(conn_id, &id))
+}
+
+/// Handle a buff or debuff going on a character or adversary
+async fn handle_add_modifier(
+    state: &AppState,
+    conn_id: &Uuid,
+    target_id: String,
+    modifier: protocol::modifiers::Modifier,
+) {
+    let mut game = state.game.write().await;
+    if !can_modify(&game, conn_id, &target_id) {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "You can only modify your own characters",
+        )
+        .await;
+        return;
+    }
+    if let Err(e) = game.add_modifier(&target_id, modifier) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_modifiers(state, &game);
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Handle a buff or debuff coming off before it runs out
+async fn handle_remove_modifier(state: &AppState, conn_id: &Uuid, modifier_id: String) {
+    let mut game = state.game.write().await;
+    let Some(target_id) = game
+        .modifiers
+        .get(&modifier_id)
+        .map(|m| m.target_id.clone())
+    else {
+        drop(game);
+        send_error(state, ErrorCode::NotFound, "Modifier not found").await;
+        return;
+    };
+    if !can_modify(&game, conn_id, &target_id) {
+        drop(game);
+        send_error(
+            state,
+            ErrorCode::Forbidden,
+            "You can only modify your own characters",
+        )
+        .await;
+        return;
+    }
+    if let Err(e) = game.remove_modifier(&modifier_id) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_modifiers(state, &game);
+    if let Some(event) = game.event_log.last() {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Send every modifier on the table, e.g. after some ran out
+pub(crate) fn broadcast_modifiers(state: &AppState, game: &GameState) {
+    let _ = state.broadcaster.send(ServerMessage::ModifiersUpdated {
+        modifiers: game.modifier_list(),
+    });
+}
+
+/// Send every adversary group and its members' HP
+pub(crate) fn broadcast_adversary_groups(state: &AppState, game: &GameState) {
+    let _ = state
+        .broadcaster
+        .send(ServerMessage::AdversaryGroupsUpdated {
+            groups: game.adversary_group_list(),
+        });
+}
+
+/// After adversaries were hurt, healed or removed: groups' HP as it now
+/// stands, and any group that went down with its last member or got back up
+async fn refresh_adversary_groups(state: &AppState, game: &mut GameState) {
+    if game.adversary_groups.is_empty() {
+        return;
+    }
+    let events_before = game.event_log.len();
+    game.refresh_adversary_groups();
+    broadcast_adversary_groups(state, game);
+    for event in game.event_log.iter().skip(events_before) {
+        broadcast_event(state, event).await;
+    }
+}
+
+/// Re-check aura membership after tokens move, appear or disappear
+///
+/// Broadcasts each token entering or leaving an aura, and auras removed
+/// along with their owner.
+async fn refresh_auras(state: &AppState, game: &mut GameState) {
+    let changes = game.refresh_auras();
+    if changes.is_empty() {
+        return;
+    }
+
+    for change in &changes {
+        let msg = match change.clone() {
+            game::AuraChange::Entered {
+                aura_id,
+                aura_name,
+                token_id,
+                token_name,
+            } => ServerMessage::AuraEntered {
+                aura_id,
+                aura_name,
+                token_id,
+                token_name,
+            },
+            game::AuraChange::Left {
+                aura_id,
+                aura_name,
+                token_id,
+                token_name,
+            } => ServerMessage::AuraLeft {
+                aura_id,
+                aura_name,
+                token_id,
+                token_name,
+            },
+            game::AuraChange::Removed { aura_id, aura_name } => ServerMessage::AuraDetached {
+                aura_id,
+                name: aura_name,
+            },
         };
-        state.broadcaster.send(msg.to_json()).ok();
+        let _ = state.broadcaster.send(msg);
     }
-    
-    drop(game);
-    
-    // Broadcast event
-    if let Some(ev) = event {
-        broadcast_event(state, &ev).await;
+
+    // refresh_auras logs one event per change
+    let skip = game.event_log.len().saturating_sub(changes.len());
+    for event in game.event_log.iter().skip(skip) {
+        broadcast_event(state, event).await;
     }
 }
 
-// ===== Combat & Adversary Handlers =====
+// ===== Inventory Handlers =====
 
-/// Handle spawning an adversary from template
-async fn handle_spawn_adversary(state: &AppState, template: String, position: protocol::Position) {
+/// Handle adding an item to the controlled character
+async fn handle_add_item(
+    state: &AppState,
+    conn_id: &Uuid,
+    name: String,
+    description: String,
+    quantity: u32,
+) {
     let mut game = state.game.write().await;
-    
-    match game.spawn_adversary(&template, position) {
-        Ok(adversary) => {
-            // Broadcast adversary spawned
-            let msg = ServerMessage::AdversarySpawned {
-                adversary_id: adversary.id.clone(),
-                name: adversary.name.clone(),
-                template: adversary.template.clone(),
-                position,
-                hp: adversary.hp,
-                max_hp: adversary.max_hp,
-                evasion: adversary.evasion,
-                armor: adversary.armor,
-                attack_modifier: adversary.attack_modifier,
-                damage_dice: adversary.damage_dice.clone(),
-            };
-            let _ = state.broadcaster.send(msg.to_json());
-            
-            // Broadcast event
-            if let Some(event) = game.event_log.last() {
-                broadcast_event(state, event).await;
-            }
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Err(e) = game.add_item(&char_id, name, description, quantity) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
+}
+
+/// Handle removing an item from the controlled character
+async fn handle_remove_item(state: &AppState, conn_id: &Uuid, item_id: String, quantity: u32) {
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Err(e) = game.remove_item(&char_id, &item_id, quantity) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
+}
+
+/// Handle giving an item from the controlled character to another character
+async fn handle_transfer_item(
+    state: &AppState,
+    conn_id: &Uuid,
+    item_id: String,
+    target_character_id: String,
+    quantity: u32,
+) {
+    let target_id = match Uuid::parse_str(&target_character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Err(e) = game.transfer_item(&char_id, &target_id, &item_id, quantity) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_characters_updated(state, &game, &[char_id, target_id]).await;
+}
+
+/// Handle equipping a weapon on the controlled character
+async fn handle_equip_weapon(state: &AppState, conn_id: &Uuid, weapon: Option<Weapon>) {
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
         }
+    };
+
+    if let Err(e) = game.equip_weapon(&char_id, weapon) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
+}
+
+/// Handle equipping armor on the controlled character
+async fn handle_equip_armor(state: &AppState, conn_id: &Uuid, armor: Option<Armor>) {
+    let mut game = state.game.write().await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
         Err(e) => {
-            send_error(state, &e).await;
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
+    };
+
+    if let Err(e) = game.equip_armor(&char_id, armor) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
+}
+
+/// Handle GM granting an item to a character
+async fn handle_grant_item(
+    state: &AppState,
+    character_id: String,
+    name: String,
+    description: String,
+    quantity: u32,
+) {
+    let char_id = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
+        }
+    };
+
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.add_item(&char_id, name, description, quantity) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
+}
+
+/// Handle GM awarding gold to a character
+async fn handle_award_gold(state: &AppState, character_id: String, handfuls: u32) {
+    let char_id = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
         }
+    };
+
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.award_gold(&char_id, handfuls) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
     }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
 }
 
-/// Handle spawning a custom adversary
-async fn handle_spawn_custom_adversary(
+/// Handle adding an Experience to the controlled character
+async fn handle_add_experience(
     state: &AppState,
+    conn_id: &Uuid,
     name: String,
-    position: protocol::Position,
-    hp: u8,
-    evasion: u8,
-    armor: u8,
-    attack_modifier: i8,
-    damage_dice: String,
+    modifier: Option<i8>,
 ) {
     let mut game = state.game.write().await;
-    
-    let adversary = game.create_custom_adversary(
-        name,
-        position,
-        hp,
-        evasion,
-        armor,
-        attack_modifier,
-        damage_dice.clone(),
-    );
-    
-    // Broadcast adversary spawned
-    let msg = ServerMessage::AdversarySpawned {
-        adversary_id: adversary.id.clone(),
-        name: adversary.name.clone(),
-        template: adversary.template.clone(),
-        position,
-        hp: adversary.hp,
-        max_hp: adversary.max_hp,
-        evasion: adversary.evasion,
-        armor: adversary.armor,
-        attack_modifier: adversary.attack_modifier,
-        damage_dice: adversary.damage_dice.clone(),
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
     };
-    let _ = state.broadcaster.send(msg.to_json());
-    
-    // Broadcast event
-    if let Some(event) = game.event_log.last() {
-        broadcast_event(state, event).await;
+
+    if let Err(e) = game.add_experience(&char_id, name, modifier) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
     }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
 }
 
-/// Handle removing an adversary
-async fn handle_remove_adversary(state: &AppState, adversary_id: String) {
+/// Handle renaming or re-scoring an Experience on the controlled character
+async fn handle_edit_experience(
+    state: &AppState,
+    conn_id: &Uuid,
+    name: String,
+    new_name: Option<String>,
+    modifier: Option<i8>,
+) {
     let mut game = state.game.write().await;
-    
-    if let Some(adversary) = game.remove_adversary(&adversary_id) {
-        let msg = ServerMessage::AdversaryRemoved {
-            adversary_id,
-            name: adversary.name.clone(),
-        };
-        let _ = state.broadcaster.send(msg.to_json());
-        
-        // Broadcast event
-        if let Some(event) = game.event_log.last() {
-            broadcast_event(state, event).await;
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
         }
+    };
+
+    if let Err(e) = game.edit_experience(&char_id, &name, new_name, modifier) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
     }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
 }
 
-/// Handle starting combat
-async fn handle_start_combat(state: &AppState) {
-    let mut game = state.game.write().await;
-    
-    let encounter_id = game.start_combat();
-    
-    if let Some(encounter) = game.get_combat() {
-        let msg = ServerMessage::CombatStarted {
-            encounter_id,
-            pc_tokens: encounter.action_tracker.pc_tokens,
-            adversary_tokens: encounter.action_tracker.adversary_tokens,
-        };
-        let _ = state.broadcaster.send(msg.to_json());
-        
-        // Broadcast event
-        if let Some(event) = game.event_log.last() {
-            broadcast_event(state, event).await;
+/// Handle GM marking that a character can level up
+async fn handle_mark_level_up(state: &AppState, character_id: String) {
+    let char_id = match Uuid::parse_str(&character_id) {
+        Ok(id) => id,
+        Err(_) => {
+            send_error(state, ErrorCode::InvalidId, "Invalid character ID").await;
+            return;
         }
+    };
+
+    let mut game = state.game.write().await;
+
+    if let Err(e) = game.mark_level_up(&char_id) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
+    }
+
+    if let Some(character) = game.get_character(&char_id) {
+        let _ = state.broadcaster.send(level_up_offer(character));
     }
+    broadcast_characters_updated(state, &game, &[char_id]).await;
 }
 
-/// Handle ending combat
-async fn handle_end_combat(state: &AppState) {
+/// Handle a player choosing advancements for their pending level up
+async fn handle_choose_level_up(
+    state: &AppState,
+    conn_id: &Uuid,
+    advancements: Vec<Advancement>,
+    new_experience: Option<String>,
+) {
     let mut game = state.game.write().await;
-    
-    game.end_combat("manual");
-    
-    let msg = ServerMessage::CombatEnded {
-        reason: "manual".to_string(),
+
+    let char_id = match game.acting_character(conn_id, None) {
+        Ok(id) => id,
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
+            return;
+        }
     };
-    let _ = state.broadcaster.send(msg.to_json());
-    
-    // Broadcast event
-    if let Some(event) = game.event_log.last() {
-        broadcast_event(state, event).await;
+
+    if let Err(e) = game.apply_level_up(&char_id, advancements, new_experience) {
+        drop(game);
+        send_error(state, ErrorCode::Rejected, &e).await;
+        return;
     }
+
+    broadcast_characters_updated(state, &game, &[char_id]).await;
 }
 
-/// Handle adding a tracker token
-async fn handle_add_tracker_token(state: &AppState, token_type: String) {
-    let mut game = state.game.write().await;
-    
-    if let Some(encounter) = game.get_combat_mut() {
-        match token_type.as_str() {
-            "pc" => encounter.action_tracker.add_pc_token(),
-            "adversary" => encounter.action_tracker.add_adversary_token(),
-            _ => {
-                send_error(state, &format!("Invalid token type: {}", token_type)).await;
-                return;
-            }
-        }
-        
-        let next_token = encounter.action_tracker.get_next()
-            .map(|t| format!("{:?}", t).to_lowercase())
-            .unwrap_or_else(|| "none".to_string());
-        
-        let msg = ServerMessage::TrackerUpdated {
-            pc_tokens: encounter.action_tracker.pc_tokens,
-            adversary_tokens: encounter.action_tracker.adversary_tokens,
-            next_token,
-        };
-        let _ = state.broadcaster.send(msg.to_json());
+/// Level-up prompt listing the advancement options for a character's next level
+fn level_up_offer(character: &game::Character) -> ServerMessage {
+    let new_level = character.level + 1;
+    ServerMessage::LevelUpOffered {
+        character_id: character.id.to_string(),
+        new_level,
+        tier: leveling::tier_for_level(new_level),
+        tier_achievement: leveling::is_tier_achievement(new_level),
+        options: character.progression.options_for(new_level),
     }
 }
 
-/// Handle attack roll
-async fn handle_attack(
-    state: &AppState,
-    attacker_id: String,
-    target_id: String,
-    modifier: i8,
-    with_advantage: bool,
-) {
-    use daggerheart_engine::core::dice::duality::DualityRoll;
-    
+/// Handle a client asking for a character's whole sheet after missing a change
+async fn handle_request_sheet(state: &AppState, conn_id: &Uuid, character_id: String) {
     let game = state.game.read().await;
-    
-    // Get attacker and target names
-    let attacker_name = game.characters.values()
-        .find(|c| c.id.to_string() == attacker_id)
-        .map(|c| c.name.clone())
-        .or_else(|| {
-            game.adversaries.values()
-                .find(|a| a.id == attacker_id)
-                .map(|a| a.name.clone())
-        })
-        .unwrap_or_else(|| "Unknown".to_string());
-    
-    let target_name = game.characters.values()
-        .find(|c| c.id.to_string() == target_id)
-        .map(|c| c.name.clone())
-        .or_else(|| {
-            game.adversaries.values()
-                .find(|a| a.id == target_id)
-                .map(|a| a.name.clone())
-        })
-        .unwrap_or_else(|| "Unknown".to_string());
-    
-    let target_evasion = game.characters.values()
-        .find(|c| c.id.to_string() == target_id)
-        .map(|c| c.evasion as u8)
-        .or_else(|| {
-            game.adversaries.values()
-                .find(|a| a.id == target_id)
-                .map(|a| a.evasion)
-        })
-        .unwrap_or(10);
-    
-    // Roll attack
-    let roll = DualityRoll::roll();
-    let result = if with_advantage {
-        roll.with_advantage()
-    } else {
-        roll.with_modifier(modifier)
+    let Some(character) = Uuid::parse_str(&character_id)
+        .ok()
+        .and_then(|id| game.get_character(&id))
+    else {
+        drop(game);
+        send_error(state, ErrorCode::NotFound, "Character not found").await;
+        return;
     };
-    
-    let hope = result.roll.hope as u16;
-    let fear = result.roll.fear as u16;
-    let controlling_die = if hope > fear { "hope" } else { "fear" };
-    let total = result.total as u16;
-    let hit = total >= target_evasion as u16;
-    let is_critical = result.is_critical;
-    
-    // Broadcast attack result
-    let msg = ServerMessage::AttackResult {
-        attacker_id: attacker_id.clone(),
-        attacker_name: attacker_name.clone(),
-        target_id: target_id.clone(),
-        target_name: target_name.clone(),
-        hope,
-        fear,
-        modifier,
+
+    // Anything not sent yet goes to everyone first, so the reply is current
+    broadcast_sheet(state, &character.id, character.to_data());
+    if let Some(msg) = state
+        .sheets
+        .full(&character.id.to_string(), Some(conn_id.to_string()))
+    {
+        let _ = state.broadcaster.send(msg);
+    }
+}
+
+/// Send one connection a page of the event log; a running cutaway's events
+/// are in it only for the GM and the players in the scene
+async fn handle_request_event_log(state: &AppState, conn_id: &Uuid, query: EventLogQuery) {
+    let game = state.game.read().await;
+    let in_cutaway = Recipient::of(&game, conn_id).is_some_and(|r| !r.outside_cutaway());
+    let (events, total) = game.event_page(&query, in_cutaway);
+    let _ = state.broadcaster.send(ServerMessage::EventLog {
+        events,
         total,
-        target_evasion,
-        hit,
-        controlling_die: controlling_die.to_string(),
-        is_critical,
-    };
-    let _ = state.broadcaster.send(msg.to_json());
+        offset: query.offset,
+        connection_id: Some(conn_id.to_string()),
+    });
 }
 
-/// Handle damage roll
-async fn handle_roll_damage(
-    state: &AppState,
-    _attacker_id: String,
-    target_id: String,
-    damage_dice: String,
-    armor: u8,
-) {
-    use daggerheart_engine::combat::damage::DamageResult;
-    
-    // Parse and roll damage dice
-    let raw_damage = parse_and_roll_dice(&damage_dice);
-    
-    // Calculate damage with threshold system
-    let damage_result = DamageResult::calculate(raw_damage, armor);
-    
+/// Send a connection the event log by scene and fight (with a running
+/// cutaway's events for the GM and the players in the scene)
+async fn handle_request_scene_log(state: &AppState, conn_id: &Uuid, query: EventLogQuery) {
+    let game = state.game.read().await;
+    let in_cutaway = Recipient::of(&game, conn_id).is_some_and(|r| !r.outside_cutaway());
+    let _ = state.broadcaster.send(ServerMessage::SceneLog {
+        scenes: game.scene_log(&query, in_cutaway),
+        connection_id: Some(conn_id.to_string()),
+    });
+}
+
+/// Handle the GM starting a new scene in the log
+async fn handle_mark_scene(state: &AppState, title: String) {
     let mut game = state.game.write().await;
-    
-    // Get target name
-    let target_name = game.characters.values()
-        .find(|c| c.id.to_string() == target_id)
-        .map(|c| c.name.clone())
-        .or_else(|| {
-            game.adversaries.values()
-                .find(|a| a.id == target_id)
-                .map(|a| a.name.clone())
-        })
-        .unwrap_or_else(|| "Unknown".to_string());
-    
-    // Apply damage to target
-    let mut taken_out = false;
-    let mut new_hp = 0;
-    let mut new_stress = 0;
-    
-    if let Some(character) = game.characters.values_mut().find(|c| c.id.to_string() == target_id) {
-        // Apply to character
-        if damage_result.hp_lost > 0 {
-            character.hp_current = character.hp_current.saturating_sub(damage_result.hp_lost);
+    match game.mark_scene(&title) {
+        Ok(title) => {
+            println!("🎬 Scene: {}", title);
+            if let Some(event) = game.event_log.last() {
+                broadcast_event(state, event).await;
+            }
         }
-        if damage_result.stress_gained > 0 {
-            character.stress_current = (character.stress_current + damage_result.stress_gained).min(character.hp_max);
+        Err(e) => {
+            drop(game);
+            send_error(state, ErrorCode::Rejected, &e).await;
         }
-        new_hp = character.hp_current;
-        new_stress = character.stress_current;
-        
-        if character.hp_current == 0 && character.stress_current >= character.hp_max {
-            taken_out = true;
+    }
+}
+
+/// Broadcast what changed on a character's sheet since it was last sent
+fn broadcast_sheet(state: &AppState, char_id: &Uuid, sheet: protocol::CharacterData) {
+    if let Some(msg) = state.sheets.update(&char_id.to_string(), sheet) {
+        let _ = state.broadcaster.send(msg);
+    }
+}
+
+/// Broadcast updated character data and the latest event after a character sheet change
+async fn broadcast_characters_updated(state: &AppState, game: &GameState, char_ids: &[Uuid]) {
+    for char_id in char_ids {
+        if let Some(character) = game.get_character(char_id) {
+            broadcast_sheet(state, char_id, character.to_data());
         }
-    } else if let Some(adversary) = game.adversaries.values_mut().find(|a| a.id == target_id) {
-        // Apply to adversary
-        taken_out = adversary.take_damage(damage_result.hp_lost, damage_result.stress_gained);
-        new_hp = adversary.hp;
-        new_stress = adversary.stress;
     }
-    
-    // Broadcast damage result
-    let msg = ServerMessage::DamageResult {
-        target_id: target_id.clone(),
-        target_name: target_name.clone(),
-        raw_damage: damage_result.raw_damage,
-        after_armor: damage_result.after_armor,
-        hp_lost: damage_result.hp_lost,
-        stress_gained: damage_result.stress_gained,
-        new_hp,
-        new_stress,
-        taken_out,
-    };
-    let _ = state.broadcaster.send(msg.to_json());
-    
-    // Log event
-    game.add_event(
-        game::GameEventType::CombatAction,
-        format!(
-            "{} took {} damage ({} HP, {} Stress)",
-            target_name, damage_result.after_armor, damage_result.hp_lost, damage_result.stress_gained
-        ),
-        Some(target_name),
-        if taken_out {
-            Some("Taken out!".to_string())
-        } else {
-            None
-        },
-    );
-    
+
     if let Some(event) = game.event_log.last() {
         broadcast_event(state, event).await;
     }
 }
 
-/// Parse and roll damage dice (e.g., "1d8+2" or "2d6")
-fn parse_and_roll_dice(dice_str: &str) -> u16 {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    
-    // Split on '+' or '-'
-    let (dice_part, modifier) = if let Some(pos) = dice_str.find('+') {
-        let (d, m) = dice_str.split_at(pos);
-        (d, m[1..].parse::<i16>().unwrap_or(0))
-    } else if let Some(pos) = dice_str.find('-') {
-        let (d, m) = dice_str.split_at(pos);
-        (d, -m[1..].parse::<i16>().unwrap_or(0))
-    } else {
-        (dice_str, 0)
-    };
-    
-    // Parse "XdY" format
-    if let Some(d_pos) = dice_part.find('d') {
-        let (num_str, die_str) = dice_part.split_at(d_pos);
-        let num_dice = num_str.parse::<u16>().unwrap_or(1);
-        let die_size = die_str[1..].parse::<u16>().unwrap_or(6);
-        
-        let mut total = 0;
-        for _ in 0..num_dice {
-            total += rng.gen_range(1..=die_size);
-        }
-        
-        (total as i16 + modifier).max(0) as u16
-    } else {
-        // Just a flat number
-        dice_part.parse::<u16>().unwrap_or(0)
-    }
+/// Parse and roll damage dice (e.g., "1d8+2", "2d6+1d4" or "4d6kh3")
+fn parse_and_roll_dice(dice_rng: &DiceRng, dice_str: &str) -> Result<DiceRoll, String> {
+    dice_rng.with(|rng| dice::roll(dice_str, rng))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
     use tokio::sync::RwLock;
 
     #[test]
     fn test_app_state_clone() {
         let game_state = Arc::new(RwLock::new(GameState::new()));
-        let (broadcaster, _) = broadcast::channel::<String>(100);
+        let (broadcaster, _) = broadcast::channel::<ServerMessage>(100);
 
         let state = AppState {
             game: game_state,
             broadcaster,
+            security: Arc::new(SecurityConfig::default()),
+            journal: None,
+            handouts: Arc::new(HandoutStore::new("handouts")),
+            maps: Arc::new(MapStore::new("maps")),
+            vault: Arc::new(Vault::new("vault.json")),
+            encounters: Arc::new(EncounterStore::new("encounters.json")),
+            sheets: Arc::new(SheetTracker::new()),
+            history: None,
+            saves: Arc::new(crate::save::FileSaveStore::default()),
+            config: Arc::new(ServerConfig::default()),
+            client: Arc::new(ClientAssets::Embedded),
+            metrics: Arc::new(Metrics::new()),
+            moves: Arc::new(MoveThrottle::new(10.0)),
         };
 
         let cloned = state.clone();
@@ -1366,31 +6146,39 @@ mod tests {
     fn test_parse_and_roll_dice_simple() {
         // Test simple dice rolls multiple times to ensure validity
         for _ in 0..10 {
-            let result = parse_and_roll_dice("1d6");
-            assert!(result >= 1 && result <= 6, "1d6 out of range: {}", result);
+            let result = parse_and_roll_dice(&DiceRng::default(), "1d6")
+                .unwrap()
+                .total;
+            assert!((1..=6).contains(&result), "1d6 out of range: {}", result);
         }
     }
 
     #[test]
     fn test_parse_and_roll_dice_with_modifier() {
         for _ in 0..10 {
-            let result = parse_and_roll_dice("1d8+2");
-            assert!(result >= 3 && result <= 10, "1d8+2 out of range: {}", result);
+            let result = parse_and_roll_dice(&DiceRng::default(), "1d8+2")
+                .unwrap()
+                .total;
+            assert!((3..=10).contains(&result), "1d8+2 out of range: {}", result);
         }
     }
 
     #[test]
     fn test_parse_and_roll_dice_multiple_dice() {
         for _ in 0..10 {
-            let result = parse_and_roll_dice("2d6");
-            assert!(result >= 2 && result <= 12, "2d6 out of range: {}", result);
+            let result = parse_and_roll_dice(&DiceRng::default(), "2d6")
+                .unwrap()
+                .total;
+            assert!((2..=12).contains(&result), "2d6 out of range: {}", result);
         }
     }
 
     #[test]
     fn test_parse_and_roll_dice_with_negative_modifier() {
         for _ in 0..10 {
-            let result = parse_and_roll_dice("1d6-1");
+            let result = parse_and_roll_dice(&DiceRng::default(), "1d6-1")
+                .unwrap()
+                .total;
             assert!(result <= 5, "1d6-1 out of range: {}", result);
         }
     }
@@ -1398,14 +6186,30 @@ mod tests {
     #[test]
     fn test_parse_and_roll_dice_complex() {
         for _ in 0..10 {
-            let result = parse_and_roll_dice("2d8+3");
-            assert!(result >= 5 && result <= 19, "2d8+3 out of range: {}", result);
+            let result = parse_and_roll_dice(&DiceRng::default(), "2d8+3")
+                .unwrap()
+                .total;
+            assert!((5..=19).contains(&result), "2d8+3 out of range: {}", result);
         }
     }
 
     #[test]
     fn test_parse_and_roll_dice_flat_number() {
-        let result = parse_and_roll_dice("5");
+        let result = parse_and_roll_dice(&DiceRng::default(), "5").unwrap().total;
         assert_eq!(result, 5);
     }
+
+    #[test]
+    fn test_parse_and_roll_dice_multiple_terms() {
+        for _ in 0..10 {
+            let roll = parse_and_roll_dice(&DiceRng::default(), "2d6+1d4+3").unwrap();
+            assert_eq!(roll.dice.len(), 3);
+            assert!(
+                roll.total >= 6 && roll.total <= 19,
+                "out of range: {}",
+                roll.total
+            );
+        }
+        assert!(parse_and_roll_dice(&DiceRng::default(), "2d6+").is_err());
+    }
 }
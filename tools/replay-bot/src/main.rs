@@ -0,0 +1,320 @@
+//! Replay a recorded session journal against a running server
+//!
+//! Record a session by starting the server with `DAGGERHEART_JOURNAL` set,
+//! then replay it:
+//!
+//! ```text
+//! replay-bot session.jsonl [--url ws://localhost:3000/ws] [--speed 2.0]
+//! ```
+//!
+//! Each recorded connection gets its own WebSocket and its client messages
+//! are re-sent at the recorded times divided by `--speed` (`0` sends as fast
+//! as the server accepts them). Character and adversary ids are rewritten to
+//! the ids the live server hands out.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use daggerheart_vtt_protocol::journal::{
+    parse_journal, spawned_entity_id, JournalEntry, JournalRecord,
+};
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const DEFAULT_URL: &str = "ws://localhost:3000/ws";
+
+/// How long a message waits for the spawn it refers to before being sent anyway
+const SPAWN_WAIT: Duration = Duration::from_secs(5);
+
+/// Command-line options
+#[derive(Debug, PartialEq)]
+struct Options {
+    journal: PathBuf,
+    url: String,
+    speed: f64,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut journal = None;
+    let mut url = DEFAULT_URL.to_string();
+    let mut speed = 1.0;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => {
+                url = args.next().ok_or("--url needs a value")?.clone();
+            }
+            "--speed" => {
+                let value = args.next().ok_or("--speed needs a value")?;
+                speed = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|s| *s >= 0.0 && s.is_finite())
+                    .ok_or_else(|| format!("Invalid speed: {}", value))?;
+            }
+            other if other.starts_with("--") => return Err(format!("Unknown option: {}", other)),
+            other => {
+                if journal.replace(PathBuf::from(other)).is_some() {
+                    return Err("Only one journal file can be replayed".to_string());
+                }
+            }
+        }
+    }
+
+    Ok(Options {
+        journal: journal.ok_or("Missing journal file")?,
+        url,
+        speed,
+    })
+}
+
+/// Pairs ids spawned in the recording with those spawned during the replay
+#[derive(Debug, Default)]
+struct IdMap {
+    /// Recorded ids whose live counterpart has not been seen yet, in spawn order
+    unmatched: VecDeque<String>,
+    live: HashMap<String, String>,
+}
+
+impl IdMap {
+    fn from_journal(entries: &[JournalEntry]) -> Self {
+        let unmatched = entries
+            .iter()
+            .filter_map(|entry| match &entry.record {
+                JournalRecord::Server { message } => spawned_entity_id(message),
+                _ => None,
+            })
+            .collect();
+        Self {
+            unmatched,
+            live: HashMap::new(),
+        }
+    }
+
+    /// Match the next recorded spawn to a live one
+    fn observe(&mut self, live_id: String) {
+        if let Some(recorded_id) = self.unmatched.pop_front() {
+            self.live.insert(recorded_id, live_id);
+        }
+    }
+
+    /// True while the message refers to a spawn the replay hasn't reached yet
+    fn waiting_on(&self, message: &str) -> bool {
+        self.unmatched
+            .iter()
+            .any(|id| message.contains(id.as_str()))
+    }
+
+    fn rewrite(&self, message: &str) -> String {
+        self.live
+            .iter()
+            .fold(message.to_string(), |msg, (recorded, live)| {
+                msg.replace(recorded.as_str(), live)
+            })
+    }
+}
+
+/// Discard whatever the server sends a replayed connection
+fn drain(mut stream: SplitStream<Socket>) {
+    tokio::spawn(async move { while let Some(Ok(_)) = stream.next().await {} });
+}
+
+async fn connect(url: &str) -> anyhow::Result<SplitSink<Socket, Message>> {
+    let (socket, _) = connect_async(url)
+        .await
+        .with_context(|| format!("Failed to connect to {}", url))?;
+    let (sink, stream) = socket.split();
+    drain(stream);
+    Ok(sink)
+}
+
+/// Watch broadcasts on a dedicated connection to learn live spawn ids
+async fn observe_spawns(url: &str, ids: Arc<Mutex<IdMap>>) -> anyhow::Result<()> {
+    let (socket, _) = connect_async(url)
+        .await
+        .with_context(|| format!("Failed to connect to {}", url))?;
+    let (sink, mut stream) = socket.split();
+
+    tokio::spawn(async move {
+        let _keep_open = sink;
+        while let Some(Ok(msg)) = stream.next().await {
+            if let Message::Text(text) = msg {
+                if let Some(live_id) = spawned_entity_id(&text) {
+                    if let Ok(mut ids) = ids.lock() {
+                        ids.observe(live_id);
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+async fn replay(options: &Options, entries: &[JournalEntry]) -> anyhow::Result<usize> {
+    let ids = Arc::new(Mutex::new(IdMap::from_journal(entries)));
+    observe_spawns(&options.url, ids.clone()).await?;
+
+    let mut connections: HashMap<String, SplitSink<Socket, Message>> = HashMap::new();
+    let mut sent = 0;
+    let start = Instant::now();
+
+    for entry in entries {
+        if options.speed > 0.0 {
+            let offset = Duration::from_secs_f64(entry.at_ms as f64 / 1000.0 / options.speed);
+            tokio::time::sleep_until(start + offset).await;
+        }
+
+        match &entry.record {
+            JournalRecord::Connected { connection } => {
+                connections.insert(connection.clone(), connect(&options.url).await?);
+            }
+            JournalRecord::Disconnected { connection } => {
+                if let Some(mut sink) = connections.remove(connection) {
+                    let _ = sink.close().await;
+                }
+            }
+            JournalRecord::Client {
+                connection,
+                message,
+            } => {
+                // Journals may start mid-session; open connections on first use
+                let sink = match connections.entry(connection.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(connect(&options.url).await?),
+                };
+
+                let deadline = Instant::now() + SPAWN_WAIT;
+                while ids
+                    .lock()
+                    .map(|ids| ids.waiting_on(message))
+                    .unwrap_or(false)
+                {
+                    if Instant::now() >= deadline {
+                        eprintln!(
+                            "⚠️  Spawn never replayed; sending at {}ms as recorded",
+                            entry.at_ms
+                        );
+                        break;
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+
+                let text = ids
+                    .lock()
+                    .map(|ids| ids.rewrite(message))
+                    .map_err(|_| anyhow!("Id map poisoned"))?;
+                sink.send(Message::Text(text))
+                    .await
+                    .with_context(|| format!("Server closed connection {}", connection))?;
+                sent += 1;
+            }
+            JournalRecord::Server { .. } | JournalRecord::Keyframe { .. } => {}
+        }
+    }
+
+    for (_, mut sink) in connections {
+        let _ = sink.close().await;
+    }
+
+    Ok(sent)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = parse_args(&args).map_err(|e| {
+        anyhow!(
+            "{}\nUsage: replay-bot <journal.jsonl> [--url {}] [--speed 1.0]",
+            e,
+            DEFAULT_URL
+        )
+    })?;
+
+    let contents = std::fs::read_to_string(&options.journal)
+        .with_context(|| format!("Failed to read {}", options.journal.display()))?;
+    let entries =
+        parse_journal(&contents).map_err(|e| anyhow!("{}: {}", options.journal.display(), e))?;
+
+    println!(
+        "🎬 Replaying {} journal entries against {} at {}x",
+        entries.len(),
+        options.url,
+        options.speed
+    );
+
+    let started = Instant::now();
+    let sent = replay(&options, &entries).await?;
+
+    println!(
+        "✅ Sent {} client messages in {:.1}s",
+        sent,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(&args(&["session.jsonl", "--speed", "2.5"])).unwrap();
+        assert_eq!(options.journal, PathBuf::from("session.jsonl"));
+        assert_eq!(options.url, DEFAULT_URL);
+        assert_eq!(options.speed, 2.5);
+
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["a.jsonl", "--speed", "-1"])).is_err());
+        assert!(parse_args(&args(&["a.jsonl", "--fast"])).is_err());
+    }
+
+    #[test]
+    fn test_id_map_rewrites_in_spawn_order() {
+        let entries = vec![
+            JournalEntry::new(
+                0,
+                JournalRecord::Server {
+                    message:
+                        r#"{"type":"character_spawned","payload":{"character_id":"old-char"}}"#
+                            .to_string(),
+                },
+            ),
+            JournalEntry::new(
+                10,
+                JournalRecord::Server {
+                    message: r#"{"type":"adversary_spawned","payload":{"adversary_id":"old-adv"}}"#
+                        .to_string(),
+                },
+            ),
+        ];
+        let mut ids = IdMap::from_journal(&entries);
+
+        let message = r#"{"type":"remove_adversary","payload":{"adversary_id":"old-adv"}}"#;
+        assert!(ids.waiting_on(message));
+
+        ids.observe("new-char".to_string());
+        ids.observe("new-adv".to_string());
+
+        assert!(!ids.waiting_on(message));
+        assert_eq!(
+            ids.rewrite(message),
+            r#"{"type":"remove_adversary","payload":{"adversary_id":"new-adv"}}"#
+        );
+    }
+}